@@ -0,0 +1,37 @@
+use binggan::{black_box, InputGroup};
+use tantivy::schema::{OwnedValue, Schema, Value, STORED};
+use tantivy::TantivyDocument;
+
+fn main() {
+    let inputs: Vec<(String, TantivyDocument)> = [100, 1_000, 10_000]
+        .into_iter()
+        .map(|len| (format!("array_len={len}"), doc_with_array(len)))
+        .collect();
+    let mut group = InputGroup::new_with_inputs(inputs);
+    group.register("iter_nth", |doc: &TantivyDocument| {
+        let value = doc.field_values().next().unwrap().1;
+        let len = value.as_array().unwrap().len();
+        for index in (0..len).step_by(97) {
+            black_box(value.as_array().unwrap().nth(index));
+        }
+    });
+    group.register("accessor_get", |doc: &TantivyDocument| {
+        let value = doc.field_values().next().unwrap().1;
+        let accessor = value.as_array_accessor().unwrap();
+        for index in (0..accessor.len()).step_by(97) {
+            black_box(accessor.get(index));
+        }
+    });
+    group.run();
+}
+
+fn doc_with_array(len: u64) -> TantivyDocument {
+    let mut schema_builder = Schema::builder();
+    let json_field = schema_builder.add_json_field("json", STORED);
+    let mut doc = TantivyDocument::default();
+    let elements = (0..len)
+        .map(|i| OwnedValue::Str(format!("metadata {i}")))
+        .collect();
+    doc.add_field_value(json_field, &OwnedValue::Array(elements));
+    doc
+}
@@ -0,0 +1,66 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+use binggan::plugins::PeakMemAllocPlugin;
+use binggan::{black_box, InputGroup, PeakMemAlloc, INSTRUMENTED_SYSTEM};
+use serde_json::{json, Map, Value};
+use tantivy::schema::{Schema, STORED};
+use tantivy::TantivyDocument;
+
+#[global_allocator]
+pub static GLOBAL: &PeakMemAlloc<std::alloc::System> = &INSTRUMENTED_SYSTEM;
+
+fn main() {
+    let mut schema_builder = Schema::builder();
+    let title_field = schema_builder.add_text_field("title", STORED);
+    let count_field = schema_builder.add_u64_field("count", STORED);
+    let json_field = schema_builder.add_json_field("json", STORED);
+    let doc = |num_keys: usize| {
+        let mut doc = TantivyDocument::default();
+        doc.add_text(title_field, "a log record");
+        doc.add_u64(count_field, num_keys as u64);
+        doc.add_json_object(json_field, &object(num_keys));
+        doc
+    };
+    let inputs: Vec<(String, (TantivyDocument, TantivyDocument))> = [10, 100]
+        .into_iter()
+        .map(|num_keys| (format!("keys={num_keys}"), (doc(num_keys), doc(num_keys))))
+        .collect();
+    let mut group = InputGroup::new_with_inputs(inputs);
+    group.add_plugin(PeakMemAllocPlugin::new(GLOBAL));
+    group.register(
+        "eq",
+        |(doc, other_doc): &(TantivyDocument, TantivyDocument)| {
+            black_box(doc == other_doc);
+        },
+    );
+    group.register(
+        "eq_unordered",
+        |(doc, other_doc): &(TantivyDocument, TantivyDocument)| {
+            black_box(doc.eq_unordered(other_doc));
+        },
+    );
+    let hasher_builder = RandomState::new();
+    group.register(
+        "hash",
+        move |(doc, _): &(TantivyDocument, TantivyDocument)| {
+            black_box(hasher_builder.hash_one(doc));
+        },
+    );
+    group.run();
+}
+
+/// A nested object with `num_keys` keys, looking like a log record.
+fn object(num_keys: usize) -> Map<String, Value> {
+    (0..num_keys)
+        .map(|i| {
+            let value = match i % 4 {
+                0 => json!(format!("value number {i}")),
+                1 => json!(i as i64 - 50),
+                2 => json!({"host": format!("host-{i}"), "latency": i as f64 / 3.0}),
+                _ => json!([i, "tag", true, null]),
+            };
+            (format!("key_{i}"), value)
+        })
+        .collect()
+}
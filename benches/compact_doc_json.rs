@@ -0,0 +1,46 @@
+use binggan::plugins::PeakMemAllocPlugin;
+use binggan::{black_box, InputGroup, PeakMemAlloc, INSTRUMENTED_SYSTEM};
+use serde_json::{json, Map, Value};
+use tantivy::schema::{OwnedValue, Schema, STORED};
+use tantivy::TantivyDocument;
+
+#[global_allocator]
+pub static GLOBAL: &PeakMemAlloc<std::alloc::System> = &INSTRUMENTED_SYSTEM;
+
+fn main() {
+    let mut schema_builder = Schema::builder();
+    let json_field = schema_builder.add_json_field("json", STORED);
+    let inputs: Vec<(String, Map<String, Value>)> = vec![("keys=100".to_string(), object(100))];
+    let mut group = InputGroup::new_with_inputs(inputs);
+    group.add_plugin(PeakMemAllocPlugin::new(GLOBAL));
+    group.register("add_object", move |object: &Map<String, Value>| {
+        let mut doc = TantivyDocument::default();
+        let owned_object = object
+            .iter()
+            .map(|(key, value)| (key.clone(), OwnedValue::from(value.clone())))
+            .collect();
+        doc.add_object(json_field, owned_object);
+        black_box(doc);
+    });
+    group.register("add_json_object", move |object: &Map<String, Value>| {
+        let mut doc = TantivyDocument::default();
+        doc.add_json_object(json_field, object);
+        black_box(doc);
+    });
+    group.run();
+}
+
+/// A nested object with `num_keys` keys, looking like a log record.
+fn object(num_keys: usize) -> Map<String, Value> {
+    (0..num_keys)
+        .map(|i| {
+            let value = match i % 4 {
+                0 => json!(format!("value number {i}")),
+                1 => json!(i as i64 - 50),
+                2 => json!({"host": format!("host-{i}"), "latency": i as f64 / 3.0}),
+                _ => json!([i, "tag", true, null]),
+            };
+            (format!("key_{i}"), value)
+        })
+        .collect()
+}
@@ -0,0 +1,46 @@
+use binggan::{black_box, InputGroup};
+use tantivy::schema::{JsonDocParser, Schema, FAST, STORED, STRING, TEXT};
+use tantivy::TantivyDocument;
+
+fn main() {
+    let schema = schema();
+    let inputs = vec![("100k_lines".to_string(), json_lines(100_000))];
+    let mut group = InputGroup::new_with_inputs(inputs);
+    let parse_json_schema = schema.clone();
+    group.register("parse_json_per_line", move |json_lines: &String| {
+        for line in json_lines.lines() {
+            black_box(TantivyDocument::parse_json(&parse_json_schema, line).unwrap());
+        }
+    });
+    group.register("json_doc_parser", move |json_lines: &String| {
+        for doc in JsonDocParser::new(schema.clone()).parse_lines(json_lines.as_bytes()) {
+            black_box(doc.unwrap());
+        }
+    });
+    group.run();
+}
+
+fn schema() -> Schema {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("title", TEXT | STORED);
+    schema_builder.add_text_field("body", TEXT);
+    schema_builder.add_text_field("category", STRING | FAST);
+    schema_builder.add_u64_field("views", FAST);
+    schema_builder.add_f64_field("score", FAST);
+    schema_builder.build()
+}
+
+fn json_lines(num_lines: usize) -> String {
+    (0..num_lines)
+        .map(|i| {
+            format!(
+                "{{\"title\": \"title {i}\", \"body\": \"the body of the document number {i}\", \
+                 \"category\": \"category{}\", \"views\": {}, \"score\": {}, \"ignored\": \
+                 {{\"nested\": [1, 2, 3]}}}}\n",
+                i % 10,
+                i * 7,
+                i as f64 / 3.0
+            )
+        })
+        .collect()
+}
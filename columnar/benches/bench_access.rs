@@ -64,5 +64,14 @@ fn bench_group(mut runner: InputGroup<Column>) {
 
         black_box(sum);
     });
+    runner.register("access_for_each_doc_values", |column| {
+        let mut sum = 0;
+        column.for_each_doc_values(|_doc, values| {
+            for value in values {
+                sum += value;
+            }
+        });
+        black_box(sum);
+    });
     runner.run();
 }
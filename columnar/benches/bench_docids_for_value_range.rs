@@ -0,0 +1,60 @@
+use binggan::{InputGroup, black_box};
+use common::*;
+use tantivy_columnar::{Column, DynamicColumn, NumericalType};
+
+pub mod common;
+
+const NUM_DOCS: u32 = 10_000_000;
+
+fn generate_columnar_and_open(card: Card, num_docs: u32) -> Column<u64> {
+    let reader = generate_columnar_with_name(card, num_docs, "price");
+    let column = reader.read_columns("price").unwrap()[0]
+        .open()
+        .unwrap()
+        .coerce_numerical(NumericalType::U64)
+        .unwrap();
+    let DynamicColumn::U64(column) = column else {
+        panic!("expected a u64 column");
+    };
+    column
+}
+
+fn main() {
+    let mut inputs = Vec::new();
+
+    let mut add_card = |card: Card| {
+        inputs.push((card.to_string(), generate_columnar_and_open(card, NUM_DOCS)));
+    };
+
+    add_card(Card::MultiSparse);
+    add_card(Card::Multi);
+
+    bench_group(InputGroup::new_with_inputs(inputs));
+}
+
+fn bench_group(mut runner: InputGroup<Column<u64>>) {
+    // The values are the doc ids, so the matching docs are concentrated in a few ranges of docs.
+    let mut register_value_range = |name: &str, value_ranges: Vec<(u64, u64)>| {
+        runner.register(name, move |column| {
+            let mut doc_ids = Vec::new();
+            let mut num_docs = 0;
+            for &(start, end) in &value_ranges {
+                doc_ids.clear();
+                column.get_docids_for_value_range(start..=end, 0..NUM_DOCS, &mut doc_ids);
+                num_docs += doc_ids.len();
+            }
+            black_box(num_docs);
+        });
+    };
+    register_value_range(
+        "docids_for_value_range_upper_half",
+        vec![(9_000_000, 9_001_000)],
+    );
+    register_value_range(
+        "docids_for_value_range_spread",
+        (0..10u64)
+            .map(|i| (i * 1_000_000, i * 1_000_000 + 100))
+            .collect(),
+    );
+    runner.run();
+}
@@ -16,7 +16,7 @@ pub use serialize::{
 
 use crate::column_index::{ColumnIndex, Set};
 use crate::column_values::monotonic_mapping::StrictlyMonotonicMappingToInternal;
-use crate::column_values::{ColumnValues, monotonic_map_column};
+use crate::column_values::{BlockStats, ColumnValues, monotonic_map_column};
 use crate::{Cardinality, DocId, EmptyColumnValues, MonotonicallyMappableToU64, RowId};
 
 #[derive(Clone)]
@@ -84,11 +84,18 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
         self.values.max_value()
     }
 
+    /// Returns the first value for the given doc, or `None` if the doc has no value.
     #[inline]
     pub fn first(&self, doc_id: DocId) -> Option<T> {
         self.values_for_doc(doc_id).next()
     }
 
+    /// Returns the first value for the given doc, or `default_value` if the doc has no value.
+    #[inline]
+    pub fn first_or(&self, doc_id: DocId, default_value: T) -> T {
+        self.first(doc_id).unwrap_or(default_value)
+    }
+
     /// Load the first value for each docid in the provided slice.
     #[inline]
     pub fn first_vals(&self, docids: &[DocId], output: &mut [Option<T>]) {
@@ -139,6 +146,53 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
             .map(|value_row_id: RowId| self.values.get_val(value_row_id))
     }
 
+    /// Calls `callback` with every document that has at least one value, by increasing doc id,
+    /// along with its values.
+    ///
+    /// This is a lot faster than calling [`Column::values_for_doc`] for every document: the
+    /// index and the values are read sequentially, and the values are decoded by blocks.
+    pub fn for_each_doc_values(&self, mut callback: impl FnMut(DocId, &[T])) {
+        const BLOCK_LEN: usize = 1_024;
+        let num_vals = self.values.num_vals();
+        if num_vals == 0 {
+            return;
+        }
+        // The values of `block_start..block_start + block.len()`.
+        let mut block: Vec<T> = Vec::new();
+        let mut block_start: RowId = 0;
+        let filler = self.values.min_value();
+        let mut visit_doc = |doc: DocId, rows: Range<RowId>| {
+            let block_end = block_start + block.len() as RowId;
+            if rows.start < block_start || rows.end > block_end {
+                let block_len = rows.len().max(BLOCK_LEN).min((num_vals - rows.start) as usize);
+                block.clear();
+                block.resize(block_len, filler);
+                self.values.get_range(rows.start as u64, &mut block);
+                block_start = rows.start;
+            }
+            let offset = (rows.start - block_start) as usize;
+            callback(doc, &block[offset..offset + rows.len()]);
+        };
+        match &self.index {
+            ColumnIndex::Empty { .. } => {}
+            ColumnIndex::Full => {
+                for doc in 0..num_vals {
+                    visit_doc(doc, doc..doc + 1);
+                }
+            }
+            ColumnIndex::Optional(optional_index) => {
+                for (row, doc) in (0..).zip(optional_index.iter_non_null_docs()) {
+                    visit_doc(doc, row..row + 1);
+                }
+            }
+            ColumnIndex::Multivalued(multivalued_index) => {
+                for (doc, rows) in multivalued_index.iter_non_null_docs_with_ranges() {
+                    visit_doc(doc, rows);
+                }
+            }
+        }
+    }
+
     /// Get the docids of values which are in the provided value and docid range.
     #[inline]
     pub fn get_docids_for_value_range(
@@ -160,6 +214,15 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
             .select_batch_in_place(selected_docid_range.start, doc_ids);
     }
 
+    /// Returns the statistics of consecutive blocks of values of the column, if the
+    /// codec records them, and an empty iterator otherwise.
+    ///
+    /// Blocks are expressed in row ids, which are equal to document ids for columns with
+    /// exactly one value per document.
+    pub fn block_stats(&self) -> impl Iterator<Item = BlockStats<T>> + use<T> {
+        self.values.block_stats().into_iter()
+    }
+
     pub fn first_or_default_col(self, default_value: T) -> Arc<dyn ColumnValues<T>> {
         Arc::new(FirstValueWithDefault {
             column: self,
@@ -109,14 +109,11 @@ impl MultiValueIndexV1 {
     /// Converts a list of ranks (row ids of values) in a 1:n index to the corresponding list of
     /// docids. Positions are converted inplace to docids.
     ///
-    /// Since there is no index for value pos -> docid, but docid -> value pos range, we scan the
-    /// index.
+    /// Since there is no index for value pos -> docid, but docid -> value pos range, we search
+    /// the index, see [`find_idx_containing_pos`].
     ///
     /// Correctness: positions needs to be sorted. idx_reader needs to contain monotonically
     /// increasing positions.
-    ///
-    /// TODO: Instead of a linear scan we can employ a exponential search into binary search to
-    /// match a docid to its value position.
     pub(crate) fn select_batch_in_place(&self, docid_start: DocId, ranks: &mut Vec<u32>) {
         if ranks.is_empty() {
             return;
@@ -129,15 +126,11 @@ impl MultiValueIndexV1 {
         let mut write_doc_pos = 0;
         for i in 0..ranks.len() {
             let pos = ranks[i];
-            loop {
-                let end = self.start_index_column.get_val(cur_doc + 1);
-                if end > pos {
-                    ranks[write_doc_pos] = cur_doc;
-                    write_doc_pos += if last_doc == Some(cur_doc) { 0 } else { 1 };
-                    last_doc = Some(cur_doc);
-                    break;
-                }
-                cur_doc += 1;
+            cur_doc = find_idx_containing_pos(&*self.start_index_column, cur_doc, pos);
+            if last_doc != Some(cur_doc) {
+                ranks[write_doc_pos] = cur_doc;
+                write_doc_pos += 1;
+                last_doc = Some(cur_doc);
             }
         }
         ranks.truncate(write_doc_pos);
@@ -241,17 +234,45 @@ impl MultiValueIndex {
         }
     }
 
+    /// Returns an iterator over the document ids that have at least one value, along with the
+    /// range of their values.
+    ///
+    /// The start index column is read sequentially, rather than looked up for every document.
+    pub fn iter_non_null_docs_with_ranges(
+        &self,
+    ) -> Box<dyn Iterator<Item = (DocId, Range<RowId>)> + '_> {
+        match self {
+            MultiValueIndex::MultiValueIndexV1(idx) => {
+                let mut starts = idx.start_index_column.iter();
+                let mut start = starts.next().unwrap_or(0);
+                Box::new(starts.enumerate().filter_map(move |(doc, end)| {
+                    let range = start..end;
+                    start = end;
+                    (!range.is_empty()).then_some((doc as DocId, range))
+                }))
+            }
+            MultiValueIndex::MultiValueIndexV2(idx) => {
+                let mut starts = idx.start_index_column.iter();
+                let mut start = starts.next().unwrap_or(0);
+                Box::new(idx.optional_index.iter_non_null_docs().zip(starts).map(
+                    move |(doc, end)| {
+                        let range = start..end;
+                        start = end;
+                        (doc, range)
+                    },
+                ))
+            }
+        }
+    }
+
     /// Converts a list of ranks (row ids of values) in a 1:n index to the corresponding list of
     /// docids. Positions are converted inplace to docids.
     ///
-    /// Since there is no index for value pos -> docid, but docid -> value pos range, we scan the
-    /// index.
+    /// Since there is no index for value pos -> docid, but docid -> value pos range, we search
+    /// the index, see [`find_idx_containing_pos`].
     ///
     /// Correctness: positions needs to be sorted. idx_reader needs to contain monotonically
     /// increasing positions.
-    ///
-    /// TODO: Instead of a linear scan we can employ a exponential search into binary search to
-    /// match a docid to its value position.
     pub(crate) fn select_batch_in_place(&self, docid_start: DocId, ranks: &mut Vec<u32>) {
         match self {
             MultiValueIndex::MultiValueIndexV1(idx) => {
@@ -285,14 +306,11 @@ impl MultiValueIndexV2 {
     /// Converts a list of ranks (row ids of values) in a 1:n index to the corresponding list of
     /// docids. Positions are converted inplace to docids.
     ///
-    /// Since there is no index for value pos -> docid, but docid -> value pos range, we scan the
-    /// index.
+    /// Since there is no index for value pos -> docid, but docid -> value pos range, we search
+    /// the index, see [`find_idx_containing_pos`].
     ///
     /// Correctness: positions needs to be sorted. idx_reader needs to contain monotonically
     /// increasing positions.
-    ///
-    /// TODO: Instead of a linear scan we can employ a exponential search into binary search to
-    /// match a docid to its value position.
     pub(crate) fn select_batch_in_place(&self, docid_start: DocId, ranks: &mut Vec<u32>) {
         if ranks.is_empty() {
             return;
@@ -305,19 +323,12 @@ impl MultiValueIndexV2 {
         let mut write_doc_pos = 0;
         for i in 0..ranks.len() {
             let pos = ranks[i];
-            loop {
-                let end = self.start_index_column.get_val(cur_pos_in_idx + 1);
-                if end > pos {
-                    ranks[write_doc_pos] = cur_pos_in_idx;
-                    write_doc_pos += if last_doc == Some(cur_pos_in_idx) {
-                        0
-                    } else {
-                        1
-                    };
-                    last_doc = Some(cur_pos_in_idx);
-                    break;
-                }
-                cur_pos_in_idx += 1;
+            cur_pos_in_idx =
+                find_idx_containing_pos(&*self.start_index_column, cur_pos_in_idx, pos);
+            if last_doc != Some(cur_pos_in_idx) {
+                ranks[write_doc_pos] = cur_pos_in_idx;
+                write_doc_pos += 1;
+                last_doc = Some(cur_pos_in_idx);
             }
         }
         ranks.truncate(write_doc_pos);
@@ -328,6 +339,49 @@ impl MultiValueIndexV2 {
     }
 }
 
+/// Returns the first index `idx >= start_idx` of the start index column whose value range
+/// `start_index_column[idx]..start_index_column[idx + 1]` ends after `pos`.
+///
+/// The range is found with an exponential search followed by a binary search, so that it is
+/// cheap both when the owning index is close to `start_idx` and when it is far away.
+///
+/// Panics if `pos` is after the last value range.
+fn find_idx_containing_pos(
+    start_index_column: &dyn ColumnValues<RowId>,
+    start_idx: u32,
+    pos: RowId,
+) -> u32 {
+    let ends_after_pos = |idx: u32| start_index_column.get_val(idx + 1) > pos;
+    if ends_after_pos(start_idx) {
+        return start_idx;
+    }
+    let last_idx = start_index_column.num_vals() - 2;
+    // Invariant: the range of `lo` ends before `pos`, the range of `hi` ends after `pos`.
+    let mut lo = start_idx;
+    let mut step = 1u32;
+    let mut hi = loop {
+        assert!(
+            lo < last_idx,
+            "Position {pos} is out of the multivalued index"
+        );
+        let probe = lo.saturating_add(step).min(last_idx);
+        if ends_after_pos(probe) {
+            break probe;
+        }
+        lo = probe;
+        step = step.saturating_mul(2);
+    };
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if ends_after_pos(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    hi
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
@@ -361,6 +415,32 @@ mod tests {
         assert_eq!(index_to_pos_helper(&index, 2..5, &[12, 14, 15]), vec![2, 3]);
     }
 
+    #[test]
+    fn test_positions_to_docid_far_apart() {
+        // Documents with 0 to 2 values.
+        let mut start_offsets = vec![0u32];
+        for doc in 0..2_000u32 {
+            let num_vals = (doc * 7 % 5) % 3;
+            start_offsets.push(start_offsets.last().unwrap() + num_vals);
+        }
+        let index = MultiValueIndex::for_test(&start_offsets);
+        let num_vals = *start_offsets.last().unwrap();
+        for (doc_start, step) in [(0, 1), (0, 997), (700, 13), (1_990, 1)] {
+            let val_start = start_offsets[doc_start as usize];
+            let positions: Vec<u32> = (val_start..num_vals).step_by(step).collect();
+            let mut expected: Vec<u32> = positions
+                .iter()
+                .map(|&pos| (doc_start..2_000).find(|&doc| index.range(doc).contains(&pos)))
+                .map(Option::unwrap)
+                .collect();
+            expected.dedup();
+            assert_eq!(
+                index_to_pos_helper(&index, doc_start..2_000, &positions),
+                expected
+            );
+        }
+    }
+
     #[test]
     fn test_range_to_rowids() {
         use crate::ColumnarWriter;
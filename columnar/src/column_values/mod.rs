@@ -171,9 +171,40 @@ pub trait ColumnValues<T: PartialOrd = u64>: Send + Sync + DowncastSync {
     fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = T> + 'a> {
         Box::new((0..self.num_vals()).map(|idx| self.get_val(idx)))
     }
+
+    /// Returns the statistics of consecutive blocks of values, as recorded by the codec.
+    ///
+    /// The blocks are ordered by row id and cover all of the values.
+    /// Codecs that do not record any statistics return an empty vec.
+    fn block_stats(&self) -> Vec<BlockStats<T>> {
+        Vec::new()
+    }
 }
 downcast_rs::impl_downcast!(sync ColumnValues<T> where T: PartialOrd);
 
+/// Statistics of a block of consecutive values of a column.
+///
+/// Like [`ColumnValues::min_value`] and [`ColumnValues::max_value`], `min_value` and
+/// `max_value` are bounds of the values of the block, which are not necessarily reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockStats<T> {
+    /// The row ids of the values in the block.
+    ///
+    /// For columns with one value per document, row ids are document ids.
+    pub row_range: Range<RowId>,
+    /// Lower bound of the values of the block.
+    pub min_value: T,
+    /// Upper bound of the values of the block.
+    pub max_value: T,
+}
+
+impl<T> BlockStats<T> {
+    /// Returns the number of values in the block.
+    pub fn num_values(&self) -> u32 {
+        self.row_range.end - self.row_range.start
+    }
+}
+
 /// Empty column of values.
 pub struct EmptyColumnValues;
 
@@ -241,4 +272,8 @@ impl<T: Copy + PartialOrd + Debug + 'static> ColumnValues<T> for Arc<dyn ColumnV
         self.as_ref()
             .get_row_ids_for_value_range(range, doc_id_range, positions)
     }
+    #[inline(always)]
+    fn block_stats(&self) -> Vec<BlockStats<T>> {
+        self.as_ref().block_stats()
+    }
 }
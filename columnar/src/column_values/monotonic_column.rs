@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 use std::ops::{Range, RangeInclusive};
 
 use crate::ColumnValues;
+use crate::column_values::BlockStats;
 use crate::column_values::monotonic_mapping::StrictlyMonotonicFn;
 
 struct MonotonicMappingColumn<C, T, Input> {
@@ -92,6 +93,18 @@ where
         )
     }
 
+    fn block_stats(&self) -> Vec<BlockStats<Output>> {
+        self.from_column
+            .block_stats()
+            .into_iter()
+            .map(|block_stats| BlockStats {
+                row_range: block_stats.row_range,
+                min_value: self.monotonic_mapping.mapping(block_stats.min_value),
+                max_value: self.monotonic_mapping.mapping(block_stats.max_value),
+            })
+            .collect()
+    }
+
     // We voluntarily do not implement get_range as it yields a regression,
     // and we do not have any specialized implementation anyway.
 }
@@ -186,6 +186,18 @@ impl MonotonicallyMappableToU64 for f64 {
     }
 }
 
+impl MonotonicallyMappableToU64 for f32 {
+    #[inline(always)]
+    fn to_u64(self) -> u64 {
+        u64::from(common::f32_to_u32(self))
+    }
+
+    #[inline(always)]
+    fn from_u64(val: u64) -> Self {
+        common::u32_to_f32(val as u32)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -5,6 +5,7 @@ use std::num::NonZeroU64;
 use common::{BinarySerializable, VInt};
 
 use crate::RowId;
+use crate::column_values::BlockStats;
 
 /// Column statistics.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -25,6 +26,19 @@ impl ColumnStats {
     pub fn amplitude(&self) -> u64 {
         self.max_value - self.min_value
     }
+
+    /// Returns the stats of a single block spanning the whole column,
+    /// or `None` if the column is empty.
+    pub(crate) fn whole_column_block_stats(&self) -> Option<BlockStats<u64>> {
+        if self.num_rows == 0 {
+            return None;
+        }
+        Some(BlockStats {
+            row_range: 0..self.num_rows,
+            min_value: self.min_value,
+            max_value: self.max_value,
+        })
+    }
 }
 
 impl BinarySerializable for ColumnStats {
@@ -6,6 +6,7 @@ use common::{BinarySerializable, OwnedBytes};
 use fastdivide::DividerU64;
 use tantivy_bitpacker::{BitPacker, BitUnpacker, compute_num_bits};
 
+use crate::column_values::BlockStats;
 use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
 use crate::{ColumnValues, RowId};
 
@@ -66,6 +67,10 @@ impl ColumnValues for BitpackedReader {
         self.stats.num_rows
     }
 
+    fn block_stats(&self) -> Vec<BlockStats<u64>> {
+        self.stats.whole_column_block_stats().into_iter().collect()
+    }
+
     fn get_row_ids_for_value_range(
         &self,
         range: RangeInclusive<u64>,
@@ -9,7 +9,7 @@ use tantivy_bitpacker::{BitPacker, BitUnpacker, compute_num_bits};
 use crate::MonotonicallyMappableToU64;
 use crate::column_values::u64_based::line::Line;
 use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
-use crate::column_values::{ColumnValues, VecColumn};
+use crate::column_values::{BlockStats, ColumnValues, VecColumn};
 
 const BLOCK_SIZE: u32 = 512u32;
 
@@ -234,6 +234,68 @@ impl ColumnValues for BlockwiseLinearReader {
     fn num_vals(&self) -> u32 {
         self.stats.num_rows
     }
+
+    fn block_stats(&self) -> Vec<BlockStats<u64>> {
+        let gcd = self.stats.gcd.get();
+        let max_normalized_value = (self.stats.max_value - self.stats.min_value) / gcd;
+        self.blocks
+            .iter()
+            .enumerate()
+            .map(|(block_id, block)| {
+                let block_start = block_id as u32 * BLOCK_SIZE;
+                let block_end = (block_start + BLOCK_SIZE).min(self.stats.num_rows);
+                let (min_normalized_value, max_normalized_value) =
+                    block.normalized_value_bounds(block_end - block_start, max_normalized_value);
+                BlockStats {
+                    row_range: block_start..block_end,
+                    min_value: self.stats.min_value + gcd * min_normalized_value,
+                    max_value: self.stats.min_value + gcd * max_normalized_value,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Block {
+    /// Returns bounds of the normalized values, `(val - min_value) / gcd`, of the `num_vals`
+    /// first rows of the block, without decoding them.
+    ///
+    /// The normalized values are `line.eval(idx) + diff`, where `diff` fits in the bit width of
+    /// the block. When the line or this sum wraps around, the bounds fall back to those of the
+    /// column, `0..=max_normalized_value`.
+    fn normalized_value_bounds(&self, num_vals: u32, max_normalized_value: u64) -> (u64, u64) {
+        let column_bounds = (0, max_normalized_value);
+        // `Line::eval` adds `(idx * slope) >> 32`, with `slope` taken as a signed value. It is
+        // monotonic over the block as long as the product does not overflow an `i64`.
+        let last_product = (num_vals as i128 - 1) * (self.line.slope as i64 as i128);
+        if i64::try_from(last_product).is_err() {
+            return column_bounds;
+        }
+        let last_linear_part = (last_product >> 32) as i64;
+        let min_linear_part = last_linear_part.min(0);
+        let line_range = (last_linear_part.max(0) - min_linear_part) as u64;
+        let bit_width = self.bit_unpacker.bit_width();
+        if bit_width >= 64 {
+            return column_bounds;
+        }
+        let max_diff = (1u64 << bit_width) - 1;
+        let Some(range) = line_range.checked_add(max_diff) else {
+            return column_bounds;
+        };
+        let lower_bound = self.line.intercept.wrapping_add(min_linear_part as u64);
+        match lower_bound.checked_add(range) {
+            Some(upper_bound) => (
+                lower_bound.min(max_normalized_value),
+                upper_bound.min(max_normalized_value),
+            ),
+            // The values wrap around past `u64::MAX` back to `0`, unless `lower_bound` is a
+            // possible value.
+            None if lower_bound > max_normalized_value => {
+                (0, lower_bound.wrapping_add(range).min(max_normalized_value))
+            }
+            None => column_bounds,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -6,8 +6,8 @@ use tantivy_bitpacker::{BitPacker, BitUnpacker, compute_num_bits};
 use super::ColumnValues;
 use super::line::Line;
 use crate::RowId;
-use crate::column_values::VecColumn;
 use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
+use crate::column_values::{BlockStats, VecColumn};
 
 const HALF_SPACE: u64 = u64::MAX / 2;
 const LINE_ESTIMATION_BLOCK_LEN: usize = 512;
@@ -43,6 +43,10 @@ impl ColumnValues for LinearReader {
     fn num_vals(&self) -> u32 {
         self.stats.num_rows
     }
+
+    fn block_stats(&self) -> Vec<BlockStats<u64>> {
+        self.stats.whole_column_block_stats().into_iter().collect()
+    }
 }
 
 /// Fastfield serializer, which tries to guess values by linear interpolation
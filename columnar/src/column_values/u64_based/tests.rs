@@ -1,6 +1,9 @@
 use proptest::prelude::*;
 use proptest::{prop_oneof, proptest};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::column_values::BlockStats;
 
 #[test]
 fn test_serialize_and_load_simple() {
@@ -413,3 +416,72 @@ pub fn test_fastfield2() {
     assert_eq!(test_fastfield.get_val(1), 200);
     assert_eq!(test_fastfield.get_val(2), 300);
 }
+
+fn check_block_stats(vals: &[i64], codec_type: CodecType) -> Vec<BlockStats<i64>> {
+    let mut buffer = Vec::new();
+    serialize_u64_based_column_values(&vals, &[codec_type], &mut buffer).unwrap();
+    let column = load_u64_based_column_values::<i64>(OwnedBytes::new(buffer)).unwrap();
+    let block_stats: Vec<BlockStats<i64>> = column.block_stats();
+    let mut next_row_id = 0;
+    for block in &block_stats {
+        assert_eq!(block.row_range.start, next_row_id);
+        next_row_id = block.row_range.end;
+        for &val in &vals[block.row_range.start as usize..block.row_range.end as usize] {
+            assert!(block.min_value <= val && val <= block.max_value);
+        }
+    }
+    assert_eq!(next_row_id, vals.len() as u32);
+    block_stats
+}
+
+#[test]
+fn test_block_stats_monotonically_increasing() {
+    let vals: Vec<i64> = (0..2000i64).map(|i| i * i - 1000).collect();
+    let block_stats = check_block_stats(&vals, CodecType::BlockwiseLinear);
+    assert_eq!(block_stats.len(), 4);
+    assert_eq!(block_stats[0].row_range, 0..512);
+    assert_eq!(block_stats[3].row_range, 1536..2000);
+    assert_eq!(block_stats[3].num_values(), 464);
+    // The bounds derived from the lines are loose, but still separate distant blocks.
+    assert!(block_stats[0].max_value < block_stats[3].min_value);
+    assert_eq!(block_stats[3].max_value, 1999 * 1999 - 1000);
+
+    for codec_type in [CodecType::Bitpacked, CodecType::Linear] {
+        let block_stats = check_block_stats(&vals, codec_type);
+        assert_eq!(block_stats.len(), 1);
+        assert_eq!(block_stats[0].num_values(), 2000);
+        assert_eq!(block_stats[0].min_value, -1000);
+        assert_eq!(block_stats[0].max_value, 1999 * 1999 - 1000);
+    }
+}
+
+#[test]
+fn test_block_stats_random() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let vals: Vec<i64> = (0..3000)
+        .map(|_| rng.random_range(-1_000_000..1_000_000))
+        .collect();
+    for codec_type in ALL_U64_CODEC_TYPES {
+        check_block_stats(&vals, codec_type);
+    }
+    let block_stats = check_block_stats(&vals, CodecType::BlockwiseLinear);
+    assert_eq!(block_stats.len(), 6);
+    let column_min = *vals.iter().min().unwrap();
+    let column_max = *vals.iter().max().unwrap();
+    for block in &block_stats {
+        assert!(column_min <= block.min_value && block.max_value <= column_max);
+    }
+}
+
+#[test]
+fn test_block_stats_empty_column() {
+    let vals: [u64; 0] = [];
+    for codec in ALL_U64_CODEC_TYPES {
+        let mut buffer = Vec::new();
+        if serialize_u64_based_column_values(&&vals[..], &[codec], &mut buffer).is_err() {
+            continue;
+        }
+        let col = load_u64_based_column_values::<u64>(OwnedBytes::new(buffer)).unwrap();
+        assert!(col.block_stats().is_empty());
+    }
+}
@@ -20,6 +20,7 @@ pub enum ColumnType {
     Bool = 5u8,
     IpAddr = 6u8,
     DateTime = 7u8,
+    F32 = 8u8,
 }
 
 impl fmt::Display for ColumnType {
@@ -33,13 +34,14 @@ impl fmt::Display for ColumnType {
             ColumnType::Bool => "bool",
             ColumnType::IpAddr => "ip",
             ColumnType::DateTime => "datetime",
+            ColumnType::F32 => "f32",
         };
         write!(f, "{short_str}")
     }
 }
 
 // The order needs to match _exactly_ the order in the enum
-const COLUMN_TYPES: [ColumnType; 8] = [
+const COLUMN_TYPES: [ColumnType; 9] = [
     ColumnType::I64,
     ColumnType::U64,
     ColumnType::F64,
@@ -48,6 +50,7 @@ const COLUMN_TYPES: [ColumnType; 8] = [
     ColumnType::Bool,
     ColumnType::IpAddr,
     ColumnType::DateTime,
+    ColumnType::F32,
 ];
 
 impl ColumnType {
@@ -83,7 +86,8 @@ impl ColumnType {
             | ColumnType::Str
             | ColumnType::Bool
             | ColumnType::IpAddr
-            | ColumnType::DateTime => None,
+            | ColumnType::DateTime
+            | ColumnType::F32 => None,
         }
     }
 }
@@ -124,6 +128,16 @@ impl HasAssociatedColumnType for f64 {
     }
 }
 
+impl HasAssociatedColumnType for f32 {
+    fn column_type() -> ColumnType {
+        ColumnType::F32
+    }
+
+    fn default_value() -> Self {
+        Default::default()
+    }
+}
+
 impl HasAssociatedColumnType for bool {
     fn column_type() -> ColumnType {
         ColumnType::Bool
@@ -38,6 +38,7 @@ pub(crate) enum ColumnTypeCategory {
     Bool,
     IpAddr,
     DateTime,
+    F32,
 }
 
 impl From<ColumnType> for ColumnTypeCategory {
@@ -51,6 +52,7 @@ impl From<ColumnType> for ColumnTypeCategory {
             ColumnType::Bool => ColumnTypeCategory::Bool,
             ColumnType::IpAddr => ColumnTypeCategory::IpAddr,
             ColumnType::DateTime => ColumnTypeCategory::DateTime,
+            ColumnType::F32 => ColumnTypeCategory::F32,
         }
     }
 }
@@ -122,6 +124,7 @@ fn dynamic_column_to_u64_monotonic(dynamic_column: DynamicColumn) -> Option<Colu
         DynamicColumn::I64(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::U64(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::F64(column) => Some(column.to_u64_monotonic()),
+        DynamicColumn::F32(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::DateTime(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::IpAddr(_) | DynamicColumn::Bytes(_) | DynamicColumn::Str(_) => None,
     }
@@ -138,6 +141,7 @@ fn merge_column(
         ColumnType::I64
         | ColumnType::U64
         | ColumnType::F64
+        | ColumnType::F32
         | ColumnType::DateTime
         | ColumnType::Bool => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
@@ -466,6 +470,7 @@ fn min_max_if_numerical(column: &DynamicColumn) -> Option<(NumericalValue, Numer
         DynamicColumn::U64(column) => Some((column.min_value().into(), column.max_value().into())),
         DynamicColumn::F64(column) => Some((column.min_value().into(), column.max_value().into())),
         DynamicColumn::Bool(_)
+        | DynamicColumn::F32(_)
         | DynamicColumn::IpAddr(_)
         | DynamicColumn::DateTime(_)
         | DynamicColumn::Bytes(_)
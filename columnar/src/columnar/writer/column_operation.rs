@@ -155,6 +155,18 @@ impl SymbolValue for Ipv6Addr {
     }
 }
 
+impl SymbolValue for f32 {
+    fn serialize(self, buffer: &mut [u8]) -> u8 {
+        buffer[0..4].copy_from_slice(&self.to_le_bytes());
+        4
+    }
+
+    fn deserialize(bytes: &[u8]) -> Self {
+        let quartet: [u8; 4] = bytes[0..4].try_into().unwrap();
+        f32::from_le_bytes(quartet)
+    }
+}
+
 #[derive(Default)]
 struct MiniBuffer {
     pub bytes: [u8; 17],
@@ -3,6 +3,7 @@ mod column_writers;
 mod serializer;
 mod value_index;
 
+use std::collections::BTreeMap;
 use std::io;
 use std::net::Ipv6Addr;
 
@@ -51,6 +52,10 @@ pub struct ColumnarWriter {
     numerical_field_hash_map: ArenaHashMap,
     datetime_field_hash_map: ArenaHashMap,
     bool_field_hash_map: ArenaHashMap,
+    // f32 columns are only declared by the schema, so there are few of them. Their writers are
+    // stored in `arena` rather than in an `ArenaHashMap`, which would allocate an arena of its
+    // own.
+    f32_column_writer_addrs: BTreeMap<String, Addr>,
     ip_addr_field_hash_map: ArenaHashMap,
     bytes_field_hash_map: ArenaHashMap,
     str_field_hash_map: ArenaHashMap,
@@ -130,6 +135,9 @@ impl ColumnarWriter {
                     |column_opt: Option<ColumnWriter>| column_opt.unwrap_or_default(),
                 );
             }
+            ColumnType::F32 => {
+                self.f32_column_writer_addr(column_name);
+            }
             ColumnType::I64 | ColumnType::F64 | ColumnType::U64 => {
                 let numerical_type = column_type.numerical_type().unwrap();
                 self.numerical_field_hash_map.mutate_or_create(
@@ -189,6 +197,27 @@ impl ColumnarWriter {
         );
     }
 
+    pub fn record_f32(&mut self, doc: RowId, column_name: &str, val: f32) {
+        let addr = self.f32_column_writer_addr(column_name);
+        let mut column: ColumnWriter = self.arena.read(addr);
+        column.record(doc, val, &mut self.arena);
+        self.arena.write_at(addr, column);
+    }
+
+    /// Returns the address of the writer of the f32 column, creating it if necessary.
+    fn f32_column_writer_addr(&mut self, column_name: &str) -> Addr {
+        if let Some(addr) = self.f32_column_writer_addrs.get(column_name) {
+            return *addr;
+        }
+        let addr = self
+            .arena
+            .allocate_space(std::mem::size_of::<ColumnWriter>());
+        self.arena.write_at(addr, ColumnWriter::default());
+        self.f32_column_writer_addrs
+            .insert(column_name.to_string(), addr);
+        addr
+    }
+
     pub fn record_datetime(&mut self, doc: RowId, column_name: &str, datetime: common::DateTime) {
         let (hash_map, arena) = (&mut self.datetime_field_hash_map, &mut self.arena);
         hash_map.mutate_or_create(
@@ -274,6 +303,11 @@ impl ColumnarWriter {
                 .iter()
                 .map(|(column_name, addr)| (column_name, ColumnType::Bool, addr)),
         );
+        columns.extend(
+            self.f32_column_writer_addrs
+                .iter()
+                .map(|(column_name, addr)| (column_name.as_bytes(), ColumnType::F32, *addr)),
+        );
         columns.extend(
             self.ip_addr_field_hash_map
                 .iter()
@@ -309,6 +343,20 @@ impl ColumnarWriter {
                     )?;
                     column_serializer.finalize()?;
                 }
+                ColumnType::F32 => {
+                    let column_writer: ColumnWriter = self.arena.read(addr);
+                    let cardinality = column_writer.get_cardinality(num_docs);
+                    let mut column_serializer =
+                        serializer.start_serialize_column(column_name, column_type);
+                    serialize_f32_column(
+                        cardinality,
+                        num_docs,
+                        column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        buffers,
+                        &mut column_serializer,
+                    )?;
+                    column_serializer.finalize()?;
+                }
                 ColumnType::IpAddr => {
                     let column_writer: ColumnWriter = self.ip_addr_field_hash_map.read(addr);
                     let cardinality = column_writer.get_cardinality(num_docs);
@@ -513,6 +561,33 @@ fn serialize_bool_column(
     Ok(())
 }
 
+fn serialize_f32_column(
+    cardinality: Cardinality,
+    num_docs: RowId,
+    column_operations_it: impl Iterator<Item = ColumnOperation<f32>>,
+    buffers: &mut SpareBuffers,
+    wrt: &mut impl io::Write,
+) -> io::Result<()> {
+    let SpareBuffers {
+        value_index_builders,
+        u64_values,
+        ..
+    } = buffers;
+    send_to_serialize_column_mappable_to_u64(
+        column_operations_it.map(|f32_column_operation| match f32_column_operation {
+            ColumnOperation::NewDoc(doc) => ColumnOperation::NewDoc(doc),
+            ColumnOperation::Value(f32_val) => ColumnOperation::Value(f32_val.to_u64()),
+        }),
+        cardinality,
+        num_docs,
+        false,
+        value_index_builders,
+        u64_values,
+        wrt,
+    )?;
+    Ok(())
+}
+
 fn serialize_ip_addr_column(
     cardinality: Cardinality,
     num_docs: RowId,
@@ -17,6 +17,7 @@ pub enum DynamicColumn {
     I64(Column<i64>),
     U64(Column<u64>),
     F64(Column<f64>),
+    F32(Column<f32>),
     IpAddr(Column<Ipv6Addr>),
     DateTime(Column<DateTime>),
     Bytes(BytesColumn),
@@ -31,6 +32,7 @@ impl fmt::Debug for DynamicColumn {
             DynamicColumn::I64(col) => write!(f, " {col:?}")?,
             DynamicColumn::U64(col) => write!(f, " {col:?}")?,
             DynamicColumn::F64(col) => write!(f, "{col:?}")?,
+            DynamicColumn::F32(col) => write!(f, "{col:?}")?,
             DynamicColumn::IpAddr(col) => write!(f, "{col:?}")?,
             DynamicColumn::DateTime(col) => write!(f, "{col:?}")?,
             DynamicColumn::Bytes(col) => write!(f, "{col:?}")?,
@@ -47,6 +49,7 @@ impl DynamicColumn {
             DynamicColumn::I64(c) => &c.index,
             DynamicColumn::U64(c) => &c.index,
             DynamicColumn::F64(c) => &c.index,
+            DynamicColumn::F32(c) => &c.index,
             DynamicColumn::IpAddr(c) => &c.index,
             DynamicColumn::DateTime(c) => &c.index,
             DynamicColumn::Bytes(c) => &c.ords().index,
@@ -64,6 +67,7 @@ impl DynamicColumn {
             DynamicColumn::I64(c) => c.values.num_vals(),
             DynamicColumn::U64(c) => c.values.num_vals(),
             DynamicColumn::F64(c) => c.values.num_vals(),
+            DynamicColumn::F32(c) => c.values.num_vals(),
             DynamicColumn::IpAddr(c) => c.values.num_vals(),
             DynamicColumn::DateTime(c) => c.values.num_vals(),
             DynamicColumn::Bytes(c) => c.ords().values.num_vals(),
@@ -77,6 +81,7 @@ impl DynamicColumn {
             DynamicColumn::I64(_) => ColumnType::I64,
             DynamicColumn::U64(_) => ColumnType::U64,
             DynamicColumn::F64(_) => ColumnType::F64,
+            DynamicColumn::F32(_) => ColumnType::F32,
             DynamicColumn::IpAddr(_) => ColumnType::IpAddr,
             DynamicColumn::DateTime(_) => ColumnType::DateTime,
             DynamicColumn::Bytes(_) => ColumnType::Bytes,
@@ -224,6 +229,7 @@ static_dynamic_conversions!(Column<bool>, Bool);
 static_dynamic_conversions!(Column<u64>, U64);
 static_dynamic_conversions!(Column<i64>, I64);
 static_dynamic_conversions!(Column<f64>, F64);
+static_dynamic_conversions!(Column<f32>, F32);
 static_dynamic_conversions!(Column<DateTime>, DateTime);
 static_dynamic_conversions!(StrColumn, Str);
 static_dynamic_conversions!(BytesColumn, Bytes);
@@ -249,7 +255,7 @@ impl DynamicColumnHandle {
     }
 
     /// Returns the `u64` fast field reader reader associated with `fields` of types
-    /// Str, u64, i64, f64, bool, ip, or datetime.
+    /// Str, u64, i64, f64, f32, bool, ip, or datetime.
     ///
     /// Notice that for IpAddr, the fastfield reader will return the u64 representation of the
     /// IpAddr.
@@ -277,6 +283,7 @@ impl DynamicColumnHandle {
             | ColumnType::I64
             | ColumnType::U64
             | ColumnType::F64
+            | ColumnType::F32
             | ColumnType::DateTime => {
                 let column =
                     crate::column::open_column_u64::<u64>(column_bytes, self.format_version)?;
@@ -302,6 +309,9 @@ impl DynamicColumnHandle {
             ColumnType::F64 => {
                 crate::column::open_column_u64::<f64>(column_bytes, self.format_version)?.into()
             }
+            ColumnType::F32 => {
+                crate::column::open_column_u64::<f32>(column_bytes, self.format_version)?.into()
+            }
             ColumnType::Bool => {
                 crate::column::open_column_u64::<bool>(column_bytes, self.format_version)?.into()
             }
@@ -254,6 +254,7 @@ enum ColumnValue {
     IpAddr(Ipv6Addr),
     Bool(bool),
     DateTime(DateTime),
+    F32(f32),
 }
 
 impl<T: Into<NumericalValue>> From<T> for ColumnValue {
@@ -271,6 +272,7 @@ impl ColumnValue {
             ColumnValue::IpAddr(_) => ColumnTypeCategory::IpAddr,
             ColumnValue::Bool(_) => ColumnTypeCategory::Bool,
             ColumnValue::DateTime(_) => ColumnTypeCategory::DateTime,
+            ColumnValue::F32(_) => ColumnTypeCategory::F32,
         }
     }
 }
@@ -305,7 +307,8 @@ fn column_value_strategy() -> impl Strategy<Value = ColumnValue> {
         ))),
         1 => any::<bool>().prop_map(ColumnValue::Bool),
         1 => (679_723_993i64..1_679_723_995i64)
-            .prop_map(|val| { ColumnValue::DateTime(DateTime::from_timestamp_secs(val)) })
+            .prop_map(|val| { ColumnValue::DateTime(DateTime::from_timestamp_secs(val)) }),
+        1 => (-2i8..3i8).prop_map(|val| ColumnValue::F32(f32::from(val) * 0.5))
     ]
 }
 
@@ -359,6 +362,9 @@ fn build_columnar_with_mapping(docs: &[Vec<(&'static str, ColumnValue)>]) -> Col
                 ColumnValue::DateTime(date_time) => {
                     columnar_writer.record_datetime(doc_id as u32, column_name, date_time);
                 }
+                ColumnValue::F32(f32_val) => {
+                    columnar_writer.record_f32(doc_id as u32, column_name, f32_val);
+                }
             }
         }
     }
@@ -458,6 +464,9 @@ fn assert_dyn_column_eq(
         (DynamicColumn::DateTime(left_col), DynamicColumn::DateTime(right_col)) => {
             assert_column_eq(left_col, right_col);
         }
+        (DynamicColumn::F32(left_col), DynamicColumn::F32(right_col)) => {
+            assert_column_eq(left_col, right_col);
+        }
         (DynamicColumn::IpAddr(left_col), DynamicColumn::IpAddr(right_col)) => {
             assert_column_eq(left_col, right_col);
         }
@@ -515,6 +524,15 @@ impl<T: Coerce + PartialEq + Debug + Into<NumericalValue>> AssertEqualToColumnVa
     }
 }
 
+impl AssertEqualToColumnValue for f32 {
+    fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
+        let ColumnValue::F32(val) = column_value else {
+            panic!()
+        };
+        assert_eq!(self, val);
+    }
+}
+
 impl AssertEqualToColumnValue for DateTime {
     fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
         let ColumnValue::DateTime(dt) = column_value else {
@@ -544,6 +562,13 @@ fn assert_column_values<
         }
     }
     assert_eq!(num_non_empty_rows, expected.len());
+    let mut visited_docs = Vec::new();
+    col.for_each_doc_values(|doc, doc_vals| {
+        assert_eq!(doc_vals, col.values_for_doc(doc).collect::<Vec<T>>());
+        visited_docs.push(doc);
+    });
+    assert_eq!(visited_docs.len(), expected.len());
+    assert!(visited_docs.windows(2).all(|docs| docs[0] < docs[1]));
 }
 
 fn assert_bytes_column_values(
@@ -619,6 +644,8 @@ proptest! {
                     assert_column_values(col, expected_col_values),
                 DynamicColumn::DateTime(col) =>
                     assert_column_values(col, expected_col_values),
+                DynamicColumn::F32(col) =>
+                    assert_column_values(col, expected_col_values),
                 DynamicColumn::Bytes(col) =>
                     assert_bytes_column_values(col, expected_col_values, false),
                 DynamicColumn::Str(col) =>
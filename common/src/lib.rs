@@ -113,6 +113,32 @@ pub fn u64_to_f64(val: u64) -> f64 {
     })
 }
 
+/// Maps a `f32` to `u32` in a monotonic manner, so that bytes lexical order is preserved.
+///
+/// This is the `f32` counterpart of [`f64_to_u64()`].
+///
+/// # See also
+/// The reverse mapping is [`u32_to_f32()`].
+#[inline]
+pub fn f32_to_u32(val: f32) -> u32 {
+    let bits = val.to_bits();
+    if val.is_sign_positive() {
+        bits ^ (1u32 << 31)
+    } else {
+        !bits
+    }
+}
+
+/// Reverse the mapping given by [`f32_to_u32()`].
+#[inline]
+pub fn u32_to_f32(val: u32) -> f32 {
+    f32::from_bits(if val & (1u32 << 31) != 0 {
+        val ^ (1u32 << 31)
+    } else {
+        !val
+    })
+}
+
 /// Replaces a given byte in the `bytes` slice of bytes.
 ///
 /// This function assumes that the needle is rarely contained in the bytes string
@@ -134,7 +160,7 @@ pub(crate) mod test {
 
     use proptest::prelude::*;
 
-    use super::{f64_to_u64, i64_to_u64, u64_to_f64, u64_to_i64};
+    use super::{f32_to_u32, f64_to_u64, i64_to_u64, u32_to_f32, u64_to_f64, u64_to_i64};
 
     fn test_i64_converter_helper(val: i64) {
         assert_eq!(u64_to_i64(i64_to_u64(val)), val);
@@ -190,6 +216,26 @@ pub(crate) mod test {
         assert!(f64_to_u64(-2.0) < f64_to_u64(-1.5));
     }
 
+    #[test]
+    fn test_f32_converter() {
+        for val in [
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            1.5e-40,
+        ] {
+            assert_eq!(u32_to_f32(f32_to_u32(val)), val);
+        }
+        assert!(f32_to_u32(1.5) > f32_to_u32(1.0));
+        assert!(f32_to_u32(2.0) > f32_to_u32(1.5));
+        assert!(f32_to_u32(1.0) > f32_to_u32(-1.0));
+        assert!(f32_to_u32(-1.5) < f32_to_u32(-1.0));
+        assert!(f32_to_u32(-2.0) < f32_to_u32(-1.5));
+    }
+
     #[test]
     fn test_replace_in_place() {
         let test_aux = |before_replacement: &[u8], expected: &[u8]| {
@@ -211,6 +211,12 @@ impl BinarySerializable for VInt {
                         return Ok(VInt(result));
                     }
                     shift += 7;
+                    if shift >= 64 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "VInt does not fit in a u64",
+                        ));
+                    }
                 }
                 _ => {
                     return Err(io::Error::new(
@@ -268,6 +274,15 @@ mod tests {
         assert_eq!(&buffer[..len_vint], res2, "array wrong for {val}");
     }
 
+    #[test]
+    fn test_vint_too_long() {
+        let mut buffer = [0u8; 10];
+        let num_bytes = VInt(u64::MAX).serialize_into(&mut buffer);
+        assert_eq!(num_bytes, 10);
+        assert_eq!(VInt::deserialize(&mut &buffer[..]).unwrap().val(), u64::MAX);
+        assert!(VInt::deserialize(&mut &[0u8; 11][..]).is_err());
+    }
+
     #[test]
     fn test_vint_u32() {
         aux_test_serialize_vint_u32(0);
@@ -0,0 +1,218 @@
+//! Derive macro mapping Rust structs to tantivy documents.
+//!
+//! This crate is re-exported by `tantivy` when its `derive` feature is enabled, and should be
+//! used through `tantivy::TantivyDoc`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Derives `schema()`, `to_document()` and `from_document()` for a struct with named fields.
+///
+/// Each field maps to the schema field of the same name, and is configured with a
+/// `#[tantivy(...)]` attribute accepting:
+/// - `field = "name"`: the name of the schema field, if it differs from the struct field.
+/// - `stored`, `indexed`, `fast`: the usual field options.
+/// - `text`, `string`: index strings with the default tokenizer, or untokenized.
+/// - `json`: map the field, which must implement `Serialize` and `Deserialize`, to a JSON
+///   field. Its string values are indexed if `indexed`, `text` or `string` is set.
+/// - `skip`: do not map the field. It is set to its `Default` value by `from_document()`.
+///
+/// Supported field types are `String`, the integer and float types, `bool`,
+/// `tantivy::DateTime`, `Ipv6Addr`, and `Option` or `Vec` of them.
+#[proc_macro_derive(TantivyDoc, attributes(tantivy))]
+pub fn derive_tantivy_doc(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_tantivy_doc(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct FieldAttributes {
+    field_name: Option<String>,
+    stored: bool,
+    indexed: bool,
+    fast: bool,
+    text: bool,
+    string: bool,
+    json: bool,
+    skip: bool,
+}
+
+impl FieldAttributes {
+    fn parse(field: &syn::Field) -> syn::Result<FieldAttributes> {
+        let mut attributes = FieldAttributes::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("tantivy"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let flag = if meta.path.is_ident("field") {
+                    let field_name: LitStr = meta.value()?.parse()?;
+                    attributes.field_name = Some(field_name.value());
+                    return Ok(());
+                } else if meta.path.is_ident("stored") {
+                    &mut attributes.stored
+                } else if meta.path.is_ident("indexed") {
+                    &mut attributes.indexed
+                } else if meta.path.is_ident("fast") {
+                    &mut attributes.fast
+                } else if meta.path.is_ident("text") {
+                    &mut attributes.text
+                } else if meta.path.is_ident("string") {
+                    &mut attributes.string
+                } else if meta.path.is_ident("json") {
+                    &mut attributes.json
+                } else if meta.path.is_ident("skip") {
+                    &mut attributes.skip
+                } else {
+                    return Err(meta.error("unsupported tantivy attribute"));
+                };
+                *flag = true;
+                Ok(())
+            })?;
+        }
+        if attributes.text && attributes.string {
+            return Err(syn::Error::new(
+                field.span(),
+                "`text` and `string` cannot be used together",
+            ));
+        }
+        Ok(attributes)
+    }
+
+    fn to_tokens(&self) -> TokenStream2 {
+        let FieldAttributes {
+            stored,
+            indexed,
+            fast,
+            text,
+            string,
+            ..
+        } = *self;
+        quote! {
+            ::tantivy::schema::document::derive::FieldAttributes {
+                stored: #stored,
+                indexed: #indexed,
+                fast: #fast,
+                text: #text,
+                string: #string,
+            }
+        }
+    }
+}
+
+fn expand_tantivy_doc(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "`TantivyDoc` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`TantivyDoc` can only be derived for structs",
+            ))
+        }
+    };
+    let derive = quote!(::tantivy::schema::document::derive);
+
+    let mut add_fields = Vec::new();
+    let mut add_values = Vec::new();
+    let mut read_values = Vec::new();
+    for field in fields {
+        let attributes = FieldAttributes::parse(field)?;
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let ty = &field.ty;
+        if attributes.skip {
+            read_values.push(quote! {
+                #ident: ::std::default::Default::default()
+            });
+            continue;
+        }
+        let field_name = attributes
+            .field_name
+            .clone()
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+        let field_name = LitStr::new(&field_name, Span::call_site());
+        let field_attributes = attributes.to_tokens();
+        if attributes.json {
+            add_fields.push(quote! {
+                #derive::add_json_field(&mut schema_builder, #field_name, #field_attributes);
+            });
+            add_values.push(quote! {
+                if let Ok(field) = schema.get_field(#field_name) {
+                    #derive::add_json_value(&self.#ident, &mut document, field);
+                }
+            });
+            read_values.push(quote! {
+                #ident: #derive::from_json_values::<#ty>(
+                    #field_name,
+                    #derive::owned_values(document, #derive::get_field(schema, #field_name)?),
+                )?
+            });
+        } else {
+            // Spanned on the type, so that an unsupported type points at the field.
+            let field_value = quote_spanned!(ty.span()=> <#ty as #derive::DerivedFieldValue>);
+            add_fields.push(quote! {
+                #field_value::add_field(&mut schema_builder, #field_name, #field_attributes);
+            });
+            add_values.push(quote! {
+                if let Ok(field) = schema.get_field(#field_name) {
+                    #field_value::add_to_document(&self.#ident, &mut document, field);
+                }
+            });
+            read_values.push(quote! {
+                #ident: #field_value::from_owned_values(
+                    #field_name,
+                    #derive::owned_values(document, #derive::get_field(schema, #field_name)?),
+                )?
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Returns a schema holding a field for each of the mapped fields of the struct.
+            pub fn schema() -> ::tantivy::schema::Schema {
+                let mut schema_builder = ::tantivy::schema::Schema::builder();
+                #(#add_fields)*
+                schema_builder.build()
+            }
+
+            /// Converts the struct into a document of `schema`.
+            ///
+            /// Fields missing from the schema are left out of the document.
+            pub fn to_document(
+                &self,
+                schema: &::tantivy::schema::Schema,
+            ) -> ::tantivy::TantivyDocument {
+                let mut document = ::tantivy::TantivyDocument::default();
+                #(#add_values)*
+                document
+            }
+
+            /// Builds the struct back from a document of `schema`.
+            pub fn from_document(
+                document: &::tantivy::TantivyDocument,
+                schema: &::tantivy::schema::Schema,
+            ) -> ::std::result::Result<Self, ::tantivy::schema::document::DeserializeError> {
+                ::std::result::Result::Ok(Self {
+                    #(#read_values,)*
+                })
+            }
+        }
+    })
+}
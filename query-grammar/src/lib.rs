@@ -9,6 +9,7 @@ mod user_input_ast;
 
 pub use crate::infallible::LenientError;
 pub use crate::occur::Occur;
+pub use crate::query_grammar::MATCH_ALL_IN_FIELD_GROUP;
 use crate::query_grammar::{parse_to_ast, parse_to_ast_lenient};
 pub use crate::user_input_ast::{
     Delimiter, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral,
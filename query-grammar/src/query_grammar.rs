@@ -1,5 +1,4 @@
 use std::borrow::Cow;
-use std::iter::once;
 
 use fnv::FnvHashSet;
 use nom::IResult;
@@ -19,27 +18,66 @@ use crate::infallible::*;
 use crate::user_input_ast::Delimiter;
 
 // Note: '-' char is only forbidden at the beginning of a field name, would be clearer to add it to
-// special characters.
+// special characters. '<' and '>' are forbidden at the beginning of a field name too, so that
+// unbounded ranges such as `>=2020-01-01T00:00:00Z` are not mistaken for a field name in a group.
 const SPECIAL_CHARS: &[char] = &[
     '+', '^', '`', ':', '{', '}', '"', '\'', '[', ']', '(', ')', '!', '\\', '*', ' ',
 ];
 
+/// Characters which can be escaped with a backslash in terms, field names and double quoted
+/// phrases.
+///
+/// A backslash followed by one of these characters, or by a whitespace, stands for the character
+/// itself. A backslash followed by any other character is kept as is.
+const ESCAPABLE_CHARS: &[char] = &[
+    '+', '-', '&', '|', '!', '(', ')', '{', '}', '[', ']', '^', '"', '\'', '`', '~', '*', '?', ':',
+    '\\', '/',
+];
+
+fn is_escapable(c: char) -> bool {
+    c.is_whitespace() || ESCAPABLE_CHARS.contains(&c)
+}
+
 /// consume a field name followed by colon. Return the field name with escape sequence
-/// already interpreted
+/// already interpreted, except for `\\` which is kept as it also escapes characters of
+/// json paths.
 fn field_name(inp: &str) -> IResult<&str, String> {
     let simple_char = none_of(SPECIAL_CHARS);
-    let first_char = verify(none_of(SPECIAL_CHARS), |c| *c != '-');
-    let escape_sequence = || preceded(char('\\'), one_of(SPECIAL_CHARS));
+    let first_char = verify(none_of(SPECIAL_CHARS), |c| !['-', '<', '>'].contains(c));
+    let escape_sequence = || {
+        map(preceded(char('\\'), satisfy(is_escapable)), |c| match c {
+            '\\' => Cow::Borrowed("\\\\"),
+            c => Cow::Owned(c.to_string()),
+        })
+    };
+    // A `*` json path segment, as in `attrs.*.status`.
+    let wildcard_segment = terminated(
+        tag(".*"),
+        peek(alt((char('.'), preceded(multispace0, char(':'))))),
+    );
 
     map(
         terminated(
             tuple((
-                alt((first_char, escape_sequence())),
-                many0(alt((simple_char, escape_sequence(), char('\\')))),
+                alt((
+                    escape_sequence(),
+                    map(first_char, |c| Cow::Owned(c.to_string())),
+                )),
+                many0(alt((
+                    map(wildcard_segment, Cow::Borrowed),
+                    escape_sequence(),
+                    map(alt((simple_char, char('\\'))), |c| {
+                        Cow::Owned(c.to_string())
+                    }),
+                ))),
             )),
             tuple((multispace0, char(':'), multispace0)),
         ),
-        |(first_char, next)| once(first_char).chain(next).collect(),
+        |(first_char, next)| {
+            let mut field_name = first_char.into_owned();
+            field_name.extend(next);
+            field_name
+        },
     )(inp)
 }
 
@@ -48,11 +86,10 @@ const ESCAPE_IN_WORD: &[char] = &['^', '`', ':', '{', '}', '"', '\'', '[', ']',
 fn interpret_escape(source: &str) -> String {
     let mut res = String::with_capacity(source.len());
     let mut in_escape = false;
-    let require_escape = |c: char| c.is_whitespace() || ESCAPE_IN_WORD.contains(&c) || c == '-';
 
     for c in source.chars() {
         if in_escape {
-            if !require_escape(c) {
+            if !is_escapable(c) {
                 // we re-add the escape sequence
                 res.push('\\');
             }
@@ -67,8 +104,21 @@ fn interpret_escape(source: &str) -> String {
     res
 }
 
+/// Returns true if the word contains a `:` which is not escaped.
+fn has_unescaped_colon(word: &str) -> bool {
+    let mut in_escape = false;
+    for c in word.chars() {
+        match c {
+            _ if in_escape => in_escape = false,
+            '\\' => in_escape = true,
+            ':' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
 /// Consume a word outside of any context.
-// TODO should support escape sequences
 fn word(inp: &str) -> IResult<&str, Cow<'_, str>> {
     map_res(
         recognize(tuple((
@@ -89,6 +139,12 @@ fn word(inp: &str) -> IResult<&str, Cow<'_, str>> {
     )(inp)
 }
 
+const INVALID_FIELD_AS_TERM: &str = "parsed possible invalid field as term";
+
+/// Message of the [`LenientError`](crate::LenientError) reported for a `*:*` within a field group, e.g.
+/// `title:(stable *:*)`.
+pub const MATCH_ALL_IN_FIELD_GROUP: &str = "`*:*` cannot be used in a field group";
+
 fn word_infallible(
     delimiter: &str,
     emit_error: bool,
@@ -109,16 +165,10 @@ fn word_infallible(
             ),
             |(opt_s, mut errors)| match opt_s {
                 Some(s) => {
-                    if emit_error
-                        && (s
-                            .as_bytes()
-                            .windows(2)
-                            .any(|window| window[0] != b'\\' && window[1] == b':')
-                            || s.starts_with(':'))
-                    {
+                    if emit_error && has_unescaped_colon(s) {
                         errors.push(LenientErrorInternal {
                             pos: inp.len(),
-                            message: "parsed possible invalid field as term".to_string(),
+                            message: INVALID_FIELD_AS_TERM.to_string(),
                         });
                     }
                     if s.contains('\\') {
@@ -151,29 +201,40 @@ fn negative_number(inp: &str) -> IResult<&str, &str> {
     ))(inp)
 }
 
-fn simple_term(inp: &str) -> IResult<&str, (Delimiter, String)> {
-    let escaped_string = |delimiter| {
-        // we need this because none_of can't accept an owned array of char.
-        let not_delimiter = verify(anychar, move |parsed| *parsed != delimiter);
-        map(
-            delimited(
-                char(delimiter),
-                many0(alt((preceded(char('\\'), anychar), not_delimiter))),
-                char(delimiter),
-            ),
-            |res| res.into_iter().collect::<String>(),
-        )
-    };
+/// Consume the content of a double quoted phrase, up to its closing quote, interpreting the
+/// escape sequences.
+fn double_quoted_content(inp: &str) -> IResult<&str, String> {
+    map(
+        many0(alt((
+            preceded(char('\\'), satisfy(is_escapable)),
+            none_of("\""),
+        ))),
+        |chars| chars.into_iter().collect(),
+    )(inp)
+}
 
+/// Consume the content of a single quoted literal, up to its closing quote.
+///
+/// The content is raw: the only escape sequence is `''`, standing for a single quote.
+fn single_quoted_content(inp: &str) -> IResult<&str, String> {
+    map(
+        many0(alt((value('\'', tag("''")), none_of("'")))),
+        |chars| chars.into_iter().collect(),
+    )(inp)
+}
+
+fn simple_term(inp: &str) -> IResult<&str, (Delimiter, String)> {
     let negative_number = map(negative_number, |number| {
         (Delimiter::None, number.to_string())
     });
-    let double_quotes = map(escaped_string('"'), |phrase| {
-        (Delimiter::DoubleQuotes, phrase)
-    });
-    let simple_quotes = map(escaped_string('\''), |phrase| {
-        (Delimiter::SingleQuotes, phrase)
-    });
+    let double_quotes = map(
+        delimited(char('"'), double_quoted_content, char('"')),
+        |phrase| (Delimiter::DoubleQuotes, phrase),
+    );
+    let simple_quotes = map(
+        delimited(char('\''), single_quoted_content, char('\'')),
+        |phrase| (Delimiter::SingleQuotes, phrase),
+    );
     let text_no_delimiter = map(word, |text| (Delimiter::None, text.to_string()));
 
     alt((
@@ -188,28 +249,28 @@ fn simple_term_infallible(
     delimiter: &str,
 ) -> impl Fn(&str) -> JResult<&str, Option<(Delimiter, String)>> + '_ {
     |inp| {
-        let escaped_string = |delimiter| {
-            // we need this because none_of can't accept an owned array of char.
-            let not_delimiter = verify(anychar, move |parsed| *parsed != delimiter);
+        let quoted_string = |delimiter, content: fn(&str) -> IResult<&str, String>| {
             map(
                 delimited_infallible(
                     nothing,
-                    opt_i(many0(alt((preceded(char('\\'), anychar), not_delimiter)))),
+                    opt_i(content),
                     opt_i_err(char(delimiter), format!("missing delimiter \\{delimiter}")),
                 ),
                 |(res, err)| {
-                    // many0 can't fail
-                    (res.unwrap().into_iter().collect::<String>(), err)
+                    // the content can't fail
+                    (res.unwrap(), err)
                 },
             )
         };
 
-        let double_quotes = map(escaped_string('"'), |(phrase, errors)| {
-            (Some((Delimiter::DoubleQuotes, phrase)), errors)
-        });
-        let simple_quotes = map(escaped_string('\''), |(phrase, errors)| {
-            (Some((Delimiter::SingleQuotes, phrase)), errors)
-        });
+        let double_quotes = map(
+            quoted_string('"', double_quoted_content),
+            |(phrase, errors)| (Some((Delimiter::DoubleQuotes, phrase)), errors),
+        );
+        let simple_quotes = map(
+            quoted_string('\'', single_quoted_content),
+            |(phrase, errors)| (Some((Delimiter::SingleQuotes, phrase)), errors),
+        );
 
         alt_infallible(
             (
@@ -308,7 +369,19 @@ fn term_group_infallible(inp: &str) -> JResult<&str, UserInputAst> {
 
     delimited_infallible(
         nothing,
-        map(ast_infallible, |(mut ast, errors)| {
+        map(ast_infallible, |(mut ast, mut errors)| {
+            // `*:*` gets parsed as a term with an invalid field. Within a group, give a more
+            // helpful message, as the field of the group cannot apply to it.
+            for error in &mut errors {
+                let Some(error_offset) = inp.len().checked_sub(error.pos) else {
+                    continue;
+                };
+                if error.message == INVALID_FIELD_AS_TERM
+                    && inp[error_offset..].trim_start().starts_with("*:*")
+                {
+                    error.message = MATCH_ALL_IN_FIELD_GROUP.to_string();
+                }
+            }
             ast.set_default_field(field_name.to_string());
             (ast, errors)
         }),
@@ -367,10 +440,16 @@ fn literal(inp: &str) -> IResult<&str, UserInputAst> {
     // something (a field name) got parsed before
     alt((
         map(
-            tuple((
-                opt(field_name),
-                alt((range, set, exists, regex, term_or_phrase)),
-            )),
+            verify(
+                tuple((
+                    opt(field_name),
+                    alt((range, set, exists, regex, term_or_phrase)),
+                )),
+                // `exists` requires a field name: `*:*` is not a valid query.
+                |(field_name, leaf)| {
+                    field_name.is_some() || !matches!(leaf, UserInputLeaf::Exists { .. })
+                },
+            ),
             |(field_name, leaf): (Option<String>, UserInputLeaf)| leaf.set_field(field_name).into(),
         ),
         term_group,
@@ -1364,7 +1443,7 @@ mod test {
         );
         assert_eq!(
             super::field_name(r#"my\\field:a"#),
-            Ok(("a", r#"my\field"#.to_string()))
+            Ok(("a", r#"my\\field"#.to_string()))
         );
         assert!(super::field_name("my field:a").is_err());
         assert_eq!(
@@ -1398,11 +1477,26 @@ mod test {
             super::field_name(".my.field.name : a"),
             Ok(("a", ".my.field.name".to_string()))
         );
+        assert_eq!(
+            super::field_name("attrs.*.status:a"),
+            Ok(("a", "attrs.*.status".to_string()))
+        );
+        assert_eq!(
+            super::field_name("attrs.* :a"),
+            Ok(("a", "attrs.*".to_string()))
+        );
+        assert!(super::field_name("attrs.*status:a").is_err());
         for special_char in SPECIAL_CHARS.iter() {
             let query = &format!("\\{special_char}my\\{special_char}field:a");
+            // Escaped backslashes are kept, to be unescaped when splitting json paths.
+            let expected = if *special_char == '\\' {
+                "\\\\".to_string()
+            } else {
+                special_char.to_string()
+            };
             assert_eq!(
                 super::field_name(query),
-                Ok(("a", format!("{special_char}my{special_char}field")))
+                Ok(("a", format!("{expected}my{expected}field")))
             );
         }
     }
@@ -1721,6 +1815,66 @@ mod test {
         test_parse_query_to_ast_helper("foo:(/A.*/ OR /B.*/)", "(?\"foo\":/A.*/ ?\"foo\":/B.*/)");
     }
 
+    #[test]
+    fn test_parse_query_field_group() {
+        // comparison operators and ranges
+        test_parse_query_to_ast_helper(
+            "price:(>=10 AND <100)",
+            r#"(+"price":["10" TO "*"} +"price":{"*" TO "100"})"#,
+        );
+        test_parse_query_to_ast_helper(
+            "price:(> 10 <=100)",
+            r#"(*"price":{"10" TO "*"} *"price":{"*" TO "100"])"#,
+        );
+        test_parse_query_to_ast_helper(
+            "price:([1 TO 5] OR {7 TO 9})",
+            r#"(?"price":["1" TO "5"] ?"price":{"7" TO "9"})"#,
+        );
+        test_parse_query_to_ast_helper(
+            "date:(>=2020-01-01T00:00:00Z AND <2021-01-01T00:00:00Z)",
+            r#"(+"date":["2020-01-01T00:00:00Z" TO "*"} +"date":{"*" TO "2021-01-01T00:00:00Z"})"#,
+        );
+        // +/- modifiers
+        test_parse_query_to_ast_helper(
+            "tags:(-deprecated +stable)",
+            r#"(-"tags":deprecated +"tags":stable)"#,
+        );
+        test_parse_query_to_ast_helper(
+            "price:(+[1 TO 5] ->=3)",
+            r#"(+"price":["1" TO "5"] -"price":["3" TO "*"})"#,
+        );
+        test_parse_query_to_ast_helper("tags:(-*)", r#"(-$exists("tags"))"#);
+        // nested parentheses, mixing terms and ranges
+        test_parse_query_to_ast_helper(
+            "tags:(stable (-deprecated OR <b))",
+            r#"(*"tags":stable *(?(-"tags":deprecated) ?"tags":{"*" TO "b"}))"#,
+        );
+        // an explicit field takes precedence over the field of the group
+        test_parse_query_to_ast_helper(
+            "tags:(stable price:>10)",
+            r#"(*"tags":stable *"price":{"10" TO "*"})"#,
+        );
+        // comparison operators are still accepted without a group
+        test_parse_query_to_ast_helper(">=10", r#"["10" TO "*"}"#);
+    }
+
+    #[test]
+    fn test_parse_query_field_group_match_all() {
+        test_is_parse_err("*:*", "*:*");
+        test_is_parse_err("tags:(stable *:*)", r#"(*"tags":stable *"tags":*:*)"#);
+        let (_, errors) = parse_to_ast_lenient("tags:(stable *:*)");
+        assert_eq!(
+            errors,
+            vec![LenientError {
+                pos: 13,
+                message: "`*:*` cannot be used in a field group".to_string(),
+            }]
+        );
+        let (_, errors) = parse_to_ast_lenient("tags:(a (b *:*))");
+        assert_eq!(errors[0].pos, 11);
+        assert_eq!(errors[0].message, "`*:*` cannot be used in a field group");
+    }
+
     #[test]
     fn test_parse_query_all() {
         test_parse_query_to_ast_helper("*", "*");
@@ -1802,12 +1956,85 @@ mod test {
             r#"myfield:"hello\"happy\'tax""#,
             r#""myfield":"hello"happy'tax""#,
         );
+        // single quoted literals are raw, `''` being their only escape sequence.
         test_parse_query_to_ast_helper(
-            r#"myfield:'hello\"happy\'tax'"#,
-            r#""myfield":'hello"happy'tax'"#,
+            r#"myfield:'hello\"happy''tax'"#,
+            r#""myfield":'hello\"happy''tax'"#,
         );
-        // we don't process escape sequence for chars which don't require it
-        test_parse_query_to_ast_helper(r#"abc\*"#, r#"abc\*"#);
+        test_parse_query_to_ast_helper(r#"myfield:'C:\path\'"#, r#""myfield":'C:\path\'"#);
+        test_parse_query_to_ast_helper("myfield:''", r#""myfield":''"#);
+        // we don't process escape sequence for chars which can't be escaped
+        test_parse_query_to_ast_helper(r#"abc\e"#, r#"abc\e"#);
+        test_parse_query_to_ast_helper(r#""abc\e\\""#, r#""abc\e\""#);
+        test_parse_query_to_ast_helper(r#"abc\*"#, r#"abc*"#);
+    }
+
+    fn parse_phrase(query: &str) -> (Option<String>, String) {
+        let ast = parse_to_ast(query).unwrap().1;
+        let UserInputAst::Leaf(leaf) = ast else {
+            panic!("Expected a leaf, got {ast:?}");
+        };
+        let UserInputLeaf::Literal(literal) = *leaf else {
+            panic!("Expected a literal, got {leaf:?}");
+        };
+        (literal.field_name, literal.phrase)
+    }
+
+    #[test]
+    fn test_escape_table() {
+        for c in ESCAPABLE_CHARS.iter().copied().chain([' ', '\t']) {
+            let expected = format!("a{c}b");
+            assert_eq!(parse_phrase(&format!("a\\{c}b")).1, expected);
+            assert_eq!(parse_phrase(&format!("\"a\\{c}b\"")).1, expected);
+            let field_name = if c == '\\' {
+                // kept escaped, as the field name is a json path.
+                r"a\\b".to_string()
+            } else {
+                expected.clone()
+            };
+            assert_eq!(
+                parse_phrase(&format!("a\\{c}b:x")).0,
+                Some(field_name),
+                "escaping {c:?} in a field name"
+            );
+        }
+        // colons are escaped the same way in field names and values.
+        assert_eq!(
+            parse_phrase(r"a\:b:c\:d"),
+            (Some("a:b".to_string()), "c:d".to_string())
+        );
+        assert!(has_unescaped_colon(r"abc\\:def"));
+        assert!(!has_unescaped_colon(r"abc\:def"));
+    }
+
+    #[test]
+    fn test_single_quotes_round_trip() {
+        let values = [
+            "",
+            "a",
+            "hello world",
+            r"C:\\path\",
+            "it's",
+            "''",
+            r#"a"b\"#,
+            "+-&&||!(){}[]^\"~*?:\\/",
+            "tab\tand\nnewline",
+            "にんじん",
+        ];
+        for value in values {
+            let query = format!("field:'{}'", value.replace('\'', "''"));
+            assert_eq!(
+                parse_phrase(&query),
+                (Some("field".to_string()), value.to_string()),
+                "{query}"
+            );
+            let (ast, errors) = parse_to_ast_lenient(&query);
+            assert!(errors.is_empty(), "{query}: {errors:?}");
+            assert_eq!(
+                format!("{ast:?}"),
+                format!("\"field\":'{}'", value.replace('\'', "''"))
+            );
+        }
     }
 
     #[test]
@@ -1816,7 +2043,7 @@ mod test {
         test_parse_query_to_ast_helper(r#"'abc:def'"#, r#"'abc:def'"#);
         test_parse_query_to_ast_helper(r#"abc\:def"#, r#"abc:def"#);
         test_parse_query_to_ast_helper(r#""abc\:def""#, r#""abc:def""#);
-        test_parse_query_to_ast_helper(r#"'abc\:def'"#, r#"'abc:def'"#);
+        test_parse_query_to_ast_helper(r#"'abc\:def'"#, r#"'abc\:def'"#);
     }
 
     #[test]
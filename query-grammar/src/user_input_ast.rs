@@ -147,8 +147,7 @@ impl fmt::Debug for UserInputLiteral {
         }
         match self.delimiter {
             Delimiter::SingleQuotes => {
-                // TODO properly escape element (in case of \')
-                write!(formatter, "'{}'", self.phrase)?;
+                write!(formatter, "'{}'", self.phrase.replace('\'', "''"))?;
             }
             Delimiter::DoubleQuotes => {
                 // TODO properly escape element (in case of \")
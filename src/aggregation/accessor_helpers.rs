@@ -6,6 +6,7 @@ use columnar::{Column, ColumnType};
 
 use crate::aggregation::{f64_to_fastfield_u64, Key};
 use crate::index::SegmentReader;
+use crate::schema::Type;
 
 /// Get the missing value as internal u64 representation
 ///
@@ -103,3 +104,21 @@ pub(crate) fn get_all_ff_reader_or_empty(
     }
     Ok(ff_field_with_type)
 }
+
+/// Get the column of the field holding the number of documents each document stands for.
+///
+/// Returns `None` if the segment has no value for that field.
+pub(crate) fn get_doc_count_column(
+    reader: &SegmentReader,
+    field_name: &str,
+) -> crate::Result<Option<Column<u64>>> {
+    let schema = reader.schema();
+    let field = schema.get_field(field_name)?;
+    let field_type = schema.get_field_entry(field).field_type();
+    if field_type.value_type() != Type::U64 || !field_type.is_fast() {
+        return Err(crate::TantivyError::InvalidArgument(format!(
+            "Doc count field `{field_name}` is not a u64 fast field"
+        )));
+    }
+    reader.fast_fields().column_opt(field_name)
+}
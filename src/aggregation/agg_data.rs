@@ -5,8 +5,8 @@ use serde::Serialize;
 use tantivy_fst::Regex;
 
 use crate::aggregation::accessor_helpers::{
-    get_all_ff_reader_or_empty, get_dynamic_columns, get_ff_reader, get_missing_val_as_u64_lenient,
-    get_numeric_or_date_column_types,
+    get_all_ff_reader_or_empty, get_doc_count_column, get_dynamic_columns, get_ff_reader,
+    get_missing_val_as_u64_lenient, get_numeric_or_date_column_types,
 };
 use crate::aggregation::agg_req::{Aggregation, AggregationVariants, Aggregations};
 use crate::aggregation::bucket::{
@@ -23,11 +23,13 @@ use crate::aggregation::metric::{
     SegmentPercentilesCollector, StatsAggregation, StatsType, SumAggregation, TopHitsAggReqData,
     TopHitsSegmentCollector,
 };
+use crate::aggregation::pipeline::validate_pipeline_aggs;
 use crate::aggregation::segment_agg_result::{
     GenericSegmentAggregationResultsCollector, SegmentAggregationCollector,
 };
-use crate::aggregation::{f64_to_fastfield_u64, AggContextParams, Key};
-use crate::{SegmentOrdinal, SegmentReader};
+use crate::aggregation::{f64_to_fastfield_u64, AggContextParams, AggregationError, Key};
+use crate::error::UNEXPECTED_STATE;
+use crate::{DocId, SegmentOrdinal, SegmentReader};
 
 #[derive(Default)]
 /// Datastructure holding all request data for executing aggregations on a segment.
@@ -37,6 +39,16 @@ pub struct AggregationsSegmentCtx {
     pub per_request: PerRequestAggSegCtx,
     pub context: AggContextParams,
     pub column_block_accessor: ColumnBlockAccessor<u64>,
+    /// Column of the doc count field, if one is configured and the segment has values for it.
+    pub doc_count_column: Option<Column<u64>>,
+}
+
+/// Returns the number of documents `doc` stands for, given the column of the doc count field.
+///
+/// Documents without a value count as 1.
+#[inline]
+pub(crate) fn doc_count_weight(doc_count_column: &Column<u64>, doc: DocId) -> u64 {
+    doc_count_column.first(doc).unwrap_or(1)
 }
 
 impl AggregationsSegmentCtx {
@@ -524,10 +536,15 @@ pub(crate) fn build_aggregations_data_from_req(
     segment_ordinal: SegmentOrdinal,
     context: AggContextParams,
 ) -> crate::Result<AggregationsSegmentCtx> {
+    let doc_count_column = match context.doc_count_field.as_deref() {
+        Some(field_name) => get_doc_count_column(reader, field_name)?,
+        None => None,
+    };
     let mut data = AggregationsSegmentCtx {
         per_request: Default::default(),
         context,
         column_block_accessor: ColumnBlockAccessor::default(),
+        doc_count_column,
     };
 
     for (name, agg) in aggs.iter() {
@@ -585,7 +602,8 @@ fn build_nodes(
                 },
                 offset: 0.0,
             });
-            let children = build_children(&req.sub_aggregation, reader, segment_ordinal, data)?;
+            let children =
+                build_histogram_children(&req.sub_aggregation, reader, segment_ordinal, data)?;
             Ok(vec![AggRefNode {
                 kind: AggKind::Histogram,
                 idx_in_req_data,
@@ -610,7 +628,8 @@ fn build_nodes(
                 },
                 offset: 0.0,
             });
-            let children = build_children(&req.sub_aggregation, reader, segment_ordinal, data)?;
+            let children =
+                build_histogram_children(&req.sub_aggregation, reader, segment_ordinal, data)?;
             Ok(vec![AggRefNode {
                 kind: AggKind::DateHistogram,
                 idx_in_req_data,
@@ -670,11 +689,10 @@ fn build_nodes(
                 Sum(_) => StatsType::Sum,
                 Count(_) => StatsType::Count,
                 _ => {
-                    return Err(crate::TantivyError::InvalidArgument(
-                        "Internal error: unexpected aggregation type in metric aggregation \
+                    return Err(crate::TantivyError::InvalidArgument(format!(
+                        "{UNEXPECTED_STATE} unexpected aggregation type in metric aggregation \
                          handling."
-                            .to_string(),
-                    ))
+                    )))
                 }
             };
             let (accessor, field_type) = get_ff_reader(reader, field, allowed_column_types)?;
@@ -799,6 +817,12 @@ fn build_nodes(
                 children,
             }])
         }
+        Derivative(_) | CumulativeSum(_) | MovingAverage(_) => Err(
+            crate::TantivyError::AggregationError(AggregationError::InvalidRequest(format!(
+                "pipeline aggregation {agg_name:?} must be a direct sub-aggregation of a \
+                 histogram or date_histogram aggregation"
+            ))),
+        ),
     }
 }
 
@@ -851,6 +875,32 @@ fn build_children(
     Ok(children)
 }
 
+/// Builds the children of a histogram. Pipeline aggregations are validated, but have no
+/// segment collector: they are computed on the final buckets.
+fn build_histogram_children(
+    aggs: &Aggregations,
+    reader: &SegmentReader,
+    segment_ordinal: SegmentOrdinal,
+    data: &mut AggregationsSegmentCtx,
+) -> crate::Result<Vec<AggRefNode>> {
+    validate_pipeline_aggs(aggs)?;
+    let mut children = Vec::new();
+    for (name, agg) in aggs.iter() {
+        if agg.agg.as_pipeline().is_some() {
+            continue;
+        }
+        children.extend(build_nodes(
+            name,
+            agg,
+            reader,
+            segment_ordinal,
+            data,
+            false,
+        )?);
+    }
+    Ok(children)
+}
+
 fn get_term_agg_accessors(
     reader: &SegmentReader,
     field_name: &str,
@@ -40,6 +40,10 @@ use super::metric::{
     MaxAggregation, MinAggregation, PercentilesAggregationReq, StatsAggregation, SumAggregation,
     TopHitsAggregationReq,
 };
+use super::pipeline::{
+    CumulativeSumAggregation, DerivativeAggregation, MovingAverageAggregation, PipelineKind,
+    PipelineReq,
+};
 
 /// The top-level aggregation request structure, which contains [`Aggregation`] and their user
 /// defined names. It is also used in buckets aggregations to define sub-aggregations.
@@ -172,6 +176,17 @@ pub enum AggregationVariants {
     /// Computes an estimate of the number of unique values
     #[serde(rename = "cardinality")]
     Cardinality(CardinalityAggregationReq),
+
+    // Pipeline aggregation types
+    /// Computes the difference between the values of consecutive histogram buckets.
+    #[serde(rename = "derivative")]
+    Derivative(DerivativeAggregation),
+    /// Computes the running total of a value over histogram buckets.
+    #[serde(rename = "cumulative_sum")]
+    CumulativeSum(CumulativeSumAggregation),
+    /// Computes the moving average of a value over histogram buckets.
+    #[serde(rename = "moving_avg")]
+    MovingAverage(MovingAverageAggregation),
 }
 
 impl AggregationVariants {
@@ -198,6 +213,9 @@ impl AggregationVariants {
             AggregationVariants::Percentiles(per) => vec![per.field_name()],
             AggregationVariants::TopHits(top_hits) => top_hits.field_names(),
             AggregationVariants::Cardinality(per) => vec![per.field_name()],
+            AggregationVariants::Derivative(_)
+            | AggregationVariants::CumulativeSum(_)
+            | AggregationVariants::MovingAverage(_) => vec![],
         }
     }
 
@@ -228,6 +246,28 @@ impl AggregationVariants {
             _ => None,
         }
     }
+    pub(crate) fn as_pipeline(&self) -> Option<PipelineReq<'_>> {
+        match &self {
+            AggregationVariants::Derivative(derivative) => Some(PipelineReq {
+                kind: PipelineKind::Derivative,
+                buckets_path: &derivative.buckets_path,
+                gap_policy: derivative.gap_policy,
+            }),
+            AggregationVariants::CumulativeSum(cumulative_sum) => Some(PipelineReq {
+                kind: PipelineKind::CumulativeSum,
+                buckets_path: &cumulative_sum.buckets_path,
+                gap_policy: cumulative_sum.gap_policy,
+            }),
+            AggregationVariants::MovingAverage(moving_avg) => Some(PipelineReq {
+                kind: PipelineKind::MovingAverage {
+                    window: moving_avg.window,
+                },
+                buckets_path: &moving_avg.buckets_path,
+                gap_policy: moving_avg.gap_policy,
+            }),
+            _ => None,
+        }
+    }
     pub(crate) fn as_percentile(&self) -> Option<&PercentilesAggregationReq> {
         match &self {
             AggregationVariants::Percentiles(percentile_req) => Some(percentile_req),
@@ -22,6 +22,56 @@ use crate::TantivyError;
 pub struct AggregationResults(pub FxHashMap<String, AggregationResult>);
 
 impl AggregationResults {
+    /// Returns the result of the aggregation `name`.
+    pub fn get(&self, name: &str) -> crate::Result<&AggregationResult> {
+        self.0.get(name).ok_or_else(|| {
+            TantivyError::AggregationError(AggregationError::MissingAggregation(name.to_string()))
+        })
+    }
+
+    /// Returns the result of the terms aggregation `name`.
+    ///
+    /// Returns an error if there is no such aggregation, or if it is not a terms aggregation.
+    pub fn get_terms(&self, name: &str) -> crate::Result<TermsResult<'_>> {
+        match self.get(name)? {
+            AggregationResult::BucketResult(BucketResult::Terms {
+                buckets,
+                sum_other_doc_count,
+                doc_count_error_upper_bound,
+            }) => Ok(TermsResult {
+                buckets,
+                sum_other_doc_count: *sum_other_doc_count,
+                doc_count_error_upper_bound: *doc_count_error_upper_bound,
+            }),
+            agg_result => Err(unexpected_aggregation_kind(name, "terms", agg_result)),
+        }
+    }
+
+    /// Returns the value of the single value metric aggregation `name`, i.e. of an `avg`,
+    /// `value_count`, `max`, `min`, `sum` or `cardinality` aggregation.
+    ///
+    /// The value is `None` if the aggregation did not see any value.
+    /// Returns an error if there is no such aggregation, or if it is not a single value metric
+    /// aggregation.
+    pub fn get_metric_f64(&self, name: &str) -> crate::Result<Option<f64>> {
+        match self.get(name)? {
+            AggregationResult::MetricResult(
+                MetricResult::Average(single_metric)
+                | MetricResult::Count(single_metric)
+                | MetricResult::Max(single_metric)
+                | MetricResult::Min(single_metric)
+                | MetricResult::Sum(single_metric)
+                | MetricResult::Cardinality(single_metric)
+                | MetricResult::Pipeline(single_metric),
+            ) => Ok(single_metric.value),
+            agg_result => Err(unexpected_aggregation_kind(
+                name,
+                "single value metric",
+                agg_result,
+            )),
+        }
+    }
+
     pub(crate) fn get_bucket_count(&self) -> u64 {
         self.0
             .values()
@@ -45,6 +95,35 @@ impl AggregationResults {
     }
 }
 
+fn unexpected_aggregation_kind(
+    name: &str,
+    requested: &'static str,
+    agg_result: &AggregationResult,
+) -> TantivyError {
+    TantivyError::AggregationError(AggregationError::UnexpectedAggregationKind {
+        name: name.to_string(),
+        requested,
+        actual: agg_result.kind_name(),
+    })
+}
+
+/// The result of a terms aggregation, returned by [`AggregationResults::get_terms`].
+#[derive(Clone, Copy, Debug)]
+pub struct TermsResult<'a> {
+    buckets: &'a [BucketEntry],
+    /// The number of documents that didn’t make it into to TOP N due to shard_size or size
+    pub sum_other_doc_count: u64,
+    /// The upper bound error for the doc count of each term.
+    pub doc_count_error_upper_bound: Option<u64>,
+}
+
+impl<'a> TermsResult<'a> {
+    /// The buckets, in the order of the request.
+    pub fn buckets(&self) -> &'a [BucketEntry] {
+        self.buckets
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 /// An aggregation is either a bucket or a metric.
@@ -56,6 +135,35 @@ pub enum AggregationResult {
 }
 
 impl AggregationResult {
+    /// Name of the kind of aggregation, as in the aggregation request.
+    ///
+    /// Results deserialized from json cannot tell apart the single value metrics, nor the
+    /// histograms and date histograms.
+    fn kind_name(&self) -> &'static str {
+        match self {
+            AggregationResult::BucketResult(bucket_result) => match bucket_result {
+                BucketResult::Range { .. } => "range",
+                BucketResult::Histogram { .. } => "histogram",
+                BucketResult::Terms { .. } => "terms",
+                BucketResult::Filter(_) => "filter",
+                BucketResult::Composite { .. } => "composite",
+            },
+            AggregationResult::MetricResult(metric_result) => match metric_result {
+                MetricResult::Average(_) => "avg",
+                MetricResult::Count(_) => "value_count",
+                MetricResult::Max(_) => "max",
+                MetricResult::Min(_) => "min",
+                MetricResult::Stats(_) => "stats",
+                MetricResult::ExtendedStats(_) => "extended_stats",
+                MetricResult::Sum(_) => "sum",
+                MetricResult::Percentiles(_) => "percentiles",
+                MetricResult::TopHits(_) => "top_hits",
+                MetricResult::Cardinality(_) => "cardinality",
+                MetricResult::Pipeline(_) => "pipeline",
+            },
+        }
+    }
+
     pub(crate) fn get_bucket_count(&self) -> u64 {
         match self {
             AggregationResult::BucketResult(bucket) => bucket.get_bucket_count(),
@@ -103,6 +211,8 @@ pub enum MetricResult {
     TopHits(TopHitsMetricResult),
     /// Cardinality metric result
     Cardinality(SingleMetricResult),
+    /// Pipeline aggregation result, e.g. of a derivative aggregation.
+    Pipeline(SingleMetricResult),
 }
 
 impl MetricResult {
@@ -122,6 +232,7 @@ impl MetricResult {
                 AggregationError::InvalidRequest("top_hits can't be used to order".to_string()),
             )),
             MetricResult::Cardinality(card) => Ok(card.value),
+            MetricResult::Pipeline(pipeline) => Ok(pipeline.value),
         }
     }
 }
@@ -260,6 +371,30 @@ impl BucketEntry {
     pub(crate) fn get_bucket_count(&self) -> u64 {
         1 + self.sub_aggregation.get_bucket_count()
     }
+
+    /// Returns the key if it is a string, e.g. for a terms aggregation on a text field.
+    pub fn key_as_str(&self) -> Option<&str> {
+        match &self.key {
+            Key::Str(key) => Some(key),
+            Key::I64(_) | Key::U64(_) | Key::F64(_) => None,
+        }
+    }
+
+    /// Returns the key if it is numerical, e.g. for a histogram or a terms aggregation on a
+    /// numerical field.
+    pub fn key_as_f64(&self) -> Option<f64> {
+        match self.key {
+            Key::Str(_) => None,
+            Key::I64(key) => Some(key as f64),
+            Key::U64(key) => Some(key as f64),
+            Key::F64(key) => Some(key),
+        }
+    }
+
+    /// Returns the results of the sub-aggregations in this bucket.
+    pub fn sub(&self) -> &AggregationResults {
+        &self.sub_aggregation
+    }
 }
 impl GetDocCount for &BucketEntry {
     fn doc_count(&self) -> u64 {
@@ -5,10 +5,10 @@ use crate::aggregation::agg_result::AggregationResults;
 use crate::aggregation::collector::AggregationCollector;
 use crate::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use crate::aggregation::tests::{get_test_index_2_segments, get_test_index_from_values_and_terms};
-use crate::aggregation::DistributedAggregationCollector;
+use crate::aggregation::{AggContextParams, DistributedAggregationCollector};
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
 use crate::query::{AllQuery, TermQuery};
-use crate::schema::{IndexRecordOption, Schema, FAST};
+use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
 use crate::{Index, IndexWriter, Term};
 
 // The following tests ensure that each bucket aggregation type correctly functions as a
@@ -732,6 +732,97 @@ fn test_aggregation_level1_simple() -> crate::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_aggregation_results_typed_access() -> crate::Result<()> {
+    let index = get_test_index_2_segments(false)?;
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "by_text": {
+            "terms": {"field": "text", "order": {"_key": "asc"}},
+            "aggs": {
+                "avg_score": {"avg": {"field": "score"}},
+                "by_score": {
+                    "terms": {"field": "score", "order": {"_key": "asc"}},
+                    "aggs": {"max_score_f64": {"max": {"field": "score_f64"}}}
+                }
+            }
+        },
+        "avg_score": {"avg": {"field": "score"}},
+        "score_stats": {"stats": {"field": "score"}},
+        "score_ranges": {"range": {"field": "score", "ranges": [{"to": 5.0}]}}
+    }))
+    .unwrap();
+    let collector = get_collector(agg_req);
+    let searcher = index.reader()?.searcher();
+    let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+    let res: Value = serde_json::to_value(&agg_res)?;
+
+    assert_eq!(
+        agg_res.get_metric_f64("avg_score")?,
+        res["avg_score"]["value"].as_f64()
+    );
+    let by_text = agg_res.get_terms("by_text")?;
+    assert_eq!(
+        by_text.sum_other_doc_count,
+        res["by_text"]["sum_other_doc_count"].as_u64().unwrap()
+    );
+    let keys: Vec<&str> = by_text
+        .buckets()
+        .iter()
+        .flat_map(|bucket| bucket.key_as_str())
+        .collect();
+    assert_eq!(keys, ["cool", "nohit"]);
+    for (bucket, bucket_json) in by_text
+        .buckets()
+        .iter()
+        .zip(res["by_text"]["buckets"].as_array().unwrap())
+    {
+        assert_eq!(bucket.key_as_f64(), None);
+        assert_eq!(bucket.doc_count, bucket_json["doc_count"].as_u64().unwrap());
+        assert_eq!(
+            bucket.sub().get_metric_f64("avg_score")?,
+            bucket_json["avg_score"]["value"].as_f64()
+        );
+        let by_score = bucket.sub().get_terms("by_score")?;
+        let by_score_json = bucket_json["by_score"]["buckets"].as_array().unwrap();
+        assert_eq!(by_score.buckets().len(), by_score_json.len());
+        for (score_bucket, score_bucket_json) in by_score.buckets().iter().zip(by_score_json) {
+            let score = score_bucket.key_as_f64().unwrap();
+            assert_eq!(score_bucket.key_as_str(), None);
+            assert_eq!(Some(score), score_bucket_json["key"].as_f64());
+            assert_eq!(
+                score_bucket.sub().get_metric_f64("max_score_f64")?,
+                score_bucket_json["max_score_f64"]["value"].as_f64()
+            );
+        }
+    }
+
+    let error_message = |result: crate::Result<()>| result.unwrap_err().to_string();
+    assert_eq!(
+        error_message(agg_res.get_terms("avg_score").map(|_| ())),
+        "Expected aggregation \"avg_score\" to be of kind terms, but it is of kind avg"
+    );
+    assert_eq!(
+        error_message(agg_res.get_terms("score_ranges").map(|_| ())),
+        "Expected aggregation \"score_ranges\" to be of kind terms, but it is of kind range"
+    );
+    assert_eq!(
+        error_message(agg_res.get_metric_f64("score_stats").map(|_| ())),
+        "Expected aggregation \"score_stats\" to be of kind single value metric, but it is of \
+         kind stats"
+    );
+    assert_eq!(
+        error_message(agg_res.get_metric_f64("by_text").map(|_| ())),
+        "Expected aggregation \"by_text\" to be of kind single value metric, but it is of kind \
+         terms"
+    );
+    // Sub-aggregations are only accessible from their bucket.
+    assert_eq!(
+        error_message(agg_res.get_terms("by_score").map(|_| ())),
+        "Aggregation \"by_score\" not found in the aggregation results"
+    );
+    Ok(())
+}
+
 #[test]
 fn test_aggregation_level1() -> crate::Result<()> {
     let index = get_test_index_2_segments(true)?;
@@ -1436,3 +1527,292 @@ fn test_aggregation_on_json_object_mixed_numerical_segments() {
         )
     );
 }
+
+fn doc_count_test_index(docs: &[(&str, f64, Option<u64>)]) -> crate::Result<Index> {
+    let mut schema_builder = Schema::builder();
+    let category = schema_builder.add_text_field("category", STRING | FAST);
+    let value = schema_builder.add_f64_field("value", FAST);
+    let doc_count = schema_builder.add_u64_field("_doc_count", FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    for (ord, &(category_val, value_val, doc_count_val)) in docs.iter().enumerate() {
+        let mut doc = doc!(category => category_val, value => value_val);
+        if let Some(doc_count_val) = doc_count_val {
+            doc.add_u64(doc_count, doc_count_val);
+        }
+        index_writer.add_document(doc)?;
+        if ord % 3 == 2 {
+            index_writer.commit()?;
+        }
+    }
+    index_writer.commit()?;
+    Ok(index)
+}
+
+#[test]
+fn test_aggregation_doc_count_field_rolled_up_vs_raw() -> crate::Result<()> {
+    let rolled_up = [
+        ("a", 1.0, Some(3)),
+        ("a", 7.0, Some(1)),
+        ("b", 2.5, Some(4)),
+        ("b", 12.0, None),
+        ("c", 5.0, Some(2)),
+    ];
+    let raw: Vec<(&str, f64, Option<u64>)> = rolled_up
+        .iter()
+        .flat_map(|&(category, value, doc_count)| {
+            std::iter::repeat_n((category, value, None), doc_count.unwrap_or(1) as usize)
+        })
+        .collect();
+    let rolled_up_index = doc_count_test_index(&rolled_up)?;
+    let raw_index = doc_count_test_index(&raw)?;
+
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "categories": {
+            "terms": { "field": "category" },
+            "aggs": {
+                "avg_value": { "avg": { "field": "value" } },
+                "sum_value": { "sum": { "field": "value" } },
+                "count_value": { "value_count": { "field": "value" } },
+                "histo": { "histogram": { "field": "value", "interval": 5.0 } },
+            }
+        },
+        "categories_no_sub_agg": { "terms": { "field": "category" } },
+        "histo": {
+            "histogram": { "field": "value", "interval": 5.0 },
+            "aggs": { "stats": { "stats": { "field": "value" } } }
+        },
+        "stats": { "stats": { "field": "value" } },
+    }))
+    .unwrap();
+
+    let context = AggContextParams::default().with_doc_count_field("_doc_count");
+    let collector = AggregationCollector::from_aggs(agg_req.clone(), context);
+    let rolled_up_res = rolled_up_index
+        .reader()?
+        .searcher()
+        .search(&AllQuery, &collector)?;
+    let raw_res = raw_index
+        .reader()?
+        .searcher()
+        .search(&AllQuery, &get_collector(agg_req))?;
+
+    let rolled_up_res = serde_json::to_value(rolled_up_res)?;
+    assert_eq!(rolled_up_res, serde_json::to_value(raw_res)?);
+    assert_eq!(rolled_up_res["stats"]["count"], 11);
+    assert_eq!(rolled_up_res["categories"]["buckets"][0]["key"], "b");
+    assert_eq!(rolled_up_res["categories"]["buckets"][0]["doc_count"], 5);
+    assert_eq!(
+        rolled_up_res["categories"]["buckets"][1]["avg_value"]["value"],
+        2.5
+    );
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_doc_count_field_invalid() -> crate::Result<()> {
+    let index = doc_count_test_index(&[("a", 1.0, Some(2))])?;
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "stats": { "stats": { "field": "value" } },
+    }))
+    .unwrap();
+    let searcher = index.reader()?.searcher();
+    for field_name in ["value", "category", "does_not_exist"] {
+        let context = AggContextParams::default().with_doc_count_field(field_name);
+        let collector = AggregationCollector::from_aggs(agg_req.clone(), context);
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_collect_async() -> crate::Result<()> {
+    use std::future::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    /// Counts the number of times the future yields.
+    #[derive(Default)]
+    struct CountingWaker {
+        num_wakes: AtomicUsize,
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.num_wakes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> (F::Output, usize) {
+        let counting_waker = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counting_waker.clone());
+        let mut context = Context::from_waker(&waker);
+        let mut future = std::pin::pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return (output, counting_waker.num_wakes.load(Ordering::Relaxed));
+            }
+        }
+    }
+
+    fn assert_send<F: Future + Send>(future: F) -> F {
+        future
+    }
+
+    let segment_and_values: Vec<Vec<(f64, String)>> = (0..5)
+        .map(|segment_ord| {
+            (0..10)
+                .map(|val| ((segment_ord * 10 + val) as f64, format!("term{}", val % 3)))
+                .collect()
+        })
+        .collect();
+    let index = get_test_index_from_values_and_terms(false, &segment_and_values)?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 5);
+
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "terms": {
+            "terms": { "field": "string_id" },
+            "aggs": { "avg_score": { "avg": { "field": "score" } } }
+        },
+        "histogram": { "histogram": { "field": "score", "interval": 10.0 } }
+    }))
+    .unwrap();
+    let term_query = TermQuery::new(
+        Term::from_field_text(index.schema().get_field("string_id")?, "term1"),
+        IndexRecordOption::Basic,
+    );
+    let collector = AggregationCollector::from_aggs(agg_req.clone(), Default::default());
+    for query in [&AllQuery as &dyn crate::query::Query, &term_query] {
+        let sync_res = searcher.search(query, &collector)?;
+        let (async_res, num_yields) =
+            block_on(assert_send(collector.collect_async(&searcher, query)));
+        assert_eq!(
+            serde_json::to_value(async_res?)?,
+            serde_json::to_value(sync_res)?
+        );
+        assert_eq!(num_yields, 5);
+    }
+
+    let distributed_collector =
+        DistributedAggregationCollector::from_aggs(agg_req.clone(), Default::default());
+    let (intermediate_res, num_yields) =
+        block_on(distributed_collector.collect_async(&searcher, &AllQuery));
+    assert_eq!(num_yields, 5);
+    let res = intermediate_res?.into_final_result(agg_req, Default::default())?;
+    assert_eq!(
+        serde_json::to_value(res)?,
+        serde_json::to_value(searcher.search(&AllQuery, &collector)?)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_pipeline_on_histogram() -> crate::Result<()> {
+    // Buckets: [0, 10) -> 1, 2; [10, 20) -> 11, 12, 13; [20, 30) is empty; [30, 40) -> 31;
+    // [40, 50) -> 41, 45
+    let values = [1.0, 11.0, 31.0, 2.0, 12.0, 41.0, 13.0, 45.0];
+    let docs: Vec<(&str, f64, Option<u64>)> = values.iter().map(|&val| ("a", val, None)).collect();
+    let index = doc_count_test_index(&docs)?;
+
+    let agg_req: Aggregations = serde_json::from_value(json!({
+        "histo": {
+            "histogram": { "field": "value", "interval": 10.0 },
+            "aggs": {
+                "avg": { "avg": { "field": "value" } },
+                "stats": { "stats": { "field": "value" } },
+                "count_cumsum": { "cumulative_sum": { "buckets_path": "_count" } },
+                "count_deriv": { "derivative": { "buckets_path": "_count" } },
+                "max_deriv": { "derivative": { "buckets_path": "stats.max" } },
+                "avg_deriv": { "derivative": { "buckets_path": "avg" } },
+                "avg_deriv_zeros": {
+                    "derivative": { "buckets_path": "avg", "gap_policy": "insert_zeros" }
+                },
+                "avg_cumsum": { "cumulative_sum": { "buckets_path": "avg" } },
+                "avg_movavg": { "moving_avg": { "buckets_path": "avg", "window": 2 } },
+                "avg_movavg_zeros": {
+                    "moving_avg": {
+                        "buckets_path": "avg",
+                        "window": 2,
+                        "gap_policy": "insert_zeros"
+                    }
+                },
+            }
+        }
+    }))
+    .unwrap();
+
+    let searcher = index.reader()?.searcher();
+    let agg_res: AggregationResults = searcher.search(&AllQuery, &get_collector(agg_req))?;
+    let res: Value = serde_json::to_value(agg_res)?;
+    let series = |name: &str| -> Value {
+        res["histo"]["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket[name]["value"].clone())
+            .collect()
+    };
+
+    assert_eq!(res["histo"]["buckets"].as_array().unwrap().len(), 5);
+    assert_eq!(series("count_cumsum"), json!([2.0, 5.0, 5.0, 6.0, 8.0]));
+    assert_eq!(series("count_deriv"), json!([null, 1.0, -3.0, 1.0, 1.0]));
+    assert_eq!(series("max_deriv"), json!([null, 11.0, null, 18.0, 14.0]));
+    assert_eq!(series("avg_deriv"), json!([null, 10.5, null, 19.0, 12.0]));
+    assert_eq!(
+        series("avg_deriv_zeros"),
+        json!([null, 10.5, -12.0, 31.0, 12.0])
+    );
+    assert_eq!(series("avg_cumsum"), json!([1.5, 13.5, null, 44.5, 87.5]));
+    assert_eq!(series("avg_movavg"), json!([null, 1.5, null, 6.75, 21.5]));
+    assert_eq!(
+        series("avg_movavg_zeros"),
+        json!([null, 1.5, 6.75, 6.0, 15.5])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_aggregation_pipeline_invalid_requests() -> crate::Result<()> {
+    let index = doc_count_test_index(&[("a", 1.0, None)])?;
+    let searcher = index.reader()?.searcher();
+    let search_err = |agg_req: Value| {
+        let agg_req: Aggregations = serde_json::from_value(agg_req).unwrap();
+        let err = searcher
+            .search(&AllQuery, &get_collector(agg_req))
+            .unwrap_err();
+        format!("{err:?}")
+    };
+
+    let err = search_err(json!({
+        "deriv": { "derivative": { "buckets_path": "_count" } }
+    }));
+    assert!(err.contains("must be a direct sub-aggregation of a histogram"));
+    let err = search_err(json!({
+        "terms": {
+            "terms": { "field": "category" },
+            "aggs": { "deriv": { "derivative": { "buckets_path": "_count" } } }
+        }
+    }));
+    assert!(err.contains("must be a direct sub-aggregation of a histogram"));
+    let err = search_err(json!({
+        "histo": {
+            "histogram": { "field": "value", "interval": 10.0 },
+            "aggs": { "deriv": { "derivative": { "buckets_path": "does_not_exist" } } }
+        }
+    }));
+    assert!(err.contains("must be `_count` or refer to a sibling numeric metric aggregation"));
+    let err = search_err(json!({
+        "histo": {
+            "histogram": { "field": "value", "interval": 10.0 },
+            "aggs": { "movavg": { "moving_avg": { "buckets_path": "_count", "window": 0 } } }
+        }
+    }));
+    assert!(err.contains("must be greater than 0"));
+    Ok(())
+}
@@ -302,7 +302,9 @@ impl ToTypePaginationOrder for ColumnType {
         match self {
             ColumnType::Bool => ColumnPaginationOrder::Bool,
             ColumnType::Str => ColumnPaginationOrder::Str,
-            ColumnType::F64 | ColumnType::I64 | ColumnType::U64 => ColumnPaginationOrder::Numeric,
+            ColumnType::F64 | ColumnType::F32 | ColumnType::I64 | ColumnType::U64 => {
+                ColumnPaginationOrder::Numeric
+            }
             ColumnType::IpAddr => ColumnPaginationOrder::IpAddr,
             ColumnType::DateTime => ColumnPaginationOrder::DateTime,
             ColumnType::Bytes => panic!("unsupported"),
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use tantivy_bitpacker::minmax;
 
 use crate::aggregation::agg_data::{
-    build_segment_agg_collectors, AggRefNode, AggregationsSegmentCtx,
+    build_segment_agg_collectors, doc_count_weight, AggRefNode, AggregationsSegmentCtx,
 };
 use crate::aggregation::agg_req::Aggregations;
 use crate::aggregation::agg_result::BucketEntry;
@@ -15,6 +15,7 @@ use crate::aggregation::intermediate_agg_result::{
     IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
     IntermediateHistogramBucketEntry,
 };
+use crate::aggregation::pipeline::apply_pipeline_aggs;
 use crate::aggregation::segment_agg_result::{BucketIdProvider, SegmentAggregationCollector};
 use crate::aggregation::*;
 use crate::TantivyError;
@@ -335,6 +336,7 @@ impl SegmentAggregationCollector for SegmentHistogramCollector {
         agg_data
             .column_block_accessor
             .fetch_block(docs, &req.accessor);
+        let doc_count_column = agg_data.doc_count_column.as_ref();
         for (doc, val) in agg_data
             .column_block_accessor
             .iter_docid_vals(docs, &req.accessor)
@@ -350,7 +352,8 @@ impl SegmentAggregationCollector for SegmentHistogramCollector {
                         bucket_id: self.bucket_id_provider.next_bucket_id(),
                     }
                 });
-                bucket.doc_count += 1;
+                bucket.doc_count +=
+                    doc_count_column.map_or(1, |column| doc_count_weight(column, doc));
                 if let Some(sub_agg) = &mut self.sub_agg {
                     sub_agg.push(bucket.bucket_id, doc);
                 }
@@ -557,6 +560,8 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
             .collect::<crate::Result<Vec<_>>>()?
     };
 
+    apply_pipeline_aggs(&mut buckets, sub_aggregation)?;
+
     // If we have a date type on the histogram buckets, we add the `key_as_string` field as rfc339
     // and normalize from nanoseconds to milliseconds
     if is_date_agg {
@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{CustomOrder, Order, OrderTarget};
 use crate::aggregation::agg_data::{
-    build_segment_agg_collectors, AggRefNode, AggregationsSegmentCtx,
+    build_segment_agg_collectors, doc_count_weight, AggRefNode, AggregationsSegmentCtx,
 };
 use crate::aggregation::agg_limits::MemoryConsumption;
 use crate::aggregation::agg_req::Aggregations;
@@ -464,8 +464,13 @@ trait TermAggregationMap: Clone + Debug + 'static {
     /// Estimate the memory consumption of this struct in bytes.
     fn get_memory_consumption(&self) -> usize;
 
-    /// Increments the count and returns the bucket_id associated to a given term_id.
-    fn term_entry(&mut self, term_id: u64, bucket_id_provider: &mut BucketIdProvider) -> BucketId;
+    /// Adds `count` to the count and returns the bucket_id associated to a given term_id.
+    fn term_entry(
+        &mut self,
+        term_id: u64,
+        count: u32,
+        bucket_id_provider: &mut BucketIdProvider,
+    ) -> BucketId;
 
     /// Returns the term aggregation as a vector of (term_id, bucket) pairs,
     /// in any order.
@@ -563,7 +568,12 @@ impl TermAggregationMap for PagedTermMap {
     }
 
     #[inline]
-    fn term_entry(&mut self, term_id: u64, bucket_id_provider: &mut BucketIdProvider) -> BucketId {
+    fn term_entry(
+        &mut self,
+        term_id: u64,
+        count: u32,
+        bucket_id_provider: &mut BucketIdProvider,
+    ) -> BucketId {
         let term_id = term_id as usize;
         let page_idx = term_id >> PAGE_SHIFT;
         let offset = term_id & PAGE_MASK;
@@ -581,12 +591,12 @@ impl TermAggregationMap for PagedTermMap {
 
         if page.is_set(offset) {
             let bucket = &mut page.data[offset];
-            bucket.count += 1;
+            bucket.count = bucket.count.saturating_add(count);
             bucket.bucket_id
         } else {
             let new_id = bucket_id_provider.next_bucket_id();
             page.data[offset] = Bucket {
-                count: 1,
+                count,
                 bucket_id: new_id,
             };
             page.set_present(offset);
@@ -631,12 +641,17 @@ impl TermAggregationMap for HashMapTermBuckets {
     }
 
     #[inline(always)]
-    fn term_entry(&mut self, term_id: u64, bucket_id_provider: &mut BucketIdProvider) -> BucketId {
+    fn term_entry(
+        &mut self,
+        term_id: u64,
+        count: u32,
+        bucket_id_provider: &mut BucketIdProvider,
+    ) -> BucketId {
         let bucket = self
             .bucket_map
             .entry(term_id)
             .or_insert_with(|| Bucket::new(bucket_id_provider.next_bucket_id()));
-        bucket.count += 1;
+        bucket.count = bucket.count.saturating_add(count);
         bucket.bucket_id
     }
 
@@ -667,7 +682,12 @@ impl TermAggregationMap for VecTermBucketsNoAgg {
 
     /// Add an occurrence of the given term id.
     #[inline(always)]
-    fn term_entry(&mut self, term_id: u64, _bucket_id_provider: &mut BucketIdProvider) -> BucketId {
+    fn term_entry(
+        &mut self,
+        term_id: u64,
+        count: u32,
+        _bucket_id_provider: &mut BucketIdProvider,
+    ) -> BucketId {
         let term_id_usize = term_id as usize;
         debug_assert!(
             term_id_usize < self.buckets.len(),
@@ -675,8 +695,8 @@ impl TermAggregationMap for VecTermBucketsNoAgg {
             term_id,
             self.buckets.len()
         );
-        let count = unsafe { self.buckets.get_unchecked_mut(term_id_usize) };
-        *count += 1;
+        let term_count = unsafe { self.buckets.get_unchecked_mut(term_id_usize) };
+        *term_count = term_count.saturating_add(count);
         0 // unused
     }
 
@@ -724,7 +744,12 @@ impl TermAggregationMap for VecTermBuckets {
 
     /// Add an occurrence of the given term id.
     #[inline(always)]
-    fn term_entry(&mut self, term_id: u64, _bucket_id_provider: &mut BucketIdProvider) -> BucketId {
+    fn term_entry(
+        &mut self,
+        term_id: u64,
+        count: u32,
+        _bucket_id_provider: &mut BucketIdProvider,
+    ) -> BucketId {
         let term_id_usize = term_id as usize;
         debug_assert!(
             term_id_usize < self.buckets.len(),
@@ -733,7 +758,7 @@ impl TermAggregationMap for VecTermBuckets {
             self.buckets.len()
         );
         let bucket = unsafe { self.buckets.get_unchecked_mut(term_id_usize) };
-        bucket.count += 1;
+        bucket.count = bucket.count.saturating_add(count);
         bucket.bucket_id
     }
 
@@ -821,7 +846,31 @@ impl<TermMap: TermAggregationMap, B: SubAggBuffer> SegmentAggregationCollector
                 req_data.missing_value_for_accessor,
             );
 
-        if let Some(sub_agg) = &mut self.sub_agg {
+        if let Some(doc_count_column) = agg_data.doc_count_column.as_ref() {
+            let term_buckets = &mut self.parent_buckets[parent_bucket_id as usize];
+            let it = agg_data
+                .column_block_accessor
+                .iter_docid_vals(docs, &req_data.accessor);
+            let sub_agg = self.sub_agg.as_mut();
+            if let Some(allowed_bs) = req_data.allowed_term_ids.as_ref() {
+                let it = it.filter(move |&(_doc, term_id)| allowed_bs.contains(term_id as u32));
+                Self::collect_terms_weighted(
+                    it,
+                    term_buckets,
+                    &mut self.bucket_id_provider,
+                    doc_count_column,
+                    sub_agg,
+                );
+            } else {
+                Self::collect_terms_weighted(
+                    it,
+                    term_buckets,
+                    &mut self.bucket_id_provider,
+                    doc_count_column,
+                    sub_agg,
+                );
+            }
+        } else if let Some(sub_agg) = &mut self.sub_agg {
             let term_buckets = &mut self.parent_buckets[parent_bucket_id as usize];
             let it = agg_data
                 .column_block_accessor
@@ -1170,7 +1219,7 @@ impl<TermMap: TermAggregationMap, B: SubAggBuffer> SegmentTermCollector<TermMap,
         sub_agg: &mut BufferedSubAggs<B>,
     ) {
         for (doc, term_id) in iter {
-            let bucket_id = term_buckets.term_entry(term_id, bucket_id_provider);
+            let bucket_id = term_buckets.term_entry(term_id, 1, bucket_id_provider);
             sub_agg.push(bucket_id, doc);
         }
     }
@@ -1182,7 +1231,25 @@ impl<TermMap: TermAggregationMap, B: SubAggBuffer> SegmentTermCollector<TermMap,
         bucket_id_provider: &mut BucketIdProvider,
     ) {
         for term_id in iter {
-            term_buckets.term_entry(term_id, bucket_id_provider);
+            term_buckets.term_entry(term_id, 1, bucket_id_provider);
+        }
+    }
+
+    #[inline]
+    fn collect_terms_weighted(
+        iter: impl Iterator<Item = (crate::DocId, u64)>,
+        term_buckets: &mut TermMap,
+        bucket_id_provider: &mut BucketIdProvider,
+        doc_count_column: &Column<u64>,
+        mut sub_agg: Option<&mut BufferedSubAggs<B>>,
+    ) {
+        for (doc, term_id) in iter {
+            let weight = doc_count_weight(doc_count_column, doc);
+            let count = u32::try_from(weight).unwrap_or(u32::MAX);
+            let bucket_id = term_buckets.term_entry(term_id, count, bucket_id_provider);
+            if let Some(sub_agg) = sub_agg.as_deref_mut() {
+                sub_agg.push(bucket_id, doc);
+            }
         }
     }
 }
@@ -1248,18 +1315,18 @@ mod tests {
         let mut bucket_id_provider = BucketIdProvider::default();
         let mut map = PagedTermMap::new((PAGE_SIZE * 2) as u64, &mut bucket_id_provider);
 
-        let bucket_first = map.term_entry(5, &mut bucket_id_provider);
-        let bucket_second_page = map.term_entry((PAGE_SIZE + 7) as u64, &mut bucket_id_provider);
+        let bucket_first = map.term_entry(5, 1, &mut bucket_id_provider);
+        let bucket_second_page = map.term_entry((PAGE_SIZE + 7) as u64, 1, &mut bucket_id_provider);
 
         // Reinsertions should increment counts and reuse bucket ids
-        assert_eq!(map.term_entry(5, &mut bucket_id_provider), bucket_first);
+        assert_eq!(map.term_entry(5, 1, &mut bucket_id_provider), bucket_first);
         assert_eq!(
-            map.term_entry((PAGE_SIZE + 7) as u64, &mut bucket_id_provider),
+            map.term_entry((PAGE_SIZE + 7) as u64, 1, &mut bucket_id_provider),
             bucket_second_page
         );
 
         // High offset exercises the TinySet presence word boundaries.
-        let bucket_high_bit = map.term_entry(63, &mut bucket_id_provider);
+        let bucket_high_bit = map.term_entry(63, 1, &mut bucket_id_provider);
 
         let mut entries = map.into_vec();
         entries.sort_by_key(|(term_id, _)| *term_id);
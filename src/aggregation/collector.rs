@@ -1,3 +1,7 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
 use super::agg_req::Aggregations;
 use super::agg_result::AggregationResults;
 use super::buffered_sub_aggs::LowCardBufferedSubAggs;
@@ -9,7 +13,8 @@ use crate::aggregation::agg_data::{
 };
 use crate::collector::{Collector, SegmentCollector};
 use crate::index::SegmentReader;
-use crate::{DocId, SegmentOrdinal, TantivyError};
+use crate::query::{EnableScoring, Query};
+use crate::{DocId, Searcher, SegmentOrdinal, TantivyError};
 
 /// The default max bucket count, before the aggregation fails.
 pub const DEFAULT_BUCKET_LIMIT: u32 = 65000;
@@ -33,6 +38,22 @@ impl AggregationCollector {
     pub fn from_aggs(agg: Aggregations, context: AggContextParams) -> Self {
         Self { agg, context }
     }
+
+    /// Runs the aggregation over the documents of `searcher` matching `query`, yielding to the
+    /// async runtime between segments.
+    ///
+    /// Segments are collected one after the other on the polling thread, so that an aggregation
+    /// over many segments does not hold a thread for its whole duration. The future does not
+    /// depend on a specific runtime. The result is the same as with
+    /// [`Searcher::search`].
+    pub async fn collect_async(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+    ) -> crate::Result<AggregationResults> {
+        let segment_fruits = collect_segments_async(self, searcher, query).await?;
+        self.merge_fruits(segment_fruits)
+    }
 }
 
 /// Collector for distributed aggregations.
@@ -56,6 +77,58 @@ impl DistributedAggregationCollector {
     pub fn from_aggs(agg: Aggregations, context: AggContextParams) -> Self {
         Self { agg, context }
     }
+
+    /// Same as [`AggregationCollector::collect_async`], returning the intermediate results.
+    pub async fn collect_async(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+    ) -> crate::Result<IntermediateAggregationResults> {
+        let segment_fruits = collect_segments_async(self, searcher, query).await?;
+        self.merge_fruits(segment_fruits)
+    }
+}
+
+/// Collects the segments of `searcher` one by one, yielding after each of them.
+async fn collect_segments_async<C>(
+    collector: &C,
+    searcher: &Searcher,
+    query: &dyn Query,
+) -> crate::Result<Vec<crate::Result<IntermediateAggregationResults>>>
+where
+    C: Collector<Child = AggregationSegmentCollector>,
+{
+    let weight = query.weight(EnableScoring::disabled_from_searcher(searcher))?;
+    collector.check_schema(searcher.schema())?;
+    let mut segment_fruits = Vec::with_capacity(searcher.segment_readers().len());
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        segment_fruits.push(collector.collect_segment(
+            weight.as_ref(),
+            segment_ord as SegmentOrdinal,
+            segment_reader,
+        )?);
+        YieldNow { yielded: false }.await;
+    }
+    Ok(segment_fruits)
+}
+
+/// Future returning `Pending` once, after waking its task up, so that the runtime can run
+/// other tasks before polling it again.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
 }
 
 impl Collector for DistributedAggregationCollector {
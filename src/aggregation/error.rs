@@ -25,6 +25,19 @@ pub enum AggregationError {
         /// Current memory consumption
         current: ByteCount,
     },
+    /// The aggregation is not in the aggregation results
+    #[error("Aggregation {0:?} not found in the aggregation results")]
+    MissingAggregation(String),
+    /// The aggregation result is not of the requested kind
+    #[error("Expected aggregation {name:?} to be of kind {requested}, but it is of kind {actual}")]
+    UnexpectedAggregationKind {
+        /// Name of the aggregation
+        name: String,
+        /// Requested kind of aggregation
+        requested: &'static str,
+        /// Actual kind of the aggregation
+        actual: &'static str,
+    },
     /// Bucket limit exceeded
     #[error(
         "Aborting aggregation because bucket limit was exceeded. Limit: {limit:?}, Current: \
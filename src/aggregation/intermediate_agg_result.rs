@@ -189,8 +189,11 @@ impl IntermediateAggregationResults {
         // Handle empty results
         if results.len() != req.len() {
             for (key, req) in req.iter() {
-                if !results.contains_key(key) {
-                    let empty_res = empty_from_req(req);
+                if results.contains_key(key) {
+                    continue;
+                }
+                // Pipeline aggregations are computed by their parent histogram.
+                if let Some(empty_res) = empty_from_req(req) {
                     results.insert(key.to_string(), empty_res.into_final_result(req, limits)?);
                 }
             }
@@ -202,8 +205,9 @@ impl IntermediateAggregationResults {
     pub(crate) fn empty_from_req(req: &Aggregations) -> Self {
         let mut aggs_res: FxHashMap<String, IntermediateAggregationResult> = FxHashMap::default();
         for (key, req) in req.iter() {
-            let empty_res = empty_from_req(req);
-            aggs_res.insert(key.to_string(), empty_res);
+            if let Some(empty_res) = empty_from_req(req) {
+                aggs_res.insert(key.to_string(), empty_res);
+            }
         }
 
         Self { aggs_res }
@@ -226,9 +230,11 @@ impl IntermediateAggregationResults {
     }
 }
 
-pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult {
+/// Returns the empty intermediate result of `req`, or `None` for pipeline aggregations, which
+/// have no intermediate result.
+pub(crate) fn empty_from_req(req: &Aggregation) -> Option<IntermediateAggregationResult> {
     use AggregationVariants::*;
-    match req.agg {
+    let empty_res = match req.agg {
         Terms(_) => IntermediateAggregationResult::Bucket(IntermediateBucketResult::Terms {
             buckets: Default::default(),
         }),
@@ -286,7 +292,9 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
                 buckets: IntermediateCompositeBucketResult::default(),
             })
         }
-    }
+        Derivative(_) | CumulativeSum(_) | MovingAverage(_) => return None,
+    };
+    Some(empty_res)
 }
 
 /// An aggregation is either a bucket or a metric.
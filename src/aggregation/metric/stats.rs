@@ -4,7 +4,7 @@ use columnar::{Column, ColumnType};
 use serde::{Deserialize, Serialize};
 
 use super::*;
-use crate::aggregation::agg_data::AggregationsSegmentCtx;
+use crate::aggregation::agg_data::{doc_count_weight, AggregationsSegmentCtx};
 use crate::aggregation::intermediate_agg_result::{
     IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
 };
@@ -173,6 +173,21 @@ impl IntermediateStats {
         self.min = self.min.min(value);
         self.max = self.max.max(value);
     }
+
+    /// Collects a value standing for `weight` documents.
+    #[inline]
+    pub(in crate::aggregation::metric) fn collect_weighted(&mut self, value: f64, weight: u64) {
+        self.count += weight;
+
+        // kahan algorithm for sum
+        let y = value * weight as f64 - self.delta;
+        let t = self.sum + y;
+        self.delta = (t - self.sum) - y;
+        self.sum = t;
+
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
 }
 
 /// The type of stats aggregation to perform.
@@ -218,6 +233,7 @@ pub(crate) fn build_segment_stats_collector(
         ColumnType::I64 => Ok(create_collector::<{ ColumnType::I64 as u8 }>(req)),
         ColumnType::U64 => Ok(create_collector::<{ ColumnType::U64 as u8 }>(req)),
         ColumnType::F64 => Ok(create_collector::<{ ColumnType::F64 as u8 }>(req)),
+        ColumnType::F32 => Ok(create_collector::<{ ColumnType::F32 as u8 }>(req)),
         ColumnType::Bool => Ok(create_collector::<{ ColumnType::Bool as u8 }>(req)),
         ColumnType::DateTime => Ok(create_collector::<{ ColumnType::DateTime as u8 }>(req)),
         ColumnType::Bytes => Ok(create_collector::<{ ColumnType::Bytes as u8 }>(req)),
@@ -285,6 +301,26 @@ impl<const COLUMN_TYPE_ID: u8> SegmentAggregationCollector
         docs: &[crate::DocId],
         agg_data: &mut AggregationsSegmentCtx,
     ) -> crate::Result<()> {
+        if let Some(doc_count_column) = agg_data.doc_count_column.as_ref() {
+            agg_data.column_block_accessor.fetch_block_with_missing(
+                docs,
+                &self.accessor,
+                self.missing_u64,
+            );
+            let stats = &mut self.buckets[parent_bucket_id as usize];
+            for (doc, val) in agg_data
+                .column_block_accessor
+                .iter_docid_vals(docs, &self.accessor)
+            {
+                let val = if self.is_number_or_date_type {
+                    convert_to_f64::<COLUMN_TYPE_ID>(val)
+                } else {
+                    0.0
+                };
+                stats.collect_weighted(val, doc_count_weight(doc_count_column, doc));
+            }
+            return Ok(());
+        }
         // TODO: remove once we fetch all values for all bucket ids in one go
         if docs.len() == 1 && self.missing_u64.is_none() {
             collect_stats::<COLUMN_TYPE_ID>(
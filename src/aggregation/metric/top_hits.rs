@@ -291,6 +291,10 @@ impl TopHitsAggregationReq {
                             .values_for_doc(doc_id)
                             .map(FastFieldValue::F64)
                             .collect::<Vec<_>>(),
+                        DynamicColumn::F32(accessor) => accessor
+                            .values_for_doc(doc_id)
+                            .map(|val| FastFieldValue::F64(f64::from(val)))
+                            .collect::<Vec<_>>(),
                         DynamicColumn::Bytes(accessor) => accessor
                             .term_ords(doc_id)
                             .map(|term_ord| {
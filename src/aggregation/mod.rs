@@ -7,7 +7,8 @@
 //! - How many errors with status code 500 do we have per day?
 //! - What is the average listing price of cars grouped by color?
 //!
-//! There are two categories: [Metrics](metric) and [Buckets](bucket).
+//! There are three categories: [Metrics](metric), [Buckets](bucket) and
+//! [Pipelines](pipeline).
 //!
 //! ## Prerequisite
 //! Currently aggregations work only on [fast fields](`crate::fastfield`). Fast fields
@@ -52,6 +53,10 @@
 //!     - [Percentiles](metric::PercentilesAggregationReq)
 //!     - [Cardinality](metric::CardinalityAggregationReq)
 //!     - [TopHits](metric::TopHitsAggregationReq)
+//! - [Pipeline](pipeline)
+//!     - [Derivative](pipeline::DerivativeAggregation)
+//!     - [CumulativeSum](pipeline::CumulativeSumAggregation)
+//!     - [MovingAverage](pipeline::MovingAverageAggregation)
 //!
 //! # Example
 //! Compute the average metric, by building [`agg_req::Aggregations`], which is built from an
@@ -139,6 +144,7 @@ mod date;
 mod error;
 pub mod intermediate_agg_result;
 pub mod metric;
+pub mod pipeline;
 
 mod segment_agg_result;
 use std::fmt::Display;
@@ -180,18 +186,39 @@ pub type BucketId = u32;
 /// This struct holds shared resources needed during aggregation execution:
 /// - `limits`: Memory and bucket limits for the aggregation
 /// - `tokenizers`: TokenizerManager for parsing query strings in filter aggregations
+/// - `doc_count_field`: Optional u64 fast field holding the number of documents each document
+///   stands for
 #[derive(Clone, Default)]
 pub struct AggContextParams {
     /// Aggregation limits (memory and bucket count)
     pub limits: AggregationLimitsGuard,
     /// Tokenizer manager for query string parsing
     pub tokenizers: TokenizerManager,
+    /// Name of a u64 fast field holding the number of raw documents a document stands for.
+    ///
+    /// This is useful for pre-aggregated documents, where one document represents many
+    /// events. When set, terms and histogram buckets add the value of that field to their
+    /// `doc_count` instead of 1, and the `count`, `sum`, `avg` and `stats` metrics weight
+    /// each value by it. Documents without a value in that field count as 1.
+    pub doc_count_field: Option<String>,
 }
 
 impl AggContextParams {
     /// Create new aggregation context parameters
     pub fn new(limits: AggregationLimitsGuard, tokenizers: TokenizerManager) -> Self {
-        Self { limits, tokenizers }
+        Self {
+            limits,
+            tokenizers,
+            doc_count_field: None,
+        }
+    }
+
+    /// Weights documents by the given u64 fast field.
+    ///
+    /// See [`AggContextParams::doc_count_field`].
+    pub fn with_doc_count_field(mut self, field_name: impl Into<String>) -> Self {
+        self.doc_count_field = Some(field_name.into());
+        self
     }
 }
 
@@ -357,6 +384,8 @@ pub(crate) fn convert_to_f64<const COLUMN_TYPE_ID: u8>(val: u64) -> f64 {
         i64::from_u64(val) as f64
     } else if COLUMN_TYPE_ID == ColumnType::F64 as u8 {
         f64::from_u64(val)
+    } else if COLUMN_TYPE_ID == ColumnType::F32 as u8 {
+        f64::from(f32::from_u64(val))
     } else if COLUMN_TYPE_ID == ColumnType::Bool as u8 {
         val as f64
     } else {
@@ -376,6 +405,7 @@ pub(crate) fn f64_from_fastfield_u64(val: u64, field_type: ColumnType) -> f64 {
         ColumnType::U64 => convert_to_f64::<{ ColumnType::U64 as u8 }>(val),
         ColumnType::I64 => convert_to_f64::<{ ColumnType::I64 as u8 }>(val),
         ColumnType::F64 => convert_to_f64::<{ ColumnType::F64 as u8 }>(val),
+        ColumnType::F32 => convert_to_f64::<{ ColumnType::F32 as u8 }>(val),
         ColumnType::Bool => convert_to_f64::<{ ColumnType::Bool as u8 }>(val),
         ColumnType::DateTime => convert_to_f64::<{ ColumnType::DateTime as u8 }>(val),
         _ => panic!("unexpected type {field_type:?}. This should not happen"),
@@ -396,6 +426,7 @@ pub(crate) fn f64_to_fastfield_u64(val: f64, field_type: &ColumnType) -> Option<
         ColumnType::U64 => Some(val as u64),
         ColumnType::I64 | ColumnType::DateTime => Some((val as i64).to_u64()),
         ColumnType::F64 => Some(val.to_u64()),
+        ColumnType::F32 => Some((val as f32).to_u64()),
         ColumnType::Bool => Some(val as u64),
         _ => None,
     }
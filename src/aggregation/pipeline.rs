@@ -0,0 +1,311 @@
+//! Module for all pipeline aggregations.
+//!
+//! Pipeline aggregations don't read any fast field. Instead they post-process the output of
+//! their sibling aggregations, after the intermediate results of all segments (and indices)
+//! have been merged.
+//!
+//! A pipeline aggregation must be a direct sub-aggregation of a
+//! [Histogram](crate::aggregation::bucket::HistogramAggregation) or
+//! [DateHistogram](crate::aggregation::bucket::DateHistogramAggregationReq). It produces one
+//! [SingleMetricResult] per bucket of its parent, computed from the series of values
+//! addressed by its `buckets_path`:
+//! - `_count` refers to the document count of the bucket.
+//! - `<agg_name>` refers to a sibling single-value metric aggregation.
+//! - `<agg_name>.<property>` refers to a property of a sibling multi-value metric aggregation, e.g.
+//!   `price_stats.avg`.
+//!
+//! ## Supported Pipeline Aggregations
+//! - [Derivative](DerivativeAggregation)
+//! - [CumulativeSum](CumulativeSumAggregation)
+//! - [MovingAverage](MovingAverageAggregation)
+//!
+//! # JSON Format
+//! ```json
+//! {
+//!     "sales_per_week": {
+//!         "date_histogram": { "field": "date", "fixed_interval": "7d" },
+//!         "aggs": {
+//!             "sales": { "sum": { "field": "price" } },
+//!             "sales_delta": { "derivative": { "buckets_path": "sales" } },
+//!             "sales_total": { "cumulative_sum": { "buckets_path": "sales" } }
+//!         }
+//!     }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::agg_req::{AggregationVariants, Aggregations};
+use super::agg_result::{AggregationResult, BucketEntry, MetricResult};
+use super::bucket::get_agg_name_and_property;
+use super::metric::SingleMetricResult;
+use super::AggregationError;
+use crate::TantivyError;
+
+/// The `buckets_path` referring to the document count of a bucket.
+const COUNT_BUCKETS_PATH: &str = "_count";
+
+/// Defines how a pipeline aggregation handles buckets without a value.
+///
+/// A bucket has no value if the metric it refers to has no value, which happens for instance
+/// for the `avg` of an empty bucket created by `min_doc_count: 0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapPolicy {
+    /// Buckets without a value are skipped: their pipeline value is `null`, and the series
+    /// continues with the next bucket that has a value.
+    #[default]
+    Skip,
+    /// Buckets without a value are treated as if their value was 0.
+    InsertZeros,
+}
+
+/// Computes the difference between the value of a bucket and the value of the previous
+/// bucket in the parent histogram.
+///
+/// The first bucket has no derivative. With [GapPolicy::Skip], the derivative is computed
+/// against the last previous bucket that has a value.
+/// See [SingleMetricResult] for return value.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "derivative": {
+///         "buckets_path": "sales",
+///         "gap_policy": "skip"
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DerivativeAggregation {
+    /// The path to the values the derivative is computed on.
+    pub buckets_path: String,
+    /// How buckets without a value are handled.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+/// Computes the running total of a value over the buckets of the parent histogram.
+///
+/// See [SingleMetricResult] for return value.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "cumulative_sum": {
+///         "buckets_path": "_count"
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CumulativeSumAggregation {
+    /// The path to the values that are summed up.
+    pub buckets_path: String,
+    /// How buckets without a value are handled.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+/// Computes the simple moving average of a value over the buckets of the parent histogram.
+///
+/// The value of a bucket is the unweighted average of the values of the (up to) `window`
+/// preceding buckets. The current bucket is not part of its own window, so the first bucket
+/// has no moving average.
+/// See [SingleMetricResult] for return value.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "moving_avg": {
+///         "buckets_path": "sales",
+///         "window": 3
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MovingAverageAggregation {
+    /// The path to the values that are averaged.
+    pub buckets_path: String,
+    /// The number of preceding buckets to average. Defaults to 5.
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// How buckets without a value are handled.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+fn default_window() -> usize {
+    5
+}
+
+/// The computation carried out by a pipeline aggregation.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum PipelineKind {
+    Derivative,
+    CumulativeSum,
+    MovingAverage { window: usize },
+}
+
+/// Common view over the pipeline aggregation requests.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PipelineReq<'a> {
+    pub kind: PipelineKind,
+    pub buckets_path: &'a str,
+    pub gap_policy: GapPolicy,
+}
+
+fn invalid_request(msg: String) -> TantivyError {
+    TantivyError::AggregationError(AggregationError::InvalidRequest(msg))
+}
+
+/// Validates the pipeline aggregations among the sub-aggregations of a histogram.
+pub(crate) fn validate_pipeline_aggs(sub_aggregation: &Aggregations) -> crate::Result<()> {
+    for (agg_name, agg) in sub_aggregation.iter() {
+        let Some(pipeline) = agg.agg.as_pipeline() else {
+            continue;
+        };
+        if !agg.sub_aggregation.is_empty() {
+            return Err(invalid_request(format!(
+                "pipeline aggregation {agg_name:?} can't have sub-aggregations"
+            )));
+        }
+        if let PipelineKind::MovingAverage { window: 0 } = pipeline.kind {
+            return Err(invalid_request(format!(
+                "window of moving_avg aggregation {agg_name:?} must be greater than 0"
+            )));
+        }
+        if pipeline.buckets_path == COUNT_BUCKETS_PATH {
+            continue;
+        }
+        let (target_name, _property) = get_agg_name_and_property(pipeline.buckets_path);
+        let is_valid_target = sub_aggregation
+            .get(target_name)
+            .map(|target| is_numeric_metric(&target.agg))
+            .unwrap_or(false);
+        if !is_valid_target {
+            return Err(invalid_request(format!(
+                "buckets_path {:?} of pipeline aggregation {agg_name:?} must be `_count` or refer \
+                 to a sibling numeric metric aggregation",
+                pipeline.buckets_path
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn is_numeric_metric(agg: &AggregationVariants) -> bool {
+    use AggregationVariants::*;
+    matches!(
+        agg,
+        Average(_)
+            | Count(_)
+            | Max(_)
+            | Min(_)
+            | Stats(_)
+            | ExtendedStats(_)
+            | Sum(_)
+            | Cardinality(_)
+    )
+}
+
+/// Computes the pipeline aggregations of `sub_aggregation` on the final buckets of a histogram
+/// and adds their results to each bucket.
+///
+/// The buckets are expected to be sorted by key.
+pub(crate) fn apply_pipeline_aggs(
+    buckets: &mut [BucketEntry],
+    sub_aggregation: &Aggregations,
+) -> crate::Result<()> {
+    for (agg_name, agg) in sub_aggregation.iter() {
+        let Some(pipeline) = agg.agg.as_pipeline() else {
+            continue;
+        };
+        let values = buckets
+            .iter()
+            .map(|bucket| bucket_value(bucket, pipeline))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let results = compute_series(pipeline.kind, &values);
+        for (bucket, value) in buckets.iter_mut().zip(results) {
+            bucket.sub_aggregation.0.insert(
+                agg_name.to_string(),
+                AggregationResult::MetricResult(MetricResult::Pipeline(SingleMetricResult {
+                    value,
+                })),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the value `pipeline` reads from `bucket`, after applying the gap policy.
+///
+/// `None` means the bucket is skipped.
+fn bucket_value(bucket: &BucketEntry, pipeline: PipelineReq) -> crate::Result<Option<f64>> {
+    let value = if pipeline.buckets_path == COUNT_BUCKETS_PATH {
+        Some(bucket.doc_count as f64)
+    } else {
+        let (name, property) = get_agg_name_and_property(pipeline.buckets_path);
+        bucket
+            .sub_aggregation
+            .get_value_from_aggregation(name, property)?
+            .filter(|value| !value.is_nan())
+    };
+    Ok(match pipeline.gap_policy {
+        GapPolicy::Skip => value,
+        GapPolicy::InsertZeros => Some(value.unwrap_or(0.0)),
+    })
+}
+
+/// Computes the pipeline value of each bucket. Skipped buckets get no value and don't affect
+/// the values of the following buckets.
+fn compute_series(kind: PipelineKind, values: &[Option<f64>]) -> Vec<Option<f64>> {
+    let mut previous_values: Vec<f64> = Vec::with_capacity(values.len());
+    let mut sum = 0.0;
+    values
+        .iter()
+        .map(|value| {
+            let value = (*value)?;
+            let result = match kind {
+                PipelineKind::Derivative => previous_values.last().map(|previous| value - previous),
+                PipelineKind::CumulativeSum => {
+                    sum += value;
+                    Some(sum)
+                }
+                PipelineKind::MovingAverage { window } => {
+                    let start = previous_values.len().saturating_sub(window);
+                    let window_values = &previous_values[start..];
+                    if window_values.is_empty() {
+                        None
+                    } else {
+                        Some(window_values.iter().sum::<f64>() / window_values.len() as f64)
+                    }
+                }
+            };
+            previous_values.push(value);
+            Some(result)
+        })
+        .map(Option::flatten)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_series() {
+        let values = [Some(1.0), Some(3.0), None, Some(10.0), Some(2.0)];
+        assert_eq!(
+            compute_series(PipelineKind::Derivative, &values),
+            vec![None, Some(2.0), None, Some(7.0), Some(-8.0)]
+        );
+        assert_eq!(
+            compute_series(PipelineKind::CumulativeSum, &values),
+            vec![Some(1.0), Some(4.0), None, Some(14.0), Some(16.0)]
+        );
+        assert_eq!(
+            compute_series(PipelineKind::MovingAverage { window: 2 }, &values),
+            vec![None, Some(1.0), None, Some(2.0), Some(6.5)]
+        );
+    }
+}
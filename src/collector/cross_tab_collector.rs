@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+
+use columnar::StrColumn;
+use rustc_hash::FxHashMap;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Counts the documents matching a query for every pair of values of two
+/// `str` fast fields, i.e. a contingency table.
+///
+/// This gives the same counts as a `terms` aggregation nested in another `terms` aggregation,
+/// but is a lot cheaper: segments count the pairs of term ordinals in a single hash map, and the
+/// ordinals are only resolved to their values when harvesting the segment.
+///
+/// A document with several values in one of the fields is counted once for every pair of
+/// values. Documents without any value in one of the fields are not counted.
+///
+/// The number of cells of the table is bounded by `max_cells`. Once this limit is reached,
+/// new pairs of values are ignored and the resulting [`CrossTab`] is flagged as truncated.
+/// When merging segments, the cells with the highest counts are kept.
+///
+/// ```rust
+/// use tantivy::collector::CrossTabCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, STRING};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let country = schema_builder.add_text_field("country", STRING | FAST);
+/// let device = schema_builder.add_text_field("device", STRING | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(country => "fr", device => "mobile"))?;
+/// index_writer.add_document(doc!(country => "fr", device => "mobile"))?;
+/// index_writer.add_document(doc!(country => "de", device => "desktop"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let cross_tab = searcher.search(&AllQuery, &CrossTabCollector::new("country", "device", 100))?;
+/// assert_eq!(cross_tab.count("fr", "mobile"), 2);
+/// assert_eq!(cross_tab.count("fr", "desktop"), 0);
+/// assert_eq!(cross_tab.num_cells(), 2);
+/// assert!(!cross_tab.is_truncated());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CrossTabCollector {
+    field_a: String,
+    field_b: String,
+    max_cells: usize,
+}
+
+impl CrossTabCollector {
+    /// Creates a collector counting the pairs of values of `field_a` and `field_b`,
+    /// with at most `max_cells` non-zero cells.
+    pub fn new(
+        field_a: impl Into<String>,
+        field_b: impl Into<String>,
+        max_cells: usize,
+    ) -> CrossTabCollector {
+        CrossTabCollector {
+            field_a: field_a.into(),
+            field_b: field_b.into(),
+            max_cells,
+        }
+    }
+}
+
+/// Sparse contingency table computed by the [`CrossTabCollector`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrossTab {
+    cells: BTreeMap<(String, String), u64>,
+    truncated: bool,
+}
+
+impl CrossTab {
+    /// Returns the number of documents for the given pair of values.
+    pub fn count(&self, value_a: &str, value_b: &str) -> u64 {
+        self.cells
+            .get(&(value_a.to_string(), value_b.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Iterates over the non-zero cells as `(value_a, value_b, count)`,
+    /// ordered by `value_a` and then `value_b`.
+    pub fn cells(&self) -> impl Iterator<Item = (&str, &str, u64)> {
+        self.cells
+            .iter()
+            .map(|((value_a, value_b), count)| (value_a.as_str(), value_b.as_str(), *count))
+    }
+
+    /// Returns the number of non-zero cells.
+    pub fn num_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns true if some pairs of values were dropped because of the cell limit.
+    ///
+    /// The counts of a truncated table are lower bounds.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Collector for CrossTabCollector {
+    type Fruit = CrossTab;
+    type Child = CrossTabSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<CrossTabSegmentCollector> {
+        for field_name in [&self.field_a, &self.field_b] {
+            if segment_reader.schema().find_field(field_name).is_none() {
+                return Err(TantivyError::FieldNotFound(field_name.clone()));
+            }
+        }
+        let fast_fields = segment_reader.fast_fields();
+        let columns = fast_fields
+            .str(&self.field_a)?
+            .zip(fast_fields.str(&self.field_b)?);
+        Ok(CrossTabSegmentCollector {
+            columns,
+            counts: FxHashMap::default(),
+            max_cells: self.max_cells,
+            truncated: false,
+            values_b: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_cross_tabs: Vec<CrossTab>) -> crate::Result<CrossTab> {
+        let mut merged = CrossTab::default();
+        for segment_cross_tab in segment_cross_tabs {
+            merged.truncated |= segment_cross_tab.truncated;
+            for (key, count) in segment_cross_tab.cells {
+                *merged.cells.entry(key).or_default() += count;
+            }
+        }
+        if merged.cells.len() > self.max_cells {
+            let mut cells: Vec<((String, String), u64)> = merged.cells.into_iter().collect();
+            cells.sort_by(|(left_key, left_count), (right_key, right_count)| {
+                right_count
+                    .cmp(left_count)
+                    .then_with(|| left_key.cmp(right_key))
+            });
+            cells.truncate(self.max_cells);
+            merged.cells = cells.into_iter().collect();
+            merged.truncated = true;
+        }
+        Ok(merged)
+    }
+}
+
+/// Segment collector of the [`CrossTabCollector`].
+pub struct CrossTabSegmentCollector {
+    // `None` if one of the fields has no values in the segment.
+    columns: Option<(StrColumn, StrColumn)>,
+    counts: FxHashMap<(u64, u64), u64>,
+    max_cells: usize,
+    truncated: bool,
+    // Buffer for the ordinals of `field_b` of the current document.
+    values_b: Vec<u64>,
+}
+
+impl SegmentCollector for CrossTabSegmentCollector {
+    type Fruit = CrossTab;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let Some((column_a, column_b)) = &self.columns else {
+            return;
+        };
+        self.values_b.clear();
+        self.values_b.extend(column_b.ords().values_for_doc(doc));
+        if self.values_b.is_empty() {
+            return;
+        }
+        for ord_a in column_a.ords().values_for_doc(doc) {
+            for &ord_b in &self.values_b {
+                let num_cells = self.counts.len();
+                match self.counts.get_mut(&(ord_a, ord_b)) {
+                    Some(count) => *count += 1,
+                    None if num_cells < self.max_cells => {
+                        self.counts.insert((ord_a, ord_b), 1);
+                    }
+                    None => self.truncated = true,
+                }
+            }
+        }
+    }
+
+    fn harvest(self) -> CrossTab {
+        let mut cross_tab = CrossTab {
+            cells: BTreeMap::new(),
+            truncated: self.truncated,
+        };
+        let Some((column_a, column_b)) = &self.columns else {
+            return cross_tab;
+        };
+        let mut value_a = String::new();
+        let mut value_b = String::new();
+        for ((ord_a, ord_b), count) in self.counts {
+            // The ordinals come from the columns themselves, so they are expected to resolve.
+            let resolved = column_a.ord_to_str(ord_a, &mut value_a).unwrap_or(false)
+                && column_b.ord_to_str(ord_b, &mut value_b).unwrap_or(false);
+            if resolved {
+                cross_tab
+                    .cells
+                    .insert((value_a.clone(), value_b.clone()), count);
+            }
+        }
+        cross_tab
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::CrossTabCollector;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::AggregationCollector;
+    use crate::query::{AllQuery, QueryParser};
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::{Index, IndexWriter};
+
+    fn cross_tab_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let country = schema_builder.add_text_field("country", STRING | FAST);
+        let device = schema_builder.add_text_field("device", STRING | FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        // Never populated.
+        schema_builder.add_text_field("browser", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let countries = ["fr", "de", "us", "jp"];
+        let devices = ["mobile", "desktop", "tablet"];
+        for i in 0..60usize {
+            index_writer.add_document(doc!(
+                country => countries[i % 4],
+                device => devices[(i / 4) % 3],
+                text => if i % 2 == 0 { "even" } else { "odd" },
+            ))?;
+            if i % 25 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        // Multivalued and missing values.
+        index_writer.add_document(doc!(country => "fr", device => "mobile", device => "tv"))?;
+        index_writer.add_document(doc!(country => "fr", text => "odd"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn nested_terms_counts(
+        index: &Index,
+        query: &dyn crate::query::Query,
+    ) -> Vec<(String, String, u64)> {
+        let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+            "country": {
+                "terms": { "field": "country", "size": 100 },
+                "aggs": { "device": { "terms": { "field": "device", "size": 100 } } }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let agg_res = index
+            .reader()
+            .unwrap()
+            .searcher()
+            .search(query, &collector)
+            .unwrap();
+        let agg_res: Value = serde_json::to_value(agg_res).unwrap();
+        let mut counts = Vec::new();
+        for country_bucket in agg_res["country"]["buckets"].as_array().unwrap() {
+            for device_bucket in country_bucket["device"]["buckets"].as_array().unwrap() {
+                counts.push((
+                    country_bucket["key"].as_str().unwrap().to_string(),
+                    device_bucket["key"].as_str().unwrap().to_string(),
+                    device_bucket["doc_count"].as_u64().unwrap(),
+                ));
+            }
+        }
+        counts.sort();
+        counts
+    }
+
+    #[test]
+    fn test_cross_tab_matches_nested_terms_aggregation() -> crate::Result<()> {
+        let index = cross_tab_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let odd_query = QueryParser::for_index(&index, vec![text]).parse_query("odd")?;
+        for query in [&AllQuery as &dyn crate::query::Query, odd_query.as_ref()] {
+            let cross_tab =
+                searcher.search(query, &CrossTabCollector::new("country", "device", 100))?;
+            assert!(!cross_tab.is_truncated());
+            let cells: Vec<(String, String, u64)> = cross_tab
+                .cells()
+                .map(|(value_a, value_b, count)| (value_a.to_string(), value_b.to_string(), count))
+                .collect();
+            assert_eq!(cells, nested_terms_counts(&index, query));
+        }
+        let cross_tab =
+            searcher.search(&AllQuery, &CrossTabCollector::new("country", "device", 100))?;
+        assert_eq!(cross_tab.count("fr", "mobile"), 6);
+        assert_eq!(cross_tab.count("fr", "tv"), 1);
+        assert_eq!(cross_tab.count("jp", "tv"), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cross_tab_truncated() -> crate::Result<()> {
+        let index = cross_tab_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let cross_tab =
+            searcher.search(&AllQuery, &CrossTabCollector::new("country", "device", 3))?;
+        assert!(cross_tab.is_truncated());
+        assert_eq!(cross_tab.num_cells(), 3);
+        let full_cross_tab =
+            searcher.search(&AllQuery, &CrossTabCollector::new("country", "device", 100))?;
+        for (value_a, value_b, count) in cross_tab.cells() {
+            assert!(count <= full_cross_tab.count(value_a, value_b));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cross_tab_missing_field() -> crate::Result<()> {
+        let index = cross_tab_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let cross_tab = searcher.search(
+            &AllQuery,
+            &CrossTabCollector::new("country", "browser", 100),
+        )?;
+        assert_eq!(cross_tab.num_cells(), 0);
+        for field_name in ["text", "unknown"] {
+            assert!(searcher
+                .search(
+                    &AllQuery,
+                    &CrossTabCollector::new("country", field_name, 100)
+                )
+                .is_err());
+        }
+        Ok(())
+    }
+}
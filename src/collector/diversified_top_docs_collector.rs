@@ -0,0 +1,413 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use columnar::StrColumn;
+
+use crate::collector::sort_key::NaturalComparator;
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+const DEFAULT_CANDIDATE_MULTIPLIER: usize = 5;
+
+/// Collects the top scored documents, with at most `max_per_key` documents per value of a `str`
+/// fast field, e.g. at most 2 hits per domain.
+///
+/// The hits are filled in score order. The documents whose value already has `max_per_key`
+/// hits are skipped, and replaced by lower scored documents.
+///
+/// Every segment collects a pool of `candidate_multiplier` times as many candidates as
+/// requested, and the diversity constraint is applied when merging the pools. If the best
+/// documents of a segment all share the same few values, the pool may run out before the
+/// requested number of hits is reached, in which case fewer hits are returned. A larger
+/// multiplier makes this less likely, at the cost of collecting more candidates.
+///
+/// Only the first value of the field is considered. Documents without a value are not limited.
+///
+/// Created with [`TopDocs::with_diversity`](crate::collector::TopDocs::with_diversity).
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, STRING, TEXT};
+/// use tantivy::{doc, DocAddress, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let domain = schema_builder.add_text_field("domain", STRING | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "rust rust rust", domain => "a.com"))?;
+/// index_writer.add_document(doc!(title => "rust rust", domain => "a.com"))?;
+/// index_writer.add_document(doc!(title => "rust", domain => "b.com"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+/// let hits = searcher.search(&query, &TopDocs::with_limit(2).with_diversity("domain", 1))?;
+/// let doc_addresses: Vec<DocAddress> =
+///     hits.top_docs().iter().map(|(_, doc_address)| *doc_address).collect();
+/// assert_eq!(doc_addresses, vec![DocAddress::new(0, 0), DocAddress::new(0, 2)]);
+/// assert_eq!(hits.num_skipped(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DiversifiedTopDocsCollector {
+    field: String,
+    max_per_key: usize,
+    limit: usize,
+    offset: usize,
+    candidate_multiplier: usize,
+}
+
+impl DiversifiedTopDocsCollector {
+    pub(crate) fn new(
+        field: String,
+        max_per_key: usize,
+        limit: usize,
+        offset: usize,
+    ) -> DiversifiedTopDocsCollector {
+        DiversifiedTopDocsCollector {
+            field,
+            max_per_key,
+            limit,
+            offset,
+            candidate_multiplier: DEFAULT_CANDIDATE_MULTIPLIER,
+        }
+    }
+
+    /// Sets the number of candidates collected by every segment, as a multiple of the number of
+    /// requested hits. Defaults to 5.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `candidate_multiplier` is zero.
+    pub fn with_candidate_multiplier(
+        mut self,
+        candidate_multiplier: usize,
+    ) -> DiversifiedTopDocsCollector {
+        assert_ne!(
+            candidate_multiplier, 0,
+            "The candidate multiplier must be greater than 0"
+        );
+        self.candidate_multiplier = candidate_multiplier;
+        self
+    }
+
+    fn num_candidates(&self) -> usize {
+        (self.limit + self.offset).saturating_mul(self.candidate_multiplier)
+    }
+}
+
+/// Top documents computed by the [`DiversifiedTopDocsCollector`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DiversifiedTopDocs {
+    top_docs: Vec<(Score, DocAddress)>,
+    num_skipped: usize,
+}
+
+impl DiversifiedTopDocs {
+    /// Returns the top documents, sorted by decreasing score.
+    pub fn top_docs(&self) -> &[(Score, DocAddress)] {
+        &self.top_docs
+    }
+
+    /// Returns the number of candidates which were skipped because their value already had
+    /// enough hits.
+    pub fn num_skipped(&self) -> usize {
+        self.num_skipped
+    }
+
+    /// Returns the top documents, sorted by decreasing score.
+    pub fn into_top_docs(self) -> Vec<(Score, DocAddress)> {
+        self.top_docs
+    }
+}
+
+/// A candidate of a segment, with the value of its field if it has one.
+type Candidate = (Score, DocAddress, Option<String>);
+
+impl Collector for DiversifiedTopDocsCollector {
+    type Fruit = DiversifiedTopDocs;
+    type Child = DiversifiedTopDocsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<DiversifiedTopDocsSegmentCollector> {
+        if segment_reader.schema().find_field(&self.field).is_none() {
+            return Err(TantivyError::FieldNotFound(self.field.clone()));
+        }
+        let column = segment_reader.fast_fields().str(&self.field)?;
+        Ok(DiversifiedTopDocsSegmentCollector {
+            column,
+            segment_ord,
+            top_n: TopNComputer::new_with_comparator(self.num_candidates(), NaturalComparator),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_candidates: Vec<Vec<Candidate>>,
+    ) -> crate::Result<DiversifiedTopDocs> {
+        let mut candidates: Vec<Candidate> = segment_candidates.into_iter().flatten().collect();
+        candidates.sort_by(|(left_score, left_doc, _), (right_score, right_doc, _)| {
+            right_score
+                .partial_cmp(left_score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left_doc.cmp(right_doc))
+        });
+        let num_hits = self.limit + self.offset;
+        let mut hits = Vec::with_capacity(num_hits);
+        let mut num_skipped = 0;
+        let mut num_hits_per_key: HashMap<String, usize> = HashMap::new();
+        for (score, doc_address, key) in candidates {
+            if hits.len() == num_hits {
+                break;
+            }
+            if let Some(key) = key {
+                let num_key_hits = num_hits_per_key.entry(key).or_default();
+                if *num_key_hits >= self.max_per_key {
+                    num_skipped += 1;
+                    continue;
+                }
+                *num_key_hits += 1;
+            }
+            hits.push((score, doc_address));
+        }
+        Ok(DiversifiedTopDocs {
+            top_docs: hits.into_iter().skip(self.offset).collect(),
+            num_skipped,
+        })
+    }
+}
+
+/// Segment collector of the [`DiversifiedTopDocsCollector`].
+pub struct DiversifiedTopDocsSegmentCollector {
+    // `None` if the field has no values in the segment.
+    column: Option<StrColumn>,
+    segment_ord: SegmentOrdinal,
+    top_n: TopNComputer<Score, DocId, NaturalComparator>,
+}
+
+impl SegmentCollector for DiversifiedTopDocsSegmentCollector {
+    type Fruit = Vec<Candidate>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.top_n.push(score, doc);
+    }
+
+    fn harvest(self) -> Vec<Candidate> {
+        let mut value = String::new();
+        self.top_n
+            .into_vec()
+            .into_iter()
+            .map(|comparable_doc| {
+                let doc = comparable_doc.doc;
+                // The ordinals come from the column itself, so they are expected to resolve.
+                let key = self.column.as_ref().and_then(|column| {
+                    let ord = column.ords().first(doc)?;
+                    column
+                        .ord_to_str(ord, &mut value)
+                        .unwrap_or(false)
+                        .then(|| value.clone())
+                });
+                (
+                    comparable_doc.sort_key,
+                    DocAddress::new(self.segment_ord, doc),
+                    key,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Score, Searcher};
+
+    fn diversity_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let domain = schema_builder.add_text_field("domain", STRING | FAST);
+        schema_builder.add_text_field("other", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..60usize {
+            let num_words = 1 + (i * 7) % 11;
+            let words = vec!["tantivy"; num_words].join(" ");
+            // Skewed domains: most documents belong to a.com.
+            let domain_value = match i % 10 {
+                0..=6 => "a.com",
+                7 | 8 => "b.com",
+                _ => "c.com",
+            };
+            if i % 13 == 0 {
+                index_writer.add_document(doc!(text => format!("{words} filler")))?;
+            } else {
+                index_writer.add_document(doc!(
+                    text => format!("{words} filler"),
+                    domain => domain_value,
+                ))?;
+            }
+            if i % 25 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    /// Applies the diversity constraint to the full list of hits.
+    fn expected_diversified(
+        all_hits: &[(Score, DocAddress)],
+        domains: &HashMap<DocAddress, String>,
+        max_per_key: usize,
+        limit: usize,
+    ) -> (Vec<(Score, DocAddress)>, usize) {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut hits = Vec::new();
+        let mut num_skipped = 0;
+        for &(score, doc_address) in all_hits {
+            if hits.len() == limit {
+                break;
+            }
+            if let Some(domain) = domains.get(&doc_address) {
+                let count = counts.entry(domain.as_str()).or_default();
+                if *count >= max_per_key {
+                    num_skipped += 1;
+                    continue;
+                }
+                *count += 1;
+            }
+            hits.push((score, doc_address));
+        }
+        (hits, num_skipped)
+    }
+
+    fn hit_domains(
+        searcher: &Searcher,
+        hits: &[(Score, DocAddress)],
+    ) -> crate::Result<HashMap<DocAddress, String>> {
+        let mut domains = HashMap::new();
+        for (_, doc_address) in hits {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+            let column = segment_reader.fast_fields().str("domain")?.unwrap();
+            if let Some(ord) = column.ords().first(doc_address.doc_id) {
+                let mut value = String::new();
+                column.ord_to_str(ord, &mut value)?;
+                domains.insert(*doc_address, value);
+            }
+        }
+        Ok(domains)
+    }
+
+    /// Checks the limit of hits per value and the score order of diversified hits.
+    fn assert_diversified(
+        hits: &[(Score, DocAddress)],
+        domains: &HashMap<DocAddress, String>,
+        max_per_key: usize,
+    ) {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (_, doc_address) in hits {
+            if let Some(domain) = domains.get(doc_address) {
+                *counts.entry(domain.as_str()).or_default() += 1;
+            }
+        }
+        assert!(counts.values().all(|&count| count <= max_per_key));
+        assert!(hits.windows(2).all(|window| window[0].0 >= window[1].0));
+    }
+
+    #[test]
+    fn test_diversified_top_docs() -> crate::Result<()> {
+        let index = diversity_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("tantivy")?;
+        let all_hits = searcher.search(&query, &TopDocs::with_limit(100).order_by_score())?;
+        let domains = hit_domains(&searcher, &all_hits)?;
+        for (max_per_key, limit) in [(1, 5), (2, 10), (3, 4), (100, 20)] {
+            let collector = TopDocs::with_limit(limit)
+                .with_diversity("domain", max_per_key)
+                .with_candidate_multiplier(100);
+            let diversified = searcher.search(&query, &collector)?;
+            let (expected_hits, expected_num_skipped) =
+                expected_diversified(&all_hits, &domains, max_per_key, limit);
+            assert_eq!(diversified.top_docs(), expected_hits.as_slice());
+            assert_eq!(diversified.num_skipped(), expected_num_skipped);
+            assert_diversified(diversified.top_docs(), &domains, max_per_key);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_diversified_top_docs_offset_and_small_pool() -> crate::Result<()> {
+        let index = diversity_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("tantivy")?;
+        let all = searcher.search(&query, &TopDocs::with_limit(6).with_diversity("domain", 2))?;
+        let page = searcher.search(
+            &query,
+            &TopDocs::with_limit(3)
+                .and_offset(3)
+                .with_diversity("domain", 2),
+        )?;
+        assert_eq!(page.top_docs(), &all.top_docs()[3..]);
+
+        // The candidate pools are collected per segment, so that with a small pool the best hit
+        // of a value may not be a candidate, and fewer hits than requested may be left. The
+        // diversity constraint and the score order still hold.
+        let all_hits = searcher.search(&query, &TopDocs::with_limit(100).order_by_score())?;
+        let domains = hit_domains(&searcher, &all_hits)?;
+        let small_pool = searcher.search(
+            &query,
+            &TopDocs::with_limit(3)
+                .with_diversity("domain", 1)
+                .with_candidate_multiplier(1),
+        )?;
+        assert!(small_pool.top_docs().len() <= 3);
+        assert_diversified(small_pool.top_docs(), &domains, 1);
+        let small_pool_page = searcher.search(
+            &query,
+            &TopDocs::with_limit(2)
+                .and_offset(1)
+                .with_diversity("domain", 1)
+                .with_candidate_multiplier(1),
+        )?;
+        assert_diversified(small_pool_page.top_docs(), &domains, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diversified_top_docs_field_without_values() -> crate::Result<()> {
+        let index = diversity_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("tantivy")?;
+        // Documents without a value are not limited.
+        let diversified =
+            searcher.search(&query, &TopDocs::with_limit(10).with_diversity("other", 1))?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10).order_by_score())?;
+        assert_eq!(diversified.top_docs(), top_docs.as_slice());
+        assert_eq!(diversified.num_skipped(), 0);
+        assert!(searcher
+            .search(
+                &query,
+                &TopDocs::with_limit(10).with_diversity("unknown", 1)
+            )
+            .is_err());
+        Ok(())
+    }
+}
@@ -92,6 +92,9 @@ pub use self::count_collector::Count;
 /// Sort keys
 pub mod sort_key;
 
+mod cross_tab_collector;
+pub use self::cross_tab_collector::{CrossTab, CrossTabCollector, CrossTabSegmentCollector};
+
 mod histogram_collector;
 pub use histogram_collector::HistogramCollector;
 
@@ -104,6 +107,16 @@ pub use self::top_collector::ComparableDoc;
 mod top_score_collector;
 pub use self::top_score_collector::{TopDocs, TopNComputer};
 
+mod diversified_top_docs_collector;
+pub use self::diversified_top_docs_collector::{
+    DiversifiedTopDocs, DiversifiedTopDocsCollector, DiversifiedTopDocsSegmentCollector,
+};
+
+mod top_docs_per_bucket_collector;
+pub use self::top_docs_per_bucket_collector::{
+    TopDocsPerBucket, TopDocsPerBucketCollector, TopDocsPerBucketSegmentCollector,
+};
+
 mod sort_key_top_collector;
 pub use self::sort_key::{SegmentSortKeyComputer, SortKeyComputer};
 mod facet_collector;
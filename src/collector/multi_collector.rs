@@ -3,6 +3,7 @@ use std::ops::Deref;
 
 use super::{Collector, SegmentCollector};
 use crate::collector::Fruit;
+use crate::error::UNEXPECTED_STATE;
 use crate::schema::Schema;
 use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
@@ -45,7 +46,9 @@ impl<TCollector: Collector> Collector for CollectorWrapper<TCollector> {
                     .downcast::<<TCollector::Child as SegmentCollector>::Fruit>()
                     .map(|boxed_but_typed| *boxed_but_typed)
                     .map_err(|_| {
-                        TantivyError::InvalidArgument("Failed to cast child fruit.".to_string())
+                        TantivyError::InvalidArgument(format!(
+                            "{UNEXPECTED_STATE} failed to cast child fruit."
+                        ))
                     })
             })
             .collect::<crate::Result<_>>()?;
@@ -285,13 +285,37 @@ pub enum ComparatorEnum {
 
 impl From<Order> for ComparatorEnum {
     fn from(order: Order) -> Self {
-        match order {
-            Order::Asc => ComparatorEnum::ReverseNoneLower,
-            Order::Desc => ComparatorEnum::Natural,
+        ComparatorEnum::for_order_and_missing(order, MissingValuePlacement::Last)
+    }
+}
+
+impl ComparatorEnum {
+    /// Returns the comparator sorting values in the given `order`, and placing documents
+    /// without any value according to `missing_placement`.
+    pub fn for_order_and_missing(
+        order: Order,
+        missing_placement: MissingValuePlacement,
+    ) -> ComparatorEnum {
+        match (order, missing_placement) {
+            (Order::Asc, MissingValuePlacement::First) => ComparatorEnum::Reverse,
+            (Order::Asc, MissingValuePlacement::Last) => ComparatorEnum::ReverseNoneLower,
+            (Order::Desc, MissingValuePlacement::First) => ComparatorEnum::NaturalNoneHigher,
+            (Order::Desc, MissingValuePlacement::Last) => ComparatorEnum::Natural,
         }
     }
 }
 
+/// Where documents without a value should appear in a sorted result,
+/// regardless of the sort order.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub enum MissingValuePlacement {
+    /// Documents without a value appear before all other documents.
+    First,
+    /// Documents without a value appear after all other documents.
+    #[default]
+    Last,
+}
+
 impl<T> Comparator<T> for ComparatorEnum
 where
     ReverseNoneIsLowerComparator: Comparator<T>,
@@ -83,8 +83,9 @@ fn merge_top_k<D: Ord, TSortKey: Clone + std::fmt::Debug, C: Comparator<TSortKey
     }
     let mut top_collector: TopNComputer<TSortKey, D, C> =
         TopNComputer::new_with_comparator(doc_range.end, comparator);
+    // Segment results are not sorted by doc address.
     for (sort_key, doc) in sort_key_docs {
-        top_collector.push(sort_key, doc);
+        top_collector.push_unordered(sort_key, doc);
     }
     top_collector
         .into_sorted_vec()
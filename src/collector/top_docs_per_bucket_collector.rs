@@ -0,0 +1,428 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use columnar::Column;
+
+use crate::collector::sort_key::NaturalComparator;
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::schema::Type;
+use crate::{DateTime, DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+const DEFAULT_MAX_BUCKETS: usize = 10_000;
+
+/// Collects the `k` best scored documents of every time bucket, e.g. the top 10 documents of
+/// each day.
+///
+/// The bucket of a document is derived from the first value of a date fast field: buckets are
+/// the intervals `[n * interval, (n + 1) * interval)` since the Unix epoch, and are identified by
+/// their start. Documents without a value in the date field are ignored.
+///
+/// The number of buckets is bounded by `max_buckets`. Once this limit is reached, only the
+/// earliest buckets are kept and the resulting [`TopDocsPerBucket`] is flagged as truncated.
+/// The kept buckets are exact.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::TopDocsPerBucketCollector;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, TEXT};
+/// use tantivy::{doc, DateTime, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// let day = 24 * 3_600;
+/// index_writer.add_document(doc!(
+///     title => "rust rust",
+///     published => DateTime::from_timestamp_secs(3_600),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "rust and tantivy",
+///     published => DateTime::from_timestamp_secs(7_200),
+/// ))?;
+/// index_writer.add_document(doc!(
+///     title => "rust",
+///     published => DateTime::from_timestamp_secs(day + 3_600),
+/// ))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("rust")?;
+/// let collector =
+///     TopDocsPerBucketCollector::new("published", Duration::from_secs(day as u64), 1);
+/// let top_docs_per_day = searcher.search(&query, &collector)?;
+/// assert_eq!(top_docs_per_day.num_buckets(), 2);
+/// assert_eq!(top_docs_per_day.top_docs(DateTime::from_timestamp_secs(0)).len(), 1);
+/// assert_eq!(top_docs_per_day.top_docs(DateTime::from_timestamp_secs(day)).len(), 1);
+/// assert!(!top_docs_per_day.is_truncated());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TopDocsPerBucketCollector {
+    date_field: String,
+    interval_nanos: i64,
+    k: usize,
+    max_buckets: usize,
+}
+
+impl TopDocsPerBucketCollector {
+    /// Creates a collector keeping the `k` best scored documents of every bucket of `interval`
+    /// of the date field `date_field`, with at most 10,000 buckets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero or does not fit in an `i64` of nanoseconds.
+    pub fn new(
+        date_field: impl Into<String>,
+        interval: Duration,
+        k: usize,
+    ) -> TopDocsPerBucketCollector {
+        let interval_nanos = i64::try_from(interval.as_nanos())
+            .expect("The bucket interval does not fit in an i64 of nanoseconds.");
+        assert!(interval_nanos > 0, "The bucket interval must not be zero.");
+        TopDocsPerBucketCollector {
+            date_field: date_field.into(),
+            interval_nanos,
+            k,
+            max_buckets: DEFAULT_MAX_BUCKETS,
+        }
+    }
+
+    /// Sets the maximum number of buckets.
+    pub fn with_max_buckets(mut self, max_buckets: usize) -> TopDocsPerBucketCollector {
+        self.max_buckets = max_buckets;
+        self
+    }
+}
+
+/// Top documents of every bucket, computed by the [`TopDocsPerBucketCollector`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TopDocsPerBucket {
+    buckets: BTreeMap<DateTime, Vec<(Score, DocAddress)>>,
+    truncated: bool,
+}
+
+impl TopDocsPerBucket {
+    /// Returns the top documents of the bucket starting at `bucket`, sorted by decreasing score.
+    ///
+    /// Returns an empty slice if no document matched in this bucket.
+    pub fn top_docs(&self, bucket: DateTime) -> &[(Score, DocAddress)] {
+        self.buckets
+            .get(&bucket)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Iterates over the non-empty buckets as `(bucket start, top documents)`, ordered by bucket.
+    pub fn buckets(&self) -> impl Iterator<Item = (DateTime, &[(Score, DocAddress)])> {
+        self.buckets
+            .iter()
+            .map(|(bucket, top_docs)| (*bucket, top_docs.as_slice()))
+    }
+
+    /// Returns the number of non-empty buckets.
+    pub fn num_buckets(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Returns true if the latest buckets were dropped because of the bucket limit.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Collector for TopDocsPerBucketCollector {
+    type Fruit = TopDocsPerBucket;
+    type Child = TopDocsPerBucketSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<TopDocsPerBucketSegmentCollector> {
+        let schema = segment_reader.schema();
+        let field = schema
+            .get_field(&self.date_field)
+            .map_err(|_| TantivyError::FieldNotFound(self.date_field.clone()))?;
+        let field_entry = schema.get_field_entry(field);
+        if field_entry.field_type().value_type() != Type::Date {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a date field.",
+                self.date_field
+            )));
+        }
+        if !field_entry.is_fast() {
+            return Err(FastFieldNotAvailableError::new(field_entry).into());
+        }
+        let column = segment_reader
+            .fast_fields()
+            .column_opt::<DateTime>(&self.date_field)?;
+        Ok(TopDocsPerBucketSegmentCollector {
+            column,
+            segment_ord,
+            interval_nanos: self.interval_nanos,
+            k: self.k,
+            max_buckets: self.max_buckets,
+            buckets: BTreeMap::new(),
+            truncated: false,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<TopDocsPerBucket>,
+    ) -> crate::Result<TopDocsPerBucket> {
+        let mut truncated = false;
+        let mut computers: BTreeMap<DateTime, TopNComputer<Score, DocAddress, NaturalComparator>> =
+            BTreeMap::new();
+        for segment_fruit in segment_fruits {
+            truncated |= segment_fruit.truncated;
+            for (bucket, top_docs) in segment_fruit.buckets {
+                let computer = computers.entry(bucket).or_insert_with(|| {
+                    TopNComputer::new_with_comparator(self.k, NaturalComparator)
+                });
+                for (score, doc_address) in top_docs {
+                    computer.push_unordered(score, doc_address);
+                }
+            }
+        }
+        // Every segment kept its earliest buckets, so the earliest merged buckets are complete.
+        if computers.len() > self.max_buckets {
+            computers = computers.into_iter().take(self.max_buckets).collect();
+            truncated = true;
+        }
+        let buckets = computers
+            .into_iter()
+            .map(|(bucket, computer)| {
+                let top_docs = computer
+                    .into_sorted_vec()
+                    .into_iter()
+                    .map(|comparable_doc| (comparable_doc.sort_key, comparable_doc.doc))
+                    .collect();
+                (bucket, top_docs)
+            })
+            .collect();
+        Ok(TopDocsPerBucket { buckets, truncated })
+    }
+}
+
+/// Segment collector of the [`TopDocsPerBucketCollector`].
+pub struct TopDocsPerBucketSegmentCollector {
+    // `None` if the date field has no values in the segment.
+    column: Option<Column<DateTime>>,
+    segment_ord: SegmentOrdinal,
+    interval_nanos: i64,
+    k: usize,
+    max_buckets: usize,
+    // Keyed by the bucket start, in nanoseconds.
+    buckets: BTreeMap<i64, TopNComputer<Score, DocId, NaturalComparator>>,
+    truncated: bool,
+}
+
+impl TopDocsPerBucketSegmentCollector {
+    fn bucket_computer(
+        &mut self,
+        bucket: i64,
+    ) -> Option<&mut TopNComputer<Score, DocId, NaturalComparator>> {
+        if !self.buckets.contains_key(&bucket) {
+            if self.buckets.len() >= self.max_buckets {
+                self.truncated = true;
+                // Make room for the new bucket if it is earlier than the latest one.
+                match self.buckets.last_key_value() {
+                    Some((&last_bucket, _)) if bucket < last_bucket => {
+                        self.buckets.remove(&last_bucket);
+                    }
+                    _ => return None,
+                }
+            }
+            self.buckets.insert(
+                bucket,
+                TopNComputer::new_with_comparator(self.k, NaturalComparator),
+            );
+        }
+        self.buckets.get_mut(&bucket)
+    }
+}
+
+impl SegmentCollector for TopDocsPerBucketSegmentCollector {
+    type Fruit = TopDocsPerBucket;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let Some(date) = self.column.as_ref().and_then(|column| column.first(doc)) else {
+            return;
+        };
+        let timestamp_nanos = date.into_timestamp_nanos();
+        let bucket = timestamp_nanos - timestamp_nanos.rem_euclid(self.interval_nanos);
+        if let Some(computer) = self.bucket_computer(bucket) {
+            computer.push(score, doc);
+        }
+    }
+
+    fn harvest(self) -> TopDocsPerBucket {
+        let segment_ord = self.segment_ord;
+        let buckets = self
+            .buckets
+            .into_iter()
+            .map(|(bucket, computer)| {
+                let top_docs = computer
+                    .into_vec()
+                    .into_iter()
+                    .map(|comparable_doc| {
+                        (
+                            comparable_doc.sort_key,
+                            DocAddress::new(segment_ord, comparable_doc.doc),
+                        )
+                    })
+                    .collect();
+                (DateTime::from_timestamp_nanos(bucket), top_docs)
+            })
+            .collect();
+        TopDocsPerBucket {
+            buckets,
+            truncated: self.truncated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+    use std::time::Duration;
+
+    use super::TopDocsPerBucketCollector;
+    use crate::collector::TopDocs;
+    use crate::query::{BooleanQuery, BoostQuery, Occur, Query, QueryParser, RangeQuery};
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{DateTime, Index, IndexWriter, Term};
+
+    const HOUR: i64 = 3_600;
+    const DAY: i64 = 24 * HOUR;
+
+    fn top_docs_per_bucket_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let date = schema_builder.add_date_field("date", INDEXED | FAST);
+        // Never populated.
+        schema_builder.add_date_field("other_date", FAST);
+        schema_builder.add_text_field("category", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..100i64 {
+            let num_words = 1 + (i * 7) % 5;
+            let words = vec!["tantivy"; num_words as usize].join(" ");
+            index_writer.add_document(doc!(
+                text => format!("{words} filler"),
+                // Documents before the epoch land in negative buckets.
+                date => DateTime::from_timestamp_secs((i % 7 - 2) * DAY + (i * 5) % 24 * HOUR),
+            ))?;
+            if i % 30 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.add_document(doc!(text => "tantivy without a date"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn range_filtered_query(
+        index: &Index,
+        query: &dyn Query,
+        start: DateTime,
+        end: DateTime,
+    ) -> BooleanQuery {
+        let date = index.schema().get_field("date").unwrap();
+        let range_query = RangeQuery::new(
+            Bound::Included(Term::from_field_date(date, start)),
+            Bound::Excluded(Term::from_field_date(date, end)),
+        );
+        // The range query does not contribute to the score.
+        BooleanQuery::new(vec![
+            (Occur::Must, query.box_clone()),
+            (
+                Occur::Must,
+                Box::new(BoostQuery::new(Box::new(range_query), 0.0)),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_top_docs_per_bucket_matches_range_filtered_top_docs() -> crate::Result<()> {
+        let index = top_docs_per_bucket_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("tantivy")?;
+        let collector = TopDocsPerBucketCollector::new("date", Duration::from_secs(DAY as u64), 3);
+        let top_docs_per_bucket = searcher.search(&query, &collector)?;
+        assert!(!top_docs_per_bucket.is_truncated());
+        assert_eq!(top_docs_per_bucket.num_buckets(), 7);
+        let buckets: Vec<DateTime> = top_docs_per_bucket
+            .buckets()
+            .map(|(bucket, _)| bucket)
+            .collect();
+        assert_eq!(buckets[0], DateTime::from_timestamp_secs(-2 * DAY));
+        for bucket_start_secs in [-2 * DAY, 0, 3 * DAY] {
+            let start = DateTime::from_timestamp_secs(bucket_start_secs);
+            let end = DateTime::from_timestamp_secs(bucket_start_secs + DAY);
+            let expected = searcher.search(
+                &range_filtered_query(&index, query.as_ref(), start, end),
+                &TopDocs::with_limit(3).order_by_score(),
+            )?;
+            assert_eq!(expected.len(), 3);
+            assert_eq!(top_docs_per_bucket.top_docs(start), expected.as_slice());
+        }
+        assert!(top_docs_per_bucket
+            .top_docs(DateTime::from_timestamp_secs(10 * DAY))
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_per_bucket_truncated() -> crate::Result<()> {
+        let index = top_docs_per_bucket_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("tantivy")?;
+        let collector = TopDocsPerBucketCollector::new("date", Duration::from_secs(DAY as u64), 2);
+        let full = searcher.search(&query, &collector)?;
+        let truncated = searcher.search(&query, &collector.with_max_buckets(3))?;
+        assert!(truncated.is_truncated());
+        assert_eq!(truncated.num_buckets(), 3);
+        // The earliest buckets are kept, and are complete.
+        let expected: Vec<_> = full.buckets().take(3).collect();
+        assert_eq!(truncated.buckets().collect::<Vec<_>>(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_docs_per_bucket_missing_field() -> crate::Result<()> {
+        let index = top_docs_per_bucket_test_index()?;
+        let searcher = index.reader()?.searcher();
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("tantivy")?;
+        let interval = Duration::from_secs(DAY as u64);
+        let top_docs_per_bucket = searcher.search(
+            &query,
+            &TopDocsPerBucketCollector::new("other_date", interval, 3),
+        )?;
+        assert_eq!(top_docs_per_bucket.num_buckets(), 0);
+        for field_name in ["category", "unknown"] {
+            assert!(searcher
+                .search(
+                    &query,
+                    &TopDocsPerBucketCollector::new(field_name, interval, 3)
+                )
+                .is_err());
+        }
+        Ok(())
+    }
+}
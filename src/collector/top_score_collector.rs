@@ -6,12 +6,12 @@ use serde::{Deserialize, Serialize};
 
 use super::Collector;
 use crate::collector::sort_key::{
-    Comparator, ComparatorEnum, NaturalComparator, ReverseComparator, SortBySimilarityScore,
-    SortByStaticFastValue, SortByString,
+    Comparator, ComparatorEnum, MissingValuePlacement, NaturalComparator, ReverseComparator,
+    SortBySimilarityScore, SortByStaticFastValue, SortByString,
 };
 use crate::collector::sort_key_top_collector::TopBySortKeyCollector;
 use crate::collector::top_collector::ComparableDoc;
-use crate::collector::{SegmentSortKeyComputer, SortKeyComputer};
+use crate::collector::{DiversifiedTopDocsCollector, SegmentSortKeyComputer, SortKeyComputer};
 use crate::fastfield::FastValue;
 use crate::{DocAddress, DocId, Order, Score, SegmentReader};
 
@@ -25,7 +25,12 @@ use crate::{DocAddress, DocId, Order, Score, SegmentReader};
 ///
 /// This collector guarantees a stable sorting in case of a tie on the
 /// document score/sort key: The document address (`DocAddress`) is used as a tie breaker.
-/// In case of a tie on the sort key, documents are always sorted by ascending `DocAddress`.
+/// In case of a tie on the sort key, documents are always sorted by ascending `DocAddress`,
+/// that is by ascending segment ordinal, then by ascending `DocId`.
+///
+/// The ordering does not depend on the [`Executor`](crate::Executor) of the searcher: the
+/// same query over the same searcher always returns the documents in the same order, whether
+/// segments are collected sequentially or concurrently.
 ///
 /// ```rust
 /// use tantivy::collector::TopDocs;
@@ -210,6 +215,11 @@ impl TopDocs {
     /// }
     /// ```
     ///
+    /// Documents without a value for the field get a `None` sort key, and appear last,
+    /// regardless of the order. Use
+    /// [.order_by_u64_field_with_missing(...)](TopDocs::order_by_u64_field_with_missing)
+    /// to put them first instead.
+    ///
     /// # See also
     ///
     /// To comfortably work with `u64`s, `i64`s, `f64`s, or `date`s, please refer to
@@ -219,7 +229,18 @@ impl TopDocs {
         field: impl ToString,
         order: Order,
     ) -> impl Collector<Fruit = Vec<(Option<u64>, DocAddress)>> {
-        self.order_by((SortByStaticFastValue::for_field(field), order))
+        self.order_by_u64_field_with_missing(field, order, MissingValuePlacement::Last)
+    }
+
+    /// Like [.order_by_u64_field(...)](TopDocs::order_by_u64_field), but lets the caller
+    /// decide whether documents without a value should appear first or last.
+    pub fn order_by_u64_field_with_missing(
+        self,
+        field: impl ToString,
+        order: Order,
+        missing_placement: MissingValuePlacement,
+    ) -> impl Collector<Fruit = Vec<(Option<u64>, DocAddress)>> {
+        self.order_by_fast_field_with_missing(field, order, missing_placement)
     }
 
     /// Order docs by decreasing BM25 similarity score.
@@ -227,6 +248,18 @@ impl TopDocs {
         TopBySortKeyCollector::new(SortBySimilarityScore, self.doc_range())
     }
 
+    /// Order docs by decreasing score, with at most `max_per_key` docs per value of the `str`
+    /// fast field `field`.
+    ///
+    /// See [`DiversifiedTopDocsCollector`].
+    pub fn with_diversity(
+        self,
+        field: impl Into<String>,
+        max_per_key: usize,
+    ) -> DiversifiedTopDocsCollector {
+        DiversifiedTopDocsCollector::new(field.into(), max_per_key, self.limit, self.offset)
+    }
+
     /// Set top-K to rank documents by a given fast field.
     ///
     /// If the field is not a fast field, or its field type does not match the generic type, this
@@ -308,7 +341,30 @@ impl TopDocs {
         self.order_by((SortByStaticFastValue::for_field(fast_field), order))
     }
 
+    /// Like [.order_by_fast_field(...)](TopDocs::order_by_fast_field), but lets the caller
+    /// decide whether documents without a value should appear first or last.
+    ///
+    /// `order_by_fast_field` is equivalent to using [`MissingValuePlacement::Last`].
+    pub fn order_by_fast_field_with_missing<TFastValue>(
+        self,
+        fast_field: impl ToString,
+        order: Order,
+        missing_placement: MissingValuePlacement,
+    ) -> impl Collector<Fruit = Vec<(Option<TFastValue>, DocAddress)>>
+    where
+        TFastValue: FastValue,
+        ComparatorEnum: Comparator<Option<TFastValue>>,
+    {
+        let comparator = ComparatorEnum::for_order_and_missing(order, missing_placement);
+        self.order_by((SortByStaticFastValue::for_field(fast_field), comparator))
+    }
+
     /// Like `order_by_fast_field`, but for a `String` fast field.
+    ///
+    /// The sort key of each hit is the value of the fast field, or `None` if the document has
+    /// no value, so that it can be displayed without fetching the stored document. The term
+    /// ordinals are compared during the collection, and only the ordinals of the top hits of each
+    /// segment are resolved to their string.
     pub fn order_by_string_fast_field(
         self,
         fast_field: impl ToString,
@@ -632,6 +688,22 @@ where
         self.append_doc(doc, sort_key);
     }
 
+    /// Push a new document to the top n, in any `DocId`/`DocAddress` order.
+    ///
+    /// Unlike `push`, documents tied with the threshold are kept until the next truncation,
+    /// which breaks the tie by ascending `DocId`/`DocAddress`. The top n elements therefore do
+    /// not depend on the order of the calls. This is used to merge the results of segments,
+    /// which are not sorted.
+    #[inline]
+    pub(crate) fn push_unordered(&mut self, sort_key: TSortKey, doc: D) {
+        if let Some(last_median) = &self.threshold {
+            if self.comparator.compare(&sort_key, last_median) == Ordering::Less {
+                return;
+            }
+        }
+        self.append_doc(doc, sort_key);
+    }
+
     // Append a document to the top n.
     //
     // At this point, we need to have established that the doc is above the threshold.
@@ -703,7 +775,9 @@ mod tests {
     use proptest::prelude::*;
 
     use super::{TopDocs, TopNComputer};
-    use crate::collector::sort_key::{ComparatorEnum, NaturalComparator, ReverseComparator};
+    use crate::collector::sort_key::{
+        ComparatorEnum, MissingValuePlacement, NaturalComparator, ReverseComparator,
+    };
     use crate::collector::top_collector::ComparableDoc;
     use crate::collector::{Collector, DocSetCollector};
     use crate::query::{AllQuery, Query, QueryParser};
@@ -1217,6 +1291,80 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_field_collector_missing_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_u64_field(SIZE, FAST);
+        let schema = schema_builder.build();
+        let (index, query) = index("beer", title, schema, |index_writer| {
+            index_writer
+                .add_document(doc!(title => "bottle of beer", size => 12u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "keg of beer"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "pint of beer", size => 0u64))
+                .unwrap();
+        });
+        let searcher = index.reader()?.searcher();
+
+        let size_column = searcher.segment_reader(0).fast_fields().u64(SIZE)?;
+        assert_eq!(size_column.first(1), None);
+        assert_eq!(size_column.first(2), Some(0));
+        assert_eq!(size_column.first_or(1, 42), 42);
+        assert_eq!(size_column.first_or(0, 42), 12);
+
+        let search = |order: Order, missing_placement: MissingValuePlacement| {
+            let collector = TopDocs::with_limit(4).order_by_u64_field_with_missing(
+                SIZE,
+                order,
+                missing_placement,
+            );
+            searcher.search(&query, &collector).unwrap()
+        };
+        assert_eq!(
+            search(Order::Asc, MissingValuePlacement::Last),
+            vec![
+                (Some(0), DocAddress::new(0, 2)),
+                (Some(12), DocAddress::new(0, 0)),
+                (None, DocAddress::new(0, 1)),
+            ]
+        );
+        assert_eq!(
+            search(Order::Asc, MissingValuePlacement::First),
+            vec![
+                (None, DocAddress::new(0, 1)),
+                (Some(0), DocAddress::new(0, 2)),
+                (Some(12), DocAddress::new(0, 0)),
+            ]
+        );
+        assert_eq!(
+            search(Order::Desc, MissingValuePlacement::Last),
+            vec![
+                (Some(12), DocAddress::new(0, 0)),
+                (Some(0), DocAddress::new(0, 2)),
+                (None, DocAddress::new(0, 1)),
+            ]
+        );
+        assert_eq!(
+            search(Order::Desc, MissingValuePlacement::First),
+            vec![
+                (None, DocAddress::new(0, 1)),
+                (Some(12), DocAddress::new(0, 0)),
+                (Some(0), DocAddress::new(0, 2)),
+            ]
+        );
+        // `order_by_u64_field` keeps missing values last.
+        let collector = TopDocs::with_limit(4).order_by_u64_field(SIZE, Order::Asc);
+        assert_eq!(
+            searcher.search(&query, &collector)?,
+            search(Order::Asc, MissingValuePlacement::Last)
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_top_field_collector_datetime() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -1406,6 +1554,56 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_field_collector_string_values_match_stored_docs() -> crate::Result<()> {
+        use crate::schema::{Value, STRING};
+        use crate::TantivyDocument;
+
+        let mut schema_builder = Schema::builder();
+        let city = schema_builder.add_text_field("city", STRING | FAST | STORED);
+        let population = schema_builder.add_u64_field("population", STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for segment_cities in [
+            &[Some("tokyo"), None, Some("austin")][..],
+            &[Some("greenville"), Some("austin"), None, Some("paris")][..],
+        ] {
+            for (doc_id, city_opt) in segment_cities.iter().enumerate() {
+                let mut doc = doc!(population => doc_id as u64);
+                if let Some(city_value) = city_opt {
+                    doc.add_text(city, city_value);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        for order in [Order::Asc, Order::Desc] {
+            let top_docs = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(10).order_by_string_fast_field("city", order),
+            )?;
+            assert_eq!(top_docs.len(), 7);
+            assert_eq!(
+                top_docs
+                    .iter()
+                    .filter(|(city_opt, _)| city_opt.is_none())
+                    .count(),
+                2
+            );
+            for (city_opt, doc_address) in top_docs {
+                let stored_doc: TantivyDocument = searcher.doc(doc_address)?;
+                let stored_city = stored_doc
+                    .get_first(city)
+                    .and_then(|value| value.as_str())
+                    .map(str::to_string);
+                assert_eq!(city_opt, stored_city);
+            }
+        }
+        Ok(())
+    }
+
     proptest! {
         #[test]
         fn test_top_field_collect_string_prop(
@@ -1812,6 +2010,128 @@ mod tests {
 
         assert_eq!(&docs_limit_2, &docs_limit_3[..2],);
     }
+
+    proptest! {
+        #[test]
+        fn test_top_n_computer_push_unordered(
+            limit in 0..10_usize,
+            scores in proptest::collection::vec(0..4_u32, 0..100_usize),
+            rotation in 0..100_usize,
+        ) {
+            let mut computer: TopNComputer<u32, u32, NaturalComparator> =
+                TopNComputer::new_with_comparator(limit, NaturalComparator);
+            for (doc, &score) in scores.iter().enumerate() {
+                computer.push(score, doc as u32);
+            }
+            // Reversed and rotated, to push ties in descending doc order.
+            let mut unordered_docs: Vec<(u32, u32)> = scores
+                .iter()
+                .enumerate()
+                .map(|(doc, &score)| (score, doc as u32))
+                .rev()
+                .collect();
+            if !unordered_docs.is_empty() {
+                let len = unordered_docs.len();
+                unordered_docs.rotate_left(rotation % len);
+            }
+            let mut unordered_computer: TopNComputer<u32, u32, NaturalComparator> =
+                TopNComputer::new_with_comparator(limit, NaturalComparator);
+            for (score, doc) in unordered_docs {
+                unordered_computer.push_unordered(score, doc);
+            }
+            prop_assert_eq!(
+                computer.into_sorted_vec(),
+                unordered_computer.into_sorted_vec()
+            );
+        }
+    }
+
+    #[test]
+    fn test_top_docs_tie_breaking_with_multithread_executor() -> crate::Result<()> {
+        use crate::indexer::NoMergePolicy;
+        use crate::query::EnableScoring;
+        use crate::Executor;
+
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let texts = ["hello", "hello hello", "hello world", "hello hello hello"];
+        // Segments holding fewer documents than the limit shift the truncations of the merge
+        // to the middle of the results of the next segment.
+        for (segment_ord, num_docs) in [3, 50, 17, 50, 8, 50].into_iter().enumerate() {
+            for doc in 0..num_docs {
+                // Few distinct scores, shared by documents of every segment.
+                let text = texts[(doc * 7 + segment_ord * 3) % 4];
+                index_writer.add_document(doc!(text_field => text))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 6);
+        let query = QueryParser::for_index(&index, vec![text_field]).parse_query("hello")?;
+
+        // Without truncation, all of the documents are sorted by score, then by doc address.
+        let mut expected = searcher.search(&query, &TopDocs::with_limit(300).order_by_score())?;
+        expected.sort_by(|(left_score, left_doc), (right_score, right_doc)| {
+            right_score
+                .partial_cmp(left_score)
+                .unwrap()
+                .then(left_doc.cmp(right_doc))
+        });
+        let to_bits = |top_docs: &[(Score, DocAddress)]| -> Vec<(u32, DocAddress)> {
+            top_docs
+                .iter()
+                .map(|&(score, doc_address)| (score.to_bits(), doc_address))
+                .collect()
+        };
+        let executor = Executor::multi_thread(4, "tie-breaking-test")?;
+        let weight = query.weight(EnableScoring::enabled_from_searcher(&searcher))?;
+        for (limit, offset) in [(1, 0), (5, 0), (10, 3), (20, 35), (40, 0), (60, 100)] {
+            let collector = TopDocs::with_limit(limit)
+                .and_offset(offset)
+                .order_by_score();
+            let expected_bits = to_bits(&expected[offset..offset + limit]);
+            assert_eq!(
+                to_bits(&searcher.search(&query, &collector)?),
+                expected_bits
+            );
+            for _ in 0..20 {
+                let top_docs = searcher.search_with_executor(
+                    &query,
+                    &collector,
+                    &executor,
+                    EnableScoring::enabled_from_searcher(&searcher),
+                )?;
+                assert_eq!(to_bits(&top_docs), expected_bits);
+            }
+            // The merge does not depend on the order of the segment fruits either.
+            let segment_readers = searcher.segment_readers();
+            for rotation in 0..segment_readers.len() {
+                let mut segment_ords: Vec<usize> = (0..segment_readers.len()).collect();
+                segment_ords.rotate_left(rotation);
+                for _ in 0..2 {
+                    let segment_fruits = segment_ords
+                        .iter()
+                        .map(|&segment_ord| {
+                            collector.collect_segment(
+                                weight.as_ref(),
+                                segment_ord as u32,
+                                &segment_readers[segment_ord],
+                            )
+                        })
+                        .collect::<crate::Result<Vec<_>>>()?;
+                    assert_eq!(
+                        to_bits(&collector.merge_fruits(segment_fruits)?),
+                        expected_bits
+                    );
+                    segment_ords.reverse();
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
@@ -0,0 +1,243 @@
+use crate::fastfield::AliveBitSet;
+use crate::index::SegmentReader;
+use crate::query::score_combiner::DoNothingCombiner;
+use crate::query::{intersect_scorers, BufferedUnionScorer, Scorer, Weight};
+use crate::{DocId, DocSet, TantivyError};
+
+/// Number of consecutive doc ids making up a block, the unit of sampling of
+/// [`EstimateMode::Sampled`].
+const SAMPLE_BLOCK_NUM_DOCS: u32 = 512;
+
+/// Z-score of the two-sided 95% confidence interval of a normal distribution.
+const Z_SCORE_95: f64 = 1.96;
+
+/// Defines how [`Searcher::estimate_intersection`](crate::Searcher::estimate_intersection)
+/// and [`Searcher::estimate_union`](crate::Searcher::estimate_union) count matching documents.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EstimateMode {
+    /// Counts all of the matching documents.
+    Exact,
+    /// Only counts the matching documents of a fraction of the blocks of doc ids of each
+    /// segment, and extrapolates. The rate must be within `(0, 1]`.
+    Sampled(f64),
+}
+
+/// Confidence information about a sampled estimate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EstimateConfidence {
+    /// Number of alive documents within the sampled blocks.
+    pub num_sampled_docs: u64,
+    /// Number of matching documents within the sampled blocks.
+    pub num_sampled_matches: u64,
+    /// Standard error of the estimated count.
+    ///
+    /// Documents are assumed to match independently of each other. Matches clustered by doc
+    /// id make the actual error larger.
+    pub standard_error: f64,
+    /// Lower bound of the 95% confidence interval of the count.
+    pub lower_bound: u64,
+    /// Upper bound of the 95% confidence interval of the count.
+    pub upper_bound: u64,
+}
+
+/// Estimated number of documents matching a combination of queries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CardinalityEstimate {
+    /// Estimated count for each segment, ordered as the segment readers of the searcher.
+    pub segment_counts: Vec<u64>,
+    /// Estimated count over all segments.
+    pub count: u64,
+    /// Confidence information, for sampled estimates only.
+    pub confidence: Option<EstimateConfidence>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Combination {
+    Intersection,
+    Union,
+}
+
+/// Counts for one segment, before the extrapolation of sampled counts.
+#[derive(Default)]
+struct SegmentSample {
+    num_docs: u64,
+    num_sampled_docs: u64,
+    num_sampled_matches: u64,
+}
+
+impl SegmentSample {
+    fn estimated_count(&self) -> f64 {
+        if self.num_sampled_docs == 0 {
+            return 0.0;
+        }
+        self.num_sampled_matches as f64 * self.num_docs as f64 / self.num_sampled_docs as f64
+    }
+
+    /// Variance of the estimated count, with the finite population correction.
+    fn variance(&self) -> f64 {
+        if self.num_sampled_docs == 0 || self.num_sampled_docs >= self.num_docs {
+            return 0.0;
+        }
+        let n = self.num_sampled_docs as f64;
+        let population = self.num_docs as f64;
+        let match_rate = self.num_sampled_matches as f64 / n;
+        population * population * match_rate * (1.0 - match_rate) / n * (1.0 - n / population)
+    }
+}
+
+pub(crate) fn estimate_cardinality(
+    segment_readers: &[SegmentReader],
+    weights: &[Box<dyn Weight>],
+    combination: Combination,
+    mode: EstimateMode,
+) -> crate::Result<CardinalityEstimate> {
+    if weights.is_empty() {
+        return Err(TantivyError::InvalidArgument(
+            "At least one query is required to estimate a cardinality".to_string(),
+        ));
+    }
+    let sample_rate_opt = match mode {
+        EstimateMode::Exact => None,
+        EstimateMode::Sampled(rate) if rate > 0.0 && rate <= 1.0 => Some(rate),
+        EstimateMode::Sampled(rate) => {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Sample rate must be within (0, 1], got {rate}"
+            )));
+        }
+    };
+    let mut segment_samples = Vec::with_capacity(segment_readers.len());
+    for (segment_ord, segment_reader) in segment_readers.iter().enumerate() {
+        let scorers = weights
+            .iter()
+            .map(|weight| weight.scorer(segment_reader, 1.0))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let max_doc = segment_reader.max_doc();
+        let mut docset: Box<dyn Scorer> = match combination {
+            Combination::Intersection => intersect_scorers(scorers, max_doc),
+            Combination::Union => Box::new(BufferedUnionScorer::build(
+                scorers,
+                DoNothingCombiner::default,
+                max_doc,
+            )),
+        };
+        let segment_sample = match sample_rate_opt {
+            None => {
+                let num_matches = if let Some(alive_bitset) = segment_reader.alive_bitset() {
+                    docset.count(alive_bitset)
+                } else {
+                    docset.count_including_deleted()
+                };
+                let num_docs = u64::from(segment_reader.num_docs());
+                SegmentSample {
+                    num_docs,
+                    num_sampled_docs: num_docs,
+                    num_sampled_matches: u64::from(num_matches),
+                }
+            }
+            Some(sample_rate) => {
+                sample_segment(segment_reader, segment_ord, docset.as_mut(), sample_rate)
+            }
+        };
+        segment_samples.push(segment_sample);
+    }
+    let segment_counts: Vec<u64> = segment_samples
+        .iter()
+        .map(|segment_sample| segment_sample.estimated_count().round() as u64)
+        .collect();
+    let count = segment_counts.iter().sum();
+    let confidence = sample_rate_opt.map(|_| {
+        let num_docs: u64 = segment_samples.iter().map(|sample| sample.num_docs).sum();
+        let num_sampled_docs = segment_samples
+            .iter()
+            .map(|sample| sample.num_sampled_docs)
+            .sum();
+        let num_sampled_matches: u64 = segment_samples
+            .iter()
+            .map(|sample| sample.num_sampled_matches)
+            .sum();
+        let standard_error = segment_samples
+            .iter()
+            .map(SegmentSample::variance)
+            .sum::<f64>()
+            .sqrt();
+        let margin = Z_SCORE_95 * standard_error;
+        // Matches observed in the sample are certain.
+        let lower_bound =
+            ((count as f64 - margin).floor().max(0.0) as u64).max(num_sampled_matches);
+        let upper_bound = ((count as f64 + margin).ceil() as u64).min(num_docs);
+        EstimateConfidence {
+            num_sampled_docs,
+            num_sampled_matches,
+            standard_error,
+            lower_bound,
+            upper_bound,
+        }
+    });
+    Ok(CardinalityEstimate {
+        segment_counts,
+        count,
+        confidence,
+    })
+}
+
+/// Counts the matches of `docset` within a pseudo-random selection of the blocks of the
+/// segment.
+fn sample_segment(
+    segment_reader: &SegmentReader,
+    segment_ord: usize,
+    docset: &mut dyn Scorer,
+    sample_rate: f64,
+) -> SegmentSample {
+    let alive_bitset = segment_reader.alive_bitset();
+    let mut segment_sample = SegmentSample {
+        num_docs: u64::from(segment_reader.num_docs()),
+        ..Default::default()
+    };
+    let max_doc = segment_reader.max_doc();
+    let num_blocks = max_doc.div_ceil(SAMPLE_BLOCK_NUM_DOCS);
+    let mut sampled_blocks: Vec<u32> = (0..num_blocks)
+        .filter(|&block_ord| is_block_sampled(segment_ord, block_ord, sample_rate))
+        .collect();
+    // Small segments may not have any block selected, but still need to be sampled.
+    if sampled_blocks.is_empty() && num_blocks > 0 {
+        sampled_blocks.push(0);
+    }
+    for block_ord in sampled_blocks {
+        let block_start = block_ord * SAMPLE_BLOCK_NUM_DOCS;
+        let block_end = (block_start + SAMPLE_BLOCK_NUM_DOCS).min(max_doc);
+        segment_sample.num_sampled_docs +=
+            u64::from(num_alive_docs(alive_bitset, block_start, block_end));
+        // Once the docset is exhausted, `doc` is `TERMINATED`, and no more docs are counted.
+        let mut doc = docset.doc();
+        if doc < block_start {
+            doc = docset.seek(block_start);
+        }
+        while doc < block_end {
+            if alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+                segment_sample.num_sampled_matches += 1;
+            }
+            doc = docset.advance();
+        }
+    }
+    segment_sample
+}
+
+fn num_alive_docs(alive_bitset: Option<&AliveBitSet>, start: DocId, end: DocId) -> u32 {
+    match alive_bitset {
+        Some(alive_bitset) => (start..end)
+            .filter(|&doc| alive_bitset.is_alive(doc))
+            .count() as u32,
+        None => end - start,
+    }
+}
+
+/// Deterministically selects a fraction `sample_rate` of the blocks.
+fn is_block_sampled(segment_ord: usize, block_ord: u32, sample_rate: f64) -> bool {
+    // splitmix64 finalizer, spreading consecutive block ordinals uniformly.
+    let mut hash =
+        ((segment_ord as u64) << 32 | u64::from(block_ord)).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    hash ^= hash >> 31;
+    sample_rate >= 1.0 || (hash as f64) < sample_rate * (u64::MAX as f64)
+}
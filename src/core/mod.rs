@@ -1,14 +1,22 @@
+mod cardinality_estimate;
 mod executor;
 #[doc(hidden)]
 pub mod json_utils;
+pub(crate) mod search_options;
+mod search_session;
 pub mod searcher;
+mod stable_doc_handle;
 
 use std::path::Path;
 
 use once_cell::sync::Lazy;
 
+pub use self::cardinality_estimate::{CardinalityEstimate, EstimateConfidence, EstimateMode};
 pub use self::executor::Executor;
+pub use self::search_options::SearchOptions;
+pub use self::search_session::{SearchSession, SessionFilter};
 pub use self::searcher::{Searcher, SearcherGeneration};
+pub use self::stable_doc_handle::StableDocHandle;
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+use crate::TantivyError;
+
+/// Options of a search, see
+/// [`Searcher::search_with_options`](crate::Searcher::search_with_options).
+///
+/// The deadlines of the two phases of a search are distinct, so that they can be tuned
+/// separately:
+/// - the rewrite phase creates the weight of the query. Queries matching many terms, such as
+///   [`RegexQuery`](crate::query::RegexQuery) or [`FuzzyTermQuery`](crate::query::FuzzyTermQuery),
+///   also enumerate the matching terms of each segment in this phase. Exceeding its deadline fails
+///   the search with [`TantivyError::RewriteDeadlineExceeded`].
+/// - the collection phase pushes the matching documents to the collector. Exceeding its deadline
+///   fails the search with [`TantivyError::CollectionDeadlineExceeded`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Deadline of the rewrite phase, if any.
+    pub rewrite_deadline: Option<Instant>,
+    /// Deadline of the collection phase, if any.
+    pub collection_deadline: Option<Instant>,
+}
+
+impl SearchOptions {
+    /// Sets the deadline of the rewrite phase to `timeout` from now.
+    #[must_use]
+    pub fn with_rewrite_timeout(mut self, timeout: Duration) -> SearchOptions {
+        self.rewrite_deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    /// Sets the deadline of the collection phase to `timeout` from now.
+    #[must_use]
+    pub fn with_collection_timeout(mut self, timeout: Duration) -> SearchOptions {
+        self.collection_deadline = Some(Instant::now() + timeout);
+        self
+    }
+}
+
+/// Returns true if the deadline is set, and passed.
+pub(crate) fn is_deadline_exceeded(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Returns an error if the rewrite deadline is set, and passed.
+pub(crate) fn check_rewrite_deadline(rewrite_deadline: Option<Instant>) -> crate::Result<()> {
+    if is_deadline_exceeded(rewrite_deadline) {
+        return Err(TantivyError::RewriteDeadlineExceeded);
+    }
+    Ok(())
+}
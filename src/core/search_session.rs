@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use common::BitSet;
+
+use crate::collector::Collector;
+use crate::index::{SegmentId, SegmentReader};
+use crate::query::{
+    does_not_match, BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::Term;
+use crate::store::CacheStats;
+use crate::{DocId, DocSet, Score, Searcher, TERMINATED};
+
+/// Memory budget of the filter doc sets of a session, unless set with
+/// [`SearchSession::with_memory_budget`].
+const DEFAULT_MEMORY_BUDGET_NUM_BYTES: usize = 50_000_000;
+
+#[derive(Default)]
+struct CacheCounters {
+    cache_hits: AtomicUsize,
+    cache_misses: AtomicUsize,
+}
+
+impl CacheCounters {
+    fn record(&self, is_hit: bool) {
+        let counter = if is_hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn cache_stats(&self, num_entries: usize) -> CacheStats {
+        CacheStats {
+            num_entries,
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Runs several queries on the same [`Searcher`], sharing the work they have in common.
+///
+/// A session is created with [`Searcher::session`], and lives as long as the searcher it
+/// borrows, so that all of its searches see the same snapshot of the index.
+///
+/// - The weight of each query searched in the session is cached, so that searching the same
+///   query again, for instance with another collector, does not create its weight again. The
+///   weights are kept until the session is dropped.
+/// - The filters created with [`SearchSession::filter`] cache the documents they match in each
+///   segment, so that the queries sharing a filter only evaluate it once. These doc sets take
+///   one bit per document of the segment, and are only cached within the memory budget of the
+///   session. Past it, the filter is evaluated again by each search.
+///
+/// Queries are shared as `Arc<dyn Query>`, and told apart by the identity of their `Arc`: the
+/// clones of an `Arc` are the same query, while two queries created separately are distinct,
+/// even if they are equal. The session keeps the queries it caches alive.
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use tantivy::collector::{Count, DocSetCollector};
+/// use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, STRING, TEXT};
+/// use tantivy::{doc, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let lang = schema_builder.add_text_field("lang", STRING);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea", lang => "en"))?;
+/// index_writer.add_document(doc!(title => "Le Vieil Homme et la Mer", lang => "fr"))?;
+/// index_writer.add_document(doc!(title => "The Sea Wolf", lang => "en"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let session = searcher.session();
+/// let english: Arc<dyn Query> = Arc::new(session.filter(Arc::new(TermQuery::new(
+///     Term::from_field_text(lang, "en"),
+///     IndexRecordOption::Basic,
+/// )))?);
+/// let sea: Arc<dyn Query> = Arc::new(TermQuery::new(
+///     Term::from_field_text(title, "sea"),
+///     IndexRecordOption::Basic,
+/// ));
+/// let english_sea: Arc<dyn Query> = Arc::new(BooleanQuery::new(vec![
+///     (Occur::Must, english.box_clone()),
+///     (Occur::Must, sea.box_clone()),
+/// ]));
+/// assert_eq!(session.search(&english, &Count)?, 2);
+/// // The documents matching `english` are reused.
+/// assert_eq!(session.search(&english_sea, &Count)?, 2);
+/// assert_eq!(session.search(&sea, &DocSetCollector)?.len(), 2);
+/// assert_eq!(session.filter_cache_stats().cache_hits, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SearchSession<'a> {
+    searcher: &'a Searcher,
+    // The cached query is kept, so that its address is not reused while it is a key.
+    weights: Mutex<HashMap<(QueryKey, bool), CachedWeight>>,
+    weight_counters: CacheCounters,
+    filters: Mutex<HashMap<QueryKey, SessionFilter>>,
+    filter_cache: Arc<FilterCache>,
+}
+
+/// Identity of a query shared as an `Arc`: the address of its allocation.
+type QueryKey = usize;
+
+/// A cached weight, along with the query it was built from.
+type CachedWeight = (Arc<dyn Query>, Arc<dyn Weight>);
+
+fn query_key(query: &Arc<dyn Query>) -> QueryKey {
+    Arc::as_ptr(query) as *const () as usize
+}
+
+impl<'a> SearchSession<'a> {
+    pub(crate) fn new(searcher: &'a Searcher) -> SearchSession<'a> {
+        SearchSession {
+            searcher,
+            weights: Mutex::default(),
+            weight_counters: CacheCounters::default(),
+            filters: Mutex::default(),
+            filter_cache: Arc::new(FilterCache::with_memory_budget(
+                DEFAULT_MEMORY_BUDGET_NUM_BYTES,
+            )),
+        }
+    }
+
+    /// Sets the number of bytes the doc sets of the filters of the session may take.
+    ///
+    /// The default budget is 50MB.
+    #[must_use]
+    pub fn with_memory_budget(mut self, memory_budget_num_bytes: usize) -> SearchSession<'a> {
+        self.filter_cache = Arc::new(FilterCache::with_memory_budget(memory_budget_num_bytes));
+        self.filters = Mutex::default();
+        self
+    }
+
+    /// The searcher of the session.
+    pub fn searcher(&self) -> &'a Searcher {
+        self.searcher
+    }
+
+    /// Same as [`Searcher::search`], reusing the weight of `query` if it was already searched
+    /// with a collector requiring scoring, or not, like `collector`.
+    pub fn search<C: Collector>(
+        &self,
+        query: &Arc<dyn Query>,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let weight = self.weight(query, collector.requires_scoring())?;
+        let executor = self.searcher.index().search_executor();
+        self.searcher
+            .collect_with_executor(weight.as_ref(), collector, executor)
+    }
+
+    fn weight(
+        &self,
+        query: &Arc<dyn Query>,
+        scoring_enabled: bool,
+    ) -> crate::Result<Arc<dyn Weight>> {
+        let key = (query_key(query), scoring_enabled);
+        let cached_weight_opt = self
+            .weights
+            .lock()
+            .unwrap()
+            .get(&key)
+            .map(|(_, weight)| weight.clone());
+        self.weight_counters.record(cached_weight_opt.is_some());
+        if let Some(weight) = cached_weight_opt {
+            return Ok(weight);
+        }
+        let enable_scoring = if scoring_enabled {
+            EnableScoring::enabled_from_searcher(self.searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(self.searcher)
+        };
+        let weight: Arc<dyn Weight> = Arc::from(query.weight(enable_scoring)?);
+        self.weights
+            .lock()
+            .unwrap()
+            .insert(key, (query.clone(), weight.clone()));
+        Ok(weight)
+    }
+
+    /// Returns a query matching the same documents as `query`, with a constant score, which
+    /// caches the documents it matches in each segment.
+    ///
+    /// The filter can be searched on its own, or be a clause of other queries searched in the
+    /// session. Calling `filter` again with the same query, or a clone of its `Arc`, returns the
+    /// same filter.
+    pub fn filter(&self, query: Arc<dyn Query>) -> crate::Result<SessionFilter> {
+        let key = query_key(&query);
+        let mut filters = self.filters.lock().unwrap();
+        if let Some(filter) = filters.get(&key) {
+            return Ok(filter.clone());
+        }
+        let weight = query.weight(EnableScoring::disabled_from_searcher(self.searcher))?;
+        let filter = SessionFilter {
+            inner: Arc::new(SessionFilterInner {
+                query,
+                weight,
+                doc_sets: Mutex::default(),
+                filter_cache: self.filter_cache.clone(),
+            }),
+        };
+        filters.insert(key, filter.clone());
+        Ok(filter)
+    }
+
+    /// The cache stats for the weights of the queries searched in the session.
+    pub fn weight_cache_stats(&self) -> CacheStats {
+        let num_entries = self.weights.lock().unwrap().len();
+        self.weight_counters.cache_stats(num_entries)
+    }
+
+    /// The cache stats for the doc sets of the filters of the session, each entry being the
+    /// documents matched by a filter in a segment.
+    pub fn filter_cache_stats(&self) -> CacheStats {
+        let num_entries = self.filter_cache.num_entries.load(Ordering::Relaxed);
+        self.filter_cache.counters.cache_stats(num_entries)
+    }
+
+    /// The number of bytes taken by the doc sets of the filters of the session.
+    pub fn filter_cache_num_bytes(&self) -> usize {
+        self.filter_cache.num_bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Accounts for the doc sets cached by the filters of a session.
+struct FilterCache {
+    memory_budget_num_bytes: usize,
+    num_bytes: AtomicUsize,
+    num_entries: AtomicUsize,
+    counters: CacheCounters,
+}
+
+impl FilterCache {
+    fn with_memory_budget(memory_budget_num_bytes: usize) -> FilterCache {
+        FilterCache {
+            memory_budget_num_bytes,
+            num_bytes: AtomicUsize::new(0),
+            num_entries: AtomicUsize::new(0),
+            counters: CacheCounters::default(),
+        }
+    }
+
+    /// Reserves `num_bytes` for a new entry, returning false if they do not fit in the budget.
+    fn try_reserve(&self, num_bytes: usize) -> bool {
+        let is_reserved = self
+            .num_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used_num_bytes| {
+                used_num_bytes
+                    .checked_add(num_bytes)
+                    .filter(|&new_num_bytes| new_num_bytes <= self.memory_budget_num_bytes)
+            })
+            .is_ok();
+        if is_reserved {
+            self.num_entries.fetch_add(1, Ordering::Relaxed);
+        }
+        is_reserved
+    }
+
+    /// Releases the bytes reserved for an entry that ended up not being cached.
+    fn release(&self, num_bytes: usize) {
+        self.num_bytes.fetch_sub(num_bytes, Ordering::Relaxed);
+        self.num_entries.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A filter of a [`SearchSession`], see [`SearchSession::filter`].
+///
+/// The documents it matches are cached per segment, and shared by its clones.
+#[derive(Clone)]
+pub struct SessionFilter {
+    inner: Arc<SessionFilterInner>,
+}
+
+struct SessionFilterInner {
+    query: Arc<dyn Query>,
+    weight: Box<dyn Weight>,
+    doc_sets: Mutex<HashMap<SegmentId, Arc<BitSet>>>,
+    filter_cache: Arc<FilterCache>,
+}
+
+impl SessionFilterInner {
+    fn doc_set(&self, reader: &SegmentReader) -> crate::Result<Arc<BitSet>> {
+        let segment_id = reader.segment_id();
+        let cached_doc_set_opt = self.doc_sets.lock().unwrap().get(&segment_id).cloned();
+        self.filter_cache
+            .counters
+            .record(cached_doc_set_opt.is_some());
+        if let Some(doc_set) = cached_doc_set_opt {
+            return Ok(doc_set);
+        }
+        let mut doc_set = BitSet::with_max_value(reader.max_doc());
+        let mut scorer = self.weight.scorer(reader, 1.0)?;
+        let mut doc = scorer.doc();
+        while doc != TERMINATED {
+            doc_set.insert(doc);
+            doc = scorer.advance();
+        }
+        let doc_set = Arc::new(doc_set);
+        // The bitset holds one `u64` per block of 64 documents.
+        let num_bytes = (reader.max_doc() as usize).div_ceil(64) * 8;
+        if self.filter_cache.try_reserve(num_bytes) {
+            let previous_doc_set_opt = self
+                .doc_sets
+                .lock()
+                .unwrap()
+                .insert(segment_id, doc_set.clone());
+            // Another search computed the doc set of this segment concurrently.
+            if previous_doc_set_opt.is_some() {
+                self.filter_cache.release(num_bytes);
+            }
+        }
+        Ok(doc_set)
+    }
+}
+
+impl fmt::Debug for SessionFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SessionFilter({:?})", self.inner.query)
+    }
+}
+
+impl Query for SessionFilter {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        Ok(Box::new(SessionFilterWeight {
+            inner: self.inner.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.inner.query.query_terms(visitor);
+    }
+}
+
+struct SessionFilterWeight {
+    inner: Arc<SessionFilterInner>,
+}
+
+impl Weight for SessionFilterWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let doc_set = self.inner.doc_set(reader)?;
+        let doc_set = BitSetDocSet::from(BitSet::clone(&doc_set));
+        Ok(Box::new(ConstScorer::new(doc_set, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("SessionFilter", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+    use std::sync::Arc;
+
+    use crate::collector::{Count, DocSetCollector, TopDocs};
+    use crate::query::{BooleanQuery, Occur, Query, RangeQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, TEXT};
+    use crate::{Index, IndexWriter, Searcher, Term};
+
+    fn make_searcher() -> crate::Result<(Searcher, Schema)> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let year = schema_builder.add_u64_field("year", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // Two segments.
+        for segment_ord in 0..2u64 {
+            for doc_id in 0..20u64 {
+                let body = if doc_id % 3 == 0 {
+                    "red apple"
+                } else {
+                    "green apple pie"
+                };
+                index_writer.add_document(doc!(
+                    text => body,
+                    year => 2000 + segment_ord * 20 + doc_id,
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+        Ok((index.reader()?.searcher(), schema))
+    }
+
+    fn term_query(schema: &Schema, text: &str) -> Arc<dyn Query> {
+        let field = schema.get_field("text").unwrap();
+        Arc::new(TermQuery::new(
+            Term::from_field_text(field, text),
+            IndexRecordOption::WithFreqs,
+        ))
+    }
+
+    #[test]
+    fn test_search_session_same_results() -> crate::Result<()> {
+        let (searcher, schema) = make_searcher()?;
+        let year = schema.get_field("year").unwrap();
+        let recent: Arc<dyn Query> = Arc::new(RangeQuery::new(
+            Bound::Included(Term::from_field_u64(year, 2025)),
+            Bound::Unbounded,
+        ));
+        let session = searcher.session();
+        let recent_filter: Arc<dyn Query> = Arc::new(session.filter(recent.clone())?);
+        // Each query, along with the same query where the range is replaced by the cached
+        // filter.
+        let with_recent = |recent: &Arc<dyn Query>| -> Vec<Arc<dyn Query>> {
+            vec![
+                term_query(&schema, "red"),
+                Arc::new(BooleanQuery::new(vec![
+                    (Occur::Must, term_query(&schema, "apple").box_clone()),
+                    (Occur::Must, recent.box_clone()),
+                ])),
+                Arc::new(BooleanQuery::new(vec![
+                    (Occur::Should, term_query(&schema, "red").box_clone()),
+                    (Occur::Should, term_query(&schema, "pie").box_clone()),
+                    (Occur::Must, recent.box_clone()),
+                ])),
+            ]
+        };
+        let queries = with_recent(&recent);
+        let mut filtered_queries = with_recent(&recent_filter);
+        filtered_queries[0] = queries[0].clone();
+        for _ in 0..2 {
+            for (query, filtered_query) in queries.iter().zip(&filtered_queries) {
+                for session_query in [query, filtered_query] {
+                    let top_docs = TopDocs::with_limit(10).order_by_score();
+                    assert_eq!(
+                        session.search(session_query, &top_docs)?,
+                        searcher.search(query.as_ref(), &top_docs)?
+                    );
+                    assert_eq!(
+                        session.search(session_query, &DocSetCollector)?,
+                        searcher.search(query.as_ref(), &DocSetCollector)?
+                    );
+                    assert_eq!(
+                        session.search(session_query, &Count)?,
+                        searcher.search(query.as_ref(), &Count)?
+                    );
+                }
+            }
+        }
+        // 5 distinct queries, searched with and without scoring.
+        let weight_cache_stats = session.weight_cache_stats();
+        assert_eq!(weight_cache_stats.num_entries, 10);
+        assert_eq!(weight_cache_stats.cache_misses, 10);
+        assert_eq!(weight_cache_stats.cache_hits, 2 * 3 * 2 * 3 - 10);
+        // The doc set of each segment is computed by the first search.
+        let filter_cache_stats = session.filter_cache_stats();
+        assert_eq!(filter_cache_stats.num_entries, 2);
+        assert_eq!(filter_cache_stats.cache_misses, 2);
+        assert!(filter_cache_stats.cache_hits > 0);
+        assert!(session.filter_cache_num_bytes() > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_session_queries_with_same_debug() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "apple apple apple pie"))?;
+        index_writer.add_document(doc!(text => "apple pie"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let term = Term::from_field_text(text, "apple");
+        let basic: Arc<dyn Query> =
+            Arc::new(TermQuery::new(term.clone(), IndexRecordOption::Basic));
+        let with_freqs: Arc<dyn Query> =
+            Arc::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+        assert_eq!(format!("{basic:?}"), format!("{with_freqs:?}"));
+        let session = searcher.session();
+        let top_docs = TopDocs::with_limit(2).order_by_score();
+        for query in [&basic, &with_freqs] {
+            assert_eq!(
+                session.search(query, &top_docs)?,
+                searcher.search(query.as_ref(), &top_docs)?
+            );
+        }
+        assert_ne!(
+            session.search(&basic, &top_docs)?,
+            session.search(&with_freqs, &top_docs)?
+        );
+        assert_eq!(session.weight_cache_stats().num_entries, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_session_filter_is_shared() -> crate::Result<()> {
+        let (searcher, schema) = make_searcher()?;
+        let session = searcher.session();
+        let red = term_query(&schema, "red");
+        let red_filter: Arc<dyn Query> = Arc::new(session.filter(red.clone())?);
+        assert_eq!(session.search(&red_filter, &Count)?, 14);
+        let red_filter_again: Arc<dyn Query> = Arc::new(session.filter(red)?);
+        assert_eq!(session.search(&red_filter_again, &Count)?, 14);
+        let filter_cache_stats = session.filter_cache_stats();
+        assert_eq!(filter_cache_stats.cache_misses, 2);
+        assert_eq!(filter_cache_stats.cache_hits, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_session_memory_budget() -> crate::Result<()> {
+        let (searcher, schema) = make_searcher()?;
+        // Room for the doc set of one segment only.
+        let session = searcher.session().with_memory_budget(8);
+        let red_filter: Arc<dyn Query> = Arc::new(session.filter(term_query(&schema, "red"))?);
+        for _ in 0..3 {
+            assert_eq!(session.search(&red_filter, &Count)?, 14);
+        }
+        let filter_cache_stats = session.filter_cache_stats();
+        assert_eq!(filter_cache_stats.num_entries, 1);
+        assert_eq!(filter_cache_stats.cache_hits, 2);
+        assert_eq!(filter_cache_stats.cache_misses, 4);
+        assert_eq!(session.filter_cache_num_bytes(), 8);
+
+        let session = searcher.session().with_memory_budget(0);
+        let red_filter: Arc<dyn Query> = Arc::new(session.filter(term_query(&schema, "red"))?);
+        assert_eq!(session.search(&red_filter, &Count)?, 14);
+        assert_eq!(session.filter_cache_stats().num_entries, 0);
+        assert_eq!(session.filter_cache_num_bytes(), 0);
+        Ok(())
+    }
+}
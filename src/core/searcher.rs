@@ -1,16 +1,29 @@
 use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 use std::{fmt, io};
 
+use columnar::{Column, DynamicColumn, HasAssociatedColumnType};
+
 use crate::collector::Collector;
-use crate::core::Executor;
-use crate::index::{SegmentId, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::core::cardinality_estimate::{estimate_cardinality, Combination};
+use crate::core::search_options::check_rewrite_deadline;
+use crate::core::stable_doc_handle::key_term;
+use crate::core::{
+    CardinalityEstimate, EstimateMode, Executor, SearchOptions, SearchSession, StableDocHandle,
+};
+use crate::index::{SegmentId, SegmentReader, TermVectorEntry};
+use crate::postings::Postings;
+use crate::query::{
+    Bm25StatisticsProvider, CollectionDeadlineWeight, EnableScoring, Query, Weight,
+};
 use crate::schema::document::DocumentDeserialize;
-use crate::schema::{Schema, Term};
+use crate::schema::{Field, IndexRecordOption, OwnedValue, Schema, Term};
 use crate::space_usage::SearcherSpaceUsage;
 use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, TrackedObject};
+use crate::{
+    DocAddress, DocSet, Index, Opstamp, TantivyDocument, TantivyError, TrackedObject, TERMINATED,
+};
 
 /// Identifies the searcher generation accessed by a [`Searcher`].
 ///
@@ -90,6 +103,163 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Creates a [`StableDocHandle`] for the document at `doc_address`.
+    ///
+    /// If `key_field` is given, the value of that field is read from the stored document and
+    /// captured in the handle. The handle can then still be resolved after the segment of the
+    /// document was merged. The key field needs to be stored, and indexed without being
+    /// tokenized (e.g. with [`STRING`](crate::schema::STRING)), and its value should uniquely
+    /// identify the document.
+    pub fn stable_doc_handle(
+        &self,
+        doc_address: DocAddress,
+        key_field: Option<Field>,
+    ) -> crate::Result<StableDocHandle> {
+        let segment_reader = self.segment_reader(doc_address.segment_ord);
+        let key = if let Some(key_field) = key_field {
+            let field_entry = self.schema().get_field_entry(key_field);
+            if !field_entry.is_indexed() || !field_entry.is_stored() {
+                return Err(TantivyError::SchemaError(format!(
+                    "Key field `{}` must be indexed and stored",
+                    field_entry.name()
+                )));
+            }
+            let doc: TantivyDocument = self.doc(doc_address)?;
+            doc.get_first(key_field)
+                .map(|value| key_term(key_field, &OwnedValue::from(value)))
+                .transpose()?
+        } else {
+            None
+        };
+        Ok(StableDocHandle {
+            segment_id: segment_reader.segment_id(),
+            delete_opstamp: segment_reader.delete_opstamp(),
+            doc_id: doc_address.doc_id,
+            key,
+        })
+    }
+
+    /// Resolves a [`StableDocHandle`] to the current address of its document.
+    ///
+    /// If the segment of the handle is part of this searcher and the document is still alive,
+    /// its address is returned directly. Otherwise, if the handle captured a key, the document
+    /// is looked up by that key. Returns `None` if the document could not be found.
+    ///
+    /// Returns an error if the doc id of the handle is out of bounds for its segment, which can
+    /// only happen for a handle that was not created by [`Searcher::stable_doc_handle`].
+    pub fn resolve_handle(&self, handle: &StableDocHandle) -> crate::Result<Option<DocAddress>> {
+        let original_segment = self
+            .segment_readers()
+            .iter()
+            .enumerate()
+            .find(|(_, segment_reader)| segment_reader.segment_id() == handle.segment_id);
+        if let Some((segment_ord, segment_reader)) = original_segment {
+            if handle.doc_id >= segment_reader.max_doc() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "Doc id {} of the handle is out of bounds for segment {} with max doc {}",
+                    handle.doc_id,
+                    handle.segment_id.short_uuid_string(),
+                    segment_reader.max_doc()
+                )));
+            }
+            // Deletes do not change doc ids, so the doc id stays valid as long as
+            // the document is alive.
+            if segment_reader.delete_opstamp() == handle.delete_opstamp
+                || !segment_reader.is_deleted(handle.doc_id)
+            {
+                return Ok(Some(DocAddress::new(segment_ord as u32, handle.doc_id)));
+            }
+        }
+        let Some(key) = handle.key.as_ref() else {
+            return Ok(None);
+        };
+        for (segment_ord, segment_reader) in self.segment_readers().iter().enumerate() {
+            let inverted_index = segment_reader.inverted_index(key.field())?;
+            let Some(mut postings) = inverted_index.read_postings(key, IndexRecordOption::Basic)?
+            else {
+                continue;
+            };
+            let mut doc_id = postings.doc();
+            while doc_id != TERMINATED {
+                if !segment_reader.is_deleted(doc_id) {
+                    return Ok(Some(DocAddress::new(segment_ord as u32, doc_id)));
+                }
+                doc_id = postings.advance();
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns, for each of the documents at `doc_addresses`, the earliest position at which
+    /// any of the terms of `query` occurs, per field.
+    ///
+    /// The terms are extracted with [`Query::query_terms`], and their positions are read from
+    /// the postings. Every field of the extracted terms has an entry in the maps, which is
+    /// `usize::MAX` if none of the terms of the field occur in the document, or if the field
+    /// does not index positions.
+    ///
+    /// This is typically used to compute ranking features for the top hits of a query.
+    pub fn first_term_positions(
+        &self,
+        query: &dyn Query,
+        doc_addresses: &[DocAddress],
+    ) -> crate::Result<Vec<BTreeMap<Field, usize>>> {
+        let mut terms: Vec<&Term> = Vec::new();
+        query.query_terms(&mut |term, _| terms.push(term));
+        terms.sort();
+        terms.dedup();
+        let no_positions: BTreeMap<Field, usize> = terms
+            .iter()
+            .map(|term| (term.field(), usize::MAX))
+            .collect();
+        let mut first_positions = vec![no_positions; doc_addresses.len()];
+        // Postings can only be advanced, so the documents are visited in order.
+        let mut hit_ords: Vec<usize> = (0..doc_addresses.len()).collect();
+        hit_ords.sort_by_key(|&hit_ord| doc_addresses[hit_ord]);
+        let mut positions = Vec::new();
+        for segment_hit_ords in hit_ords.chunk_by(|&left, &right| {
+            doc_addresses[left].segment_ord == doc_addresses[right].segment_ord
+        }) {
+            let segment_reader =
+                self.segment_reader(doc_addresses[segment_hit_ords[0]].segment_ord);
+            for term in &terms {
+                let inverted_index = segment_reader.inverted_index(term.field())?;
+                let Some(mut postings) =
+                    inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+                else {
+                    continue;
+                };
+                for &hit_ord in segment_hit_ords {
+                    let doc_id = doc_addresses[hit_ord].doc_id;
+                    if postings.doc() > doc_id || postings.seek(doc_id) != doc_id {
+                        continue;
+                    }
+                    postings.positions(&mut positions);
+                    if let Some(&first_position) = positions.first() {
+                        let first_position_mut = first_positions[hit_ord]
+                            .get_mut(&term.field())
+                            .expect("all of the fields of the terms have an entry");
+                        *first_position_mut = (*first_position_mut).min(first_position as usize);
+                    }
+                }
+            }
+        }
+        Ok(first_positions)
+    }
+
+    /// Returns the term vector of the document at `doc_address` for `field`, i.e. the terms of
+    /// the field in the document along with their term frequency and positions.
+    ///
+    /// See [`SegmentReader::term_vector`].
+    pub fn term_vector(
+        &self,
+        doc_address: DocAddress,
+        field: Field,
+    ) -> crate::Result<Vec<TermVectorEntry>> {
+        self.segment_reader(doc_address.segment_ord)
+            .term_vector(doc_address.doc_id, field)
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -103,6 +273,16 @@ impl Searcher {
         cache_stats
     }
 
+    /// The cache stats for the fast field columns and fieldnorm readers.
+    ///
+    /// Aggregates the sum for each segment reader.
+    pub fn handle_cache_stats(&self) -> CacheStats {
+        self.segment_readers()
+            .iter()
+            .map(SegmentReader::handle_cache_stats)
+            .sum()
+    }
+
     /// Fetches a document in an asynchronous manner.
     #[cfg(feature = "quickwit")]
     pub async fn doc_async<D: DocumentDeserialize>(
@@ -153,6 +333,101 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Estimates the fraction of the values of the fast field `field_name` that lie in
+    /// `value_range`.
+    ///
+    /// The estimation relies on the [block statistics](columnar::Column::block_stats) of the
+    /// fast field: all of the values of a block whose bounds intersect `value_range` are counted
+    /// as matching. The estimate is therefore an upper bound of the actual selectivity, which
+    /// gets tighter when values are clustered by document id. Columns without block statistics
+    /// are handled as a single block, bounded by the min and max values of the column.
+    ///
+    /// Returns a value between 0 and 1, which is 0 if the field has no values.
+    pub fn estimate_fast_field_range_selectivity<T>(
+        &self,
+        field_name: &str,
+        value_range: RangeInclusive<T>,
+    ) -> crate::Result<f64>
+    where
+        T: HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        let mut num_values = 0u64;
+        let mut num_matching_values = 0u64;
+        for segment_reader in &self.inner.segment_readers {
+            let Some(column) = segment_reader.fast_fields().column_opt::<T>(field_name)? else {
+                continue;
+            };
+            let intersects_range = |min_value: &T, max_value: &T| {
+                min_value <= value_range.end() && max_value >= value_range.start()
+            };
+            let mut has_block_stats = false;
+            for block_stats in column.block_stats() {
+                has_block_stats = true;
+                let block_num_values = u64::from(block_stats.num_values());
+                num_values += block_num_values;
+                if intersects_range(&block_stats.min_value, &block_stats.max_value) {
+                    num_matching_values += block_num_values;
+                }
+            }
+            if !has_block_stats {
+                let column_num_values = u64::from(column.values.num_vals());
+                num_values += column_num_values;
+                if intersects_range(&column.min_value(), &column.max_value()) {
+                    num_matching_values += column_num_values;
+                }
+            }
+        }
+        if num_values == 0 {
+            return Ok(0.0);
+        }
+        Ok(num_matching_values as f64 / num_values as f64)
+    }
+
+    /// Estimates the number of documents matching all of the `queries`, without collecting
+    /// them.
+    ///
+    /// In [`EstimateMode::Exact`] mode, the queries are intersected, driven by the sparsest
+    /// one, and the count is the same as the [`Count`](crate::collector::Count) of a
+    /// `BooleanQuery` made of `Must` clauses. In [`EstimateMode::Sampled`] mode, only a
+    /// fraction of the blocks of doc ids of each segment are visited, and the estimate comes
+    /// with [confidence information](crate::EstimateConfidence).
+    pub fn estimate_intersection(
+        &self,
+        queries: &[&dyn Query],
+        mode: EstimateMode,
+    ) -> crate::Result<CardinalityEstimate> {
+        self.estimate_cardinality(queries, Combination::Intersection, mode)
+    }
+
+    /// Estimates the number of documents matching any of the `queries`, without collecting
+    /// them.
+    ///
+    /// In [`EstimateMode::Exact`] mode, the count is the same as the
+    /// [`Count`](crate::collector::Count) of a `BooleanQuery` made of `Should` clauses. See
+    /// [`Searcher::estimate_intersection`] for the sampled mode.
+    pub fn estimate_union(
+        &self,
+        queries: &[&dyn Query],
+        mode: EstimateMode,
+    ) -> crate::Result<CardinalityEstimate> {
+        self.estimate_cardinality(queries, Combination::Union, mode)
+    }
+
+    fn estimate_cardinality(
+        &self,
+        queries: &[&dyn Query],
+        combination: Combination,
+        mode: EstimateMode,
+    ) -> crate::Result<CardinalityEstimate> {
+        let enable_scoring = EnableScoring::disabled_from_searcher(self);
+        let weights = queries
+            .iter()
+            .map(|query| query.weight(enable_scoring))
+            .collect::<crate::Result<Vec<_>>>()?;
+        estimate_cardinality(self.segment_readers(), &weights, combination, mode)
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.inner.segment_readers
@@ -163,6 +438,30 @@ impl Searcher {
         &self.inner.segment_readers[segment_ord as usize]
     }
 
+    /// Returns a searcher over the same segments, for which the soft deleted documents are
+    /// alive.
+    ///
+    /// Searches then match the soft deleted documents like any other document, until their
+    /// retention expires or they are purged. The returned searcher shares the generation of
+    /// this searcher, but not its doc store cache.
+    /// See [`SoftDeleteOptions`](crate::indexer::SoftDeleteOptions).
+    pub fn include_soft_deleted(&self) -> crate::Result<Searcher> {
+        let segment_readers = self
+            .inner
+            .segment_readers
+            .iter()
+            .map(SegmentReader::include_soft_deleted)
+            .collect();
+        let inner = SearcherInner::new(
+            self.inner.schema.clone(),
+            self.inner.index.clone(),
+            segment_readers,
+            self.inner.generation.clone(),
+            self.inner.doc_store_cache_num_blocks,
+        )?;
+        Ok(Searcher::from(Arc::new(inner)))
+    }
+
     /// Runs a query on the segment readers wrapped by the searcher.
     ///
     /// Search works as follows :
@@ -205,6 +504,40 @@ impl Searcher {
         self.search_with_executor(query, collector, executor, enabled_scoring)
     }
 
+    /// Same as [`search(...)`](Searcher::search) but with deadlines for the rewrite and the
+    /// collection phases of the search.
+    ///
+    /// The search fails with [`TantivyError::RewriteDeadlineExceeded`] if the creation of the
+    /// weight of the query, or the enumeration of the terms it matches, does not complete
+    /// before the rewrite deadline, and with [`TantivyError::CollectionDeadlineExceeded`] if
+    /// the collection of the matching documents does not complete before the collection
+    /// deadline. See [`SearchOptions`].
+    pub fn search_with_options<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        search_options: &SearchOptions,
+    ) -> crate::Result<C::Fruit> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        }
+        .with_rewrite_deadline(search_options.rewrite_deadline);
+        let weight = query.weight(enabled_scoring)?;
+        check_rewrite_deadline(search_options.rewrite_deadline)?;
+        let executor = self.inner.index.search_executor();
+        let Some(collection_deadline) = search_options.collection_deadline else {
+            return self.collect_with_executor(weight.as_ref(), collector, executor);
+        };
+        let weight = CollectionDeadlineWeight::new(weight, collection_deadline);
+        let fruit = self.collect_with_executor(&weight, collector, executor)?;
+        if weight.is_deadline_exceeded() {
+            return Err(TantivyError::CollectionDeadlineExceeded);
+        }
+        Ok(fruit)
+    }
+
     /// Same as [`search(...)`](Searcher::search) but multithreaded.
     ///
     /// The current implementation is rather naive :
@@ -225,11 +558,46 @@ impl Searcher {
         enabled_scoring: EnableScoring,
     ) -> crate::Result<C::Fruit> {
         let weight = query.weight(enabled_scoring)?;
+        self.collect_with_executor(weight.as_ref(), collector, executor)
+    }
+
+    /// Starts a session running several searches on this searcher, which share the weights of
+    /// their queries and the documents matched by their filters. See [`SearchSession`].
+    pub fn session(&self) -> SearchSession<'_> {
+        SearchSession::new(self)
+    }
+
+    /// Calls `callback` on the address of every alive document of the searcher.
+    ///
+    /// Segments are visited on the search executor of the index, so the callback may be
+    /// called concurrently from several threads. Within a segment, documents are visited in
+    /// increasing doc id order.
+    pub fn for_each_alive_doc<F>(&self, callback: F) -> crate::Result<()>
+    where F: Fn(DocAddress) + Sync {
+        let executor = self.inner.index.search_executor();
+        executor.map(
+            |(segment_ord, segment_reader)| {
+                for doc_id in segment_reader.alive_docids() {
+                    callback(DocAddress::new(segment_ord as u32, doc_id));
+                }
+                Ok(())
+            },
+            self.segment_readers().iter().enumerate(),
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn collect_with_executor<C: Collector>(
+        &self,
+        weight: &dyn Weight,
+        collector: &C,
+        executor: &Executor,
+    ) -> crate::Result<C::Fruit> {
         collector.check_schema(self.schema())?;
         let segment_readers = self.segment_readers();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
-                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+                collector.collect_segment(weight, segment_ord as u32, segment_reader)
             },
             segment_readers.iter().enumerate(),
         )?;
@@ -237,10 +605,18 @@ impl Searcher {
     }
 
     /// Summarize total space usage of this searcher.
+    ///
+    /// Besides the files of each segment, the report includes the memory held by the data
+    /// loaded at runtime, including the doc store block cache of the searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
-        for segment_reader in self.segment_readers() {
-            space_usage.add_segment(segment_reader.space_usage()?);
+        for (segment_reader, store_reader) in
+            self.segment_readers().iter().zip(&self.inner.store_readers)
+        {
+            let mut segment_space_usage = segment_reader.space_usage()?;
+            segment_space_usage
+                .set_memory(segment_reader.memory_usage(store_reader.cache_num_bytes()));
+            space_usage.add_segment(segment_space_usage);
         }
         Ok(space_usage)
     }
@@ -262,6 +638,7 @@ pub(crate) struct SearcherInner {
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
     generation: TrackedObject<SearcherGeneration>,
+    doc_store_cache_num_blocks: usize,
 }
 
 impl SearcherInner {
@@ -292,6 +669,7 @@ impl SearcherInner {
             segment_readers,
             store_readers,
             generation,
+            doc_store_cache_num_blocks,
         })
     }
 }
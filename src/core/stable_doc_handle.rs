@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::index::SegmentId;
+use crate::schema::{Field, OwnedValue, Term};
+use crate::{DocId, Opstamp, TantivyError};
+
+/// A handle on a document which, unlike [`DocAddress`](crate::DocAddress), outlives the
+/// [`Searcher`](crate::Searcher) it was created from.
+///
+/// A `DocAddress` relies on the ordinal of a segment within a given searcher, and gets
+/// invalidated as soon as segments are merged. A `StableDocHandle` identifies the segment by its
+/// [`SegmentId`] instead. It can optionally capture the value of a unique key field of the
+/// document, which makes it possible to find the document again after its segment was merged.
+///
+/// Handles are created with [`Searcher::stable_doc_handle`](crate::Searcher::stable_doc_handle),
+/// and resolved to a `DocAddress` with
+/// [`Searcher::resolve_handle`](crate::Searcher::resolve_handle). They can be serialized, e.g.
+/// to be sent to a client and resolved in a later request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StableDocHandle {
+    pub(crate) segment_id: SegmentId,
+    pub(crate) delete_opstamp: Option<Opstamp>,
+    pub(crate) doc_id: DocId,
+    pub(crate) key: Option<Term>,
+}
+
+impl StableDocHandle {
+    /// The id of the segment holding the document when the handle was created.
+    pub fn segment_id(&self) -> SegmentId {
+        self.segment_id
+    }
+
+    /// The delete opstamp of the segment when the handle was created.
+    pub fn delete_opstamp(&self) -> Option<Opstamp> {
+        self.delete_opstamp
+    }
+
+    /// The id of the document within its segment.
+    pub fn doc_id(&self) -> DocId {
+        self.doc_id
+    }
+
+    /// The unique key of the document, if the handle was created with a key field.
+    pub fn key(&self) -> Option<&Term> {
+        self.key.as_ref()
+    }
+}
+
+/// Builds the term used to look up a document by the stored value of its key field.
+pub(crate) fn key_term(field: Field, value: &OwnedValue) -> crate::Result<Term> {
+    match value {
+        OwnedValue::Str(text) => Ok(Term::from_field_text(field, text)),
+        OwnedValue::U64(val) => Ok(Term::from_field_u64(field, *val)),
+        OwnedValue::I64(val) => Ok(Term::from_field_i64(field, *val)),
+        OwnedValue::Bytes(bytes) => Ok(Term::from_field_bytes(field, bytes)),
+        OwnedValue::Facet(facet) => Ok(Term::from_facet(field, facet)),
+        OwnedValue::IpAddr(ip_addr) => Ok(Term::from_field_ip_addr(field, *ip_addr)),
+        _ => Err(TantivyError::InvalidArgument(format!(
+            "Unsupported value for a key field: {value:?}"
+        ))),
+    }
+}
@@ -1,14 +1,18 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
 use crate::collector::Count;
 use crate::directory::{RamDirectory, WatchCallback};
 use crate::index::SegmentId;
 use crate::indexer::{LogMergePolicy, NoMergePolicy};
 use crate::postings::Postings;
 use crate::query::TermQuery;
-use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, STRING, TEXT};
+use crate::schema::{Field, IndexRecordOption, Schema, Value, FAST, INDEXED, STORED, STRING, TEXT};
 use crate::tokenizer::TokenizerManager;
 use crate::{
     Directory, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter, ReloadPolicy,
-    TantivyDocument, Term,
+    StableDocHandle, TantivyDocument, Term,
 };
 
 #[test]
@@ -466,3 +470,546 @@ fn test_non_text_json_term_freq_bitpacked() {
         assert_eq!(postings.term_freq(), 1u32);
     }
 }
+
+#[test]
+fn test_estimate_fast_field_range_selectivity() -> crate::Result<()> {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let mut schema_builder = Schema::builder();
+    let increasing = schema_builder.add_u64_field("increasing", FAST);
+    let random = schema_builder.add_i64_field("random", FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    let mut rng = StdRng::seed_from_u64(4);
+    for i in 0..10_000u64 {
+        index_writer.add_document(doc!(
+            increasing => i * i,
+            random => rng.random_range(-1_000_000i64..1_000_000),
+        ))?;
+    }
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+
+    // 708 documents match, which are all in the first two blocks of 512 values.
+    let selectivity =
+        searcher.estimate_fast_field_range_selectivity::<u64>("increasing", 0..=500_000)?;
+    assert_eq!(selectivity, 0.1024);
+    let selectivity = searcher
+        .estimate_fast_field_range_selectivity::<u64>("increasing", 100_000_000..=u64::MAX)?;
+    assert_eq!(selectivity, 0.0);
+
+    // Every block of random values intersects the range.
+    let selectivity = searcher.estimate_fast_field_range_selectivity::<i64>("random", -10..=10)?;
+    assert_eq!(selectivity, 1.0);
+    let selectivity =
+        searcher.estimate_fast_field_range_selectivity::<i64>("random", 1_000_000..=i64::MAX)?;
+    assert_eq!(selectivity, 0.0);
+
+    let selectivity = searcher.estimate_fast_field_range_selectivity::<u64>("missing", 0..=1)?;
+    assert_eq!(selectivity, 0.0);
+    Ok(())
+}
+
+#[test]
+fn test_stable_doc_handle_across_merge() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let id = schema_builder.add_text_field("id", STRING | STORED);
+    let body = schema_builder.add_text_field("body", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(id => "a", body => "first"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(id => "b", body => "second"))?;
+    index_writer.commit()?;
+    let reader = index.reader()?;
+    let searcher = reader.searcher();
+    let doc_address_b = searcher.search(
+        &TermQuery::new(Term::from_field_text(id, "b"), IndexRecordOption::Basic),
+        &crate::collector::DocSetCollector,
+    )?;
+    let doc_address_b = doc_address_b.into_iter().next().unwrap();
+
+    let handle = searcher.stable_doc_handle(doc_address_b, Some(id))?;
+    let handle_without_key = searcher.stable_doc_handle(doc_address_b, None)?;
+    assert_eq!(handle.key(), Some(&Term::from_field_text(id, "b")));
+    assert!(searcher
+        .stable_doc_handle(doc_address_b, Some(body))
+        .is_err());
+    let handle: StableDocHandle = serde_json::from_str(&serde_json::to_string(&handle)?)?;
+    assert_eq!(searcher.resolve_handle(&handle)?, Some(doc_address_b));
+    assert_eq!(
+        searcher.resolve_handle(&handle_without_key)?,
+        Some(doc_address_b)
+    );
+    let out_of_bounds_handle = StableDocHandle {
+        doc_id: 1,
+        ..handle_without_key.clone()
+    };
+    assert!(matches!(
+        searcher.resolve_handle(&out_of_bounds_handle),
+        Err(crate::TantivyError::InvalidArgument(_))
+    ));
+
+    let segment_ids = index.searchable_segment_ids()?;
+    index_writer.merge(&segment_ids).wait()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    assert_eq!(searcher.segment_readers().len(), 1);
+    let doc_address = searcher.resolve_handle(&handle)?.unwrap();
+    let doc: TantivyDocument = searcher.doc(doc_address)?;
+    assert_eq!(doc.get_first(id).and_then(|val| val.as_str()), Some("b"));
+    assert_eq!(searcher.resolve_handle(&handle_without_key)?, None);
+
+    // Updating the document deletes it from the original segment: it is found by its key.
+    let handle = searcher.stable_doc_handle(doc_address, Some(id))?;
+    index_writer.delete_term(Term::from_field_text(id, "b"));
+    index_writer.add_document(doc!(id => "b", body => "updated"))?;
+    index_writer.commit()?;
+    reader.reload()?;
+    let searcher = reader.searcher();
+    let doc_address = searcher.resolve_handle(&handle)?.unwrap();
+    assert_ne!(
+        searcher
+            .segment_reader(doc_address.segment_ord)
+            .segment_id(),
+        handle.segment_id()
+    );
+
+    index_writer.delete_term(Term::from_field_text(id, "b"));
+    index_writer.commit()?;
+    reader.reload()?;
+    assert_eq!(reader.searcher().resolve_handle(&handle)?, None);
+    Ok(())
+}
+
+#[test]
+fn test_estimate_intersection_and_union() -> crate::Result<()> {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::query::{BooleanQuery, Occur, Query};
+    use crate::{CardinalityEstimate, EstimateMode};
+
+    type EstimateFn<'a> = &'a dyn Fn(EstimateMode) -> crate::Result<CardinalityEstimate>;
+
+    for seed in [1u64, 2, 3] {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let tag_probabilities = [
+            ("a", 0.5),
+            ("b", rng.random_range(0.1..0.4)),
+            ("c", rng.random_range(0.01..0.1)),
+            ("deleted", 0.1),
+        ];
+        for num_docs in [12_000, 5_000, 300] {
+            for _ in 0..num_docs {
+                let mut doc = TantivyDocument::default();
+                for (tag_value, probability) in tag_probabilities {
+                    if rng.random_bool(probability) {
+                        doc.add_text(tag, tag_value);
+                    }
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_text(tag, "deleted"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let term_query = |tag_value: &str| {
+            TermQuery::new(
+                Term::from_field_text(tag, tag_value),
+                IndexRecordOption::Basic,
+            )
+        };
+        let (query_a, query_b, query_c) = (term_query("a"), term_query("b"), term_query("c"));
+        for queries in [
+            vec![&query_a as &dyn Query],
+            vec![&query_a, &query_b],
+            vec![&query_a, &query_b, &query_c],
+        ] {
+            let boolean_query = |occur: Occur| {
+                BooleanQuery::new(
+                    queries
+                        .iter()
+                        .map(|query| (occur, query.box_clone()))
+                        .collect(),
+                )
+            };
+            let estimates: [(EstimateFn, u64); 2] = [
+                (
+                    &|mode| searcher.estimate_intersection(&queries, mode),
+                    searcher.search(&boolean_query(Occur::Must), &Count)? as u64,
+                ),
+                (
+                    &|mode| searcher.estimate_union(&queries, mode),
+                    searcher.search(&boolean_query(Occur::Should), &Count)? as u64,
+                ),
+            ];
+            for (estimate, expected_count) in estimates {
+                let exact = estimate(EstimateMode::Exact)?;
+                assert_eq!(exact.count, expected_count);
+                assert_eq!(exact.segment_counts.iter().sum::<u64>(), expected_count);
+                assert!(exact.confidence.is_none());
+
+                let sampled = estimate(EstimateMode::Sampled(0.25))?;
+                let confidence = sampled.confidence.unwrap();
+                assert!(confidence.num_sampled_docs < searcher.num_docs());
+                assert!(confidence.num_sampled_matches <= expected_count);
+                assert!(confidence.lower_bound <= sampled.count);
+                assert!(sampled.count <= confidence.upper_bound);
+                assert!(
+                    sampled.count.abs_diff(expected_count) as f64
+                        <= 4.0 * confidence.standard_error + 1.0,
+                    "seed={seed} sampled={} expected={expected_count} {confidence:?}",
+                    sampled.count
+                );
+
+                let fully_sampled = estimate(EstimateMode::Sampled(1.0))?;
+                assert_eq!(fully_sampled.count, expected_count);
+                assert_eq!(fully_sampled.segment_counts, exact.segment_counts);
+                assert_eq!(fully_sampled.confidence.unwrap().standard_error, 0.0);
+            }
+        }
+        assert!(searcher
+            .estimate_intersection(&[&query_a], EstimateMode::Sampled(0.0))
+            .is_err());
+        assert!(searcher.estimate_union(&[], EstimateMode::Exact).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_search_with_options_deadlines() -> crate::Result<()> {
+    use std::time::{Duration, Instant};
+
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, RegexQuery};
+    use crate::{SearchOptions, TantivyError};
+
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    for i in 0..2_000 {
+        index_writer.add_document(doc!(text => format!("term{i}")))?;
+    }
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    // Matches all of the terms of the dictionary.
+    let pathological_query = RegexQuery::from_pattern("(t|te)*(e|r)*.*m[0-9]*", text)?;
+
+    let far_deadline = Instant::now() + Duration::from_secs(3_600);
+    let options = SearchOptions {
+        rewrite_deadline: Some(far_deadline),
+        collection_deadline: Some(far_deadline),
+    };
+    assert_eq!(
+        searcher.search_with_options(&pathological_query, &Count, &options)?,
+        2_000
+    );
+    assert_eq!(
+        searcher.search_with_options(&pathological_query, &Count, &SearchOptions::default())?,
+        2_000
+    );
+
+    let options = SearchOptions {
+        rewrite_deadline: Some(Instant::now()),
+        collection_deadline: Some(far_deadline),
+    };
+    let error = searcher
+        .search_with_options(&pathological_query, &Count, &options)
+        .unwrap_err();
+    assert!(matches!(error, TantivyError::RewriteDeadlineExceeded));
+
+    let options = SearchOptions {
+        rewrite_deadline: Some(far_deadline),
+        collection_deadline: Some(Instant::now()),
+    };
+    let error = searcher
+        .search_with_options(&AllQuery, &Count, &options)
+        .unwrap_err();
+    assert!(matches!(error, TantivyError::CollectionDeadlineExceeded));
+    let error = searcher
+        .search_with_options(
+            &AllQuery,
+            &TopDocs::with_limit(10).order_by_score(),
+            &options,
+        )
+        .unwrap_err();
+    assert!(matches!(error, TantivyError::CollectionDeadlineExceeded));
+    Ok(())
+}
+
+#[test]
+fn test_first_term_positions() -> crate::Result<()> {
+    use crate::query::QueryParser;
+    use crate::DocAddress;
+
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", TEXT);
+    let body = schema_builder.add_text_field("body", TEXT);
+    // Indexed without positions.
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    index_writer.add_document(doc!(
+        title => "a dog",
+        body => "the quick brown fox jumps over the lazy dog",
+        tag => "fox",
+    ))?;
+    index_writer.add_document(doc!(title => "fox", body => "nothing to see here"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(body => "dog"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let query = QueryParser::for_index(&index, vec![title, body, tag]).parse_query("fox dog")?;
+
+    let first_segment_ord = searcher
+        .segment_readers()
+        .iter()
+        .position(|segment_reader| segment_reader.max_doc() == 2)
+        .unwrap() as u32;
+    let doc_addresses = [
+        DocAddress::new(1 - first_segment_ord, 0),
+        DocAddress::new(first_segment_ord, 1),
+        DocAddress::new(first_segment_ord, 0),
+        DocAddress::new(first_segment_ord, 1),
+    ];
+    let first_positions = searcher.first_term_positions(&query, &doc_addresses)?;
+    let expected = |title_pos: usize, body_pos: usize| {
+        BTreeMap::from([(title, title_pos), (body, body_pos), (tag, usize::MAX)])
+    };
+    assert_eq!(
+        first_positions,
+        vec![
+            expected(usize::MAX, 0),
+            expected(0, usize::MAX),
+            expected(1, 3),
+            expected(0, usize::MAX),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_term_vector() -> crate::Result<()> {
+    use crate::DocAddress;
+
+    let mut schema_builder = Schema::builder();
+    let body = schema_builder.add_text_field("body", TEXT);
+    // Indexed without term frequencies nor positions.
+    let tag = schema_builder.add_text_field("tag", STRING);
+    let count = schema_builder.add_u64_field("count", INDEXED);
+    let title = schema_builder.add_text_field("title", STORED);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.add_document(doc!(body => "fox fox", tag => "fox"))?;
+    index_writer.add_document(doc!(
+        body => "the quick brown fox jumps over the lazy dog, the end",
+        tag => "animals",
+        count => 7u64,
+        title => "fable",
+    ))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let doc_address = DocAddress::new(0, 1);
+
+    let body_term_vector: Vec<(String, u32, Vec<u32>)> = searcher
+        .term_vector(doc_address, body)?
+        .iter()
+        .map(|entry| {
+            (
+                entry.term().value().as_str().unwrap().to_string(),
+                entry.term_freq(),
+                entry.positions().to_vec(),
+            )
+        })
+        .collect();
+    let expected_body_term_vector: Vec<(String, u32, Vec<u32>)> = [
+        ("brown", 1, vec![2]),
+        ("dog", 1, vec![8]),
+        ("end", 1, vec![10]),
+        ("fox", 1, vec![3]),
+        ("jumps", 1, vec![4]),
+        ("lazy", 1, vec![7]),
+        ("over", 1, vec![5]),
+        ("quick", 1, vec![1]),
+        ("the", 3, vec![0, 6, 9]),
+    ]
+    .into_iter()
+    .map(|(text, term_freq, positions)| (text.to_string(), term_freq, positions))
+    .collect();
+    assert_eq!(body_term_vector, expected_body_term_vector);
+
+    let tag_term_vector = searcher.term_vector(doc_address, tag)?;
+    assert_eq!(tag_term_vector.len(), 1);
+    assert_eq!(
+        tag_term_vector[0].term(),
+        &Term::from_field_text(tag, "animals")
+    );
+    assert_eq!(tag_term_vector[0].term_freq(), 1);
+    assert!(tag_term_vector[0].positions().is_empty());
+
+    let count_term_vector = searcher.term_vector(doc_address, count)?;
+    assert_eq!(count_term_vector.len(), 1);
+    assert_eq!(count_term_vector[0].term(), &Term::from_field_u64(count, 7));
+
+    let fox_term_vector = searcher.term_vector(DocAddress::new(0, 0), body)?;
+    assert_eq!(fox_term_vector.len(), 1);
+    assert_eq!(fox_term_vector[0].term_freq(), 2);
+    assert_eq!(fox_term_vector[0].positions(), &[0, 1]);
+    assert!(searcher
+        .term_vector(DocAddress::new(0, 0), count)?
+        .is_empty());
+
+    assert!(searcher.term_vector(doc_address, title).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_for_each_alive_doc() -> crate::Result<()> {
+    use std::sync::Mutex;
+
+    use crate::DocAddress;
+
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", STRING);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    // Segment without deletes.
+    index_writer.add_document(doc!(text => "a"))?;
+    index_writer.add_document(doc!(text => "b"))?;
+    index_writer.commit()?;
+    // Segment with some deletes.
+    index_writer.add_document(doc!(text => "c"))?;
+    index_writer.add_document(doc!(text => "d"))?;
+    index_writer.add_document(doc!(text => "e"))?;
+    index_writer.commit()?;
+    // Segment with all of its documents deleted.
+    index_writer.add_document(doc!(text => "f"))?;
+    index_writer.commit()?;
+    index_writer.delete_term(Term::from_field_text(text, "d"));
+    index_writer.delete_term(Term::from_field_text(text, "f"));
+    index_writer.commit()?;
+
+    let searcher = index.reader()?.searcher();
+    let doc_addresses = Mutex::new(Vec::new());
+    searcher.for_each_alive_doc(|doc_address| doc_addresses.lock().unwrap().push(doc_address))?;
+    let mut doc_addresses = doc_addresses.into_inner().unwrap();
+    doc_addresses.sort();
+
+    let mut expected = Vec::new();
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        for doc_id in 0..segment_reader.max_doc() {
+            if !segment_reader.is_deleted(doc_id) {
+                expected.push(DocAddress::new(segment_ord as u32, doc_id));
+            }
+        }
+    }
+    assert_eq!(doc_addresses.len(), 4);
+    assert_eq!(doc_addresses, expected);
+    Ok(())
+}
+
+mod read_only {
+    use std::io;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use crate::collector::Count;
+    use crate::directory::error::{DeleteError, OpenReadError, OpenWriteError};
+    use crate::directory::{FileHandle, RamDirectory, WatchCallback, WatchHandle, WritePtr};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING};
+    use crate::{Directory, Index, IndexWriter, TantivyError, Term};
+
+    /// A directory panicking on any write.
+    #[derive(Clone, Debug)]
+    struct PanicOnWriteDirectory(RamDirectory);
+
+    impl Directory for PanicOnWriteDirectory {
+        fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+            self.0.get_file_handle(path)
+        }
+
+        fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+            panic!("delete {path:?}");
+        }
+
+        fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+            self.0.exists(path)
+        }
+
+        fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+            panic!("open_write {path:?}");
+        }
+
+        fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.0.atomic_read(path)
+        }
+
+        fn atomic_write(&self, path: &Path, _data: &[u8]) -> io::Result<()> {
+            panic!("atomic_write {path:?}");
+        }
+
+        fn sync_directory(&self) -> io::Result<()> {
+            panic!("sync_directory");
+        }
+
+        fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+            self.0.watch(watch_callback)
+        }
+    }
+
+    #[test]
+    fn test_open_read_only() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let schema = schema_builder.build();
+        let ram_directory = RamDirectory::default();
+        {
+            let index = Index::create(ram_directory.clone(), schema, Default::default())?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(tag => "a"))?;
+            index_writer.add_document(doc!(tag => "b"))?;
+            index_writer.add_document(doc!(tag => "a"))?;
+            index_writer.commit()?;
+        }
+
+        let index = Index::open_read_only(PanicOnWriteDirectory(ram_directory))?;
+        assert!(index.is_read_only());
+        let reader = index.reader()?;
+        reader.reload()?;
+        let query = TermQuery::new(Term::from_field_text(tag, "a"), IndexRecordOption::Basic);
+        assert_eq!(reader.searcher().search(&query, &Count)?, 2);
+
+        assert!(matches!(
+            index.writer::<crate::TantivyDocument>(15_000_000),
+            Err(TantivyError::ReadOnlyIndex)
+        ));
+        assert!(matches!(
+            crate::indexer::BulkLoader::<crate::TantivyDocument>::new(&index, 15_000_000, 1_000),
+            Err(TantivyError::ReadOnlyIndex)
+        ));
+        assert!(index.directory().open_write(Path::new("file")).is_err());
+        assert!(index.directory().delete(Path::new("file")).is_err());
+        assert!(index
+            .directory()
+            .clone()
+            .garbage_collect(Default::default)?
+            .deleted_files
+            .is_empty());
+        Ok(())
+    }
+}
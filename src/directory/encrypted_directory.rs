@@ -0,0 +1,715 @@
+//! Transparent encryption of the files of an index.
+//!
+//! The [`EncryptedDirectory`] wraps another [`Directory`] and encrypts the content of all the
+//! files it writes, including the files written with [`Directory::atomic_write`] like
+//! `meta.json`, with AES-256-GCM.
+//!
+//! A file starts with a header, followed by its content split in blocks of
+//! [`ENCRYPTION_BLOCK_SIZE`] bytes, which are encrypted independently. Reading a range of a file
+//! only requires to decrypt the blocks overlapping with it.
+//!
+//! Each block is authenticated with a GCM tag, which covers the ciphertext of the block, the
+//! header of the file, the position of the block, and whether it is the last block of the file.
+//! Any modification of a block, as well as reordering or truncating blocks, is detected when
+//! the block is read, and reported as an [`io::ErrorKind::InvalidData`] error.
+//!
+//! The footer that tantivy appends to the files it writes is encrypted along with their content.
+//!
+//! Lock files are not encrypted: locking is delegated to the wrapped directory.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use common::{AntiCallToken, HasLen, OwnedBytes};
+use lru::LruCache;
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    Directory, DirectoryLock, FileHandle, Lock, TerminatingWrite, WatchCallback, WatchHandle,
+    WritePtr,
+};
+
+/// Number of bytes of content encrypted together in a block.
+pub const ENCRYPTION_BLOCK_SIZE: usize = 16_384;
+
+/// Default memory budget of the cache of decrypted blocks.
+pub const DEFAULT_BLOCK_CACHE_BUDGET: usize = 32_000_000;
+
+const MAGIC_BYTES: [u8; 4] = *b"TENC";
+const FORMAT_VERSION: u8 = 1;
+const FILE_ID_LEN: usize = 8;
+/// The header is made of the magic bytes, the format version, the block size and the random id
+/// of the file.
+const HEADER_LEN: usize = MAGIC_BYTES.len() + 1 + 4 + FILE_ID_LEN;
+const TAG_LEN: usize = 16;
+
+/// Provides the key used to encrypt the files of an [`EncryptedDirectory`].
+pub trait KeyProvider {
+    /// Returns the 256-bit AES key.
+    fn key(&self) -> io::Result<[u8; 32]>;
+}
+
+/// Directory encrypting the files of the directory it wraps.
+///
+/// See the [module documentation](self) for the format of the encrypted files.
+///
+/// Unlike other directories, the content written to a file is only readable once its writer
+/// has been terminated: a block can only be encrypted once it is complete, and flushing the
+/// writer does not write the block being filled.
+///
+/// ```
+/// use std::io;
+///
+/// use tantivy::directory::{EncryptedDirectory, KeyProvider, RamDirectory};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::Index;
+///
+/// struct IndexKey;
+///
+/// impl KeyProvider for IndexKey {
+///     fn key(&self) -> io::Result<[u8; 32]> {
+///         // Fetch the key of the index from a key management service.
+///         Ok([42u8; 32])
+///     }
+/// }
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let directory = EncryptedDirectory::new(RamDirectory::create(), &IndexKey)?;
+/// let mut schema_builder = Schema::builder();
+/// schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create(directory, schema_builder.build(), Default::default())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct EncryptedDirectory {
+    inner: Box<dyn Directory>,
+    cipher: Arc<Aes256Gcm>,
+    block_cache: Arc<BlockCache>,
+}
+
+impl EncryptedDirectory {
+    /// Wraps `inner`, encrypting its files with the key returned by `key_provider`.
+    ///
+    /// The decrypted blocks are cached, within a memory budget of
+    /// [`DEFAULT_BLOCK_CACHE_BUDGET`] bytes.
+    pub fn new(
+        inner: impl Into<Box<dyn Directory>>,
+        key_provider: &dyn KeyProvider,
+    ) -> io::Result<EncryptedDirectory> {
+        EncryptedDirectory::with_block_cache_budget(inner, key_provider, DEFAULT_BLOCK_CACHE_BUDGET)
+    }
+
+    /// Wraps `inner`, caching the decrypted blocks within a memory budget of
+    /// `block_cache_budget` bytes.
+    ///
+    /// A budget of 0 disables the cache.
+    pub fn with_block_cache_budget(
+        inner: impl Into<Box<dyn Directory>>,
+        key_provider: &dyn KeyProvider,
+        block_cache_budget: usize,
+    ) -> io::Result<EncryptedDirectory> {
+        let key = key_provider.key()?;
+        Ok(EncryptedDirectory {
+            inner: inner.into(),
+            cipher: Arc::new(Aes256Gcm::new(&key.into())),
+            block_cache: Arc::new(BlockCache::with_budget(block_cache_budget)),
+        })
+    }
+
+    fn new_file_encryption(&self) -> FileEncryption {
+        let mut file_id = [0u8; FILE_ID_LEN];
+        OsRng.fill_bytes(&mut file_id);
+        let mut header = [0u8; HEADER_LEN];
+        header[..4].copy_from_slice(&MAGIC_BYTES);
+        header[4] = FORMAT_VERSION;
+        header[5..9].copy_from_slice(&(ENCRYPTION_BLOCK_SIZE as u32).to_le_bytes());
+        header[9..].copy_from_slice(&file_id);
+        FileEncryption {
+            cipher: self.cipher.clone(),
+            header,
+            block_size: ENCRYPTION_BLOCK_SIZE,
+        }
+    }
+
+    fn open_encrypted_file(
+        &self,
+        ciphertext: Arc<dyn FileHandle>,
+        block_cache: Option<Arc<BlockCache>>,
+    ) -> io::Result<EncryptedFileHandle> {
+        if ciphertext.len() == 0 {
+            // The file was just created, and nothing has been written in it yet.
+            return Ok(EncryptedFileHandle {
+                encryption: FileEncryption {
+                    cipher: self.cipher.clone(),
+                    header: [0u8; HEADER_LEN],
+                    block_size: ENCRYPTION_BLOCK_SIZE,
+                },
+                ciphertext,
+                len: 0,
+                num_blocks: 0,
+                block_cache,
+            });
+        }
+        if ciphertext.len() < HEADER_LEN {
+            return Err(invalid_data("File too short to be encrypted"));
+        }
+        let header_bytes = ciphertext.read_bytes(0..HEADER_LEN)?;
+        let header: [u8; HEADER_LEN] = header_bytes.as_slice().try_into().unwrap();
+        if header[..4] != MAGIC_BYTES {
+            return Err(invalid_data("File is not encrypted"));
+        }
+        if header[4] != FORMAT_VERSION {
+            return Err(invalid_data(&format!(
+                "Unsupported encryption format version {}",
+                header[4]
+            )));
+        }
+        let block_size = u32::from_le_bytes(header[5..9].try_into().unwrap()) as usize;
+        if block_size == 0 {
+            return Err(invalid_data("Invalid encryption block size"));
+        }
+        // A file has at least one block, which may be empty.
+        let num_ciphertext_bytes = ciphertext.len() - HEADER_LEN;
+        let num_blocks = num_ciphertext_bytes.div_ceil(block_size + TAG_LEN);
+        let last_block_len = num_ciphertext_bytes
+            .checked_sub((num_blocks.saturating_sub(1)) * (block_size + TAG_LEN) + TAG_LEN)
+            .ok_or_else(|| invalid_data("Truncated encrypted file"))?;
+        Ok(EncryptedFileHandle {
+            encryption: FileEncryption {
+                cipher: self.cipher.clone(),
+                header,
+                block_size,
+            },
+            ciphertext,
+            len: (num_blocks - 1) * block_size + last_block_len,
+            num_blocks,
+            block_cache,
+        })
+    }
+}
+
+impl fmt::Debug for EncryptedDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedDirectory({:?})", self.inner)
+    }
+}
+
+impl Directory for EncryptedDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let ciphertext = self.inner.get_file_handle(path)?;
+        let file_handle = self
+            .open_encrypted_file(ciphertext, Some(self.block_cache.clone()))
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(Arc::new(file_handle))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.inner.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.inner.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let wrt = self.inner.open_write(path)?;
+        let encrypted_writer = EncryptedWriter::new(self.new_file_encryption(), wrt)
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(io::BufWriter::new(Box::new(encrypted_writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let ciphertext = OwnedBytes::new(self.inner.atomic_read(path)?);
+        // The blocks are not cached, as the file is read as a whole.
+        self.open_encrypted_file(Arc::new(ciphertext), None)
+            .and_then(|file_handle| file_handle.read_bytes(0..file_handle.len()))
+            .map(|data| data.as_slice().to_vec())
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let mut encrypted_writer = EncryptedWriter::new(self.new_file_encryption(), Vec::new())?;
+        encrypted_writer.write_all(data)?;
+        let ciphertext = encrypted_writer.finish()?;
+        self.inner.atomic_write(path, &ciphertext)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.inner.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.inner.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.inner.watch(watch_callback)
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Encrypts and decrypts the blocks of a file.
+#[derive(Clone)]
+struct FileEncryption {
+    cipher: Arc<Aes256Gcm>,
+    header: [u8; HEADER_LEN],
+    block_size: usize,
+}
+
+impl FileEncryption {
+    fn file_id(&self) -> u64 {
+        u64::from_le_bytes(self.header[HEADER_LEN - FILE_ID_LEN..].try_into().unwrap())
+    }
+
+    /// The nonce of a block is made of the random id of the file and the id of the block.
+    fn nonce(&self, block_id: u32) -> Nonce<aes_gcm::aead::consts::U12> {
+        let mut nonce = [0u8; 12];
+        nonce[..FILE_ID_LEN].copy_from_slice(&self.header[HEADER_LEN - FILE_ID_LEN..]);
+        nonce[FILE_ID_LEN..].copy_from_slice(&block_id.to_le_bytes());
+        nonce.into()
+    }
+
+    fn aad(&self, is_last_block: bool) -> [u8; HEADER_LEN + 1] {
+        let mut aad = [0u8; HEADER_LEN + 1];
+        aad[..HEADER_LEN].copy_from_slice(&self.header);
+        aad[HEADER_LEN] = is_last_block as u8;
+        aad
+    }
+
+    fn encrypt_block(&self, block_id: u32, is_last_block: bool, data: &[u8]) -> Vec<u8> {
+        let aad = self.aad(is_last_block);
+        self.cipher
+            .encrypt(
+                &self.nonce(block_id),
+                Payload {
+                    msg: data,
+                    aad: &aad,
+                },
+            )
+            .expect("encrypting a block should not fail")
+    }
+
+    fn decrypt_block(
+        &self,
+        block_id: u32,
+        is_last_block: bool,
+        ciphertext: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        let aad = self.aad(is_last_block);
+        self.cipher
+            .decrypt(
+                &self.nonce(block_id),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| invalid_data(&format!("Failed to decrypt block {block_id}")))
+    }
+}
+
+/// Writer encrypting the data written into it.
+///
+/// The data is only readable once the writer is terminated, as the last block is written on
+/// termination.
+struct EncryptedWriter<W: Write> {
+    encryption: FileEncryption,
+    wrt: W,
+    // Data of the block being written.
+    block: Vec<u8>,
+    num_blocks: u32,
+}
+
+impl<W: Write> EncryptedWriter<W> {
+    fn new(encryption: FileEncryption, mut wrt: W) -> io::Result<EncryptedWriter<W>> {
+        wrt.write_all(&encryption.header)?;
+        let block = Vec::with_capacity(encryption.block_size);
+        Ok(EncryptedWriter {
+            encryption,
+            wrt,
+            block,
+            num_blocks: 0,
+        })
+    }
+
+    fn write_block(&mut self, is_last_block: bool) -> io::Result<()> {
+        let ciphertext = self
+            .encryption
+            .encrypt_block(self.num_blocks, is_last_block, &self.block);
+        self.wrt.write_all(&ciphertext)?;
+        self.block.clear();
+        self.num_blocks = self
+            .num_blocks
+            .checked_add(1)
+            .ok_or_else(|| io::Error::other("Too many blocks in encrypted file"))?;
+        Ok(())
+    }
+
+    /// Writes the last block, and returns the underlying writer.
+    fn finish(mut self) -> io::Result<W> {
+        self.write_block(true)?;
+        Ok(self.wrt)
+    }
+}
+
+impl<W: Write> Write for EncryptedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A full block is only written once more data comes, as the last block is written
+        // differently.
+        if self.block.len() == self.encryption.block_size && !buf.is_empty() {
+            self.write_block(false)?;
+        }
+        let num_bytes = buf.len().min(self.encryption.block_size - self.block.len());
+        self.block.extend_from_slice(&buf[..num_bytes]);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.wrt.flush()
+    }
+}
+
+impl<W: TerminatingWrite> TerminatingWrite for EncryptedWriter<W> {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.write_block(true)?;
+        self.wrt.terminate_ref(token)
+    }
+}
+
+/// File handle decrypting the blocks of an encrypted file on demand.
+struct EncryptedFileHandle {
+    encryption: FileEncryption,
+    ciphertext: Arc<dyn FileHandle>,
+    // Length of the decrypted content.
+    len: usize,
+    num_blocks: usize,
+    block_cache: Option<Arc<BlockCache>>,
+}
+
+impl EncryptedFileHandle {
+    fn read_block(&self, block_id: usize) -> io::Result<OwnedBytes> {
+        let cache_key = (self.encryption.file_id(), block_id as u32);
+        if let Some(block_cache) = &self.block_cache {
+            if let Some(block) = block_cache.get(&cache_key) {
+                return Ok(block);
+            }
+        }
+        let encrypted_block_size = self.encryption.block_size + TAG_LEN;
+        let start = HEADER_LEN + block_id * encrypted_block_size;
+        let end = (start + encrypted_block_size).min(self.ciphertext.len());
+        let ciphertext = self.ciphertext.read_bytes(start..end)?;
+        let is_last_block = block_id + 1 == self.num_blocks;
+        let block = OwnedBytes::new(self.encryption.decrypt_block(
+            block_id as u32,
+            is_last_block,
+            ciphertext.as_slice(),
+        )?);
+        if let Some(block_cache) = &self.block_cache {
+            block_cache.insert(cache_key, block.clone());
+        }
+        Ok(block)
+    }
+}
+
+impl fmt::Debug for EncryptedFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptedFileHandle({:?})", self.ciphertext)
+    }
+}
+
+impl HasLen for EncryptedFileHandle {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl FileHandle for EncryptedFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        if range.end > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Range {range:?} out of bounds, the file has {} bytes",
+                    self.len
+                ),
+            ));
+        }
+        let block_size = self.encryption.block_size;
+        let first_block_id = range.start / block_size;
+        let last_block_id = (range.end - 1) / block_size;
+        if first_block_id == last_block_id {
+            let block_start = first_block_id * block_size;
+            let block = self.read_block(first_block_id)?;
+            return Ok(block.slice(range.start - block_start..range.end - block_start));
+        }
+        let mut data = Vec::with_capacity(range.len());
+        for block_id in first_block_id..=last_block_id {
+            let block_start = block_id * block_size;
+            let block = self.read_block(block_id)?;
+            let start = range.start.saturating_sub(block_start);
+            let end = (range.end - block_start).min(block.len());
+            data.extend_from_slice(&block.as_slice()[start..end]);
+        }
+        Ok(OwnedBytes::new(data))
+    }
+}
+
+/// LRU cache of decrypted blocks, within a memory budget.
+struct BlockCache {
+    budget: usize,
+    state: Mutex<BlockCacheState>,
+}
+
+struct BlockCacheState {
+    // Blocks by file id and block id.
+    blocks: LruCache<(u64, u32), OwnedBytes>,
+    num_bytes: usize,
+}
+
+impl BlockCache {
+    fn with_budget(budget: usize) -> BlockCache {
+        BlockCache {
+            budget,
+            state: Mutex::new(BlockCacheState {
+                blocks: LruCache::unbounded(),
+                num_bytes: 0,
+            }),
+        }
+    }
+
+    fn get(&self, key: &(u64, u32)) -> Option<OwnedBytes> {
+        self.state.lock().unwrap().blocks.get(key).cloned()
+    }
+
+    fn insert(&self, key: (u64, u32), block: OwnedBytes) {
+        if block.len() > self.budget {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.num_bytes += block.len();
+        if let Some(previous_block) = state.blocks.put(key, block) {
+            state.num_bytes -= previous_block.len();
+        }
+        while state.num_bytes > self.budget {
+            let Some((_, evicted_block)) = state.blocks.pop_lru() else {
+                break;
+            };
+            state.num_bytes -= evicted_block.len();
+        }
+    }
+
+    #[cfg(test)]
+    fn num_bytes(&self) -> usize {
+        self.state.lock().unwrap().num_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use super::*;
+    use crate::directory::RamDirectory;
+
+    struct TestKey([u8; 32]);
+
+    impl KeyProvider for TestKey {
+        fn key(&self) -> io::Result<[u8; 32]> {
+            Ok(self.0)
+        }
+    }
+
+    fn write_file(directory: &dyn Directory, path: &Path, data: &[u8]) {
+        let mut wrt = directory.open_write(path).unwrap();
+        wrt.write_all(data).unwrap();
+        wrt.terminate().unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let ram_directory = RamDirectory::create();
+        let directory =
+            EncryptedDirectory::new(ram_directory.clone(), &TestKey([1u8; 32])).unwrap();
+        let data: Vec<u8> = (0..3 * ENCRYPTION_BLOCK_SIZE + 17)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        for len in [
+            0,
+            1,
+            ENCRYPTION_BLOCK_SIZE,
+            ENCRYPTION_BLOCK_SIZE + 1,
+            data.len(),
+        ] {
+            let path = Path::new("file");
+            write_file(&directory, path, &data[..len]);
+            let ciphertext = ram_directory.open_read(path).unwrap().read_bytes().unwrap();
+            let num_blocks = len.div_ceil(ENCRYPTION_BLOCK_SIZE).max(1);
+            assert_eq!(ciphertext.len(), HEADER_LEN + len + num_blocks * TAG_LEN);
+            // A few bytes of ciphertext could match the plaintext by chance.
+            if len >= 16 {
+                assert_ne!(
+                    &ciphertext.as_slice()[HEADER_LEN..HEADER_LEN + len],
+                    &data[..len]
+                );
+            }
+            let file_slice = directory.open_read(path).unwrap();
+            assert_eq!(file_slice.len(), len);
+            assert_eq!(file_slice.read_bytes().unwrap().as_slice(), &data[..len]);
+            directory.delete(path).unwrap();
+        }
+
+        let path = Path::new("file");
+        write_file(&directory, path, &data);
+        let file_slice = directory.open_read(path).unwrap();
+        for range in [
+            0..0,
+            3..10,
+            ENCRYPTION_BLOCK_SIZE - 1..ENCRYPTION_BLOCK_SIZE + 1,
+            10..3 * ENCRYPTION_BLOCK_SIZE + 2,
+            data.len() - 5..data.len(),
+        ] {
+            assert_eq!(
+                file_slice
+                    .read_bytes_slice(range.clone())
+                    .unwrap()
+                    .as_slice(),
+                &data[range]
+            );
+        }
+    }
+
+    #[test]
+    fn test_encrypted_atomic_write() {
+        let ram_directory = RamDirectory::create();
+        let directory =
+            EncryptedDirectory::new(ram_directory.clone(), &TestKey([1u8; 32])).unwrap();
+        let path = Path::new("meta.json");
+        directory.atomic_write(path, b"{\"segments\": []}").unwrap();
+        assert_ne!(
+            ram_directory.atomic_read(path).unwrap(),
+            b"{\"segments\": []}"
+        );
+        assert_eq!(directory.atomic_read(path).unwrap(), b"{\"segments\": []}");
+        let other_key_directory =
+            EncryptedDirectory::new(ram_directory, &TestKey([2u8; 32])).unwrap();
+        assert!(other_key_directory.atomic_read(path).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_file_tampering() {
+        let ram_directory = RamDirectory::create();
+        let directory = EncryptedDirectory::with_block_cache_budget(
+            ram_directory.clone(),
+            &TestKey([1u8; 32]),
+            0,
+        )
+        .unwrap();
+        let data = vec![7u8; 2 * ENCRYPTION_BLOCK_SIZE + 3];
+        write_file(&directory, Path::new("file"), &data);
+        let ciphertext = ram_directory.atomic_read(Path::new("file")).unwrap();
+
+        // Modified byte.
+        let mut modified_ciphertext = ciphertext.clone();
+        modified_ciphertext[HEADER_LEN + 5] ^= 1;
+        ram_directory
+            .atomic_write(Path::new("modified"), &modified_ciphertext)
+            .unwrap();
+        let file_slice = directory.open_read(Path::new("modified")).unwrap();
+        let err = file_slice.read_bytes_slice(0..1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        // The other blocks are still readable.
+        assert_eq!(
+            file_slice
+                .read_bytes_slice(ENCRYPTION_BLOCK_SIZE..ENCRYPTION_BLOCK_SIZE + 2)
+                .unwrap()
+                .as_slice(),
+            &[7u8, 7u8]
+        );
+
+        // Truncated after a full block.
+        let truncated_len = HEADER_LEN + 2 * (ENCRYPTION_BLOCK_SIZE + TAG_LEN);
+        ram_directory
+            .atomic_write(Path::new("truncated"), &ciphertext[..truncated_len])
+            .unwrap();
+        let file_slice = directory.open_read(Path::new("truncated")).unwrap();
+        assert_eq!(file_slice.len(), 2 * ENCRYPTION_BLOCK_SIZE);
+        assert!(file_slice.read_bytes().is_err());
+
+        // Not encrypted.
+        ram_directory
+            .atomic_write(Path::new("plain"), &data)
+            .unwrap();
+        assert!(directory.open_read(Path::new("plain")).is_err());
+    }
+
+    #[test]
+    fn test_block_cache_budget() {
+        let block_cache = BlockCache::with_budget(10);
+        block_cache.insert((1, 0), OwnedBytes::new(vec![0u8; 4]));
+        block_cache.insert((1, 1), OwnedBytes::new(vec![1u8; 4]));
+        assert!(block_cache.get(&(1, 0)).is_some());
+        block_cache.insert((2, 0), OwnedBytes::new(vec![2u8; 4]));
+        assert_eq!(block_cache.num_bytes(), 8);
+        // The least recently used block is evicted.
+        assert!(block_cache.get(&(1, 1)).is_none());
+        assert!(block_cache.get(&(1, 0)).is_some());
+        block_cache.insert((3, 0), OwnedBytes::new(vec![3u8; 11]));
+        assert!(block_cache.get(&(3, 0)).is_none());
+        assert_eq!(block_cache.num_bytes(), 8);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_encrypted_index() -> crate::Result<()> {
+        use crate::collector::TopDocs;
+        use crate::directory::MmapDirectory;
+        use crate::query::QueryParser;
+        use crate::schema::{Schema, STORED, TEXT};
+        use crate::{Index, IndexWriter, TantivyDocument};
+
+        let tempdir = tempfile::TempDir::new()?;
+        let key = TestKey([3u8; 32]);
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let schema = schema_builder.build();
+        {
+            let directory = EncryptedDirectory::new(MmapDirectory::open(tempdir.path())?, &key)?;
+            let index = Index::create(directory, schema.clone(), Default::default())?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(body => "the secret document"))?;
+            index_writer.add_document(doc!(body => "another document"))?;
+            index_writer.commit()?;
+        }
+        // The plain directory can't read the index.
+        assert!(Index::open_in_dir(tempdir.path()).is_err());
+        for entry in std::fs::read_dir(tempdir.path())? {
+            let content = std::fs::read(entry?.path())?;
+            assert!(!content.windows(6).any(|window| window == b"secret"));
+        }
+
+        let directory = EncryptedDirectory::new(MmapDirectory::open(tempdir.path())?, &key)?;
+        let index = Index::open(directory)?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![body]).parse_query("secret")?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10).order_by_score())?;
+        assert_eq!(top_docs.len(), 1);
+        let doc: TantivyDocument = searcher.doc(top_docs[0].1)?;
+        assert_eq!(doc.get_first_str(body), Some("the secret document"));
+        assert!(index.validate_checksum()?.is_empty());
+        Ok(())
+    }
+}
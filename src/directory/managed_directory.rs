@@ -40,6 +40,7 @@ fn is_managed(path: &Path) -> bool {
 pub struct ManagedDirectory {
     directory: Box<dyn Directory>,
     meta_informations: Arc<RwLock<MetaInformation>>,
+    read_only: bool,
 }
 
 #[derive(Debug, Default)]
@@ -59,9 +60,31 @@ fn save_managed_paths(
     Ok(())
 }
 
+fn read_only_error(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::ReadOnlyFilesystem,
+        format!("Cannot write {path:?}: the directory was opened in read-only mode"),
+    )
+}
+
 impl ManagedDirectory {
     /// Wraps a directory as managed directory.
     pub fn wrap(directory: Box<dyn Directory>) -> crate::Result<ManagedDirectory> {
+        ManagedDirectory::wrap_with_mode(directory, false)
+    }
+
+    /// Wraps a directory as a read-only managed directory.
+    ///
+    /// A read-only managed directory never writes to, deletes from, or acquires locks on the
+    /// underlying directory: writes, deletes and locks fail, and garbage collection does nothing.
+    pub fn wrap_read_only(directory: Box<dyn Directory>) -> crate::Result<ManagedDirectory> {
+        ManagedDirectory::wrap_with_mode(directory, true)
+    }
+
+    fn wrap_with_mode(
+        directory: Box<dyn Directory>,
+        read_only: bool,
+    ) -> crate::Result<ManagedDirectory> {
         match directory.atomic_read(&MANAGED_FILEPATH) {
             Ok(data) => {
                 let managed_files_json = String::from_utf8_lossy(&data);
@@ -77,11 +100,13 @@ impl ManagedDirectory {
                     meta_informations: Arc::new(RwLock::new(MetaInformation {
                         managed_paths: managed_files,
                     })),
+                    read_only,
                 })
             }
             Err(OpenReadError::FileDoesNotExist(_)) => Ok(ManagedDirectory {
                 directory,
                 meta_informations: Arc::default(),
+                read_only,
             }),
             io_err @ Err(OpenReadError::IoError { .. }) => Err(io_err.err().unwrap().into()),
             Err(OpenReadError::IncompatibleIndex(incompatibility)) => {
@@ -106,10 +131,18 @@ impl ManagedDirectory {
     /// If a file cannot be deleted (for permission reasons for instance)
     /// an error is simply logged, and the file remains in the list of managed
     /// files.
+    ///
+    /// On a read-only directory, this method does nothing.
     pub fn garbage_collect<L: FnOnce() -> HashSet<PathBuf>>(
         &mut self,
         get_living_files: L,
     ) -> crate::Result<GarbageCollectionResult> {
+        if self.read_only {
+            return Ok(GarbageCollectionResult {
+                deleted_files: Vec::new(),
+                failed_to_delete_files: Vec::new(),
+            });
+        }
         info!("Garbage collect");
         let mut files_to_delete = vec![];
 
@@ -211,6 +244,9 @@ impl ManagedDirectory {
     /// They are not managed and cannot be subjected
     /// to garbage collection.
     fn register_file_as_managed(&self, filepath: &Path) -> io::Result<()> {
+        if self.read_only {
+            return Err(read_only_error(filepath));
+        }
         // Files starting by "." (e.g. lock files) are not managed.
         if !is_managed(filepath) {
             return Ok(());
@@ -239,6 +275,11 @@ impl ManagedDirectory {
         Ok(())
     }
 
+    /// Returns true if the directory was opened with [`ManagedDirectory::wrap_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     /// Verify checksum of a managed file
     pub fn validate_checksum(&self, path: &Path) -> result::Result<bool, OpenReadError> {
         let reader = self.directory.open_read(path)?;
@@ -304,6 +345,12 @@ impl Directory for ManagedDirectory {
     }
 
     fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        if self.read_only {
+            return Err(DeleteError::IoError {
+                io_error: Arc::new(read_only_error(path)),
+                filepath: path.to_path_buf(),
+            });
+        }
         self.directory.delete(path)
     }
 
@@ -312,6 +359,11 @@ impl Directory for ManagedDirectory {
     }
 
     fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        if self.read_only {
+            return Err(LockError::IoError(Arc::new(read_only_error(
+                &lock.filepath,
+            ))));
+        }
         self.directory.acquire_lock(lock)
     }
 
@@ -320,6 +372,9 @@ impl Directory for ManagedDirectory {
     }
 
     fn sync_directory(&self) -> io::Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
         self.directory.sync_directory()?;
         Ok(())
     }
@@ -330,6 +385,7 @@ impl Clone for ManagedDirectory {
         ManagedDirectory {
             directory: self.directory.box_clone(),
             meta_informations: Arc::clone(&self.meta_informations),
+            read_only: self.read_only,
         }
     }
 }
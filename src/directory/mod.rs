@@ -5,6 +5,8 @@ mod mmap_directory;
 
 mod directory;
 mod directory_lock;
+#[cfg(feature = "encryption")]
+mod encrypted_directory;
 pub mod footer;
 mod managed_directory;
 mod ram_directory;
@@ -24,6 +26,10 @@ pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 pub use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
+#[cfg(feature = "encryption")]
+pub use self::encrypted_directory::{
+    EncryptedDirectory, KeyProvider, DEFAULT_BLOCK_CACHE_BUDGET, ENCRYPTION_BLOCK_SIZE,
+};
 pub use self::ram_directory::RamDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
@@ -117,6 +117,58 @@ mod ram_directory_tests {
     }
 }
 
+#[cfg(feature = "encryption")]
+mod encrypted_directory_tests {
+    use std::io;
+
+    use crate::directory::{EncryptedDirectory, KeyProvider, RamDirectory};
+
+    type DirectoryImpl = EncryptedDirectory;
+
+    struct TestKey;
+
+    impl KeyProvider for TestKey {
+        fn key(&self) -> io::Result<[u8; 32]> {
+            Ok([7u8; 32])
+        }
+    }
+
+    fn make_directory() -> DirectoryImpl {
+        EncryptedDirectory::new(RamDirectory::default(), &TestKey).unwrap()
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
 fn test_simple(directory: &dyn Directory) -> crate::Result<()> {
     let test_path: &'static Path = Path::new("some_path_for_test");
     let mut write_file = directory.open_write(test_path)?;
@@ -14,6 +14,14 @@ use crate::fastfield::FastFieldNotAvailableError;
 use crate::schema::document::DeserializeError;
 use crate::{query, schema};
 
+/// End of the message of the [`TantivyError::InvalidArgument`] returned when a field is not
+/// configured as fast field.
+pub(crate) const NOT_A_FAST_FIELD: &str = "is not configured as fast field";
+
+/// Beginning of the message of the [`TantivyError::InvalidArgument`] errors reporting an
+/// unexpected internal state.
+pub(crate) const UNEXPECTED_STATE: &str = "Internal error:";
+
 /// Represents a `DataCorruption` error.
 ///
 /// When facing data corruption, tantivy actually panics or returns this error.
@@ -70,6 +78,10 @@ pub enum TantivyError {
     /// Index already exists in this directory.
     #[error("Index already exists")]
     IndexAlreadyExists,
+    /// The index was opened with [`Index::open_read_only`](crate::Index::open_read_only)
+    /// and cannot be written to.
+    #[error("The index was opened in read-only mode")]
+    ReadOnlyIndex,
     /// Failed to acquire file lock.
     #[error("Failed to acquire Lockfile: {0:?}. {1:?}")]
     LockFailure(LockError, Option<String>),
@@ -110,6 +122,98 @@ pub enum TantivyError {
     #[error("Deserialize error: {0}")]
     /// An error occurred while attempting to deserialize a document.
     DeserializeError(DeserializeError),
+    /// The rewrite deadline of a search was exceeded, while creating the weight of the query
+    /// or enumerating the terms it matches.
+    /// See [`SearchOptions`](crate::SearchOptions).
+    #[error("The rewrite deadline of the search was exceeded")]
+    RewriteDeadlineExceeded,
+    /// The collection deadline of a search was exceeded, while collecting the matching
+    /// documents.
+    /// See [`SearchOptions`](crate::SearchOptions).
+    #[error("The collection deadline of the search was exceeded")]
+    CollectionDeadlineExceeded,
+}
+
+/// Classification of a [`TantivyError`].
+///
+/// It is meant to tell the errors caused by the request, such as a malformed query or a
+/// field lacking the options a query requires, apart from the errors of the system, e.g. to
+/// map them to status codes.
+///
+/// The kind follows the variant of the error, except for
+/// [`TantivyError::InvalidArgument`]: the errors reporting a field which is not configured as
+/// fast field, or an unexpected internal state, are told apart by their message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The query, or an argument of the request, is invalid.
+    InvalidQuery,
+    /// The request does not match the schema, e.g. it targets a field which does not exist,
+    /// or lacks the options the request requires.
+    SchemaMismatch,
+    /// The data of the index cannot be read, as it is corrupted or was written by an
+    /// incompatible version of tantivy.
+    Corruption,
+    /// A limit was reached, e.g. the memory limit of an aggregation or a deadline of a search,
+    /// or a lock is held by another writer.
+    ResourceExhausted,
+    /// An IO operation failed.
+    Io,
+    /// An unexpected internal state was reached.
+    Internal,
+}
+
+impl TantivyError {
+    /// Returns the classification of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            TantivyError::AggregationError(aggregation_error) => match aggregation_error {
+                AggregationError::InternalError(_) => ErrorKind::Internal,
+                AggregationError::InvalidRequest(_)
+                | AggregationError::DateHistogramParseError(_)
+                | AggregationError::MissingAggregation(_)
+                | AggregationError::UnexpectedAggregationKind { .. } => ErrorKind::InvalidQuery,
+                AggregationError::MemoryExceeded { .. }
+                | AggregationError::BucketLimitExceeded { .. } => ErrorKind::ResourceExhausted,
+            },
+            TantivyError::OpenReadError(OpenReadError::IncompatibleIndex(_))
+            | TantivyError::IncompatibleIndex(_)
+            | TantivyError::DataCorruption(_) => ErrorKind::Corruption,
+            TantivyError::OpenDirectoryError(_)
+            | TantivyError::OpenReadError(_)
+            | TantivyError::OpenWriteError(_)
+            | TantivyError::IndexAlreadyExists
+            | TantivyError::ReadOnlyIndex
+            | TantivyError::IoError(_) => ErrorKind::Io,
+            TantivyError::LockFailure(LockError::LockBusy, _)
+            | TantivyError::RewriteDeadlineExceeded
+            | TantivyError::CollectionDeadlineExceeded => ErrorKind::ResourceExhausted,
+            TantivyError::LockFailure(LockError::IoError(_), _) => ErrorKind::Io,
+            TantivyError::FieldNotFound(_) | TantivyError::SchemaError(_) => {
+                ErrorKind::SchemaMismatch
+            }
+            TantivyError::InvalidArgument(message) if message.ends_with(NOT_A_FAST_FIELD) => {
+                ErrorKind::SchemaMismatch
+            }
+            TantivyError::InvalidArgument(message) if message.starts_with(UNEXPECTED_STATE) => {
+                ErrorKind::Internal
+            }
+            TantivyError::InvalidArgument(_) | TantivyError::IndexBuilderMissingArgument(_) => {
+                ErrorKind::InvalidQuery
+            }
+            TantivyError::Poisoned
+            | TantivyError::ErrorInThread(_)
+            | TantivyError::SystemError(_)
+            | TantivyError::InternalError(_) => ErrorKind::Internal,
+            TantivyError::DeserializeError(deserialize_error) => match deserialize_error {
+                DeserializeError::CorruptedValue(_) | DeserializeError::UnsupportedVersion(..) => {
+                    ErrorKind::Corruption
+                }
+                DeserializeError::UnsupportedType(_)
+                | DeserializeError::TypeMismatch { .. }
+                | DeserializeError::Custom(_) => ErrorKind::SchemaMismatch,
+            },
+        }
+    }
 }
 
 impl From<io::Error> for TantivyError {
@@ -186,3 +290,133 @@ impl From<DeserializeError> for TantivyError {
         TantivyError::DeserializeError(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::PoisonError;
+
+    use super::{ErrorKind, TantivyError};
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::{AggContextParams, AggregationCollector, AggregationLimitsGuard};
+    use crate::collector::{Count, TopDocs};
+    use crate::directory::{Directory, RamDirectory};
+    use crate::query::{AllQuery, ExistsQuery, QueryParser, RegexQuery};
+    use crate::schema::{Schema, FAST, STORED, STRING, TEXT};
+    use crate::tokenizer::TokenizerManager;
+    use crate::{Index, IndexWriter, Order, Searcher};
+
+    fn create_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let size = schema_builder.add_u64_field("size", STORED);
+        let score = schema_builder.add_u64_field("score", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..100u64 {
+            index_writer.add_document(doc!(
+                title => "hello",
+                category => format!("category{doc_id}"),
+                size => doc_id,
+                score => doc_id,
+            ))?;
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn aggregate(
+        searcher: &Searcher,
+        agg_req: serde_json::Value,
+        limits: AggregationLimitsGuard,
+    ) -> crate::Result<()> {
+        let agg_req: Aggregations = serde_json::from_value(agg_req).unwrap();
+        let context = AggContextParams::new(limits, TokenizerManager::default());
+        let collector = AggregationCollector::from_aggs(agg_req, context);
+        searcher.search(&AllQuery, &collector).map(|_| ())
+    }
+
+    #[test]
+    fn test_error_kind() -> crate::Result<()> {
+        let index = create_index()?;
+        let schema = index.schema();
+        let title = schema.get_field("title")?;
+        let searcher = index.reader()?.searcher();
+
+        // Invalid queries.
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let error: TantivyError = query_parser.parse_query("size:abc").unwrap_err().into();
+        assert_eq!(error.kind(), ErrorKind::InvalidQuery);
+        let error = RegexQuery::from_pattern("(hel", title).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidQuery);
+        let error = aggregate(
+            &searcher,
+            json!({ "histo": { "histogram": { "field": "score", "interval": -1.0 } } }),
+            AggregationLimitsGuard::default(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidQuery);
+
+        // Requests that do not match the schema.
+        assert_eq!(
+            schema.get_field("does_not_exist").unwrap_err().kind(),
+            ErrorKind::SchemaMismatch
+        );
+        let error = searcher
+            .search(&ExistsQuery::new("size".to_string(), false), &Count)
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::SchemaMismatch);
+        let error = searcher
+            .search(
+                &AllQuery,
+                &TopDocs::with_limit(10).order_by_u64_field("size", Order::Desc),
+            )
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::SchemaMismatch);
+        let error = aggregate(
+            &searcher,
+            json!({ "avg_size": { "avg": { "field": "size" } } }),
+            AggregationLimitsGuard::default(),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::SchemaMismatch);
+
+        // Exhausted resources.
+        let error = aggregate(
+            &searcher,
+            json!({ "histo": { "histogram": { "field": "score", "interval": 0.1 } } }),
+            AggregationLimitsGuard::new(Some(5_000), None),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ResourceExhausted);
+        let error = aggregate(
+            &searcher,
+            json!({ "categories": { "terms": { "field": "category", "size": 1000 } } }),
+            AggregationLimitsGuard::new(None, Some(10)),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ResourceExhausted);
+        let _index_writer: IndexWriter = index.writer_for_tests()?;
+        let error = index
+            .writer_for_tests::<crate::TantivyDocument>()
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), ErrorKind::ResourceExhausted);
+
+        // Corrupted index.
+        let directory = RamDirectory::create();
+        directory.atomic_write(Path::new("meta.json"), b"{not json")?;
+        let error = Index::open(directory).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Corruption);
+
+        // Io errors.
+        let error = Index::open_in_dir("/path/does/not/exist").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Io);
+
+        // Internal errors.
+        let error: TantivyError = PoisonError::new(()).into();
+        assert_eq!(error.kind(), ErrorKind::Internal);
+        Ok(())
+    }
+}
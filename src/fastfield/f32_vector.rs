@@ -0,0 +1,500 @@
+use std::io::{self, Write};
+use std::ops::Range;
+
+use common::{BinarySerializable, OwnedBytes};
+
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::schema::{Field, FieldType, Schema};
+use crate::{DocId, TantivyError};
+
+/// Size of the header of the column of a field: the number of docs,
+/// the number of dimensions and whether offsets are stored.
+const HEADER_NUM_BYTES: usize = 12;
+
+/// Column of the f32 vectors of a [`F32Vector`](crate::schema::FieldType::F32Vector) field.
+///
+/// The values of all of the documents are stored contiguously as little-endian `f32`,
+/// so that the vector of a document can be accessed without copying it with
+/// [`F32VectorColumn::get_slice`].
+///
+/// Documents without any value for the field have an empty vector.
+#[derive(Clone)]
+pub struct F32VectorColumn {
+    num_docs: u32,
+    dimensions: Option<u32>,
+    // `num_docs + 1` little-endian u64, the offset of the first value of each document.
+    // They are not stored if every document has a vector of `dimensions` values.
+    offsets: Option<OwnedBytes>,
+    values: OwnedBytes,
+}
+
+impl F32VectorColumn {
+    /// A column in which all of the documents have an empty vector.
+    pub(crate) fn empty() -> F32VectorColumn {
+        F32VectorColumn {
+            num_docs: 0,
+            dimensions: None,
+            offsets: None,
+            values: OwnedBytes::empty(),
+        }
+    }
+
+    pub(crate) fn open(mut bytes: OwnedBytes) -> io::Result<F32VectorColumn> {
+        if bytes.len() < HEADER_NUM_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "f32 vector column is too short",
+            ));
+        }
+        let num_docs = u32::deserialize(&mut bytes)?;
+        let dimensions = Some(u32::deserialize(&mut bytes)?).filter(|&dimensions| dimensions > 0);
+        let has_offsets = u32::deserialize(&mut bytes)? != 0;
+        let offsets = if has_offsets {
+            let offsets_num_bytes = (num_docs as usize + 1) * 8;
+            if bytes.len() < offsets_num_bytes {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "f32 vector column offsets are truncated",
+                ));
+            }
+            let (offsets, values) = bytes.split(offsets_num_bytes);
+            bytes = values;
+            Some(offsets)
+        } else {
+            None
+        };
+        let vector_num_bytes = dimensions.unwrap_or(1) as usize * 4;
+        if bytes.len() % vector_num_bytes != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "f32 vector column values are not a whole number of vectors",
+            ));
+        }
+        if offsets.is_none() && bytes.len() != num_docs as usize * vector_num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "f32 vector column values do not match the number of docs",
+            ));
+        }
+        Ok(F32VectorColumn {
+            num_docs,
+            dimensions,
+            offsets,
+            values: bytes,
+        })
+    }
+
+    /// Returns the number of documents of the column.
+    pub fn num_docs(&self) -> u32 {
+        self.num_docs
+    }
+
+    /// Returns the number of values of the vector of each document, if it is fixed by the schema.
+    ///
+    /// Documents without any value for the field still have an empty vector.
+    pub fn dimensions(&self) -> Option<u32> {
+        self.dimensions
+    }
+
+    /// Returns the number of values of the vector of the document.
+    pub fn num_vals(&self, doc: DocId) -> u32 {
+        self.value_range(doc).len() as u32
+    }
+
+    fn value_range(&self, doc: DocId) -> Range<usize> {
+        match (&self.offsets, self.dimensions) {
+            (Some(offsets), _) => {
+                let read_offset = |idx: usize| {
+                    let offset_bytes: [u8; 8] =
+                        offsets.as_slice()[idx * 8..][..8].try_into().unwrap();
+                    u64::from_le_bytes(offset_bytes) as usize
+                };
+                read_offset(doc as usize)..read_offset(doc as usize + 1)
+            }
+            (None, Some(dimensions)) => {
+                let start = doc as usize * dimensions as usize;
+                start..start + dimensions as usize
+            }
+            (None, None) => 0..0,
+        }
+    }
+
+    /// Returns the little-endian bytes of the values of the document.
+    pub(crate) fn value_bytes(&self, doc: DocId) -> &[u8] {
+        let value_range = self.value_range(doc);
+        &self.values.as_slice()[value_range.start * 4..value_range.end * 4]
+    }
+
+    /// Clears `output` and fills it with the values of the vector of the document.
+    pub fn get_vals_f32(&self, doc: DocId, output: &mut Vec<f32>) {
+        output.clear();
+        output.extend(self.values_for_doc(doc));
+    }
+
+    /// Returns an iterator over the values of the vector of the document.
+    pub fn values_for_doc(&self, doc: DocId) -> impl Iterator<Item = f32> + '_ {
+        self.value_bytes(doc)
+            .chunks_exact(4)
+            .map(|value_bytes| f32::from_le_bytes(value_bytes.try_into().unwrap()))
+    }
+
+    /// Returns the vector of the document, without copying it.
+    ///
+    /// Returns `None` if the values can't be read in place, either because the platform is
+    /// big-endian or because the underlying data is not aligned on 4 bytes. This does not happen
+    /// with the [`MmapDirectory`](crate::directory::MmapDirectory) on little-endian platforms.
+    /// [`F32VectorColumn::get_vals_f32`] works in every case.
+    pub fn get_slice(&self, doc: DocId) -> Option<&[f32]> {
+        if cfg!(target_endian = "big") {
+            return None;
+        }
+        let value_bytes = self.value_bytes(doc);
+        // SAFETY: every bit pattern is a valid f32, and the values are stored as little-endian
+        // f32 which matches the platform endianness.
+        let (prefix, values, suffix) = unsafe { value_bytes.align_to::<f32>() };
+        if !prefix.is_empty() || !suffix.is_empty() {
+            return None;
+        }
+        Some(values)
+    }
+}
+
+/// The f32 vector fields of a schema, with their fixed number of dimensions.
+#[derive(Clone)]
+pub(crate) struct F32VectorFields {
+    fields: Vec<(Field, Option<u32>)>,
+    // Position of each field of the schema in `fields`.
+    field_ords: Vec<Option<usize>>,
+}
+
+impl F32VectorFields {
+    pub(crate) fn for_schema(schema: &Schema) -> F32VectorFields {
+        let mut fields = Vec::new();
+        let mut field_ords = vec![None; schema.num_fields()];
+        for (field, field_entry) in schema.fields() {
+            if let FieldType::F32Vector(options) = field_entry.field_type() {
+                field_ords[field.field_id() as usize] = Some(fields.len());
+                fields.push((field, options.dimensions()));
+            }
+        }
+        F32VectorFields { fields, field_ords }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Checks that the document only has f64 values for the f32 vector fields,
+    /// and that their number matches the dimensions of the field.
+    pub(crate) fn check_document<D: Document>(
+        &self,
+        schema: &Schema,
+        doc: &D,
+    ) -> crate::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut values: Vec<Vec<f32>> = vec![Vec::new(); self.fields.len()];
+        self.append_document(schema, doc, &mut values)
+    }
+
+    /// Appends the values of the f32 vector fields of the document to `values`,
+    /// which has one vector per field.
+    ///
+    /// On error, some values may have been appended.
+    fn append_document<D: Document>(
+        &self,
+        schema: &Schema,
+        doc: &D,
+        values: &mut [Vec<f32>],
+    ) -> crate::Result<()> {
+        let num_vals_before: Vec<usize> = values.iter().map(Vec::len).collect();
+        for (field, value) in doc.iter_fields_and_values() {
+            let Some(field_ord) = self.field_ords[field.field_id() as usize] else {
+                continue;
+            };
+            append_values(value, &mut values[field_ord]).map_err(|_| {
+                TantivyError::SchemaError(format!(
+                    "Expected f64 values for the f32 vector field {:?}",
+                    schema.get_field_name(field)
+                ))
+            })?;
+        }
+        for (field_ord, &(field, dimensions)) in self.fields.iter().enumerate() {
+            let num_vals = values[field_ord].len() - num_vals_before[field_ord];
+            if let Some(dimensions) = dimensions {
+                if num_vals != 0 && num_vals != dimensions as usize {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The f32 vector field {:?} expects {dimensions} values, got {num_vals}",
+                        schema.get_field_name(field)
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn append_values<'a, V: Value<'a>>(value: V, values: &mut Vec<f32>) -> Result<(), ()> {
+    match value.as_value() {
+        ReferenceValue::Leaf(ReferenceValueLeaf::F64(val)) => values.push(val as f32),
+        ReferenceValue::Leaf(ReferenceValueLeaf::Null) => {}
+        ReferenceValue::Array(elements) => {
+            for element in elements {
+                append_values(element, values)?;
+            }
+        }
+        _ => return Err(()),
+    }
+    Ok(())
+}
+
+/// Buffers the values of the f32 vector fields of the documents of a segment.
+pub(crate) struct F32VectorsWriter {
+    schema: Schema,
+    fields: F32VectorFields,
+    values: Vec<Vec<f32>>,
+    // For each field, the offset of the first value of each document, followed by
+    // the total number of values.
+    offsets: Vec<Vec<u64>>,
+}
+
+impl F32VectorsWriter {
+    pub(crate) fn for_schema(schema: &Schema) -> F32VectorsWriter {
+        let fields = F32VectorFields::for_schema(schema);
+        let num_fields = fields.fields.len();
+        F32VectorsWriter {
+            schema: schema.clone(),
+            fields,
+            values: vec![Vec::new(); num_fields],
+            offsets: vec![vec![0]; num_fields],
+        }
+    }
+
+    /// The memory used (inclusive childs)
+    pub(crate) fn mem_usage(&self) -> usize {
+        let values_mem_usage: usize = self
+            .values
+            .iter()
+            .map(|values| values.capacity() * std::mem::size_of::<f32>())
+            .sum();
+        let offsets_mem_usage: usize = self
+            .offsets
+            .iter()
+            .map(|offsets| offsets.capacity() * std::mem::size_of::<u64>())
+            .sum();
+        values_mem_usage + offsets_mem_usage
+    }
+
+    /// Records the values of the f32 vector fields of a new document.
+    ///
+    /// If the document is rejected, nothing is recorded.
+    pub(crate) fn add_document<D: Document>(&mut self, doc: &D) -> crate::Result<()> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        if let Err(err) = self
+            .fields
+            .append_document(&self.schema, doc, &mut self.values)
+        {
+            for (values, offsets) in self.values.iter_mut().zip(&self.offsets) {
+                values.truncate(*offsets.last().unwrap() as usize);
+            }
+            return Err(err);
+        }
+        for (values, offsets) in self.values.iter().zip(self.offsets.iter_mut()) {
+            offsets.push(values.len() as u64);
+        }
+        Ok(())
+    }
+
+    /// Serializes the values of each f32 vector field.
+    pub(crate) fn serialize(&self, wrt: &mut CompositeWrite<WritePtr>) -> io::Result<()> {
+        for (field_ord, &(field, dimensions)) in self.fields.fields.iter().enumerate() {
+            let values = &self.values[field_ord];
+            serialize_f32_vector_field(
+                wrt.for_field(field),
+                dimensions,
+                &self.offsets[field_ord],
+                |wrt| {
+                    for value in values {
+                        wrt.write_all(&value.to_le_bytes())?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes the column of a field, given the offsets of the values of its documents.
+///
+/// `write_values` writes the values, as little-endian f32.
+pub(crate) fn serialize_f32_vector_field<W: Write>(
+    wrt: &mut W,
+    dimensions: Option<u32>,
+    offsets: &[u64],
+    write_values: impl FnOnce(&mut W) -> io::Result<()>,
+) -> io::Result<()> {
+    let num_docs = offsets.len() as u64 - 1;
+    let num_vals = *offsets.last().unwrap();
+    let all_docs_have_dimensions = dimensions
+        .map(|dimensions| num_vals == num_docs * dimensions as u64)
+        .unwrap_or(false);
+    (num_docs as u32).serialize(wrt)?;
+    dimensions.unwrap_or(0).serialize(wrt)?;
+    // The header and the offsets have a size which is a multiple of 4 bytes, so that the values
+    // are aligned if the column is.
+    if all_docs_have_dimensions {
+        0u32.serialize(wrt)?;
+    } else {
+        1u32.serialize(wrt)?;
+        for offset in offsets {
+            offset.serialize(wrt)?;
+        }
+    }
+    write_values(wrt)?;
+    wrt.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use common::OwnedBytes;
+
+    use super::F32VectorColumn;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{F32VectorOptions, Schema, INDEXED, STORED};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, TantivyError, Term};
+
+    fn vectors_of(column: &F32VectorColumn) -> Vec<Vec<f32>> {
+        let mut vals = Vec::new();
+        (0..column.num_docs())
+            .map(|doc| {
+                column.get_vals_f32(doc, &mut vals);
+                if let Some(slice) = column.get_slice(doc) {
+                    assert_eq!(slice, &vals[..]);
+                }
+                vals.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_f32_vector_with_missing_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let embedding = schema_builder.add_f32_vector_field("embedding", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(embedding => 1.0f64, embedding => 2.5f64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(embedding => -3.0f64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let column = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .f32_vector("embedding")?;
+        assert_eq!(column.num_docs(), 3);
+        assert_eq!(column.dimensions(), None);
+        assert_eq!(column.num_vals(1), 0);
+        assert_eq!(
+            vectors_of(&column),
+            vec![vec![1.0, 2.5], vec![], vec![-3.0]]
+        );
+        let doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(doc.get_all(embedding).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_f32_vector_fixed_dimensions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let embedding = schema_builder
+            .add_f32_vector_field("embedding", F32VectorOptions::default().set_dimensions(2));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(embedding => 1.0f64, embedding => 2.0f64))?;
+        let err = index_writer
+            .add_document(doc!(embedding => 3.0f64))
+            .unwrap_err();
+        assert!(matches!(err, TantivyError::InvalidArgument(_)));
+        index_writer.add_document(doc!(embedding => 3.0f64, embedding => 4.0f64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let column = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .f32_vector("embedding")?;
+        assert_eq!(column.dimensions(), Some(2));
+        assert_eq!(vectors_of(&column), vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        // The vectors are not stored again as fast field columns.
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        assert_eq!(fast_fields.columnar().num_columns(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_f32_vector_open_rejects_partial_vectors() {
+        let mut bytes = Vec::new();
+        for header_val in [1u32, 2, 0] {
+            bytes.extend_from_slice(&header_val.to_le_bytes());
+        }
+        bytes.extend_from_slice(&1.0f32.to_le_bytes());
+        assert!(F32VectorColumn::open(OwnedBytes::new(bytes.clone())).is_err());
+        bytes.extend_from_slice(&2.0f32.to_le_bytes());
+        let column = F32VectorColumn::open(OwnedBytes::new(bytes)).unwrap();
+        assert_eq!(vectors_of(&column), vec![vec![1.0, 2.0]]);
+    }
+
+    #[test]
+    fn test_f32_vector_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let embedding = schema_builder
+            .add_f32_vector_field("embedding", F32VectorOptions::default().set_dimensions(2));
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => 0u64, embedding => 0.5f64, embedding => 1.5f64))?;
+        index_writer.add_document(doc!(id => 1u64, embedding => 2.5f64, embedding => 3.5f64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => 2u64))?;
+        index_writer.add_document(doc!(id => 3u64, embedding => 4.5f64, embedding => 5.5f64))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id, 1));
+        index_writer.commit()?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let column = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .f32_vector("embedding")?;
+        assert_eq!(column.num_docs(), 3);
+        assert_eq!(column.dimensions(), Some(2));
+        let mut vectors = vectors_of(&column);
+        vectors.sort_by(|left, right| left.partial_cmp(right).unwrap());
+        assert_eq!(vectors, vec![vec![], vec![0.5, 1.5], vec![4.5, 5.5]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_f32_vector_rejects_other_types() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let embedding = schema_builder.add_f32_vector_field("embedding", ());
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let err = index_writer
+            .add_document(doc!(embedding => "text"))
+            .unwrap_err();
+        assert!(matches!(err, TantivyError::SchemaError(_)));
+        Ok(())
+    }
+}
@@ -12,18 +12,26 @@
 //!
 //!
 //! Fields have to be declared as `FAST` in the schema.
-//! Currently supported fields are: u64, i64, f64, bytes, ip and text.
+//! Currently supported fields are: u64, i64, f64, f32, bytes, ip and text.
+//!
+//! The values of `f32` fields are stored as `u32` in their column, which takes half the space of
+//! an `f64` field.
+//!
+//! The vectors of `f32` of [`F32Vector`](crate::schema::FieldType::F32Vector) fields are
+//! stored separately, so that they can be accessed in place, see [`F32VectorColumn`].
 //!
 //! Fast fields are stored in with [different codecs](columnar). The best codec is detected
 //! automatically, when serializing.
 //!
 //! Read access performance is comparable to that of an array lookup.
 
-pub use columnar::Column;
 use columnar::MonotonicallyMappableToU64;
+pub use columnar::{BlockStats, Column};
 
 pub use self::alive_bitset::{intersect_alive_bitsets, write_alive_bitset, AliveBitSet};
 pub use self::error::{FastFieldNotAvailableError, Result};
+pub use self::f32_vector::F32VectorColumn;
+pub(crate) use self::f32_vector::{serialize_f32_vector_field, F32VectorFields, F32VectorsWriter};
 pub use self::facet_reader::FacetReader;
 pub use self::readers::FastFieldReaders;
 pub use self::writer::FastFieldsWriter;
@@ -32,12 +40,13 @@ use crate::DateTime;
 
 mod alive_bitset;
 mod error;
+mod f32_vector;
 mod facet_reader;
 mod readers;
 mod writer;
 
 /// Trait for types that are allowed for fast fields:
-/// (u64, i64 and f64, bool, DateTime).
+/// (u64, i64, f64, f32, bool, DateTime).
 pub trait FastValue: MonotonicallyMappableToU64 {
     /// Returns the `schema::Type` for this FastValue.
     fn to_type() -> Type;
@@ -87,16 +96,18 @@ mod tests {
     use rand::{Rng, SeedableRng};
 
     use super::*;
+    use crate::collector::Count;
     use crate::directory::{Directory, RamDirectory, WritePtr};
     use crate::index::SegmentId;
     use crate::merge_policy::NoMergePolicy;
+    use crate::query::TermQuery;
     use crate::schema::{
-        DateOptions, Facet, FacetOptions, Field, JsonObjectOptions, Schema, SchemaBuilder,
-        TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
+        DateOptions, Facet, FacetOptions, Field, IndexRecordOption, JsonObjectOptions, Schema,
+        SchemaBuilder, TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{LowerCaser, RawTokenizer, TextAnalyzer, TokenizerManager};
-    use crate::{Index, IndexWriter, SegmentReader};
+    use crate::{Index, IndexWriter, SegmentReader, Term};
 
     pub static SCHEMA: Lazy<Schema> = Lazy::new(|| {
         let mut schema_builder = Schema::builder();
@@ -438,6 +449,63 @@ mod tests {
         assert_eq!(reader.searcher().segment_readers().len(), 1);
     }
 
+    #[test]
+    fn test_f32_fastfield() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let f32_field = schema_builder.add_f32_field("f32", FAST | INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(f32_field => 1.1f64))?;
+        index_writer.add_document(doc!(f32_field => -3.5f64, f32_field => 2.0f64))?;
+        index_writer.commit()?;
+
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let column = searcher.segment_reader(0u32).fast_fields().f32("f32")?;
+        assert_eq!(column.values_for_doc(0).collect::<Vec<f32>>(), vec![1.1f32]);
+        assert_eq!(
+            column.values_for_doc(1).collect::<Vec<f32>>(),
+            vec![-3.5f32, 2.0f32]
+        );
+        assert_eq!(column.min_value(), -3.5f32);
+        assert_eq!(column.max_value(), 2.0f32);
+        // The column is not readable as a `f64` column.
+        assert!(searcher
+            .segment_reader(0u32)
+            .fast_fields()
+            .f64("f32")
+            .is_err());
+
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(f32_field => 7.0f64))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let column = searcher.segment_reader(0u32).fast_fields().f32("f32")?;
+        let mut vals: Vec<Vec<f32>> = (0..4)
+            .map(|doc| column.values_for_doc(doc).collect())
+            .collect();
+        // The order of the documents depends on the order of the merged segments.
+        vals.sort_by(|left, right| left.partial_cmp(right).unwrap());
+        assert_eq!(
+            vals,
+            vec![vec![], vec![-3.5f32, 2.0f32], vec![1.1f32], vec![7.0f32]]
+        );
+        assert_eq!(column.min_value(), -3.5f32);
+        assert_eq!(column.max_value(), 7.0f32);
+
+        // Terms are indexed on the `f64` conversion of the rounded `f32` value.
+        let term = Term::from_field_f64(f32_field, f64::from(1.1f32));
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
     fn get_vals_for_docs(column: &Column<u64>, docs: Range<u32>) -> Vec<u64> {
         docs.into_iter()
             .flat_map(|doc| column.values_for_doc(doc))
@@ -1050,6 +1118,58 @@ mod tests {
         test_range_variant(1000, 1002);
     }
 
+    #[test]
+    fn test_multivalued_docids_for_value_range() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num_field = schema_builder.add_u64_field("nums", FAST);
+        let text_field = schema_builder.add_text_field("text", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let docs_values: [&[u64]; 7] = [&[], &[5, 10], &[20], &[1, 100], &[10, 10, 12], &[], &[7]];
+        let mut writer = index.writer_for_tests()?;
+        for values in docs_values {
+            let mut doc = TantivyDocument::default();
+            doc.add_text(text_field, "doc");
+            for &value in values {
+                doc.add_u64(num_field, value);
+            }
+            writer.add_document(doc)?;
+        }
+        writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let column = searcher.segment_reader(0).fast_fields().u64("nums")?;
+        let test_range = |value_range: RangeInclusive<u64>, doc_range: Range<u32>| {
+            let expected_docs: Vec<u32> = doc_range
+                .clone()
+                .filter(|&doc| {
+                    docs_values[doc as usize]
+                        .iter()
+                        .any(|value| value_range.contains(value))
+                })
+                .collect();
+            let mut docs = Vec::new();
+            column.get_docids_for_value_range(value_range.clone(), doc_range.clone(), &mut docs);
+            assert_eq!(docs, expected_docs, "{value_range:?} {doc_range:?}");
+        };
+        let value_ranges = [
+            0..=u64::MAX,
+            // Partial overlap with the values of docs 1, 3 and 4.
+            6..=11,
+            10..=10,
+            12..=99,
+            13..=19,
+            100..=200,
+            101..=200,
+        ];
+        for value_range in value_ranges {
+            for doc_range in [0..7, 0..1, 1..4, 2..3, 4..7, 5..6] {
+                test_range(value_range.clone(), doc_range);
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_json_object_fast_field() {
         let mut schema_builder = Schema::builder();
@@ -9,25 +9,79 @@ use columnar::{
 use common::ByteCount;
 
 use crate::core::json_utils::{encode_column_name, json_path_sep_to_dot};
-use crate::directory::FileSlice;
+use crate::directory::{CompositeFile, FileSlice};
+use crate::error::NOT_A_FAST_FIELD;
+use crate::fastfield::F32VectorColumn;
+use crate::index::HandleCache;
 use crate::schema::{Field, FieldEntry, FieldType, Schema};
 use crate::space_usage::{FieldUsage, PerFieldSpaceUsage};
+use crate::store::CacheStats;
 use crate::TantivyError;
 
 /// Provides access to all of the BitpackedFastFieldReader.
 ///
 /// Internally, `FastFieldReaders` have preloaded fast field readers,
 /// and just wraps several `HashMap`.
+///
+/// Opened columns are cached, so that looking up the same column twice
+/// returns the same handle.
 #[derive(Clone)]
 pub struct FastFieldReaders {
     columnar: Arc<ColumnarReader>,
     schema: Schema,
+    column_cache: Arc<HandleCache<String, Arc<[DynamicColumn]>>>,
+    f32_vectors: CompositeFile,
 }
 
 impl FastFieldReaders {
+    #[cfg(test)]
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
+        Self::open_with_f32_vectors(fast_field_file, CompositeFile::empty(), schema)
+    }
+
+    pub(crate) fn open_with_f32_vectors(
+        fast_field_file: FileSlice,
+        f32_vectors: CompositeFile,
+        schema: Schema,
+    ) -> io::Result<FastFieldReaders> {
         let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
-        Ok(FastFieldReaders { columnar, schema })
+        Ok(FastFieldReaders {
+            columnar,
+            schema,
+            column_cache: Default::default(),
+            f32_vectors,
+        })
+    }
+
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        self.column_cache.stats()
+    }
+
+    /// Returns the opened column of the given type associated to a given field name.
+    ///
+    /// The columns of all types associated to the field name are opened on its first lookup.
+    fn cached_dynamic_column(
+        &self,
+        field_name: &str,
+        column_type: ColumnType,
+    ) -> crate::Result<Option<DynamicColumn>> {
+        let dynamic_columns_opt = self.column_cache.get_or_try_insert_with(field_name, || {
+            let dynamic_column_handles = self.dynamic_column_handles(field_name)?;
+            if dynamic_column_handles.is_empty() {
+                return Ok(None);
+            }
+            let dynamic_columns = dynamic_column_handles
+                .iter()
+                .map(DynamicColumnHandle::open)
+                .collect::<io::Result<Arc<[DynamicColumn]>>>()?;
+            crate::Result::Ok(Some(dynamic_columns))
+        })?;
+        Ok(dynamic_columns_opt.and_then(|dynamic_columns| {
+            dynamic_columns
+                .iter()
+                .find(|dynamic_column| dynamic_column.column_type() == column_type)
+                .cloned()
+        }))
     }
 
     fn resolve_field(&self, column_name: &str) -> crate::Result<Option<String>> {
@@ -55,6 +109,10 @@ impl FastFieldReaders {
         self.columnar.as_ref()
     }
 
+    pub(crate) fn f32_vectors_space_usage(&self) -> PerFieldSpaceUsage {
+        self.f32_vectors.space_usage(&self.schema)
+    }
+
     /// Transforms a user-supplied fast field name into a column name.
     ///
     /// A user-supplied fast field name is not necessarily a schema field name
@@ -90,7 +148,7 @@ impl FastFieldReaders {
         let field_entry: &FieldEntry = self.schema.get_field_entry(field);
         if !field_entry.is_fast() {
             return Err(TantivyError::InvalidArgument(format!(
-                "Field {field_name:?} is not configured as fast field"
+                "Field {field_name:?} {NOT_A_FAST_FIELD}"
             )));
         }
         Ok(match (field_entry.field_type(), path) {
@@ -116,12 +174,9 @@ impl FastFieldReaders {
         T: HasAssociatedColumnType,
         DynamicColumn: Into<Option<Column<T>>>,
     {
-        let Some(dynamic_column_handle) =
-            self.dynamic_column_handle(field_name, T::column_type())?
-        else {
+        let Some(dynamic_column) = self.cached_dynamic_column(field_name, T::column_type())? else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
         Ok(dynamic_column.into())
     }
 
@@ -172,6 +227,9 @@ impl FastFieldReaders {
 
     /// Returns the `u64` fast field reader reader associated with `field`.
     ///
+    /// The column handles multivalued fields as well: the values of a document are returned
+    /// by [`Column::values_for_doc`], and its first value by [`Column::first`].
+    ///
     /// If `field` is not a u64 fast field, this method returns an Error.
     pub fn u64(&self, field: &str) -> crate::Result<Column<u64>> {
         self.column(field)
@@ -193,26 +251,43 @@ impl FastFieldReaders {
 
     /// Returns a `str` column.
     pub fn str(&self, field_name: &str) -> crate::Result<Option<StrColumn>> {
-        let Some(dynamic_column_handle) =
-            self.dynamic_column_handle(field_name, ColumnType::Str)?
-        else {
+        let Some(dynamic_column) = self.cached_dynamic_column(field_name, ColumnType::Str)? else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
         Ok(dynamic_column.into())
     }
 
     /// Returns a `bytes` column.
     pub fn bytes(&self, field_name: &str) -> crate::Result<Option<BytesColumn>> {
-        let Some(dynamic_column_handle) =
-            self.dynamic_column_handle(field_name, ColumnType::Bytes)?
+        let Some(dynamic_column) = self.cached_dynamic_column(field_name, ColumnType::Bytes)?
         else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
         Ok(dynamic_column.into())
     }
 
+    /// Returns the column of the f32 vectors of the field.
+    ///
+    /// If `field_name` is not a [`F32Vector`](FieldType::F32Vector) field, this method returns
+    /// an Error.
+    pub fn f32_vector(&self, field_name: &str) -> crate::Result<F32VectorColumn> {
+        let field = self.schema.get_field(field_name)?;
+        if !self
+            .schema
+            .get_field_entry(field)
+            .field_type()
+            .is_f32_vector()
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {field_name:?} is not a f32 vector field"
+            )));
+        }
+        let Some(f32_vector_file) = self.f32_vectors.open_read(field) else {
+            return Ok(F32VectorColumn::empty());
+        };
+        Ok(F32VectorColumn::open(f32_vector_file.read_bytes()?)?)
+    }
+
     /// Returns a `dynamic_column_handle`.
     pub fn dynamic_column_handle(
         &self,
@@ -371,6 +446,13 @@ impl FastFieldReaders {
         self.column(field_name)
     }
 
+    /// Returns the `f32` fast field reader reader associated with `field`.
+    ///
+    /// If `field` is not a f32 fast field, this method returns an Error.
+    pub fn f32(&self, field_name: &str) -> crate::Result<Column<f32>> {
+        self.column(field_name)
+    }
+
     /// Returns the `bool` fast field reader reader associated with `field`.
     ///
     /// If `field` is not a bool fast field, this method returns an Error.
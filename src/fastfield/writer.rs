@@ -1,6 +1,6 @@
 use std::io;
 
-use columnar::{ColumnarWriter, NumericalValue};
+use columnar::{ColumnType, ColumnarWriter, NumericalValue};
 use common::{DateTimePrecision, JsonPathWriter};
 use tokenizer_api::Token;
 
@@ -20,6 +20,8 @@ pub struct FastFieldsWriter {
     per_field_tokenizer: Vec<Option<TextAnalyzer>>,
     date_precisions: Vec<DateTimePrecision>,
     expand_dots: Vec<bool>,
+    // Fields whose numerical values are stored in an `f32` column.
+    f32_fields: Vec<bool>,
     num_docs: DocId,
     // Buffer that we recycle to avoid allocation.
     json_path_buffer: JsonPathWriter,
@@ -45,6 +47,7 @@ impl FastFieldsWriter {
                 .take(schema.num_fields())
                 .collect();
         let mut expand_dots = vec![false; schema.num_fields()];
+        let mut f32_fields = vec![false; schema.num_fields()];
         let mut per_field_tokenizer: Vec<Option<TextAnalyzer>> = vec![None; schema.num_fields()];
         // TODO see other types
         for (field_id, field_entry) in schema.fields() {
@@ -80,8 +83,14 @@ impl FastFieldsWriter {
                 }
             }
 
+            let column_type_opt = if let FieldType::F32(_) = field_entry.field_type() {
+                f32_fields[field_id.field_id() as usize] = true;
+                Some(ColumnType::F32)
+            } else {
+                value_type_to_column_type(value_type)
+            };
             let sort_values_within_row = value_type == Type::Facet;
-            if let Some(column_type) = value_type_to_column_type(value_type) {
+            if let Some(column_type) = column_type_opt {
                 columnar_writer.record_column_type(
                     field_entry.name(),
                     column_type,
@@ -96,6 +105,7 @@ impl FastFieldsWriter {
             num_docs: 0u32,
             date_precisions,
             expand_dots,
+            f32_fields,
             json_path_buffer: JsonPathWriter::default(),
         })
     }
@@ -128,6 +138,14 @@ impl FastFieldsWriter {
             Some(name) => name,
         };
 
+        if self.f32_fields[field.field_id() as usize] {
+            if let ReferenceValue::Leaf(ReferenceValueLeaf::F64(val)) = value.as_value() {
+                self.columnar_writer
+                    .record_f32(doc_id, field_name, val as f32);
+                return Ok(());
+            }
+        }
+
         match value.as_value() {
             ReferenceValue::Leaf(leaf) => match leaf {
                 ReferenceValueLeaf::Null => {}
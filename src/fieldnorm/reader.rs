@@ -1,9 +1,13 @@
 use std::sync::Arc;
 
+use common::ByteCount;
+
 use super::{fieldnorm_to_id, id_to_fieldnorm};
 use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::index::HandleCache;
 use crate::schema::{Field, Schema};
 use crate::space_usage::PerFieldSpaceUsage;
+use crate::store::CacheStats;
 use crate::DocId;
 
 /// Reader for the fieldnorm (for each document, the number of tokens indexed in the
@@ -15,6 +19,7 @@ use crate::DocId;
 #[derive(Clone)]
 pub struct FieldNormReaders {
     data: Arc<CompositeFile>,
+    reader_cache: Arc<HandleCache<Field, FieldNormReader>>,
 }
 
 impl FieldNormReaders {
@@ -23,17 +28,32 @@ impl FieldNormReaders {
         let data = CompositeFile::open(&file)?;
         Ok(FieldNormReaders {
             data: Arc::new(data),
+            reader_cache: Default::default(),
         })
     }
 
     /// Returns the FieldNormReader for a specific field.
+    ///
+    /// The reader is opened on the first call, and cached for the following ones.
     pub fn get_field(&self, field: Field) -> crate::Result<Option<FieldNormReader>> {
-        if let Some(file) = self.data.open_read(field) {
-            let fieldnorm_reader = FieldNormReader::open(file)?;
-            Ok(Some(fieldnorm_reader))
-        } else {
-            Ok(None)
-        }
+        self.reader_cache.get_or_try_insert_with(&field, || {
+            if let Some(file) = self.data.open_read(field) {
+                let fieldnorm_reader = FieldNormReader::open(file)?;
+                Ok(Some(fieldnorm_reader))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    pub(crate) fn cache_stats(&self) -> CacheStats {
+        self.reader_cache.stats()
+    }
+
+    /// Returns the number of bytes of the fieldnorm readers opened so far.
+    pub(crate) fn loaded_num_bytes(&self) -> ByteCount {
+        let num_bytes = self.reader_cache.num_bytes(FieldNormReader::num_bytes);
+        ByteCount::from(num_bytes as u64)
     }
 
     /// Return a break down of the space usage per field.
@@ -96,6 +116,14 @@ impl FieldNormReader {
         ReaderImplEnum::FromData(data).into()
     }
 
+    /// Returns the number of bytes held by the reader.
+    fn num_bytes(&self) -> usize {
+        match &self.0 {
+            ReaderImplEnum::FromData(data) => data.len(),
+            ReaderImplEnum::Const { .. } => 0,
+        }
+    }
+
     /// Returns the number of documents in this segment.
     pub fn num_docs(&self) -> u32 {
         match &self.0 {
@@ -0,0 +1,87 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use crate::store::CacheStats;
+
+/// Thread-safe cache of the handles opened by a segment reader.
+///
+/// Handles are opened lazily, on their first lookup. Missing handles are not cached, so
+/// the cache holds at most one entry per handle of the segment.
+pub(crate) struct HandleCache<K, V> {
+    entries: RwLock<HashMap<K, V>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl<K, V> Default for HandleCache<K, V> {
+    fn default() -> Self {
+        HandleCache {
+            entries: RwLock::new(HashMap::new()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> HandleCache<K, V> {
+    /// Returns the cached handle associated with `key`, opening it with `open` if
+    /// it is not cached yet.
+    ///
+    /// If `open` returns `None`, nothing is cached and `None` is returned.
+    pub fn get_or_try_insert_with<Q, E>(
+        &self,
+        key: &Q,
+        open: impl FnOnce() -> Result<Option<V>, E>,
+    ) -> Result<Option<V>, E>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        if let Some(handle) = self
+            .entries
+            .read()
+            .expect("Handle cache lock poisoned. This should never happen.")
+            .get(key)
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Some(handle.clone()));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // by releasing the lock in between, we may end up opening the handle twice,
+        // but this is fine.
+        let Some(handle) = open()? else {
+            return Ok(None);
+        };
+        self.entries
+            .write()
+            .expect("Handle cache lock poisoned. This should never happen.")
+            .entry(key.to_owned())
+            .or_insert_with(|| handle.clone());
+        Ok(Some(handle))
+    }
+
+    /// Sums `num_bytes` over the cached handles.
+    pub fn num_bytes(&self, num_bytes: impl Fn(&V) -> usize) -> usize {
+        self.entries
+            .read()
+            .expect("Handle cache lock poisoned. This should never happen.")
+            .values()
+            .map(num_bytes)
+            .sum()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            num_entries: self
+                .entries
+                .read()
+                .expect("Handle cache lock poisoned. This should never happen.")
+                .len(),
+            cache_hits: self.hits.load(Ordering::Relaxed),
+            cache_misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
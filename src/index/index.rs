@@ -340,6 +340,10 @@ impl Index {
     }
 
     /// Opens or creates a new index in the provided directory
+    ///
+    /// If the directory already holds an index, it is opened, and a
+    /// [`TantivyError::SchemaError`] is returned if its schema is not `schema`. Otherwise, a
+    /// new index is created with `schema`.
     pub fn open_or_create<T: Into<Box<dyn Directory>>>(
         dir: T,
         schema: Schema,
@@ -466,6 +470,15 @@ impl Index {
         Index::open(mmap_directory)
     }
 
+    /// Opens an index from an index path in read-only mode.
+    ///
+    /// See [`Index::open_read_only`].
+    #[cfg(feature = "mmap")]
+    pub fn open_in_dir_read_only<P: AsRef<Path>>(directory_path: P) -> crate::Result<Index> {
+        let mmap_directory = MmapDirectory::open(directory_path)?;
+        Index::open_read_only(mmap_directory)
+    }
+
     /// Returns the list of the segment metas tracked by the index.
     ///
     /// Such segments can of course be part of the index,
@@ -516,6 +529,31 @@ impl Index {
         Ok(index)
     }
 
+    /// Open the index using the provided directory, in read-only mode.
+    ///
+    /// The index never writes to the directory nor acquires any lock on it, which makes it
+    /// possible to search an index stored on a read-only filesystem:
+    /// - readers do not acquire the meta lock when loading the segments,
+    /// - garbage collection is disabled,
+    /// - creating an [`IndexWriter`] or a [`BulkLoader`](crate::indexer::BulkLoader) fails with
+    ///   [`TantivyError::ReadOnlyIndex`].
+    ///
+    /// As the meta lock is not acquired, the index must not be modified by a writer in
+    /// another process while it is opened in read-only mode.
+    pub fn open_read_only<T: Into<Box<dyn Directory>>>(directory: T) -> crate::Result<Index> {
+        let directory = directory.into();
+        let directory = ManagedDirectory::wrap_read_only(directory)?;
+        let inventory = SegmentMetaInventory::default();
+        let metas = load_metas(&directory, &inventory)?;
+        let index = Index::open_from_metas(directory, &metas, inventory);
+        Ok(index)
+    }
+
+    /// Returns true if the index was opened with [`Index::open_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.directory.is_read_only()
+    }
+
     /// Reads the index meta file from the directory.
     pub fn load_metas(&self) -> crate::Result<IndexMeta> {
         load_metas(self.directory(), &self.inventory)
@@ -536,10 +574,14 @@ impl Index {
     /// If the lockfile already exists, returns `TantivyError::LockFailure`.
     /// If the memory arena per thread is too small or too big, returns
     /// `TantivyError::InvalidArgument`
+    /// If the index was opened in read-only mode, returns `TantivyError::ReadOnlyIndex`.
     pub fn writer_with_options<D: Document>(
         &self,
         options: IndexWriterOptions,
     ) -> crate::Result<IndexWriter<D>> {
+        if self.is_read_only() {
+            return Err(TantivyError::ReadOnlyIndex);
+        }
         let directory_lock = self
             .directory
             .acquire_lock(&INDEX_WRITER_LOCK)
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 
@@ -13,9 +13,15 @@ use crate::{Inventory, Opstamp, TrackedObject};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeleteMeta {
     num_deleted_docs: u32,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    num_soft_deleted_docs: u32,
     pub opstamp: Opstamp,
 }
 
+fn is_zero(val: &u32) -> bool {
+    *val == 0
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct SegmentMetaInventory {
     inventory: Inventory<InnerSegmentMeta>,
@@ -36,6 +42,7 @@ impl SegmentMetaInventory {
             segment_id,
             max_doc,
             deletes: None,
+            registered_enum_values: BTreeMap::new(),
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -91,6 +98,19 @@ impl SegmentMeta {
             .unwrap_or(0u32)
     }
 
+    /// Returns the number of soft deleted documents.
+    ///
+    /// Soft deleted documents are not counted as deleted documents: they are kept by merges
+    /// until their retention expires, and are therefore counted by
+    /// [`SegmentMeta::num_docs`].
+    pub fn num_soft_deleted_docs(&self) -> u32 {
+        self.tracked
+            .deletes
+            .as_ref()
+            .map(|delete_meta| delete_meta.num_soft_deleted_docs)
+            .unwrap_or(0u32)
+    }
+
     /// Returns the list of files that
     /// are required for the segment meta.
     /// Note: Some of the returned files may not exist depending on the state of the segment.
@@ -117,7 +137,12 @@ impl SegmentMeta {
             SegmentComponent::Store => ".store".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
+            SegmentComponent::F32Vectors => ".vec".to_string(),
+            SegmentComponent::StoreDictionaries => ".storedict".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
+            SegmentComponent::SoftDelete => {
+                format!(".{}.sdel", self.delete_opstamp().unwrap_or(0))
+            }
         });
         PathBuf::from(path)
     }
@@ -151,6 +176,11 @@ impl SegmentMeta {
         self.num_deleted_docs() > 0
     }
 
+    /// Returns true iff some documents of the segment are soft deleted.
+    pub fn has_soft_deletes(&self) -> bool {
+        self.num_soft_deleted_docs() > 0
+    }
+
     /// Updates the max_doc value from the `SegmentMeta`.
     pub fn with_max_doc(self, max_doc: u32) -> SegmentMeta {
         assert_eq!(self.tracked.max_doc, 0);
@@ -159,6 +189,31 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc,
             deletes: None,
+            registered_enum_values: inner_meta.registered_enum_values.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the values registered in the dictionaries of the enum fields by the documents
+    /// of the segment, by field name.
+    ///
+    /// They are appended to the dictionaries of the schema when the segment is committed, see
+    /// [`EnumValues`](crate::schema::EnumValues).
+    pub fn registered_enum_values(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.tracked.registered_enum_values
+    }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_registered_enum_values(
+        self,
+        registered_enum_values: BTreeMap<String, BTreeSet<String>>,
+    ) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            registered_enum_values,
         });
         SegmentMeta { tracked }
     }
@@ -166,18 +221,31 @@ impl SegmentMeta {
     #[doc(hidden)]
     #[must_use]
     pub fn with_delete_meta(self, num_deleted_docs: u32, opstamp: Opstamp) -> SegmentMeta {
+        self.with_soft_delete_meta(num_deleted_docs, 0, opstamp)
+    }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_soft_delete_meta(
+        self,
+        num_deleted_docs: u32,
+        num_soft_deleted_docs: u32,
+        opstamp: Opstamp,
+    ) -> SegmentMeta {
         assert!(
-            num_deleted_docs <= self.max_doc(),
+            num_deleted_docs + num_soft_deleted_docs <= self.max_doc(),
             "There cannot be more deleted docs than there are docs."
         );
         let delete_meta = DeleteMeta {
             num_deleted_docs,
+            num_soft_deleted_docs,
             opstamp,
         };
         let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc: inner_meta.max_doc,
             deletes: Some(delete_meta),
+            registered_enum_values: inner_meta.registered_enum_values.clone(),
         });
         SegmentMeta { tracked }
     }
@@ -188,6 +256,8 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     pub deletes: Option<DeleteMeta>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    registered_enum_values: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl InnerSegmentMeta {
@@ -331,6 +401,16 @@ impl IndexMeta {
         }
     }
 
+    /// Appends the values registered by the segments to the dictionaries of the enum fields
+    /// of the schema.
+    pub(crate) fn register_segment_enum_values(&mut self) {
+        self.schema = self.schema.with_registered_enum_values(
+            self.segments
+                .iter()
+                .map(SegmentMeta::registered_enum_values),
+        );
+    }
+
     pub(crate) fn deserialize(
         meta_json: &str,
         inventory: &SegmentMetaInventory,
@@ -1,7 +1,8 @@
 use std::io;
+use std::ops::Range;
 
 use common::json_path_writer::JSON_END_OF_PATH;
-use common::{BinarySerializable, ByteCount};
+use common::{BinarySerializable, ByteCount, VInt};
 #[cfg(feature = "quickwit")]
 use futures_util::{FutureExt, StreamExt, TryStreamExt};
 #[cfg(feature = "quickwit")]
@@ -11,9 +12,15 @@ use tantivy_fst::automaton::{AlwaysMatch, Automaton};
 
 use crate::directory::FileSlice;
 use crate::positions::PositionReader;
-use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
+use crate::postings::{BlockSegmentPostings, Postings, SegmentPostings, TermInfo};
 use crate::schema::{IndexRecordOption, Term, Type};
 use crate::termdict::TermDictionary;
+use crate::{DocId, DocSet};
+
+/// Position of an occurrence of a term, along with the byte range it spans in the text.
+///
+/// See [`InvertedIndexReader::positions_and_offsets`].
+pub type PositionAndOffsets = (u32, Range<usize>);
 
 /// The inverted index reader is in charge of accessing
 /// the inverted index associated with a specific field.
@@ -32,6 +39,7 @@ pub struct InvertedIndexReader {
     postings_file_slice: FileSlice,
     positions_file_slice: FileSlice,
     record_option: IndexRecordOption,
+    has_offsets: bool,
     total_num_tokens: u64,
 }
 
@@ -68,6 +76,7 @@ impl InvertedIndexReader {
         postings_file_slice: FileSlice,
         positions_file_slice: FileSlice,
         record_option: IndexRecordOption,
+        has_offsets: bool,
     ) -> io::Result<InvertedIndexReader> {
         let (total_num_tokens_slice, postings_body) = postings_file_slice.split(8);
         let total_num_tokens = u64::deserialize(&mut total_num_tokens_slice.read_bytes()?)?;
@@ -76,6 +85,7 @@ impl InvertedIndexReader {
             postings_file_slice: postings_body,
             positions_file_slice,
             record_option,
+            has_offsets: has_offsets && record_option.has_positions(),
             total_num_tokens,
         })
     }
@@ -88,6 +98,7 @@ impl InvertedIndexReader {
             postings_file_slice: FileSlice::empty(),
             positions_file_slice: FileSlice::empty(),
             record_option,
+            has_offsets: false,
             total_num_tokens: 0u64,
         }
     }
@@ -175,14 +186,29 @@ impl InvertedIndexReader {
         term_info: &TermInfo,
         block_postings: &mut BlockSegmentPostings,
     ) -> io::Result<()> {
-        let postings_slice = self
-            .postings_file_slice
-            .slice(term_info.postings_range.clone());
-        let postings_bytes = postings_slice.read_bytes()?;
+        let postings_bytes = self.postings_data(term_info).read_bytes()?;
         block_postings.reset(term_info.doc_freq, postings_bytes)?;
         Ok(())
     }
 
+    /// Returns the serialized posting list of the term.
+    ///
+    /// The posting list of a term inlined in the term dictionary is serialized on the fly,
+    /// with the `VInt` encoding of the incomplete blocks.
+    fn postings_data(&self, term_info: &TermInfo) -> FileSlice {
+        let Some((doc, term_freq)) = term_info.inline_posting else {
+            return self
+                .postings_file_slice
+                .slice(term_info.postings_range.clone());
+        };
+        let mut buffer = Vec::new();
+        VInt(doc as u64).serialize_into_vec(&mut buffer);
+        if term_freq > 0 {
+            VInt(term_freq as u64).serialize_into_vec(&mut buffer);
+        }
+        FileSlice::from(buffer)
+    }
+
     /// Returns a block postings given a `Term`.
     /// This method is for an advanced usage only.
     ///
@@ -206,9 +232,7 @@ impl InvertedIndexReader {
         term_info: &TermInfo,
         requested_option: IndexRecordOption,
     ) -> io::Result<BlockSegmentPostings> {
-        let postings_data = self
-            .postings_file_slice
-            .slice(term_info.postings_range.clone());
+        let postings_data = self.postings_data(term_info);
         BlockSegmentPostings::open(
             term_info.doc_freq,
             postings_data,
@@ -229,21 +253,72 @@ impl InvertedIndexReader {
         let option = option.downgrade(self.record_option);
 
         let block_postings = self.read_block_postings_from_terminfo(term_info, option)?;
-        let position_reader = {
-            if option.has_positions() {
-                let positions_data = self
-                    .positions_file_slice
-                    .read_bytes_slice(term_info.positions_range.clone())?;
-                let position_reader = PositionReader::open(positions_data)?;
-                Some(position_reader)
-            } else {
-                None
-            }
+        if !option.has_positions() {
+            return Ok(SegmentPostings::from_block_postings(block_postings, None));
+        }
+        let positions_data = self
+            .positions_file_slice
+            .read_bytes_slice(term_info.positions_range.clone())?;
+        if self.has_offsets {
+            let (position_reader, offsets_reader) =
+                PositionReader::open_with_offsets(positions_data)?;
+            Ok(
+                SegmentPostings::from_block_postings(block_postings, Some(position_reader))
+                    .with_offsets_reader(offsets_reader),
+            )
+        } else {
+            let position_reader = PositionReader::open(positions_data)?;
+            Ok(SegmentPostings::from_block_postings(
+                block_postings,
+                Some(position_reader),
+            ))
+        }
+    }
+
+    /// Returns true if the byte offsets of the tokens are recorded along with their positions.
+    ///
+    /// See [`TextFieldIndexing::set_offsets`](crate::schema::TextFieldIndexing::set_offsets).
+    pub fn has_offsets(&self) -> bool {
+        self.has_offsets
+    }
+
+    /// Returns the positions of the occurrences of `term` in the document `doc`, along with
+    /// the byte range each of them spans in the text.
+    ///
+    /// For multivalued fields, the byte ranges refer to the values of the field joined by a
+    /// single space.
+    ///
+    /// Returns `None` if the field does not record offsets, and an empty `Vec` if the document
+    /// does not contain the term.
+    pub fn positions_and_offsets(
+        &self,
+        term: &Term,
+        doc: DocId,
+    ) -> io::Result<Option<Vec<PositionAndOffsets>>> {
+        if !self.has_offsets {
+            return Ok(None);
+        }
+        let Some(mut postings) =
+            self.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+        else {
+            return Ok(Some(Vec::new()));
         };
-        Ok(SegmentPostings::from_block_postings(
-            block_postings,
-            position_reader,
-        ))
+        if postings.seek(doc) != doc {
+            return Ok(Some(Vec::new()));
+        }
+        let mut positions = Vec::new();
+        postings.positions(&mut positions);
+        let mut offsets = Vec::new();
+        postings.offsets(&mut offsets);
+        let positions_and_offsets = positions
+            .into_iter()
+            .zip(offsets.chunks_exact(2))
+            .map(|(position, offsets)| {
+                let offset_from = offsets[0] as usize;
+                (position, offset_from..offset_from + offsets[1] as usize)
+            })
+            .collect();
+        Ok(Some(positions_and_offsets))
     }
 
     /// Returns the total number of tokens recorded for all documents
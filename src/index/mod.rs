@@ -2,6 +2,7 @@
 //!
 //! It contains `Index` and `Segment`, where a `Index` consists of one or more `Segment`s.
 
+mod handle_cache;
 mod index;
 mod index_meta;
 mod inverted_index_reader;
@@ -9,12 +10,17 @@ mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+mod soft_deletes;
+mod term_vector;
 
+pub(crate) use self::handle_cache::HandleCache;
 pub use self::index::{Index, IndexBuilder};
 pub(crate) use self::index_meta::SegmentMetaInventory;
 pub use self::index_meta::{IndexMeta, IndexSettings, Order, SegmentMeta};
-pub use self::inverted_index_reader::InvertedIndexReader;
+pub use self::inverted_index_reader::{InvertedIndexReader, PositionAndOffsets};
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::{FieldMetadata, SegmentReader};
+pub use self::soft_deletes::SoftDeletes;
+pub use self::term_vector::TermVectorEntry;
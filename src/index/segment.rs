@@ -62,6 +62,22 @@ impl Segment {
         }
     }
 
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_soft_delete_meta(
+        self,
+        num_deleted_docs: u32,
+        num_soft_deleted_docs: u32,
+        opstamp: Opstamp,
+    ) -> Segment {
+        Segment {
+            index: self.index,
+            meta: self
+                .meta
+                .with_soft_delete_meta(num_deleted_docs, num_soft_deleted_docs, opstamp),
+        }
+    }
+
     /// Returns the segment's id.
     pub fn id(&self) -> SegmentId {
         self.meta.id()
@@ -4,7 +4,8 @@ use std::slice;
 ///
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
-/// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
+/// except the delete and soft delete components that take an
+/// `segment_uuid`.`delete_opstamp`.`component_extension`
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated with terms
@@ -26,12 +27,19 @@ pub enum SegmentComponent {
     /// Bitset describing which document of the segment is alive.
     /// (It was representing deleted docs but changed to represent alive docs from v0.17)
     Delete,
+    /// Documents which have been soft deleted, with the time of their deletion.
+    SoftDelete,
+    /// Vectors of f32 of each document, stored as contiguous little-endian values.
+    F32Vectors,
+    /// Dictionaries of the values of the dictionary encoded stored fields, referenced
+    /// by the `Store`.
+    StoreDictionaries,
 }
 
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 7] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 10] = [
             SegmentComponent::Postings,
             SegmentComponent::Positions,
             SegmentComponent::FastFields,
@@ -39,6 +47,9 @@ impl SegmentComponent {
             SegmentComponent::Terms,
             SegmentComponent::Store,
             SegmentComponent::Delete,
+            SegmentComponent::SoftDelete,
+            SegmentComponent::F32Vectors,
+            SegmentComponent::StoreDictionaries,
         ];
         SEGMENT_COMPONENTS.iter()
     }
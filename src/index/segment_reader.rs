@@ -2,19 +2,23 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::{fmt, io};
 
-use common::{ByteCount, HasLen};
+use common::{BitSet, ByteCount, HasLen, ReadOnlyBitSet};
 use fnv::FnvHashMap;
-use itertools::Itertools;
+use itertools::{Either, Itertools};
+use once_cell::sync::OnceCell;
 
 use crate::directory::{CompositeFile, FileSlice};
 use crate::error::DataCorruption;
 use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
-use crate::index::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
+use crate::index::term_vector::term_vector_from_postings;
+use crate::index::{
+    InvertedIndexReader, Segment, SegmentComponent, SegmentId, SoftDeletes, TermVectorEntry,
+};
 use crate::json_utils::json_path_sep_to_dot;
 use crate::schema::{Field, IndexRecordOption, Schema, Type};
-use crate::space_usage::SegmentSpaceUsage;
-use crate::store::StoreReader;
+use crate::space_usage::{SegmentMemoryUsage, SegmentSpaceUsage};
+use crate::store::{CacheStats, StoreDictionaries, StoreReader};
 use crate::termdict::TermDictionary;
 use crate::{DocId, Opstamp};
 
@@ -45,7 +49,14 @@ pub struct SegmentReader {
     fieldnorm_readers: FieldNormReaders,
 
     store_file: FileSlice,
+    store_dictionaries_opt: Option<Arc<StoreDictionaries>>,
     alive_bitset_opt: Option<AliveBitSet>,
+    // Alive bitset with the soft deleted documents considered alive.
+    alive_bitset_with_soft_deleted_opt: Option<AliveBitSet>,
+    // Full bitset built on demand when the segment has no deletes.
+    full_alive_bitset: Arc<OnceCell<AliveBitSet>>,
+    soft_deletes_opt: Option<Arc<SoftDeletes>>,
+    soft_deletes_num_bytes: ByteCount,
     schema: Schema,
 }
 
@@ -57,7 +68,8 @@ impl SegmentReader {
     }
 
     /// Returns the number of alive documents.
-    /// Deleted documents are not counted.
+    /// Deleted documents are not counted, and neither are soft deleted documents unless
+    /// the reader was obtained via [`SegmentReader::include_soft_deleted`].
     pub fn num_docs(&self) -> DocId {
         self.num_docs
     }
@@ -131,12 +143,26 @@ impl SegmentReader {
         &self.fieldnorm_readers
     }
 
+    /// The cache stats for the fast field columns and fieldnorm readers opened
+    /// through this segment reader.
+    ///
+    /// These handles are opened on their first lookup, and kept until the segment reader
+    /// is dropped.
+    pub fn handle_cache_stats(&self) -> CacheStats {
+        let mut cache_stats = self.fast_fields_readers.cache_stats();
+        cache_stats += self.fieldnorm_readers.cache_stats();
+        cache_stats
+    }
+
     /// Accessor to the segment's [`StoreReader`](crate::store::StoreReader).
     ///
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
     /// The size of blocks is configurable, this should be reflexted in the
     pub fn get_store_reader(&self, cache_num_blocks: usize) -> io::Result<StoreReader> {
-        StoreReader::open(self.store_file.clone(), cache_num_blocks)
+        Ok(
+            StoreReader::open(self.store_file.clone(), cache_num_blocks)?
+                .with_dictionaries(self.store_dictionaries_opt.clone()),
+        )
     }
 
     /// Open a new segment for reading.
@@ -153,6 +179,15 @@ impl SegmentReader {
         let termdict_composite = CompositeFile::open(&termdict_file)?;
 
         let store_file = segment.open_read(SegmentComponent::Store)?;
+        let store_dictionaries_opt = if let Ok(store_dictionaries_file) =
+            segment.open_read(SegmentComponent::StoreDictionaries)
+        {
+            let store_dictionaries =
+                StoreDictionaries::open(store_dictionaries_file.read_bytes()?)?;
+            Some(Arc::new(store_dictionaries))
+        } else {
+            None
+        };
 
         crate::fail_point!("SegmentReader::open#middle");
 
@@ -170,7 +205,17 @@ impl SegmentReader {
         let schema = segment.schema();
 
         let fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
-        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
+        let f32_vectors_composite =
+            if let Ok(f32_vectors_file) = segment.open_read(SegmentComponent::F32Vectors) {
+                CompositeFile::open(&f32_vectors_file)?
+            } else {
+                CompositeFile::empty()
+            };
+        let fast_fields_readers = FastFieldReaders::open_with_f32_vectors(
+            fast_fields_data,
+            f32_vectors_composite,
+            schema.clone(),
+        )?;
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
 
@@ -182,13 +227,25 @@ impl SegmentReader {
             None
         };
 
-        let alive_bitset_opt = intersect_alive_bitset(original_bitset, custom_bitset);
+        let alive_bitset_with_soft_deleted_opt =
+            intersect_alive_bitset(original_bitset, custom_bitset);
+
+        let (soft_deletes_opt, soft_deletes_num_bytes) = if segment.meta().has_soft_deletes() {
+            let soft_deletes_file_slice = segment.open_read(SegmentComponent::SoftDelete)?;
+            let soft_deletes_num_bytes = soft_deletes_file_slice.num_bytes();
+            let soft_deletes = SoftDeletes::open(soft_deletes_file_slice.read_bytes()?)?;
+            (Some(Arc::new(soft_deletes)), soft_deletes_num_bytes)
+        } else {
+            (None, ByteCount::default())
+        };
 
         let max_doc = segment.meta().max_doc();
-        let num_docs = alive_bitset_opt
-            .as_ref()
-            .map(|alive_bitset| alive_bitset.num_alive_docs() as u32)
-            .unwrap_or(max_doc);
+        let alive_bitset_opt = exclude_soft_deleted(
+            alive_bitset_with_soft_deleted_opt.as_ref(),
+            soft_deletes_opt.as_deref(),
+            max_doc,
+        );
+        let num_docs = num_alive_docs(alive_bitset_opt.as_ref(), max_doc);
 
         Ok(SegmentReader {
             inv_idx_reader_cache: Default::default(),
@@ -201,12 +258,37 @@ impl SegmentReader {
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
             store_file,
+            store_dictionaries_opt,
             alive_bitset_opt,
+            alive_bitset_with_soft_deleted_opt,
+            full_alive_bitset: Default::default(),
+            soft_deletes_opt,
+            soft_deletes_num_bytes,
             positions_composite,
             schema,
         })
     }
 
+    /// Returns a reader of the same segment, for which the soft deleted documents are alive.
+    ///
+    /// Searching with it retrieves the soft deleted documents, until their retention expires
+    /// or they are purged. See [`SoftDeleteOptions`](crate::indexer::SoftDeleteOptions).
+    pub fn include_soft_deleted(&self) -> SegmentReader {
+        let mut segment_reader = self.clone();
+        segment_reader.alive_bitset_opt = self.alive_bitset_with_soft_deleted_opt.clone();
+        segment_reader.full_alive_bitset = Default::default();
+        segment_reader.num_docs =
+            num_alive_docs(segment_reader.alive_bitset_opt.as_ref(), self.max_doc);
+        segment_reader
+    }
+
+    /// Returns the soft deleted documents of the segment, if any.
+    ///
+    /// Documents that were soft deleted and then deleted for good are not included.
+    pub fn soft_deletes(&self) -> Option<&SoftDeletes> {
+        self.soft_deletes_opt.as_deref()
+    }
+
     /// Returns a field reader associated with the field given in argument.
     /// If the field was not present in the index during indexing time,
     /// the InvertedIndexReader is empty.
@@ -273,6 +355,7 @@ impl SegmentReader {
             postings_file,
             positions_file,
             record_option,
+            field_type.has_offsets(),
         )?);
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -285,6 +368,32 @@ impl SegmentReader {
         Ok(inv_idx_reader)
     }
 
+    /// Returns the term vector of the document `doc` for `field`, i.e. the terms of the field in
+    /// the document, in the order of the term dictionary, along with their occurrences.
+    ///
+    /// The term vector is rebuilt by looking the document up in the postings of every term of
+    /// the field. It does not require any additional storage, and reflects exactly what was
+    /// indexed, but is slow on segments with many terms: it is meant to be used on a handful of
+    /// documents, like the top hits of a query.
+    ///
+    /// Returns an error if the field is not indexed.
+    pub fn term_vector(&self, doc: DocId, field: Field) -> crate::Result<Vec<TermVectorEntry>> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !field_entry.is_indexed() {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Field {:?} is not indexed.",
+                field_entry.name()
+            )));
+        }
+        let inverted_index = self.inverted_index(field)?;
+        term_vector_from_postings(
+            &inverted_index,
+            field,
+            field_entry.field_type().value_type(),
+            doc,
+        )
+    }
+
     /// Returns the list of fields that have been indexed in the segment.
     /// The field list includes the field defined in the schema as well as the fields
     /// that have been indexed as a part of a JSON field.
@@ -434,6 +543,20 @@ impl SegmentReader {
         self.alive_bitset_opt.as_ref()
     }
 
+    /// Returns the bitset representing the alive `DocId`s, even if the segment has no deletes.
+    ///
+    /// For a segment without deletes, a full bitset is built on the first call and cached
+    /// for the lifetime of the reader.
+    pub fn alive_bitset_or_full(&self) -> &AliveBitSet {
+        if let Some(alive_bitset) = &self.alive_bitset_opt {
+            return alive_bitset;
+        }
+        self.full_alive_bitset.get_or_init(|| {
+            let bitset = BitSet::with_max_value_and_full(self.max_doc);
+            AliveBitSet::from(ReadOnlyBitSet::from(&bitset))
+        })
+    }
+
     /// Returns true if the `doc` is marked
     /// as deleted.
     pub fn is_deleted(&self, doc: DocId) -> bool {
@@ -451,21 +574,51 @@ impl SegmentReader {
         }
     }
 
+    /// Returns an iterator over the alive document ids, in increasing order.
+    ///
+    /// Contrary to [`SegmentReader::doc_ids_alive`], the iterator is not boxed.
+    pub fn alive_docids(&self) -> impl Iterator<Item = DocId> + Send + '_ {
+        match &self.alive_bitset_opt {
+            Some(alive_bitset) => Either::Left(alive_bitset.iter_alive()),
+            None => Either::Right(0u32..self.max_doc),
+        }
+    }
+
     /// Summarize total space usage of this segment.
+    ///
+    /// The memory usage reported does not include a doc store block cache, as the segment reader
+    /// does not hold one. See [`Searcher::space_usage`](crate::Searcher::space_usage).
     pub fn space_usage(&self) -> io::Result<SegmentSpaceUsage> {
-        Ok(SegmentSpaceUsage::new(
+        let alive_bitset_num_bytes = self
+            .alive_bitset_opt
+            .as_ref()
+            .map(AliveBitSet::space_usage)
+            .unwrap_or_default();
+        let mut space_usage = SegmentSpaceUsage::new(
             self.num_docs(),
             self.termdict_composite.space_usage(self.schema()),
             self.postings_composite.space_usage(self.schema()),
             self.positions_composite.space_usage(self.schema()),
             self.fast_fields_readers.space_usage()?,
             self.fieldnorm_readers.space_usage(self.schema()),
+            self.fast_fields_readers.f32_vectors_space_usage(),
             self.get_store_reader(0)?.space_usage(),
+            alive_bitset_num_bytes,
+            self.soft_deletes_num_bytes,
+        );
+        space_usage.set_memory(self.memory_usage(ByteCount::default()));
+        Ok(space_usage)
+    }
+
+    pub(crate) fn memory_usage(&self, store_block_cache: ByteCount) -> SegmentMemoryUsage {
+        SegmentMemoryUsage::new(
+            store_block_cache,
+            self.fieldnorm_readers.loaded_num_bytes(),
             self.alive_bitset_opt
                 .as_ref()
                 .map(AliveBitSet::space_usage)
                 .unwrap_or_default(),
-        ))
+        )
     }
 }
 
@@ -561,6 +714,31 @@ pub fn merge_field_meta_data(mut field_metadatas: Vec<Vec<FieldMetadata>>) -> Ve
     merged_field_metadata
 }
 
+fn num_alive_docs(alive_bitset_opt: Option<&AliveBitSet>, max_doc: DocId) -> DocId {
+    alive_bitset_opt
+        .map(|alive_bitset| alive_bitset.num_alive_docs() as u32)
+        .unwrap_or(max_doc)
+}
+
+fn exclude_soft_deleted(
+    alive_bitset_opt: Option<&AliveBitSet>,
+    soft_deletes_opt: Option<&SoftDeletes>,
+    max_doc: DocId,
+) -> Option<AliveBitSet> {
+    let Some(soft_deletes) = soft_deletes_opt.filter(|soft_deletes| !soft_deletes.is_empty())
+    else {
+        return alive_bitset_opt.cloned();
+    };
+    let mut bitset = BitSet::with_max_value_and_full(max_doc);
+    if let Some(alive_bitset) = alive_bitset_opt {
+        bitset.intersect_update(alive_bitset.bitset());
+    }
+    for (doc, _) in soft_deletes.iter() {
+        bitset.remove(doc);
+    }
+    Some(AliveBitSet::from(ReadOnlyBitSet::from(&bitset)))
+}
+
 fn intersect_alive_bitset(
     left_opt: Option<AliveBitSet>,
     right_opt: Option<AliveBitSet>,
@@ -810,4 +988,140 @@ mod test {
         assert_eq!(vec![0u32, 2u32], docs);
         Ok(())
     }
+
+    #[test]
+    fn test_alive_docids_and_bitset_or_full() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let name = schema_builder.add_text_field("name", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for text in ["tantivy", "horse", "jockey", "cap"] {
+            index_writer.add_document(doc!(name => text))?;
+        }
+        index_writer.commit()?;
+        let segment = index.searchable_segments()?.pop().unwrap();
+
+        // No deletes.
+        let segment_reader = SegmentReader::open(&segment)?;
+        assert!(segment_reader.alive_bitset().is_none());
+        assert_eq!(
+            segment_reader.alive_docids().collect::<Vec<DocId>>(),
+            vec![0, 1, 2, 3]
+        );
+        let full_bitset = segment_reader.alive_bitset_or_full();
+        assert_eq!(full_bitset.num_alive_docs(), 4);
+        assert_eq!(
+            full_bitset.iter_alive().collect::<Vec<DocId>>(),
+            vec![0, 1, 2, 3]
+        );
+        // The full bitset is built once, and shared by the clones of the reader.
+        assert!(std::ptr::eq(
+            full_bitset,
+            segment_reader.clone().alive_bitset_or_full()
+        ));
+
+        // Some deletes.
+        let alive_bitset = AliveBitSet::for_test_from_deleted_docs(&[1, 3], 4);
+        let segment_reader =
+            SegmentReader::open_with_custom_alive_set(&segment, Some(alive_bitset))?;
+        assert_eq!(
+            segment_reader.alive_docids().collect::<Vec<DocId>>(),
+            vec![0, 2]
+        );
+        let alive_bitset = segment_reader.alive_bitset_or_full();
+        assert_eq!(alive_bitset.num_alive_docs(), 2);
+        assert!(std::ptr::eq(
+            alive_bitset,
+            segment_reader.alive_bitset().unwrap()
+        ));
+
+        // All deleted.
+        let alive_bitset = AliveBitSet::for_test_from_deleted_docs(&[0, 1, 2, 3], 4);
+        let segment_reader =
+            SegmentReader::open_with_custom_alive_set(&segment, Some(alive_bitset))?;
+        assert_eq!(segment_reader.alive_docids().next(), None);
+        assert_eq!(segment_reader.alive_bitset_or_full().num_alive_docs(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_handle_cache_reused_across_searches() -> crate::Result<()> {
+        use crate::collector::{Count, TopDocs};
+        use crate::query::TermQuery;
+        use crate::schema::{IndexRecordOption, FAST};
+        use crate::Order;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "tantivy search", num => 1u64))?;
+        index_writer.add_document(doc!(title => "tantivy", num => 2u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(searcher.handle_cache_stats().num_entries, 0);
+
+        let query = TermQuery::new(
+            Term::from_field_text(title, "tantivy"),
+            IndexRecordOption::WithFreqs,
+        );
+        let collector = (
+            TopDocs::with_limit(2).order_by_score(),
+            TopDocs::with_limit(2).order_by_u64_field("num", Order::Desc),
+            Count,
+        );
+        searcher.search(&query, &collector)?;
+        let column = segment_reader.fast_fields().u64("num")?;
+        let stats_after_first_search = searcher.handle_cache_stats();
+
+        searcher.search(&query, &collector)?;
+        let column_after_second_search = segment_reader.fast_fields().u64("num")?;
+        let stats_after_second_search = searcher.handle_cache_stats();
+
+        assert!(Arc::ptr_eq(
+            &column.values,
+            &column_after_second_search.values
+        ));
+        assert_eq!(
+            stats_after_first_search.num_entries,
+            stats_after_second_search.num_entries
+        );
+        assert_eq!(
+            stats_after_first_search.cache_misses,
+            stats_after_second_search.cache_misses
+        );
+        assert!(stats_after_second_search.cache_hits > stats_after_first_search.cache_hits);
+        Ok(())
+    }
+
+    #[test]
+    fn test_handle_cache_dropped_with_segment_reader() -> crate::Result<()> {
+        use crate::schema::FAST;
+
+        let mut schema_builder = Schema::builder();
+        let num = schema_builder.add_u64_field("num", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(num => 1u64))?;
+        index_writer.commit()?;
+        let segment = index.searchable_segments()?.pop().unwrap();
+
+        let segment_reader = SegmentReader::open(&segment)?;
+        let column_values = Arc::downgrade(&segment_reader.fast_fields().u64("num")?.values);
+        assert!(column_values.upgrade().is_some());
+        assert_eq!(segment_reader.handle_cache_stats().num_entries, 1);
+        // missing columns are not cached
+        assert!(segment_reader
+            .fast_fields()
+            .column_opt::<u64>("missing")?
+            .is_none());
+        assert_eq!(segment_reader.handle_cache_stats().num_entries, 1);
+        drop(segment_reader);
+        assert!(column_values.upgrade().is_none());
+        Ok(())
+    }
 }
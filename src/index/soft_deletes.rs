@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::io::Write;
+
+use common::{BinarySerializable, OwnedBytes, VInt};
+
+use crate::{DateTime, DocId};
+
+/// Documents of a segment that were soft deleted, with the time of their deletion.
+///
+/// Soft deleted documents are excluded from search like deleted documents, but they are kept
+/// in the segment, and survive merges until their retention expires or they are purged.
+/// See [`SoftDeleteOptions`](crate::indexer::SoftDeleteOptions).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SoftDeletes {
+    deleted_at: BTreeMap<DocId, DateTime>,
+}
+
+impl SoftDeletes {
+    /// Returns the number of soft deleted documents.
+    pub fn num_docs(&self) -> u32 {
+        self.deleted_at.len() as u32
+    }
+
+    /// Returns true if no document is soft deleted.
+    pub fn is_empty(&self) -> bool {
+        self.deleted_at.is_empty()
+    }
+
+    /// Returns true if the document is soft deleted.
+    pub fn is_soft_deleted(&self, doc: DocId) -> bool {
+        self.deleted_at.contains_key(&doc)
+    }
+
+    /// Returns the time at which the document was soft deleted, if it is.
+    pub fn deleted_at(&self, doc: DocId) -> Option<DateTime> {
+        self.deleted_at.get(&doc).copied()
+    }
+
+    /// Iterates over the soft deleted documents and their deletion time, by increasing `DocId`.
+    pub fn iter(&self) -> impl Iterator<Item = (DocId, DateTime)> + '_ {
+        self.deleted_at
+            .iter()
+            .map(|(&doc, &deleted_at)| (doc, deleted_at))
+    }
+
+    /// Marks the document as soft deleted.
+    ///
+    /// A document soft deleted several times keeps its earliest deletion time.
+    pub(crate) fn insert(&mut self, doc: DocId, deleted_at: DateTime) {
+        self.deleted_at
+            .entry(doc)
+            .and_modify(|previous| *previous = (*previous).min(deleted_at))
+            .or_insert(deleted_at);
+    }
+
+    /// Unmarks the document, returning true if it was soft deleted.
+    pub(crate) fn remove(&mut self, doc: DocId) -> bool {
+        self.deleted_at.remove(&doc).is_some()
+    }
+
+    /// Only keeps the documents for which `predicate` returns true.
+    pub(crate) fn retain(&mut self, mut predicate: impl FnMut(DocId, DateTime) -> bool) {
+        self.deleted_at
+            .retain(|&doc, &mut deleted_at| predicate(doc, deleted_at));
+    }
+
+    /// Serializes the soft deletes.
+    ///
+    /// Warning: this function does not call terminate. The caller is in charge of
+    /// closing the writer properly.
+    pub(crate) fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.deleted_at.len() as u64).serialize(writer)?;
+        let mut previous_doc = 0;
+        for (&doc, deleted_at) in &self.deleted_at {
+            VInt((doc - previous_doc) as u64).serialize(writer)?;
+            deleted_at.into_timestamp_nanos().serialize(writer)?;
+            previous_doc = doc;
+        }
+        Ok(())
+    }
+
+    /// Opens the soft deletes serialized by [`SoftDeletes::write`].
+    pub(crate) fn open(bytes: OwnedBytes) -> io::Result<SoftDeletes> {
+        let mut data = bytes.as_slice();
+        let num_docs = VInt::deserialize(&mut data)?.val();
+        let mut deleted_at = BTreeMap::new();
+        let mut doc = 0;
+        for _ in 0..num_docs {
+            doc += VInt::deserialize(&mut data)?.val() as DocId;
+            let timestamp_nanos = i64::deserialize(&mut data)?;
+            deleted_at.insert(doc, DateTime::from_timestamp_nanos(timestamp_nanos));
+        }
+        Ok(SoftDeletes { deleted_at })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::OwnedBytes;
+
+    use super::SoftDeletes;
+    use crate::DateTime;
+
+    #[test]
+    fn test_soft_deletes_serialization() {
+        let mut soft_deletes = SoftDeletes::default();
+        soft_deletes.insert(3, DateTime::from_timestamp_secs(20));
+        soft_deletes.insert(0, DateTime::from_timestamp_secs(-5));
+        soft_deletes.insert(3, DateTime::from_timestamp_secs(10));
+        soft_deletes.insert(1000, DateTime::from_timestamp_secs(30));
+        assert_eq!(soft_deletes.num_docs(), 3);
+        assert_eq!(
+            soft_deletes.deleted_at(3),
+            Some(DateTime::from_timestamp_secs(10))
+        );
+        let mut buffer = Vec::new();
+        soft_deletes.write(&mut buffer).unwrap();
+        let reopened = SoftDeletes::open(OwnedBytes::new(buffer)).unwrap();
+        assert_eq!(reopened, soft_deletes);
+        assert!(reopened.is_soft_deleted(1000));
+        assert!(!reopened.is_soft_deleted(1));
+    }
+}
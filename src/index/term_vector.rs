@@ -0,0 +1,66 @@
+use crate::index::InvertedIndexReader;
+use crate::postings::Postings;
+use crate::schema::{Field, IndexRecordOption, Term, Type};
+use crate::{DocId, DocSet};
+
+/// A term of a document, along with its occurrences in the document.
+///
+/// See [`SegmentReader::term_vector`](crate::SegmentReader::term_vector).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermVectorEntry {
+    term: Term,
+    term_freq: u32,
+    positions: Vec<u32>,
+}
+
+impl TermVectorEntry {
+    /// Returns the term.
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
+    /// Returns the number of occurrences of the term in the document.
+    ///
+    /// This is 1 if the field does not record term frequencies.
+    pub fn term_freq(&self) -> u32 {
+        self.term_freq
+    }
+
+    /// Returns the positions of the occurrences of the term in the document.
+    ///
+    /// This is empty if the field does not record positions.
+    pub fn positions(&self) -> &[u32] {
+        &self.positions
+    }
+}
+
+/// Rebuilds the term vector of `doc` by looking the document up in the postings of all of the
+/// terms of the inverted index of `field`.
+pub(crate) fn term_vector_from_postings(
+    inverted_index: &InvertedIndexReader,
+    field: Field,
+    value_type: Type,
+    doc: DocId,
+) -> crate::Result<Vec<TermVectorEntry>> {
+    let mut term_vector = Vec::new();
+    let mut term_stream = inverted_index.terms().stream()?;
+    while term_stream.advance() {
+        let mut postings = inverted_index.read_postings_from_terminfo(
+            term_stream.value(),
+            IndexRecordOption::WithFreqsAndPositions,
+        )?;
+        if postings.doc() > doc || postings.seek(doc) != doc {
+            continue;
+        }
+        let mut term = Term::with_type_and_field(value_type, field);
+        term.append_bytes(term_stream.key());
+        let mut positions = Vec::new();
+        postings.positions(&mut positions);
+        term_vector.push(TermVectorEntry {
+            term,
+            term_freq: postings.term_freq(),
+            positions,
+        });
+    }
+    Ok(term_vector)
+}
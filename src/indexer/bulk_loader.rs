@@ -0,0 +1,383 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::marker::PhantomData;
+
+use super::index_writer::{
+    MARGIN_IN_BYTES, MEMORY_BUDGET_NUM_BYTES_MAX, MEMORY_BUDGET_NUM_BYTES_MIN,
+};
+use super::merger::IndexMerger;
+use super::operation::AddOperation;
+use super::segment_updater::save_metas;
+use super::{SegmentSerializer, SegmentWriter};
+use crate::directory::error::DeleteError;
+use crate::directory::{DirectoryLock, INDEX_WRITER_LOCK};
+use crate::index::{Index, Segment, SegmentMeta};
+use crate::schema::document::Document;
+use crate::{Directory, Opstamp, TantivyDocument, TantivyError};
+
+/// Write-once loader for building large segments with a bounded amount of memory.
+///
+/// The regular [`IndexWriter`](crate::IndexWriter) produces segments the size of its
+/// memory budget, and relies on background merges to build larger segments.
+/// The `BulkLoader` instead indexes documents in runs bounded by its memory budget,
+/// flushes each run to the directory, and streams the runs into a single segment
+/// every `max_docs_per_segment` documents. The number of segments produced by the load
+/// is therefore known upfront, and their size does not depend on the available memory.
+///
+/// The trade-off is that the loader is append-only:
+/// - documents cannot be deleted or updated during the load.
+/// - the loader holds the index writer lock, so no `IndexWriter` can run concurrently.
+/// - nothing is visible to readers until [`BulkLoader::finalize`] is called. Dropping the loader
+///   without finalizing it discards the load, and leaves its files to the next garbage collection.
+///
+/// ```rust
+/// # use tantivy::indexer::BulkLoader;
+/// # use tantivy::schema::{Schema, TEXT};
+/// # use tantivy::{doc, Index};
+/// # fn main() -> tantivy::Result<()> {
+/// # let mut schema_builder = Schema::builder();
+/// # let text = schema_builder.add_text_field("text", TEXT);
+/// # let index = Index::create_in_ram(schema_builder.build());
+/// let mut bulk_loader: BulkLoader = BulkLoader::new(&index, 15_000_000, 1_000_000)?;
+/// for i in 0..100 {
+///     bulk_loader.add_document(doc!(text => format!("document {i}")))?;
+/// }
+/// bulk_loader.finalize()?;
+/// assert_eq!(index.reader()?.searcher().num_docs(), 100);
+/// # Ok(())
+/// # }
+/// ```
+pub struct BulkLoader<D: Document = TantivyDocument> {
+    index: Index,
+    _directory_lock: DirectoryLock,
+    memory_budget_in_bytes: usize,
+    flush_threshold_in_bytes: usize,
+    max_docs_per_segment: u32,
+    // The run being indexed in memory.
+    current_run: Option<(SegmentWriter, Segment)>,
+    // Segments flushed since the last call to `build_segment`.
+    runs: Vec<Segment>,
+    num_docs_in_runs: u32,
+    // Values registered in the dictionaries of the enum fields by these runs.
+    runs_registered_enum_values: BTreeMap<String, BTreeSet<String>>,
+    segment_metas: Vec<SegmentMeta>,
+    start_opstamp: Opstamp,
+    opstamp: Opstamp,
+    _phantom: PhantomData<D>,
+}
+
+impl<D: Document> BulkLoader<D> {
+    /// Creates a new `BulkLoader`, adding segments of `max_docs_per_segment` documents
+    /// to the index.
+    ///
+    /// `memory_budget_in_bytes` bounds the memory used to index documents. It has the same
+    /// limits as the memory budget of a single `IndexWriter` thread.
+    ///
+    /// # Errors
+    /// If an `IndexWriter` is already running on the index, returns
+    /// `TantivyError::LockFailure`.
+    /// If the index was opened in read-only mode, returns `TantivyError::ReadOnlyIndex`.
+    pub fn new(
+        index: &Index,
+        memory_budget_in_bytes: usize,
+        max_docs_per_segment: u32,
+    ) -> crate::Result<Self> {
+        if memory_budget_in_bytes < MEMORY_BUDGET_NUM_BYTES_MIN {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The memory budget of the bulk loader needs to be at least \
+                 {MEMORY_BUDGET_NUM_BYTES_MIN}."
+            )));
+        }
+        if memory_budget_in_bytes >= MEMORY_BUDGET_NUM_BYTES_MAX {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The memory budget of the bulk loader cannot exceed {MEMORY_BUDGET_NUM_BYTES_MAX}"
+            )));
+        }
+        if max_docs_per_segment == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The number of documents per segment needs to be at least 1".to_string(),
+            ));
+        }
+        if index.is_read_only() {
+            return Err(TantivyError::ReadOnlyIndex);
+        }
+        let directory_lock = index
+            .directory()
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some(
+                        "Failed to acquire index lock. There is already an `IndexWriter` or a \
+                         `BulkLoader` working on this `Directory`."
+                            .to_string(),
+                    ),
+                )
+            })?;
+        let start_opstamp = index.load_metas()?.opstamp;
+        Ok(BulkLoader {
+            index: index.clone(),
+            _directory_lock: directory_lock,
+            memory_budget_in_bytes,
+            flush_threshold_in_bytes: memory_budget_in_bytes - MARGIN_IN_BYTES,
+            max_docs_per_segment,
+            current_run: None,
+            runs: Vec::new(),
+            num_docs_in_runs: 0,
+            runs_registered_enum_values: BTreeMap::new(),
+            segment_metas: Vec::new(),
+            start_opstamp,
+            opstamp: start_opstamp,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Adds a document.
+    ///
+    /// The document is not visible until the loader is finalized.
+    pub fn add_document(&mut self, document: D) -> crate::Result<Opstamp> {
+        let opstamp = self.opstamp;
+        self.opstamp += 1;
+        let (segment_writer, _) = match &mut self.current_run {
+            Some(current_run) => current_run,
+            None => {
+                let segment = self.index.new_segment();
+                let segment_writer =
+                    SegmentWriter::for_segment(self.memory_budget_in_bytes, segment.clone())?;
+                self.current_run.insert((segment_writer, segment))
+            }
+        };
+        segment_writer.add_document(AddOperation { opstamp, document })?;
+        let max_doc = segment_writer.max_doc();
+        let mem_usage = segment_writer.mem_usage();
+        if self.num_docs_in_runs + max_doc >= self.max_docs_per_segment {
+            self.flush_run()?;
+            self.build_segment()?;
+        } else if mem_usage >= self.flush_threshold_in_bytes {
+            info!("Buffer limit reached, flushing bulk load run with maxdoc={max_doc}.");
+            self.flush_run()?;
+        }
+        Ok(opstamp)
+    }
+
+    /// Number of segments built so far.
+    pub fn num_segments(&self) -> usize {
+        self.segment_metas.len()
+    }
+
+    /// Builds the last segment, and commits all of the segments built by the loader.
+    ///
+    /// Returns the opstamp of the commit.
+    pub fn finalize(mut self) -> crate::Result<Opstamp> {
+        self.flush_run()?;
+        self.build_segment()?;
+        let mut index_meta = self.index.load_metas()?;
+        index_meta.segments.append(&mut self.segment_metas);
+        index_meta.opstamp = self.opstamp;
+        index_meta.register_segment_enum_values();
+        save_metas(&index_meta, self.index.directory())?;
+        self.index.directory().sync_directory()?;
+        info!(
+            "Bulk load committed {} docs.",
+            self.opstamp - self.start_opstamp
+        );
+        Ok(self.opstamp)
+    }
+
+    fn flush_run(&mut self) -> crate::Result<()> {
+        let Some((mut segment_writer, segment)) = self.current_run.take() else {
+            return Ok(());
+        };
+        let max_doc = segment_writer.max_doc();
+        for (field_name, values) in segment_writer.take_registered_enum_values() {
+            self.runs_registered_enum_values
+                .entry(field_name)
+                .or_default()
+                .extend(values);
+        }
+        segment_writer.finalize()?;
+        self.runs.push(segment.with_max_doc(max_doc));
+        self.num_docs_in_runs += max_doc;
+        Ok(())
+    }
+
+    // Streams the runs flushed so far into a single segment.
+    fn build_segment(&mut self) -> crate::Result<()> {
+        let runs = std::mem::take(&mut self.runs);
+        self.num_docs_in_runs = 0;
+        let registered_enum_values = std::mem::take(&mut self.runs_registered_enum_values);
+        if runs.len() <= 1 {
+            self.segment_metas.extend(runs.into_iter().map(|run| {
+                run.meta()
+                    .clone()
+                    .with_registered_enum_values(registered_enum_values.clone())
+            }));
+            return Ok(());
+        }
+        let merged_segment = self.index.new_segment();
+        let alive_bitsets = runs.iter().map(|_| None).collect();
+        let merger =
+            IndexMerger::open_with_custom_alive_set(self.index.schema(), &runs, alive_bitsets)?;
+        let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+        let num_docs = merger.write(segment_serializer)?;
+        drop(merger);
+        self.segment_metas.push(
+            self.index
+                .new_segment_meta(merged_segment.id(), num_docs)
+                .with_registered_enum_values(registered_enum_values),
+        );
+        for run in &runs {
+            self.delete_run_files(run);
+        }
+        Ok(())
+    }
+
+    fn delete_run_files(&self, run: &Segment) {
+        for path in run.meta().list_files() {
+            match self.index.directory().delete(&path) {
+                Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => {}
+                Err(err) => warn!("Failed to delete bulk load run file {path:?}: {err:?}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BulkLoader;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, Value, FAST, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, TantivyError, Term};
+
+    const WORDS: [&str; 5] = ["alpha", "beta", "gamma", "delta", "epsilon"];
+
+    fn corpus_schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT | STORED);
+        schema_builder.add_text_field("id", STRING | STORED);
+        schema_builder.add_u64_field("num", FAST);
+        schema_builder.build()
+    }
+
+    fn corpus(schema: &Schema) -> Vec<TantivyDocument> {
+        let text = schema.get_field("text").unwrap();
+        let id = schema.get_field("id").unwrap();
+        let num = schema.get_field("num").unwrap();
+        (0..100u64)
+            .map(|i| {
+                doc!(
+                    text => format!("{} {}", WORDS[i as usize % 5], WORDS[i as usize % 3]),
+                    id => format!("doc-{i}"),
+                    num => i,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bulk_loader_same_results_as_index_writer() -> crate::Result<()> {
+        let schema = corpus_schema();
+        let regular_index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = regular_index.writer_for_tests()?;
+        for doc in corpus(&schema) {
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+
+        let bulk_index = Index::create_in_ram(schema.clone());
+        let mut bulk_loader: BulkLoader = BulkLoader::new(&bulk_index, 15_000_000, 40)?;
+        // Flush a run every document, so that segments are built from several runs.
+        bulk_loader.flush_threshold_in_bytes = 0;
+        for doc in corpus(&schema) {
+            bulk_loader.add_document(doc)?;
+        }
+        assert_eq!(bulk_loader.num_segments(), 2);
+        assert_eq!(bulk_loader.finalize()?, 100);
+        let segment_num_docs: Vec<u32> = bulk_index
+            .searchable_segment_metas()?
+            .iter()
+            .map(|meta| meta.num_docs())
+            .collect();
+        assert_eq!(segment_num_docs, vec![40, 40, 20]);
+
+        let text = schema.get_field("text").unwrap();
+        let id = schema.get_field("id").unwrap();
+        let regular_searcher = regular_index.reader()?.searcher();
+        let bulk_searcher = bulk_index.reader()?.searcher();
+        assert_eq!(bulk_searcher.num_docs(), 100);
+        for query in [
+            "alpha",
+            "beta",
+            "gamma AND delta",
+            "epsilon OR alpha",
+            "zeta",
+        ] {
+            let regular_query =
+                QueryParser::for_index(&regular_index, vec![text]).parse_query(query)?;
+            let bulk_query = QueryParser::for_index(&bulk_index, vec![text]).parse_query(query)?;
+            assert_eq!(
+                regular_searcher.search(&regular_query, &Count)?,
+                bulk_searcher.search(&bulk_query, &Count)?
+            );
+            let collector = TopDocs::with_limit(100).order_by_score();
+            let ids = |searcher: &crate::Searcher, query: &dyn crate::query::Query| {
+                let mut ids: Vec<(String, String)> = searcher
+                    .search(query, &collector)?
+                    .into_iter()
+                    .map(|(score, doc_address)| {
+                        let doc: TantivyDocument = searcher.doc(doc_address)?;
+                        let id = doc.get_first(id).unwrap().as_str().unwrap().to_string();
+                        Ok((format!("{score:.4}"), id))
+                    })
+                    .collect::<crate::Result<_>>()?;
+                ids.sort();
+                crate::Result::Ok(ids)
+            };
+            assert_eq!(
+                ids(&regular_searcher, &regular_query)?,
+                ids(&bulk_searcher, &bulk_query)?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_loader_appends_to_existing_index() -> crate::Result<()> {
+        let schema = corpus_schema();
+        let id = schema.get_field("id").unwrap();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "existing"))?;
+        index_writer.commit()?;
+        drop(index_writer);
+
+        let mut bulk_loader: BulkLoader = BulkLoader::new(&index, 15_000_000, 1_000)?;
+        assert!(matches!(
+            index.writer_for_tests::<TantivyDocument>(),
+            Err(TantivyError::LockFailure(..))
+        ));
+        for doc in corpus(&schema) {
+            bulk_loader.add_document(doc)?;
+        }
+        assert_eq!(bulk_loader.finalize()?, 102);
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        assert_eq!(searcher.num_docs(), 101);
+        let query = crate::query::TermQuery::new(
+            Term::from_field_text(id, "existing"),
+            crate::schema::IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        // The lock is released once the loader is finalized.
+        let _index_writer: IndexWriter = index.writer_for_tests()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_loader_invalid_arguments() {
+        let index = Index::create_in_ram(corpus_schema());
+        assert!(BulkLoader::<TantivyDocument>::new(&index, 1_000, 10).is_err());
+        assert!(BulkLoader::<TantivyDocument>::new(&index, 15_000_000, 0).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use super::{IndexWriter, PreparedCommit};
+use crate::schema::document::Document;
+use crate::{Opstamp, TantivyDocument};
+
+/// Commits several indexes together.
+///
+/// A `CommitGroup` is typically used when several indexes are derived from the
+/// same stream of documents and must advance together: either all of them publish
+/// a new commit, or none of them does.
+///
+/// Committing is done in two phases:
+/// - a commit is prepared on every [`IndexWriter`] of the group. If any of them fails, all of the
+///   writers are rolled back to their last commit, and none of the indexes advances.
+/// - the prepared commits are then committed, in the order in which the writers were given.
+///
+/// Note that the second phase cannot be undone. If committing one of the indexes fails
+/// after others were committed, the remaining writers are rolled back but the indexes
+/// committed so far keep their new commit.
+///
+/// ```rust
+/// # use tantivy::indexer::CommitGroup;
+/// # use tantivy::schema::{Schema, TEXT};
+/// # use tantivy::{doc, Index, IndexWriter};
+/// # fn main() -> tantivy::Result<()> {
+/// # let mut schema_builder = Schema::builder();
+/// # let text = schema_builder.add_text_field("text", TEXT);
+/// # let schema = schema_builder.build();
+/// let primary_index = Index::create_in_ram(schema.clone());
+/// let suggestions_index = Index::create_in_ram(schema);
+/// let mut primary_writer: IndexWriter = primary_index.writer(15_000_000)?;
+/// let mut suggestions_writer: IndexWriter = suggestions_index.writer(15_000_000)?;
+/// primary_writer.add_document(doc!(text => "hello world"))?;
+/// suggestions_writer.add_document(doc!(text => "hello"))?;
+///
+/// let mut commit_group = CommitGroup::new(vec![&mut primary_writer, &mut suggestions_writer]);
+/// commit_group.set_payload("checkpoint-1");
+/// commit_group.commit()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CommitGroup<'a, D: Document = TantivyDocument> {
+    index_writers: Vec<&'a mut IndexWriter<D>>,
+    payload: Option<String>,
+}
+
+impl<'a, D: Document> CommitGroup<'a, D> {
+    /// Creates a new `CommitGroup` over the given writers.
+    pub fn new(index_writers: Vec<&'a mut IndexWriter<D>>) -> Self {
+        CommitGroup {
+            index_writers,
+            payload: None,
+        }
+    }
+
+    /// Adds an arbitrary payload to the commit of every index of the group.
+    pub fn set_payload(&mut self, payload: &str) {
+        self.payload = Some(payload.to_string());
+    }
+
+    /// Prepares and then commits all of the writers of the group.
+    ///
+    /// Returns the opstamps of the commits, in the order in which the writers
+    /// were given.
+    pub fn commit(self) -> crate::Result<Vec<Opstamp>> {
+        let CommitGroup {
+            mut index_writers,
+            payload,
+        } = self;
+        let mut opstamps = Vec::with_capacity(index_writers.len());
+        for ord in 0..index_writers.len() {
+            match prepare_commit(index_writers[ord]) {
+                Ok(opstamp) => opstamps.push(opstamp),
+                Err(error) => {
+                    rollback_all(&mut index_writers);
+                    return Err(error);
+                }
+            }
+        }
+        for (num_committed, (index_writer, &opstamp)) in
+            index_writers.iter_mut().zip(&opstamps).enumerate()
+        {
+            let mut prepared_commit = PreparedCommit::new(index_writer, opstamp);
+            if let Some(payload) = &payload {
+                prepared_commit.set_payload(payload);
+            }
+            if let Err(error) = prepared_commit.commit() {
+                rollback_all(&mut index_writers[num_committed..]);
+                return Err(error);
+            }
+        }
+        Ok(opstamps)
+    }
+}
+
+fn prepare_commit<D: Document>(index_writer: &mut IndexWriter<D>) -> crate::Result<Opstamp> {
+    crate::fail_point!("CommitGroup::prepare_commit", |msg| Err(
+        crate::TantivyError::InternalError(msg.unwrap_or_else(|| "Undefined".to_string()))
+    ));
+    Ok(index_writer.prepare_commit()?.opstamp())
+}
+
+fn rollback_all<D: Document>(index_writers: &mut [&mut IndexWriter<D>]) {
+    for index_writer in index_writers {
+        if let Err(error) = index_writer.rollback() {
+            error!("Failed to rollback index writer: {error:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommitGroup;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_commit_group() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index_a = Index::create_in_ram(schema.clone());
+        let index_b = Index::create_in_ram(schema);
+        let mut writer_a: IndexWriter = index_a.writer_for_tests()?;
+        let mut writer_b: IndexWriter = index_b.writer_for_tests()?;
+        writer_a.add_document(doc!(text => "a"))?;
+        writer_a.add_document(doc!(text => "a"))?;
+        writer_b.add_document(doc!(text => "b"))?;
+
+        let mut commit_group = CommitGroup::new(vec![&mut writer_a, &mut writer_b]);
+        commit_group.set_payload("checkpoint");
+        let opstamps = commit_group.commit()?;
+        assert_eq!(opstamps, vec![3, 2]);
+
+        for (index, num_docs) in [(&index_a, 2), (&index_b, 1)] {
+            let meta = index.load_metas()?;
+            assert_eq!(meta.payload.as_deref(), Some("checkpoint"));
+            assert_eq!(index.reader()?.searcher().num_docs(), num_docs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_group_empty() -> crate::Result<()> {
+        let commit_group: CommitGroup = CommitGroup::new(Vec::new());
+        assert!(commit_group.commit()?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::{DocId, Opstamp};
+
+/// Counts of the documents added and deleted by a commit.
+///
+/// Available via [`IndexWriter::last_commit_summary`](crate::IndexWriter::last_commit_summary)
+/// once the commit is done.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitSummary {
+    /// Opstamp of the commit.
+    pub opstamp: Opstamp,
+    /// Number of documents added since the previous commit.
+    pub added_docs: u64,
+    /// Number of delete operations since the previous commit.
+    ///
+    /// This includes the deletes of [`IndexWriter::run`](crate::IndexWriter::run) and
+    /// [`IndexWriter::purge_soft_deleted`](crate::IndexWriter::purge_soft_deleted).
+    pub delete_operations: u64,
+    /// Number of documents deleted by the delete operations.
+    ///
+    /// Documents that were already deleted are not counted again. Soft deleted documents are
+    /// counted when they are soft deleted, and when they are purged.
+    pub deleted_docs: u64,
+    /// Number of documents deleted by each delete operation, by opstamp.
+    ///
+    /// Operations that did not delete any document, for instance because their term does not
+    /// match any document, are listed with a count of 0.
+    pub deleted_docs_per_operation: BTreeMap<Opstamp, u64>,
+    /// Number of segments created by the indexing threads since the previous commit.
+    pub segments_created: u64,
+}
+
+/// Collects the counts of the [`CommitSummary`] of the ongoing commit.
+#[derive(Default)]
+pub(crate) struct CommitStats {
+    added_docs: AtomicU64,
+    segments_created: AtomicU64,
+    deleted_docs_per_operation: Mutex<BTreeMap<Opstamp, u64>>,
+}
+
+impl CommitStats {
+    /// Records a segment created by an indexing thread.
+    pub(crate) fn record_segment(&self, max_doc: DocId) {
+        self.added_docs.fetch_add(max_doc as u64, Ordering::Relaxed);
+        self.segments_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a delete operation, before it is applied to any segment.
+    pub(crate) fn record_delete_operation(&self, opstamp: Opstamp) {
+        self.deleted_docs_per_operation
+            .lock()
+            .unwrap()
+            .entry(opstamp)
+            .or_insert(0);
+    }
+
+    /// Records documents deleted by the delete operation with the given opstamp.
+    pub(crate) fn record_deleted_docs(&self, opstamp: Opstamp, num_deleted_docs: u64) {
+        *self
+            .deleted_docs_per_operation
+            .lock()
+            .unwrap()
+            .entry(opstamp)
+            .or_insert(0) += num_deleted_docs;
+    }
+
+    /// Returns the summary of the commit with the given opstamp, and resets the counts.
+    pub(crate) fn take_summary(&self, opstamp: Opstamp) -> CommitSummary {
+        let deleted_docs_per_operation =
+            std::mem::take(&mut *self.deleted_docs_per_operation.lock().unwrap());
+        CommitSummary {
+            opstamp,
+            added_docs: self.added_docs.swap(0, Ordering::Relaxed),
+            delete_operations: deleted_docs_per_operation.len() as u64,
+            deleted_docs: deleted_docs_per_operation.values().sum(),
+            deleted_docs_per_operation,
+            segments_created: self.segments_created.swap(0, Ordering::Relaxed),
+        }
+    }
+}
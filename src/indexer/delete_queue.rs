@@ -245,6 +245,7 @@ mod tests {
 
     use super::{DeleteOperation, DeleteQueue};
     use crate::index::SegmentReader;
+    use crate::indexer::operation::DeleteKind;
     use crate::query::{Explanation, Scorer, Weight};
     use crate::{DocId, Score};
 
@@ -266,6 +267,7 @@ mod tests {
         let make_op = |i: usize| DeleteOperation {
             opstamp: i as u64,
             target: Box::new(DummyWeight),
+            kind: DeleteKind::Hard,
         };
 
         delete_queue.push(make_op(1));
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::schema::document::Document;
+use crate::schema::{Field, Schema};
+use crate::Opstamp;
+
+/// Callback receiving the [`DiscardedValues`] of the documents added to the index.
+///
+/// It is configured on the writer via
+/// [`IndexWriterOptions`](crate::indexer::IndexWriterOptions).
+pub type DiscardedValuesCallback = Arc<dyn Fn(&DiscardedValues) + Send + Sync>;
+
+/// Warning about the values a document supplies for a field which is neither indexed, stored nor
+/// fast.
+///
+/// Such values are entirely discarded by the index. They usually reveal a field whose options
+/// do not match its intended use, or a document carrying data nobody asked for. They can be
+/// removed from a document before it is added, via
+/// [`TantivyDocument::strip_discarded_values`](crate::TantivyDocument::strip_discarded_values).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscardedValues {
+    /// Opstamp of the operation which added the document.
+    pub opstamp: Opstamp,
+    /// The field the values were supplied for.
+    pub field: Field,
+    /// Name of the field.
+    pub field_name: String,
+    /// Number of values discarded.
+    pub num_values: usize,
+}
+
+/// Calls `callback` once for each field of `document` whose values are discarded.
+pub(crate) fn report_discarded_values<D: Document>(
+    schema: &Schema,
+    opstamp: Opstamp,
+    document: &D,
+    callback: &DiscardedValuesCallback,
+) {
+    let mut num_values_per_field: BTreeMap<Field, usize> = BTreeMap::new();
+    for (field, _) in document.iter_fields_and_values() {
+        if schema.get_field_entry(field).discards_values() {
+            *num_values_per_field.entry(field).or_default() += 1;
+        }
+    }
+    for (field, num_values) in num_values_per_field {
+        callback(&DiscardedValues {
+            opstamp,
+            field,
+            field_name: schema.get_field_name(field).to_string(),
+            num_values,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::DiscardedValues;
+    use crate::indexer::IndexWriterOptions;
+    use crate::schema::{Schema, TextOptions, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument};
+
+    #[test]
+    fn test_discarded_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        // Neither indexed, stored nor fast.
+        let raw = schema_builder.add_text_field("raw", TextOptions::default());
+        let schema = schema_builder.build();
+        let large_text = "lorem ipsum dolor sit amet ".repeat(1_000);
+
+        let index_docs = |strip: bool| -> crate::Result<(Index, Vec<DiscardedValues>)> {
+            let index = Index::create_in_ram(schema.clone());
+            let warnings: Arc<Mutex<Vec<DiscardedValues>>> = Arc::default();
+            let callback_warnings = warnings.clone();
+            let options = IndexWriterOptions::builder()
+                .discarded_values_callback(Arc::new(move |warning: &DiscardedValues| {
+                    callback_warnings.lock().unwrap().push(warning.clone());
+                }))
+                .build();
+            let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+            for doc_id in 0..10 {
+                let mut doc = TantivyDocument::default();
+                doc.add_text(title, format!("title {doc_id}"));
+                if doc_id % 2 == 0 {
+                    doc.add_text(raw, &large_text);
+                    doc.add_text(raw, "another raw value");
+                }
+                if strip {
+                    let num_bytes_before = doc.node_data.len();
+                    let num_stripped = doc.strip_discarded_values(&schema);
+                    assert_eq!(num_stripped, if doc_id % 2 == 0 { 2 } else { 0 });
+                    assert_eq!(doc.len(), 1);
+                    assert_eq!(doc.node_data.len() < num_bytes_before, doc_id % 2 == 0);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+            let warnings = warnings.lock().unwrap().clone();
+            Ok((index, warnings))
+        };
+        let doc_store_num_bytes = |index: &Index| -> crate::Result<u64> {
+            let searcher = index.reader()?.searcher();
+            let space_usage = searcher.space_usage()?;
+            Ok(space_usage
+                .segments()
+                .iter()
+                .map(|segment| segment.store().total().get_bytes())
+                .sum())
+        };
+
+        let (index, mut warnings) = index_docs(false)?;
+        warnings.sort_by_key(|warning| warning.opstamp);
+        assert_eq!(warnings.len(), 5);
+        assert_eq!(
+            warnings[1],
+            DiscardedValues {
+                opstamp: 2,
+                field: raw,
+                field_name: "raw".to_string(),
+                num_values: 2,
+            }
+        );
+
+        // Discarded values never reach the doc store: stripping them only spares the memory
+        // they take in the indexing queue.
+        let (stripped_index, stripped_warnings) = index_docs(true)?;
+        assert!(stripped_warnings.is_empty());
+        assert_eq!(
+            doc_store_num_bytes(&index)?,
+            doc_store_num_bytes(&stripped_index)?
+        );
+        let searcher = stripped_index.reader()?.searcher();
+        let doc: TantivyDocument = searcher.doc(crate::DocAddress::new(0, 0))?;
+        assert_eq!(doc.len(), 1);
+        Ok(())
+    }
+}
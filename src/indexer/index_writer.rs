@@ -6,22 +6,33 @@ use std::thread::JoinHandle;
 use common::BitSet;
 use smallvec::smallvec;
 
+use super::commit_summary::{CommitStats, CommitSummary};
+use super::indexing_metrics::{IndexingMetrics, IndexingMetricsSnapshot, IndexingStage};
 use super::operation::{AddOperation, UserOperation};
 use super::segment_updater::SegmentUpdater;
 use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
 use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
 use crate::error::TantivyError;
-use crate::fastfield::write_alive_bitset;
-use crate::index::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::fastfield::{write_alive_bitset, F32VectorFields};
+use crate::index::{
+    Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader, SoftDeletes,
+};
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
 use crate::indexer::index_writer_status::IndexWriterStatus;
-use crate::indexer::operation::DeleteOperation;
+use crate::indexer::operation::{DeleteKind, DeleteOperation};
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
-use crate::query::{EnableScoring, Query, TermQuery};
+use crate::indexer::{
+    DiscardedValuesCallback, MergeDocIdMappingCallback, MergeOutcome, MergePlan, MergePolicy,
+    SegmentEntry, SegmentWriter, SoftDeleteOptions, TtlOptions,
+};
+use crate::query::{AllWeight, EnableScoring, Query, TermQuery};
+#[cfg(feature = "arrow")]
+use crate::schema::document::documents_from_record_batch;
 use crate::schema::document::Document;
-use crate::schema::{IndexRecordOption, TantivyDocument, Term};
+#[cfg(feature = "arrow")]
+use crate::schema::ArrowFieldMapping;
+use crate::schema::{EnumFields, IndexRecordOption, TantivyDocument, Term};
 use crate::{FutureResult, Opstamp};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
@@ -60,6 +71,28 @@ pub struct IndexWriterOptions {
     #[builder(default = 4)]
     /// Defines the number of merger threads to use.
     num_merge_threads: usize,
+    #[builder(default = false)]
+    /// Collects [`IndexingMetrics`], available via [`IndexWriter::metrics`].
+    enable_metrics: bool,
+    /// Expires documents older than a given age when merging segments.
+    ///
+    /// The number of expired documents is available via [`IndexWriter::num_expired_docs`], and
+    /// for a single merge via [`IndexWriter::merge_with_outcome`].
+    ttl: Option<TtlOptions>,
+    /// Soft deletes the documents instead of deleting them, keeping them for a retention period.
+    soft_deletes: Option<SoftDeleteOptions>,
+    /// Called with the [`MergeDocIdMapping`](crate::indexer::MergeDocIdMapping) of each merge,
+    /// once the merged segment has replaced the segments it was merged from.
+    ///
+    /// The callback runs on the thread applying the segment updates, which it blocks, and
+    /// receives the mappings in the order of the merges. Merges of uncommitted segments are
+    /// reported as well, and are discarded if the writer is rolled back.
+    merge_doc_id_mapping_callback: Option<MergeDocIdMappingCallback>,
+    /// Called with the [`DiscardedValues`](crate::indexer::DiscardedValues) of each field of
+    /// an added document which is neither indexed, stored nor fast.
+    ///
+    /// The callback runs on the indexing threads, which it blocks.
+    discarded_values_callback: Option<DiscardedValuesCallback>,
 }
 
 /// `IndexWriter` is the user entry-point to add document to an index.
@@ -68,6 +101,10 @@ pub struct IndexWriterOptions {
 /// indexing queue.
 /// Each indexing thread builds its own independent [`Segment`], via
 /// a `SegmentWriter` object.
+///
+/// Adding and deleting documents only require a shared reference, so that several threads
+/// can do so concurrently through an `Arc<RwLock<IndexWriter>>`, taking the write lock only to
+/// commit or roll back. See the `index_from_multiple_threads` example.
 pub struct IndexWriter<D: Document = TantivyDocument> {
     // the lock is just used to bind the
     // lifetime of the lock with that of the IndexWriter.
@@ -90,14 +127,26 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
 
     stamper: Stamper,
     committed_opstamp: Opstamp,
+
+    indexing_metrics: Option<Arc<IndexingMetrics>>,
+    metrics_at_last_commit: IndexingMetricsSnapshot,
+    last_commit_metrics: Option<IndexingMetricsSnapshot>,
+    last_commit_summary: Option<CommitSummary>,
+
+    f32_vector_fields: F32VectorFields,
+    enum_fields: EnumFields,
 }
 
+/// If `commit_stats` is set, the number of documents deleted by each delete operation is
+/// recorded there. Documents already deleted are not counted.
 fn compute_deleted_bitset(
     alive_bitset: &mut BitSet,
+    soft_deletes: &mut SoftDeletes,
     segment_reader: &SegmentReader,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &DocToOpstampMapping,
     target_opstamp: Opstamp,
+    commit_stats: Option<&CommitStats>,
 ) -> crate::Result<bool> {
     let mut might_have_changed = false;
     while let Some(delete_op) = delete_cursor.get() {
@@ -105,18 +154,43 @@ fn compute_deleted_bitset(
             break;
         }
 
+        let mut num_deleted_docs = 0u64;
         // A delete operation should only affect
         // document that were inserted before it.
         delete_op
             .target
             .for_each_no_score(segment_reader, &mut |docs_matching_delete_query| {
                 for doc_matching_delete_query in docs_matching_delete_query.iter().cloned() {
-                    if doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
-                        alive_bitset.remove(doc_matching_delete_query);
-                        might_have_changed = true;
+                    if !doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
+                        continue;
                     }
+                    let was_alive = alive_bitset.contains(doc_matching_delete_query);
+                    match delete_op.kind {
+                        DeleteKind::Hard => {
+                            alive_bitset.remove(doc_matching_delete_query);
+                            num_deleted_docs += was_alive as u64;
+                        }
+                        DeleteKind::Soft { deleted_at } => {
+                            if was_alive && !soft_deletes.is_soft_deleted(doc_matching_delete_query)
+                            {
+                                num_deleted_docs += 1;
+                            }
+                            soft_deletes.insert(doc_matching_delete_query, deleted_at);
+                        }
+                        DeleteKind::PurgeSoftDeleted => {
+                            if !soft_deletes.remove(doc_matching_delete_query) {
+                                continue;
+                            }
+                            alive_bitset.remove(doc_matching_delete_query);
+                            num_deleted_docs += was_alive as u64;
+                        }
+                    }
+                    might_have_changed = true;
                 }
             })?;
+        if let Some(commit_stats) = commit_stats {
+            commit_stats.record_deleted_docs(delete_op.opstamp, num_deleted_docs);
+        }
         delete_cursor.advance();
     }
     Ok(might_have_changed)
@@ -129,50 +203,87 @@ fn compute_deleted_bitset(
 /// For instance, there was no delete operation between the state of the `segment_entry` and
 /// the `target_opstamp`, `segment_entry` is not updated.
 pub fn advance_deletes(
+    segment: Segment,
+    segment_entry: &mut SegmentEntry,
+    target_opstamp: Opstamp,
+) -> crate::Result<()> {
+    advance_deletes_with_stats(segment, segment_entry, target_opstamp, None)
+}
+
+/// Same as [`advance_deletes`], also recording the number of documents deleted by each
+/// delete operation in `commit_stats`.
+pub(crate) fn advance_deletes_with_stats(
     mut segment: Segment,
     segment_entry: &mut SegmentEntry,
     target_opstamp: Opstamp,
+    commit_stats: Option<&CommitStats>,
 ) -> crate::Result<()> {
     if segment_entry.meta().delete_opstamp() == Some(target_opstamp) {
         // We are already up-to-date here.
         return Ok(());
     }
 
-    if segment_entry.alive_bitset().is_none() && segment_entry.delete_cursor().get().is_none() {
+    if segment_entry.alive_bitset().is_none()
+        && segment_entry.soft_deletes().is_none()
+        && segment_entry.delete_cursor().get().is_none()
+    {
         // There has been no `DeleteOperation` between the segment status and `target_opstamp`.
         return Ok(());
     }
 
-    let segment_reader = SegmentReader::open(&segment)?;
+    // The soft deleted documents are still alive as far as the delete file is concerned.
+    let segment_reader = SegmentReader::open(&segment)?.include_soft_deleted();
 
     let max_doc = segment_reader.max_doc();
     let mut alive_bitset: BitSet = match segment_entry.alive_bitset() {
         Some(previous_alive_bitset) => (*previous_alive_bitset).clone(),
         None => BitSet::with_max_value_and_full(max_doc),
     };
+    // Removing the deleted documents first, so that deleting them again is not counted.
+    if let Some(seg_alive_bitset) = segment_reader.alive_bitset() {
+        alive_bitset.intersect_update(seg_alive_bitset.bitset());
+    }
 
     let num_deleted_docs_before = segment.meta().num_deleted_docs();
+    let soft_deletes_before = segment_reader.soft_deletes().cloned().unwrap_or_default();
+    let mut soft_deletes = soft_deletes_before.clone();
+    if let Some(previous_soft_deletes) = segment_entry.soft_deletes() {
+        for (doc, deleted_at) in previous_soft_deletes.iter() {
+            soft_deletes.insert(doc, deleted_at);
+        }
+    }
 
     compute_deleted_bitset(
         &mut alive_bitset,
+        &mut soft_deletes,
         &segment_reader,
         segment_entry.delete_cursor(),
         &DocToOpstampMapping::None,
         target_opstamp,
+        commit_stats,
     )?;
 
-    if let Some(seg_alive_bitset) = segment_reader.alive_bitset() {
-        alive_bitset.intersect_update(seg_alive_bitset.bitset());
-    }
+    soft_deletes.retain(|doc, _| alive_bitset.contains(doc));
 
     let num_alive_docs: u32 = alive_bitset.len() as u32;
     let num_deleted_docs = max_doc - num_alive_docs;
-    if num_deleted_docs > num_deleted_docs_before {
-        // There are new deletes. We need to write a new delete file.
-        segment = segment.with_delete_meta(num_deleted_docs, target_opstamp);
-        let mut alive_doc_file = segment.open_write(SegmentComponent::Delete)?;
-        write_alive_bitset(&alive_bitset, &mut alive_doc_file)?;
-        alive_doc_file.terminate()?;
+    if num_deleted_docs > num_deleted_docs_before || soft_deletes != soft_deletes_before {
+        // There are new deletes. We need to write new delete files.
+        segment = segment.with_soft_delete_meta(
+            num_deleted_docs,
+            soft_deletes.num_docs(),
+            target_opstamp,
+        );
+        if num_deleted_docs > 0 {
+            let mut alive_doc_file = segment.open_write(SegmentComponent::Delete)?;
+            write_alive_bitset(&alive_bitset, &mut alive_doc_file)?;
+            alive_doc_file.terminate()?;
+        }
+        if !soft_deletes.is_empty() {
+            let mut soft_deletes_file = segment.open_write(SegmentComponent::SoftDelete)?;
+            soft_deletes.write(&mut soft_deletes_file)?;
+            soft_deletes_file.terminate()?;
+        }
     }
 
     segment_entry.set_meta(segment.meta().clone());
@@ -185,8 +296,12 @@ fn index_documents<D: Document>(
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
     segment_updater: &SegmentUpdater,
     mut delete_cursor: DeleteCursor,
+    indexing_metrics: Option<&Arc<IndexingMetrics>>,
+    discarded_values_callback: Option<&DiscardedValuesCallback>,
 ) -> crate::Result<()> {
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    segment_writer.set_indexing_metrics(indexing_metrics.cloned());
+    segment_writer.set_discarded_values_callback(discarded_values_callback.cloned());
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc)?;
@@ -206,35 +321,54 @@ fn index_documents<D: Document>(
     }
 
     let max_doc = segment_writer.max_doc();
+    let registered_enum_values = segment_writer.take_registered_enum_values();
 
     // this is ensured by the call to peek before starting
     // the worker thread.
     assert!(max_doc > 0);
 
-    let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
+    let doc_opstamps: Vec<Opstamp> = if let Some(indexing_metrics) = indexing_metrics {
+        indexing_metrics.time(IndexingStage::Serialization, || segment_writer.finalize())?
+    } else {
+        segment_writer.finalize()?
+    };
 
     let segment_with_max_doc = segment.with_max_doc(max_doc);
+    let commit_stats = segment_updater.commit_stats();
+    commit_stats.record_segment(max_doc);
+
+    let (alive_bitset_opt, soft_deletes_opt) = apply_deletes(
+        &segment_with_max_doc,
+        &mut delete_cursor,
+        &doc_opstamps,
+        commit_stats,
+    )?;
 
-    let alive_bitset_opt = apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
-
-    let meta = segment_with_max_doc.meta().clone();
+    let meta = segment_with_max_doc
+        .meta()
+        .clone()
+        .with_registered_enum_values(registered_enum_values);
 
     // update segment_updater inventory to remove tempstore
-    let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
+    let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt)
+        .with_soft_deletes(soft_deletes_opt);
     segment_updater.schedule_add_segment(segment_entry).wait()?;
     Ok(())
 }
 
 /// `doc_opstamps` is required to be non-empty.
+///
+/// Returns the alive bitset and the soft deletes resulting from the delete operations.
 fn apply_deletes(
     segment: &Segment,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &[Opstamp],
-) -> crate::Result<Option<BitSet>> {
+    commit_stats: &CommitStats,
+) -> crate::Result<(Option<BitSet>, Option<SoftDeletes>)> {
     if delete_cursor.get().is_none() {
         // if there are no delete operation in the queue, no need
         // to even open the segment.
-        return Ok(None);
+        return Ok((None, None));
     }
 
     let max_doc_opstamp: Opstamp = doc_opstamps
@@ -248,17 +382,22 @@ fn apply_deletes(
 
     let max_doc = segment.meta().max_doc();
     let mut deleted_bitset = BitSet::with_max_value_and_full(max_doc);
+    let mut soft_deletes = SoftDeletes::default();
     let may_have_deletes = compute_deleted_bitset(
         &mut deleted_bitset,
+        &mut soft_deletes,
         &segment_reader,
         delete_cursor,
         &doc_to_opstamps,
         max_doc_opstamp,
+        Some(commit_stats),
     )?;
+    soft_deletes.retain(|doc, _| deleted_bitset.contains(doc));
+    let soft_deletes_opt = (!soft_deletes.is_empty()).then_some(soft_deletes);
     Ok(if may_have_deletes {
-        Some(deleted_bitset)
+        (Some(deleted_bitset), soft_deletes_opt)
     } else {
-        None
+        (None, None)
     })
 }
 
@@ -299,6 +438,9 @@ impl<D: Document> IndexWriter<D> {
             let err_msg = "At least one worker thread is required, got 0".to_string();
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        if let Some(ttl_options) = &options.ttl {
+            ttl_options.validate(&index.schema())?;
+        }
 
         let (document_sender, document_receiver) =
             crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
@@ -309,11 +451,19 @@ impl<D: Document> IndexWriter<D> {
 
         let stamper = Stamper::new(current_opstamp);
 
+        let indexing_metrics = options
+            .enable_metrics
+            .then(|| Arc::new(IndexingMetrics::default()));
+
         let segment_updater = SegmentUpdater::create(
             index.clone(),
             stamper.clone(),
             &delete_queue.cursor(),
             options.num_merge_threads,
+            indexing_metrics.clone(),
+            options.ttl.clone(),
+            options.soft_deletes.clone(),
+            options.merge_doc_id_mapping_callback.clone(),
         )?;
 
         let mut index_writer = Self {
@@ -334,6 +484,14 @@ impl<D: Document> IndexWriter<D> {
             stamper,
 
             worker_id: 0,
+
+            indexing_metrics,
+            metrics_at_last_commit: IndexingMetricsSnapshot::default(),
+            last_commit_metrics: None,
+            last_commit_summary: None,
+
+            f32_vector_fields: F32VectorFields::for_schema(&index.schema()),
+            enum_fields: EnumFields::for_schema(&index.schema()),
         };
         index_writer.start_workers()?;
         Ok(index_writer)
@@ -421,6 +579,8 @@ impl<D: Document> IndexWriter<D> {
 
         let mem_budget = self.options.memory_budget_per_thread;
         let index = self.index.clone();
+        let indexing_metrics = self.indexing_metrics.clone();
+        let discarded_values_callback = self.options.discarded_values_callback.clone();
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
             .spawn(move || {
@@ -453,6 +613,8 @@ impl<D: Document> IndexWriter<D> {
                         &mut document_iterator,
                         &segment_updater,
                         delete_cursor.clone(),
+                        indexing_metrics.as_ref(),
+                        discarded_values_callback.as_ref(),
                     )?;
                 }
             })?;
@@ -538,6 +700,37 @@ impl<D: Document> IndexWriter<D> {
         segment_updater.start_merge(merge_operation)
     }
 
+    /// Merges a given list of segments, like [`IndexWriter::merge`].
+    ///
+    /// The [`MergeOutcome`] also gives the number of documents expired by the merge, as
+    /// configured by the [`TtlOptions`] of this writer.
+    pub fn merge_with_outcome(&mut self, segment_ids: &[SegmentId]) -> FutureResult<MergeOutcome> {
+        let merge_operation = self.segment_updater.make_merge_operation(segment_ids);
+        let segment_updater = self.segment_updater.clone();
+        segment_updater.start_merge_with_outcome(merge_operation)
+    }
+
+    /// Returns the merges that the merge policy would start for the current segments, without
+    /// starting them.
+    ///
+    /// Each merge comes with the number of documents and of deleted documents of its segments,
+    /// and with their size, from which the size of the merged segment and the space it frees
+    /// are estimated. The segments being merged are not considered, like when the merge
+    /// policy is run after a commit.
+    pub fn merge_plan(&self) -> crate::Result<MergePlan> {
+        self.segment_updater.merge_plan()
+    }
+
+    /// Starts the merges of a plan returned by [`IndexWriter::merge_plan`], and returns their
+    /// results, in the order of the merges of the plan.
+    ///
+    /// The merges of the plan are started whatever the merge policy. A merge whose segments
+    /// are gone or already being merged since the plan was computed fails, without
+    /// preventing the other merges from being started.
+    pub fn execute_plan(&mut self, plan: &MergePlan) -> Vec<FutureResult<Option<SegmentMeta>>> {
+        self.segment_updater.execute_plan(plan)
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -661,14 +854,82 @@ impl<D: Document> IndexWriter<D> {
     ///
     /// Commit returns the `opstamp` of the last document
     /// that made it in the commit.
+    ///
+    /// The number of documents added and deleted by the commit is then available via
+    /// [`IndexWriter::last_commit_summary`]. If metrics are enabled, a summary of the
+    /// indexing work done since the previous commit is available via
+    /// [`IndexWriter::last_commit_metrics`].
     pub fn commit(&mut self) -> crate::Result<Opstamp> {
         self.prepare_commit()?.commit()
     }
 
+    /// Returns the indexing metrics of this writer, or `None` if metrics were not enabled
+    /// in the [`IndexWriterOptions`].
+    pub fn metrics(&self) -> Option<&IndexingMetrics> {
+        self.indexing_metrics.as_deref()
+    }
+
+    /// Returns the indexing metrics accumulated between the last two commits.
+    ///
+    /// The summary is set by [`IndexWriter::commit`] and [`PreparedCommit::commit`]. Returns
+    /// `None` if metrics are disabled or if nothing was committed yet.
+    /// Merges running in the background are accounted for in the commit following
+    /// their completion.
+    pub fn last_commit_metrics(&self) -> Option<&IndexingMetricsSnapshot> {
+        self.last_commit_metrics.as_ref()
+    }
+
+    pub(crate) fn set_last_commit_metrics(&mut self) {
+        if let Some(indexing_metrics) = &self.indexing_metrics {
+            let metrics = indexing_metrics.snapshot();
+            self.last_commit_metrics = Some(metrics.since(&self.metrics_at_last_commit));
+            self.metrics_at_last_commit = metrics;
+        }
+    }
+
+    /// Returns the number of documents added and deleted by the last commit.
+    ///
+    /// The summary is set by [`IndexWriter::commit`] and [`PreparedCommit::commit`]. Returns
+    /// `None` if nothing was committed yet, or since the last rollback.
+    pub fn last_commit_summary(&self) -> Option<&CommitSummary> {
+        self.last_commit_summary.as_ref()
+    }
+
+    pub(crate) fn set_last_commit_summary(&mut self, opstamp: Opstamp) {
+        let summary = self.segment_updater.commit_stats().take_summary(opstamp);
+        self.last_commit_summary = Some(summary);
+    }
+
+    /// Pushes a delete operation to the delete queue.
+    fn push_delete_operation(&self, delete_operation: DeleteOperation) {
+        self.segment_updater
+            .commit_stats()
+            .record_delete_operation(delete_operation.opstamp);
+        self.delete_queue.push(delete_operation);
+    }
+
+    /// Returns the number of documents expired by merges, as configured by the
+    /// [`TtlOptions`] of this writer.
+    ///
+    /// The count starts at 0 when the writer is created or rolled back.
+    pub fn num_expired_docs(&self) -> u64 {
+        self.segment_updater.num_expired_docs()
+    }
+
     pub(crate) fn segment_updater(&self) -> &SegmentUpdater {
         &self.segment_updater
     }
 
+    /// Returns how the delete operations of this writer delete documents.
+    fn delete_kind(&self) -> DeleteKind {
+        match &self.options.soft_deletes {
+            Some(soft_delete_options) => DeleteKind::Soft {
+                deleted_at: soft_delete_options.now(),
+            },
+            None => DeleteKind::Hard,
+        }
+    }
+
     /// Delete all documents containing a given term.
     ///
     /// Delete operation only affects documents that
@@ -677,6 +938,8 @@ impl<D: Document> IndexWriter<D> {
     ///
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
+    ///
+    /// If the writer is configured with [`SoftDeleteOptions`], the documents are soft deleted.
     pub fn delete_term(&self, term: Term) -> Opstamp {
         let query = TermQuery::new(term, IndexRecordOption::Basic);
         // For backward compatibility, if Term is invalid for the index, do nothing but return an
@@ -694,6 +957,8 @@ impl<D: Document> IndexWriter<D> {
     ///
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
+    ///
+    /// If the writer is configured with [`SoftDeleteOptions`], the documents are soft deleted.
     #[doc(hidden)]
     pub fn delete_query(&self, query: Box<dyn Query>) -> crate::Result<Opstamp> {
         let weight = query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
@@ -701,11 +966,27 @@ impl<D: Document> IndexWriter<D> {
         let delete_operation = DeleteOperation {
             opstamp,
             target: weight,
+            kind: self.delete_kind(),
         };
-        self.delete_queue.push(delete_operation);
+        self.push_delete_operation(delete_operation);
         Ok(opstamp)
     }
 
+    /// Deletes for good all of the documents soft deleted so far.
+    ///
+    /// Like deletes, the purge only affects documents that were soft deleted before it, and
+    /// is only visible after calling `commit()`.
+    pub fn purge_soft_deleted(&self) -> Opstamp {
+        let opstamp = self.stamper.stamp();
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: Box::new(AllWeight),
+            kind: DeleteKind::PurgeSoftDeleted,
+        };
+        self.push_delete_operation(delete_operation);
+        opstamp
+    }
+
     /// Returns the opstamp of the last successful commit.
     ///
     /// This is, for instance, the opstamp the index will
@@ -724,7 +1005,16 @@ impl<D: Document> IndexWriter<D> {
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
     /// document queue.
+    ///
+    /// Returns an error if the values of a f32 vector field do not match its
+    /// [dimensions](crate::schema::F32VectorOptions::set_dimensions), or if a value of an enum
+    /// field which rejects unknown values is not part of its
+    /// [dictionary](crate::schema::EnumValues).
     pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        self.f32_vector_fields
+            .check_document(&self.index.schema(), &document)?;
+        self.enum_fields
+            .check_document(&self.index.schema(), &document)?;
         let opstamp = self.stamper.stamp();
         self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
         Ok(opstamp)
@@ -782,10 +1072,15 @@ impl<D: Document> IndexWriter<D> {
                     let delete_operation = DeleteOperation {
                         opstamp,
                         target: weight,
+                        kind: self.delete_kind(),
                     };
-                    self.delete_queue.push(delete_operation);
+                    self.push_delete_operation(delete_operation);
                 }
                 UserOperation::Add(document) => {
+                    self.f32_vector_fields
+                        .check_document(&self.index.schema(), &document)?;
+                    self.enum_fields
+                        .check_document(&self.index.schema(), &document)?;
                     let add_operation = AddOperation { opstamp, document };
                     adds.push(add_operation);
                 }
@@ -804,6 +1099,25 @@ impl<D: Document> IndexWriter<D> {
     }
 }
 
+#[cfg(feature = "arrow")]
+impl IndexWriter<TantivyDocument> {
+    /// Adds all of the rows of an Arrow record batch, as read from a Parquet file for instance.
+    ///
+    /// Rows are converted with [`TantivyDocument::from_arrow_row`]. If any row fails to
+    /// convert, no document of the batch is added.
+    ///
+    /// The documents are added as a group, see [`IndexWriter::run`]. The batch opstamp is
+    /// returned.
+    pub fn add_record_batch(
+        &self,
+        mapping: &ArrowFieldMapping,
+        batch: &arrow_array::RecordBatch,
+    ) -> crate::Result<Opstamp> {
+        let documents = documents_from_record_batch(&self.index.schema(), mapping, batch)?;
+        self.run(documents.into_iter().map(UserOperation::Add))
+    }
+}
+
 impl<D: Document> Drop for IndexWriter<D> {
     fn drop(&mut self) {
         self.segment_updater.kill();
@@ -2585,4 +2899,146 @@ mod tests {
         );
         assert!(matches!(result, Err(TantivyError::InvalidArgument(_))));
     }
+
+    #[test]
+    fn test_index_writer_metrics() -> crate::Result<()> {
+        use crate::indexer::IndexingStage;
+
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let id_field = schema_builder.add_u64_field("id", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        assert!(index_writer.metrics().is_none());
+        index_writer.add_document(doc!(text_field => LOREM, id_field => 0u64))?;
+        index_writer.commit()?;
+        assert!(index_writer.last_commit_metrics().is_none());
+        drop(index_writer);
+
+        let options = IndexWriterOptions::builder().enable_metrics(true).build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id in 0..100u64 {
+            index_writer.add_document(doc!(text_field => LOREM, id_field => id))?;
+        }
+        index_writer.commit()?;
+
+        let metrics = index_writer.metrics().unwrap().snapshot();
+        assert_eq!(metrics.num_docs, 100);
+        assert!(metrics.num_bytes > 100 * LOREM.len() as u64);
+        assert!(metrics.docs_per_sec() > 0.0);
+        assert!(metrics.bytes_per_sec() > 0.0);
+        for stage in [
+            IndexingStage::Indexing,
+            IndexingStage::FastFields,
+            IndexingStage::DocStore,
+        ] {
+            let stage_metrics = metrics.stage(stage);
+            assert_eq!(stage_metrics.count, 100);
+            assert_eq!(stage_metrics.histogram.count(), 100);
+            assert!(stage_metrics.total > std::time::Duration::ZERO);
+            assert!(stage_metrics.total <= metrics.elapsed);
+        }
+        assert_eq!(metrics.stage(IndexingStage::Serialization).count, 1);
+        assert_eq!(metrics.stage(IndexingStage::Merge).count, 0);
+        assert_eq!(index_writer.last_commit_metrics().unwrap().num_docs, 100);
+
+        for id in 0..10u64 {
+            index_writer.add_document(doc!(text_field => LOREM, id_field => id))?;
+        }
+        index_writer.commit()?;
+        let last_commit_metrics = index_writer.last_commit_metrics().unwrap();
+        assert_eq!(last_commit_metrics.num_docs, 10);
+        assert_eq!(last_commit_metrics.stage(IndexingStage::Indexing).count, 10);
+        assert_eq!(index_writer.metrics().unwrap().snapshot().num_docs, 110);
+
+        for id in 0..5u64 {
+            index_writer.add_document(doc!(text_field => LOREM, id_field => id))?;
+        }
+        index_writer.prepare_commit()?.commit()?;
+        assert_eq!(index_writer.last_commit_metrics().unwrap().num_docs, 5);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let metrics = index_writer.metrics().unwrap().snapshot();
+        assert_eq!(metrics.stage(IndexingStage::Merge).count, 1);
+
+        index_writer.metrics().unwrap().reset();
+        let metrics = index_writer.metrics().unwrap().snapshot();
+        assert_eq!(metrics.num_docs, 0);
+        assert_eq!(metrics.stage(IndexingStage::Merge).count, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_writer_commit_summary() -> crate::Result<()> {
+        use std::collections::BTreeMap;
+
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        assert!(index_writer.last_commit_summary().is_none());
+        let delete = |index_writer: &IndexWriter, id: &str| {
+            index_writer.delete_term(Term::from_field_text(id_field, id))
+        };
+
+        for id in ["a", "b", "c"] {
+            index_writer.add_document(doc!(id_field => id))?;
+        }
+        let opstamp = index_writer.commit()?;
+        let summary = index_writer.last_commit_summary().unwrap();
+        assert_eq!(summary.opstamp, opstamp);
+        assert_eq!(summary.added_docs, 3);
+        assert_eq!(summary.delete_operations, 0);
+        assert_eq!(summary.deleted_docs, 0);
+        assert_eq!(summary.segments_created, 1);
+
+        index_writer.add_document(doc!(id_field => "d"))?;
+        index_writer.add_document(doc!(id_field => "a"))?;
+        let delete_a = delete(&index_writer, "a");
+        let delete_missing = delete(&index_writer, "missing");
+        let delete_d = delete(&index_writer, "d");
+        index_writer.commit()?;
+        let summary = index_writer.last_commit_summary().unwrap();
+        assert_eq!(summary.added_docs, 2);
+        assert_eq!(summary.delete_operations, 3);
+        assert_eq!(summary.deleted_docs, 3);
+        assert_eq!(
+            summary.deleted_docs_per_operation,
+            BTreeMap::from([(delete_a, 2), (delete_missing, 0), (delete_d, 1)])
+        );
+        assert_eq!(summary.segments_created, 1);
+
+        // Deleting documents that are already deleted is a no-op.
+        let delete_a = delete(&index_writer, "a");
+        let delete_b = delete(&index_writer, "b");
+        let mut prepared_commit = index_writer.prepare_commit()?;
+        prepared_commit.set_payload("payload");
+        prepared_commit.commit()?;
+        let summary = index_writer.last_commit_summary().unwrap();
+        assert_eq!(summary.added_docs, 0);
+        assert_eq!(summary.deleted_docs, 1);
+        assert_eq!(
+            summary.deleted_docs_per_operation,
+            BTreeMap::from([(delete_a, 0), (delete_b, 1)])
+        );
+        assert_eq!(summary.segments_created, 0);
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+
+        delete(&index_writer, "c");
+        index_writer.rollback()?;
+        assert!(index_writer.last_commit_summary().is_none());
+        index_writer.commit()?;
+        assert_eq!(
+            index_writer
+                .last_commit_summary()
+                .unwrap()
+                .delete_operations,
+            0
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,357 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const NUM_HISTOGRAM_BUCKETS: usize = 64;
+
+/// A stage of the indexing pipeline, timed by [`IndexingMetrics`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum IndexingStage {
+    /// Tokenizing text and recording terms into the in-memory postings.
+    ///
+    /// Tokens are consumed as they are produced, so tokenization and postings building
+    /// are measured together.
+    Indexing,
+    /// Recording values into the fast field writers.
+    FastFields,
+    /// Serializing documents into the doc store.
+    ///
+    /// This includes the block compression, unless the doc store compresses
+    /// blocks on a dedicated thread.
+    DocStore,
+    /// Flushing an in-memory segment to the directory.
+    Serialization,
+    /// Merging segments.
+    Merge,
+}
+
+impl IndexingStage {
+    /// All of the indexing stages.
+    pub const ALL: [IndexingStage; 5] = [
+        IndexingStage::Indexing,
+        IndexingStage::FastFields,
+        IndexingStage::DocStore,
+        IndexingStage::Serialization,
+        IndexingStage::Merge,
+    ];
+
+    fn ord(self) -> usize {
+        self as usize
+    }
+}
+
+/// Returns the histogram bucket for a duration.
+///
+/// Bucket `i` holds durations in `[2^(i-1), 2^i)` nanoseconds.
+fn bucket_for_duration(duration: Duration) -> usize {
+    let nanos = duration.as_nanos().min(u64::MAX as u128) as u64;
+    (u64::BITS - nanos.leading_zeros()).min(NUM_HISTOGRAM_BUCKETS as u32 - 1) as usize
+}
+
+struct StageRecorder {
+    count: AtomicU64,
+    total_nanos: AtomicU64,
+    histogram: [AtomicU64; NUM_HISTOGRAM_BUCKETS],
+}
+
+impl Default for StageRecorder {
+    fn default() -> Self {
+        StageRecorder {
+            count: AtomicU64::default(),
+            total_nanos: AtomicU64::default(),
+            histogram: std::array::from_fn(|_| AtomicU64::default()),
+        }
+    }
+}
+
+impl StageRecorder {
+    fn record(&self, duration: Duration) {
+        let nanos = duration.as_nanos().min(u64::MAX as u128) as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.histogram[bucket_for_duration(duration)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> StageMetrics {
+        StageMetrics {
+            count: self.count.load(Ordering::Relaxed),
+            total: Duration::from_nanos(self.total_nanos.load(Ordering::Relaxed)),
+            histogram: DurationHistogram {
+                buckets: std::array::from_fn(|i| self.histogram[i].load(Ordering::Relaxed)),
+            },
+        }
+    }
+
+    fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+        self.total_nanos.store(0, Ordering::Relaxed);
+        for bucket in &self.histogram {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Indexing counters and per-stage timings of an [`IndexWriter`](crate::IndexWriter).
+///
+/// Metrics are opt-in: they are only collected if the `IndexWriter` was created with
+/// [`IndexWriterOptions::enable_metrics`](crate::indexer::IndexWriterOptions). Recording relies on
+/// relaxed atomics, so that the metrics can be left enabled in production.
+pub struct IndexingMetrics {
+    start: Mutex<Instant>,
+    num_docs: AtomicU64,
+    num_bytes: AtomicU64,
+    stages: [StageRecorder; IndexingStage::ALL.len()],
+}
+
+impl Default for IndexingMetrics {
+    fn default() -> Self {
+        IndexingMetrics {
+            start: Mutex::new(Instant::now()),
+            num_docs: AtomicU64::default(),
+            num_bytes: AtomicU64::default(),
+            stages: Default::default(),
+        }
+    }
+}
+
+impl IndexingMetrics {
+    /// Records a document added to a segment, and the number of bytes
+    /// its serialized form takes in the doc store.
+    pub(crate) fn record_doc(&self, num_bytes: u64) {
+        self.num_docs.fetch_add(1, Ordering::Relaxed);
+        self.num_bytes.fetch_add(num_bytes, Ordering::Relaxed);
+    }
+
+    /// Records time spent in a given stage.
+    pub(crate) fn record(&self, stage: IndexingStage, duration: Duration) {
+        self.stages[stage.ord()].record(duration);
+    }
+
+    /// Runs `f`, recording its execution time for the given stage.
+    pub(crate) fn time<T>(&self, stage: IndexingStage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let res = f();
+        self.record(stage, start.elapsed());
+        res
+    }
+
+    /// Returns a point-in-time copy of the metrics.
+    pub fn snapshot(&self) -> IndexingMetricsSnapshot {
+        let elapsed = self.start.lock().unwrap().elapsed();
+        IndexingMetricsSnapshot {
+            elapsed,
+            num_docs: self.num_docs.load(Ordering::Relaxed),
+            num_bytes: self.num_bytes.load(Ordering::Relaxed),
+            stages: std::array::from_fn(|i| self.stages[i].snapshot()),
+        }
+    }
+
+    /// Resets all of the counters and timings.
+    pub fn reset(&self) {
+        let mut start = self.start.lock().unwrap();
+        self.num_docs.store(0, Ordering::Relaxed);
+        self.num_bytes.store(0, Ordering::Relaxed);
+        for stage in &self.stages {
+            stage.reset();
+        }
+        *start = Instant::now();
+    }
+}
+
+/// A point-in-time copy of [`IndexingMetrics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexingMetricsSnapshot {
+    /// Time elapsed since the metrics were created or last reset.
+    pub elapsed: Duration,
+    /// Number of documents added to a segment.
+    pub num_docs: u64,
+    /// Number of bytes of the added documents, as serialized in the doc store.
+    pub num_bytes: u64,
+    stages: [StageMetrics; IndexingStage::ALL.len()],
+}
+
+impl IndexingMetricsSnapshot {
+    /// Returns the metrics of the given stage.
+    pub fn stage(&self, stage: IndexingStage) -> &StageMetrics {
+        &self.stages[stage.ord()]
+    }
+
+    /// Number of documents indexed per second.
+    pub fn docs_per_sec(&self) -> f64 {
+        per_sec(self.num_docs, self.elapsed)
+    }
+
+    /// Number of bytes indexed per second.
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.num_bytes, self.elapsed)
+    }
+
+    /// Returns the metrics accumulated between `earlier` and `self`.
+    ///
+    /// Counters that went down, because the metrics were reset in between,
+    /// saturate at 0.
+    pub fn since(&self, earlier: &IndexingMetricsSnapshot) -> IndexingMetricsSnapshot {
+        IndexingMetricsSnapshot {
+            elapsed: self.elapsed.saturating_sub(earlier.elapsed),
+            num_docs: self.num_docs.saturating_sub(earlier.num_docs),
+            num_bytes: self.num_bytes.saturating_sub(earlier.num_bytes),
+            stages: std::array::from_fn(|i| self.stages[i].since(&earlier.stages[i])),
+        }
+    }
+}
+
+fn per_sec(count: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs == 0.0 {
+        return 0.0;
+    }
+    count as f64 / secs
+}
+
+/// Timings of one [`IndexingStage`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StageMetrics {
+    /// Number of times the stage was executed.
+    pub count: u64,
+    /// Cumulative time spent in the stage.
+    pub total: Duration,
+    /// Distribution of the time spent in each execution of the stage.
+    pub histogram: DurationHistogram,
+}
+
+impl StageMetrics {
+    /// Average time spent in one execution of the stage.
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            (self.total.as_nanos() / self.count as u128) as u64,
+        ))
+    }
+
+    fn since(&self, earlier: &StageMetrics) -> StageMetrics {
+        StageMetrics {
+            count: self.count.saturating_sub(earlier.count),
+            total: self.total.saturating_sub(earlier.total),
+            histogram: DurationHistogram {
+                buckets: std::array::from_fn(|i| {
+                    self.histogram.buckets[i].saturating_sub(earlier.histogram.buckets[i])
+                }),
+            },
+        }
+    }
+}
+
+/// Histogram of durations with power-of-two buckets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DurationHistogram {
+    buckets: [u64; NUM_HISTOGRAM_BUCKETS],
+}
+
+impl Default for DurationHistogram {
+    fn default() -> Self {
+        DurationHistogram {
+            buckets: [0; NUM_HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+impl DurationHistogram {
+    /// Number of durations recorded in the histogram.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the `(upper_bound, count)` pairs of the non-empty buckets,
+    /// by increasing upper bound.
+    ///
+    /// `count` durations were strictly lower than `upper_bound`, and greater or equal
+    /// to the upper bound of the previous bucket.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| (bucket_upper_bound(bucket), *count))
+    }
+
+    /// Returns an upper bound of the given quantile (between 0 and 1) of the recorded durations.
+    ///
+    /// The result is precise up to a factor of 2. Returns `None` if the histogram is empty.
+    pub fn quantile_upper_bound(&self, quantile: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut cumulated_count = 0u64;
+        for (bucket, bucket_count) in self.buckets.iter().enumerate() {
+            cumulated_count += bucket_count;
+            if cumulated_count >= rank {
+                return Some(bucket_upper_bound(bucket));
+            }
+        }
+        None
+    }
+}
+
+fn bucket_upper_bound(bucket: usize) -> Duration {
+    Duration::from_nanos(1u64.checked_shl(bucket as u32).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{bucket_for_duration, IndexingMetrics, IndexingStage};
+
+    #[test]
+    fn test_bucket_for_duration() {
+        assert_eq!(bucket_for_duration(Duration::ZERO), 0);
+        assert_eq!(bucket_for_duration(Duration::from_nanos(1)), 1);
+        assert_eq!(bucket_for_duration(Duration::from_nanos(3)), 2);
+        assert_eq!(bucket_for_duration(Duration::from_nanos(4)), 3);
+        assert_eq!(bucket_for_duration(Duration::MAX), 63);
+    }
+
+    #[test]
+    fn test_indexing_metrics_snapshot_and_reset() {
+        let metrics = IndexingMetrics::default();
+        metrics.record_doc(10);
+        metrics.record_doc(20);
+        metrics.record(IndexingStage::Indexing, Duration::from_nanos(3));
+        metrics.record(IndexingStage::Indexing, Duration::from_nanos(100));
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.num_docs, 2);
+        assert_eq!(snapshot.num_bytes, 30);
+        let indexing = snapshot.stage(IndexingStage::Indexing);
+        assert_eq!(indexing.count, 2);
+        assert_eq!(indexing.total, Duration::from_nanos(103));
+        assert_eq!(
+            indexing.histogram.buckets().collect::<Vec<_>>(),
+            vec![(Duration::from_nanos(4), 1), (Duration::from_nanos(128), 1)]
+        );
+        assert_eq!(
+            indexing.histogram.quantile_upper_bound(0.5),
+            Some(Duration::from_nanos(4))
+        );
+        assert_eq!(
+            indexing.histogram.quantile_upper_bound(0.99),
+            Some(Duration::from_nanos(128))
+        );
+        assert_eq!(snapshot.stage(IndexingStage::Merge).count, 0);
+
+        metrics.record_doc(5);
+        let delta = metrics.snapshot().since(&snapshot);
+        assert_eq!(delta.num_docs, 1);
+        assert_eq!(delta.num_bytes, 5);
+        assert_eq!(delta.stage(IndexingStage::Indexing).count, 0);
+
+        metrics.reset();
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.num_docs, 0);
+        assert_eq!(snapshot.stage(IndexingStage::Indexing).count, 0);
+        assert_eq!(snapshot.stage(IndexingStage::Indexing).histogram.count(), 0);
+    }
+}
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use crate::index::SegmentId;
+use crate::{DocAddress, DocId, TERMINATED};
+
+/// Callback receiving the [`MergeDocIdMapping`] of each merge.
+///
+/// It is configured on the writer via
+/// [`IndexWriterOptions`](crate::indexer::IndexWriterOptions).
+pub type MergeDocIdMappingCallback = Arc<dyn Fn(&MergeDocIdMapping) + Send + Sync>;
+
+/// Maps the doc ids of the segments consumed by a merge to the doc ids of the merged segment.
+///
+/// Data kept outside of the index and keyed by `(segment id, doc id)`, such as a vector index,
+/// can be realigned with the index by applying the mapping of every merge, in the order the
+/// callback receives them.
+///
+/// Documents that are deleted, or expired, when the merge starts are not carried over to the
+/// merged segment. Documents deleted while the merge is running are carried over, and are
+/// deleted in the merged segment.
+#[derive(Clone, Debug)]
+pub struct MergeDocIdMapping {
+    source_segment_ids: Vec<SegmentId>,
+    target_segment_id: Option<SegmentId>,
+    // `new_doc_ids[segment_ord][old_doc_id]`, `TERMINATED` if the document is not carried over.
+    new_doc_ids: Vec<Vec<DocId>>,
+    // Ordered by new doc id. `segment_ord` is the position in `source_segment_ids`.
+    old_doc_addrs: Vec<DocAddress>,
+}
+
+impl MergeDocIdMapping {
+    /// Creates the mapping from the old doc address of each new doc id.
+    ///
+    /// `source_segments` holds the id and the max doc of each merged segment.
+    pub(crate) fn new(
+        source_segments: &[(SegmentId, DocId)],
+        target_segment_id: Option<SegmentId>,
+        old_doc_addrs: Vec<DocAddress>,
+    ) -> MergeDocIdMapping {
+        let mut new_doc_ids: Vec<Vec<DocId>> = source_segments
+            .iter()
+            .map(|&(_, max_doc)| vec![TERMINATED; max_doc as usize])
+            .collect();
+        for (new_doc_id, old_doc_addr) in old_doc_addrs.iter().enumerate() {
+            new_doc_ids[old_doc_addr.segment_ord as usize][old_doc_addr.doc_id as usize] =
+                new_doc_id as DocId;
+        }
+        MergeDocIdMapping {
+            source_segment_ids: source_segments
+                .iter()
+                .map(|&(segment_id, _)| segment_id)
+                .collect(),
+            target_segment_id,
+            new_doc_ids,
+            old_doc_addrs,
+        }
+    }
+
+    /// Ids of the segments consumed by the merge.
+    pub fn source_segment_ids(&self) -> &[SegmentId] {
+        &self.source_segment_ids
+    }
+
+    /// Id of the merged segment.
+    ///
+    /// `None` if none of the documents were alive, in which case no segment is created.
+    pub fn target_segment_id(&self) -> Option<SegmentId> {
+        self.target_segment_id
+    }
+
+    /// Number of documents in the merged segment.
+    pub fn num_docs(&self) -> u32 {
+        self.old_doc_addrs.len() as u32
+    }
+
+    /// Returns the doc id in the merged segment of the document `old_doc_id` of the segment
+    /// `source_segment_id`.
+    ///
+    /// Returns `None` if the document was not carried over, or if the segment is not part of
+    /// the merge.
+    pub fn new_doc_id(&self, source_segment_id: SegmentId, old_doc_id: DocId) -> Option<DocId> {
+        let segment_ord = self
+            .source_segment_ids
+            .iter()
+            .position(|&segment_id| segment_id == source_segment_id)?;
+        let new_doc_id = *self.new_doc_ids[segment_ord].get(old_doc_id as usize)?;
+        (new_doc_id != TERMINATED).then_some(new_doc_id)
+    }
+
+    /// Returns the segment id and doc id the document `new_doc_id` of the merged segment
+    /// originates from.
+    pub fn old_doc(&self, new_doc_id: DocId) -> Option<(SegmentId, DocId)> {
+        let old_doc_addr = self.old_doc_addrs.get(new_doc_id as usize)?;
+        Some((
+            self.source_segment_ids[old_doc_addr.segment_ord as usize],
+            old_doc_addr.doc_id,
+        ))
+    }
+
+    /// Returns an iterator over the segment id and doc id each document of the merged segment
+    /// originates from, ordered by new doc id.
+    pub fn old_docs(&self) -> impl Iterator<Item = (SegmentId, DocId)> + '_ {
+        self.old_doc_addrs.iter().map(|old_doc_addr| {
+            (
+                self.source_segment_ids[old_doc_addr.segment_ord as usize],
+                old_doc_addr.doc_id,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use super::MergeDocIdMapping;
+    use crate::index::SegmentId;
+    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
+    use crate::schema::{Schema, FAST, INDEXED};
+    use crate::{Index, IndexWriter, Searcher, Term};
+
+    /// Sidecar data, holding the `id` of each document of each segment.
+    fn sidecar_from_fast_fields(
+        searcher: &Searcher,
+    ) -> crate::Result<HashMap<SegmentId, Vec<u64>>> {
+        let mut sidecar = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let id_column = segment_reader.fast_fields().u64("id")?;
+            let ids = (0..segment_reader.max_doc())
+                .map(|doc| id_column.first(doc).unwrap())
+                .collect();
+            sidecar.insert(segment_reader.segment_id(), ids);
+        }
+        Ok(sidecar)
+    }
+
+    #[test]
+    fn test_merge_doc_id_mapping_realigns_sidecar() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mappings: Arc<Mutex<Vec<MergeDocIdMapping>>> = Arc::default();
+        let callback_mappings = mappings.clone();
+        let options = IndexWriterOptions::builder()
+            .merge_doc_id_mapping_callback(Arc::new(move |mapping: &MergeDocIdMapping| {
+                callback_mappings.lock().unwrap().push(mapping.clone());
+            }))
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0..3u64 {
+            for id in segment_ord * 10..(segment_ord + 1) * 10 {
+                index_writer.add_document(doc!(id_field => id))?;
+            }
+            index_writer.commit()?;
+        }
+        let reader = index.reader()?;
+        let mut sidecar = sidecar_from_fast_fields(&reader.searcher())?;
+        for deleted_id in [3u64, 14, 25] {
+            index_writer.delete_term(Term::from_field_u64(id_field, deleted_id));
+        }
+        index_writer.commit()?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids[..2]).wait()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+
+        let mappings = mappings.lock().unwrap().clone();
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[0].num_docs(), 18);
+        assert_eq!(
+            mappings[1].source_segment_ids(),
+            &[segment_ids[0], segment_ids[1]]
+        );
+        for mapping in &mappings {
+            for (new_doc_id, (old_segment_id, old_doc_id)) in mapping.old_docs().enumerate() {
+                assert_eq!(
+                    mapping.old_doc(new_doc_id as u32),
+                    Some((old_segment_id, old_doc_id))
+                );
+                assert_eq!(
+                    mapping.new_doc_id(old_segment_id, old_doc_id),
+                    Some(new_doc_id as u32)
+                );
+            }
+            let aligned_ids: Vec<u64> = mapping
+                .old_docs()
+                .map(|(old_segment_id, old_doc_id)| sidecar[&old_segment_id][old_doc_id as usize])
+                .collect();
+            for source_segment_id in mapping.source_segment_ids() {
+                let source_ids = sidecar.remove(source_segment_id).unwrap();
+                for (old_doc_id, id) in source_ids.iter().enumerate() {
+                    // Deleted documents are not carried over.
+                    assert_eq!(
+                        mapping
+                            .new_doc_id(*source_segment_id, old_doc_id as u32)
+                            .is_none(),
+                        [3, 14, 25].contains(id)
+                    );
+                }
+            }
+            sidecar.insert(mapping.target_segment_id().unwrap(), aligned_ids);
+        }
+
+        reader.reload()?;
+        assert_eq!(sidecar, sidecar_from_fast_fields(&reader.searcher())?);
+        assert_eq!(sidecar.values().next().unwrap().len(), 27);
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::ops::Deref;
 
-use crate::index::SegmentId;
+use crate::index::{SegmentId, SegmentMeta};
 use crate::{Inventory, Opstamp, TrackedObject};
 
 #[derive(Default)]
@@ -75,3 +75,14 @@ impl MergeOperation {
         &self.inner.segment_ids[..]
     }
 }
+
+/// The outcome of a merge started with
+/// [`IndexWriter::merge_with_outcome`](crate::IndexWriter::merge_with_outcome).
+#[derive(Clone, Debug)]
+pub struct MergeOutcome {
+    /// Meta of the merged segment, or `None` if none of the merged documents were alive.
+    pub segment_meta: Option<SegmentMeta>,
+    /// Number of documents expired by the [`TtlOptions`](crate::indexer::TtlOptions) of the
+    /// writer during the merge.
+    pub num_expired_docs: u64,
+}
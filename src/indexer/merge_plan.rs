@@ -0,0 +1,135 @@
+use common::HasLen;
+
+use crate::directory::error::OpenReadError;
+use crate::directory::Directory;
+use crate::index::{SegmentId, SegmentMeta};
+
+/// The merges that the merge policy suggests for the current segments of an index.
+///
+/// Computing a plan does not start any merge. See
+/// [`IndexWriter::merge_plan`](crate::IndexWriter::merge_plan) and
+/// [`IndexWriter::execute_plan`](crate::IndexWriter::execute_plan).
+#[derive(Clone, Debug, Default)]
+pub struct MergePlan {
+    merges: Vec<PlannedMerge>,
+}
+
+impl MergePlan {
+    pub(crate) fn new(merges: Vec<PlannedMerge>) -> MergePlan {
+        MergePlan { merges }
+    }
+
+    /// Returns the merges of the plan, in the order they would be started.
+    pub fn merges(&self) -> &[PlannedMerge] {
+        &self.merges
+    }
+
+    /// Returns true if the merge policy does not suggest any merge.
+    pub fn is_empty(&self) -> bool {
+        self.merges.is_empty()
+    }
+
+    /// Returns the estimated number of bytes freed by all of the merges of the plan.
+    pub fn estimated_reclaimed_bytes(&self) -> u64 {
+        self.merges
+            .iter()
+            .map(PlannedMerge::estimated_reclaimed_bytes)
+            .sum()
+    }
+}
+
+/// A merge of a [`MergePlan`], with the statistics of the segments it merges.
+#[derive(Clone, Debug)]
+pub struct PlannedMerge {
+    segment_ids: Vec<SegmentId>,
+    is_committed: bool,
+    num_docs: u64,
+    num_deleted_docs: u64,
+    num_bytes: u64,
+}
+
+impl PlannedMerge {
+    /// Computes the statistics of the merge of `segment_ids`, whose metas are in
+    /// `segment_metas`.
+    ///
+    /// The size of the segments is the size of their files in `directory`.
+    pub(crate) fn new(
+        segment_ids: Vec<SegmentId>,
+        is_committed: bool,
+        segment_metas: &[SegmentMeta],
+        directory: &dyn Directory,
+    ) -> crate::Result<PlannedMerge> {
+        let mut num_docs = 0u64;
+        let mut num_deleted_docs = 0u64;
+        let mut num_bytes = 0u64;
+        for segment_meta in segment_metas
+            .iter()
+            .filter(|segment_meta| segment_ids.contains(&segment_meta.id()))
+        {
+            num_docs += segment_meta.num_docs() as u64;
+            num_deleted_docs += segment_meta.num_deleted_docs() as u64;
+            for path in segment_meta.list_files() {
+                match directory.open_read(&path) {
+                    Ok(file_slice) => num_bytes += file_slice.len() as u64,
+                    // Some of the files are only written if the segment needs them.
+                    Err(OpenReadError::FileDoesNotExist(_)) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        Ok(PlannedMerge {
+            segment_ids,
+            is_committed,
+            num_docs,
+            num_deleted_docs,
+            num_bytes,
+        })
+    }
+
+    /// Returns the ids of the segments to merge.
+    pub fn segment_ids(&self) -> &[SegmentId] {
+        &self.segment_ids
+    }
+
+    /// Returns true if the segments to merge are committed.
+    ///
+    /// Committed segments are never merged with uncommitted segments.
+    pub fn is_committed(&self) -> bool {
+        self.is_committed
+    }
+
+    /// Returns the number of documents of the merged segment, i.e. the number of documents
+    /// of the segments to merge which are not deleted.
+    pub fn num_docs(&self) -> u64 {
+        self.num_docs
+    }
+
+    /// Returns the number of deleted documents of the segments to merge, which the merge
+    /// removes.
+    pub fn num_deleted_docs(&self) -> u64 {
+        self.num_deleted_docs
+    }
+
+    /// Returns the number of bytes of the files of the segments to merge.
+    pub fn num_bytes(&self) -> u64 {
+        self.num_bytes
+    }
+
+    /// Returns the estimated number of bytes of the merged segment.
+    ///
+    /// The estimate assumes that all of the documents take the same space, so that the merged
+    /// segment keeps the share of the bytes of the documents which are not deleted.
+    pub fn estimated_num_bytes(&self) -> u64 {
+        let max_doc = self.num_docs + self.num_deleted_docs;
+        if max_doc == 0 {
+            return 0;
+        }
+        (self.num_bytes as u128 * self.num_docs as u128 / max_doc as u128) as u64
+    }
+
+    /// Returns the estimated number of bytes freed by the merge, i.e. the bytes of the deleted
+    /// documents.
+    pub fn estimated_reclaimed_bytes(&self) -> u64 {
+        self.num_bytes - self.estimated_num_bytes()
+    }
+}
@@ -1,22 +1,22 @@
+use std::io::Write;
 use std::sync::Arc;
 
 use columnar::{
     ColumnType, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder, StackMergeOrder,
 };
 use common::ReadOnlyBitSet;
-use itertools::Itertools;
 use measure_time::debug_time;
 
-use crate::directory::WritePtr;
+use crate::directory::{CompositeWrite, WritePtr};
 use crate::docset::{DocSet, TERMINATED};
 use crate::error::DataCorruption;
-use crate::fastfield::AliveBitSet;
+use crate::fastfield::{serialize_f32_vector_field, AliveBitSet, F32VectorColumn};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter};
-use crate::index::{Segment, SegmentComponent, SegmentReader};
+use crate::index::{Segment, SegmentComponent, SegmentReader, SoftDeletes};
 use crate::indexer::doc_id_mapping::{MappingType, SegmentDocIdMapping};
 use crate::indexer::SegmentSerializer;
 use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
-use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
+use crate::schema::{value_type_to_column_type, Field, FieldType, Schema, TantivyDocument};
 use crate::store::StoreWriter;
 use crate::termdict::{TermMerger, TermOrdinal};
 use crate::{DocAddress, DocId, InvertedIndexReader};
@@ -145,11 +145,6 @@ fn extract_fast_field_required_columns(schema: &Schema) -> Vec<(String, ColumnTy
 }
 
 impl IndexMerger {
-    pub fn open(schema: Schema, segments: &[Segment]) -> crate::Result<IndexMerger> {
-        let alive_bitset = segments.iter().map(|_| None).collect_vec();
-        Self::open_with_custom_alive_set(schema, segments, alive_bitset)
-    }
-
     // Create merge with a custom delete set.
     // For every Segment, a delete bitset can be provided, which
     // will be merged with the existing bit set. Make sure the index
@@ -166,12 +161,34 @@ impl IndexMerger {
         schema: Schema,
         segments: &[Segment],
         alive_bitset_opt: Vec<Option<AliveBitSet>>,
+    ) -> crate::Result<IndexMerger> {
+        Self::open_readers(schema, segments, alive_bitset_opt, false)
+    }
+
+    // Same as `open_with_custom_alive_set`, except that the soft deleted documents are
+    // merged too. Their soft deletes are available via `merged_soft_deletes`.
+    pub(crate) fn open_including_soft_deleted(
+        schema: Schema,
+        segments: &[Segment],
+        alive_bitset_opt: Vec<Option<AliveBitSet>>,
+    ) -> crate::Result<IndexMerger> {
+        Self::open_readers(schema, segments, alive_bitset_opt, true)
+    }
+
+    fn open_readers(
+        schema: Schema,
+        segments: &[Segment],
+        alive_bitset_opt: Vec<Option<AliveBitSet>>,
+        include_soft_deleted: bool,
     ) -> crate::Result<IndexMerger> {
         let mut readers = vec![];
         for (segment, new_alive_bitset_opt) in segments.iter().zip(alive_bitset_opt) {
             if segment.meta().num_docs() > 0 {
-                let reader =
+                let mut reader =
                     SegmentReader::open_with_custom_alive_set(segment, new_alive_bitset_opt)?;
+                if include_soft_deleted {
+                    reader = reader.include_soft_deleted();
+                }
                 readers.push(reader);
             }
         }
@@ -239,6 +256,72 @@ impl IndexMerger {
         Ok(())
     }
 
+    fn write_f32_vectors(
+        &self,
+        f32_vectors_wrt: &mut CompositeWrite<WritePtr>,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        debug_time!("write-f32-vectors");
+        let mut offsets = Vec::with_capacity(self.max_doc as usize + 1);
+        for (field, field_entry) in self.schema.fields() {
+            let FieldType::F32Vector(options) = field_entry.field_type() else {
+                continue;
+            };
+            let columns: Vec<F32VectorColumn> = self
+                .readers
+                .iter()
+                .map(|reader| reader.fast_fields().f32_vector(field_entry.name()))
+                .collect::<crate::Result<_>>()?;
+            offsets.clear();
+            offsets.push(0u64);
+            let mut num_vals = 0u64;
+            for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                let column = &columns[old_doc_addr.segment_ord as usize];
+                num_vals += column.num_vals(old_doc_addr.doc_id) as u64;
+                offsets.push(num_vals);
+            }
+            serialize_f32_vector_field(
+                f32_vectors_wrt.for_field(field),
+                options.dimensions(),
+                &offsets,
+                |wrt| {
+                    for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                        let column = &columns[old_doc_addr.segment_ord as usize];
+                        wrt.write_all(column.value_bytes(old_doc_addr.doc_id))?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the soft deletes of the merged segment.
+    ///
+    /// Only the soft deleted documents which are merged are included, which requires the merger
+    /// to have been opened with `open_including_soft_deleted`.
+    pub(crate) fn merged_soft_deletes(&self) -> crate::Result<SoftDeletes> {
+        let mut merged_soft_deletes = SoftDeletes::default();
+        if self
+            .readers
+            .iter()
+            .all(|reader| reader.soft_deletes().is_none())
+        {
+            return Ok(merged_soft_deletes);
+        }
+        let doc_id_mapping = self.get_doc_id_from_concatenated_data()?;
+        for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+            let reader = &self.readers[old_doc_addr.segment_ord as usize];
+            if let Some(deleted_at) = reader
+                .soft_deletes()
+                .and_then(|soft_deletes| soft_deletes.deleted_at(old_doc_addr.doc_id))
+            {
+                merged_soft_deletes.insert(new_doc_id as DocId, deleted_at);
+            }
+        }
+        Ok(merged_soft_deletes)
+    }
+
     /// Creates a mapping if the segments are stacked. this is helpful to merge codelines between
     /// index sorting and the others
     pub(crate) fn get_doc_id_from_concatenated_data(&self) -> crate::Result<SegmentDocIdMapping> {
@@ -293,6 +376,7 @@ impl IndexMerger {
     ) -> crate::Result<()> {
         debug_time!("write-postings-for-field");
         let mut positions_buffer: Vec<u32> = Vec::with_capacity(1_000);
+        let mut offsets_buffer: Vec<u32> = Vec::new();
         let mut delta_computer = DeltaComputer::new();
 
         let mut max_term_ords: Vec<TermOrdinal> = Vec::new();
@@ -442,17 +526,24 @@ impl IndexMerger {
                         // there is at least one document.
                         let term_freq = if has_term_freq {
                             segment_postings.positions(&mut positions_buffer);
+                            segment_postings.offsets(&mut offsets_buffer);
                             segment_postings.term_freq()
                         } else {
                             // The positions_buffer may contain positions from the previous term
                             // Existence of positions depend on the value type in JSON fields.
                             // https://github.com/quickwit-oss/tantivy/issues/2283
                             positions_buffer.clear();
+                            offsets_buffer.clear();
                             0u32
                         };
 
                         let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                        field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                        field_serializer.write_doc_with_offsets(
+                            remapped_doc_id,
+                            term_freq,
+                            delta_positions,
+                            &offsets_buffer,
+                        );
                     }
 
                     doc = segment_postings.advance();
@@ -492,7 +583,13 @@ impl IndexMerger {
 
         for reader in &self.readers {
             let store_reader = reader.get_store_reader(1)?;
-            if reader.has_deletes()
+            if store_writer.has_dictionaries() {
+                // The documents reference the dictionaries of their segment, they are stored
+                // again to build the dictionaries of the merged segment.
+                for doc_res in store_reader.iter::<TantivyDocument>(reader.alive_bitset()) {
+                    store_writer.store(&doc_res?, &self.schema)?;
+                }
+            } else if reader.has_deletes()
                     // If there is not enough data in the store, we avoid stacking in order to
                     // avoid creating many small blocks in the doc store. Once we have 5 full blocks,
                     // we start stacking. In the worst case 2/7 of the blocks would be very small.
@@ -545,6 +642,9 @@ impl IndexMerger {
         debug!("write-storagefields");
         self.write_storable_fields(serializer.get_store_writer())?;
         debug!("write-fastfields");
+        if let Some(f32_vectors_write) = serializer.get_f32_vectors_write() {
+            self.write_f32_vectors(f32_vectors_write, &doc_id_mapping)?;
+        }
         self.write_fast_fields(serializer.get_fast_field_write(), doc_id_mapping)?;
 
         debug!("close-serializer");
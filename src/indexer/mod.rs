@@ -8,19 +8,27 @@
 pub(crate) mod delete_queue;
 pub(crate) mod path_to_unordered_id;
 
+mod bulk_loader;
+mod commit_group;
+mod commit_summary;
+mod discarded_values;
 pub(crate) mod doc_id_mapping;
 mod doc_opstamp_mapping;
 mod flat_map_with_buffer;
 pub(crate) mod index_writer;
 pub(crate) mod index_writer_status;
+pub(crate) mod indexing_metrics;
 pub(crate) mod indexing_term;
 mod log_merge_policy;
+mod merge_doc_id_mapping;
 mod merge_index_test;
 mod merge_operation;
+mod merge_plan;
 pub(crate) mod merge_policy;
 pub(crate) mod merger;
 pub(crate) mod operation;
 pub(crate) mod prepared_commit;
+mod reindex;
 mod segment_entry;
 mod segment_manager;
 mod segment_register;
@@ -28,22 +36,38 @@ pub(crate) mod segment_serializer;
 pub(crate) mod segment_updater;
 pub(crate) mod segment_writer;
 pub(crate) mod single_segment_index_writer;
+mod soft_delete;
 mod stamper;
+mod ttl;
 
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
+pub use self::bulk_loader::BulkLoader;
+pub use self::commit_group::CommitGroup;
+pub use self::commit_summary::CommitSummary;
+pub use self::discarded_values::{DiscardedValues, DiscardedValuesCallback};
 pub use self::index_writer::{advance_deletes, IndexWriter, IndexWriterOptions};
+pub use self::indexing_metrics::{
+    DurationHistogram, IndexingMetrics, IndexingMetricsSnapshot, IndexingStage, StageMetrics,
+};
 pub use self::log_merge_policy::LogMergePolicy;
-pub use self::merge_operation::MergeOperation;
+pub use self::merge_doc_id_mapping::{MergeDocIdMapping, MergeDocIdMappingCallback};
+pub use self::merge_operation::{MergeOperation, MergeOutcome};
+pub use self::merge_plan::{MergePlan, PlannedMerge};
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
-pub use self::operation::{AddOperation, DeleteOperation, UserOperation};
+pub use self::operation::{AddOperation, DeleteKind, DeleteOperation, UserOperation};
 pub use self::prepared_commit::PreparedCommit;
+pub use self::reindex::{
+    reindex, FieldMapping, ReindexCheckpoint, ReindexOptions, ReindexProgress,
+};
 pub use self::segment_entry::SegmentEntry;
 pub(crate) use self::segment_serializer::SegmentSerializer;
 pub use self::segment_updater::{merge_filtered_segments, merge_indices};
 pub use self::segment_writer::SegmentWriter;
 pub use self::single_segment_index_writer::SingleSegmentIndexWriter;
+pub use self::soft_delete::SoftDeleteOptions;
+pub use self::ttl::TtlOptions;
 
 /// Alias for the default merge policy, which is the `LogMergePolicy`.
 pub type DefaultMergePolicy = LogMergePolicy;
@@ -517,7 +541,7 @@ mod tests_mmap {
                 stored: true,
                 fast_size: Some(1u64.into()),
                 term_dictionary_size: Some(1u64.into()),
-                postings_size: Some(1u64.into()),
+                postings_size: Some(0u64.into()),
                 positions_size: Some(1u64.into()),
             },
             FieldMetadata {
@@ -526,7 +550,7 @@ mod tests_mmap {
                 stored: true,
                 fast_size: Some(1u64.into()),
                 term_dictionary_size: Some(1u64.into()),
-                postings_size: Some(1u64.into()),
+                postings_size: Some(0u64.into()),
                 positions_size: Some(1u64.into()),
             },
             FieldMetadata {
@@ -535,7 +559,7 @@ mod tests_mmap {
                 typ: Type::I64,
                 fast_size: Some(1u64.into()),
                 term_dictionary_size: Some(1u64.into()),
-                postings_size: Some(1u64.into()),
+                postings_size: Some(0u64.into()),
                 positions_size: Some(1u64.into()),
             },
             FieldMetadata {
@@ -544,7 +568,7 @@ mod tests_mmap {
                 stored: true,
                 fast_size: Some(1u64.into()),
                 term_dictionary_size: Some(1u64.into()),
-                postings_size: Some(1u64.into()),
+                postings_size: Some(0u64.into()),
                 positions_size: Some(1u64.into()),
             },
             FieldMetadata {
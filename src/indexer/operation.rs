@@ -1,7 +1,7 @@
 use crate::query::Weight;
 use crate::schema::document::Document;
 use crate::schema::{TantivyDocument, Term};
-use crate::Opstamp;
+use crate::{DateTime, Opstamp};
 
 /// Timestamped Delete operation.
 pub struct DeleteOperation {
@@ -11,6 +11,23 @@ pub struct DeleteOperation {
     pub opstamp: Opstamp,
     /// Weight is used to define the set of documents to be deleted.
     pub target: Box<dyn Weight>,
+    /// Defines how the documents are deleted.
+    pub kind: DeleteKind,
+}
+
+/// Defines how a [`DeleteOperation`] deletes the documents matching its target.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DeleteKind {
+    /// The documents are deleted for good.
+    #[default]
+    Hard,
+    /// The documents are soft deleted, and remain retrievable until their retention expires.
+    Soft {
+        /// Time of the deletion, from which the retention is counted.
+        deleted_at: DateTime,
+    },
+    /// The soft deleted documents are deleted for good.
+    PurgeSoftDeleted,
 }
 
 /// Timestamped Add operation.
@@ -35,8 +35,20 @@ impl<'a, D: Document> PreparedCommit<'a, D> {
 
     /// Proceeds to commit.
     /// See `.commit_future()`.
+    ///
+    /// Once done, the number of documents added and deleted by the commit is available via
+    /// [`IndexWriter::last_commit_summary`], and the indexing metrics of the commit via
+    /// [`IndexWriter::last_commit_metrics`].
     pub fn commit(self) -> crate::Result<Opstamp> {
-        self.commit_future().wait()
+        let PreparedCommit {
+            index_writer,
+            payload,
+            opstamp,
+        } = self;
+        let opstamp = schedule_commit(index_writer, opstamp, payload).wait()?;
+        index_writer.set_last_commit_summary(opstamp);
+        index_writer.set_last_commit_metrics();
+        Ok(opstamp)
     }
 
     /// Proceeds to commit.
@@ -45,9 +57,17 @@ impl<'a, D: Document> PreparedCommit<'a, D> {
     /// this operation is not at all really light.
     /// At this point deletes have not been flushed yet.
     pub fn commit_future(self) -> FutureResult<Opstamp> {
-        info!("committing {}", self.opstamp);
-        self.index_writer
-            .segment_updater()
-            .schedule_commit(self.opstamp, self.payload)
+        schedule_commit(self.index_writer, self.opstamp, self.payload)
     }
 }
+
+fn schedule_commit<D: Document>(
+    index_writer: &IndexWriter<D>,
+    opstamp: Opstamp,
+    payload: Option<String>,
+) -> FutureResult<Opstamp> {
+    info!("committing {opstamp}");
+    index_writer
+        .segment_updater()
+        .schedule_commit(opstamp, payload)
+}
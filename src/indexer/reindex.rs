@@ -0,0 +1,492 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Index, SegmentId, SegmentReader};
+use crate::schema::{Field, Schema};
+use crate::{DocId, IndexWriter, TantivyDocument, TantivyError};
+
+const DEFAULT_COMMIT_EVERY_NUM_DOCS: u64 = 100_000;
+
+/// Maps the fields of the source schema to the fields of the destination schema of a
+/// [`reindex`].
+///
+/// By default, a field is mapped to the field of the destination schema with the same name.
+#[derive(Clone, Debug, Default)]
+pub struct FieldMapping {
+    renamed_fields: HashMap<String, String>,
+    dropped_fields: HashSet<String>,
+}
+
+impl FieldMapping {
+    /// Maps the source field `src_field_name` to the destination field `dst_field_name`.
+    pub fn rename(mut self, src_field_name: &str, dst_field_name: &str) -> Self {
+        self.renamed_fields
+            .insert(src_field_name.to_string(), dst_field_name.to_string());
+        self
+    }
+
+    /// Drops the values of the source field `src_field_name`.
+    pub fn drop_field(mut self, src_field_name: &str) -> Self {
+        self.dropped_fields.insert(src_field_name.to_string());
+        self
+    }
+
+    /// Returns the destination field of each stored field of the source schema, indexed by
+    /// field id, or `None` for the fields which are dropped.
+    fn resolve(
+        &self,
+        src_schema: &Schema,
+        dst_schema: &Schema,
+    ) -> crate::Result<Vec<Option<Field>>> {
+        for src_field_name in self.renamed_fields.keys().chain(&self.dropped_fields) {
+            src_schema.get_field(src_field_name)?;
+        }
+        let mut dst_fields = Vec::new();
+        for (_, src_field_entry) in src_schema.fields() {
+            let src_field_name = src_field_entry.name();
+            if !src_field_entry.is_stored() || self.dropped_fields.contains(src_field_name) {
+                dst_fields.push(None);
+                continue;
+            }
+            let dst_field_name = self
+                .renamed_fields
+                .get(src_field_name)
+                .map(String::as_str)
+                .unwrap_or(src_field_name);
+            let dst_field = dst_schema.get_field(dst_field_name)?;
+            let src_type = src_field_entry.field_type().value_type();
+            let dst_type = dst_schema
+                .get_field_entry(dst_field)
+                .field_type()
+                .value_type();
+            if src_type != dst_type {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {src_field_name:?} of type {src_type:?} cannot be reindexed into field \
+                     {dst_field_name:?} of type {dst_type:?}"
+                )));
+            }
+            dst_fields.push(Some(dst_field));
+        }
+        Ok(dst_fields)
+    }
+}
+
+/// Position of a [`reindex`] in the source index: the next document to reindex.
+///
+/// The checkpoint is saved as the payload of the commits of the destination index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReindexCheckpoint {
+    /// Source segment being reindexed.
+    pub segment_id: SegmentId,
+    /// Next document of the segment to reindex.
+    pub doc_id: DocId,
+}
+
+impl ReindexCheckpoint {
+    /// Returns the checkpoint saved by the last commit of the destination index, or `None` if
+    /// the last commit has no payload.
+    pub fn from_index(dst_index: &Index) -> crate::Result<Option<ReindexCheckpoint>> {
+        let Some(payload) = dst_index.load_metas()?.payload else {
+            return Ok(None);
+        };
+        let checkpoint = serde_json::from_str(&payload).map_err(|err| {
+            TantivyError::InvalidArgument(format!(
+                "The payload of the last commit is not a reindex checkpoint: {err}"
+            ))
+        })?;
+        Ok(Some(checkpoint))
+    }
+}
+
+/// Progress of a [`reindex`], reported after each commit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReindexProgress {
+    /// Checkpoint saved by the commit.
+    pub checkpoint: ReindexCheckpoint,
+    /// Ordinal of the source segment being reindexed.
+    pub segment_ord: usize,
+    /// Number of source segments.
+    pub num_segments: usize,
+    /// Number of documents reindexed so far by this call to [`reindex`].
+    pub num_docs: u64,
+}
+
+type Transform<'a> = Box<dyn FnMut(&mut TantivyDocument) + 'a>;
+type ProgressCallback<'a> = Box<dyn FnMut(&ReindexProgress) + 'a>;
+
+/// Options of a [`reindex`].
+pub struct ReindexOptions<'a> {
+    transform: Option<Transform<'a>>,
+    progress_callback: Option<ProgressCallback<'a>>,
+    resume_from: Option<ReindexCheckpoint>,
+    commit_every_num_docs: u64,
+}
+
+impl Default for ReindexOptions<'_> {
+    fn default() -> Self {
+        ReindexOptions {
+            transform: None,
+            progress_callback: None,
+            resume_from: None,
+            commit_every_num_docs: DEFAULT_COMMIT_EVERY_NUM_DOCS,
+        }
+    }
+}
+
+impl<'a> ReindexOptions<'a> {
+    /// Transforms each document, after its fields are mapped to the destination schema.
+    pub fn transform(mut self, transform: impl FnMut(&mut TantivyDocument) + 'a) -> Self {
+        self.transform = Some(Box::new(transform));
+        self
+    }
+
+    /// Calls `progress_callback` after each commit.
+    pub fn progress_callback(
+        mut self,
+        progress_callback: impl FnMut(&ReindexProgress) + 'a,
+    ) -> Self {
+        self.progress_callback = Some(Box::new(progress_callback));
+        self
+    }
+
+    /// Resumes the reindexing from a checkpoint, usually loaded with
+    /// [`ReindexCheckpoint::from_index`].
+    pub fn resume_from(mut self, checkpoint: ReindexCheckpoint) -> Self {
+        self.resume_from = Some(checkpoint);
+        self
+    }
+
+    /// Commits the destination index every `commit_every_num_docs` documents, in addition to
+    /// the commit at the end of each source segment.
+    pub fn commit_every_num_docs(mut self, commit_every_num_docs: u64) -> Self {
+        self.commit_every_num_docs = commit_every_num_docs.max(1);
+        self
+    }
+}
+
+/// Reindexes the stored documents of `src_index` with `dst_index_writer`, e.g. to apply a
+/// schema change.
+///
+/// Only the stored fields can be reindexed. The documents are streamed from the doc store of
+/// each segment, in the order of the segment ids, skipping the deleted documents. The fields
+/// are mapped to the destination schema following `field_mapping`: a stored field of the
+/// source schema which is not dropped needs a destination field of the same type.
+///
+/// The destination index is committed at the end of each source segment and every
+/// [`ReindexOptions::commit_every_num_docs`] documents, with a [`ReindexCheckpoint`] as payload.
+/// An interrupted reindexing can be resumed from the checkpoint of the last commit, as long as
+/// the segments of the source index did not change.
+///
+/// Returns the number of documents reindexed.
+pub fn reindex(
+    src_index: &Index,
+    dst_index_writer: &mut IndexWriter,
+    field_mapping: &FieldMapping,
+    mut options: ReindexOptions<'_>,
+) -> crate::Result<u64> {
+    let dst_schema = dst_index_writer.index().schema();
+    let dst_fields = field_mapping.resolve(&src_index.schema(), &dst_schema)?;
+    let mut segments = src_index.searchable_segments()?;
+    segments.sort_by_key(|segment| segment.id());
+    let mut start_doc_id = 0;
+    let mut start_segment_ord = 0;
+    if let Some(checkpoint) = options.resume_from {
+        start_segment_ord = segments
+            .iter()
+            .position(|segment| segment.id() == checkpoint.segment_id)
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "Cannot resume reindexing from segment {}, which is not in the source index",
+                    checkpoint.segment_id.short_uuid_string()
+                ))
+            })?;
+        start_doc_id = checkpoint.doc_id;
+    }
+    let num_segments = segments.len();
+    let mut num_docs = 0u64;
+    for (segment_ord, segment) in segments.iter().enumerate().skip(start_segment_ord) {
+        let segment_reader = SegmentReader::open(segment)?;
+        let store_reader = segment_reader.get_store_reader(1)?;
+        let alive_bitset = segment_reader.alive_bitset();
+        let doc_ids = (0..segment_reader.max_doc()).filter(|&doc_id| {
+            alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc_id))
+        });
+        let docs = store_reader.iter::<TantivyDocument>(alive_bitset);
+        let mut num_docs_since_commit = 0u64;
+        for (doc_id, doc) in doc_ids.zip(docs) {
+            // Documents before the checkpoint are still deserialized by the store iterator.
+            let doc = doc?;
+            if segment_ord == start_segment_ord && doc_id < start_doc_id {
+                continue;
+            }
+            let mut dst_doc = TantivyDocument::with_capacity(doc.node_data.len(), doc.len());
+            for (field, value) in doc.field_values() {
+                if let Some(dst_field) = dst_fields[field.field_id() as usize] {
+                    dst_doc.add_field_value(dst_field, value);
+                }
+            }
+            if let Some(transform) = &mut options.transform {
+                transform(&mut dst_doc);
+            }
+            dst_index_writer.add_document(dst_doc)?;
+            num_docs += 1;
+            num_docs_since_commit += 1;
+            if num_docs_since_commit == options.commit_every_num_docs {
+                num_docs_since_commit = 0;
+                let checkpoint = ReindexCheckpoint {
+                    segment_id: segment.id(),
+                    doc_id: doc_id + 1,
+                };
+                commit_checkpoint(dst_index_writer, checkpoint)?;
+                report_progress(
+                    &mut options,
+                    checkpoint,
+                    segment_ord,
+                    num_segments,
+                    num_docs,
+                );
+            }
+        }
+        let checkpoint = ReindexCheckpoint {
+            segment_id: segment.id(),
+            doc_id: segment_reader.max_doc(),
+        };
+        commit_checkpoint(dst_index_writer, checkpoint)?;
+        report_progress(
+            &mut options,
+            checkpoint,
+            segment_ord,
+            num_segments,
+            num_docs,
+        );
+    }
+    Ok(num_docs)
+}
+
+fn commit_checkpoint(
+    dst_index_writer: &mut IndexWriter,
+    checkpoint: ReindexCheckpoint,
+) -> crate::Result<()> {
+    let payload = serde_json::to_string(&checkpoint).expect("serializing a checkpoint cannot fail");
+    let mut prepared_commit = dst_index_writer.prepare_commit()?;
+    prepared_commit.set_payload(&payload);
+    prepared_commit.commit()?;
+    Ok(())
+}
+
+fn report_progress(
+    options: &mut ReindexOptions,
+    checkpoint: ReindexCheckpoint,
+    segment_ord: usize,
+    num_segments: usize,
+    num_docs: u64,
+) {
+    if let Some(progress_callback) = &mut options.progress_callback {
+        progress_callback(&ReindexProgress {
+            checkpoint,
+            segment_ord,
+            num_segments,
+            num_docs,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::{reindex, FieldMapping, ReindexCheckpoint, ReindexOptions, ReindexProgress};
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{Document, IndexRecordOption, Schema, INDEXED, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, TantivyError, Term};
+
+    fn src_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | STORED);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let tmp = schema_builder.add_text_field("tmp", STRING | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for segment_ord in 0..3u64 {
+            for doc_ord in 0..4u64 {
+                let doc_id = segment_ord * 4 + doc_ord;
+                index_writer.add_document(doc!(
+                    id => doc_id,
+                    title => format!("Hello World {doc_id}"),
+                    body => format!("body of {doc_id}"),
+                    tmp => "tmp",
+                ))?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_u64(id, 5));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn dst_index() -> Index {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("id", INDEXED | STORED);
+        // The title is not tokenized anymore.
+        schema_builder.add_text_field("title", STRING | STORED);
+        schema_builder.add_text_field("content", TEXT | STORED);
+        schema_builder.add_text_field("source", STRING | STORED);
+        Index::create_in_ram(schema_builder.build())
+    }
+
+    fn field_mapping() -> FieldMapping {
+        FieldMapping::default()
+            .rename("body", "content")
+            .drop_field("tmp")
+    }
+
+    fn count(index: &Index, field_name: &str, text: &str) -> crate::Result<usize> {
+        let field = index.schema().get_field(field_name)?;
+        let query = TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::Basic);
+        index.reader()?.searcher().search(&query, &Count)
+    }
+
+    #[test]
+    fn test_reindex() -> crate::Result<()> {
+        let src_index = src_index()?;
+        let dst_index = dst_index();
+        let source = dst_index.schema().get_field("source")?;
+        let mut dst_index_writer: IndexWriter = dst_index.writer_for_tests()?;
+        let progress: RefCell<Vec<ReindexProgress>> = RefCell::default();
+        let options = ReindexOptions::default()
+            .transform(|doc: &mut TantivyDocument| doc.add_text(source, "reindex"))
+            .progress_callback(|reindex_progress: &ReindexProgress| {
+                progress.borrow_mut().push(*reindex_progress)
+            });
+        let num_docs = reindex(&src_index, &mut dst_index_writer, &field_mapping(), options)?;
+        assert_eq!(num_docs, 11);
+
+        assert_eq!(count(&src_index, "title", "hello")?, 11);
+        assert_eq!(count(&src_index, "title", "Hello World 3")?, 0);
+        assert_eq!(count(&dst_index, "title", "hello")?, 0);
+        assert_eq!(count(&dst_index, "title", "Hello World 3")?, 1);
+        assert_eq!(count(&dst_index, "title", "Hello World 5")?, 0);
+        assert_eq!(count(&dst_index, "content", "body")?, 11);
+        assert_eq!(count(&dst_index, "source", "reindex")?, 11);
+
+        let searcher = dst_index.reader()?.searcher();
+        let title_query = TermQuery::new(
+            Term::from_field_text(dst_index.schema().get_field("title")?, "Hello World 3"),
+            IndexRecordOption::Basic,
+        );
+        let doc_address = searcher
+            .search(&title_query, &crate::collector::DocSetCollector)?
+            .into_iter()
+            .next()
+            .unwrap();
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        assert_eq!(
+            doc.to_json(&dst_index.schema()),
+            r#"{"content":["body of 3"],"id":[3],"source":["reindex"],"title":["Hello World 3"]}"#
+        );
+
+        let progress = progress.into_inner();
+        assert_eq!(
+            progress
+                .iter()
+                .map(|reindex_progress| (
+                    reindex_progress.segment_ord,
+                    reindex_progress.num_segments
+                ))
+                .collect::<Vec<_>>(),
+            [(0, 3), (1, 3), (2, 3)]
+        );
+        assert!(progress
+            .windows(2)
+            .all(|window| window[0].num_docs < window[1].num_docs));
+        assert_eq!(progress[2].num_docs, 11);
+        assert_eq!(
+            ReindexCheckpoint::from_index(&dst_index)?,
+            Some(progress[2].checkpoint)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_resume() -> crate::Result<()> {
+        let src_index = src_index()?;
+        for num_docs_before_interruption in 0..11 {
+            let dst_index = dst_index();
+            let mut dst_index_writer: IndexWriter = dst_index.writer_for_tests()?;
+            let mut num_docs = 0;
+            let options = ReindexOptions::default()
+                .commit_every_num_docs(3)
+                .transform(|_doc: &mut TantivyDocument| {
+                    if num_docs == num_docs_before_interruption {
+                        panic!("interrupted");
+                    }
+                    num_docs += 1;
+                });
+            let interrupted = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                reindex(&src_index, &mut dst_index_writer, &field_mapping(), options)
+            }));
+            assert!(interrupted.is_err());
+            // The documents added since the last commit are lost.
+            drop(dst_index_writer);
+
+            let mut dst_index_writer: IndexWriter = dst_index.writer_for_tests()?;
+            let mut options = ReindexOptions::default();
+            if let Some(checkpoint) = ReindexCheckpoint::from_index(&dst_index)? {
+                options = options.resume_from(checkpoint);
+            }
+            reindex(&src_index, &mut dst_index_writer, &field_mapping(), options)?;
+            assert_eq!(dst_index.reader()?.searcher().num_docs(), 11);
+            assert_eq!(count(&dst_index, "content", "body")?, 11);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_invalid_field_mapping() -> crate::Result<()> {
+        let src_index = src_index()?;
+        let dst_index = dst_index();
+        let mut dst_index_writer: IndexWriter = dst_index.writer_for_tests()?;
+        let mut reindex_with = |field_mapping: FieldMapping| {
+            reindex(
+                &src_index,
+                &mut dst_index_writer,
+                &field_mapping,
+                ReindexOptions::default(),
+            )
+        };
+        // `body` has no destination field, and `tmp` is not dropped.
+        assert!(matches!(
+            reindex_with(FieldMapping::default().drop_field("tmp")),
+            Err(TantivyError::FieldNotFound(field_name)) if field_name == "body"
+        ));
+        assert!(matches!(
+            reindex_with(FieldMapping::default().rename("body", "content")),
+            Err(TantivyError::FieldNotFound(field_name)) if field_name == "tmp"
+        ));
+        assert!(matches!(
+            reindex_with(field_mapping().rename("missing", "content")),
+            Err(TantivyError::FieldNotFound(field_name)) if field_name == "missing"
+        ));
+        assert!(matches!(
+            reindex_with(field_mapping().rename("id", "title")),
+            Err(TantivyError::SchemaError(_))
+        ));
+        let checkpoint = ReindexCheckpoint {
+            segment_id: crate::index::SegmentId::generate_random(),
+            doc_id: 0,
+        };
+        assert!(matches!(
+            reindex(
+                &src_index,
+                &mut dst_index_writer,
+                &field_mapping(),
+                ReindexOptions::default().resume_from(checkpoint),
+            ),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt;
 
 use common::BitSet;
 
-use crate::index::{SegmentId, SegmentMeta};
+use crate::index::{SegmentId, SegmentMeta, SoftDeletes};
 use crate::indexer::delete_queue::DeleteCursor;
 
 /// A segment entry describes the state of
@@ -11,12 +11,14 @@ use crate::indexer::delete_queue::DeleteCursor;
 /// In addition to segment `meta`,
 /// it contains a few transient states
 /// - `alive_bitset` is a bitset describing documents that were alive during the commit itself.
+/// - `soft_deletes` are the documents that were soft deleted during the commit itself.
 /// - `delete_cursor` is the position in the delete queue. Deletes happening before the cursor are
 ///   reflected either in the .del file or in the `alive_bitset`.
 #[derive(Clone)]
 pub struct SegmentEntry {
     meta: SegmentMeta,
     alive_bitset: Option<BitSet>,
+    soft_deletes: Option<SoftDeletes>,
     delete_cursor: DeleteCursor,
 }
 
@@ -30,10 +32,18 @@ impl SegmentEntry {
         SegmentEntry {
             meta: segment_meta,
             alive_bitset,
+            soft_deletes: None,
             delete_cursor,
         }
     }
 
+    /// Sets the documents soft deleted during the commit.
+    #[must_use]
+    pub fn with_soft_deletes(mut self, soft_deletes: Option<SoftDeletes>) -> SegmentEntry {
+        self.soft_deletes = soft_deletes;
+        self
+    }
+
     /// Return a reference to the segment entry deleted bitset.
     ///
     /// `DocId` in this bitset are flagged as deleted.
@@ -41,6 +51,11 @@ impl SegmentEntry {
         self.alive_bitset.as_ref()
     }
 
+    /// Return a reference to the documents soft deleted during the commit.
+    pub fn soft_deletes(&self) -> Option<&SoftDeletes> {
+        self.soft_deletes.as_ref()
+    }
+
     /// Set the `SegmentMeta` for this segment.
     pub fn set_meta(&mut self, segment_meta: SegmentMeta) {
         self.meta = segment_meta;
@@ -1,10 +1,10 @@
 use common::TerminatingWrite;
 
-use crate::directory::WritePtr;
+use crate::directory::{CompositeWrite, WritePtr};
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::index::{Segment, SegmentComponent};
 use crate::postings::InvertedIndexSerializer;
-use crate::store::StoreWriter;
+use crate::store::{StoreDictionariesWriter, StoreWriter};
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
@@ -12,6 +12,7 @@ pub struct SegmentSerializer {
     segment: Segment,
     pub(crate) store_writer: StoreWriter,
     fast_field_write: WritePtr,
+    f32_vectors_write: Option<CompositeWrite<WritePtr>>,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
     postings_serializer: InvertedIndexSerializer,
 }
@@ -20,7 +21,7 @@ impl SegmentSerializer {
     /// Creates a new `SegmentSerializer`.
     pub fn for_segment(mut segment: Segment) -> crate::Result<SegmentSerializer> {
         let settings = segment.index().settings().clone();
-        let store_writer = {
+        let mut store_writer = {
             let store_write = segment.open_write(SegmentComponent::Store)?;
             StoreWriter::new(
                 store_write,
@@ -29,9 +30,27 @@ impl SegmentSerializer {
                 settings.docstore_compress_dedicated_thread,
             )?
         };
+        let schema = segment.schema();
+        if StoreDictionariesWriter::has_dictionary_encoded_fields(&schema) {
+            let dictionaries_write = segment.open_write(SegmentComponent::StoreDictionaries)?;
+            store_writer.set_dictionaries(StoreDictionariesWriter::for_schema(
+                &schema,
+                dictionaries_write,
+            ));
+        }
 
         let fast_field_write = segment.open_write(SegmentComponent::FastFields)?;
 
+        let has_f32_vector_fields = schema
+            .fields()
+            .any(|(_, field_entry)| field_entry.field_type().is_f32_vector());
+        let f32_vectors_write = if has_f32_vector_fields {
+            let write = segment.open_write(SegmentComponent::F32Vectors)?;
+            Some(CompositeWrite::wrap(write))
+        } else {
+            None
+        };
+
         let fieldnorms_write = segment.open_write(SegmentComponent::FieldNorms)?;
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
@@ -40,6 +59,7 @@ impl SegmentSerializer {
             segment,
             store_writer,
             fast_field_write,
+            f32_vectors_write,
             fieldnorms_serializer: Some(fieldnorms_serializer),
             postings_serializer,
         })
@@ -64,6 +84,13 @@ impl SegmentSerializer {
         &mut self.fast_field_write
     }
 
+    /// Accessor to the writer of the f32 vectors.
+    ///
+    /// Returns `None` if the schema has no f32 vector field.
+    pub(crate) fn get_f32_vectors_write(&mut self) -> Option<&mut CompositeWrite<WritePtr>> {
+        self.f32_vectors_write.as_mut()
+    }
+
     /// Extract the field norm serializer.
     ///
     /// Note the fieldnorms serializer can only be extracted once.
@@ -82,6 +109,9 @@ impl SegmentSerializer {
             fieldnorms_serializer.close()?;
         }
         self.fast_field_write.terminate()?;
+        if let Some(f32_vectors_write) = self.f32_vectors_write {
+            f32_vectors_write.close()?;
+        }
         self.postings_serializer.close()?;
         self.store_writer.close()?;
         Ok(())
@@ -1,30 +1,38 @@
 use std::any::Any;
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use super::segment_manager::SegmentManager;
 use crate::core::META_FILEPATH;
-use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
-use crate::fastfield::AliveBitSet;
-use crate::index::{Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta};
+use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult, TerminatingWrite};
+use crate::fastfield::{intersect_alive_bitsets, AliveBitSet};
+use crate::index::{
+    Index, IndexMeta, IndexSettings, Segment, SegmentComponent, SegmentId, SegmentMeta, SoftDeletes,
+};
+use crate::indexer::commit_summary::CommitStats;
 use crate::indexer::delete_queue::DeleteCursor;
-use crate::indexer::index_writer::advance_deletes;
-use crate::indexer::merge_operation::MergeOperationInventory;
+use crate::indexer::index_writer::{advance_deletes, advance_deletes_with_stats};
+use crate::indexer::indexing_metrics::{IndexingMetrics, IndexingStage};
+use crate::indexer::merge_doc_id_mapping::{MergeDocIdMapping, MergeDocIdMappingCallback};
+use crate::indexer::merge_operation::{MergeOperationInventory, MergeOutcome};
+use crate::indexer::merge_plan::{MergePlan, PlannedMerge};
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
+use crate::indexer::ttl::TtlOptions;
 use crate::indexer::{
     DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
-    SegmentSerializer,
+    SegmentSerializer, SoftDeleteOptions,
 };
-use crate::{FutureResult, Opstamp, TantivyError};
+use crate::{DocId, FutureResult, Opstamp, TantivyError};
 
 const PANIC_CAUGHT: &str = "Panic caught in merge thread";
 
@@ -84,17 +92,35 @@ fn garbage_collect_files(
 
 /// Merges a list of segments the list of segment givens in the `segment_entries`.
 /// This function happens in the calling thread and is computationally expensive.
+///
+/// If `ttl_options` is set, expired documents are not written in the merged segment.
+/// Their number is returned alongside the merged segment.
+///
+/// Soft deleted documents are written in the merged segment, and remain soft deleted there,
+/// unless `soft_delete_options` is set and their retention expired.
+///
+/// If `with_doc_id_mapping` is set, the mapping of the old doc ids to the doc ids of the
+/// merged segment is returned alongside the merged segment.
 fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
-) -> crate::Result<Option<SegmentEntry>> {
+    ttl_options: Option<&TtlOptions>,
+    soft_delete_options: Option<&SoftDeleteOptions>,
+    with_doc_id_mapping: bool,
+) -> crate::Result<(Option<SegmentEntry>, Option<MergeDocIdMapping>, u64)> {
+    let source_segments: Vec<(SegmentId, DocId)> = segment_entries
+        .iter()
+        .map(|segment_entry| (segment_entry.segment_id(), segment_entry.meta().max_doc()))
+        .collect();
     let num_docs = segment_entries
         .iter()
         .map(|segment| segment.meta().num_docs() as u64)
         .sum::<u64>();
     if num_docs == 0 {
-        return Ok(None);
+        let doc_id_mapping_opt =
+            with_doc_id_mapping.then(|| MergeDocIdMapping::new(&source_segments, None, Vec::new()));
+        return Ok((None, doc_id_mapping_opt, 0));
     }
 
     // first we need to apply deletes to our segment.
@@ -113,18 +139,102 @@ fn merge(
         .map(|segment_entry| index.segment(segment_entry.meta().clone()))
         .collect();
 
+    // Documents expired by the TTL are filtered out, as if they had been deleted.
+    let mut alive_bitsets: Vec<Option<AliveBitSet>> = segments.iter().map(|_| None).collect();
+    let mut num_expired: u64 = 0;
+    if let Some(ttl_options) = ttl_options {
+        let threshold = ttl_options.expiration_threshold();
+        for (segment, alive_bitset) in segments.iter().zip(alive_bitsets.iter_mut()) {
+            if let Some((expired_alive_bitset, num_segment_expired)) =
+                ttl_options.compute_alive_bitset(segment, threshold)?
+            {
+                *alive_bitset = Some(expired_alive_bitset);
+                num_expired += num_segment_expired as u64;
+            }
+        }
+    }
+
+    // Soft deleted documents whose retention expired are filtered out as well.
+    if let Some(soft_delete_options) = soft_delete_options {
+        let threshold = soft_delete_options.expiration_threshold();
+        for (segment, alive_bitset) in segments.iter().zip(alive_bitsets.iter_mut()) {
+            if !segment.meta().has_soft_deletes() {
+                continue;
+            }
+            let soft_deletes_data = segment.open_read(SegmentComponent::SoftDelete)?;
+            let soft_deletes = SoftDeletes::open(soft_deletes_data.read_bytes()?)?;
+            if let Some(expired_alive_bitset) = soft_delete_options.compute_alive_bitset(
+                &soft_deletes,
+                segment.meta().max_doc(),
+                threshold,
+            ) {
+                *alive_bitset = Some(match alive_bitset.take() {
+                    Some(ttl_alive_bitset) => {
+                        intersect_alive_bitsets(ttl_alive_bitset, expired_alive_bitset)
+                    }
+                    None => expired_alive_bitset,
+                });
+            }
+        }
+    }
+
     // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger = IndexMerger::open(index.schema(), &segments[..])?;
+    let merger: IndexMerger =
+        IndexMerger::open_including_soft_deleted(index.schema(), &segments[..], alive_bitsets)?;
 
     // ... we just serialize this index merger in our new segment to merge the segments.
     let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
 
     let num_docs = merger.write(segment_serializer)?;
+    let merged_soft_deletes = merger.merged_soft_deletes()?;
+
+    let doc_id_mapping_opt = if with_doc_id_mapping {
+        let old_doc_addrs = merger
+            .get_doc_id_from_concatenated_data()?
+            .new_doc_id_to_old_doc_addr;
+        Some(MergeDocIdMapping::new(
+            &source_segments,
+            Some(merged_segment.id()),
+            old_doc_addrs,
+        ))
+    } else {
+        None
+    };
+
+    if num_expired > 0 {
+        info!("Merge expired {num_expired} docs");
+    }
 
     let merged_segment_id = merged_segment.id();
 
-    let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
-    Ok(Some(SegmentEntry::new(segment_meta, delete_cursor, None)))
+    // The values registered by uncommitted segments need to be committed with the merged
+    // segment.
+    let mut registered_enum_values: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for segment_entry in &segment_entries {
+        for (field_name, values) in segment_entry.meta().registered_enum_values() {
+            registered_enum_values
+                .entry(field_name.clone())
+                .or_default()
+                .extend(values.iter().cloned());
+        }
+    }
+    let mut segment_meta = index
+        .new_segment_meta(merged_segment_id, num_docs)
+        .with_registered_enum_values(registered_enum_values);
+    if !merged_soft_deletes.is_empty() {
+        segment_meta =
+            segment_meta.with_soft_delete_meta(0, merged_soft_deletes.num_docs(), target_opstamp);
+        let mut soft_deletes_file = index
+            .segment(segment_meta.clone())
+            .open_write(SegmentComponent::SoftDelete)?;
+        merged_soft_deletes.write(&mut soft_deletes_file)?;
+        soft_deletes_file.terminate()?;
+    }
+    Ok((
+        Some(SegmentEntry::new(segment_meta, delete_cursor, None)),
+        doc_id_mapping_opt,
+        num_expired,
+    ))
 }
 
 /// Advanced: Merges a list of segments from different indices in a new index.
@@ -267,14 +377,25 @@ pub(crate) struct InnerSegmentUpdater {
     killed: AtomicBool,
     stamper: Stamper,
     merge_operations: MergeOperationInventory,
+    indexing_metrics: Option<Arc<IndexingMetrics>>,
+    ttl_options: Option<TtlOptions>,
+    soft_delete_options: Option<SoftDeleteOptions>,
+    num_expired_docs: AtomicU64,
+    commit_stats: CommitStats,
+    merge_doc_id_mapping_callback: Option<MergeDocIdMappingCallback>,
 }
 
 impl SegmentUpdater {
+    #[expect(clippy::too_many_arguments)]
     pub fn create(
         index: Index,
         stamper: Stamper,
         delete_cursor: &DeleteCursor,
         num_merge_threads: usize,
+        indexing_metrics: Option<Arc<IndexingMetrics>>,
+        ttl_options: Option<TtlOptions>,
+        soft_delete_options: Option<SoftDeleteOptions>,
+        merge_doc_id_mapping_callback: Option<MergeDocIdMappingCallback>,
     ) -> crate::Result<SegmentUpdater> {
         let segments = index.searchable_segment_metas()?;
         let segment_manager = SegmentManager::from_segments(segments, delete_cursor);
@@ -316,9 +437,24 @@ impl SegmentUpdater {
             killed: AtomicBool::new(false),
             stamper,
             merge_operations: Default::default(),
+            indexing_metrics,
+            ttl_options,
+            soft_delete_options,
+            num_expired_docs: AtomicU64::new(0),
+            commit_stats: CommitStats::default(),
+            merge_doc_id_mapping_callback,
         })))
     }
 
+    pub fn num_expired_docs(&self) -> u64 {
+        self.num_expired_docs.load(Ordering::Relaxed)
+    }
+
+    /// The counts of the ongoing commit.
+    pub(crate) fn commit_stats(&self) -> &CommitStats {
+        &self.commit_stats
+    }
+
     pub fn get_merge_policy(&self) -> Arc<dyn MergePolicy> {
         self.merge_policy.read().unwrap().clone()
     }
@@ -375,7 +511,12 @@ impl SegmentUpdater {
         let mut segment_entries = self.segment_manager.segment_entries();
         for segment_entry in &mut segment_entries {
             let segment = self.index.segment(segment_entry.meta().clone());
-            advance_deletes(segment, segment_entry, target_opstamp)?;
+            advance_deletes_with_stats(
+                segment,
+                segment_entry,
+                target_opstamp,
+                Some(&self.commit_stats),
+            )?;
         }
         Ok(segment_entries)
     }
@@ -405,13 +546,15 @@ impl SegmentUpdater {
             // Segment 1 from disk 1, Segment 1 from disk 2, etc.
             committed_segment_metas
                 .sort_by_key(|segment_meta| std::cmp::Reverse(segment_meta.max_doc()));
-            let index_meta = IndexMeta {
+            // The schema of the last commit holds the enum values registered so far.
+            let mut index_meta = IndexMeta {
                 index_settings: index.settings().clone(),
                 segments: committed_segment_metas,
-                schema: index.schema(),
+                schema: self.load_meta().schema.clone(),
                 opstamp,
                 payload: commit_message,
             };
+            index_meta.register_segment_enum_values();
             // TODO add context to the error.
             save_metas(&index_meta, directory.box_clone().borrow_mut())?;
             self.store_meta(&index_meta);
@@ -476,7 +619,8 @@ impl SegmentUpdater {
     // The merge itself happens on a different thread.
     //
     // When successful, this function returns a `Future` for a `Result<SegmentMeta>` that represents
-    // the actual outcome of the merge operation.
+    // the actual outcome of the merge operation. See `start_merge_with_outcome` to also get the
+    // number of expired documents.
     //
     // It returns an error if for some reason the merge operation could not be started.
     //
@@ -489,6 +633,22 @@ impl SegmentUpdater {
         &self,
         merge_operation: MergeOperation,
     ) -> FutureResult<Option<SegmentMeta>> {
+        self.spawn_merge(merge_operation, |merge_outcome| merge_outcome.segment_meta)
+    }
+
+    // Starts a merge operation like `start_merge`, returning a `Future` for its `MergeOutcome`.
+    pub fn start_merge_with_outcome(
+        &self,
+        merge_operation: MergeOperation,
+    ) -> FutureResult<MergeOutcome> {
+        self.spawn_merge(merge_operation, |merge_outcome| merge_outcome)
+    }
+
+    fn spawn_merge<T: Send + 'static>(
+        &self,
+        merge_operation: MergeOperation,
+        into_result: fn(MergeOutcome) -> T,
+    ) -> FutureResult<T> {
         assert!(
             !merge_operation.segment_ids().is_empty(),
             "Segment_ids cannot be empty."
@@ -519,11 +679,19 @@ impl SegmentUpdater {
             // as well as which segment is currently in merge and therefore should not be
             // candidate for another merge.
             let merge_panic_res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                merge(
+                let merge_start = Instant::now();
+                let merge_res = merge(
                     &segment_updater.index,
                     segment_entries,
                     merge_operation.target_opstamp(),
-                )
+                    segment_updater.ttl_options.as_ref(),
+                    segment_updater.soft_delete_options.as_ref(),
+                    segment_updater.merge_doc_id_mapping_callback.is_some(),
+                );
+                if let Some(indexing_metrics) = &segment_updater.indexing_metrics {
+                    indexing_metrics.record(IndexingStage::Merge, merge_start.elapsed());
+                }
+                merge_res
             }));
             let merge_res = match merge_panic_res {
                 Ok(merge_res) => merge_res,
@@ -546,8 +714,22 @@ impl SegmentUpdater {
                 }
             };
             match merge_res {
-                Ok(after_merge_segment_entry) => {
-                    let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
+                Ok((after_merge_segment_entry, doc_id_mapping_opt, num_expired_docs)) => {
+                    segment_updater
+                        .num_expired_docs
+                        .fetch_add(num_expired_docs, Ordering::Relaxed);
+                    let res = segment_updater
+                        .end_merge(
+                            merge_operation,
+                            after_merge_segment_entry,
+                            doc_id_mapping_opt,
+                        )
+                        .map(|segment_meta| {
+                            into_result(MergeOutcome {
+                                segment_meta,
+                                num_expired_docs,
+                            })
+                        });
                     let _send_result = merging_future_send.send(res);
                 }
                 Err(merge_error) => {
@@ -573,7 +755,13 @@ impl SegmentUpdater {
             .get_mergeable_segments(&merge_segment_ids)
     }
 
-    fn consider_merge_options(&self) {
+    /// Asks the merge policy which of the mergeable segments should be merged.
+    ///
+    /// Committed segments cannot be merged with uncommitted segments, so the two sets of
+    /// segments are considered independently. The candidates are returned along with whether
+    /// their segments are committed, the candidates of the uncommitted segments first, and with
+    /// the metas of the mergeable segments.
+    fn compute_merge_candidates(&self) -> (Vec<(MergeCandidate, bool)>, Vec<SegmentMeta>) {
         let (mut committed_segments, mut uncommitted_segments) = self.get_mergeable_segments();
         if committed_segments.len() == 1 && committed_segments[0].num_deleted_docs() == 0 {
             committed_segments.clear();
@@ -582,40 +770,106 @@ impl SegmentUpdater {
             uncommitted_segments.clear();
         }
 
-        // Committed segments cannot be merged with uncommitted_segments.
-        // We therefore consider merges using these two sets of segments independently.
         let merge_policy = self.get_merge_policy();
-
-        let current_opstamp = self.stamper.stamp();
-        let mut merge_candidates: Vec<MergeOperation> = merge_policy
+        let mut merge_candidates: Vec<(MergeCandidate, bool)> = merge_policy
             .compute_merge_candidates(&uncommitted_segments)
             .into_iter()
-            .map(|merge_candidate| {
-                MergeOperation::new(&self.merge_operations, current_opstamp, merge_candidate.0)
-            })
+            .map(|merge_candidate| (merge_candidate, false))
             .collect();
+        merge_candidates.extend(
+            merge_policy
+                .compute_merge_candidates(&committed_segments)
+                .into_iter()
+                .map(|merge_candidate| (merge_candidate, true)),
+        );
+        let mut segment_metas = uncommitted_segments;
+        segment_metas.extend(committed_segments);
+        (merge_candidates, segment_metas)
+    }
 
-        let commit_opstamp = self.load_meta().opstamp;
-        let committed_merge_candidates = merge_policy
-            .compute_merge_candidates(&committed_segments)
-            .into_iter()
-            .map(|merge_candidate: MergeCandidate| {
-                MergeOperation::new(&self.merge_operations, commit_opstamp, merge_candidate.0)
-            });
-        merge_candidates.extend(committed_merge_candidates);
+    /// Returns the opstamp up to which the deletes are applied by a merge.
+    ///
+    /// The merges of committed segments only apply the committed deletes.
+    fn merge_target_opstamp(&self, is_committed: bool) -> Opstamp {
+        if is_committed {
+            self.load_meta().opstamp
+        } else {
+            self.stamper.stamp()
+        }
+    }
 
-        for merge_operation in merge_candidates {
+    fn consider_merge_options(&self) {
+        let (merge_candidates, _) = self.compute_merge_candidates();
+        let current_opstamp = self.merge_target_opstamp(false);
+        let commit_opstamp = self.merge_target_opstamp(true);
+        let merge_operations: Vec<MergeOperation> = merge_candidates
+            .into_iter()
+            .map(|(merge_candidate, is_committed)| {
+                let target_opstamp = if is_committed {
+                    commit_opstamp
+                } else {
+                    current_opstamp
+                };
+                MergeOperation::new(&self.merge_operations, target_opstamp, merge_candidate.0)
+            })
+            .collect();
+        for merge_operation in merge_operations {
             // If a merge cannot be started this is not a fatal error.
             // We do log a warning in `start_merge`.
             drop(self.start_merge(merge_operation));
         }
     }
 
+    /// Returns the merges that the merge policy suggests for the mergeable segments, without
+    /// starting them.
+    pub(crate) fn merge_plan(&self) -> crate::Result<MergePlan> {
+        let (merge_candidates, segment_metas) = self.compute_merge_candidates();
+        let merges = merge_candidates
+            .into_iter()
+            .map(|(merge_candidate, is_committed)| {
+                PlannedMerge::new(
+                    merge_candidate.0,
+                    is_committed,
+                    &segment_metas,
+                    self.index.directory(),
+                )
+            })
+            .collect::<crate::Result<Vec<PlannedMerge>>>()?;
+        Ok(MergePlan::new(merges))
+    }
+
+    /// Starts the merges of `plan`, in order.
+    ///
+    /// Like the merges suggested by the merge policy, a merge of committed segments only
+    /// applies the committed deletes.
+    pub(crate) fn execute_plan(&self, plan: &MergePlan) -> Vec<FutureResult<Option<SegmentMeta>>> {
+        let merge_operations: Vec<MergeOperation> = plan
+            .merges()
+            .iter()
+            .map(|planned_merge| {
+                let target_opstamp = self.merge_target_opstamp(planned_merge.is_committed());
+                MergeOperation::new(
+                    &self.merge_operations,
+                    target_opstamp,
+                    planned_merge.segment_ids().to_vec(),
+                )
+            })
+            .collect();
+        merge_operations
+            .into_iter()
+            .map(|merge_operation| self.start_merge(merge_operation))
+            .collect()
+    }
+
     /// Queues a `end_merge` in the segment updater and blocks until it is successfully processed.
+    ///
+    /// The doc id mapping, if any, is passed to the merge doc id mapping callback once the merged
+    /// segment has replaced the segments it was merged from.
     fn end_merge(
         &self,
         merge_operation: MergeOperation,
         mut after_merge_segment_entry: Option<SegmentEntry>,
+        doc_id_mapping_opt: Option<MergeDocIdMapping>,
     ) -> crate::Result<Option<SegmentMeta>> {
         let segment_updater = self.clone();
         let after_merge_segment_meta = after_merge_segment_entry
@@ -666,6 +920,15 @@ impl SegmentUpdater {
                         .save_metas(previous_metas.opstamp, previous_metas.payload.clone())?;
                 }
 
+                // Calling back before considering new merges guarantees that the mappings are
+                // received in the order of the merges.
+                if let (Some(callback), Some(doc_id_mapping)) = (
+                    &segment_updater.merge_doc_id_mapping_callback,
+                    &doc_id_mapping_opt,
+                ) {
+                    callback(doc_id_mapping);
+                }
+
                 segment_updater.consider_merge_options();
             } // we drop all possible handle to a now useless `SegmentMeta`.
 
@@ -707,9 +970,10 @@ mod tests {
     use crate::indexer::merge_policy::tests::MergeWheneverPossible;
     use crate::indexer::merger::IndexMerger;
     use crate::indexer::segment_updater::merge_filtered_segments;
+    use crate::indexer::NoMergePolicy;
     use crate::query::QueryParser;
     use crate::schema::*;
-    use crate::{Directory, DocAddress, Index, Segment};
+    use crate::{Directory, DocAddress, Index, IndexWriter, Segment};
 
     #[test]
     fn test_segment_sort_large_max_doc() {
@@ -768,6 +1032,87 @@ mod tests {
         Ok(())
     }
 
+    /// Creates an index with three committed segments, a third of whose documents are deleted.
+    fn index_with_deletes() -> crate::Result<(Index, IndexWriter, Field)> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for text in ["b", "c", "d"] {
+            for _ in 0..10 {
+                index_writer.add_document(doc!(text_field => text))?;
+            }
+            for _ in 0..5 {
+                index_writer.add_document(doc!(text_field => "a"))?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_text(text_field, "a"));
+        index_writer.commit()?;
+        Ok((index, index_writer, text_field))
+    }
+
+    #[test]
+    fn test_merge_plan() -> crate::Result<()> {
+        let (index, mut index_writer, _) = index_with_deletes()?;
+        assert!(index_writer.merge_plan()?.is_empty());
+
+        index_writer.set_merge_policy(Box::new(MergeWheneverPossible));
+        let plan = index_writer.merge_plan()?;
+        assert_eq!(plan.merges().len(), 1);
+        let planned_merge = &plan.merges()[0];
+        let mut planned_segment_ids = planned_merge.segment_ids().to_vec();
+        planned_segment_ids.sort();
+        let mut segment_ids = index.searchable_segment_ids()?;
+        segment_ids.sort();
+        assert_eq!(planned_segment_ids, segment_ids);
+        assert!(planned_merge.is_committed());
+        assert_eq!(planned_merge.num_docs(), 30);
+        assert_eq!(planned_merge.num_deleted_docs(), 15);
+        assert!(planned_merge.num_bytes() > 0);
+        assert!(planned_merge.estimated_num_bytes() < planned_merge.num_bytes());
+        assert_eq!(
+            plan.estimated_reclaimed_bytes(),
+            planned_merge.num_bytes() - planned_merge.estimated_num_bytes()
+        );
+        // Computing the plan does not start any merge.
+        assert_eq!(index.searchable_segment_ids()?.len(), 3);
+
+        let merge_results = index_writer.execute_plan(&plan);
+        assert_eq!(merge_results.len(), 1);
+        for merge_result in merge_results {
+            let merged_segment_meta = merge_result.wait()?.unwrap();
+            assert_eq!(
+                merged_segment_meta.max_doc() as u64,
+                planned_merge.num_docs()
+            );
+        }
+        index_writer.wait_merging_threads()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_plan_same_as_merge_policy() -> crate::Result<()> {
+        let (index, mut index_writer, _) = index_with_deletes()?;
+        index_writer.set_merge_policy(Box::new(MergeWheneverPossible));
+        let plan = index_writer.merge_plan()?;
+        assert_eq!(plan.merges().len(), 1);
+
+        // The merge policy is run after each commit.
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(
+            segment_metas[0].max_doc() as u64,
+            plan.merges()[0].num_docs()
+        );
+        Ok(())
+    }
+
     #[test]
     fn delete_all_docs_min() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -1,12 +1,18 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::Instant;
+
 use columnar::MonotonicallyMappableToU64;
 use common::JsonPathWriter;
 use itertools::Itertools;
 use tokenizer_api::BoxTokenStream;
 
 use super::operation::AddOperation;
-use crate::fastfield::FastFieldsWriter;
+use crate::fastfield::{F32VectorsWriter, FastFieldsWriter};
 use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::index::{Segment, SegmentComponent};
+use crate::indexer::discarded_values::{report_discarded_values, DiscardedValuesCallback};
+use crate::indexer::indexing_metrics::{IndexingMetrics, IndexingStage};
 use crate::indexer::indexing_term::IndexingTerm;
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::json_utils::{index_json_value, IndexingPositionsPerPath};
@@ -15,7 +21,7 @@ use crate::postings::{
     PerFieldPostingsWriter, PostingsWriter,
 };
 use crate::schema::document::{Document, Value};
-use crate::schema::{FieldEntry, FieldType, Schema, DATE_TIME_PRECISION_INDEXED};
+use crate::schema::{EnumFields, FieldEntry, FieldType, Schema, DATE_TIME_PRECISION_INDEXED};
 use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer};
 use crate::{DocId, Opstamp, TantivyError};
 
@@ -51,13 +57,18 @@ pub struct SegmentWriter {
     pub(crate) per_field_postings_writers: PerFieldPostingsWriter,
     pub(crate) segment_serializer: SegmentSerializer,
     pub(crate) fast_field_writers: FastFieldsWriter,
+    pub(crate) f32_vectors_writer: F32VectorsWriter,
     pub(crate) fieldnorms_writer: FieldNormsWriter,
     pub(crate) json_path_writer: JsonPathWriter,
     pub(crate) json_positions_per_path: IndexingPositionsPerPath,
     pub(crate) doc_opstamps: Vec<Opstamp>,
+    enum_fields: EnumFields,
+    registered_enum_values: BTreeMap<String, BTreeSet<String>>,
     per_field_text_analyzers: Vec<TextAnalyzer>,
     term_buffer: IndexingTerm,
     schema: Schema,
+    indexing_metrics: Option<Arc<IndexingMetrics>>,
+    discarded_values_callback: Option<DiscardedValuesCallback>,
 }
 
 impl SegmentWriter {
@@ -111,13 +122,40 @@ impl SegmentWriter {
                 &schema,
                 tokenizer_manager_fast_field,
             )?,
+            f32_vectors_writer: F32VectorsWriter::for_schema(&schema),
             doc_opstamps: Vec::with_capacity(1_000),
+            enum_fields: EnumFields::for_schema(&schema),
+            registered_enum_values: BTreeMap::new(),
             per_field_text_analyzers,
             term_buffer: IndexingTerm::with_capacity(16),
             schema,
+            indexing_metrics: None,
+            discarded_values_callback: None,
         })
     }
 
+    /// Sets the metrics in which the time spent in each indexing stage is recorded.
+    pub(crate) fn set_indexing_metrics(&mut self, indexing_metrics: Option<Arc<IndexingMetrics>>) {
+        self.indexing_metrics = indexing_metrics;
+    }
+
+    pub(crate) fn set_discarded_values_callback(
+        &mut self,
+        discarded_values_callback: Option<DiscardedValuesCallback>,
+    ) {
+        self.discarded_values_callback = discarded_values_callback;
+    }
+
+    /// Records the time elapsed since `lap_start` for the given stage,
+    /// and restarts the lap.
+    fn record_lap(&self, stage: IndexingStage, lap_start: &mut Option<Instant>) {
+        if let (Some(indexing_metrics), Some(lap_start)) = (&self.indexing_metrics, lap_start) {
+            let now = Instant::now();
+            indexing_metrics.record(stage, now - *lap_start);
+            *lap_start = now;
+        }
+    }
+
     /// Lay on disk the current content of the `SegmentWriter`
     ///
     /// Finalize consumes the `SegmentWriter`, so that it cannot
@@ -129,6 +167,7 @@ impl SegmentWriter {
             &self.per_field_postings_writers,
             self.ctx,
             self.fast_field_writers,
+            &self.f32_vectors_writer,
             &self.fieldnorms_writer,
             self.segment_serializer,
         )?;
@@ -141,6 +180,7 @@ impl SegmentWriter {
         self.ctx.mem_usage()
             + self.fieldnorms_writer.mem_usage()
             + self.fast_field_writers.mem_usage()
+            + self.f32_vectors_writer.mem_usage()
             + self.segment_serializer.mem_usage()
     }
 
@@ -196,12 +236,16 @@ impl SegmentWriter {
                     for value in values {
                         let value = value.as_value();
 
-                        let mut token_stream = if let Some(text) = value.as_str() {
+                        let (mut token_stream, text_len) = if let Some(text) = value.as_str() {
                             let text_analyzer =
                                 &mut self.per_field_text_analyzers[field.field_id() as usize];
-                            text_analyzer.token_stream(text)
+                            (text_analyzer.token_stream(text), text.len())
                         } else if let Some(tok_str) = value.into_pre_tokenized_text() {
-                            BoxTokenStream::new(PreTokenizedStream::from(*tok_str.clone()))
+                            let text_len = tok_str.text.len();
+                            (
+                                BoxTokenStream::new(PreTokenizedStream::from(*tok_str.clone())),
+                                text_len,
+                            )
                         } else {
                             continue;
                         };
@@ -214,6 +258,8 @@ impl SegmentWriter {
                             ctx,
                             &mut indexing_position,
                         );
+                        // The offsets of the next value account for a space separator.
+                        indexing_position.start_offset += text_len as u32 + 1;
                     }
                     if field_entry.has_fieldnorms() {
                         self.fieldnorms_writer
@@ -276,6 +322,20 @@ impl SegmentWriter {
                         self.fieldnorms_writer.record(doc_id, field, num_vals);
                     }
                 }
+                FieldType::F32(_) => {
+                    let mut num_vals = 0;
+                    for value in values {
+                        let value = value.as_value();
+                        num_vals += 1;
+                        let f64_val = value.as_f64().ok_or_else(make_schema_error)?;
+                        // Index the value as rounded in the fast field.
+                        term_buffer.set_f64(f64::from(f64_val as f32));
+                        postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                    }
+                    if field_entry.has_fieldnorms() {
+                        self.fieldnorms_writer.record(doc_id, field, num_vals);
+                    }
+                }
                 FieldType::Bool(_) => {
                     let mut num_vals = 0;
                     for value in values {
@@ -338,6 +398,8 @@ impl SegmentWriter {
                         self.fieldnorms_writer.record(doc_id, field, num_vals);
                     }
                 }
+                // f32 vectors are never indexed.
+                FieldType::F32Vector(_) => {}
             }
         }
         Ok(())
@@ -351,15 +413,39 @@ impl SegmentWriter {
         add_operation: AddOperation<D>,
     ) -> crate::Result<()> {
         let AddOperation { document, opstamp } = add_operation;
+        self.enum_fields.register_document(
+            &self.schema,
+            &document,
+            &mut self.registered_enum_values,
+        )?;
         self.doc_opstamps.push(opstamp);
+        if let Some(discarded_values_callback) = &self.discarded_values_callback {
+            report_discarded_values(&self.schema, opstamp, &document, discarded_values_callback);
+        }
+        let mut lap_start = self.indexing_metrics.as_ref().map(|_| Instant::now());
+        self.f32_vectors_writer.add_document(&document)?;
         self.fast_field_writers.add_document(&document)?;
+        self.record_lap(IndexingStage::FastFields, &mut lap_start);
         self.index_document(&document)?;
+        self.record_lap(IndexingStage::Indexing, &mut lap_start);
         let doc_writer = self.segment_serializer.get_store_writer();
+        let num_bytes_stored_before = doc_writer.num_bytes_stored();
         doc_writer.store(&document, &self.schema)?;
+        let num_bytes = doc_writer.num_bytes_stored() - num_bytes_stored_before;
+        self.record_lap(IndexingStage::DocStore, &mut lap_start);
+        if let Some(indexing_metrics) = &self.indexing_metrics {
+            indexing_metrics.record_doc(num_bytes);
+        }
         self.max_doc += 1;
         Ok(())
     }
 
+    /// Takes the values registered in the dictionaries of the enum fields by the documents
+    /// added so far, by field name.
+    pub(crate) fn take_registered_enum_values(&mut self) -> BTreeMap<String, BTreeSet<String>> {
+        std::mem::take(&mut self.registered_enum_values)
+    }
+
     /// Max doc is
     /// - the number of documents in the segment assuming there is no deletes
     /// - the maximum document id (including deleted documents) + 1
@@ -391,6 +477,7 @@ fn remap_and_write(
     per_field_postings_writers: &PerFieldPostingsWriter,
     ctx: IndexingContext,
     fast_field_writers: FastFieldsWriter,
+    f32_vectors_writer: &F32VectorsWriter,
     fieldnorms_writer: &FieldNormsWriter,
     mut serializer: SegmentSerializer,
 ) -> crate::Result<()> {
@@ -411,6 +498,9 @@ fn remap_and_write(
     )?;
     debug!("fastfield-serialize");
     fast_field_writers.serialize(serializer.get_fast_field_write())?;
+    if let Some(f32_vectors_write) = serializer.get_f32_vectors_write() {
+        f32_vectors_writer.serialize(f32_vectors_write)?;
+    }
 
     debug!("serializer-close");
     serializer.close()?;
@@ -462,13 +552,7 @@ mod tests {
         let mut doc = TantivyDocument::default();
         let pre_tokenized_text = PreTokenizedString {
             text: String::from("A"),
-            tokens: vec![Token {
-                offset_from: 0,
-                offset_to: 1,
-                position: 0,
-                text: String::from("A"),
-                position_length: 1,
-            }],
+            tokens: vec![Token::new(0, 1, 0, String::from("A"))],
         };
 
         doc.add_pre_tokenized_text(text_field, pre_tokenized_text);
@@ -752,8 +836,9 @@ mod tests {
             term_info,
             TermInfo {
                 doc_freq: 1,
-                postings_range: 2..4,
-                positions_range: 2..5
+                postings_range: 0..0,
+                positions_range: 2..5,
+                inline_posting: Some((0, 2)),
             }
         );
         let mut postings = inv_index
@@ -791,8 +876,9 @@ mod tests {
             term_info,
             TermInfo {
                 doc_freq: 1,
-                postings_range: 0..1,
-                positions_range: 0..0
+                postings_range: 0..0,
+                positions_range: 0..0,
+                inline_posting: Some((0, 0)),
             }
         );
         let mut postings = inv_index
@@ -978,13 +1064,7 @@ mod tests {
         // This is a bit of a contrived example.
         let tokens = PreTokenizedString {
             text: "roller-coaster".to_string(),
-            tokens: vec![Token {
-                offset_from: 0,
-                offset_to: 14,
-                position: 0,
-                text: "rollercoaster".to_string(),
-                position_length: 2,
-            }],
+            tokens: vec![Token::new(0, 14, 0, "rollercoaster".to_string()).with_position_length(2)],
         };
         doc.add_pre_tokenized_text(text, tokens.clone());
         doc.add_pre_tokenized_text(text, tokens);
@@ -1017,21 +1097,9 @@ mod tests {
         let tokens = PreTokenizedString {
             text: "contrived-example".to_string(), //< I can't think of a use case where this corner case happens in real life.
             tokens: vec![
-                Token {
-                    // Not the last token, yet ends after the last token.
-                    offset_from: 0,
-                    offset_to: 14,
-                    position: 0,
-                    text: "long_token".to_string(),
-                    position_length: 3,
-                },
-                Token {
-                    offset_from: 0,
-                    offset_to: 14,
-                    position: 1,
-                    text: "short".to_string(),
-                    position_length: 1,
-                },
+                // Not the last token, yet ends after the last token.
+                Token::new(0, 14, 0, "long_token".to_string()).with_position_length(3),
+                Token::new(0, 14, 1, "short".to_string()),
             ],
         };
         doc.add_pre_tokenized_text(text, tokens);
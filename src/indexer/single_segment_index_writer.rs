@@ -37,18 +37,23 @@ impl<D: Document> SingleSegmentIndexWriter<D> {
             .add_document(AddOperation { opstamp, document })
     }
 
-    pub fn finalize(self) -> crate::Result<Index> {
+    pub fn finalize(mut self) -> crate::Result<Index> {
         let max_doc = self.segment_writer.max_doc();
+        let registered_enum_values = self.segment_writer.take_registered_enum_values();
         self.segment_writer.finalize()?;
         let segment: Segment = self.segment.with_max_doc(max_doc);
         let index = segment.index();
-        let index_meta = IndexMeta {
+        let mut index_meta = IndexMeta {
             index_settings: index.settings().clone(),
-            segments: vec![segment.meta().clone()],
+            segments: vec![segment
+                .meta()
+                .clone()
+                .with_registered_enum_values(registered_enum_values)],
             schema: index.schema(),
             opstamp: 0,
             payload: None,
         };
+        index_meta.register_segment_enum_values();
         save_metas(&index_meta, index.directory())?;
         index.directory().sync_directory()?;
         Ok(segment.index().clone())
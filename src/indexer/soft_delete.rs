@@ -0,0 +1,254 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{BitSet, ReadOnlyBitSet};
+
+use crate::fastfield::AliveBitSet;
+use crate::index::SoftDeletes;
+use crate::time::OffsetDateTime;
+use crate::{DateTime, DocId};
+
+/// Turns deletes into soft deletes, keeping the deleted documents for a retention period.
+///
+/// When configured on the writer via [`IndexWriterOptions`](crate::indexer::IndexWriterOptions),
+/// [`IndexWriter::delete_term`](crate::IndexWriter::delete_term) and
+/// [`IndexWriter::delete_query`](crate::IndexWriter::delete_query) soft delete the documents
+/// they match. Soft deleted documents are excluded from search like deleted documents, but
+/// remain retrievable via
+/// [`Searcher::include_soft_deleted`](crate::Searcher::include_soft_deleted).
+///
+/// Expiration is lazy: soft deleted documents are kept by merges until they were deleted for
+/// longer than `retention`. The merge then drops them like deleted documents. They can also be
+/// deleted for good at any time with
+/// [`IndexWriter::purge_soft_deleted`](crate::IndexWriter::purge_soft_deleted). A writer
+/// without `SoftDeleteOptions` keeps the soft deleted documents until they are purged.
+///
+/// As merges keep them, soft deleted documents are not counted as deleted by
+/// [`SegmentMeta::num_deleted_docs`](crate::index::SegmentMeta::num_deleted_docs), which is what
+/// merge policies rely on. Merging indices with [`merge_indices`](crate::indexer::merge_indices)
+/// or [`merge_filtered_segments`](crate::indexer::merge_filtered_segments) drops them.
+#[derive(Clone)]
+pub struct SoftDeleteOptions {
+    retention: Duration,
+    clock: Arc<dyn Fn() -> DateTime + Send + Sync>,
+}
+
+impl fmt::Debug for SoftDeleteOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoftDeleteOptions")
+            .field("retention", &self.retention)
+            .finish()
+    }
+}
+
+impl SoftDeleteOptions {
+    /// Creates a new `SoftDeleteOptions`, keeping the soft deleted documents for `retention`.
+    pub fn new(retention: Duration) -> SoftDeleteOptions {
+        SoftDeleteOptions {
+            retention,
+            clock: Arc::new(|| DateTime::from_utc(OffsetDateTime::now_utc())),
+        }
+    }
+
+    /// Replaces the clock used to get the current time, which defaults to the system clock.
+    ///
+    /// This is mostly useful for tests.
+    pub fn with_clock(mut self, clock: impl Fn() -> DateTime + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// The duration for which soft deleted documents are kept.
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// The current time, used as the deletion time of the soft deletes.
+    pub(crate) fn now(&self) -> DateTime {
+        (self.clock)()
+    }
+
+    /// Documents soft deleted before the returned date are expired.
+    pub(crate) fn expiration_threshold(&self) -> DateTime {
+        let retention_nanos = i64::try_from(self.retention.as_nanos()).unwrap_or(i64::MAX);
+        let now_nanos = self.now().into_timestamp_nanos();
+        DateTime::from_timestamp_nanos(now_nanos.saturating_sub(retention_nanos))
+    }
+
+    /// Computes the alive bitset of a segment with the expired soft deleted documents removed.
+    ///
+    /// Returns `None` if no soft deleted document of the segment is expired.
+    pub(crate) fn compute_alive_bitset(
+        &self,
+        soft_deletes: &SoftDeletes,
+        max_doc: DocId,
+        threshold: DateTime,
+    ) -> Option<AliveBitSet> {
+        let mut alive_bitset = BitSet::with_max_value_and_full(max_doc);
+        let mut num_expired = 0u32;
+        for (doc, deleted_at) in soft_deletes.iter() {
+            if deleted_at < threshold {
+                alive_bitset.remove(doc);
+                num_expired += 1;
+            }
+        }
+        if num_expired == 0 {
+            return None;
+        }
+        Some(AliveBitSet::from(ReadOnlyBitSet::from(&alive_bitset)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::SoftDeleteOptions;
+    use crate::collector::Count;
+    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
+    use crate::query::TermQuery;
+    use crate::schema::{Field, IndexRecordOption, Schema, STORED, STRING};
+    use crate::{DateTime, Index, IndexWriter, Searcher, Term};
+
+    const DAY_SECS: i64 = 24 * 3600;
+
+    fn create_index() -> (Index, Field) {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        (Index::create_in_ram(schema_builder.build()), id)
+    }
+
+    fn create_writer(index: &Index, now_secs: &Arc<AtomicI64>) -> crate::Result<IndexWriter> {
+        let clock_secs = now_secs.clone();
+        let soft_delete_options = SoftDeleteOptions::new(Duration::from_secs(30 * DAY_SECS as u64))
+            .with_clock(move || DateTime::from_timestamp_secs(clock_secs.load(Ordering::SeqCst)));
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .soft_deletes(soft_delete_options)
+            .build();
+        let index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        Ok(index_writer)
+    }
+
+    fn count(searcher: &Searcher, id: Field, value: &str) -> crate::Result<usize> {
+        let query = TermQuery::new(Term::from_field_text(id, value), IndexRecordOption::Basic);
+        searcher.search(&query, &Count)
+    }
+
+    #[test]
+    fn test_soft_deleted_docs_are_only_visible_when_included() -> crate::Result<()> {
+        let (index, id) = create_index();
+        let now_secs = Arc::new(AtomicI64::new(DAY_SECS));
+        let mut index_writer = create_writer(&index, &now_secs)?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        // Soft deletes apply to committed documents, and to documents of the same commit.
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.delete_term(Term::from_field_text(id, "b"));
+        index_writer.delete_term(Term::from_field_text(id, "c"));
+        index_writer.commit()?;
+        assert_eq!(index_writer.last_commit_summary().unwrap().deleted_docs, 2);
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(count(&searcher, id, "b")?, 0);
+        assert_eq!(count(&searcher, id, "c")?, 0);
+        let metas = index.searchable_segment_metas()?;
+        assert_eq!(
+            metas
+                .iter()
+                .map(|meta| (meta.num_deleted_docs(), meta.num_soft_deleted_docs()))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (0, 1)]
+        );
+
+        let searcher = searcher.include_soft_deleted()?;
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(count(&searcher, id, "b")?, 1);
+        assert_eq!(count(&searcher, id, "c")?, 1);
+        for segment_reader in searcher.segment_readers() {
+            let soft_deletes = segment_reader.soft_deletes().unwrap();
+            let (_, deleted_at) = soft_deletes.iter().next().unwrap();
+            assert_eq!(deleted_at, DateTime::from_timestamp_secs(DAY_SECS));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_soft_deleted_docs_survive_merges_until_expired() -> crate::Result<()> {
+        let (index, id) = create_index();
+        let now_secs = Arc::new(AtomicI64::new(DAY_SECS));
+        let mut index_writer = create_writer(&index, &now_secs)?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        now_secs.store(10 * DAY_SECS, Ordering::SeqCst);
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.delete_term(Term::from_field_text(id, "b"));
+        index_writer.commit()?;
+
+        let reader = index.reader()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 1);
+        let searcher = searcher.include_soft_deleted()?;
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(count(&searcher, id, "a")?, 1);
+        assert_eq!(count(&searcher, id, "b")?, 1);
+
+        // "a" was deleted 30 days ago, and "b" 21 days ago.
+        now_secs.store(31 * DAY_SECS + 1, Ordering::SeqCst);
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        let searcher = searcher.include_soft_deleted()?;
+        assert_eq!(searcher.num_docs(), 2);
+        assert_eq!(count(&searcher, id, "a")?, 0);
+        assert_eq!(count(&searcher, id, "b")?, 1);
+        let soft_deletes = searcher.segment_reader(0).soft_deletes().unwrap();
+        assert_eq!(soft_deletes.num_docs(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_purge_soft_deleted() -> crate::Result<()> {
+        let (index, id) = create_index();
+        let now_secs = Arc::new(AtomicI64::new(DAY_SECS));
+        let mut index_writer = create_writer(&index, &now_secs)?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        // The purge only affects the documents soft deleted before it.
+        index_writer.delete_term(Term::from_field_text(id, "b"));
+        index_writer.purge_soft_deleted();
+        index_writer.delete_term(Term::from_field_text(id, "c"));
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 0);
+        let meta = &index.searchable_segment_metas()?[0];
+        assert_eq!(meta.num_deleted_docs(), 2);
+        assert_eq!(meta.num_soft_deleted_docs(), 1);
+        let searcher = searcher.include_soft_deleted()?;
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(count(&searcher, id, "a")?, 0);
+        assert_eq!(count(&searcher, id, "b")?, 0);
+        assert_eq!(count(&searcher, id, "c")?, 1);
+        Ok(())
+    }
+}
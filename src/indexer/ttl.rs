@@ -0,0 +1,213 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{BitSet, ReadOnlyBitSet};
+
+use crate::fastfield::AliveBitSet;
+use crate::index::{Segment, SegmentReader};
+use crate::schema::{Schema, Type};
+use crate::time::OffsetDateTime;
+use crate::{DateTime, TantivyError};
+
+/// Expires documents based on the age of a date fast field.
+///
+/// A document is expired if the first value of its date field is older than `max_age`.
+/// Documents without any value in that field never expire.
+///
+/// Expiration is lazy: expired documents remain visible until the segment holding
+/// them is merged. The merge then treats them as deleted, and does not write them
+/// in the merged segment. To force the expiration, the segments can be merged explicitly
+/// using [`IndexWriter::merge`](crate::IndexWriter::merge), or
+/// [`IndexWriter::merge_with_outcome`](crate::IndexWriter::merge_with_outcome) which returns
+/// the number of expired documents.
+///
+/// The TTL is configured on the writer via
+/// [`IndexWriterOptions`](crate::indexer::IndexWriterOptions).
+#[derive(Clone)]
+pub struct TtlOptions {
+    field_name: String,
+    max_age: Duration,
+    clock: Arc<dyn Fn() -> DateTime + Send + Sync>,
+}
+
+impl fmt::Debug for TtlOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TtlOptions")
+            .field("field_name", &self.field_name)
+            .field("max_age", &self.max_age)
+            .finish()
+    }
+}
+
+impl TtlOptions {
+    /// Creates a new `TtlOptions`, expiring documents for which `field_name` is
+    /// older than `max_age`.
+    ///
+    /// The field is required to be a date fast field.
+    pub fn new(field_name: impl Into<String>, max_age: Duration) -> TtlOptions {
+        TtlOptions {
+            field_name: field_name.into(),
+            max_age,
+            clock: Arc::new(|| DateTime::from_utc(OffsetDateTime::now_utc())),
+        }
+    }
+
+    /// Replaces the clock used to get the current time, which defaults to the system clock.
+    ///
+    /// This is mostly useful for tests.
+    pub fn with_clock(mut self, clock: impl Fn() -> DateTime + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// The name of the date field used to compute the age of the documents.
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    /// The maximum age of the documents.
+    pub fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// Checks that the field exists and is a date fast field.
+    pub(crate) fn validate(&self, schema: &Schema) -> crate::Result<()> {
+        let field = schema.get_field(&self.field_name)?;
+        let field_type = schema.get_field_entry(field).field_type();
+        if field_type.value_type() != Type::Date || !field_type.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "TTL field `{}` is not a date fast field.",
+                self.field_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Documents older than the returned date are expired.
+    pub(crate) fn expiration_threshold(&self) -> DateTime {
+        let max_age_nanos = i64::try_from(self.max_age.as_nanos()).unwrap_or(i64::MAX);
+        let now_nanos = (self.clock)().into_timestamp_nanos();
+        DateTime::from_timestamp_nanos(now_nanos.saturating_sub(max_age_nanos))
+    }
+
+    /// Computes the alive bitset of the segment with the expired documents removed.
+    ///
+    /// Returns `None` if no document of the segment is expired, and otherwise the new
+    /// alive bitset with the number of documents that were expired.
+    pub(crate) fn compute_alive_bitset(
+        &self,
+        segment: &Segment,
+        threshold: DateTime,
+    ) -> crate::Result<Option<(AliveBitSet, u32)>> {
+        let segment_reader = SegmentReader::open(segment)?;
+        let Some(column) = segment_reader
+            .fast_fields()
+            .column_opt::<DateTime>(&self.field_name)?
+        else {
+            return Ok(None);
+        };
+        if column.min_value() >= threshold {
+            return Ok(None);
+        }
+        let mut alive_bitset = BitSet::with_max_value_and_full(segment_reader.max_doc());
+        let mut num_expired = 0u32;
+        for doc in segment_reader.doc_ids_alive() {
+            if column.first(doc).is_some_and(|date| date < threshold) {
+                alive_bitset.remove(doc);
+                num_expired += 1;
+            }
+        }
+        if num_expired == 0 {
+            return Ok(None);
+        }
+        Ok(Some((
+            AliveBitSet::from(ReadOnlyBitSet::from(&alive_bitset)),
+            num_expired,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::TtlOptions;
+    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
+    use crate::schema::{Schema, FAST, INDEXED, TEXT};
+    use crate::{DateTime, Index, IndexWriter};
+
+    const DAY_SECS: i64 = 24 * 3600;
+
+    #[test]
+    fn test_ttl_expires_docs_at_merge_time() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp = schema_builder.add_date_field("timestamp", INDEXED | FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+
+        let now_secs = Arc::new(AtomicI64::new(8 * DAY_SECS));
+        let clock_secs = now_secs.clone();
+        let ttl_options = TtlOptions::new("timestamp", Duration::from_secs(3 * DAY_SECS as u64))
+            .with_clock(move || DateTime::from_timestamp_secs(clock_secs.load(Ordering::SeqCst)));
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .ttl(ttl_options)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for day in [1, 5, 8] {
+            index_writer.add_document(doc!(
+                timestamp => DateTime::from_timestamp_secs(day * DAY_SECS),
+                text => "dated",
+            ))?;
+            index_writer.commit()?;
+        }
+        index_writer.add_document(doc!(text => "undated"))?;
+        index_writer.commit()?;
+
+        // Time passes, but documents remain visible until a merge.
+        now_secs.store(10 * DAY_SECS, Ordering::SeqCst);
+        let reader = index.reader()?;
+        assert_eq!(reader.searcher().num_docs(), 4);
+        assert_eq!(index_writer.num_expired_docs(), 0);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        let merge_outcome = index_writer.merge_with_outcome(&segment_ids).wait()?;
+        reader.reload()?;
+        // Only the documents of day 1 and 5 are older than 3 days.
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(merge_outcome.num_expired_docs, 2);
+        assert_eq!(merge_outcome.segment_meta.unwrap().num_docs(), 2);
+        assert_eq!(index_writer.num_expired_docs(), 2);
+
+        // Advancing the clock expires the last dated document on the next merge.
+        now_secs.store(20 * DAY_SECS, Ordering::SeqCst);
+        let segment_ids = index.searchable_segment_ids()?;
+        let merge_outcome = index_writer.merge_with_outcome(&segment_ids).wait()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(merge_outcome.num_expired_docs, 1);
+        assert_eq!(index_writer.num_expired_docs(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ttl_requires_date_fast_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_date_field("timestamp", INDEXED);
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        for field_name in ["timestamp", "text", "missing"] {
+            let options = IndexWriterOptions::builder()
+                .ttl(TtlOptions::new(field_name, Duration::from_secs(1)))
+                .build();
+            assert!(index
+                .writer_with_options::<crate::TantivyDocument>(options)
+                .is_err());
+        }
+    }
+}
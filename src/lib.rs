@@ -169,6 +169,10 @@ mod macros;
 mod future_result;
 
 // Re-exports
+#[cfg(feature = "arrow")]
+pub use arrow_array;
+#[cfg(feature = "derive")]
+pub use tantivy_derive::TantivyDoc;
 pub use columnar;
 pub use common::{ByteCount, DateTime};
 pub use query_grammar;
@@ -195,6 +199,7 @@ pub mod directory;
 pub mod fastfield;
 pub mod fieldnorm;
 pub mod index;
+pub mod percolator;
 pub mod positions;
 pub mod postings;
 
@@ -223,7 +228,10 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
 pub use self::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN, TERMINATED};
-pub use crate::core::{json_utils, Executor, Searcher, SearcherGeneration};
+pub use crate::core::{
+    json_utils, CardinalityEstimate, EstimateConfidence, EstimateMode, Executor, SearchOptions,
+    SearchSession, Searcher, SearcherGeneration, SessionFilter, StableDocHandle,
+};
 pub use crate::directory::Directory;
 pub use crate::index::{
     Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, Order, Segment,
@@ -233,7 +241,7 @@ pub use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
 pub use crate::schema::{Document, TantivyDocument, Term};
 
 /// Index format version.
-pub const INDEX_FORMAT_VERSION: u32 = 7;
+pub const INDEX_FORMAT_VERSION: u32 = 8;
 /// Oldest index format version this tantivy version can read.
 pub const INDEX_FORMAT_OLDEST_SUPPORTED_VERSION: u32 = 4;
 
@@ -286,7 +294,8 @@ pub fn version_string() -> &'static str {
 /// Defines tantivy's merging strategy
 pub mod merge_policy {
     pub use crate::indexer::{
-        DefaultMergePolicy, LogMergePolicy, MergeCandidate, MergePolicy, NoMergePolicy,
+        DefaultMergePolicy, LogMergePolicy, MergeCandidate, MergePlan, MergePolicy, NoMergePolicy,
+        PlannedMerge,
     };
 }
 
@@ -0,0 +1,441 @@
+//! Matches documents against a set of saved queries.
+//!
+//! A search runs one query against many documents. A percolator does the opposite: it
+//! registers many queries, and finds which of them match a given document. This is typically
+//! used for alerting, e.g. to notify users when a new document matches one of their saved
+//! searches.
+//!
+//! Running every saved query for every document would be too slow with thousands of queries.
+//! Instead, the terms of each query are extracted and indexed in a dedicated index. A document
+//! is first indexed on its own in RAM, and its terms are looked up in that dedicated index to
+//! select the candidate queries. Only the candidate queries then run against the document.
+//!
+//! Some queries, such as range queries, do not have terms that a matching document
+//! necessarily contains. They cannot be pre-selected, and run against every document.
+//!
+//! ```rust
+//! use tantivy::percolator::Percolator;
+//! use tantivy::query::QueryParser;
+//! use tantivy::schema::{Schema, FAST, INDEXED, TEXT};
+//! use tantivy::{doc, Index};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let price = schema_builder.add_u64_field("price", INDEXED | FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//!
+//! let query_parser = QueryParser::for_index(&index, vec![title]);
+//! let mut percolator = Percolator::for_index(&index)?;
+//! percolator.add_query(1, query_parser.parse_query("bicycle")?)?;
+//! percolator.add_query(2, query_parser.parse_query("\"electric car\"")?)?;
+//! percolator.add_query(3, query_parser.parse_query("price:<100")?)?;
+//! percolator.commit()?;
+//!
+//! let matching_query_ids =
+//!     percolator.match_document(&doc!(title => "A cheap bicycle", price => 80u64))?;
+//! assert_eq!(matching_query_ids, vec![1, 3]);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::collector::{Count, DocSetCollector};
+use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
+use crate::query::{
+    BooleanQuery, BoostQuery, ConstScoreQuery, Occur, PhraseQuery, Query, TermQuery, TermSetQuery,
+};
+use crate::schema::{Field, Schema, FAST, INDEXED};
+use crate::tokenizer::TokenizerManager;
+use crate::{
+    DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, SingleSegmentIndexWriter,
+    TantivyDocument, Term,
+};
+
+/// Matches documents against a set of saved queries.
+///
+/// See the [module documentation](self) for more details.
+///
+/// Queries are added and removed with [`Percolator::add_query`] and
+/// [`Percolator::remove_query`]. These changes are only taken into account by
+/// [`Percolator::match_document`] after a call to [`Percolator::commit`].
+pub struct Percolator {
+    schema: Schema,
+    tokenizers: TokenizerManager,
+    // Committed queries, by id.
+    queries: FxHashMap<u64, Box<dyn Query>>,
+    // Ids of the committed queries without extractable terms.
+    always_checked: FxHashSet<u64>,
+    // Changes since the last commit. `None` removes the query.
+    pending: Vec<(u64, Option<Box<dyn Query>>)>,
+    query_index_writer: IndexWriter,
+    query_index_reader: IndexReader,
+    query_id_field: Field,
+    query_term_field: Field,
+}
+
+impl Percolator {
+    /// Creates a percolator matching documents of the given schema.
+    ///
+    /// Text is tokenized with the default tokenizers.
+    pub fn new(schema: Schema) -> crate::Result<Percolator> {
+        Percolator::with_tokenizers(schema, TokenizerManager::default())
+    }
+
+    /// Creates a percolator matching documents as they would be indexed in `index`,
+    /// i.e. with the same schema and tokenizers.
+    pub fn for_index(index: &Index) -> crate::Result<Percolator> {
+        Percolator::with_tokenizers(index.schema(), index.tokenizers().clone())
+    }
+
+    fn with_tokenizers(schema: Schema, tokenizers: TokenizerManager) -> crate::Result<Percolator> {
+        let mut schema_builder = Schema::builder();
+        let query_id_field = schema_builder.add_u64_field("query_id", INDEXED | FAST);
+        let query_term_field = schema_builder.add_bytes_field("query_term", INDEXED);
+        let query_index = Index::create_in_ram(schema_builder.build());
+        let query_index_writer =
+            query_index.writer_with_num_threads(1, MEMORY_BUDGET_NUM_BYTES_MIN)?;
+        let query_index_reader = query_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(Percolator {
+            schema,
+            tokenizers,
+            queries: FxHashMap::default(),
+            always_checked: FxHashSet::default(),
+            pending: Vec::new(),
+            query_index_writer,
+            query_index_reader,
+            query_id_field,
+            query_term_field,
+        })
+    }
+
+    /// Adds a query, replacing the query previously added with the same id if any.
+    pub fn add_query(&mut self, query_id: u64, query: Box<dyn Query>) -> crate::Result<()> {
+        self.query_index_writer
+            .delete_term(Term::from_field_u64(self.query_id_field, query_id));
+        let mut query_doc = TantivyDocument::new();
+        query_doc.add_u64(self.query_id_field, query_id);
+        for term in extract_terms(query.as_ref()).unwrap_or_default() {
+            query_doc.add_bytes(self.query_term_field, &candidate_key(&term));
+        }
+        self.query_index_writer.add_document(query_doc)?;
+        self.pending.push((query_id, Some(query)));
+        Ok(())
+    }
+
+    /// Removes the query with the given id.
+    pub fn remove_query(&mut self, query_id: u64) {
+        self.query_index_writer
+            .delete_term(Term::from_field_u64(self.query_id_field, query_id));
+        self.pending.push((query_id, None));
+    }
+
+    /// Commits the queries added and removed since the last commit.
+    pub fn commit(&mut self) -> crate::Result<()> {
+        self.query_index_writer.commit()?;
+        self.query_index_reader.reload()?;
+        for (query_id, query_opt) in self.pending.drain(..) {
+            self.always_checked.remove(&query_id);
+            match query_opt {
+                Some(query) => {
+                    if extract_terms(query.as_ref()).is_none() {
+                        self.always_checked.insert(query_id);
+                    }
+                    self.queries.insert(query_id, query);
+                }
+                None => {
+                    self.queries.remove(&query_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of committed queries.
+    pub fn num_queries(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// Returns the ids of the committed queries matching `doc`, in increasing order.
+    pub fn match_document(&self, doc: &TantivyDocument) -> crate::Result<Vec<u64>> {
+        let doc_index = Index::builder()
+            .schema(self.schema.clone())
+            .tokenizers(self.tokenizers.clone())
+            .create_in_ram()?;
+        let mut doc_index_writer: SingleSegmentIndexWriter =
+            SingleSegmentIndexWriter::new(doc_index, MEMORY_BUDGET_NUM_BYTES_MIN)?;
+        doc_index_writer.add_document(doc.clone())?;
+        let doc_index = doc_index_writer.finalize()?;
+        let doc_searcher = doc_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?
+            .searcher();
+
+        let mut doc_terms = Vec::new();
+        for segment_reader in doc_searcher.segment_readers() {
+            for (field, field_entry) in self.schema.fields() {
+                if !field_entry.is_indexed() {
+                    continue;
+                }
+                let inverted_index = segment_reader.inverted_index(field)?;
+                let mut term_stream = inverted_index.terms().stream()?;
+                while term_stream.advance() {
+                    let mut key = field.field_id().to_be_bytes().to_vec();
+                    key.extend_from_slice(term_stream.key());
+                    doc_terms.push(Term::from_field_bytes(self.query_term_field, &key));
+                }
+            }
+        }
+
+        let mut candidates: FxHashSet<u64> = self.always_checked.clone();
+        let query_searcher = self.query_index_reader.searcher();
+        let candidate_docs: HashSet<DocAddress> =
+            query_searcher.search(&TermSetQuery::new(doc_terms), &DocSetCollector)?;
+        for doc_address in candidate_docs {
+            let query_ids = query_searcher
+                .segment_reader(doc_address.segment_ord)
+                .fast_fields()
+                .u64("query_id")?;
+            candidates.extend(query_ids.first(doc_address.doc_id));
+        }
+
+        let mut matching_query_ids = Vec::new();
+        for query_id in candidates {
+            let Some(query) = self.queries.get(&query_id) else {
+                continue;
+            };
+            if doc_searcher.search(query.as_ref(), &Count)? > 0 {
+                matching_query_ids.push(query_id);
+            }
+        }
+        matching_query_ids.sort_unstable();
+        Ok(matching_query_ids)
+    }
+}
+
+/// Identifies a term of the searched documents in the query index.
+fn candidate_key(term: &Term) -> Vec<u8> {
+    let mut key = term.field().field_id().to_be_bytes().to_vec();
+    key.extend_from_slice(term.serialized_value_bytes());
+    key
+}
+
+/// Extracts terms such that any document matching the query contains at least one of them.
+///
+/// Returns `None` if there is no such set of terms, or if it cannot be determined.
+fn extract_terms(query: &dyn Query) -> Option<Vec<Term>> {
+    if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+        return Some(vec![term_query.term().clone()]);
+    }
+    if let Some(phrase_query) = query.downcast_ref::<PhraseQuery>() {
+        // All of the terms are required, the first one is enough.
+        return phrase_query
+            .phrase_terms()
+            .into_iter()
+            .next()
+            .map(|term| vec![term]);
+    }
+    if let Some(boost_query) = query.downcast_ref::<BoostQuery>() {
+        return extract_terms(boost_query.query());
+    }
+    if let Some(const_score_query) = query.downcast_ref::<ConstScoreQuery>() {
+        return extract_terms(const_score_query.query());
+    }
+    if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+        let clauses = boolean_query.clauses();
+        // Any of the required clauses is enough: pick the one with the fewest terms.
+        let must_terms = clauses
+            .iter()
+            .filter(|(occur, _)| *occur == Occur::Must)
+            .filter_map(|(_, subquery)| extract_terms(subquery.as_ref()))
+            .min_by_key(Vec::len);
+        if must_terms.is_some() {
+            return must_terms;
+        }
+        if clauses.iter().any(|(occur, _)| *occur == Occur::Must) {
+            return None;
+        }
+        // Otherwise, one of the optional clauses has to match.
+        let mut should_terms = Vec::new();
+        for (_, subquery) in clauses.iter().filter(|(occur, _)| *occur == Occur::Should) {
+            should_terms.extend(extract_terms(subquery.as_ref())?);
+        }
+        if should_terms.is_empty() {
+            return None;
+        }
+        return Some(should_terms);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_terms, Percolator};
+    use crate::query::{AllQuery, Query, QueryParser, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{Index, Term};
+
+    fn percolator_test_index() -> Index {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("tag", STRING);
+        schema_builder.add_u64_field("price", INDEXED | FAST);
+        Index::create_in_ram(schema_builder.build())
+    }
+
+    fn parse(index: &Index, query: &str) -> Box<dyn Query> {
+        let title = index.schema().get_field("title").unwrap();
+        QueryParser::for_index(index, vec![title])
+            .parse_query(query)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_percolator_term_phrase_and_range_queries() -> crate::Result<()> {
+        let index = percolator_test_index();
+        let schema = index.schema();
+        let title = schema.get_field("title")?;
+        let tag = schema.get_field("tag")?;
+        let price = schema.get_field("price")?;
+
+        let mut percolator = Percolator::for_index(&index)?;
+        percolator.add_query(1, parse(&index, "bicycle"))?;
+        percolator.add_query(2, parse(&index, "\"electric bicycle\""))?;
+        percolator.add_query(3, parse(&index, "price:[100 TO 200]"))?;
+        percolator.add_query(4, parse(&index, "+tag:sale +price:<50"))?;
+        percolator.add_query(5, parse(&index, "car OR truck"))?;
+        percolator.add_query(6, parse(&index, "bicycle -tag:sale"))?;
+        percolator.commit()?;
+        assert_eq!(percolator.num_queries(), 6);
+
+        assert_eq!(
+            percolator.match_document(&doc!(title => "An electric bicycle", price => 150u64))?,
+            vec![1, 2, 3, 6]
+        );
+        assert_eq!(
+            percolator.match_document(
+                &doc!(title => "A bicycle, electric", tag => "sale", price => 20u64)
+            )?,
+            vec![1, 4]
+        );
+        assert_eq!(
+            percolator.match_document(&doc!(title => "A red truck", price => 1000u64))?,
+            vec![5]
+        );
+        assert!(percolator
+            .match_document(&doc!(title => "nothing to see"))?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_percolator_matches_like_a_search() -> crate::Result<()> {
+        let index = percolator_test_index();
+        let schema = index.schema();
+        let title = schema.get_field("title")?;
+        let price = schema.get_field("price")?;
+        let queries = [
+            "bicycle",
+            "\"red bicycle\"",
+            "bicycle AND red",
+            "red OR blue",
+            "price:>10",
+            "blue OR price:<5",
+            "bicycle -red",
+        ];
+        let docs = [
+            doc!(title => "a red bicycle", price => 3u64),
+            doc!(title => "a bicycle, red", price => 30u64),
+            doc!(title => "a blue car", price => 300u64),
+            doc!(title => "nothing"),
+        ];
+
+        let mut percolator = Percolator::for_index(&index)?;
+        for (query_id, query) in queries.iter().enumerate() {
+            percolator.add_query(query_id as u64, parse(&index, query))?;
+        }
+        percolator.commit()?;
+
+        for doc in docs {
+            let single_doc_index = Index::create_in_ram(schema.clone());
+            let mut index_writer = single_doc_index.writer_for_tests()?;
+            index_writer.add_document(doc.clone())?;
+            index_writer.commit()?;
+            let searcher = single_doc_index.reader()?.searcher();
+            let mut expected = Vec::new();
+            for (query_id, query) in queries.iter().enumerate() {
+                let query = parse(&single_doc_index, query);
+                if searcher.search(query.as_ref(), &crate::collector::Count)? > 0 {
+                    expected.push(query_id as u64);
+                }
+            }
+            assert_eq!(percolator.match_document(&doc)?, expected, "{doc:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_percolator_add_remove_queries() -> crate::Result<()> {
+        let index = percolator_test_index();
+        let title = index.schema().get_field("title")?;
+        let mut percolator = Percolator::for_index(&index)?;
+        percolator.add_query(1, parse(&index, "bicycle"))?;
+        percolator.add_query(2, parse(&index, "price:>10"))?;
+        // Not committed yet.
+        assert!(percolator
+            .match_document(&doc!(title => "bicycle"))?
+            .is_empty());
+        percolator.commit()?;
+        assert_eq!(
+            percolator.match_document(&doc!(title => "bicycle"))?,
+            vec![1]
+        );
+
+        // Replacing a query updates its terms.
+        percolator.add_query(1, parse(&index, "car"))?;
+        percolator.remove_query(2);
+        percolator.commit()?;
+        assert_eq!(percolator.num_queries(), 1);
+        assert!(percolator
+            .match_document(&doc!(title => "bicycle"))?
+            .is_empty());
+        assert_eq!(percolator.match_document(&doc!(title => "car"))?, vec![1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_terms() {
+        let index = percolator_test_index();
+        let title = index.schema().get_field("title").unwrap();
+        let extract = |query: &str| {
+            extract_terms(parse(&index, query).as_ref()).map(|terms| {
+                terms
+                    .iter()
+                    .map(|term| term.value().as_str().unwrap_or("").to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(extract("a"), Some(vec!["a".to_string()]));
+        assert_eq!(extract("\"a b\""), Some(vec!["a".to_string()]));
+        assert_eq!(extract("+a +\"b c\" -d"), Some(vec!["a".to_string()]));
+        assert_eq!(
+            extract("a OR b^2"),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(extract("+price:>3 +a"), Some(vec!["a".to_string()]));
+        // A range may match documents without any term of the query.
+        assert_eq!(extract("a OR price:>3"), None);
+        assert_eq!(extract("* -a"), None);
+        assert_eq!(extract_terms(&AllQuery), None);
+        let term_query =
+            TermQuery::new(Term::from_field_text(title, "a"), IndexRecordOption::Basic);
+        assert!(extract_terms(&term_query).is_some());
+    }
+}
@@ -28,6 +28,13 @@
 //! * *VIntPosDeltas* := *VIntPosDelta*^(*P* % 128).
 //!
 //! The skip widths encoded separately makes it easy and fast to rapidly skip over n positions.
+//!
+//! Fields recording [offsets](crate::schema::TextFieldIndexing::set_offsets) follow the positions
+//! of each term with the byte offsets of its occurrences, encoded the same way as a sequence of
+//! `offset_from`, `offset_to - offset_from` values. The positions are then prefixed with their
+//! number of bytes:
+//! * *PositionsAndOffsets* := *NumPositionsBytes* *Positions* *Offsets*
+//! * *NumPositionsBytes* := the number of bytes of *Positions*, encoded as a variable byte integer.
 mod reader;
 mod serializer;
 
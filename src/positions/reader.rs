@@ -54,6 +54,25 @@ impl PositionReader {
         })
     }
 
+    /// Open the term positions and offsets serialized by
+    /// [`PositionSerializer::close_term_with_offsets`](crate::positions::PositionSerializer::close_term_with_offsets).
+    ///
+    /// Returns a reader for the positions, and a reader for the offsets.
+    pub fn open_with_offsets(mut data: OwnedBytes) -> io::Result<(PositionReader, PositionReader)> {
+        let positions_num_bytes = VInt::deserialize(&mut data)?.0 as usize;
+        if positions_num_bytes > data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "positions exceed the data of the term",
+            ));
+        }
+        let (positions_data, offsets_data) = data.split(positions_num_bytes);
+        Ok((
+            PositionReader::open(positions_data)?,
+            PositionReader::open(offsets_data)?,
+        ))
+    }
+
     fn reset(&mut self) {
         self.positions = self.original_positions.clone();
         self.bit_widths = self.original_bit_widths.clone();
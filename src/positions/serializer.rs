@@ -86,6 +86,35 @@ impl<W: io::Write> PositionSerializer<W> {
         Ok(())
     }
 
+    /// Close the positions for the current term, and appends the values of the current term of
+    /// `offsets_serializer`, closing it as well.
+    ///
+    /// The positions are prefixed with their number of bytes, so that both streams can be read
+    /// back with [`PositionReader::open_with_offsets`](crate::positions::PositionReader::open_with_offsets).
+    pub fn close_term_with_offsets<O: io::Write>(
+        &mut self,
+        offsets_serializer: &mut PositionSerializer<O>,
+    ) -> io::Result<()> {
+        let mut positions = Vec::new();
+        self.take_term(&mut positions);
+        let mut offsets = Vec::new();
+        offsets_serializer.take_term(&mut offsets);
+        VInt(positions.len() as u64).serialize(&mut self.positions_wrt)?;
+        self.positions_wrt.write_all(&positions)?;
+        self.positions_wrt.write_all(&offsets)?;
+        Ok(())
+    }
+
+    /// Appends the encoded values of the current term to `output`, and starts a new term.
+    fn take_term(&mut self, output: &mut Vec<u8>) {
+        self.flush_block();
+        VInt(self.bit_widths.len() as u64).serialize_into_vec(output);
+        output.extend_from_slice(&self.bit_widths);
+        output.extend_from_slice(&self.positions_buffer);
+        self.bit_widths.clear();
+        self.positions_buffer.clear();
+    }
+
     /// Close the positions for this term and flushes the data.
     pub fn close(mut self) -> io::Result<()> {
         self.positions_wrt.flush()
@@ -5,6 +5,7 @@ use common::VInt;
 use crate::directory::{FileSlice, OwnedBytes};
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
+use crate::postings::dense_postings::{DensePostings, DENSE_POSTINGS_MARKER};
 use crate::postings::{BlockInfo, FreqReadingOption, SkipReader};
 use crate::query::Bm25Weight;
 use crate::schema::IndexRecordOption;
@@ -31,6 +32,10 @@ pub struct BlockSegmentPostings {
     doc_freq: u32,
     data: OwnedBytes,
     skip_reader: SkipReader,
+    // Set if the posting list is serialized as a bitset, in which case the skip reader is
+    // unused.
+    dense_postings: Option<DensePostings>,
+    dense_block_ord: usize,
 }
 
 fn decode_bitpacked_block(
@@ -75,16 +80,30 @@ fn decode_vint_block(
     }
 }
 
-fn split_into_skips_and_postings(
-    doc_freq: u32,
-    mut bytes: OwnedBytes,
-) -> io::Result<(Option<OwnedBytes>, OwnedBytes)> {
+enum PostingsData {
+    Blocks {
+        skip_data: Option<OwnedBytes>,
+        postings_data: OwnedBytes,
+    },
+    Dense(DensePostings),
+}
+
+fn split_into_skips_and_postings(doc_freq: u32, mut bytes: OwnedBytes) -> io::Result<PostingsData> {
     if doc_freq < COMPRESSION_BLOCK_SIZE as u32 {
-        return Ok((None, bytes));
+        return Ok(PostingsData::Blocks {
+            skip_data: None,
+            postings_data: bytes,
+        });
     }
-    let skip_len = VInt::deserialize_u64(&mut bytes)? as usize;
-    let (skip_data, postings_data) = bytes.split(skip_len);
-    Ok((Some(skip_data), postings_data))
+    let skip_len = VInt::deserialize_u64(&mut bytes)?;
+    if skip_len == DENSE_POSTINGS_MARKER {
+        return Ok(PostingsData::Dense(DensePostings::open(doc_freq, bytes)?));
+    }
+    let (skip_data, postings_data) = bytes.split(skip_len as usize);
+    Ok(PostingsData::Blocks {
+        skip_data: Some(skip_data),
+        postings_data,
+    })
 }
 
 impl BlockSegmentPostings {
@@ -101,7 +120,18 @@ impl BlockSegmentPostings {
         requested_option: IndexRecordOption,
     ) -> io::Result<BlockSegmentPostings> {
         let bytes = data.read_bytes()?;
-        let (skip_data_opt, postings_data) = split_into_skips_and_postings(doc_freq, bytes)?;
+        let (skip_data_opt, postings_data, dense_postings) =
+            match split_into_skips_and_postings(doc_freq, bytes)? {
+                PostingsData::Blocks {
+                    skip_data,
+                    postings_data,
+                } => (skip_data, postings_data, None),
+                PostingsData::Dense(dense_postings) => {
+                    // Dense posting lists do not have term frequencies.
+                    record_option = IndexRecordOption::Basic;
+                    (None, OwnedBytes::empty(), Some(dense_postings))
+                }
+            };
         let skip_reader = match skip_data_opt {
             Some(skip_data) => {
                 let block_count = doc_freq as usize / COMPRESSION_BLOCK_SIZE;
@@ -116,6 +146,9 @@ impl BlockSegmentPostings {
                 }
                 SkipReader::new(skip_data, doc_freq, record_option)
             }
+            None if dense_postings.is_some() => {
+                SkipReader::new(OwnedBytes::empty(), 0, record_option)
+            }
             None => SkipReader::new(OwnedBytes::empty(), doc_freq, record_option),
         };
 
@@ -134,6 +167,8 @@ impl BlockSegmentPostings {
             doc_freq,
             data: postings_data,
             skip_reader,
+            dense_postings,
+            dense_block_ord: 0,
         };
         block_segment_postings.load_block();
         Ok(block_segment_postings)
@@ -152,6 +187,11 @@ impl BlockSegmentPostings {
         if let Some(score) = self.block_max_score_cache {
             return score;
         }
+        if self.dense_postings.is_some() {
+            // Dense posting lists do not have term frequencies, and hence no block max
+            // information.
+            return bm25_weight.max_score();
+        }
         if let Some(skip_reader_max_score) = self.skip_reader.block_max_score(bm25_weight) {
             // if we are on a full block, the skip reader should have the block max information
             // for us
@@ -193,15 +233,24 @@ impl BlockSegmentPostings {
     //
     // This does not reset the positions list.
     pub(crate) fn reset(&mut self, doc_freq: u32, postings_data: OwnedBytes) -> io::Result<()> {
-        let (skip_data_opt, postings_data) =
-            split_into_skips_and_postings(doc_freq, postings_data)?;
-        self.data = postings_data;
         self.block_max_score_cache = None;
         self.block_loaded = false;
-        if let Some(skip_data) = skip_data_opt {
-            self.skip_reader.reset(skip_data, doc_freq);
-        } else {
-            self.skip_reader.reset(OwnedBytes::empty(), doc_freq);
+        self.dense_block_ord = 0;
+        match split_into_skips_and_postings(doc_freq, postings_data)? {
+            PostingsData::Blocks {
+                skip_data,
+                postings_data,
+            } => {
+                self.data = postings_data;
+                self.dense_postings = None;
+                self.skip_reader
+                    .reset(skip_data.unwrap_or_else(OwnedBytes::empty), doc_freq);
+            }
+            PostingsData::Dense(dense_postings) => {
+                self.data = OwnedBytes::empty();
+                self.dense_postings = Some(dense_postings);
+                self.skip_reader.reset(OwnedBytes::empty(), 0);
+            }
         }
         self.doc_freq = doc_freq;
         self.load_block();
@@ -292,6 +341,15 @@ impl BlockSegmentPostings {
     /// If all docs are smaller than target, the block loaded may be empty,
     /// or be the last an incomplete VInt block.
     pub(crate) fn seek_block(&mut self, target_doc: DocId) {
+        if let Some(dense_postings) = self.dense_postings.as_ref() {
+            let block_ord = dense_postings.seek_block(self.dense_block_ord, target_doc);
+            if block_ord != self.dense_block_ord {
+                self.dense_block_ord = block_ord;
+                self.block_max_score_cache = None;
+                self.block_loaded = false;
+            }
+            return;
+        }
         if self.skip_reader.seek(target_doc) {
             self.block_max_score_cache = None;
             self.block_loaded = false;
@@ -306,6 +364,12 @@ impl BlockSegmentPostings {
         if self.block_is_loaded() {
             return;
         }
+        if let Some(dense_postings) = self.dense_postings.as_ref() {
+            self.doc_decoder
+                .uncompress_dense_block(dense_postings, self.dense_block_ord);
+            self.block_loaded = true;
+            return;
+        }
         let offset = self.skip_reader.byte_offset();
         match self.skip_reader.block_info() {
             BlockInfo::BitPacked {
@@ -354,7 +418,11 @@ impl BlockSegmentPostings {
 
     /// Advance to the next block.
     pub fn advance(&mut self) {
-        self.skip_reader.advance();
+        if self.dense_postings.is_some() {
+            self.dense_block_ord += 1;
+        } else {
+            self.skip_reader.advance();
+        }
         self.block_loaded = false;
         self.block_max_score_cache = None;
         self.load_block();
@@ -371,17 +439,26 @@ impl BlockSegmentPostings {
             doc_freq: 0,
             data: OwnedBytes::empty(),
             skip_reader: SkipReader::new(OwnedBytes::empty(), 0, IndexRecordOption::Basic),
+            dense_postings: None,
+            dense_block_ord: 0,
         }
     }
 
-    pub(crate) fn skip_reader(&self) -> &SkipReader {
-        &self.skip_reader
+    /// Returns the last doc of the current block, or `TERMINATED` for the last, incomplete
+    /// block.
+    pub(crate) fn last_doc_in_block(&self) -> DocId {
+        if let Some(dense_postings) = self.dense_postings.as_ref() {
+            return dense_postings.last_doc_in_block(self.dense_block_ord);
+        }
+        self.skip_reader.last_doc_in_block()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use common::HasLen;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     use super::BlockSegmentPostings;
     use crate::docset::{DocSet, TERMINATED};
@@ -504,6 +581,78 @@ mod tests {
         Ok(block_postings)
     }
 
+    // About `percent`% of the docs, picked randomly.
+    fn sample_docs(num_docs: usize, percent: u32) -> Vec<DocId> {
+        let mut rng = StdRng::seed_from_u64(num_docs as u64);
+        (0u32..)
+            .filter(|_| rng.random_bool(percent as f64 / 100.0))
+            .take(num_docs)
+            .collect()
+    }
+
+    #[test]
+    fn test_block_segment_postings_representations() -> crate::Result<()> {
+        let stride_docs = |num_docs: u32, stride: u32| (0..num_docs).map(|i| i * stride).collect();
+        let cases: Vec<(Vec<DocId>, bool)> = vec![
+            (vec![17], false),
+            (sample_docs(127, 50), false),
+            (sample_docs(128, 50), true),
+            (sample_docs(129, 50), true),
+            (sample_docs(256, 50), true),
+            (sample_docs(1_000, 80), true),
+            (sample_docs(1_000, 5), false),
+            (stride_docs(300, 2), false),
+            (stride_docs(1_000, 1), false),
+        ];
+        for (docs, expect_dense) in cases {
+            let block_postings = build_block_postings(&docs)?;
+            assert_eq!(
+                block_postings.dense_postings.is_some(),
+                expect_dense,
+                "doc_freq {}",
+                docs.len()
+            );
+            let last_doc = *docs.last().unwrap();
+            let targets: Vec<DocId> = (0..last_doc + 3).step_by(7).collect();
+            crate::postings::tests::test_skip_against_unoptimized(
+                || {
+                    Box::new(SegmentPostings::from_block_postings(
+                        block_postings.clone(),
+                        None,
+                    ))
+                },
+                targets,
+            );
+
+            let mut block_postings = block_postings;
+            let mut read_docs = Vec::new();
+            while !block_postings.docs().is_empty() {
+                read_docs.extend_from_slice(block_postings.docs());
+                block_postings.advance();
+            }
+            assert_eq!(read_docs, docs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dense_block_segment_postings_seek() -> crate::Result<()> {
+        let docs = sample_docs(1_000, 70);
+        let block_postings = build_block_postings(&docs)?;
+        assert!(block_postings.dense_postings.is_some());
+        let mut postings = SegmentPostings::from_block_postings(block_postings, None);
+        for &target in &[3, 200, 201, 900, 1_400] {
+            let expected = docs
+                .iter()
+                .copied()
+                .find(|&doc| doc >= target)
+                .unwrap_or(TERMINATED);
+            assert_eq!(postings.seek(target), expected);
+        }
+        assert_eq!(postings.seek(*docs.last().unwrap() + 1), TERMINATED);
+        Ok(())
+    }
+
     #[test]
     fn test_block_segment_postings_seek() -> crate::Result<()> {
         let mut docs = vec![0];
@@ -1,5 +1,7 @@
 use bitpacking::{BitPacker, BitPacker4x};
 
+use crate::postings::dense_postings::DensePostings;
+
 pub const COMPRESSION_BLOCK_SIZE: usize = BitPacker4x::BLOCK_LEN;
 // in vint encoding, each byte stores 7 bits of data, so we need at most 32 / 7 = 4.57 bytes to
 // store a u32 in the worst case, rounding up to 5 bytes total
@@ -149,6 +151,15 @@ impl BlockDecoder {
         res
     }
 
+    /// Decodes a block of a dense posting list, padded with `TERMINATED`.
+    pub(crate) fn uncompress_dense_block(
+        &mut self,
+        dense_postings: &DensePostings,
+        block_ord: usize,
+    ) {
+        self.output_len = dense_postings.decode_block(block_ord, &mut self.output);
+    }
+
     #[inline]
     pub fn output_array(&self) -> &[u32] {
         &self.output[..self.output_len]
@@ -0,0 +1,201 @@
+use std::io;
+
+use common::{BinarySerializable, OwnedBytes, VInt};
+
+use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+use crate::{DocId, TERMINATED};
+
+/// Marker written in place of the length of the skip data, identifying a dense posting list.
+///
+/// Block encoded posting lists always have a non-empty skip data.
+pub(crate) const DENSE_POSTINGS_MARKER: u64 = 0;
+
+/// Number of bytes of the dense representation of the given posting list, marker included.
+pub(crate) fn dense_postings_num_bytes(docs: &[DocId]) -> usize {
+    let num_words = num_words(docs);
+    let num_full_blocks = docs.len() / COMPRESSION_BLOCK_SIZE;
+    1 + VInt(num_words as u64).serialize_into(&mut [0u8; 10]) + num_full_blocks * 4 + num_words * 8
+}
+
+fn num_words(docs: &[DocId]) -> usize {
+    docs.last()
+        .map(|&last_doc| last_doc as usize / 64 + 1)
+        .unwrap_or(0)
+}
+
+/// Serializes a posting list without term frequencies as a bitset over the doc ids.
+///
+/// The format is the marker, the number of 64 bits words of the bitset, the last doc of each
+/// full block of `COMPRESSION_BLOCK_SIZE` docs, and the words of the bitset. The documents are
+/// read by blocks, like the block encoded posting lists, and the last docs of the blocks make
+/// it possible to seek.
+pub(crate) fn serialize_dense_postings(
+    docs: &[DocId],
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut words = vec![0u64; num_words(docs)];
+    for &doc in docs {
+        words[doc as usize / 64] |= 1u64 << (doc % 64);
+    }
+    VInt(DENSE_POSTINGS_MARKER).serialize(output)?;
+    VInt(words.len() as u64).serialize(output)?;
+    for block in docs.chunks_exact(COMPRESSION_BLOCK_SIZE) {
+        output.write_all(&block[COMPRESSION_BLOCK_SIZE - 1].to_le_bytes())?;
+    }
+    for word in words {
+        output.write_all(&word.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// A posting list serialized by [`serialize_dense_postings`].
+///
+/// Block `block_ord` holds the docs of ranks `block_ord * COMPRESSION_BLOCK_SIZE` and above,
+/// the blocks after the last doc being empty.
+#[derive(Clone)]
+pub(crate) struct DensePostings {
+    doc_freq: u32,
+    block_last_docs: OwnedBytes,
+    words: OwnedBytes,
+}
+
+impl DensePostings {
+    /// Opens a dense posting list, the marker having already been read.
+    pub(crate) fn open(doc_freq: u32, mut data: OwnedBytes) -> io::Result<DensePostings> {
+        let num_words = VInt::deserialize_u64(&mut data)? as usize;
+        let num_full_blocks = doc_freq as usize / COMPRESSION_BLOCK_SIZE;
+        let (block_last_docs, words) = data.split(num_full_blocks * 4);
+        if words.len() != num_words * 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Dense posting list has an unexpected length",
+            ));
+        }
+        Ok(DensePostings {
+            doc_freq,
+            block_last_docs,
+            words,
+        })
+    }
+
+    fn num_full_blocks(&self) -> usize {
+        self.block_last_docs.len() / 4
+    }
+
+    fn word(&self, word_ord: usize) -> u64 {
+        u64::from_le_bytes(self.words[word_ord * 8..][..8].try_into().unwrap())
+    }
+
+    /// Returns the last doc of the block, or `TERMINATED` if the block is not full.
+    pub(crate) fn last_doc_in_block(&self, block_ord: usize) -> DocId {
+        if block_ord >= self.num_full_blocks() {
+            return TERMINATED;
+        }
+        u32::from_le_bytes(
+            self.block_last_docs[block_ord * 4..][..4]
+                .try_into()
+                .unwrap(),
+        )
+    }
+
+    /// Returns the first block, starting from `block_ord`, that may contain `target`.
+    pub(crate) fn seek_block(&self, block_ord: usize, target: DocId) -> usize {
+        let mut start = block_ord;
+        let mut end = self.num_full_blocks().max(block_ord);
+        while start < end {
+            let mid = start + (end - start) / 2;
+            if self.last_doc_in_block(mid) < target {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        start
+    }
+
+    /// Decodes the docs of the block in `output`, padding it with `TERMINATED`.
+    ///
+    /// Returns the number of docs of the block.
+    pub(crate) fn decode_block(
+        &self,
+        block_ord: usize,
+        output: &mut [DocId; COMPRESSION_BLOCK_SIZE],
+    ) -> usize {
+        let first_rank = block_ord.saturating_mul(COMPRESSION_BLOCK_SIZE);
+        let num_docs = (self.doc_freq as usize)
+            .saturating_sub(first_rank)
+            .min(COMPRESSION_BLOCK_SIZE);
+        output[num_docs..].fill(TERMINATED);
+        if num_docs == 0 {
+            return 0;
+        }
+        let start_doc = if block_ord == 0 {
+            0
+        } else {
+            self.last_doc_in_block(block_ord - 1) + 1
+        };
+        let mut word_ord = start_doc as usize / 64;
+        let mut word = self.word(word_ord) & (u64::MAX << (start_doc % 64));
+        let mut len = 0;
+        while len < num_docs {
+            while word == 0 {
+                word_ord += 1;
+                word = self.word(word_ord);
+            }
+            output[len] = (word_ord * 64) as DocId + word.trailing_zeros();
+            word &= word - 1;
+            len += 1;
+        }
+        num_docs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dense_postings_num_bytes, serialize_dense_postings, DensePostings};
+    use crate::directory::OwnedBytes;
+    use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+    use crate::{DocId, TERMINATED};
+
+    fn open_dense_postings(docs: &[DocId]) -> DensePostings {
+        let mut buffer = Vec::new();
+        serialize_dense_postings(docs, &mut buffer).unwrap();
+        assert_eq!(buffer.len(), dense_postings_num_bytes(docs));
+        assert_eq!(buffer[0], 128u8);
+        DensePostings::open(docs.len() as u32, OwnedBytes::new(buffer[1..].to_vec())).unwrap()
+    }
+
+    #[test]
+    fn test_dense_postings_blocks() {
+        let docs: Vec<DocId> = (0..1_000u32).filter(|doc| doc % 3 != 1).collect();
+        let dense_postings = open_dense_postings(&docs);
+        let mut output = [0u32; COMPRESSION_BLOCK_SIZE];
+        let mut decoded_docs = Vec::new();
+        for block_ord in 0..=docs.len() / COMPRESSION_BLOCK_SIZE {
+            let len = dense_postings.decode_block(block_ord, &mut output);
+            assert!(output[len..].iter().all(|&doc| doc == TERMINATED));
+            if len == COMPRESSION_BLOCK_SIZE {
+                assert_eq!(dense_postings.last_doc_in_block(block_ord), output[len - 1]);
+            } else {
+                assert_eq!(dense_postings.last_doc_in_block(block_ord), TERMINATED);
+            }
+            decoded_docs.extend_from_slice(&output[..len]);
+        }
+        assert_eq!(decoded_docs, docs);
+        assert_eq!(dense_postings.decode_block(100, &mut output), 0);
+    }
+
+    #[test]
+    fn test_dense_postings_seek_block() {
+        let docs: Vec<DocId> = (0..2 * COMPRESSION_BLOCK_SIZE as u32)
+            .map(|doc| doc * 2)
+            .collect();
+        let dense_postings = open_dense_postings(&docs);
+        assert_eq!(dense_postings.seek_block(0, 0), 0);
+        assert_eq!(dense_postings.seek_block(0, 254), 0);
+        assert_eq!(dense_postings.seek_block(0, 255), 1);
+        assert_eq!(dense_postings.seek_block(1, 10), 1);
+        assert_eq!(dense_postings.seek_block(0, 511), 2);
+        assert_eq!(dense_postings.seek_block(2, 10_000), 2);
+    }
+}
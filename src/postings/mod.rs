@@ -6,6 +6,7 @@ pub(crate) use self::block_search::branchless_binary_search;
 
 mod block_segment_postings;
 pub(crate) mod compression;
+mod dense_postings;
 mod indexing_context;
 mod json_postings_writer;
 mod loaded_postings;
@@ -44,15 +45,20 @@ pub(crate) enum FreqReadingOption {
 pub(crate) mod tests {
     use std::mem;
 
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
     use super::{InvertedIndexSerializer, Postings};
+    use crate::collector::{Count, TopDocs};
     use crate::docset::{DocSet, TERMINATED};
     use crate::fieldnorm::FieldNormReader;
     use crate::index::{Index, SegmentComponent, SegmentReader};
     use crate::indexer::operation::AddOperation;
     use crate::indexer::SegmentWriter;
-    use crate::query::Scorer;
+    use crate::query::{BooleanQuery, Occur, Query, Scorer, TermQuery};
     use crate::schema::{
-        Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, INDEXED, TEXT,
+        Field, IndexRecordOption, Schema, TantivyDocument, Term, TextFieldIndexing, TextOptions,
+        INDEXED, STRING, TEXT,
     };
     use crate::tokenizer::{SimpleTokenizer, MAX_TOKEN_LEN};
     use crate::{DocId, HasLen, IndexWriter, Score};
@@ -501,6 +507,116 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_boolean_queries_over_mixed_postings_representations() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let flag_field = schema_builder.add_text_field("flag", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let num_docs = 2_000u32;
+        // "dense" is serialized as a bitset, "single" is inlined in the term dictionary,
+        // and the other terms are block encoded.
+        let mut rng = StdRng::seed_from_u64(2u64);
+        let dense_docs: Vec<bool> = (0..num_docs).map(|_| rng.random_bool(0.6)).collect();
+        let flags_of_doc = |doc: u32| -> Vec<&'static str> {
+            let mut flags = Vec::new();
+            if dense_docs[doc as usize] {
+                flags.push("dense");
+            }
+            if doc % 7 == 0 {
+                flags.push("every7");
+            }
+            if doc == 1_234 {
+                flags.push("single");
+            }
+            if doc == 5 || doc == 1_500 {
+                flags.push("pair");
+            }
+            flags
+        };
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc in 0..num_docs {
+            let mut document = TantivyDocument::default();
+            for flag in flags_of_doc(doc) {
+                document.add_text(flag_field, flag);
+            }
+            index_writer.add_document(document)?;
+            if doc == num_docs / 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+
+        let term_query = |flag: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(flag_field, flag),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let clauses_list: Vec<Vec<(Occur, &str)>> = vec![
+            vec![(Occur::Must, "dense"), (Occur::Must, "every7")],
+            vec![(Occur::Must, "dense"), (Occur::Must, "single")],
+            vec![(Occur::Must, "dense"), (Occur::MustNot, "every7")],
+            vec![(Occur::Should, "single"), (Occur::Should, "pair")],
+            vec![(Occur::Should, "dense"), (Occur::Should, "pair")],
+            vec![(Occur::Must, "every7"), (Occur::Should, "single")],
+            vec![(Occur::Must, "pair"), (Occur::MustNot, "dense")],
+        ];
+        let check_queries = |index: &Index| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            for clauses in &clauses_list {
+                let matches = |doc: u32| {
+                    let flags = flags_of_doc(doc);
+                    let has_must = clauses.iter().any(|(occur, _)| *occur == Occur::Must);
+                    clauses.iter().all(|(occur, flag)| match occur {
+                        Occur::Must => flags.contains(flag),
+                        Occur::MustNot => !flags.contains(flag),
+                        Occur::Should => true,
+                    }) && (has_must
+                        || clauses
+                            .iter()
+                            .any(|(occur, flag)| *occur == Occur::Should && flags.contains(flag)))
+                };
+                let expected_count = (0..num_docs).filter(|&doc| matches(doc)).count();
+                let query = BooleanQuery::new(
+                    clauses
+                        .iter()
+                        .map(|(occur, flag)| (*occur, term_query(flag)))
+                        .collect(),
+                );
+                assert_eq!(
+                    searcher.search(&query, &Count)?,
+                    expected_count,
+                    "{clauses:?}"
+                );
+                let top_docs = searcher.search(&query, &TopDocs::with_limit(3).order_by_score())?;
+                assert_eq!(top_docs.len(), expected_count.min(3), "{clauses:?}");
+            }
+            Ok(())
+        };
+        check_queries(&index)?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        check_queries(&index)?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let inverted_index = searcher.segment_reader(0).inverted_index(flag_field)?;
+        let term_info = |flag: &str| {
+            inverted_index
+                .get_term_info(&Term::from_field_text(flag_field, flag))
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(term_info("single").inline_posting, Some((1_234, 0)));
+        assert!(term_info("single").postings_range.is_empty());
+        assert_eq!(term_info("pair").inline_posting, None);
+        assert_eq!(term_info("dense").inline_posting, None);
+        Ok(())
+    }
+
     /// Wraps a given docset, and forward all call but the
     /// `.skip_next(...)`. This is useful to test that a specialized
     /// implementation of `.skip_next(...)` is consistent
@@ -98,6 +98,8 @@ pub(crate) fn serialize_postings(
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// Byte offset of the text being indexed, within the values of the field joined by a space.
+    pub start_offset: u32,
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -114,6 +116,21 @@ pub(crate) trait PostingsWriter: Send + Sync {
     ///   information.
     fn subscribe(&mut self, doc: DocId, pos: u32, term: &IndexingTerm, ctx: &mut IndexingContext);
 
+    /// Record that a document contains a term at a given position, spanning the given byte
+    /// offsets of the text.
+    ///
+    /// The offsets are ignored unless the field records them.
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        pos: u32,
+        _offsets: Range<u32>,
+        term: &IndexingTerm,
+        ctx: &mut IndexingContext,
+    ) {
+        self.subscribe(doc, pos, term, ctx);
+    }
+
     /// Serializes the postings on disk.
     /// The actual serialization format is handled by the `PostingsSerializer`.
     fn serialize(
@@ -151,7 +168,9 @@ pub(crate) trait PostingsWriter: Send + Sync {
             term_buffer.append_bytes(token.text.as_bytes());
             let start_position = indexing_position.end_position + token.position as u32;
             end_position = end_position.max(start_position + token.position_length as u32);
-            self.subscribe(doc_id, start_position, term_buffer, ctx);
+            let offsets = indexing_position.start_offset + token.offset_from as u32
+                ..indexing_position.start_offset + token.offset_to as u32;
+            self.subscribe_with_offsets(doc_id, start_position, offsets, term_buffer, ctx);
             num_tokens += 1;
         });
 
@@ -205,6 +224,18 @@ impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
         position: u32,
         term: &IndexingTerm,
         ctx: &mut IndexingContext,
+    ) {
+        self.subscribe_with_offsets(doc, position, 0..0, term, ctx);
+    }
+
+    #[inline]
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        position: u32,
+        offsets: Range<u32>,
+        term: &IndexingTerm,
+        ctx: &mut IndexingContext,
     ) {
         debug_assert!(term.serialized_term().len() >= 4);
         self.total_num_tokens += 1;
@@ -216,12 +247,12 @@ impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
                     recorder.close_doc(arena);
                     recorder.new_doc(doc, arena);
                 }
-                recorder.record_position(position, arena);
+                recorder.record_position_and_offsets(position, offsets.clone(), arena);
                 recorder
             } else {
                 let mut recorder = Rec::default();
                 recorder.new_doc(doc, arena);
-                recorder.record_position(position, arena);
+                recorder.record_position_and_offsets(position, offsets.clone(), arena);
                 recorder
             }
         });
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use common::read_u32_vint;
 use stacker::{ExpUnrolledLinkedList, MemoryArena};
 
@@ -10,6 +12,7 @@ const POSITION_END: u32 = 0;
 pub(crate) struct BufferLender {
     buffer_u8: Vec<u8>,
     buffer_u32: Vec<u32>,
+    buffer_offsets: Vec<u32>,
 }
 
 impl BufferLender {
@@ -22,6 +25,16 @@ impl BufferLender {
         self.buffer_u32.clear();
         (&mut self.buffer_u8, &mut self.buffer_u32)
     }
+    pub fn lend_all_with_offsets(&mut self) -> (&mut Vec<u8>, &mut Vec<u32>, &mut Vec<u32>) {
+        self.buffer_u8.clear();
+        self.buffer_u32.clear();
+        self.buffer_offsets.clear();
+        (
+            &mut self.buffer_u8,
+            &mut self.buffer_u32,
+            &mut self.buffer_offsets,
+        )
+    }
 }
 
 pub struct VInt32Reader<'a> {
@@ -64,6 +77,18 @@ pub(crate) trait Recorder: Copy + Default + Send + Sync + 'static {
     /// Record the position of a term. For each document,
     /// this method will be called `term_freq` times.
     fn record_position(&mut self, position: u32, arena: &mut MemoryArena);
+    /// Record the position of a term, along with the byte offsets of the token in the text.
+    ///
+    /// Recorders which do not store offsets only record the position.
+    #[inline]
+    fn record_position_and_offsets(
+        &mut self,
+        position: u32,
+        _offsets: Range<u32>,
+        arena: &mut MemoryArena,
+    ) {
+        self.record_position(position, arena);
+    }
     /// Close the document. It will help record the term frequency.
     fn close_doc(&mut self, arena: &mut MemoryArena);
     /// Pushes the postings information to the serializer.
@@ -271,6 +296,97 @@ impl Recorder for TfAndPositionRecorder {
     }
 }
 
+/// Recorder encoding term frequencies, positions, and the byte offsets of the tokens.
+#[derive(Clone, Copy, Default)]
+pub struct TfPositionAndOffsetRecorder {
+    stack: ExpUnrolledLinkedList,
+    current_doc: DocId,
+    term_doc_freq: u32,
+}
+
+impl Recorder for TfPositionAndOffsetRecorder {
+    #[inline]
+    fn current_doc(&self) -> DocId {
+        self.current_doc
+    }
+
+    #[inline]
+    fn new_doc(&mut self, doc: DocId, arena: &mut MemoryArena) {
+        let delta = doc - self.current_doc;
+        self.current_doc = doc;
+        self.term_doc_freq += 1u32;
+        self.stack.writer(arena).write_u32_vint(delta);
+    }
+
+    #[inline]
+    fn record_position(&mut self, position: u32, arena: &mut MemoryArena) {
+        self.record_position_and_offsets(position, 0..0, arena);
+    }
+
+    #[inline]
+    fn record_position_and_offsets(
+        &mut self,
+        position: u32,
+        offsets: Range<u32>,
+        arena: &mut MemoryArena,
+    ) {
+        let mut writer = self.stack.writer(arena);
+        writer.write_u32_vint(position.wrapping_add(1u32));
+        writer.write_u32_vint(offsets.start);
+        writer.write_u32_vint(offsets.end - offsets.start);
+    }
+
+    #[inline]
+    fn close_doc(&mut self, arena: &mut MemoryArena) {
+        self.stack.writer(arena).write_u32_vint(POSITION_END);
+    }
+
+    fn serialize(
+        &self,
+        arena: &MemoryArena,
+        serializer: &mut FieldSerializer<'_>,
+        buffer_lender: &mut BufferLender,
+    ) {
+        let (buffer_u8, buffer_positions, buffer_offsets) = buffer_lender.lend_all_with_offsets();
+        self.stack.read_to_end(arena, buffer_u8);
+        let mut u32_it = VInt32Reader::new(&buffer_u8[..]);
+        let mut prev_doc = 0;
+        while let Some(delta_doc_id) = u32_it.next() {
+            let doc_id = prev_doc + delta_doc_id;
+            prev_doc = doc_id;
+            let mut prev_position_plus_one = 1u32;
+            buffer_positions.clear();
+            buffer_offsets.clear();
+            loop {
+                match u32_it.next() {
+                    Some(POSITION_END) | None => {
+                        break;
+                    }
+                    Some(position_plus_one) => {
+                        let delta_position = position_plus_one - prev_position_plus_one;
+                        buffer_positions.push(delta_position);
+                        prev_position_plus_one = position_plus_one;
+                        let offset_from = u32_it.next().unwrap_or(0u32);
+                        let offset_len = u32_it.next().unwrap_or(0u32);
+                        buffer_offsets.push(offset_from);
+                        buffer_offsets.push(offset_len);
+                    }
+                }
+            }
+            serializer.write_doc_with_offsets(
+                doc_id,
+                buffer_positions.len() as u32,
+                buffer_positions,
+                buffer_offsets,
+            );
+        }
+    }
+
+    fn term_doc_freq(&self) -> Option<u32> {
+        Some(self.term_doc_freq)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -17,6 +17,7 @@ pub struct SegmentPostings {
     pub(crate) block_cursor: BlockSegmentPostings,
     cur: usize,
     position_reader: Option<PositionReader>,
+    offsets_reader: Option<PositionReader>,
 }
 
 impl SegmentPostings {
@@ -26,6 +27,7 @@ impl SegmentPostings {
             block_cursor: BlockSegmentPostings::empty(),
             cur: 0,
             position_reader: None,
+            offsets_reader: None,
         }
     }
 
@@ -149,6 +151,41 @@ impl SegmentPostings {
             block_cursor: segment_block_postings,
             cur: 0, // cursor within the block
             position_reader,
+            offsets_reader: None,
+        }
+    }
+
+    /// Attaches the reader of the offsets recorded along with the positions.
+    pub(crate) fn with_offsets_reader(mut self, offsets_reader: PositionReader) -> SegmentPostings {
+        self.offsets_reader = Some(offsets_reader);
+        self
+    }
+
+    /// Returns the index of the first position of the current document, among all of the
+    /// positions of the term.
+    fn position_index(&self) -> u64 {
+        self.block_cursor.position_offset()
+            + (self.block_cursor.freqs()[..self.cur]
+                .iter()
+                .cloned()
+                .sum::<u32>() as u64)
+    }
+
+    /// Fills `output` with the byte offsets of the occurrences of the term in the current
+    /// document, as `offset_from`, `offset_to - offset_from` pairs following the order of the
+    /// positions.
+    ///
+    /// `output` is left empty if the field does not record offsets.
+    pub(crate) fn offsets(&mut self, output: &mut Vec<u32>) {
+        output.clear();
+        if self.offsets_reader.is_none() {
+            return;
+        }
+        let term_freq = self.term_freq() as usize;
+        let read_offset = 2 * self.position_index();
+        output.resize(2 * term_freq, 0u32);
+        if let Some(offsets_reader) = self.offsets_reader.as_mut() {
+            offsets_reader.read(read_offset, &mut output[..]);
         }
     }
 }
@@ -3,6 +3,7 @@ use std::io::{self, Write};
 
 use common::{BinarySerializable, CountingWriter, VInt};
 
+use super::dense_postings::{dense_postings_num_bytes, serialize_dense_postings};
 use super::TermInfo;
 use crate::directory::{CompositeWrite, WritePtr};
 use crate::fieldnorm::FieldNormReader;
@@ -86,6 +87,7 @@ impl InvertedIndexSerializer {
             .unwrap_or(IndexRecordOption::Basic);
         FieldSerializer::create(
             index_record_option,
+            field_entry.field_type().has_offsets(),
             total_num_tokens,
             term_dictionary_write,
             postings_write,
@@ -109,7 +111,10 @@ pub struct FieldSerializer<'a, W: Write = WritePtr> {
     term_dictionary_builder: TermDictionaryBuilder<&'a mut CountingWriter<W>>,
     postings_serializer: PostingsSerializer,
     positions_serializer_opt: Option<PositionSerializer<&'a mut CountingWriter<W>>>,
+    offsets_serializer_opt: Option<PositionSerializer<io::Sink>>,
     current_term_info: TermInfo,
+    // Doc and term frequency of the first doc of the current term.
+    first_posting: (DocId, u32),
     term_open: bool,
     postings_write: &'a mut CountingWriter<W>,
     postings_start_offset: u64,
@@ -117,8 +122,11 @@ pub struct FieldSerializer<'a, W: Write = WritePtr> {
 
 impl<'a, W: Write> FieldSerializer<'a, W> {
     /// Creates a new `FieldSerializer` for the given field type.
+    ///
+    /// `record_offsets` is only taken in account if `index_record_option` has positions.
     pub fn create(
         index_record_option: IndexRecordOption,
+        record_offsets: bool,
         total_num_tokens: u64,
         term_dictionary_write: &'a mut CountingWriter<W>,
         postings_write: &'a mut CountingWriter<W>,
@@ -138,13 +146,21 @@ impl<'a, W: Write> FieldSerializer<'a, W> {
         } else {
             None
         };
+        // The offsets of a term are buffered, and written right after its positions.
+        let offsets_serializer_opt = if record_offsets && index_record_option.has_positions() {
+            Some(PositionSerializer::new(io::sink()))
+        } else {
+            None
+        };
 
         let postings_start_offset = postings_write.written_bytes();
         Ok(FieldSerializer {
             term_dictionary_builder,
             postings_serializer,
             positions_serializer_opt,
+            offsets_serializer_opt,
             current_term_info: TermInfo::default(),
+            first_posting: (0, 0),
             term_open: false,
             postings_write,
             postings_start_offset,
@@ -167,6 +183,7 @@ impl<'a, W: Write> FieldSerializer<'a, W> {
             doc_freq: 0,
             postings_range: addr..addr,
             positions_range: positions_start..positions_start,
+            inline_posting: None,
         }
     }
 
@@ -208,18 +225,43 @@ impl<'a, W: Write> FieldSerializer<'a, W> {
     /// Term frequencies and positions may be ignored by the serializer depending
     /// on the configuration of the field in the `Schema`.
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32, position_deltas: &[u32]) {
+        self.write_doc_with_offsets(doc_id, term_freq, position_deltas, &[]);
+    }
+
+    /// Serialize the information that a document contains for the current term, like
+    /// [`FieldSerializer::write_doc`], along with the byte offsets of its occurrences.
+    ///
+    /// `offsets` holds an `offset_from`, `offset_to - offset_from` pair for each position.
+    /// It is ignored unless the field records offsets.
+    pub fn write_doc_with_offsets(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+        offsets: &[u32],
+    ) {
         self.current_term_info.doc_freq += 1;
+        if self.current_term_info.doc_freq == 1 {
+            self.first_posting = (doc_id, term_freq);
+        }
         self.postings_serializer.write_doc(doc_id, term_freq);
         if let Some(ref mut positions_serializer) = self.positions_serializer_opt.as_mut() {
             assert_eq!(term_freq as usize, position_deltas.len());
             positions_serializer.write_positions_delta(position_deltas);
         }
+        if let Some(offsets_serializer) = self.offsets_serializer_opt.as_mut() {
+            assert_eq!(2 * term_freq as usize, offsets.len());
+            offsets_serializer.write_positions_delta(offsets);
+        }
     }
 
     /// Finish the serialization for this term postings.
     ///
     /// If the current block is incomplete, it needs to be encoded
     /// using `VInt` encoding.
+    ///
+    /// The posting list of a term contained in a single document is not written in the
+    /// postings file, but inlined in its `TermInfo`.
     pub fn close_term(&mut self) -> io::Result<()> {
         crate::fail_point!("FieldSerializer::close_term", |msg: Option<String>| {
             Err(io::Error::new(io::ErrorKind::Other, format!("{msg:?}")))
@@ -229,11 +271,26 @@ impl<'a, W: Write> FieldSerializer<'a, W> {
             return Ok(());
         };
 
-        self.postings_serializer
-            .close_term(self.current_term_info.doc_freq, self.postings_write)?;
+        if self.current_term_info.doc_freq == 1 {
+            let (doc_id, term_freq) = self.first_posting;
+            let term_freq = if self.postings_serializer.term_has_freq() {
+                term_freq
+            } else {
+                0
+            };
+            self.current_term_info.inline_posting = Some((doc_id, term_freq));
+            self.postings_serializer.discard_term();
+        } else {
+            self.postings_serializer
+                .close_term(self.current_term_info.doc_freq, self.postings_write)?;
+        }
         self.current_term_info.postings_range.end = self.postings_offset();
         if let Some(positions_serializer) = self.positions_serializer_opt.as_mut() {
-            positions_serializer.close_term()?;
+            if let Some(offsets_serializer) = self.offsets_serializer_opt.as_mut() {
+                positions_serializer.close_term_with_offsets(offsets_serializer)?;
+            } else {
+                positions_serializer.close_term()?;
+            }
             self.current_term_info.positions_range.end =
                 positions_serializer.written_bytes() as usize;
         }
@@ -312,6 +369,9 @@ pub struct PostingsSerializer {
 
     postings_write: Vec<u8>,
     skip_write: SkipSerializer,
+    // Docs of the current term, kept for terms without term frequencies, which may be
+    // serialized as a bitset.
+    term_docs: Vec<DocId>,
 
     mode: IndexRecordOption,
     fieldnorm_reader: Option<FieldNormReader>,
@@ -337,6 +397,7 @@ impl PostingsSerializer {
 
             postings_write: Vec::new(),
             skip_write: SkipSerializer::new(),
+            term_docs: Vec::new(),
 
             last_doc_id_encoded: 0u32,
             mode,
@@ -434,6 +495,9 @@ impl PostingsSerializer {
     /// * doc_id - the document id.
     /// * term_freq - the term frequency within the document.
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32) {
+        if !self.term_has_freq {
+            self.term_docs.push(doc_id);
+        }
         self.block.append_doc(doc_id, term_freq);
         if self.block.is_full() {
             self.write_block();
@@ -441,6 +505,9 @@ impl PostingsSerializer {
     }
 
     /// Finish the serialization for this term.
+    ///
+    /// Terms without term frequencies are serialized as a bitset if it is smaller than their
+    /// block encoding.
     pub fn close_term(
         &mut self,
         doc_freq: u32,
@@ -470,18 +537,40 @@ impl PostingsSerializer {
         }
         if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
             let skip_data = self.skip_write.data();
-            VInt(skip_data.len() as u64).serialize(output_write)?;
-            output_write.write_all(skip_data)?;
+            let block_encoded_num_bytes = skip_data.len() + self.postings_write.len();
+            if !self.term_has_freq
+                && dense_postings_num_bytes(&self.term_docs) < block_encoded_num_bytes
+            {
+                serialize_dense_postings(&self.term_docs, output_write)?;
+            } else {
+                VInt(skip_data.len() as u64).serialize(output_write)?;
+                output_write.write_all(skip_data)?;
+                output_write.write_all(&self.postings_write[..])?;
+            }
+        } else {
+            output_write.write_all(&self.postings_write[..])?;
         }
-        output_write.write_all(&self.postings_write[..])?;
+        self.discard_term();
+        Ok(())
+    }
+
+    /// Returns true if the term frequencies of the current term are recorded.
+    pub(crate) fn term_has_freq(&self) -> bool {
+        self.term_has_freq
+    }
+
+    /// Drops the postings of the current term without serializing them.
+    pub(crate) fn discard_term(&mut self) {
+        self.block.clear();
         self.skip_write.clear();
         self.postings_write.clear();
+        self.term_docs.clear();
         self.bm25_weight = None;
-        Ok(())
     }
 
     fn clear(&mut self) {
         self.block.clear();
+        self.term_docs.clear();
         self.last_doc_id_encoded = 0;
     }
 }
@@ -3,6 +3,8 @@ use std::ops::Range;
 
 use common::{BinarySerializable, FixedSize};
 
+use crate::DocId;
+
 /// `TermInfo` wraps the metadata associated with a Term.
 /// It is segment-local.
 #[derive(Debug, Default, Eq, PartialEq, Clone)]
@@ -13,6 +15,12 @@ pub struct TermInfo {
     pub postings_range: Range<usize>,
     /// Byte range of the positions of this terms in the positions (`.pos`) file.
     pub positions_range: Range<usize>,
+    /// Document and term frequency of a term contained in a single document.
+    ///
+    /// Such a posting list is stored in the term dictionary rather than in the postings
+    /// (`.idx`) file, and `postings_range` is empty. The term frequency is 0 if it is
+    /// not recorded for the term.
+    pub inline_posting: Option<(DocId, u32)>,
 }
 
 impl TermInfo {
@@ -37,6 +45,8 @@ impl FixedSize for TermInfo {
     const SIZE_IN_BYTES: usize = 3 * u32::SIZE_IN_BYTES + 2 * u64::SIZE_IN_BYTES;
 }
 
+// The inline posting is not part of this representation. The term dictionaries serialize it
+// separately.
 impl BinarySerializable for TermInfo {
     fn serialize<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         self.doc_freq.serialize(writer)?;
@@ -59,6 +69,7 @@ impl BinarySerializable for TermInfo {
             doc_freq,
             postings_range: postings_start_offset..postings_end_offset,
             positions_range: positions_start_offset..positions_end_offset,
+            inline_posting: None,
         })
     }
 }
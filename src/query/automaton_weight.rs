@@ -1,10 +1,12 @@
 use std::io;
 use std::sync::Arc;
+use std::time::Instant;
 
 use common::BitSet;
 use tantivy_fst::Automaton;
 
 use super::phrase_prefix_query::prefix_end;
+use crate::core::search_options::check_rewrite_deadline;
 use crate::index::SegmentReader;
 use crate::postings::TermInfo;
 use crate::query::{BitSetDocSet, ConstScorer, Explanation, Scorer, Weight};
@@ -20,8 +22,12 @@ pub struct AutomatonWeight<A> {
     // We apply additional filtering based on the given JSON path, when searching within the term
     // dictionary. This prevents terms from unrelated paths from matching the search criteria.
     json_path_bytes: Option<Box<[u8]>>,
+    rewrite_deadline: Option<Instant>,
 }
 
+/// The rewrite deadline is checked every `DEADLINE_CHECK_INTERVAL` terms.
+const DEADLINE_CHECK_INTERVAL: usize = 64;
+
 impl<A> AutomatonWeight<A>
 where
     A: Automaton + Send + Sync + 'static,
@@ -33,6 +39,7 @@ where
             field,
             automaton: automaton.into(),
             json_path_bytes: None,
+            rewrite_deadline: None,
         }
     }
 
@@ -46,9 +53,20 @@ where
             field,
             automaton: automaton.into(),
             json_path_bytes: Some(json_path_bytes.to_vec().into_boxed_slice()),
+            rewrite_deadline: None,
         }
     }
 
+    /// Sets the deadline of the enumeration of the terms matching the automaton.
+    ///
+    /// Once it is passed, creating a scorer fails with
+    /// [`TantivyError::RewriteDeadlineExceeded`].
+    #[must_use]
+    pub fn with_rewrite_deadline(mut self, rewrite_deadline: Option<Instant>) -> Self {
+        self.rewrite_deadline = rewrite_deadline;
+        self
+    }
+
     fn automaton_stream<'a>(
         &'a self,
         term_dict: &'a TermDictionary,
@@ -73,6 +91,9 @@ where
         let mut term_stream = self.automaton_stream(term_dict)?;
         let mut term_infos = Vec::new();
         while term_stream.advance() {
+            if term_infos.len() % DEADLINE_CHECK_INTERVAL == 0 {
+                check_rewrite_deadline(self.rewrite_deadline)?;
+            }
             term_infos.push(term_stream.value().clone());
         }
         Ok(term_infos)
@@ -90,7 +111,12 @@ where
         let inverted_index = reader.inverted_index(self.field)?;
         let term_dict = inverted_index.terms();
         let mut term_stream = self.automaton_stream(term_dict)?;
+        let mut num_terms = 0;
         while term_stream.advance() {
+            if num_terms % DEADLINE_CHECK_INTERVAL == 0 {
+                check_rewrite_deadline(self.rewrite_deadline)?;
+            }
+            num_terms += 1;
             let term_info = term_stream.value();
             let mut block_segment_postings = inverted_index
                 .read_block_postings_from_terminfo(term_info, IndexRecordOption::Basic)?;
@@ -124,13 +150,15 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::time::{Duration, Instant};
+
     use tantivy_fst::Automaton;
 
     use super::AutomatonWeight;
     use crate::docset::TERMINATED;
     use crate::query::Weight;
     use crate::schema::{Schema, STRING};
-    use crate::{Index, IndexWriter};
+    use crate::{Index, IndexWriter, TantivyError};
 
     fn create_index() -> crate::Result<Index> {
         let mut schema = Schema::builder();
@@ -208,4 +236,32 @@ mod tests {
         assert_eq!(scorer.score(), 1.32);
         Ok(())
     }
+
+    #[test]
+    fn test_automaton_weight_rewrite_deadline() -> crate::Result<()> {
+        let index = create_index()?;
+        let field = index.schema().get_field("title").unwrap();
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let segment_reader = searcher.segment_reader(0u32);
+        let far_deadline = Instant::now() + Duration::from_secs(3_600);
+        let automaton_weight =
+            AutomatonWeight::new(field, PrefixedByA).with_rewrite_deadline(Some(far_deadline));
+        assert_eq!(
+            automaton_weight.get_match_term_infos(segment_reader)?.len(),
+            2
+        );
+        assert!(automaton_weight.scorer(segment_reader, 1.0).is_ok());
+        let automaton_weight =
+            AutomatonWeight::new(field, PrefixedByA).with_rewrite_deadline(Some(Instant::now()));
+        assert!(matches!(
+            automaton_weight.get_match_term_infos(segment_reader),
+            Err(TantivyError::RewriteDeadlineExceeded)
+        ));
+        assert!(matches!(
+            automaton_weight.scorer(segment_reader, 1.0),
+            Err(TantivyError::RewriteDeadlineExceeded)
+        ));
+        Ok(())
+    }
 }
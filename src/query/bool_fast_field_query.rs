@@ -0,0 +1,182 @@
+use columnar::{Column, ColumnIndex};
+use common::BitSet;
+
+use super::{ConstScorer, EmptyScorer};
+use crate::index::SegmentReader;
+use crate::query::all_query::AllScorer;
+use crate::query::boost_query::BoostScorer;
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Type;
+use crate::{DocId, DocSet, Score, TantivyError};
+
+/// Query matching the documents holding a given value in a bool fast field.
+///
+/// The query is evaluated on the fast field alone, so that the field does not need to be
+/// indexed. Documents with several values match if any of them is equal to `value`.
+///
+/// By default, documents without any value for the field do not match. With
+/// [`set_match_missing(true)`](BoolFastFieldQuery::set_match_missing), they match too: excluding
+/// such a query leaves the documents missing the field out of the result. This is how the query
+/// parser evaluates `NOT field:true`.
+///
+/// All of the matched documents get the score 1.0.
+#[derive(Clone, Debug)]
+pub struct BoolFastFieldQuery {
+    field_name: String,
+    value: bool,
+    match_missing: bool,
+}
+
+impl BoolFastFieldQuery {
+    /// Creates a new `BoolFastFieldQuery`, matching the documents of which the bool fast field
+    /// `field_name` holds `value`.
+    ///
+    /// Executing the search returns an error if the field does not exist, or is not a bool
+    /// fast field.
+    pub fn new(field_name: String, value: bool) -> BoolFastFieldQuery {
+        BoolFastFieldQuery {
+            field_name,
+            value,
+            match_missing: false,
+        }
+    }
+
+    /// Sets whether the documents without any value for the field match.
+    pub fn set_match_missing(&mut self, match_missing: bool) {
+        self.match_missing = match_missing;
+    }
+}
+
+impl Query for BoolFastFieldQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field = schema.get_field(&self.field_name)?;
+        let field_type = schema.get_field_entry(field).field_type();
+        if field_type.value_type() != Type::Bool || !field_type.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {} is not a bool fast field.",
+                self.field_name
+            )));
+        }
+        Ok(Box::new(BoolFastFieldWeight {
+            field_name: self.field_name.clone(),
+            value: self.value,
+            match_missing: self.match_missing,
+        }))
+    }
+}
+
+/// Weight associated with the `BoolFastFieldQuery` query.
+pub struct BoolFastFieldWeight {
+    field_name: String,
+    value: bool,
+    match_missing: bool,
+}
+
+impl BoolFastFieldWeight {
+    fn matching_docs(&self, column: &Column<bool>, max_doc: DocId) -> BitSet {
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let mut doc_ids = Vec::new();
+        column.get_docids_for_value_range(self.value..=self.value, 0..max_doc, &mut doc_ids);
+        for doc in doc_ids {
+            doc_bitset.insert(doc);
+        }
+        if self.match_missing {
+            match &column.index {
+                ColumnIndex::Full => {}
+                ColumnIndex::Empty { .. } => {
+                    for doc in 0..max_doc {
+                        doc_bitset.insert(doc);
+                    }
+                }
+                column_index @ (ColumnIndex::Optional(_) | ColumnIndex::Multivalued(_)) => {
+                    for doc in 0..max_doc {
+                        if !column_index.has_value(doc) {
+                            doc_bitset.insert(doc);
+                        }
+                    }
+                }
+            }
+        }
+        doc_bitset
+    }
+}
+
+impl Weight for BoolFastFieldWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let Some(column) = reader.fast_fields().column_opt::<bool>(&self.field_name)? else {
+            // None of the documents of the segment have a value.
+            if !self.match_missing {
+                return Ok(Box::new(EmptyScorer));
+            }
+            let all_scorer = AllScorer::new(max_doc);
+            if boost != 1.0f32 {
+                return Ok(Box::new(BoostScorer::new(all_scorer, boost)));
+            } else {
+                return Ok(Box::new(all_scorer));
+            }
+        };
+        let docset = BitSetDocSet::from(self.matching_docs(&column, max_doc));
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("BoolFastFieldQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoolFastFieldQuery;
+    use crate::collector::DocSetCollector;
+    use crate::query::Query;
+    use crate::schema::{Schema, FAST, INDEXED};
+    use crate::{Index, IndexWriter, TantivyDocument};
+
+    #[test]
+    fn test_bool_fast_field_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let flag = schema_builder.add_bool_field("flag", FAST);
+        let indexed_only = schema_builder.add_bool_field("indexed_only", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // Doc 0: true, doc 1: false, doc 2: missing, doc 3: true and false.
+        index_writer.add_document(doc!(flag => true))?;
+        index_writer.add_document(doc!(flag => false))?;
+        index_writer.add_document(TantivyDocument::default())?;
+        index_writer.add_document(doc!(flag => true, flag => false))?;
+        index_writer.commit()?;
+        // A segment where none of the documents have a value.
+        index_writer.add_document(doc!(indexed_only => true))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let matching_docs = |query: &dyn Query| -> crate::Result<Vec<(u32, u32)>> {
+            let mut docs: Vec<(u32, u32)> = searcher
+                .search(query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| (doc_address.segment_ord, doc_address.doc_id))
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+
+        let mut query = BoolFastFieldQuery::new("flag".to_string(), true);
+        assert_eq!(matching_docs(&query)?, vec![(0, 0), (0, 3)]);
+        query.set_match_missing(true);
+        assert_eq!(matching_docs(&query)?, vec![(0, 0), (0, 2), (0, 3), (1, 0)]);
+        let mut query = BoolFastFieldQuery::new("flag".to_string(), false);
+        assert_eq!(matching_docs(&query)?, vec![(0, 1), (0, 3)]);
+        query.set_match_missing(true);
+        assert_eq!(matching_docs(&query)?, vec![(0, 1), (0, 2), (0, 3), (1, 0)]);
+
+        let query = BoolFastFieldQuery::new("indexed_only".to_string(), true);
+        assert!(searcher.search(&query, &DocSetCollector).is_err());
+        Ok(())
+    }
+}
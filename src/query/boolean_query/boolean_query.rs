@@ -1,6 +1,9 @@
+use std::fmt;
+
 use super::boolean_weight::BooleanWeight;
-use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermQuery, Weight};
+use crate::query::{AllQuery, EnableScoring, Occur, Query, SumCombiner, TermQuery, Weight};
 use crate::schema::{IndexRecordOption, Term};
+use crate::Score;
 
 /// The boolean query returns a set of documents
 /// that matches the Boolean combination of constituent subqueries.
@@ -10,6 +13,10 @@ use crate::schema::{IndexRecordOption, Term};
 /// - match none of the sub queries associated with the `MustNot` occurrence.
 /// - match at least one of the sub queries associated with the `Must` or `Should` occurrence.
 ///
+/// A query made only of `MustNot` clauses matches all of the documents except the excluded
+/// ones, with a constant score, as if it had an extra [`AllQuery`](crate::query::AllQuery)
+/// `Must` clause.
+///
 /// You can combine other query types and their `Occur`ances into one `BooleanQuery`
 ///
 /// ```rust
@@ -127,12 +134,29 @@ use crate::schema::{IndexRecordOption, Term};
 ///    Ok(())
 /// }
 /// ```
-#[derive(Debug)]
 pub struct BooleanQuery {
     subqueries: Vec<(Occur, Box<dyn Query>)>,
+    // Empty if none of the clauses is named.
+    clause_names: Vec<Option<String>>,
     minimum_number_should_match: usize,
 }
 
+impl fmt::Debug for BooleanQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug_struct = f.debug_struct("BooleanQuery");
+        debug_struct.field("subqueries", &self.subqueries);
+        if !self.clause_names.is_empty() {
+            debug_struct.field("clause_names", &self.clause_names);
+        }
+        debug_struct
+            .field(
+                "minimum_number_should_match",
+                &self.minimum_number_should_match,
+            )
+            .finish()
+    }
+}
+
 impl Clone for BooleanQuery {
     fn clone(&self) -> Self {
         let subqueries = self
@@ -142,6 +166,7 @@ impl Clone for BooleanQuery {
             .collect::<Vec<_>>();
         Self {
             subqueries,
+            clause_names: self.clause_names.clone(),
             minimum_number_should_match: self.minimum_number_should_match,
         }
     }
@@ -155,17 +180,28 @@ impl From<Vec<(Occur, Box<dyn Query>)>> for BooleanQuery {
 
 impl Query for BooleanQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
-        let sub_weights = self
+        let mut sub_weights: Vec<(Occur, Box<dyn Weight>)> = self
             .subqueries
             .iter()
             .map(|(occur, subquery)| Ok((*occur, subquery.weight(enable_scoring)?)))
             .collect::<crate::Result<_>>()?;
-        Ok(Box::new(BooleanWeight::with_minimum_number_should_match(
-            sub_weights,
-            self.minimum_number_should_match,
-            enable_scoring.is_scoring_enabled(),
-            Box::new(SumCombiner::default),
-        )))
+        let mut clause_names = self.clause_names.clone();
+        if self.is_all_negative() {
+            // Excluding documents requires something to exclude them from.
+            sub_weights.push((Occur::Must, AllQuery.weight(enable_scoring)?));
+            if !clause_names.is_empty() {
+                clause_names.push(None);
+            }
+        }
+        Ok(Box::new(
+            BooleanWeight::with_minimum_number_should_match(
+                sub_weights,
+                self.minimum_number_should_match,
+                enable_scoring.is_scoring_enabled(),
+                Box::new(SumCombiner::default),
+            )
+            .with_clause_names(clause_names),
+        ))
     }
 
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
@@ -173,9 +209,23 @@ impl Query for BooleanQuery {
             subquery.query_terms(visitor);
         }
     }
+
+    fn query_terms_with_boost<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, Score)) {
+        for (_occur, subquery) in &self.subqueries {
+            subquery.query_terms_with_boost(visitor);
+        }
+    }
 }
 
 impl BooleanQuery {
+    /// Creates a new builder, making it possible to name the clauses of the query.
+    ///
+    /// Clause names show up in the [`Explanation`](crate::query::Explanation) of the
+    /// query, which helps finding out which clause of a large query contributes what.
+    pub fn builder() -> BooleanQueryBuilder {
+        BooleanQueryBuilder::default()
+    }
+
     /// Creates a new boolean query.
     pub fn new(subqueries: Vec<(Occur, Box<dyn Query>)>) -> BooleanQuery {
         // If the bool query includes at least one should clause
@@ -201,6 +251,7 @@ impl BooleanQuery {
     ) -> BooleanQuery {
         BooleanQuery {
             subqueries,
+            clause_names: Vec::new(),
             minimum_number_should_match,
         }
     }
@@ -253,10 +304,118 @@ impl BooleanQuery {
         BooleanQuery::new(occur_term_queries)
     }
 
+    /// Returns true if the query only has `MustNot` clauses.
+    ///
+    /// Such a query matches all of the documents except the excluded ones.
+    pub fn is_all_negative(&self) -> bool {
+        !self.subqueries.is_empty()
+            && self
+                .subqueries
+                .iter()
+                .all(|(occur, _)| *occur == Occur::MustNot)
+    }
+
     /// Deconstructed view of the clauses making up this query.
     pub fn clauses(&self) -> &[(Occur, Box<dyn Query>)] {
         &self.subqueries[..]
     }
+
+    /// Returns the name of the clause at position `clause_ord` in
+    /// [`clauses()`](BooleanQuery::clauses), if it was given one.
+    pub fn clause_name(&self, clause_ord: usize) -> Option<&str> {
+        self.clause_names.get(clause_ord)?.as_deref()
+    }
+}
+
+/// The builder for boolean queries, see [`BooleanQuery::builder`].
+#[derive(Debug, Default)]
+pub struct BooleanQueryBuilder {
+    subqueries: Vec<(Occur, Box<dyn Query>)>,
+    clause_names: Vec<Option<String>>,
+    minimum_number_should_match: Option<usize>,
+}
+
+impl BooleanQueryBuilder {
+    /// Adds a clause.
+    #[must_use]
+    pub fn clause(mut self, occur: Occur, query: Box<dyn Query>) -> Self {
+        self.subqueries.push((occur, query));
+        self.clause_names.push(None);
+        self
+    }
+
+    /// Adds a named clause.
+    #[must_use]
+    pub fn named_clause(
+        mut self,
+        occur: Occur,
+        name: impl Into<String>,
+        query: Box<dyn Query>,
+    ) -> Self {
+        self.subqueries.push((occur, query));
+        self.clause_names.push(Some(name.into()));
+        self
+    }
+
+    /// Adds a `Must` clause.
+    #[must_use]
+    pub fn must(self, query: Box<dyn Query>) -> Self {
+        self.clause(Occur::Must, query)
+    }
+
+    /// Adds a named `Must` clause.
+    #[must_use]
+    pub fn must_named(self, name: impl Into<String>, query: Box<dyn Query>) -> Self {
+        self.named_clause(Occur::Must, name, query)
+    }
+
+    /// Adds a `Should` clause.
+    #[must_use]
+    pub fn should(self, query: Box<dyn Query>) -> Self {
+        self.clause(Occur::Should, query)
+    }
+
+    /// Adds a named `Should` clause.
+    #[must_use]
+    pub fn should_named(self, name: impl Into<String>, query: Box<dyn Query>) -> Self {
+        self.named_clause(Occur::Should, name, query)
+    }
+
+    /// Adds a `MustNot` clause.
+    #[must_use]
+    pub fn must_not(self, query: Box<dyn Query>) -> Self {
+        self.clause(Occur::MustNot, query)
+    }
+
+    /// Adds a named `MustNot` clause.
+    #[must_use]
+    pub fn must_not_named(self, name: impl Into<String>, query: Box<dyn Query>) -> Self {
+        self.named_clause(Occur::MustNot, name, query)
+    }
+
+    /// Sets the minimum number of `Should` clauses documents must match.
+    ///
+    /// If not set, the default of [`BooleanQuery::new`] applies.
+    #[must_use]
+    pub fn with_minimum_number_should_match(mut self, minimum_number_should_match: usize) -> Self {
+        self.minimum_number_should_match = Some(minimum_number_should_match);
+        self
+    }
+
+    /// Builds the boolean query.
+    pub fn build(self) -> BooleanQuery {
+        let mut boolean_query = match self.minimum_number_should_match {
+            Some(minimum_number_should_match) => BooleanQuery::with_minimum_required_clauses(
+                self.subqueries,
+                minimum_number_should_match,
+            ),
+            None => BooleanQuery::new(self.subqueries),
+        };
+        if self.clause_names.iter().any(Option::is_some) {
+            boolean_query.clause_names = self.clause_names;
+        }
+        boolean_query
+    }
 }
 
 #[cfg(test)]
@@ -168,6 +168,8 @@ enum ShouldScorersCombinationMethod {
 /// Weight associated to the `BoolQuery`.
 pub struct BooleanWeight<TScoreCombiner: ScoreCombiner> {
     weights: Vec<(Occur, Box<dyn Weight>)>,
+    // Either empty, or one optional name per weight.
+    clause_names: Vec<Option<String>>,
     minimum_number_should_match: usize,
     scoring_enabled: bool,
     score_combiner_fn: Box<dyn Fn() -> TScoreCombiner + Sync + Send>,
@@ -182,6 +184,7 @@ impl<TScoreCombiner: ScoreCombiner> BooleanWeight<TScoreCombiner> {
     ) -> BooleanWeight<TScoreCombiner> {
         BooleanWeight {
             weights,
+            clause_names: Vec::new(),
             scoring_enabled,
             score_combiner_fn,
             minimum_number_should_match: 1,
@@ -197,12 +200,21 @@ impl<TScoreCombiner: ScoreCombiner> BooleanWeight<TScoreCombiner> {
     ) -> BooleanWeight<TScoreCombiner> {
         BooleanWeight {
             weights,
+            clause_names: Vec::new(),
             minimum_number_should_match,
             scoring_enabled,
             score_combiner_fn,
         }
     }
 
+    /// Names the clauses, in the same order as the weights.
+    ///
+    /// Names are reported in the explanation of the matching clauses.
+    pub(crate) fn with_clause_names(mut self, clause_names: Vec<Option<String>>) -> Self {
+        self.clause_names = clause_names;
+        self
+    }
+
     fn per_occur_scorers(
         &self,
         reader: &SegmentReader,
@@ -447,9 +459,12 @@ impl<TScoreCombiner: ScoreCombiner + Sync> Weight for BooleanWeight<TScoreCombin
         }
 
         let mut explanation = Explanation::new("BooleanClause. sum of ...", scorer.score());
-        for (occur, subweight) in &self.weights {
+        for (clause_ord, (occur, subweight)) in self.weights.iter().enumerate() {
             if is_include_occur(*occur) {
-                if let Ok(child_explanation) = subweight.explain(reader, doc) {
+                if let Ok(mut child_explanation) = subweight.explain(reader, doc) {
+                    if let Some(Some(clause_name)) = self.clause_names.get(clause_ord) {
+                        child_explanation.add_context(format!("{occur:?} clause {clause_name:?}"));
+                    }
                     explanation.add_detail(child_explanation);
                 }
             }
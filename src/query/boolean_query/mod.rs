@@ -3,7 +3,7 @@ mod boolean_query;
 mod boolean_weight;
 
 pub(crate) use self::block_wand::{block_wand, block_wand_single_scorer};
-pub use self::boolean_query::BooleanQuery;
+pub use self::boolean_query::{BooleanQuery, BooleanQueryBuilder};
 pub use self::boolean_weight::BooleanWeight;
 
 #[cfg(test)]
@@ -13,7 +13,7 @@ mod tests {
 
     use super::*;
     use crate::collector::tests::TEST_COLLECTOR_WITH_SCORE;
-    use crate::collector::{Count, TopDocs};
+    use crate::collector::{Count, DocSetCollector, TopDocs};
     use crate::query::term_query::TermScorer;
     use crate::query::{
         AllScorer, EmptyScorer, EnableScoring, Intersection, Occur, Query, QueryParser, RangeQuery,
@@ -161,7 +161,7 @@ mod tests {
         }
         {
             let boolean_query = BooleanQuery::new(vec![(Occur::MustNot, make_term_query("d"))]);
-            assert_eq!(matching_docs(&boolean_query), Vec::<u32>::new());
+            assert_eq!(matching_docs(&boolean_query), vec![0, 1, 2]);
         }
         Ok(())
     }
@@ -215,6 +215,102 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_boolean_query_all_negative() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let status_field = schema_builder.add_text_field("status", STRING);
+        let year_field = schema_builder.add_u64_field("year", INDEXED | FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(
+                doc!(text_field => "a", status_field => "archived", year_field => 2020u64),
+            )?;
+            index_writer.add_document(
+                doc!(text_field => "a", status_field => "draft", year_field => 2021u64),
+            )?;
+            index_writer.add_document(
+                doc!(text_field => "b", status_field => "published", year_field => 2022u64),
+            )?;
+            index_writer.commit()?;
+            index_writer.add_document(
+                doc!(text_field => "a", status_field => "published", year_field => 2023u64),
+            )?;
+            index_writer.add_document(
+                doc!(text_field => "b", status_field => "deleted", year_field => 2024u64),
+            )?;
+            index_writer.add_document(
+                doc!(text_field => "c", status_field => "archived", year_field => 2025u64),
+            )?;
+            index_writer.commit()?;
+            index_writer.delete_term(Term::from_field_text(status_field, "deleted"));
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let matching_years = |query: &dyn Query| -> crate::Result<Vec<u64>> {
+            let mut years: Vec<u64> = Vec::new();
+            for doc_address in searcher.search(query, &DocSetCollector)? {
+                let doc: TantivyDocument = searcher.doc(doc_address)?;
+                years.push(doc.get_first(year_field).unwrap().as_u64().unwrap());
+            }
+            years.sort();
+            assert_eq!(query.count(&searcher)?, years.len());
+            Ok(years)
+        };
+
+        // Pure negation excludes deleted documents as well, with a constant score.
+        let query = query_parser.parse_query("-status:archived")?;
+        assert_eq!(matching_years(query.as_ref())?, vec![2021, 2022, 2023]);
+        assert!(searcher
+            .search(query.as_ref(), &TopDocs::with_limit(10).order_by_score())?
+            .iter()
+            .all(|(score, _)| *score == 1.0));
+        let query = query_parser.parse_query("-status:archived -status:draft")?;
+        assert_eq!(matching_years(query.as_ref())?, vec![2022, 2023]);
+        let query = BooleanQuery::builder()
+            .must_not_named(
+                "not_archived",
+                Box::new(TermQuery::new(
+                    Term::from_field_text(status_field, "archived"),
+                    IndexRecordOption::Basic,
+                )),
+            )
+            .build();
+        assert_eq!(matching_years(&query)?, vec![2021, 2022, 2023]);
+        let explanation = query.explain(&searcher, DocAddress::new(0, 1))?;
+        assert_eq!(explanation.value(), 1.0);
+
+        // Negations nested in groups are anchored within their group.
+        let query = query_parser.parse_query("+a +(-status:archived)")?;
+        assert_eq!(matching_years(query.as_ref())?, vec![2021, 2023]);
+        let query = query_parser.parse_query("(-status:archived) OR (-status:published)")?;
+        assert_eq!(
+            matching_years(query.as_ref())?,
+            vec![2020, 2021, 2022, 2023, 2025]
+        );
+
+        // Negations combined with filters.
+        let year_filter: Box<dyn Query> = Box::new(RangeQuery::new(
+            Bound::Included(Term::from_field_u64(year_field, 2021)),
+            Bound::Unbounded,
+        ));
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, year_filter),
+            (Occur::Must, query_parser.parse_query("-status:published")?),
+        ]);
+        assert_eq!(matching_years(&query)?, vec![2021, 2025]);
+
+        let mut strict_query_parser = QueryParser::for_index(&index, vec![text_field]);
+        strict_query_parser.forbid_all_but_queries();
+        assert!(matches!(
+            strict_query_parser.parse_query("-status:archived"),
+            Err(crate::query::QueryParserError::AllButQueryForbidden)
+        ));
+        Ok(())
+    }
+
     #[test]
     pub fn test_boolean_query_with_weight() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -314,6 +410,73 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_explain_named_clauses() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a"))?;
+        index_writer.add_document(doc!(text=>"a"))?;
+        index_writer.add_document(doc!(text=>"b"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |text_val: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, text_val),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let query = BooleanQuery::builder()
+            .must_named("acl", term_query("a"))
+            .should_named("title_match", term_query("a"))
+            .should(term_query("c"))
+            .must_not_named("spam", term_query("b"))
+            .build();
+        assert_eq!(query.clause_name(0), Some("acl"));
+        assert_eq!(query.clause_name(2), None);
+        assert_eq!(query.clause_name(3), Some("spam"));
+        assert_eq!(query.get_minimum_number_should_match(), 0);
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+
+        let explanation = query.explain(&searcher, DocAddress::new(0, 0u32))?;
+        let explanation_json: serde_json::Value =
+            serde_json::from_str(&explanation.to_pretty_json()).unwrap();
+        let clause_contexts: Vec<&str> = explanation_json["details"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|detail| detail["context"].as_array().into_iter().flatten())
+            .filter_map(|context| context.as_str())
+            .filter(|context| context.contains("clause"))
+            .collect();
+        assert_eq!(
+            clause_contexts,
+            vec![r#"Must clause "acl""#, r#"Should clause "title_match""#]
+        );
+
+        // Unnamed clauses keep working, and do not add any context.
+        let unnamed_query = BooleanQuery::builder()
+            .must(term_query("a"))
+            .should(term_query("a"))
+            .build();
+        let unnamed_explanation = unnamed_query.explain(&searcher, DocAddress::new(0, 0u32))?;
+        assert_nearly_equals!(unnamed_explanation.value(), explanation.value());
+        assert!(!unnamed_explanation.to_pretty_json().contains("clause"));
+        assert_eq!(
+            format!("{unnamed_query:?}"),
+            format!(
+                "{:?}",
+                BooleanQuery::new(vec![
+                    (Occur::Must, term_query("a")),
+                    (Occur::Should, term_query("a"))
+                ])
+            )
+        );
+        Ok(())
+    }
+
     #[test]
     pub fn test_boolean_weight_optimization() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
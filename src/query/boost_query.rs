@@ -20,6 +20,11 @@ impl BoostQuery {
     pub fn new(query: Box<dyn Query>, boost: Score) -> BoostQuery {
         BoostQuery { query, boost }
     }
+
+    /// The wrapped query.
+    pub(crate) fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
 }
 
 impl Clone for BoostQuery {
@@ -51,6 +56,12 @@ impl Query for BoostQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
+
+    fn query_terms_with_boost<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, Score)) {
+        let boost = self.boost;
+        self.query
+            .query_terms_with_boost(&mut |term, term_boost| visitor(term, boost * term_boost))
+    }
 }
 
 /// Weight associated to the BoostQuery.
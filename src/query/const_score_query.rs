@@ -19,6 +19,11 @@ impl ConstScoreQuery {
     pub fn new(query: Box<dyn Query>, score: Score) -> ConstScoreQuery {
         ConstScoreQuery { query, score }
     }
+
+    /// The wrapped query.
+    pub(crate) fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
 }
 
 impl Clone for ConstScoreQuery {
@@ -49,6 +54,10 @@ impl Query for ConstScoreQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
+
+    fn query_terms_with_boost<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, Score)) {
+        self.query.query_terms_with_boost(visitor);
+    }
 }
 
 struct ConstWeight {
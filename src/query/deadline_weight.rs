@@ -0,0 +1,154 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::index::SegmentReader;
+use crate::query::{Explanation, Scorer, Weight};
+use crate::{DocId, DocSet, Score, TantivyError, TERMINATED};
+
+/// The deadline is checked every `DEADLINE_CHECK_INTERVAL` calls to `advance` or `seek`.
+const DEADLINE_CHECK_INTERVAL: u32 = 1_024;
+
+/// Wraps a weight, to stop the collection of the matching documents once the deadline is
+/// passed.
+///
+/// The scorers cannot return an error, so they are terminated early and the weight records
+/// that the deadline was exceeded. The search then fails with
+/// [`TantivyError::CollectionDeadlineExceeded`], see [`Self::is_deadline_exceeded`].
+pub(crate) struct CollectionDeadlineWeight {
+    weight: Box<dyn Weight>,
+    deadline: Instant,
+    exceeded: Arc<AtomicBool>,
+}
+
+impl CollectionDeadlineWeight {
+    pub(crate) fn new(weight: Box<dyn Weight>, deadline: Instant) -> CollectionDeadlineWeight {
+        CollectionDeadlineWeight {
+            weight,
+            deadline,
+            exceeded: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns true if the deadline was exceeded during the collection, in which case the
+    /// documents collected are incomplete.
+    pub(crate) fn is_deadline_exceeded(&self) -> bool {
+        self.exceeded.load(Ordering::Relaxed)
+    }
+
+    fn check_deadline(&self) -> crate::Result<()> {
+        if self.exceeded.load(Ordering::Relaxed) || Instant::now() >= self.deadline {
+            self.exceeded.store(true, Ordering::Relaxed);
+            return Err(TantivyError::CollectionDeadlineExceeded);
+        }
+        Ok(())
+    }
+}
+
+impl Weight for CollectionDeadlineWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        self.check_deadline()?;
+        let scorer = self.weight.scorer(reader, boost)?;
+        Ok(Box::new(CollectionDeadlineScorer {
+            scorer,
+            deadline: self.deadline,
+            exceeded: self.exceeded.clone(),
+            num_calls: 0,
+            terminated: false,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        self.weight.explain(reader, doc)
+    }
+
+    // `count`, `for_each` and `for_each_no_score` go through `scorer`, so that they are
+    // stopped at the deadline.
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> crate::Result<()> {
+        // The underlying weight is kept, as it may prune more efficiently than its scorer.
+        // Once the deadline is passed, no more document can be competitive.
+        self.check_deadline()?;
+        self.weight
+            .for_each_pruning(threshold, reader, &mut |doc, score| {
+                if self.check_deadline().is_err() {
+                    return Score::MAX;
+                }
+                callback(doc, score)
+            })
+    }
+}
+
+struct CollectionDeadlineScorer {
+    scorer: Box<dyn Scorer>,
+    deadline: Instant,
+    exceeded: Arc<AtomicBool>,
+    num_calls: u32,
+    terminated: bool,
+}
+
+impl CollectionDeadlineScorer {
+    /// Returns true, and terminates the scorer, if the deadline is passed.
+    fn is_deadline_exceeded(&mut self) -> bool {
+        self.num_calls += 1;
+        if self.num_calls % DEADLINE_CHECK_INTERVAL != 0 {
+            return false;
+        }
+        if self.exceeded.load(Ordering::Relaxed) || Instant::now() >= self.deadline {
+            self.exceeded.store(true, Ordering::Relaxed);
+            self.terminated = true;
+        }
+        self.terminated
+    }
+}
+
+impl DocSet for CollectionDeadlineScorer {
+    fn advance(&mut self) -> DocId {
+        if self.terminated || self.is_deadline_exceeded() {
+            return TERMINATED;
+        }
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.terminated || self.is_deadline_exceeded() {
+            return TERMINATED;
+        }
+        self.scorer.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; COLLECT_BLOCK_BUFFER_LEN]) -> usize {
+        if self.terminated || self.is_deadline_exceeded() {
+            return 0;
+        }
+        self.scorer.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        if self.terminated {
+            return TERMINATED;
+        }
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn cost(&self) -> u64 {
+        self.scorer.cost()
+    }
+}
+
+impl Scorer for CollectionDeadlineScorer {
+    #[inline]
+    fn score(&mut self) -> Score {
+        self.scorer.score()
+    }
+}
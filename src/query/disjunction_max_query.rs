@@ -109,6 +109,12 @@ impl Query for DisjunctionMaxQuery {
             disjunct.query_terms(visitor);
         }
     }
+
+    fn query_terms_with_boost<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, Score)) {
+        for disjunct in &self.disjuncts {
+            disjunct.query_terms_with_boost(visitor);
+        }
+    }
 }
 
 impl DisjunctionMaxQuery {
@@ -0,0 +1,171 @@
+use columnar::StrColumn;
+use common::BitSet;
+
+use super::{ConstScorer, EmptyScorer};
+use crate::index::SegmentReader;
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Type;
+use crate::{DocId, Score, TantivyError};
+
+/// Query matching the documents holding a given value in a str fast field.
+///
+/// The query is evaluated on the fast field alone, so that the field does not need to be
+/// indexed: in each segment, the value is looked up in the dictionary of the column, and the
+/// documents holding its ordinal match. A segment where no document holds the value does not
+/// match anything. Documents with several values match if any of them is equal to `value`.
+///
+/// `value` is compared to the values as they are stored in the column. If the fast field has a
+/// tokenizer, `value` must already be normalized by it.
+///
+/// All of the matched documents get the score 1.0.
+#[derive(Clone, Debug)]
+pub struct FastFieldTermQuery {
+    field_name: String,
+    value: String,
+}
+
+impl FastFieldTermQuery {
+    /// Creates a new `FastFieldTermQuery`, matching the documents of which the str fast field
+    /// `field_name` holds `value`.
+    ///
+    /// Executing the search returns an error if the field does not exist, or is not a str
+    /// fast field.
+    pub fn new(field_name: String, value: String) -> FastFieldTermQuery {
+        FastFieldTermQuery { field_name, value }
+    }
+}
+
+impl Query for FastFieldTermQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field = schema.get_field(&self.field_name)?;
+        let field_type = schema.get_field_entry(field).field_type();
+        if field_type.value_type() != Type::Str || !field_type.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {} is not a str fast field.",
+                self.field_name
+            )));
+        }
+        Ok(Box::new(FastFieldTermWeight {
+            field_name: self.field_name.clone(),
+            value: self.value.clone(),
+        }))
+    }
+}
+
+/// Weight associated with the `FastFieldTermQuery` query.
+pub struct FastFieldTermWeight {
+    field_name: String,
+    value: String,
+}
+
+impl FastFieldTermWeight {
+    /// Returns the documents holding the value, or `None` if the value is not in the
+    /// dictionary of the column.
+    fn matching_docs(
+        &self,
+        str_column: &StrColumn,
+        max_doc: DocId,
+    ) -> crate::Result<Option<BitSet>> {
+        let Some(term_ord) = str_column.dictionary().term_ord(&self.value)? else {
+            return Ok(None);
+        };
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let mut doc_ids = Vec::new();
+        str_column
+            .ords()
+            .get_docids_for_value_range(term_ord..=term_ord, 0..max_doc, &mut doc_ids);
+        for doc in doc_ids {
+            doc_bitset.insert(doc);
+        }
+        Ok(Some(doc_bitset))
+    }
+}
+
+impl Weight for FastFieldTermWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(str_column) = reader.fast_fields().str(&self.field_name)? else {
+            // None of the documents of the segment have a value.
+            return Ok(Box::new(EmptyScorer));
+        };
+        let Some(doc_bitset) = self.matching_docs(&str_column, reader.max_doc())? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let docset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("FastFieldTermQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastFieldTermQuery;
+    use crate::collector::DocSetCollector;
+    use crate::query::{Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_fast_field_term_query_same_as_indexed_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", FAST);
+        let tag_indexed = schema_builder.add_text_field("tag_indexed", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let segments: [&[&[&str]]; 3] = [
+            &[&["red"], &["blue"], &[], &["red", "green"]],
+            &[&["green"], &["blue", "blue"]],
+            // A segment where none of the documents hold `red`.
+            &[&["yellow"]],
+        ];
+        for segment_docs in segments {
+            for tags in segment_docs {
+                let mut doc = TantivyDocument::default();
+                for tag_value in *tags {
+                    doc.add_text(tag, tag_value);
+                    doc.add_text(tag_indexed, tag_value);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let matching_docs = |query: &dyn Query| -> crate::Result<Vec<DocAddress>> {
+            let mut docs: Vec<DocAddress> = searcher
+                .search(query, &DocSetCollector)?
+                .into_iter()
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+        for value in ["red", "blue", "green", "yellow", "purple"] {
+            let fast_field_query = FastFieldTermQuery::new("tag".to_string(), value.to_string());
+            let term_query = TermQuery::new(
+                Term::from_field_text(tag_indexed, value),
+                IndexRecordOption::Basic,
+            );
+            assert_eq!(
+                matching_docs(&fast_field_query)?,
+                matching_docs(&term_query)?,
+                "{value}"
+            );
+        }
+        let red_query = FastFieldTermQuery::new("tag".to_string(), "red".to_string());
+        assert_eq!(matching_docs(&red_query)?.len(), 2);
+
+        let explanation = red_query.explain(&searcher, matching_docs(&red_query)?[0])?;
+        assert_eq!(explanation.value(), 1.0);
+
+        let not_fast_query = FastFieldTermQuery::new("tag_indexed".to_string(), "red".to_string());
+        assert!(searcher.search(&not_fast_query, &DocSetCollector).is_err());
+        Ok(())
+    }
+}
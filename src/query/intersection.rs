@@ -1,7 +1,7 @@
 use common::TinySet;
 
 use super::size_hint::estimate_intersection;
-use crate::docset::{DocSet, SeekDangerResult, BLOCK_NUM_TINYBITSETS, TERMINATED};
+use crate::docset::{DocSet, SeekDangerResult, BLOCK_NUM_TINYBITSETS, BLOCK_WINDOW, TERMINATED};
 use crate::query::term_query::TermScorer;
 use crate::query::{EmptyScorer, Scorer};
 use crate::{DocId, Score};
@@ -55,6 +55,156 @@ pub fn intersect_scorers(
     })
 }
 
+/// Strategy used by [`intersect_docsets_with_strategy`] to intersect `DocSet`s.
+///
+/// All of the strategies give the same result, but their costs differ depending on the sizes
+/// of the `DocSet`s.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IntersectionStrategy {
+    /// Advances all of the `DocSet`s in lockstep, one document at a time.
+    ///
+    /// This is the cheapest strategy for small `DocSet`s of similar sizes, as skipping would
+    /// not save much.
+    Zipper,
+    /// Iterates over the smallest `DocSet`, and seeks each of its documents in the others.
+    ///
+    /// `DocSet`s backed by posting lists seek using their skip index, so that the cost is
+    /// driven by the size of the smallest `DocSet`.
+    Galloping,
+    /// Fills a bitmask of 1024 documents from each `DocSet`, and ANDs the bitmasks together
+    /// 64 documents at a time.
+    ///
+    /// This is the cheapest strategy for large and dense `DocSet`s of similar sizes.
+    Bitset,
+}
+
+impl IntersectionStrategy {
+    /// The smallest `DocSet` is galloped through if the second smallest one is at least this
+    /// many times larger.
+    const GALLOPING_MIN_SIZE_RATIO: u32 = 32;
+
+    /// `DocSet`s of similar sizes are intersected by bitset if the smallest one has at least
+    /// this many documents.
+    const BITSET_MIN_SIZE: u32 = 4 * BLOCK_WINDOW;
+
+    /// Selects the strategy to intersect `DocSet`s given their [`DocSet::size_hint`]s.
+    ///
+    /// - If the smallest `DocSet` is at least 32 times smaller than all of the others,
+    ///   [`Galloping`](IntersectionStrategy::Galloping) is selected.
+    /// - Otherwise, if the smallest `DocSet` has at least 4096 documents,
+    ///   [`Bitset`](IntersectionStrategy::Bitset) is selected.
+    /// - Otherwise, [`Zipper`](IntersectionStrategy::Zipper) is selected.
+    pub fn select(size_hints: &[u32]) -> IntersectionStrategy {
+        let mut smallest = u32::MAX;
+        let mut second_smallest = u32::MAX;
+        for &size_hint in size_hints {
+            if size_hint < smallest {
+                second_smallest = smallest;
+                smallest = size_hint;
+            } else if size_hint < second_smallest {
+                second_smallest = size_hint;
+            }
+        }
+        if second_smallest / Self::GALLOPING_MIN_SIZE_RATIO >= smallest.max(1) {
+            IntersectionStrategy::Galloping
+        } else if smallest >= Self::BITSET_MIN_SIZE {
+            IntersectionStrategy::Bitset
+        } else {
+            IntersectionStrategy::Zipper
+        }
+    }
+}
+
+/// Calls `callback` with each of the documents present in all of the `docsets`, in increasing
+/// order.
+///
+/// The strategy is selected from the [`DocSet::size_hint`]s of the `docsets` by
+/// [`IntersectionStrategy::select`]. No document is produced if `docsets` is empty.
+///
+/// Unlike [`intersect_scorers`], this does not require the `docsets` to be scorers, nor to come
+/// from a query, which makes it usable to intersect posting lists or any other `DocSet`.
+pub fn intersect_docsets(docsets: Vec<Box<dyn DocSet>>, callback: &mut dyn FnMut(DocId)) {
+    let size_hints: Vec<u32> = docsets.iter().map(|docset| docset.size_hint()).collect();
+    let strategy = IntersectionStrategy::select(&size_hints);
+    intersect_docsets_with_strategy(docsets, strategy, callback);
+}
+
+/// Same as [`intersect_docsets`], using the given strategy.
+pub fn intersect_docsets_with_strategy(
+    mut docsets: Vec<Box<dyn DocSet>>,
+    strategy: IntersectionStrategy,
+    callback: &mut dyn FnMut(DocId),
+) {
+    if docsets.is_empty() {
+        return;
+    }
+    // The smallest docset drives the intersection.
+    docsets.sort_by_key(|docset| docset.size_hint());
+    match strategy {
+        IntersectionStrategy::Zipper => intersect_zipper(&mut docsets, callback),
+        IntersectionStrategy::Galloping => intersect_galloping(&mut docsets, callback),
+        IntersectionStrategy::Bitset => intersect_bitset(&mut docsets, callback),
+    }
+}
+
+// In the functions below, all of the docsets are positioned on documents lower or equal to
+// `candidate` as we enter the loop, so that seeking it is legal.
+
+fn intersect_zipper(docsets: &mut [Box<dyn DocSet>], callback: &mut dyn FnMut(DocId)) {
+    let mut candidate = docsets.iter().map(|docset| docset.doc()).max().unwrap();
+    'outer: while candidate != TERMINATED {
+        for docset in docsets.iter_mut() {
+            let mut doc = docset.doc();
+            while doc < candidate {
+                doc = docset.advance();
+            }
+            if doc > candidate {
+                candidate = doc;
+                continue 'outer;
+            }
+        }
+        callback(candidate);
+        candidate = docsets[0].advance();
+    }
+}
+
+fn intersect_galloping(docsets: &mut [Box<dyn DocSet>], callback: &mut dyn FnMut(DocId)) {
+    let mut candidate = docsets.iter().map(|docset| docset.doc()).max().unwrap();
+    'outer: while candidate != TERMINATED {
+        for docset in docsets.iter_mut() {
+            let doc = docset.seek(candidate);
+            if doc > candidate {
+                candidate = doc;
+                continue 'outer;
+            }
+        }
+        callback(candidate);
+        candidate = docsets[0].advance();
+    }
+}
+
+fn intersect_bitset(docsets: &mut [Box<dyn DocSet>], callback: &mut dyn FnMut(DocId)) {
+    let mut next_base = docsets.iter().map(|docset| docset.doc()).max().unwrap();
+    'outer: while next_base < TERMINATED {
+        let base = next_base;
+        let mut mask = EMPTY_BLOCK;
+        next_base = docsets[0].fill_bitset_block(base, &mut mask);
+        for docset in &mut docsets[1..] {
+            let mut docset_mask = EMPTY_BLOCK;
+            next_base = next_base.max(docset.fill_bitset_block(base, &mut docset_mask));
+            if and_blocks_and_return_is_empty(&mut mask, &docset_mask) {
+                continue 'outer;
+            }
+        }
+        for (bucket, tinyset) in mask.into_iter().enumerate() {
+            let bucket_base = base + bucket as u32 * 64;
+            for delta in tinyset {
+                callback(bucket_base + delta);
+            }
+        }
+    }
+}
+
 /// Creates a `DocSet` that iterate through the intersection of two or more `DocSet`s.
 pub struct Intersection<TDocSet: DocSet, TOtherDocSet: DocSet = Box<dyn Scorer>> {
     left: TDocSet,
@@ -332,14 +482,106 @@ where
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
-    use super::Intersection;
+    use super::{
+        intersect_docsets, intersect_docsets_with_strategy, Intersection, IntersectionStrategy,
+    };
     use crate::collector::Count;
     use crate::docset::{DocSet, TERMINATED};
     use crate::postings::tests::test_skip_against_unoptimized;
     use crate::query::{QueryParser, VecDocSet};
     use crate::schema::{Schema, TEXT};
-    use crate::Index;
+    use crate::{DocId, Index};
+
+    fn random_docs_list(seed: u64, sizes: &[usize]) -> Vec<Vec<DocId>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        sizes
+            .iter()
+            .map(|&size| {
+                let mut docs: Vec<DocId> =
+                    (0..size).map(|_| rng.random_range(0..100_000)).collect();
+                docs.sort_unstable();
+                docs.dedup();
+                docs
+            })
+            .collect()
+    }
+
+    fn boxed_docsets(docs_list: &[Vec<DocId>]) -> Vec<Box<dyn DocSet>> {
+        docs_list
+            .iter()
+            .map(|docs| Box::new(VecDocSet::from(docs.clone())) as Box<dyn DocSet>)
+            .collect()
+    }
+
+    #[test]
+    fn test_intersection_strategy_select() {
+        assert_eq!(
+            IntersectionStrategy::select(&[10, 50_000]),
+            IntersectionStrategy::Galloping
+        );
+        assert_eq!(
+            IntersectionStrategy::select(&[50_000, 10, 20_000]),
+            IntersectionStrategy::Galloping
+        );
+        assert_eq!(
+            IntersectionStrategy::select(&[10, 50_000, 20]),
+            IntersectionStrategy::Zipper
+        );
+        assert_eq!(
+            IntersectionStrategy::select(&[10_000, 50_000]),
+            IntersectionStrategy::Bitset
+        );
+        assert_eq!(
+            IntersectionStrategy::select(&[1_000, 2_000]),
+            IntersectionStrategy::Zipper
+        );
+    }
+
+    #[test]
+    fn test_intersect_docsets_against_brute_force() {
+        let sizes_list: [&[usize]; 9] = [
+            &[],
+            &[1_000],
+            &[1, 90_000],
+            &[5, 30, 80_000],
+            &[0, 1_000],
+            &[20, 25],
+            &[500, 300, 400],
+            &[40_000, 60_000],
+            &[60_000, 90_000, 99_000, 2_000],
+        ];
+        for (seed, sizes) in sizes_list.into_iter().enumerate() {
+            let docs_list = random_docs_list(seed as u64, sizes);
+            let expected: Vec<DocId> = docs_list
+                .first()
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|doc| {
+                    docs_list[1..]
+                        .iter()
+                        .all(|docs| docs.binary_search(doc).is_ok())
+                })
+                .collect();
+            let mut docs = Vec::new();
+            intersect_docsets(boxed_docsets(&docs_list), &mut |doc| docs.push(doc));
+            assert_eq!(docs, expected, "sizes: {sizes:?}");
+            for strategy in [
+                IntersectionStrategy::Zipper,
+                IntersectionStrategy::Galloping,
+                IntersectionStrategy::Bitset,
+            ] {
+                let mut docs = Vec::new();
+                intersect_docsets_with_strategy(boxed_docsets(&docs_list), strategy, &mut |doc| {
+                    docs.push(doc)
+                });
+                assert_eq!(docs, expected, "sizes: {sizes:?}, strategy: {strategy:?}");
+            }
+        }
+    }
 
     #[test]
     fn test_intersection() {
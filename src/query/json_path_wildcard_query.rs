@@ -0,0 +1,313 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+
+use common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP};
+
+use super::phrase_prefix_query::prefix_end;
+use crate::core::json_utils::split_json_path;
+use crate::query::{BooleanQuery, EmptyQuery, EnableScoring, Occur, Query, Weight};
+use crate::schema::{Field, Term, Type};
+use crate::termdict::TermDictionary;
+use crate::TantivyError;
+
+/// Path segment matching any single segment of a json path.
+const WILDCARD_SEGMENT: &str = "*";
+
+/// Default maximum number of paths a [`JsonPathWildcardQuery`] expands to.
+pub const DEFAULT_MAX_JSON_PATHS: usize = 1_000;
+
+/// Returns true if one of the segments of `json_path` is a wildcard.
+pub(crate) fn is_json_path_wildcard(json_path: &str) -> bool {
+    split_json_path(json_path)
+        .iter()
+        .any(|segment| segment == WILDCARD_SEGMENT)
+}
+
+/// A query over the json paths matching a pattern, in which `*` segments match any single
+/// segment.
+///
+/// For instance, the pattern `*.status` of a json field `attrs` matches `attrs.http.status`
+/// and `attrs.grpc.status`, but neither `attrs.status` nor `attrs.http.response.status`.
+///
+/// When building its weight, the query enumerates the matching paths from the term
+/// dictionaries of the searcher, and expands to the disjunction of the queries returned by
+/// `query_for_path` for each of them. This requires a searcher: the query cannot be used to
+/// delete documents. If more than [`max_paths`](JsonPathWildcardQuery::set_max_paths) paths
+/// match, building the weight fails.
+///
+/// The query parser builds this query for json paths with a `*` segment, as in
+/// `attrs.*.status:error`.
+#[derive(Clone)]
+pub struct JsonPathWildcardQuery {
+    field: Field,
+    path_pattern: String,
+    // `None` for wildcard segments.
+    segments: Vec<Option<String>>,
+    max_paths: usize,
+    query_for_path: Arc<dyn Fn(Term) -> Box<dyn Query> + Send + Sync>,
+}
+
+impl fmt::Debug for JsonPathWildcardQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonPathWildcardQuery")
+            .field("field", &self.field)
+            .field("path_pattern", &self.path_pattern)
+            .field("max_paths", &self.max_paths)
+            .finish()
+    }
+}
+
+impl JsonPathWildcardQuery {
+    /// Creates a new query over the paths of the json `field` matching `path_pattern`.
+    ///
+    /// `query_for_path` receives a json term holding a matching path, to which the value
+    /// remains to be appended, and returns the query for that path.
+    ///
+    /// As in [`Term::from_field_json_path`], dots within a segment are escaped with a backslash
+    /// unless `expand_dots_enabled` is set.
+    pub fn new(
+        field: Field,
+        path_pattern: &str,
+        expand_dots_enabled: bool,
+        query_for_path: impl Fn(Term) -> Box<dyn Query> + Send + Sync + 'static,
+    ) -> JsonPathWildcardQuery {
+        let mut segments: Vec<Option<String>> = Vec::new();
+        for segment in split_json_path(path_pattern) {
+            if segment == WILDCARD_SEGMENT {
+                segments.push(None);
+            } else if expand_dots_enabled {
+                segments.extend(segment.split('.').map(|segment| Some(segment.to_string())));
+            } else {
+                segments.push(Some(segment));
+            }
+        }
+        JsonPathWildcardQuery {
+            field,
+            path_pattern: path_pattern.to_string(),
+            segments,
+            max_paths: DEFAULT_MAX_JSON_PATHS,
+            query_for_path: Arc::new(query_for_path),
+        }
+    }
+
+    /// Sets the maximum number of paths the query expands to.
+    ///
+    /// Defaults to [`DEFAULT_MAX_JSON_PATHS`].
+    pub fn set_max_paths(&mut self, max_paths: usize) {
+        self.max_paths = max_paths;
+    }
+
+    /// Returns the path pattern of the query.
+    pub fn path_pattern(&self) -> &str {
+        &self.path_pattern
+    }
+
+    /// Path bytes shared by all of the matching paths, up to the first wildcard segment.
+    fn fixed_prefix(&self) -> Vec<u8> {
+        let mut prefix: Vec<u8> = Vec::new();
+        for segment in &self.segments {
+            let Some(segment) = segment else {
+                break;
+            };
+            prefix.extend_from_slice(segment.as_bytes());
+            prefix.push(JSON_PATH_SEGMENT_SEP);
+        }
+        prefix
+    }
+
+    fn is_matching_path(&self, path: &[u8]) -> bool {
+        let mut path_segments = path.split(|&byte| byte == JSON_PATH_SEGMENT_SEP);
+        for segment in &self.segments {
+            match (segment, path_segments.next()) {
+                (_, None) => return false,
+                (None, Some(_)) => {}
+                (Some(segment), Some(path_segment)) => {
+                    if segment.as_bytes() != path_segment {
+                        return false;
+                    }
+                }
+            }
+        }
+        path_segments.next().is_none()
+    }
+
+    /// Adds the matching paths of the term dictionary to `paths`, visiting each distinct path
+    /// once.
+    fn collect_matching_paths(
+        &self,
+        term_dict: &TermDictionary,
+        paths: &mut BTreeSet<Vec<u8>>,
+    ) -> crate::Result<()> {
+        let prefix = self.fixed_prefix();
+        let prefix_end_opt = prefix_end(&prefix);
+        let mut lower_bound = prefix;
+        loop {
+            let mut stream_builder = term_dict.range().ge(&lower_bound);
+            if let Some(prefix_end) = &prefix_end_opt {
+                stream_builder = stream_builder.lt(prefix_end);
+            }
+            let mut stream = stream_builder.into_stream()?;
+            if !stream.advance() {
+                return Ok(());
+            }
+            let key = stream.key();
+            let Some(path_len) = key.iter().position(|&byte| byte == JSON_END_OF_PATH) else {
+                return Ok(());
+            };
+            let path = &key[..path_len];
+            if self.is_matching_path(path)
+                && paths.insert(path.to_vec())
+                && paths.len() > self.max_paths
+            {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The json path pattern `{}` matches more than {} paths",
+                    self.path_pattern, self.max_paths
+                )));
+            }
+            // Skips the other values of this path. Its children start right after.
+            lower_bound = path.to_vec();
+            lower_bound.push(JSON_END_OF_PATH + 1);
+        }
+    }
+}
+
+impl Query for JsonPathWildcardQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let searcher = enable_scoring.searcher().ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                "Expanding a json path pattern requires a searcher".to_string(),
+            )
+        })?;
+        let mut paths: BTreeSet<Vec<u8>> = BTreeSet::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field)?;
+            self.collect_matching_paths(inverted_index.terms(), &mut paths)?;
+        }
+        if paths.is_empty() {
+            return EmptyQuery.weight(enable_scoring);
+        }
+        let subqueries: Vec<(Occur, Box<dyn Query>)> = paths
+            .into_iter()
+            .map(|path| {
+                let mut term = Term::with_type_and_field(Type::Json, self.field);
+                term.append_bytes(&path);
+                term.append_bytes(&[JSON_END_OF_PATH]);
+                (Occur::Should, (self.query_for_path)(term))
+            })
+            .collect();
+        BooleanQuery::new(subqueries).weight(enable_scoring)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonPathWildcardQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::{Query, QueryParser, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument, TantivyError};
+
+    fn create_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let attrs = schema_builder.add_json_field("attrs", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for attrs_json in [
+            r#"{"http": {"status": "error"}, "grpc": {"status": "ok"}}"#,
+            r#"{"grpc": {"status": "error", "code": 14}}"#,
+            r#"{"status": "error", "db": {"pool": {"status": "error"}}}"#,
+            r#"{"http": {"status": "ok"}, "db": {"status": ["ok", "bad error"]}}"#,
+            r#"{"http": {"message": "error"}}"#,
+        ] {
+            let mut doc = TantivyDocument::default();
+            doc.add_object(attrs, serde_json::from_str(attrs_json).unwrap());
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn matching_docs(index: &Index, query: &dyn Query) -> crate::Result<Vec<u32>> {
+        let searcher = index.reader()?.searcher();
+        let mut docs: Vec<u32> = searcher
+            .search(query, &DocSetCollector)?
+            .into_iter()
+            .map(|doc_address| doc_address.doc_id)
+            .collect();
+        docs.sort();
+        Ok(docs)
+    }
+
+    #[test]
+    fn test_json_path_wildcard_query() -> crate::Result<()> {
+        let index = create_index()?;
+        let attrs = index.schema().get_field("attrs")?;
+        let query = JsonPathWildcardQuery::new(attrs, "*.status", false, |mut term| {
+            term.append_type_and_str("error");
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+        });
+        assert_eq!(matching_docs(&index, &query)?, vec![0, 1, 3]);
+        let query = JsonPathWildcardQuery::new(attrs, "*.*.status", false, |mut term| {
+            term.append_type_and_str("error");
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+        });
+        assert_eq!(matching_docs(&index, &query)?, vec![2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_path_wildcard_query_parser() -> crate::Result<()> {
+        let index = create_index()?;
+        let attrs = index.schema().get_field("attrs")?;
+        let query_parser = QueryParser::for_index(&index, vec![attrs]);
+        let parse = |query: &str| query_parser.parse_query(query).unwrap();
+        assert_eq!(
+            matching_docs(&index, parse("attrs.*.status:error").as_ref())?,
+            vec![0, 1, 3]
+        );
+        assert_eq!(
+            matching_docs(&index, parse("attrs.*.status:\"bad error\"").as_ref())?,
+            vec![3]
+        );
+        assert_eq!(
+            matching_docs(&index, parse("attrs.db.*.status:error").as_ref())?,
+            vec![2]
+        );
+        // Numbers are looked up as numbers, in every matching path.
+        assert_eq!(
+            matching_docs(&index, parse("attrs.*.code:14").as_ref())?,
+            vec![1]
+        );
+        assert_eq!(
+            matching_docs(
+                &index,
+                parse("attrs.*.status:error -attrs.grpc.status:ok").as_ref()
+            )?,
+            vec![1, 3]
+        );
+        // Wildcards are only supported by term and phrase queries on json fields.
+        assert!(query_parser.parse_query("attrs.*.code:[1 TO 20]").is_err());
+        assert!(query_parser.parse_query("attrs.*.code:*").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_path_wildcard_query_max_paths() -> crate::Result<()> {
+        let index = create_index()?;
+        let attrs = index.schema().get_field("attrs")?;
+        let mut query = JsonPathWildcardQuery::new(attrs, "*.status", false, |mut term| {
+            term.append_type_and_str("error");
+            Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+        });
+        query.set_max_paths(2);
+        let searcher = index.reader()?.searcher();
+        assert!(matches!(
+            searcher.search(&query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        query.set_max_paths(3);
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        Ok(())
+    }
+}
@@ -2,17 +2,21 @@ mod all_query;
 mod automaton_weight;
 mod bitset;
 mod bm25;
+mod bool_fast_field_query;
 mod boolean_query;
 mod boost_query;
 mod const_score_query;
+mod deadline_weight;
 mod disjunction;
 mod disjunction_max_query;
 mod empty_query;
 mod exclude;
 mod exist_query;
 mod explanation;
+mod fast_field_term_query;
 mod fuzzy_query;
 mod intersection;
+mod json_path_wildcard_query;
 mod more_like_this;
 mod phrase_prefix_query;
 mod phrase_query;
@@ -38,18 +42,27 @@ pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
 pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
-pub use self::boolean_query::{BooleanQuery, BooleanWeight};
+pub use self::bool_fast_field_query::BoolFastFieldQuery;
+pub use self::boolean_query::{BooleanQuery, BooleanQueryBuilder, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
+pub(crate) use self::deadline_weight::CollectionDeadlineWeight;
 pub use self::disjunction_max_query::DisjunctionMaxQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::{Exclude, ExclusionSet};
 pub use self::exist_query::ExistsQuery;
+pub(crate) use self::explanation::does_not_match;
 pub use self::explanation::Explanation;
+pub use self::fast_field_term_query::FastFieldTermQuery;
 #[cfg(test)]
 pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
-pub use self::intersection::{intersect_scorers, Intersection};
+pub use self::intersection::{
+    intersect_docsets, intersect_docsets_with_strategy, intersect_scorers, Intersection,
+    IntersectionStrategy,
+};
+pub(crate) use self::json_path_wildcard_query::is_json_path_wildcard;
+pub use self::json_path_wildcard_query::{JsonPathWildcardQuery, DEFAULT_MAX_JSON_PATHS};
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
 pub use self::phrase_query::regex_phrase_query::{wildcard_query_to_regex_str, RegexPhraseQuery};
@@ -63,7 +76,7 @@ pub use self::score_combiner::{DisjunctionMaxCombiner, ScoreCombiner, SumCombine
 pub use self::scorer::Scorer;
 pub use self::set_query::TermSetQuery;
 pub use self::term_query::TermQuery;
-pub use self::union::BufferedUnionScorer;
+pub use self::union::{union_docsets, union_docsets_with_counts, BufferedUnionScorer};
 #[cfg(test)]
 pub use self::vec_docset::VecDocSet;
 pub use self::weight::Weight;
@@ -267,6 +267,17 @@ impl MoreLikeThis {
                     }
                 }
             }
+            FieldType::F32(_) => {
+                for value in values {
+                    let val = value.as_f64().ok_or_else(|| {
+                        TantivyError::InvalidArgument("invalid value".to_string())
+                    })?;
+                    if !self.is_noise_word(val.to_string()) {
+                        let term = Term::from_field_f64(field, f64::from(val as f32));
+                        *term_frequencies.entry(term).or_insert(0) += 1;
+                    }
+                }
+            }
             _ => {}
         }
         Ok(())
@@ -155,7 +155,8 @@ impl RegexPhraseQuery {
             bm25_weight_opt,
             self.max_expansions,
             self.slop,
-        );
+        )
+        .with_rewrite_deadline(enable_scoring.rewrite_deadline());
         Ok(weight)
     }
 }
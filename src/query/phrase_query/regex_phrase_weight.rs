@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Instant;
 
 use common::BitSet;
 use tantivy_fst::Regex;
@@ -24,6 +25,7 @@ pub struct RegexPhraseWeight {
     similarity_weight_opt: Option<Bm25Weight>,
     slop: u32,
     max_expansions: u32,
+    rewrite_deadline: Option<Instant>,
 }
 
 impl RegexPhraseWeight {
@@ -42,9 +44,19 @@ impl RegexPhraseWeight {
             similarity_weight_opt,
             slop,
             max_expansions,
+            rewrite_deadline: None,
         }
     }
 
+    /// Sets the deadline of the enumeration of the terms matching the regexes.
+    ///
+    /// See [`AutomatonWeight::with_rewrite_deadline`].
+    #[must_use]
+    pub fn with_rewrite_deadline(mut self, rewrite_deadline: Option<Instant>) -> Self {
+        self.rewrite_deadline = rewrite_deadline;
+        self
+    }
+
     fn fieldnorm_reader(&self, reader: &SegmentReader) -> crate::Result<FieldNormReader> {
         if self.similarity_weight_opt.is_some() {
             if let Some(fieldnorm_reader) = reader.fieldnorms_readers().get_field(self.field)? {
@@ -72,7 +84,8 @@ impl RegexPhraseWeight {
                 .map_err(|e| crate::TantivyError::InvalidArgument(format!("Invalid regex: {e}")))?;
 
             let automaton: AutomatonWeight<Regex> =
-                AutomatonWeight::new(self.field, Arc::new(regex));
+                AutomatonWeight::new(self.field, Arc::new(regex))
+                    .with_rewrite_deadline(self.rewrite_deadline);
             let term_infos = automaton.get_match_term_infos(reader)?;
             // If term_infos is empty, the phrase can not match any documents.
             if term_infos.is_empty() {
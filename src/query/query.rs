@@ -1,4 +1,5 @@
 use std::fmt;
+use std::time::Instant;
 
 use downcast_rs::impl_downcast;
 
@@ -7,7 +8,7 @@ use super::Weight;
 use crate::core::searcher::Searcher;
 use crate::query::Explanation;
 use crate::schema::Schema;
-use crate::{DocAddress, Term};
+use crate::{DocAddress, Score, Term};
 
 /// Argument used in `Query::weight(..)`
 #[derive(Copy, Clone)]
@@ -22,6 +23,11 @@ pub enum EnableScoring<'a> {
         /// Normally this should be the [Searcher], but you can specify a custom
         /// one to adjust the statistics.
         statistics_provider: &'a dyn Bm25StatisticsProvider,
+
+        /// Deadline of the rewrite phase of the search, if any.
+        ///
+        /// See [`SearchOptions`](crate::SearchOptions).
+        rewrite_deadline: Option<Instant>,
     },
     /// Pass this to disable scoring.
     /// This can improve performance.
@@ -30,6 +36,10 @@ pub enum EnableScoring<'a> {
         schema: &'a Schema,
         /// Searcher should be provided if available.
         searcher_opt: Option<&'a Searcher>,
+        /// Deadline of the rewrite phase of the search, if any.
+        ///
+        /// See [`SearchOptions`](crate::SearchOptions).
+        rewrite_deadline: Option<Instant>,
     },
 }
 
@@ -39,6 +49,7 @@ impl<'a> EnableScoring<'a> {
         EnableScoring::Enabled {
             searcher,
             statistics_provider: searcher,
+            rewrite_deadline: None,
         }
     }
 
@@ -50,6 +61,7 @@ impl<'a> EnableScoring<'a> {
         EnableScoring::Enabled {
             statistics_provider,
             searcher,
+            rewrite_deadline: None,
         }
     }
 
@@ -58,6 +70,7 @@ impl<'a> EnableScoring<'a> {
         EnableScoring::Disabled {
             schema: searcher.schema(),
             searcher_opt: Some(searcher),
+            rewrite_deadline: None,
         }
     }
 
@@ -66,6 +79,38 @@ impl<'a> EnableScoring<'a> {
         Self::Disabled {
             schema,
             searcher_opt: None,
+            rewrite_deadline: None,
+        }
+    }
+
+    /// Sets the deadline of the rewrite phase of the search.
+    ///
+    /// Weights enumerating the terms matching the query, such as the ones of
+    /// [`RegexQuery`](crate::query::RegexQuery), stop with
+    /// [`TantivyError::RewriteDeadlineExceeded`](crate::TantivyError::RewriteDeadlineExceeded)
+    /// once it is passed.
+    #[must_use]
+    pub fn with_rewrite_deadline(mut self, deadline: Option<Instant>) -> EnableScoring<'a> {
+        match &mut self {
+            EnableScoring::Enabled {
+                rewrite_deadline, ..
+            }
+            | EnableScoring::Disabled {
+                rewrite_deadline, ..
+            } => *rewrite_deadline = deadline,
+        }
+        self
+    }
+
+    /// Returns the deadline of the rewrite phase of the search, if any.
+    pub fn rewrite_deadline(&self) -> Option<Instant> {
+        match self {
+            EnableScoring::Enabled {
+                rewrite_deadline, ..
+            }
+            | EnableScoring::Disabled {
+                rewrite_deadline, ..
+            } => *rewrite_deadline,
         }
     }
 
@@ -160,6 +205,15 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Extract all of the terms associated with the query, together with the
+    /// boost applied to them, and pass them to the given closure.
+    ///
+    /// By default, the terms reported by [`Query::query_terms`] are given a boost
+    /// of 1. Queries wrapping other queries propagate the boosts of their children.
+    fn query_terms_with_boost<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, Score)) {
+        self.query_terms(&mut |term, _| visitor(term, 1.0));
+    }
 }
 
 /// Implements `box_clone`.
@@ -188,6 +242,10 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn query_terms_with_boost<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, Score)) {
+        self.as_ref().query_terms_with_boost(visitor);
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -24,10 +24,66 @@ pub enum LogicalLiteral {
         elements: Vec<Term>,
     },
     All,
+    Exists {
+        field_name: String,
+        json_subpaths: bool,
+    },
+    /// Value of a bool field which is fast but not indexed.
+    BoolFastField {
+        field_name: String,
+        value: bool,
+        match_missing: bool,
+    },
+    /// Value of a str field which is fast but not indexed.
+    StrFastField {
+        field_name: String,
+        value: String,
+    },
     Regex {
         pattern: Arc<Regex>,
         field: Field,
     },
+    /// Literals over the paths of a json field matching a pattern with wildcard segments.
+    ///
+    /// The terms of `literals` hold the pattern as their path, and get the matching paths
+    /// substituted when the query is built.
+    JsonPathWildcard {
+        field: Field,
+        path_pattern: String,
+        expand_dots_enabled: bool,
+        literals: Vec<LogicalLiteral>,
+    },
+}
+
+impl LogicalLiteral {
+    /// Replaces the json path of the terms of the literal by the one of `path_term`.
+    pub fn with_json_path(&self, path_term: &Term) -> LogicalLiteral {
+        let replace_path = |term: &Term| -> Term {
+            let value = term.value();
+            let Some((_, value_bytes)) = value.as_json() else {
+                return term.clone();
+            };
+            let mut new_term = path_term.clone();
+            new_term.append_bytes(value_bytes.as_serialized());
+            new_term
+        };
+        match self {
+            LogicalLiteral::Term(term) => LogicalLiteral::Term(replace_path(term)),
+            LogicalLiteral::Phrase {
+                terms,
+                slop,
+                prefix,
+            } => LogicalLiteral::Phrase {
+                terms: terms
+                    .iter()
+                    .map(|(position, term)| (*position, replace_path(term)))
+                    .collect(),
+                slop: *slop,
+                prefix: *prefix,
+            },
+            _ => self.clone(),
+        }
+    }
 }
 
 pub enum LogicalAst {
@@ -45,6 +101,27 @@ impl LogicalAst {
         }
     }
 
+    /// Flips whether the bool fast field literals of the ast match the documents missing the
+    /// field.
+    ///
+    /// Applied to the ast under a negation, it makes the negation leave those documents out,
+    /// as their value is unknown. Nested negations flip the literals back.
+    pub fn flip_bool_fast_field_match_missing(&mut self) {
+        match self {
+            LogicalAst::Clause(clauses) => {
+                for (_, sub_ast) in clauses {
+                    sub_ast.flip_bool_fast_field_match_missing();
+                }
+            }
+            LogicalAst::Leaf(literal) => {
+                if let LogicalLiteral::BoolFastField { match_missing, .. } = literal.as_mut() {
+                    *match_missing = !*match_missing;
+                }
+            }
+            LogicalAst::Boost(ast, _) => ast.flip_bool_fast_field_match_missing(),
+        }
+    }
+
     // TODO: Move to rewrite_ast in query_grammar
     pub fn simplify(self) -> LogicalAst {
         match self {
@@ -155,10 +232,37 @@ impl fmt::Debug for LogicalLiteral {
                 write!(formatter, "]")
             }
             LogicalLiteral::All => write!(formatter, "*"),
+            LogicalLiteral::Exists { ref field_name, .. } => {
+                write!(formatter, "$exists({field_name:?})")
+            }
+            LogicalLiteral::BoolFastField {
+                ref field_name,
+                value,
+                match_missing,
+            } => {
+                if match_missing {
+                    write!(formatter, "$bool_or_missing({field_name:?}, {value})")
+                } else {
+                    write!(formatter, "$bool({field_name:?}, {value})")
+                }
+            }
+            LogicalLiteral::StrFastField {
+                ref field_name,
+                ref value,
+            } => write!(formatter, "$str({field_name:?}, {value:?})"),
             LogicalLiteral::Regex {
                 ref pattern,
                 ref field,
             } => write!(formatter, "Regex({field:?}, {pattern:?})"),
+            LogicalLiteral::JsonPathWildcard {
+                ref field,
+                ref path_pattern,
+                ref literals,
+                ..
+            } => write!(
+                formatter,
+                "JsonPath({field:?}, {path_pattern:?}, {literals:?})"
+            ),
         }
     }
 }
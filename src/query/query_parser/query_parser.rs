@@ -16,15 +16,16 @@ use crate::index::Index;
 use crate::json_utils::convert_to_fast_value_and_append_to_json_term;
 use crate::query::range_query::{is_type_valid_for_fastfield_range_query, RangeQuery};
 use crate::query::{
-    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery,
-    PhraseQuery, Query, RegexQuery, TermQuery, TermSetQuery,
+    is_json_path_wildcard, AllQuery, BoolFastFieldQuery, BooleanQuery, BoostQuery, EmptyQuery,
+    ExistsQuery, FastFieldTermQuery, FuzzyTermQuery, JsonPathWildcardQuery, Occur,
+    PhrasePrefixQuery, PhraseQuery, Query, RegexQuery, TermQuery, TermSetQuery,
 };
 use crate::schema::{
     Facet, FacetParseError, Field, FieldType, IndexRecordOption, IntoIpv6Addr, JsonObjectOptions,
-    Schema, Term, TextFieldIndexing, Type,
+    Schema, Term, TextFieldIndexing, TextOptions, Type,
 };
-use crate::time::format_description::well_known::Rfc3339;
-use crate::time::OffsetDateTime;
+use crate::time::format_description::well_known::{Iso8601, Rfc3339};
+use crate::time::{Date, OffsetDateTime, PrimitiveDateTime};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::{DateTime, Score};
 
@@ -40,6 +41,10 @@ pub enum QueryParserError {
     /// The query references a field that is not in the schema
     #[error("Field does not exist: '{0}'")]
     FieldDoesNotExist(String),
+    /// A field mapping was registered under a name that is already used by a field of the
+    /// schema.
+    #[error("Field mapping '{0}' conflicts with a field of the schema")]
+    FieldMappingConflict(String),
     /// The query contains a term for a `u64` or `i64`-field, but the value
     /// is neither.
     #[error("Expected a valid integer: '{0:?}'")]
@@ -54,9 +59,11 @@ pub enum QueryParserError {
     ExpectedFloat(#[from] ParseFloatError),
     /// The query contains a term for a bool field, but the value
     /// is not a bool.
-    #[error("Expected a bool value: '{0:?}'")]
+    #[error("Expected a bool value, `true` or `false`: '{0:?}'")]
     ExpectedBool(#[from] ParseBoolError),
     /// It is forbidden queries that are only "excluding". (e.g. -title:pop)
+    ///
+    /// Only returned after calling [`QueryParser::forbid_all_but_queries`].
     #[error("Invalid query: Only excluding terms given")]
     AllButQueryForbidden,
     /// If no default field is declared, running a query without any
@@ -95,8 +102,12 @@ pub enum QueryParserError {
     /// Only terms can be used as bounds.
     #[error("A range query cannot have a phrase as one of the bounds")]
     RangeMustNotHavePhrase,
-    /// The format for the date field is not RFC 3339 compliant.
-    #[error("The date field has an invalid format")]
+    /// The format for the date field is not supported.
+    #[error(
+        "The date field has an invalid format, expected a RFC 3339 date time \
+         (2023-05-01T10:00:00Z), a date time without offset taken as UTC (2023-05-01T10:00:00) or \
+         a date (2023-05-01)"
+    )]
     DateFormatError(#[from] time::error::Parse),
     /// The format for the facet field is invalid.
     #[error("The facet field is malformed: {0}")]
@@ -165,8 +176,18 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///
 /// * phrase terms: Quoted terms become phrase searches on fields that have positions indexed. e.g.,
 ///   `title:"Barack Obama"` will only find documents that have "barack" immediately followed by
-///   "obama". Single quotes can also be used. If the text to be searched contains quotation mark,
-///   it is possible to escape them with a `\`.
+///   "obama". If the text to be searched contains quotation mark, it is possible to escape them
+///   with a `\`.
+///
+/// * raw terms: Single quoted terms, e.g. `path:'C:\dir\file (1).txt'`, are taken as is. Nothing
+///   needs to be escaped in them except the single quote itself, which is written twice: `'it''s'`.
+///   They are otherwise handled like double quoted terms.
+///
+/// * escaping: Outside of single quotes, the special characters ``+ - & | ! ( ) { } [ ] ^ " ' ` ~ *
+///   ? : \ /`` and whitespaces are escaped by prepending a `\`, the same way in field names, terms
+///   and double quoted phrases. e.g. `my\ field:a\:b` targets the field `my field` with the term
+///   `a:b`. A `\` followed by any other character is kept as is. In field names, `\\` stands for a
+///   `\` and `\.` for a `.` within a json path segment.
 ///
 /// * range terms: Range searches can be done by specifying the start and end bound. These can be
 ///   inclusive or exclusive. e.g., `title:[a TO c}` will find all documents whose title contains a
@@ -179,7 +200,15 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///
 /// * date values: The query parser supports rfc3339 formatted dates. For example
 ///   `"2002-10-02T15:00:00.05Z"` or `some_date_field:[2002-10-02T15:00:00Z TO
-///   2002-10-02T18:00:00Z}`
+///   2002-10-02T18:00:00Z}`. Date times without offset, like `2002-10-02T15:00:00`, are taken as
+///   UTC, and dates, like `2002-10-02`, as midnight UTC. Values are truncated to the precision of
+///   the field. On a date field that is fast but not indexed, `some_date_field:2002-10-02` is
+///   evaluated on the fast field.
+///
+/// * bool values: `true` and `false`, regardless of their case. On a bool field that is fast but
+///   not indexed, `some_bool_field:true` is evaluated on the fast field. Documents missing the
+///   field match neither `some_bool_field:true` nor `NOT some_bool_field:true`, unless
+///   [`QueryParser::set_negations_match_missing_bools`] is called.
 ///
 /// * all docs query: A plain `*` will match all documents in the index.
 ///
@@ -194,6 +223,10 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 /// Additionally, specific fields can be marked to use fuzzy term queries for each literal
 /// via the [`QueryParser::set_field_fuzzy`] method.
 ///
+/// A logical field name can be mapped to several fields of the schema, each with its own boost,
+/// via the [`QueryParser::add_field_mapping`] method. This is typically used to search a title
+/// that is indexed once per language with a single `title:...` query.
+///
 /// Phrase terms support the `~` slop operator which allows to set the phrase's matching
 /// distance in words. `"big wolf"~1` will return documents containing the phrase `"big bad wolf"`.
 ///
@@ -206,9 +239,13 @@ pub struct QueryParser {
     default_fields: Vec<Field>,
     conjunction_by_default: bool,
     tokenizer_manager: TokenizerManager,
+    fast_field_tokenizer_manager: TokenizerManager,
     boost: FxHashMap<Field, Score>,
     fuzzy: FxHashMap<Field, Fuzzy>,
+    field_mappings: FxHashMap<String, Vec<(Field, Score)>>,
     regexes_allowed: bool,
+    all_but_queries_forbidden: bool,
+    negations_match_missing_bools: bool,
 }
 
 #[derive(Clone)]
@@ -260,10 +297,14 @@ impl QueryParser {
             schema,
             default_fields,
             tokenizer_manager,
+            fast_field_tokenizer_manager: TokenizerManager::default(),
             conjunction_by_default: false,
             boost: Default::default(),
             fuzzy: Default::default(),
+            field_mappings: Default::default(),
             regexes_allowed: false,
+            all_but_queries_forbidden: false,
+            negations_match_missing_bools: false,
         }
     }
 
@@ -277,7 +318,10 @@ impl QueryParser {
     ///  * an index
     ///  * a set of default fields used to search if no field is specifically defined in the query.
     pub fn for_index(index: &Index, default_fields: Vec<Field>) -> QueryParser {
-        QueryParser::new(index.schema(), default_fields, index.tokenizers().clone())
+        let mut query_parser =
+            QueryParser::new(index.schema(), default_fields, index.tokenizers().clone());
+        query_parser.set_fast_field_tokenizers(index.fast_field_tokenizer().clone());
+        query_parser
     }
 
     /// Set the default way to compose queries to a conjunction.
@@ -324,11 +368,65 @@ impl QueryParser {
         );
     }
 
+    /// Maps a logical field name to several fields of the schema.
+    ///
+    /// Terms, phrases, ranges, sets and exists queries targeting `name` are expanded into a
+    /// disjunction over the given fields, each boosted by its associated boost. This boost is
+    /// multiplied with the boosts set via [`set_field_boost(...)`](QueryParser::set_field_boost)
+    /// and the ones defined in the query.
+    ///
+    /// Fields for which the value cannot be interpreted (e.g. a range on a text field mapped
+    /// together with an integer field) are left out of the disjunction.
+    ///
+    /// Registering a mapping under the name of a field of the schema is an error.
+    /// Registering the same name twice replaces the previous mapping.
+    pub fn add_field_mapping(
+        &mut self,
+        name: impl Into<String>,
+        fields: Vec<(Field, Score)>,
+    ) -> Result<(), QueryParserError> {
+        let name = name.into();
+        if self.schema.get_field(&name).is_ok() {
+            return Err(QueryParserError::FieldMappingConflict(name));
+        }
+        self.field_mappings.insert(name, fields);
+        Ok(())
+    }
+
+    /// Sets the tokenizers normalizing the values searched in the str fields which are fast but
+    /// not indexed.
+    ///
+    /// They should be the fast field tokenizers of the index, which
+    /// [`QueryParser::for_index`] uses.
+    pub fn set_fast_field_tokenizers(&mut self, tokenizers: TokenizerManager) {
+        self.fast_field_tokenizer_manager = tokenizers;
+    }
+
     /// Allow regexes in queries
     pub fn allow_regexes(&mut self) {
         self.regexes_allowed = true;
     }
 
+    /// Forbid queries that are only excluding documents.
+    ///
+    /// By default, the query `-status:archived` matches all of the documents except the
+    /// archived ones. After calling `.forbid_all_but_queries()`, it is rejected with
+    /// [`QueryParserError::AllButQueryForbidden`] instead. When parsing leniently, the error
+    /// is reported, and the query matches all of the documents but the excluded ones.
+    pub fn forbid_all_but_queries(&mut self) {
+        self.all_but_queries_forbidden = true;
+    }
+
+    /// Makes negations over bool fields that are fast but not indexed match the documents
+    /// missing the field.
+    ///
+    /// By default, the value of such documents is deemed unknown, so that they match neither
+    /// `flag:true` nor `NOT flag:true`. After calling `.set_negations_match_missing_bools()`,
+    /// `NOT flag:true` matches all of the documents but the ones holding `true`.
+    pub fn set_negations_match_missing_bools(&mut self) {
+        self.negations_match_missing_bools = true;
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
@@ -380,8 +478,8 @@ impl QueryParser {
 
     /// Parse the user query into an AST.
     fn parse_query_to_logical_ast(&self, query: &str) -> Result<LogicalAst, QueryParserError> {
-        let user_input_ast = query_grammar::parse_query(query)
-            .map_err(|_| QueryParserError::SyntaxError(query.to_string()))?;
+        let user_input_ast =
+            query_grammar::parse_query(query).map_err(|_| locate_syntax_error(query))?;
         let (ast, mut err) = self.compute_logical_ast_lenient(user_input_ast);
         if !err.is_empty() {
             return Err(err.swap_remove(0));
@@ -419,7 +517,8 @@ impl QueryParser {
                 return (ast, err);
             }
         }
-        if all_negative(&ast) {
+        // Otherwise, all-negative boolean queries are anchored to all documents on their own.
+        if self.all_but_queries_forbidden && all_negative(&ast) {
             err.push(QueryParserError::AllButQueryForbidden);
             make_non_negative(&mut ast);
         }
@@ -448,6 +547,12 @@ impl QueryParser {
                 field_entry.name()
             )));
         }
+        if is_json_path_wildcard(json_path) {
+            return Err(QueryParserError::UnsupportedQuery(
+                "Wildcard json path segments are only supported by term and phrase queries"
+                    .to_string(),
+            ));
+        }
         match *field_type {
             FieldType::U64(_) => {
                 let val: u64 = u64::from_str(phrase)?;
@@ -461,13 +566,20 @@ impl QueryParser {
                 let val: f64 = f64::from_str(phrase)?;
                 Ok(Term::from_field_f64(field, val))
             }
+            FieldType::F32(_) => {
+                let val: f32 = f32::from_str(phrase)?;
+                Ok(Term::from_field_f64(field, f64::from(val)))
+            }
             FieldType::Bool(_) => {
-                let val: bool = bool::from_str(phrase)?;
+                let val: bool = parse_bool_literal(phrase)?;
                 Ok(Term::from_field_bool(field, val))
             }
-            FieldType::Date(_) => {
-                let dt = OffsetDateTime::parse(phrase, &Rfc3339)?;
-                Ok(Term::from_field_date(field, DateTime::from_utc(dt)))
+            FieldType::Date(ref date_options) => {
+                let date_time = parse_date_literal(phrase)?;
+                Ok(Term::from_field_date(
+                    field,
+                    date_time.truncate(date_options.get_precision()),
+                ))
             }
             FieldType::Str(ref str_options) => {
                 let option = str_options.get_indexing_options().ok_or_else(|| {
@@ -524,9 +636,43 @@ impl QueryParser {
                 let ip_v6 = IpAddr::from_str(phrase)?.into_ipv6_addr();
                 Ok(Term::from_field_ip_addr(field, ip_v6))
             }
+            FieldType::F32Vector(_) => Err(QueryParserError::FieldNotIndexed(
+                field_entry.name().to_string(),
+            )),
         }
     }
 
+    /// Normalizes the value searched in a str fast field with the tokenizer of the fast field,
+    /// so that it can be compared to the values of the column.
+    fn normalize_fast_field_str(
+        &self,
+        field_name: &str,
+        str_options: &TextOptions,
+        phrase: &str,
+    ) -> Result<String, QueryParserError> {
+        let Some(tokenizer_name) = str_options.get_fast_field_tokenizer_name() else {
+            return Ok(phrase.to_string());
+        };
+        let mut text_analyzer = self
+            .fast_field_tokenizer_manager
+            .get(tokenizer_name)
+            .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                field: field_name.to_string(),
+                tokenizer: tokenizer_name.to_string(),
+            })?;
+        let mut tokens = Vec::new();
+        text_analyzer
+            .token_stream(phrase)
+            .process(&mut |token| tokens.push(token.text.clone()));
+        if tokens.len() != 1 {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "The fast field tokenizer of the field '{field_name}' does not turn '{phrase}' \
+                 into a single value"
+            )));
+        }
+        Ok(tokens.pop().unwrap())
+    }
+
     fn compute_logical_ast_for_leaf(
         &self,
         field: Field,
@@ -539,6 +685,38 @@ impl QueryParser {
         let field_type = field_entry.field_type();
         let field_name = field_entry.name();
         if !field_type.is_indexed() {
+            if let FieldType::Date(ref date_options) = *field_type {
+                if date_options.is_fast() && json_path.is_empty() {
+                    // Equality on the fast field, as a range over a single value.
+                    let date_time = parse_date_literal(phrase)?;
+                    let term = Term::from_field_date(
+                        field,
+                        date_time.truncate(date_options.get_precision()),
+                    );
+                    return Ok(vec![LogicalLiteral::Range {
+                        lower: Bound::Included(term.clone()),
+                        upper: Bound::Included(term),
+                    }]);
+                }
+            }
+            if let FieldType::Bool(ref bool_options) = *field_type {
+                if bool_options.is_fast() && json_path.is_empty() {
+                    return Ok(vec![LogicalLiteral::BoolFastField {
+                        field_name: field_name.to_string(),
+                        value: parse_bool_literal(phrase)?,
+                        match_missing: false,
+                    }]);
+                }
+            }
+            if let FieldType::Str(ref str_options) = *field_type {
+                if str_options.is_fast() && json_path.is_empty() && !prefix {
+                    let value = self.normalize_fast_field_str(field_name, str_options, phrase)?;
+                    return Ok(vec![LogicalLiteral::StrFastField {
+                        field_name: field_name.to_string(),
+                        value,
+                    }]);
+                }
+            }
             return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
         }
         if field_type.value_type() != Type::Json && !json_path.is_empty() {
@@ -563,14 +741,18 @@ impl QueryParser {
                 let f64_term = Term::from_field_f64(field, val);
                 Ok(vec![LogicalLiteral::Term(f64_term)])
             }
+            FieldType::F32(_) => {
+                let val: f32 = f32::from_str(phrase)?;
+                let f32_term = Term::from_field_f64(field, f64::from(val));
+                Ok(vec![LogicalLiteral::Term(f32_term)])
+            }
             FieldType::Bool(_) => {
-                let val: bool = bool::from_str(phrase)?;
+                let val: bool = parse_bool_literal(phrase)?;
                 let bool_term = Term::from_field_bool(field, val);
                 Ok(vec![LogicalLiteral::Term(bool_term)])
             }
             FieldType::Date(_) => {
-                let dt = OffsetDateTime::parse(phrase, &Rfc3339)?;
-                let dt_term = Term::from_field_date_for_search(field, DateTime::from_utc(dt));
+                let dt_term = Term::from_field_date_for_search(field, parse_date_literal(phrase)?);
                 Ok(vec![LogicalLiteral::Term(dt_term)])
             }
             FieldType::Str(ref str_options) => {
@@ -597,14 +779,25 @@ impl QueryParser {
                 .into_iter()
                 .collect())
             }
-            FieldType::JsonObject(ref json_options) => generate_literals_for_json_object(
-                field_name,
-                field,
-                json_path,
-                phrase,
-                &self.tokenizer_manager,
-                json_options,
-            ),
+            FieldType::JsonObject(ref json_options) => {
+                let literals = generate_literals_for_json_object(
+                    field_name,
+                    field,
+                    json_path,
+                    phrase,
+                    &self.tokenizer_manager,
+                    json_options,
+                )?;
+                if !is_json_path_wildcard(json_path) {
+                    return Ok(literals);
+                }
+                Ok(vec![LogicalLiteral::JsonPathWildcard {
+                    field,
+                    path_pattern: json_path.to_string(),
+                    expand_dots_enabled: json_options.is_expand_dots_enabled(),
+                    literals,
+                }])
+            }
             FieldType::Facet(_) => match Facet::from_text(phrase) {
                 Ok(facet) => {
                     let facet_term = Term::from_facet(field, &facet);
@@ -624,6 +817,9 @@ impl QueryParser {
                 let term = Term::from_field_ip_addr(field, ip_v6);
                 Ok(vec![LogicalLiteral::Term(term)])
             }
+            FieldType::F32Vector(_) => {
+                Err(QueryParserError::FieldNotIndexed(field_name.to_string()))
+            }
         }
     }
 
@@ -662,9 +858,12 @@ impl QueryParser {
                 let mut logical_sub_queries: Vec<(Occur, LogicalAst)> = Vec::new();
                 let mut errors = Vec::new();
                 for (occur_opt, sub_ast) in sub_queries {
-                    let (sub_ast, mut sub_errors) =
+                    let (mut sub_ast, mut sub_errors) =
                         self.compute_logical_ast_with_occur_lenient(sub_ast);
                     let occur = occur_opt.unwrap_or(default_occur);
+                    if occur == Occur::MustNot && !self.negations_match_missing_bools {
+                        sub_ast.flip_bool_fast_field_match_missing();
+                    }
                     logical_sub_queries.push((occur, sub_ast));
                     errors.append(&mut sub_errors);
                 }
@@ -750,48 +949,166 @@ impl QueryParser {
         Ok(triplets)
     }
 
+    fn compute_logical_ast_for_triplets(
+        &self,
+        term_phrases: Vec<(Field, &str, &str)>,
+        literal: &UserInputLiteral,
+    ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
+        let mut asts: Vec<LogicalAst> = Vec::new();
+        let mut errors: Vec<QueryParserError> = Vec::new();
+        for (field, json_path, phrase) in term_phrases {
+            let unboosted_asts = match self.compute_logical_ast_for_leaf(
+                field,
+                json_path,
+                phrase,
+                literal.slop,
+                literal.prefix,
+            ) {
+                Ok(asts) => asts,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            for ast in unboosted_asts {
+                // Apply some field specific boost defined at the query parser level.
+                let boost = self.field_boost(field);
+                asts.push(LogicalAst::Leaf(Box::new(ast)).boost(boost));
+            }
+        }
+        if !asts.is_empty() {
+            // if some fields failed but other succeeded, we consider this a success, it
+            // probably means the default_fields contains
+            // text and non-text fields, and the non-text ones failed
+            errors.clear();
+        }
+        let result_ast: LogicalAst = if asts.len() == 1 {
+            asts.into_iter().next().unwrap()
+        } else {
+            LogicalAst::Clause(asts.into_iter().map(|ast| (Occur::Should, ast)).collect())
+        };
+        (Some(result_ast), errors)
+    }
+
+    /// Expands a leaf targeting a field mapping into a disjunction over the mapped fields.
+    ///
+    /// `compute_ast` builds the leaf for a single field. Fields yielding an error are left out,
+    /// and the errors are only reported if none of the fields yields an ast.
+    fn compute_logical_ast_for_mapping(
+        &self,
+        mapped_fields: &[(Field, Score)],
+        mut compute_ast: impl FnMut(Field) -> (Option<LogicalAst>, Vec<QueryParserError>),
+    ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
+        let mut asts: Vec<LogicalAst> = Vec::new();
+        let mut errors: Vec<QueryParserError> = Vec::new();
+        for &(field, boost) in mapped_fields {
+            let (ast_opt, field_errors) = compute_ast(field);
+            if !field_errors.is_empty() {
+                errors.extend(field_errors);
+                continue;
+            }
+            if let Some(ast) = ast_opt {
+                asts.push(ast.boost(boost));
+            }
+        }
+        if asts.is_empty() {
+            return (None, errors);
+        }
+        let result_ast: LogicalAst = if asts.len() == 1 {
+            asts.into_iter().next().unwrap()
+        } else {
+            LogicalAst::Clause(asts.into_iter().map(|ast| (Occur::Should, ast)).collect())
+        };
+        (Some(result_ast), Vec::new())
+    }
+
+    fn compute_range_ast(
+        &self,
+        field: Field,
+        json_path: &str,
+        lower: &UserInputBound,
+        upper: &UserInputBound,
+    ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
+        let mut errors = Vec::new();
+        let lower = match self.resolve_bound(field, json_path, lower) {
+            Ok(bound) => bound,
+            Err(error) => {
+                errors.push(error);
+                Bound::Unbounded
+            }
+        };
+        let upper = match self.resolve_bound(field, json_path, upper) {
+            Ok(bound) => bound,
+            Err(error) => {
+                errors.push(error);
+                Bound::Unbounded
+            }
+        };
+        if lower == Bound::Unbounded && upper == Bound::Unbounded {
+            // this range is useless, either because a user requested [* TO *], or because
+            // we failed to parse something. Either way, there is no point emitting it
+            return (None, errors);
+        }
+        let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Range { lower, upper }));
+        (Some(logical_ast), errors)
+    }
+
+    fn compute_set_ast(
+        &self,
+        field: Field,
+        json_path: &str,
+        elements: &[String],
+    ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
+        let (elements, errors) = elements
+            .iter()
+            .map(|element| self.compute_boundary_term(field, json_path, element))
+            .partition_result();
+        let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Set { elements }));
+        (Some(logical_ast), errors)
+    }
+
+    fn compute_exists_ast(
+        &self,
+        field: Field,
+        full_path: &str,
+    ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
+        let field_type = self.schema.get_field_entry(field).field_type();
+        if !field_type.is_fast() {
+            return (
+                None,
+                vec![QueryParserError::UnsupportedQuery(format!(
+                    "Exists query requires '{full_path}' to be a fast field."
+                ))],
+            );
+        }
+        let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Exists {
+            field_name: full_path.to_string(),
+            json_subpaths: field_type.value_type() == Type::Json,
+        }));
+        (Some(logical_ast), Vec::new())
+    }
+
     fn compute_logical_ast_from_leaf_lenient(
         &self,
         leaf: UserInputLeaf,
     ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
         match leaf {
             UserInputLeaf::Literal(literal) => {
+                let mapped_fields_opt = literal
+                    .field_name
+                    .as_ref()
+                    .and_then(|name| self.field_mappings.get(name));
+                if let Some(mapped_fields) = mapped_fields_opt {
+                    return self.compute_logical_ast_for_mapping(mapped_fields, |field| {
+                        self.compute_logical_ast_for_triplets(
+                            vec![(field, "", literal.phrase.as_str())],
+                            &literal,
+                        )
+                    });
+                }
                 let term_phrases: Vec<(Field, &str, &str)> =
                     try_tuple!(self.compute_path_triplets_for_literal(&literal));
-                let mut asts: Vec<LogicalAst> = Vec::new();
-                let mut errors: Vec<QueryParserError> = Vec::new();
-                for (field, json_path, phrase) in term_phrases {
-                    let unboosted_asts = match self.compute_logical_ast_for_leaf(
-                        field,
-                        json_path,
-                        phrase,
-                        literal.slop,
-                        literal.prefix,
-                    ) {
-                        Ok(asts) => asts,
-                        Err(e) => {
-                            errors.push(e);
-                            continue;
-                        }
-                    };
-                    for ast in unboosted_asts {
-                        // Apply some field specific boost defined at the query parser level.
-                        let boost = self.field_boost(field);
-                        asts.push(LogicalAst::Leaf(Box::new(ast)).boost(boost));
-                    }
-                }
-                if !asts.is_empty() {
-                    // if some fields failed but other succeeded, we consider this a success, it
-                    // probably means the default_fields contains
-                    // text and non-text fields, and the non-text ones failed
-                    errors.clear();
-                }
-                let result_ast: LogicalAst = if asts.len() == 1 {
-                    asts.into_iter().next().unwrap()
-                } else {
-                    LogicalAst::Clause(asts.into_iter().map(|ast| (Occur::Should, ast)).collect())
-                };
-                (Some(result_ast), errors)
+                self.compute_logical_ast_for_triplets(term_phrases, &literal)
             }
             UserInputLeaf::All => (
                 Some(LogicalAst::Leaf(Box::new(LogicalLiteral::All))),
@@ -810,32 +1127,15 @@ impl QueryParser {
                         )],
                     );
                 };
+                if let Some(mapped_fields) = self.field_mappings.get(&full_path) {
+                    return self.compute_logical_ast_for_mapping(mapped_fields, |field| {
+                        self.compute_range_ast(field, "", &lower, &upper)
+                    });
+                }
                 let (field, json_path) = try_tuple!(self
                     .split_full_path(&full_path)
                     .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone())));
-                let mut errors = Vec::new();
-                let lower = match self.resolve_bound(field, json_path, &lower) {
-                    Ok(bound) => bound,
-                    Err(error) => {
-                        errors.push(error);
-                        Bound::Unbounded
-                    }
-                };
-                let upper = match self.resolve_bound(field, json_path, &upper) {
-                    Ok(bound) => bound,
-                    Err(error) => {
-                        errors.push(error);
-                        Bound::Unbounded
-                    }
-                };
-                if lower == Bound::Unbounded && upper == Bound::Unbounded {
-                    // this range is useless, either because a user requested [* TO *], or because
-                    // we failed to parse something. Either way, there is no point emitting it
-                    return (None, errors);
-                }
-                let logical_ast =
-                    LogicalAst::Leaf(Box::new(LogicalLiteral::Range { lower, upper }));
-                (Some(logical_ast), errors)
+                self.compute_range_ast(field, json_path, &lower, &upper)
             }
             UserInputLeaf::Set {
                 field: full_field_opt,
@@ -846,22 +1146,37 @@ impl QueryParser {
                         "Range query need to target a specific field.".to_string(),
                     )
                 }));
+                if let Some(mapped_fields) = self.field_mappings.get(&full_path) {
+                    return self.compute_logical_ast_for_mapping(mapped_fields, |field| {
+                        self.compute_set_ast(field, "", &elements)
+                    });
+                }
                 let (field, json_path) = try_tuple!(self
                     .split_full_path(&full_path)
                     .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone())));
-                let (elements, errors) = elements
-                    .into_iter()
-                    .map(|element| self.compute_boundary_term(field, json_path, &element))
-                    .partition_result();
-                let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Set { elements }));
-                (Some(logical_ast), errors)
+                self.compute_set_ast(field, json_path, &elements)
+            }
+            UserInputLeaf::Exists { field: full_path } => {
+                if let Some(mapped_fields) = self.field_mappings.get(&full_path) {
+                    return self.compute_logical_ast_for_mapping(mapped_fields, |field| {
+                        self.compute_exists_ast(field, self.schema.get_field_name(field))
+                    });
+                }
+                let (field, json_path) = try_tuple!(self
+                    .split_full_path(&full_path)
+                    .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone())));
+                if is_json_path_wildcard(json_path) {
+                    return (
+                        None,
+                        vec![QueryParserError::UnsupportedQuery(
+                            "Wildcard json path segments are only supported by term and phrase \
+                             queries"
+                                .to_string(),
+                        )],
+                    );
+                }
+                self.compute_exists_ast(field, &full_path)
             }
-            UserInputLeaf::Exists { .. } => (
-                None,
-                vec![QueryParserError::UnsupportedQuery(
-                    "Range query need to target a specific field.".to_string(),
-                )],
-            ),
             UserInputLeaf::Regex { field, pattern } => {
                 if !self.regexes_allowed {
                     return (
@@ -911,6 +1226,38 @@ impl QueryParser {
     }
 }
 
+fn parse_bool_literal(bool_str: &str) -> Result<bool, ParseBoolError> {
+    bool::from_str(&bool_str.to_ascii_lowercase())
+}
+
+/// Parses a RFC 3339 date time, a ISO 8601 date time without offset, taken as UTC, or a ISO 8601
+/// date, taken as midnight UTC.
+fn parse_date_literal(date_str: &str) -> Result<DateTime, time::error::Parse> {
+    let rfc3339_error = match OffsetDateTime::parse(date_str, &Rfc3339) {
+        Ok(date_time) => return Ok(DateTime::from_utc(date_time)),
+        Err(rfc3339_error) => rfc3339_error,
+    };
+    if let Ok(date_time) = PrimitiveDateTime::parse(date_str, &Iso8601::DEFAULT) {
+        return Ok(DateTime::from_primitive(date_time));
+    }
+    if let Ok(date) = Date::parse(date_str, &Iso8601::DEFAULT) {
+        return Ok(DateTime::from_primitive(date.midnight()));
+    }
+    Err(rfc3339_error)
+}
+
+/// The strict grammar does not tell where it failed. The lenient grammar is used to
+/// locate the errors it reports specifically, such as a `*:*` in a field group.
+fn locate_syntax_error(query: &str) -> QueryParserError {
+    let (_, errors) = query_grammar::parse_query_lenient(query);
+    match errors.into_iter().next() {
+        Some(error) if error.message == query_grammar::MATCH_ALL_IN_FIELD_GROUP => {
+            QueryParserError::SyntaxError(format!("{} at position {}", error.message, error.pos))
+        }
+        _ => QueryParserError::SyntaxError(query.to_string()),
+    }
+}
+
 fn convert_literal_to_query(
     fuzzy: &FxHashMap<Field, Fuzzy>,
     logical_literal: LogicalLiteral,
@@ -949,9 +1296,51 @@ fn convert_literal_to_query(
         LogicalLiteral::Range { lower, upper } => Box::new(RangeQuery::new(lower, upper)),
         LogicalLiteral::Set { elements, .. } => Box::new(TermSetQuery::new(elements)),
         LogicalLiteral::All => Box::new(AllQuery),
+        LogicalLiteral::Exists {
+            field_name,
+            json_subpaths,
+        } => Box::new(ExistsQuery::new(field_name, json_subpaths)),
+        LogicalLiteral::BoolFastField {
+            field_name,
+            value,
+            match_missing,
+        } => {
+            let mut query = BoolFastFieldQuery::new(field_name, value);
+            query.set_match_missing(match_missing);
+            Box::new(query)
+        }
+        LogicalLiteral::StrFastField { field_name, value } => {
+            Box::new(FastFieldTermQuery::new(field_name, value))
+        }
         LogicalLiteral::Regex { pattern, field } => {
             Box::new(RegexQuery::from_regex(pattern, field))
         }
+        LogicalLiteral::JsonPathWildcard {
+            field,
+            path_pattern,
+            expand_dots_enabled,
+            literals,
+        } => {
+            let fuzzy = fuzzy.clone();
+            Box::new(JsonPathWildcardQuery::new(
+                field,
+                &path_pattern,
+                expand_dots_enabled,
+                move |path_term| {
+                    let mut subqueries: Vec<Box<dyn Query>> = literals
+                        .iter()
+                        .map(|literal| {
+                            convert_literal_to_query(&fuzzy, literal.with_json_path(&path_term))
+                        })
+                        .collect();
+                    if subqueries.len() == 1 {
+                        subqueries.pop().unwrap()
+                    } else {
+                        Box::new(BooleanQuery::union(subqueries))
+                    }
+                },
+            ))
+        }
     }
 }
 
@@ -1558,9 +1947,15 @@ mod test {
 
     #[test]
     fn test_single_negative_term() {
-        assert_matches!(
-            parse_query_to_logical_ast("-title:toto", false),
-            Err(QueryParserError::AllButQueryForbidden)
+        test_parse_query_to_logical_ast_helper(
+            "-title:toto",
+            r#"(-Term(field=0, type=Str, "toto"))"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "+title:toto +(-title:titi)",
+            r#"(+Term(field=0, type=Str, "toto") +(-Term(field=0, type=Str, "titi")))"#,
+            false,
         );
     }
 
@@ -1631,6 +2026,64 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_field_group_typed_fields() {
+        test_parse_query_to_logical_ast_helper(
+            "unsigned:(>=10 AND <100)",
+            r#"(+(Included(Term(field=3, type=U64, 10)) TO Unbounded) +(Unbounded TO Excluded(Term(field=3, type=U64, 100))))"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "signed:(-[1 TO 3] +>=0)",
+            r#"(-(Included(Term(field=2, type=I64, 1)) TO Included(Term(field=2, type=I64, 3))) +(Included(Term(field=2, type=I64, 0)) TO Unbounded))"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "float:(1.5 OR >=3)",
+            r#"(Term(field=10, type=F64, 1.5) (Included(Term(field=10, type=F64, 3.0)) TO Unbounded))"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "date:(>=2020-01-01T00:00:00Z AND <2021-01-01T00:00:00Z)",
+            r#"(+(Included(Term(field=9, type=Date, 2020-01-01T00:00:00Z)) TO Unbounded) +(Unbounded TO Excluded(Term(field=9, type=Date, 2021-01-01T00:00:00Z))))"#,
+            false,
+        );
+        // mixing text and ranges on a text field
+        test_parse_query_to_logical_ast_helper(
+            "title:(-deprecated +stable [a TO c])",
+            r#"(-Term(field=0, type=Str, "deprecated") +Term(field=0, type=Str, "stable") (Included(Term(field=0, type=Str, "a")) TO Included(Term(field=0, type=Str, "c"))))"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:(stable (unsigned:>5 OR <b))",
+            r#"(Term(field=0, type=Str, "stable") (Excluded(Term(field=3, type=U64, 5)) TO Unbounded) (Unbounded TO Excluded(Term(field=0, type=Str, "b"))))"#,
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_field_group_error_position() {
+        let query_parser = make_query_parser();
+        assert_eq!(
+            query_parser.parse_query("title:(stable *:*)").unwrap_err(),
+            QueryParserError::SyntaxError(
+                "`*:*` cannot be used in a field group at position 14".to_string()
+            )
+        );
+        let (_, errors) = query_parser.parse_query_lenient("title:(stable *:*)");
+        assert_eq!(
+            errors[0],
+            QueryParserError::SyntaxError(
+                "`*:*` cannot be used in a field group at position 14".to_string()
+            )
+        );
+        // other syntax errors keep the query as their payload
+        assert_eq!(
+            query_parser.parse_query("title:(stable").unwrap_err(),
+            QueryParserError::SyntaxError("title:(stable".to_string())
+        );
+    }
+
     #[test]
     pub fn test_query_parser_field_does_not_exist() {
         let query_parser = make_query_parser();
@@ -1739,6 +2192,351 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_query_parser_date_literals() {
+        test_parse_query_to_logical_ast_helper(
+            "date:2010-11-21",
+            r#"Term(field=9, type=Date, 2010-11-21T00:00:00Z)"#,
+            true,
+        );
+        test_parse_query_to_logical_ast_helper(
+            r#"date:"2010-11-21T09:55:06.25""#,
+            r#"Term(field=9, type=Date, 2010-11-21T09:55:06Z)"#,
+            true,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "date:[2010-11-21 TO 2010-11-22}",
+            "(Included(Term(field=9, type=Date, 2010-11-21T00:00:00Z)) TO Excluded(Term(field=9, \
+             type=Date, 2010-11-22T00:00:00Z)))",
+            true,
+        );
+        let query_parser = make_query_parser();
+        let error = query_parser.parse_query("date:21/11/2010").unwrap_err();
+        assert_matches!(error, QueryParserError::DateFormatError(_));
+        assert!(error.to_string().contains("(2023-05-01)"));
+    }
+
+    #[test]
+    pub fn test_query_parser_bool_literals() {
+        test_parse_query_to_logical_ast_helper(
+            "bool:TRUE",
+            "Term(field=16, type=Bool, true)",
+            true,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "bool:False",
+            "Term(field=16, type=Bool, false)",
+            true,
+        );
+        let query_parser = make_query_parser();
+        let error = query_parser.parse_query("bool:yes").unwrap_err();
+        assert!(error.to_string().contains("`true` or `false`"));
+    }
+
+    #[test]
+    pub fn test_query_parser_fast_only_date_equality() -> crate::Result<()> {
+        use crate::collector::DocSetCollector;
+        use crate::schema::{DateOptions, DateTimePrecision};
+        use crate::time::format_description::well_known::Rfc3339;
+        use crate::time::OffsetDateTime;
+        use crate::{DateTime, Index, IndexWriter};
+
+        let mut schema_builder = Schema::builder();
+        let created = schema_builder.add_date_field(
+            "created",
+            DateOptions::default()
+                .set_fast()
+                .set_precision(DateTimePrecision::Seconds),
+        );
+        let active = schema_builder.add_bool_field("active", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for (created_str, is_active) in [
+            ("2023-05-01T00:00:00Z", true),
+            ("2023-05-01T10:00:00.5Z", false),
+            ("2023-05-02T00:00:00Z", true),
+        ] {
+            let created_date = OffsetDateTime::parse(created_str, &Rfc3339).unwrap();
+            index_writer.add_document(doc!(
+                created => DateTime::from_utc(created_date),
+                active => is_active,
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let matching_docs = |query: &str| -> crate::Result<Vec<u32>> {
+            let query = query_parser.parse_query(query)?;
+            let mut docs: Vec<u32> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+        assert_eq!(matching_docs("created:2023-05-01")?, vec![0]);
+        // Truncated to the precision of the field.
+        assert_eq!(
+            matching_docs(r#"created:"2023-05-01T10:00:00.9Z""#)?,
+            vec![1]
+        );
+        assert_eq!(matching_docs(r#"created:"2023-05-01T10:00:00""#)?, vec![1]);
+        assert_eq!(matching_docs("created:2023-05-03")?, Vec::<u32>::new());
+        assert_eq!(matching_docs("active:true")?, vec![0, 2]);
+        assert_eq!(
+            matching_docs("active:true AND created:2023-05-02")?,
+            vec![2]
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_query_parser_range_shorthand_matches() -> crate::Result<()> {
+        use std::net::Ipv6Addr;
+
+        use crate::collector::DocSetCollector;
+        use crate::schema::{DateOptions, IpAddrOptions, NumericOptions};
+        use crate::{DateTime, Index, IndexWriter};
+
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_u64_field("price", NumericOptions::default().set_fast());
+        let delta = schema_builder.add_i64_field("delta", INDEXED);
+        let score = schema_builder.add_f64_field("score", NumericOptions::default().set_fast());
+        let created = schema_builder.add_date_field("created", DateOptions::default().set_fast());
+        let ip = schema_builder.add_ip_addr_field("ip", IpAddrOptions::default().set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for val in 0..4u64 {
+            index_writer.add_document(doc!(
+                price => val * 10,
+                delta => val as i64 - 2,
+                score => val as f64 / 2.0,
+                created => DateTime::from_timestamp_secs(1_672_531_200 + val as i64 * 86_400),
+                ip => Ipv6Addr::from(val as u128 + 1),
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let matching_docs = |query: &str| -> crate::Result<Vec<u32>> {
+            let query = query_parser.parse_query(query)?;
+            let mut docs: Vec<u32> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+        for (query, expected_docs) in [
+            ("price:>10", vec![2, 3]),
+            ("price:>=10", vec![1, 2, 3]),
+            ("price:<10", vec![0]),
+            ("price:<=10", vec![0, 1]),
+            ("price:(+>0 +<30)", vec![1, 2]),
+            ("price:(>=10 AND <=20)", vec![1, 2]),
+            ("delta:>0", vec![3]),
+            ("delta:>=-1", vec![1, 2, 3]),
+            ("delta:<-1", vec![0]),
+            ("delta:<=0", vec![0, 1, 2]),
+            ("score:>0.5", vec![2, 3]),
+            ("score:>=0.5", vec![1, 2, 3]),
+            ("score:<0.5", vec![0]),
+            ("score:<=1", vec![0, 1, 2]),
+            ("created:>2023-01-02T00:00:00Z", vec![2, 3]),
+            ("created:>=2023-01-02T00:00:00Z", vec![1, 2, 3]),
+            ("created:<2023-01-02T00:00:00Z", vec![0]),
+            ("created:<=2023-01-03T00:00:00Z", vec![0, 1, 2]),
+            (
+                "created:(>2023-01-01T00:00:00Z AND <2023-01-04T00:00:00Z)",
+                vec![1, 2],
+            ),
+            ("ip:>::2", vec![2, 3]),
+            ("ip:>=::2", vec![1, 2, 3]),
+            ("ip:<::2", vec![0]),
+            ("ip:<=::3", vec![0, 1, 2]),
+            ("ip:(>::1 AND <::4)", vec![1, 2]),
+        ] {
+            assert_eq!(matching_docs(query)?, expected_docs, "{query}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_query_parser_fast_only_bool() -> crate::Result<()> {
+        use crate::collector::DocSetCollector;
+        use crate::{Index, IndexWriter, TantivyDocument};
+
+        let mut schema_builder = Schema::builder();
+        let is_published = schema_builder.add_bool_field("is_published", FAST);
+        let has_attachment = schema_builder.add_bool_field("has_attachment", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // Docs 0 and 1 are published, 2 and 3 are not, 4 and 5 miss the field. Even docs have
+        // an attachment.
+        for is_published_opt in [Some(true), Some(false), None] {
+            for with_attachment in [true, false] {
+                let mut doc = TantivyDocument::default();
+                if let Some(is_published_val) = is_published_opt {
+                    doc.add_bool(is_published, is_published_val);
+                }
+                if with_attachment {
+                    doc.add_bool(has_attachment, true);
+                }
+                index_writer.add_document(doc)?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let matching_docs = |query_parser: &QueryParser, query: &str| -> crate::Result<Vec<u32>> {
+            let query = query_parser.parse_query(query)?;
+            let mut docs: Vec<u32> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        for (query, expected_docs) in [
+            ("is_published:true", vec![0, 1]),
+            ("is_published:FALSE", vec![2, 3]),
+            ("NOT is_published:true", vec![2, 3]),
+            ("-is_published:false", vec![0, 1]),
+            ("NOT (is_published:true OR is_published:false)", vec![]),
+            // Doc 1 misses `has_attachment`: whether it is excluded is unknown.
+            (
+                "NOT (is_published:true AND has_attachment:true)",
+                vec![2, 3],
+            ),
+            // Doc 5 has no attachment: the conjunction is false whatever its missing value is.
+            (
+                "NOT (is_published:true AND has_attachment:*)",
+                vec![1, 2, 3, 5],
+            ),
+            ("is_published:true AND NOT has_attachment:*", vec![1]),
+            ("has_attachment:* AND NOT is_published:true", vec![2]),
+            ("NOT is_published:true AND NOT has_attachment:*", vec![3]),
+            ("is_published:*", vec![0, 1, 2, 3]),
+            ("NOT is_published:*", vec![4, 5]),
+        ] {
+            assert_eq!(
+                matching_docs(&query_parser, query)?,
+                expected_docs,
+                "{query}"
+            );
+        }
+
+        let mut query_parser = QueryParser::for_index(&index, vec![]);
+        query_parser.set_negations_match_missing_bools();
+        for (query, expected_docs) in [
+            ("is_published:true", vec![0, 1]),
+            ("NOT is_published:true", vec![2, 3, 4, 5]),
+            ("NOT (is_published:true OR is_published:false)", vec![4, 5]),
+            ("has_attachment:* AND NOT is_published:true", vec![2, 4]),
+        ] {
+            assert_eq!(
+                matching_docs(&query_parser, query)?,
+                expected_docs,
+                "{query}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_query_parser_fast_only_str() -> crate::Result<()> {
+        use crate::collector::DocSetCollector;
+        use crate::{DocAddress, Index, IndexWriter, TantivyDocument};
+
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", FAST);
+        let tag_indexed = schema_builder.add_text_field("tag_indexed", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for tags in [&["rust"][..], &["search", "rust"], &[], &["Search engine"]] {
+            let mut doc = TantivyDocument::default();
+            for tag_value in tags {
+                doc.add_text(tag, tag_value);
+                doc.add_text(tag_indexed, tag_value);
+            }
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let matching_docs = |query: &str| -> crate::Result<Vec<DocAddress>> {
+            let query = query_parser.parse_query(query)?;
+            let mut docs: Vec<DocAddress> = searcher
+                .search(&query, &DocSetCollector)?
+                .into_iter()
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+
+        let query = query_parser.parse_query("tag:rust")?;
+        assert_eq!(
+            format!("{query:?}"),
+            r#"FastFieldTermQuery { field_name: "tag", value: "rust" }"#
+        );
+        for (query, indexed_twin_query) in [
+            ("tag:rust", "tag_indexed:rust"),
+            (
+                "tag:search AND tag:rust",
+                "tag_indexed:search AND tag_indexed:rust",
+            ),
+            ("tag:\"Search engine\"", "tag_indexed:\"Search engine\""),
+            ("tag:unknown", "tag_indexed:unknown"),
+            ("NOT tag:rust", "NOT tag_indexed:rust"),
+        ] {
+            assert_eq!(
+                matching_docs(query)?,
+                matching_docs(indexed_twin_query)?,
+                "{query}"
+            );
+        }
+        assert_eq!(matching_docs("tag:rust")?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_query_parser_fast_only_str_normalized() -> crate::Result<()> {
+        use crate::collector::Count;
+        use crate::tokenizer::{LowerCaser, RawTokenizer};
+        use crate::{Index, IndexWriter};
+
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder
+            .add_text_field("tag", TextOptions::default().set_fast(Some("lowercase")));
+        let index = Index::create_in_ram(schema_builder.build());
+        index.fast_field_tokenizer().register(
+            "lowercase",
+            TextAnalyzer::builder(RawTokenizer::default())
+                .filter(LowerCaser)
+                .build(),
+        );
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "rust"))?;
+        index_writer.add_document(doc!(tag => "Search"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+
+        let query = query_parser.parse_query("tag:Rust")?;
+        assert_eq!(
+            format!("{query:?}"),
+            r#"FastFieldTermQuery { field_name: "tag", value: "rust" }"#
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let query = query_parser.parse_query("tag:SEARCH")?;
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
     #[test]
     pub fn test_query_parser_expected_facet() {
         let query_parser = make_query_parser();
@@ -1761,14 +2559,19 @@ mod test {
 
     #[test]
     pub fn test_parse_query_single_negative_term_through_error() {
-        assert_matches!(
-            parse_query_to_logical_ast("-title:toto", true),
-            Err(QueryParserError::AllButQueryForbidden)
-        );
-        assert_matches!(
-            parse_query_to_logical_ast("-title:toto", false),
-            Err(QueryParserError::AllButQueryForbidden)
-        );
+        for default_conjunction in [true, false] {
+            let mut query_parser = make_query_parser();
+            if default_conjunction {
+                query_parser.set_conjunction_by_default();
+            }
+            query_parser.forbid_all_but_queries();
+            assert_matches!(
+                query_parser.parse_query("-title:toto"),
+                Err(QueryParserError::AllButQueryForbidden)
+            );
+            let (_, errors) = query_parser.parse_query_lenient("-title:toto");
+            assert_matches!(errors[..], [QueryParserError::AllButQueryForbidden]);
+        }
     }
 
     #[test]
@@ -1988,6 +2791,95 @@ mod test {
         );
     }
 
+    fn make_query_parser_with_field_mappings() -> QueryParser {
+        let mut query_parser = make_query_parser();
+        let schema = make_schema();
+        let title = schema.get_field("title").unwrap();
+        let text = schema.get_field("text").unwrap();
+        let signed = schema.get_field("signed").unwrap();
+        let unsigned = schema.get_field("unsigned").unwrap();
+        let u64_ff = schema.get_field("u64_ff").unwrap();
+        query_parser
+            .add_field_mapping("content", vec![(title, 1.0), (text, 0.8)])
+            .unwrap();
+        query_parser
+            .add_field_mapping("number", vec![(signed, 1.0), (unsigned, 2.0), (title, 0.5)])
+            .unwrap();
+        query_parser
+            .add_field_mapping("fast", vec![(u64_ff, 1.0), (title, 1.0)])
+            .unwrap();
+        query_parser
+    }
+
+    #[track_caller]
+    fn test_field_mapping_helper(query: &str, expected: &str) {
+        let query_parser = make_query_parser_with_field_mappings();
+        let logical_ast = query_parser.parse_query_to_logical_ast(query).unwrap();
+        assert_eq!(format!("{logical_ast:?}"), expected);
+    }
+
+    #[test]
+    pub fn test_field_mapping_term_and_phrase() {
+        test_field_mapping_helper(
+            "content:hello",
+            r#"(Term(field=0, type=Str, "hello") Term(field=1, type=Str, "hello")^0.8)"#,
+        );
+        test_field_mapping_helper(
+            r#"content:"hello happy""#,
+            r#"("[(0, Term(field=0, type=Str, "hello")), (1, Term(field=0, type=Str, "happy"))]" "[(0, Term(field=1, type=Str, "hello")), (1, Term(field=1, type=Str, "happy"))]"^0.8)"#,
+        );
+    }
+
+    #[test]
+    pub fn test_field_mapping_with_field_boost() {
+        let mut query_parser = make_query_parser_with_field_mappings();
+        let text = make_schema().get_field("text").unwrap();
+        query_parser.set_field_boost(text, 2.0);
+        let query = query_parser.parse_query("content:hello").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            r#"BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, "hello"))), (Should, Boost(query=Boost(query=TermQuery(Term(field=1, type=Str, "hello")), boost=2), boost=0.8))], minimum_number_should_match: 1 }"#
+        );
+    }
+
+    #[test]
+    pub fn test_field_mapping_range_and_set() {
+        test_field_mapping_helper(
+            "number:[1 TO 3]",
+            r#"((Included(Term(field=2, type=I64, 1)) TO Included(Term(field=2, type=I64, 3))) (Included(Term(field=3, type=U64, 1)) TO Included(Term(field=3, type=U64, 3)))^2 (Included(Term(field=0, type=Str, "1")) TO Included(Term(field=0, type=Str, "3")))^0.5)"#,
+        );
+        // -1 is not a valid u64: the unsigned field is left out.
+        test_field_mapping_helper(
+            "number:[-1 TO 3]",
+            r#"((Included(Term(field=2, type=I64, -1)) TO Included(Term(field=2, type=I64, 3))) (Included(Term(field=0, type=Str, "1")) TO Included(Term(field=0, type=Str, "3")))^0.5)"#,
+        );
+        test_field_mapping_helper(
+            "number: IN [1 2]",
+            r#"(IN [Term(field=2, type=I64, 1), Term(field=2, type=I64, 2)] IN [Term(field=3, type=U64, 1), Term(field=3, type=U64, 2)]^2 IN [Term(field=0, type=Str, "1"), Term(field=0, type=Str, "2")]^0.5)"#,
+        );
+    }
+
+    #[test]
+    pub fn test_field_mapping_exists() {
+        // title is not a fast field: it is left out.
+        test_field_mapping_helper("fast:*", r#"$exists("u64_ff")"#);
+        let query_parser = make_query_parser_with_field_mappings();
+        assert_matches!(
+            query_parser.parse_query("content:*"),
+            Err(QueryParserError::UnsupportedQuery(_))
+        );
+    }
+
+    #[test]
+    pub fn test_field_mapping_conflict() {
+        let mut query_parser = make_query_parser();
+        let text = make_schema().get_field("text").unwrap();
+        assert_eq!(
+            query_parser.add_field_mapping("title", vec![(text, 1.0)]),
+            Err(QueryParserError::FieldMappingConflict("title".to_string()))
+        );
+    }
+
     #[test]
     pub fn test_set_field_fuzzy() {
         {
@@ -2121,4 +3013,37 @@ mod test {
             "Unsupported query: Regex queries are not allowed."
         );
     }
+
+    #[test]
+    fn test_escaped_field_name() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field(r"my\field", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(field => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        let query = query_parser.parse_query(r"my\\field:a").unwrap();
+        assert_eq!(searcher.search(&query, &crate::collector::Count)?, 1);
+        Ok(())
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_single_quoted_term_round_trip(value in "(?s).{1,20}") {
+            let mut schema_builder = Schema::builder();
+            let field = schema_builder.add_text_field("field", STRING);
+            let index = Index::create_in_ram(schema_builder.build());
+            let mut index_writer = index.writer_for_tests().unwrap();
+            index_writer.add_document(doc!(field => value.as_str())).unwrap();
+            index_writer.commit().unwrap();
+            let searcher = index.reader().unwrap().searcher();
+            let query_parser = QueryParser::for_index(&index, vec![]);
+            let query_str = format!("field:'{}'", value.replace('\'', "''"));
+            let query = query_parser.parse_query(&query_str).unwrap();
+            let count = searcher.search(&query, &crate::collector::Count).unwrap();
+            proptest::prop_assert_eq!(count, 1);
+        }
+    }
 }
@@ -15,7 +15,7 @@ use super::fast_field_range_doc_set::RangeDocSet;
 use crate::query::{
     AllScorer, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight,
 };
-use crate::schema::{Type, ValueBytes};
+use crate::schema::{FieldType, Type, ValueBytes};
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
 #[derive(Clone, Debug)]
@@ -199,6 +199,9 @@ impl Weight for FastFieldRangeWeight {
                 "{field_type:?}"
             );
 
+            // The values of f32 fields are stored with the f32 mapping, while their terms hold
+            // f64 values.
+            let is_f32_field = matches!(field_type, FieldType::F32(_));
             let bounds = self.bounds.map_bound_res(|term| {
                 let value = term.value();
                 let val = if let Some(val) = value.as_u64() {
@@ -206,7 +209,11 @@ impl Weight for FastFieldRangeWeight {
                 } else if let Some(val) = value.as_i64() {
                     val.to_u64()
                 } else if let Some(val) = value.as_f64() {
-                    val.to_u64()
+                    if is_f32_field {
+                        (val as f32).to_u64()
+                    } else {
+                        val.to_u64()
+                    }
                 } else if let Some(val) = value.as_date() {
                     val.to_u64()
                 } else {
@@ -223,6 +230,7 @@ impl Weight for FastFieldRangeWeight {
                     ColumnType::U64,
                     ColumnType::I64,
                     ColumnType::F64,
+                    ColumnType::F32,
                     ColumnType::DateTime,
                 ]),
                 &field_name,
@@ -586,6 +594,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_f32_field_ff_range_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let score = schema_builder.add_f32_field("score", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        index_writer.add_document(doc!(score => -2.5f64))?;
+        index_writer.add_document(doc!(score => 1.1f64))?;
+        index_writer.add_document(doc!(score => 3.0f64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let count = |lower_bound: Bound<f64>, upper_bound: Bound<f64>| {
+            let to_term = |val: f64| Term::from_field_f64(score, val);
+            let query = RangeQuery::new(lower_bound.map(to_term), upper_bound.map(to_term));
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count(Bound::Included(-3.0), Bound::Included(3.0)), 3);
+        assert_eq!(count(Bound::Included(0.0), Bound::Unbounded), 2);
+        assert_eq!(count(Bound::Unbounded, Bound::Excluded(3.0)), 2);
+        assert_eq!(count(Bound::Excluded(-2.5), Bound::Included(1.1)), 1);
+        assert_eq!(count(Bound::Included(1.2), Bound::Excluded(3.0)), 0);
+
+        let query_parser = QueryParser::for_index(&index, vec![score]);
+        let query = query_parser.parse_query("score:[1 TO 5]")?;
+        assert_eq!(searcher.search(&query, &Count)?, 2);
+        Ok(())
+    }
+
     #[test]
     fn test_date_range_query() {
         let mut schema_builder = Schema::builder();
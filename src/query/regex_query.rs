@@ -81,8 +81,11 @@ impl RegexQuery {
 }
 
 impl Query for RegexQuery {
-    fn weight(&self, _enabled_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
-        Ok(Box::new(self.specialized_weight()))
+    fn weight(&self, enabled_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        Ok(Box::new(
+            self.specialized_weight()
+                .with_rewrite_deadline(enabled_scoring.rewrite_deadline()),
+        ))
     }
 }
 
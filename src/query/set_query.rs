@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
 use tantivy_fst::raw::CompiledAddr;
 use tantivy_fst::{Automaton, Map};
@@ -33,6 +34,7 @@ impl TermSetQuery {
     fn specialized_weight(
         &self,
         schema: &Schema,
+        rewrite_deadline: Option<Instant>,
     ) -> crate::Result<BooleanWeight<DoNothingCombiner>> {
         let mut sub_queries: Vec<(_, Box<dyn Weight>)> = Vec::with_capacity(self.terms_map.len());
 
@@ -56,7 +58,10 @@ impl TermSetQuery {
 
             sub_queries.push((
                 Occur::Should,
-                Box::new(AutomatonWeight::new(field, SetDfaWrapper(map))),
+                Box::new(
+                    AutomatonWeight::new(field, SetDfaWrapper(map))
+                        .with_rewrite_deadline(rewrite_deadline),
+                ),
             ));
         }
 
@@ -70,7 +75,10 @@ impl TermSetQuery {
 
 impl Query for TermSetQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
-        Ok(Box::new(self.specialized_weight(enable_scoring.schema())?))
+        Ok(Box::new(self.specialized_weight(
+            enable_scoring.schema(),
+            enable_scoring.rewrite_deadline(),
+        )?))
     }
 
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
@@ -0,0 +1,125 @@
+use common::TinySet;
+
+use crate::docset::{DocSet, TERMINATED};
+use crate::DocId;
+
+// The union is computed over windows of `WINDOW` documents.
+const WINDOW_NUM_TINYBITSETS: usize = WINDOW as usize / 64;
+const WINDOW: u32 = 64u32 * 64u32;
+
+/// Calls `callback` with each of the documents present in any of the `docsets`, in increasing
+/// order.
+///
+/// See [`union_docsets_with_counts`] for the strategy.
+pub fn union_docsets(docsets: Vec<Box<dyn DocSet>>, callback: &mut dyn FnMut(DocId)) {
+    union_docsets_with_counts(docsets, &mut |doc, _count| callback(doc));
+}
+
+/// Calls `callback` with each of the documents present in any of the `docsets`, in increasing
+/// order, along with the number of `docsets` it is present in.
+///
+/// The documents are buffered by windows of 4096 documents, starting at the lowest document of
+/// the `docsets`: each `DocSet` marks its documents of the window in a bitset and increments
+/// their counts, after which the window is emitted by iterating over the bitset. The cost is
+/// proportional to the number of documents of the `docsets`, and empty stretches of documents
+/// are skipped.
+pub fn union_docsets_with_counts(
+    mut docsets: Vec<Box<dyn DocSet>>,
+    callback: &mut dyn FnMut(DocId, u32),
+) {
+    docsets.retain(|docset| docset.doc() != TERMINATED);
+    if docsets.len() == 1 {
+        let mut doc = docsets[0].doc();
+        while doc != TERMINATED {
+            callback(doc, 1);
+            doc = docsets[0].advance();
+        }
+        return;
+    }
+    let mut bitsets = [TinySet::empty(); WINDOW_NUM_TINYBITSETS];
+    let mut counts = vec![0u32; WINDOW as usize];
+    while let Some(window_start_doc) = docsets.iter().map(|docset| docset.doc()).min() {
+        let window_end_doc = window_start_doc + WINDOW;
+        for docset in &mut docsets {
+            let mut doc = docset.doc();
+            while doc < window_end_doc {
+                let delta = doc - window_start_doc;
+                bitsets[delta as usize / 64].insert_mut(delta % 64);
+                counts[delta as usize] += 1;
+                doc = docset.advance();
+            }
+        }
+        for (bucket, tinyset) in bitsets.iter_mut().enumerate() {
+            while let Some(bit) = tinyset.pop_lowest() {
+                let delta = bucket as u32 * 64 + bit;
+                callback(
+                    window_start_doc + delta,
+                    std::mem::take(&mut counts[delta as usize]),
+                );
+            }
+        }
+        docsets.retain(|docset| docset.doc() != TERMINATED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{union_docsets, union_docsets_with_counts};
+    use crate::docset::DocSet;
+    use crate::query::VecDocSet;
+    use crate::DocId;
+
+    #[test]
+    fn test_union_docsets_against_brute_force() {
+        let sizes_list: [&[usize]; 7] = [
+            &[],
+            &[1_000],
+            &[1, 90_000],
+            &[0, 5, 30, 80_000],
+            &[20, 25],
+            &[40_000, 60_000, 90_000],
+            &[3, 3, 3, 3, 3, 3, 3, 3, 3, 3],
+        ];
+        for (seed, sizes) in sizes_list.into_iter().enumerate() {
+            let mut rng = StdRng::seed_from_u64(seed as u64);
+            let docs_list: Vec<Vec<DocId>> = sizes
+                .iter()
+                .map(|&size| {
+                    let mut docs: Vec<DocId> =
+                        (0..size).map(|_| rng.random_range(0..100_000)).collect();
+                    docs.sort_unstable();
+                    docs.dedup();
+                    docs
+                })
+                .collect();
+            let boxed_docsets = || -> Vec<Box<dyn DocSet>> {
+                docs_list
+                    .iter()
+                    .map(|docs| Box::new(VecDocSet::from(docs.clone())) as Box<dyn DocSet>)
+                    .collect()
+            };
+            let mut expected: BTreeMap<DocId, u32> = BTreeMap::new();
+            for &doc in docs_list.iter().flatten() {
+                *expected.entry(doc).or_default() += 1;
+            }
+            let expected: Vec<(DocId, u32)> = expected.into_iter().collect();
+
+            let mut docs_with_counts = Vec::new();
+            union_docsets_with_counts(boxed_docsets(), &mut |doc, count| {
+                docs_with_counts.push((doc, count))
+            });
+            assert_eq!(docs_with_counts, expected, "sizes: {sizes:?}");
+            let mut docs = Vec::new();
+            union_docsets(boxed_docsets(), &mut |doc| docs.push(doc));
+            assert!(docs
+                .iter()
+                .copied()
+                .eq(expected.iter().map(|&(doc, _)| doc)));
+        }
+    }
+}
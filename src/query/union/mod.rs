@@ -1,9 +1,11 @@
 mod bitset_union;
 mod buffered_union;
+mod docset_union;
 mod simple_union;
 
 pub use bitset_union::BitSetPostingUnion;
 pub use buffered_union::BufferedUnionScorer;
+pub use docset_union::{union_docsets, union_docsets_with_counts};
 pub use simple_union::SimpleUnion;
 
 #[cfg(test)]
@@ -188,10 +188,14 @@ impl InnerIndexReader {
     /// Opens the freshest segments [`SegmentReader`].
     ///
     /// This function acquires a lock to prevent GC from removing files
-    /// as we are opening our index.
+    /// as we are opening our index, unless the index is read-only.
     fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
         // Prevents segment files from getting deleted while we are in the process of opening them
-        let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
+        let _meta_lock = if index.is_read_only() {
+            None
+        } else {
+            Some(index.directory().acquire_lock(&META_LOCK)?)
+        };
         let searchable_segments = index.searchable_segments()?;
         let segment_readers = searchable_segments
             .iter()
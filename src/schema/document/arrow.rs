@@ -0,0 +1,605 @@
+//! Conversion of Arrow record batches into [`TantivyDocument`]s.
+//!
+//! Parquet files can be read as Arrow record batches with the `parquet` crate's
+//! `ParquetRecordBatchReader`, which makes it possible to index them without going through JSON.
+
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Date32Type, Date64Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+    TimestampSecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::{DataType, TimeUnit};
+
+use super::default_document::ValuePath;
+use super::{OwnedValue, TantivyDocument};
+use crate::schema::field_type::ValueParsingError;
+use crate::schema::{Facet, Field, FieldType, IntoIpv6Addr, Schema};
+use crate::{DateTime, TantivyError};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Maps the columns of Arrow record batches to the fields of a [`Schema`].
+///
+/// Columns which are not mapped are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct ArrowFieldMapping {
+    columns: Vec<(String, Field)>,
+}
+
+impl ArrowFieldMapping {
+    /// Creates an empty mapping.
+    pub fn new() -> ArrowFieldMapping {
+        ArrowFieldMapping::default()
+    }
+
+    /// Maps the column named `column_name` to `field`.
+    pub fn map_column(mut self, column_name: impl Into<String>, field: Field) -> Self {
+        self.columns.push((column_name.into(), field));
+        self
+    }
+
+    /// Maps each column of `arrow_schema` to the field of `schema` with the same name.
+    ///
+    /// Columns without a matching field are ignored.
+    pub fn by_name(schema: &Schema, arrow_schema: &arrow_schema::Schema) -> ArrowFieldMapping {
+        let columns = arrow_schema
+            .fields()
+            .iter()
+            .filter_map(|arrow_field| {
+                let field = schema.get_field(arrow_field.name()).ok()?;
+                Some((arrow_field.name().to_string(), field))
+            })
+            .collect();
+        ArrowFieldMapping { columns }
+    }
+
+    /// Returns the mapped columns, with the field they are mapped to.
+    pub fn columns(&self) -> impl Iterator<Item = (&str, Field)> + '_ {
+        self.columns
+            .iter()
+            .map(|(column_name, field)| (column_name.as_str(), *field))
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        batch: &'a RecordBatch,
+    ) -> crate::Result<Vec<(&'a str, Field, &'a ArrayRef)>> {
+        self.columns()
+            .map(|(column_name, field)| {
+                let column = batch.column_by_name(column_name).ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "Column {column_name:?} is missing from the record batch"
+                    ))
+                })?;
+                Ok((column_name, field, column))
+            })
+            .collect()
+    }
+}
+
+impl TantivyDocument {
+    /// Builds a document from the row `row_idx` of an Arrow record batch.
+    ///
+    /// Null values are skipped, list columns add one value per item, struct columns are
+    /// converted to JSON objects and timestamps are converted to [`DateTime`] whatever their
+    /// unit.
+    pub fn from_arrow_row(
+        schema: &Schema,
+        mapping: &ArrowFieldMapping,
+        batch: &RecordBatch,
+        row_idx: usize,
+    ) -> crate::Result<TantivyDocument> {
+        if row_idx >= batch.num_rows() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Row {row_idx} is out of bounds for a record batch of {} rows",
+                batch.num_rows()
+            )));
+        }
+        let columns = mapping.resolve(batch)?;
+        row_to_document(schema, &columns, row_idx)
+    }
+}
+
+/// Builds the documents of all of the rows of an Arrow record batch.
+pub(crate) fn documents_from_record_batch(
+    schema: &Schema,
+    mapping: &ArrowFieldMapping,
+    batch: &RecordBatch,
+) -> crate::Result<Vec<TantivyDocument>> {
+    let columns = mapping.resolve(batch)?;
+    (0..batch.num_rows())
+        .map(|row_idx| row_to_document(schema, &columns, row_idx))
+        .collect()
+}
+
+fn row_to_document(
+    schema: &Schema,
+    columns: &[(&str, Field, &ArrayRef)],
+    row_idx: usize,
+) -> crate::Result<TantivyDocument> {
+    let mut doc = TantivyDocument::default();
+    for &(column_name, field, column) in columns {
+        let field_type = schema.get_field_entry(field).field_type();
+        let path = ValuePath::new(column_name);
+        add_cell_values(&mut doc, field, field_type, &path, column, row_idx)?;
+    }
+    Ok(doc)
+}
+
+fn add_cell_values(
+    doc: &mut TantivyDocument,
+    field: Field,
+    field_type: &FieldType,
+    path: &ValuePath,
+    array: &dyn Array,
+    row_idx: usize,
+) -> crate::Result<()> {
+    if array.is_null(row_idx) {
+        return Ok(());
+    }
+    // A list column is a multivalued field, unless it targets a JSON field.
+    if !matches!(field_type, FieldType::JsonObject(_)) {
+        if let Some(items) = list_items(array, row_idx) {
+            for item_idx in 0..items.len() {
+                let item_path = path.index(item_idx);
+                add_cell_values(doc, field, field_type, &item_path, &items, item_idx)?;
+            }
+            return Ok(());
+        }
+    }
+    let value = coerce_value(field_type, arrow_value(array, row_idx)?)
+        .and_then(|value| {
+            field_type.check_enum_value(&value)?;
+            Ok(value)
+        })
+        .map_err(|err| path.value_error(err))?;
+    doc.add_field_value(field, &value);
+    Ok(())
+}
+
+fn list_items(array: &dyn Array, row_idx: usize) -> Option<ArrayRef> {
+    match array.data_type() {
+        DataType::List(_) => Some(array.as_list::<i32>().value(row_idx)),
+        DataType::LargeList(_) => Some(array.as_list::<i64>().value(row_idx)),
+        DataType::FixedSizeList(_, _) => Some(array.as_fixed_size_list().value(row_idx)),
+        _ => None,
+    }
+}
+
+/// Converts a non-null cell to the `OwnedValue` matching its Arrow type.
+fn arrow_value(array: &dyn Array, row_idx: usize) -> crate::Result<OwnedValue> {
+    if let Some(items) = list_items(array, row_idx) {
+        let values = (0..items.len())
+            .filter(|&item_idx| !items.is_null(item_idx))
+            .map(|item_idx| arrow_value(&items, item_idx))
+            .collect::<crate::Result<_>>()?;
+        return Ok(OwnedValue::Array(values));
+    }
+    let value = match array.data_type() {
+        DataType::Null => OwnedValue::Null,
+        DataType::Boolean => OwnedValue::Bool(array.as_boolean().value(row_idx)),
+        DataType::Int8 => OwnedValue::I64(array.as_primitive::<Int8Type>().value(row_idx).into()),
+        DataType::Int16 => OwnedValue::I64(array.as_primitive::<Int16Type>().value(row_idx).into()),
+        DataType::Int32 => OwnedValue::I64(array.as_primitive::<Int32Type>().value(row_idx).into()),
+        DataType::Int64 => OwnedValue::I64(array.as_primitive::<Int64Type>().value(row_idx)),
+        DataType::UInt8 => OwnedValue::U64(array.as_primitive::<UInt8Type>().value(row_idx).into()),
+        DataType::UInt16 => {
+            OwnedValue::U64(array.as_primitive::<UInt16Type>().value(row_idx).into())
+        }
+        DataType::UInt32 => {
+            OwnedValue::U64(array.as_primitive::<UInt32Type>().value(row_idx).into())
+        }
+        DataType::UInt64 => OwnedValue::U64(array.as_primitive::<UInt64Type>().value(row_idx)),
+        DataType::Float32 => {
+            OwnedValue::F64(array.as_primitive::<Float32Type>().value(row_idx).into())
+        }
+        DataType::Float64 => OwnedValue::F64(array.as_primitive::<Float64Type>().value(row_idx)),
+        DataType::Utf8 => OwnedValue::Str(array.as_string::<i32>().value(row_idx).to_string()),
+        DataType::LargeUtf8 => OwnedValue::Str(array.as_string::<i64>().value(row_idx).to_string()),
+        DataType::Utf8View => OwnedValue::Str(array.as_string_view().value(row_idx).to_string()),
+        DataType::Binary => OwnedValue::Bytes(array.as_binary::<i32>().value(row_idx).to_vec()),
+        DataType::LargeBinary => {
+            OwnedValue::Bytes(array.as_binary::<i64>().value(row_idx).to_vec())
+        }
+        DataType::BinaryView => OwnedValue::Bytes(array.as_binary_view().value(row_idx).to_vec()),
+        DataType::FixedSizeBinary(_) => {
+            OwnedValue::Bytes(array.as_fixed_size_binary().value(row_idx).to_vec())
+        }
+        DataType::Timestamp(time_unit, _) => {
+            // Timestamps are stored relative to the UTC epoch, whatever their timezone.
+            let date_time = match time_unit {
+                TimeUnit::Second => DateTime::from_timestamp_secs(
+                    array.as_primitive::<TimestampSecondType>().value(row_idx),
+                ),
+                TimeUnit::Millisecond => DateTime::from_timestamp_millis(
+                    array
+                        .as_primitive::<TimestampMillisecondType>()
+                        .value(row_idx),
+                ),
+                TimeUnit::Microsecond => DateTime::from_timestamp_micros(
+                    array
+                        .as_primitive::<TimestampMicrosecondType>()
+                        .value(row_idx),
+                ),
+                TimeUnit::Nanosecond => DateTime::from_timestamp_nanos(
+                    array
+                        .as_primitive::<TimestampNanosecondType>()
+                        .value(row_idx),
+                ),
+            };
+            OwnedValue::Date(date_time)
+        }
+        DataType::Date32 => {
+            let num_days = i64::from(array.as_primitive::<Date32Type>().value(row_idx));
+            OwnedValue::Date(DateTime::from_timestamp_secs(num_days * SECONDS_PER_DAY))
+        }
+        DataType::Date64 => OwnedValue::Date(DateTime::from_timestamp_millis(
+            array.as_primitive::<Date64Type>().value(row_idx),
+        )),
+        DataType::Struct(_) => {
+            let struct_array = array.as_struct();
+            let mut entries = Vec::with_capacity(struct_array.num_columns());
+            for (arrow_field, child) in struct_array.fields().iter().zip(struct_array.columns()) {
+                if !child.is_null(row_idx) {
+                    entries.push((arrow_field.name().to_string(), arrow_value(child, row_idx)?));
+                }
+            }
+            OwnedValue::Object(entries)
+        }
+        data_type => {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Unsupported Arrow data type: {data_type}"
+            )));
+        }
+    };
+    Ok(value)
+}
+
+/// Converts a value to the type expected by the field, following the same rules as
+/// [`FieldType::value_from_json`].
+fn coerce_value(
+    field_type: &FieldType,
+    value: OwnedValue,
+) -> Result<OwnedValue, ValueParsingError> {
+    let coerced_value = match (field_type, value) {
+        (FieldType::Str(_), value @ OwnedValue::Str(_))
+        | (FieldType::U64(_), value @ OwnedValue::U64(_))
+        | (FieldType::I64(_), value @ OwnedValue::I64(_))
+        | (FieldType::F64(_), value @ OwnedValue::F64(_))
+        | (FieldType::Bool(_), value @ OwnedValue::Bool(_))
+        | (FieldType::Date(_), value @ OwnedValue::Date(_))
+        | (FieldType::Bytes(_), value @ OwnedValue::Bytes(_))
+        | (FieldType::JsonObject(_), value @ OwnedValue::Object(_)) => value,
+        (FieldType::U64(_), OwnedValue::I64(val)) => match u64::try_from(val) {
+            Ok(val) => OwnedValue::U64(val),
+            Err(_) => return Err(overflow_error("u64", val.into())),
+        },
+        (FieldType::I64(_), OwnedValue::U64(val)) => match i64::try_from(val) {
+            Ok(val) => OwnedValue::I64(val),
+            Err(_) => return Err(overflow_error("an i64 int", val.into())),
+        },
+        (FieldType::F64(_), OwnedValue::U64(val)) => OwnedValue::F64(val as f64),
+        (FieldType::F64(_), OwnedValue::I64(val)) => OwnedValue::F64(val as f64),
+        (FieldType::F32(_), OwnedValue::F64(val)) => OwnedValue::F64(f64::from(val as f32)),
+        (FieldType::F32(_), OwnedValue::U64(val)) => OwnedValue::F64(f64::from(val as f32)),
+        (FieldType::F32(_), OwnedValue::I64(val)) => OwnedValue::F64(f64::from(val as f32)),
+        (FieldType::Str(opt), OwnedValue::U64(val)) if opt.should_coerce() => {
+            OwnedValue::Str(val.to_string())
+        }
+        (FieldType::Str(opt), OwnedValue::I64(val)) if opt.should_coerce() => {
+            OwnedValue::Str(val.to_string())
+        }
+        (FieldType::Str(opt), OwnedValue::F64(val)) if opt.should_coerce() => {
+            OwnedValue::Str(val.to_string())
+        }
+        (FieldType::Facet(_), OwnedValue::Str(text)) => OwnedValue::Facet(Facet::from(&text)),
+        (FieldType::IpAddr(_), OwnedValue::Str(text)) => match IpAddr::from_str(&text) {
+            Ok(ip_addr) => OwnedValue::IpAddr(ip_addr.into_ipv6_addr()),
+            Err(err) => {
+                return Err(ValueParsingError::ParseError {
+                    error: err.to_string(),
+                    json: serde_json::Value::String(text),
+                })
+            }
+        },
+        (field_type, value) => {
+            return Err(ValueParsingError::TypeError {
+                expected: field_type.value_type().name(),
+                json: serde_json::to_value(&value).unwrap_or_default(),
+            })
+        }
+    };
+    Ok(coerced_value)
+}
+
+fn overflow_error(expected: &'static str, json: serde_json::Value) -> ValueParsingError {
+    ValueParsingError::OverflowError { expected, json }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+    use std::sync::Arc;
+
+    use arrow_array::builder::{ListBuilder, StringBuilder};
+    use arrow_array::{
+        BinaryArray, BooleanArray, Date32Array, Date64Array, Float32Array, Float64Array,
+        Int16Array, Int32Array, Int64Array, Int8Array, LargeStringArray, StringArray, StructArray,
+        TimestampMicrosecondArray, TimestampMillisecondArray, TimestampNanosecondArray,
+        TimestampSecondArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+    };
+    use arrow_schema::Field as ArrowField;
+
+    use super::*;
+    use crate::collector::TopDocs;
+    use crate::query::AllQuery;
+    use crate::schema::{
+        DateOptions, DateTimePrecision, Document, FAST, INDEXED, STORED, STRING, TEXT,
+    };
+    use crate::{Index, IndexWriter, Order};
+
+    fn record_batch(columns: Vec<(&str, ArrayRef)>) -> RecordBatch {
+        RecordBatch::try_from_iter(columns).unwrap()
+    }
+
+    #[test]
+    fn test_from_arrow_row_all_types() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let date_options = DateOptions::from(STORED).set_precision(DateTimePrecision::Nanoseconds);
+        let flag = schema_builder.add_bool_field("flag", STORED);
+        let small_ints = schema_builder.add_i64_field("small_ints", STORED);
+        let int = schema_builder.add_i64_field("int", STORED);
+        let small_uints = schema_builder.add_u64_field("small_uints", STORED);
+        let uint = schema_builder.add_u64_field("uint", STORED);
+        let float = schema_builder.add_f64_field("float", STORED);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let category = schema_builder.add_facet_field("category", STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", STORED);
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let dates = schema_builder.add_date_field("dates", date_options);
+        let tags = schema_builder.add_text_field("tags", STRING | STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+
+        let mut tags_builder = ListBuilder::new(StringBuilder::new());
+        tags_builder.values().append_value("rust");
+        tags_builder.values().append_null();
+        tags_builder.values().append_value("search");
+        tags_builder.append(true);
+        tags_builder.append(false);
+        let attributes_array = StructArray::from(vec![
+            (
+                Arc::new(ArrowField::new("color", DataType::Utf8, true)),
+                Arc::new(StringArray::from(vec![Some("red"), None])) as ArrayRef,
+            ),
+            (
+                Arc::new(ArrowField::new("size", DataType::Int32, false)),
+                Arc::new(Int32Array::from(vec![3, 4])) as ArrayRef,
+            ),
+        ]);
+        let batch = record_batch(vec![
+            ("flag", Arc::new(BooleanArray::from(vec![Some(true), None]))),
+            ("i8", Arc::new(Int8Array::from(vec![-8, 8]))),
+            ("i16", Arc::new(Int16Array::from(vec![-16, 16]))),
+            ("i32", Arc::new(Int32Array::from(vec![-32, 32]))),
+            ("int", Arc::new(Int64Array::from(vec![-64, 64]))),
+            ("u8", Arc::new(UInt8Array::from(vec![8, 9]))),
+            ("u16", Arc::new(UInt16Array::from(vec![16, 17]))),
+            ("u32", Arc::new(UInt32Array::from(vec![32, 33]))),
+            ("uint", Arc::new(UInt64Array::from(vec![u64::MAX, 0]))),
+            ("f32", Arc::new(Float32Array::from(vec![0.5, 1.5]))),
+            ("float", Arc::new(Float64Array::from(vec![Some(2.5), None]))),
+            ("title", Arc::new(StringArray::from(vec!["hello", "world"]))),
+            (
+                "category",
+                Arc::new(LargeStringArray::from(vec!["/a/b", "/c"])),
+            ),
+            ("ip", Arc::new(StringArray::from(vec!["127.0.0.1", "::1"]))),
+            (
+                "payload",
+                Arc::new(BinaryArray::from_vec(vec![b"ab", b"cd"])),
+            ),
+            ("ts_s", Arc::new(TimestampSecondArray::from(vec![1, 2]))),
+            (
+                "ts_ms",
+                Arc::new(TimestampMillisecondArray::from(vec![1_001, 2_001])),
+            ),
+            (
+                "ts_us",
+                Arc::new(
+                    TimestampMicrosecondArray::from(vec![1_000_001, 2_000_001])
+                        .with_timezone("+02:00"),
+                ),
+            ),
+            (
+                "ts_ns",
+                Arc::new(TimestampNanosecondArray::from(vec![
+                    1_000_000_001,
+                    2_000_000_001,
+                ])),
+            ),
+            ("day", Arc::new(Date32Array::from(vec![1, 2]))),
+            (
+                "day_ms",
+                Arc::new(Date64Array::from(vec![86_400_000, 172_800_000])),
+            ),
+            ("tags", Arc::new(tags_builder.finish())),
+            ("attributes", Arc::new(attributes_array)),
+        ]);
+        let mapping = ArrowFieldMapping::by_name(&schema, &batch.schema())
+            .map_column("i8", small_ints)
+            .map_column("i16", small_ints)
+            .map_column("i32", small_ints)
+            .map_column("u8", small_uints)
+            .map_column("u16", small_uints)
+            .map_column("u32", small_uints)
+            .map_column("f32", float)
+            .map_column("ts_s", dates)
+            .map_column("ts_ms", dates)
+            .map_column("ts_us", dates)
+            .map_column("ts_ns", dates)
+            .map_column("day", dates)
+            .map_column("day_ms", dates);
+
+        let doc = TantivyDocument::from_arrow_row(&schema, &mapping, &batch, 0)?;
+        let values = |field: Field| -> Vec<OwnedValue> {
+            doc.get_all(field).map(OwnedValue::from).collect()
+        };
+        assert_eq!(values(flag), vec![OwnedValue::Bool(true)]);
+        assert_eq!(
+            values(small_ints),
+            vec![
+                OwnedValue::I64(-8),
+                OwnedValue::I64(-16),
+                OwnedValue::I64(-32)
+            ]
+        );
+        assert_eq!(values(int), vec![OwnedValue::I64(-64)]);
+        assert_eq!(
+            values(small_uints),
+            vec![OwnedValue::U64(8), OwnedValue::U64(16), OwnedValue::U64(32)]
+        );
+        assert_eq!(values(uint), vec![OwnedValue::U64(u64::MAX)]);
+        assert_eq!(
+            values(float),
+            vec![OwnedValue::F64(2.5), OwnedValue::F64(0.5)]
+        );
+        assert_eq!(values(title), vec![OwnedValue::Str("hello".to_string())]);
+        assert_eq!(
+            values(category),
+            vec![OwnedValue::Facet(Facet::from("/a/b"))]
+        );
+        assert_eq!(
+            values(ip),
+            vec![OwnedValue::IpAddr(
+                Ipv6Addr::from_str("::ffff:127.0.0.1").unwrap()
+            )]
+        );
+        assert_eq!(values(payload), vec![OwnedValue::Bytes(b"ab".to_vec())]);
+        assert_eq!(
+            values(dates),
+            vec![
+                OwnedValue::Date(DateTime::from_timestamp_secs(1)),
+                OwnedValue::Date(DateTime::from_timestamp_millis(1_001)),
+                OwnedValue::Date(DateTime::from_timestamp_micros(1_000_001)),
+                OwnedValue::Date(DateTime::from_timestamp_nanos(1_000_000_001)),
+                OwnedValue::Date(DateTime::from_timestamp_secs(86_400)),
+                OwnedValue::Date(DateTime::from_timestamp_secs(86_400)),
+            ]
+        );
+        assert_eq!(
+            values(tags),
+            vec![
+                OwnedValue::Str("rust".to_string()),
+                OwnedValue::Str("search".to_string())
+            ]
+        );
+        assert_eq!(
+            values(attributes),
+            vec![OwnedValue::Object(vec![
+                ("color".to_string(), OwnedValue::Str("red".to_string())),
+                ("size".to_string(), OwnedValue::I64(3)),
+            ])]
+        );
+
+        // Null cells and empty lists do not add any value.
+        let doc = TantivyDocument::from_arrow_row(&schema, &mapping, &batch, 1)?;
+        assert_eq!(doc.get_all(flag).count(), 0);
+        assert_eq!(doc.get_all(tags).count(), 0);
+        assert_eq!(
+            doc.get_all(float).map(OwnedValue::from).collect::<Vec<_>>(),
+            vec![OwnedValue::F64(1.5)]
+        );
+        assert_eq!(
+            doc.get_all(attributes)
+                .map(OwnedValue::from)
+                .collect::<Vec<_>>(),
+            vec![OwnedValue::Object(vec![(
+                "size".to_string(),
+                OwnedValue::I64(4)
+            )])]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_record_batch_round_trip() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | FAST | STORED);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let published = schema_builder.add_date_field("published", STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+
+        let batch = record_batch(vec![
+            ("id", Arc::new(UInt64Array::from(vec![1, 2, 3]))),
+            (
+                "title",
+                Arc::new(StringArray::from(vec![Some("first"), None, Some("third")])),
+            ),
+            (
+                "published",
+                Arc::new(TimestampMillisecondArray::from(vec![1_000, 2_000, 3_000])),
+            ),
+            ("ignored", Arc::new(Int64Array::from(vec![7, 8, 9]))),
+        ]);
+        let mapping = ArrowFieldMapping::by_name(&schema, &batch.schema());
+        assert_eq!(mapping.columns().count(), 3);
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_record_batch(&mapping, &batch)?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let top_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(3).order_by_u64_field("id", Order::Asc),
+        )?;
+        let docs: Vec<TantivyDocument> = top_docs
+            .into_iter()
+            .map(|(_, doc_address)| searcher.doc(doc_address))
+            .collect::<crate::Result<_>>()?;
+        for (row_idx, doc) in docs.iter().enumerate() {
+            let expected = TantivyDocument::from_arrow_row(&schema, &mapping, &batch, row_idx)?;
+            assert_eq!(doc.to_json(&schema), expected.to_json(&schema));
+        }
+        assert_eq!(
+            docs[1].get_first(id).map(OwnedValue::from),
+            Some(OwnedValue::U64(2))
+        );
+        assert!(docs[1].get_first(title).is_none());
+        assert_eq!(
+            docs[2].get_first(published).map(OwnedValue::from),
+            Some(OwnedValue::Date(DateTime::from_timestamp_secs(3)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_arrow_row_errors() {
+        let mut schema_builder = Schema::builder();
+        let count = schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        let batch = record_batch(vec![("count", Arc::new(Int64Array::from(vec![-1])))]);
+
+        let mapping = ArrowFieldMapping::new().map_column("count", count);
+        assert!(matches!(
+            TantivyDocument::from_arrow_row(&schema, &mapping, &batch, 0),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            TantivyDocument::from_arrow_row(&schema, &mapping, &batch, 1),
+            Err(TantivyError::InvalidArgument(msg)) if msg.contains("out of bounds")
+        ));
+        let missing_column_mapping = ArrowFieldMapping::new().map_column("missing", count);
+        assert!(matches!(
+            TantivyDocument::from_arrow_row(&schema, &missing_column_mapping, &batch, 0),
+            Err(TantivyError::InvalidArgument(msg)) if msg.contains("missing")
+        ));
+    }
+}
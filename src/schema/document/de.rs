@@ -22,7 +22,7 @@ use super::se::BinaryObjectSerializer;
 use super::{OwnedValue, Value};
 use crate::schema::document::type_codes;
 use crate::schema::{Facet, Field};
-use crate::store::DocStoreVersion;
+use crate::store::{DocStoreVersion, StoreDictionaries};
 use crate::tokenizer::PreTokenizedString;
 
 #[derive(Debug, thiserror::Error, Clone)]
@@ -297,6 +297,7 @@ pub struct BinaryDocumentDeserializer<'de, R> {
     position: usize,
     doc_store_version: DocStoreVersion,
     reader: &'de mut R,
+    dictionaries: Option<&'de StoreDictionaries>,
 }
 
 impl<'de, R> BinaryDocumentDeserializer<'de, R>
@@ -314,9 +315,19 @@ where R: Read
             position: 0,
             doc_store_version,
             reader,
+            dictionaries: None,
         })
     }
 
+    /// Sets the dictionaries resolving the values of the dictionary encoded fields.
+    pub(crate) fn with_dictionaries(
+        mut self,
+        dictionaries: Option<&'de StoreDictionaries>,
+    ) -> Self {
+        self.dictionaries = dictionaries;
+        self
+    }
+
     /// Returns true if the deserializer has deserialized all the entries
     /// within the document.
     fn is_complete(&self) -> bool {
@@ -338,8 +349,14 @@ where R: Read
         }
 
         let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
-        let deserializer =
-            BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
+        let dictionary = self
+            .dictionaries
+            .and_then(|dictionaries| dictionaries.field_values(field));
+        let deserializer = BinaryValueDeserializer::from_reader_with_dictionary(
+            self.reader,
+            self.doc_store_version,
+            dictionary,
+        )?;
         let value = V::deserialize(deserializer)?;
 
         self.position += 1;
@@ -354,6 +371,8 @@ pub struct BinaryValueDeserializer<'de, R> {
     value_type: ValueType,
     reader: &'de mut R,
     doc_store_version: DocStoreVersion,
+    // The string resolved from the store dictionary, for dictionary encoded values.
+    dictionary_value: Option<String>,
 }
 
 impl<'de, R> BinaryValueDeserializer<'de, R>
@@ -364,6 +383,17 @@ where R: Read
         reader: &'de mut R,
         doc_store_version: DocStoreVersion,
     ) -> Result<Self, DeserializeError> {
+        Self::from_reader_with_dictionary(reader, doc_store_version, None)
+    }
+
+    /// Attempts to create a new value deserializer from a given reader, resolving
+    /// dictionary encoded values with the store dictionary of the field.
+    fn from_reader_with_dictionary(
+        reader: &'de mut R,
+        doc_store_version: DocStoreVersion,
+        dictionary: Option<&[String]>,
+    ) -> Result<Self, DeserializeError> {
+        let mut dictionary_value = None;
         let type_code = <u8 as BinarySerializable>::deserialize(reader)?;
 
         let value_type = match type_code {
@@ -380,6 +410,23 @@ where R: Read
 
                 match ext_type_code {
                     type_codes::TOK_STR_EXT_CODE => ValueType::PreTokStr,
+                    type_codes::DICT_STR_EXT_CODE => {
+                        let ord = VInt::deserialize(reader)?.val() as usize;
+                        let value =
+                            dictionary
+                                .and_then(|values| values.get(ord))
+                                .ok_or_else(|| {
+                                    DeserializeError::from(io::Error::new(
+                                        io::ErrorKind::InvalidData,
+                                        format!(
+                                            "No value is associated with the dictionary ordinal \
+                                             {ord}"
+                                        ),
+                                    ))
+                                })?;
+                        dictionary_value = Some(value.clone());
+                        ValueType::String
+                    }
                     _ => {
                         return Err(DeserializeError::from(io::Error::new(
                             io::ErrorKind::InvalidData,
@@ -408,6 +455,7 @@ where R: Read
             value_type,
             reader,
             doc_store_version,
+            dictionary_value,
         })
     }
 
@@ -433,6 +481,9 @@ where R: Read
 
     fn deserialize_string(self) -> Result<String, DeserializeError> {
         self.validate_type(ValueType::String)?;
+        if let Some(dictionary_value) = self.dictionary_value {
+            return Ok(dictionary_value);
+        }
         <String as BinarySerializable>::deserialize(self.reader).map_err(DeserializeError::from)
     }
 
@@ -1,25 +1,31 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::io::{self, Read, Write};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, Read, Write};
+use std::mem;
 use std::net::Ipv6Addr;
 
 use columnar::MonotonicallyMappableToU128;
 use common::{read_u32_vint_no_advance, serialize_vint_u32, BinarySerializable, DateTime, VInt};
+use itertools::Either;
 use serde_json::Map;
 pub use CompactDoc as TantivyDocument;
 
-use super::{ReferenceValue, ReferenceValueLeaf, Value};
+use super::{JsonDocParser, JsonLines, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::json_utils::split_json_path;
 use crate::schema::document::{
     DeserializeError, Document, DocumentDeserialize, DocumentDeserializer,
 };
 use crate::schema::field_type::ValueParsingError;
-use crate::schema::{Facet, Field, NamedFieldDocument, OwnedValue, Schema};
+use crate::schema::{
+    Facet, Field, FieldType, JsonParseOptions, NamedFieldDocument, OwnedValue, Schema,
+};
 use crate::tokenizer::PreTokenizedString;
 
 #[repr(C, packed)]
 #[derive(Debug, Clone)]
 /// A field value pair in the compact tantivy document
 struct FieldValueAddr {
-    pub field: u16,
+    pub field: u32,
     pub value_addr: ValueAddr,
 }
 
@@ -32,6 +38,12 @@ pub struct CompactDoc {
     pub node_data: Vec<u8>,
     /// The root (Field, Value) pairs
     field_values: Vec<FieldValueAddr>,
+    /// The addresses of the strings written in `node_data`, if strings are interned.
+    interned_strs: Option<HashMap<String, Addr>>,
+    /// Whether the lists of the values nested in arrays and objects start with their number of
+    /// entries. Only false for the documents of a format version below 5, which are rewritten
+    /// when they are read, see [`CompactDoc::from_bytes`].
+    nested_list_lens: bool,
 }
 
 impl Default for CompactDoc {
@@ -42,17 +54,35 @@ impl Default for CompactDoc {
 
 impl CompactDoc {
     /// Creates a new, empty document object
-    /// The reserved capacity is for the total serialized data
-    pub fn with_capacity(bytes: usize) -> CompactDoc {
+    /// The reserved capacity is for the total serialized data, and for the number of
+    /// (field, value) pairs of the document.
+    pub fn with_capacity(bytes: usize, num_field_values: usize) -> CompactDoc {
         CompactDoc {
             node_data: Vec::with_capacity(bytes),
-            field_values: Vec::with_capacity(4),
+            field_values: Vec::with_capacity(num_field_values),
+            interned_strs: None,
+            nested_list_lens: true,
         }
     }
 
+    /// Creates a new, empty document object which interns its strings.
+    ///
+    /// The payload of a string value or of an object key is written only once, and identical
+    /// strings added afterwards point to it. This makes documents repeating the same strings,
+    /// typically the keys of an array of objects, much smaller, at the cost of a lookup for
+    /// each string added.
+    ///
+    /// Interning does not change how the document is read or serialized, but a deserialized
+    /// document does not intern the strings added to it.
+    pub fn with_interning() -> CompactDoc {
+        let mut doc = CompactDoc::new();
+        doc.interned_strs = Some(HashMap::new());
+        doc
+    }
+
     /// Creates a new, empty document object
     pub fn new() -> CompactDoc {
-        CompactDoc::with_capacity(1024)
+        CompactDoc::with_capacity(1024, 4)
     }
 
     /// Skrinks the capacity of the document to fit the data
@@ -61,6 +91,54 @@ impl CompactDoc {
         self.field_values.shrink_to_fit();
     }
 
+    /// Returns an estimate of the memory used by the document, in bytes.
+    ///
+    /// Only the data of the document is counted, not the spare capacity of its buffers. This is
+    /// the memory the document uses once [`CompactDoc::shrink_to_fit`] has been called, and it
+    /// only grows as values are added.
+    pub fn estimated_memory_usage(&self) -> usize {
+        let interned_strs_usage = self.interned_strs.as_ref().map_or(0, |interned_strs| {
+            interned_strs.keys().map(String::len).sum::<usize>()
+                + interned_strs.len() * mem::size_of::<(String, Addr)>()
+        });
+        mem::size_of::<Self>()
+            + self.node_data.len()
+            + self.field_values.len() * mem::size_of::<FieldValueAddr>()
+            + interned_strs_usage
+    }
+
+    /// Returns the memory allocated by the document, in bytes.
+    ///
+    /// Unlike [`CompactDoc::estimated_memory_usage`], the whole capacity of the buffers of the
+    /// document is counted, including the part that does not hold data yet. For instance, an
+    /// empty document created with [`CompactDoc::new`] already allocates 1KB.
+    pub fn allocated_memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.mem_usage()
+    }
+
+    /// Returns the heap memory allocated by the buffers of the document, in bytes.
+    ///
+    /// This is the capacity of the data of the document, of its (field, value) pairs and of
+    /// its interning table, if any. It is what a batch of documents kept before being added
+    /// to an [`IndexWriter`](crate::IndexWriter) costs, on top of the size of the documents
+    /// themselves.
+    pub fn mem_usage(&self) -> usize {
+        let interned_strs_usage = self.interned_strs.as_ref().map_or(0, |interned_strs| {
+            interned_strs.keys().map(String::capacity).sum::<usize>()
+                + interned_strs.capacity() * mem::size_of::<(String, Addr)>()
+        });
+        self.node_data.capacity()
+            + self.field_values.capacity() * mem::size_of::<FieldValueAddr>()
+            + interned_strs_usage
+    }
+
+    /// Returns the number of bytes of data written in the document.
+    ///
+    /// The data of removed values is counted until [`CompactDoc::compact_node_data`] is called.
+    pub fn payload_len(&self) -> usize {
+        self.node_data.len()
+    }
+
     /// Returns the length of the document.
     pub fn len(&self) -> usize {
         self.field_values.len()
@@ -123,21 +201,57 @@ impl CompactDoc {
         self.add_field_value(field, &OwnedValue::from(object));
     }
 
+    /// Add a (field, JSON object) to the document.
+    ///
+    /// Contrary to [`CompactDoc::add_object`], the entries of the object are written into the
+    /// document as they are, without being converted to `OwnedValue` first.
+    /// Numbers are added as `i64`, `u64` or `f64` values, in that order of preference, and
+    /// strings holding RFC 3339 dates as dates, like `OwnedValue::from` does.
+    pub fn add_json_object(&mut self, field: Field, object: &Map<String, serde_json::Value>) {
+        let field_value = FieldValueAddr {
+            field: field.field_id(),
+            value_addr: self
+                .add_object_value(object.iter().map(|(key, value)| (key.as_str(), value))),
+        };
+        self.field_values.push(field_value);
+    }
+
+    /// Add a (field, JSON value) to the document.
+    ///
+    /// The value is converted like in [`CompactDoc::add_json_object`].
+    pub fn add_json_value(&mut self, field: Field, value: &serde_json::Value) {
+        self.add_field_value(field, value);
+    }
+
     /// Add a (field, value) to the document.
     ///
     /// `OwnedValue` implements Value, which should be easiest to use, but is not the most
     /// performant.
     pub fn add_field_value<'a, V: Value<'a>>(&mut self, field: Field, value: V) {
         let field_value = FieldValueAddr {
-            field: field
-                .field_id()
-                .try_into()
-                .expect("support only up to u16::MAX field ids"),
+            field: field.field_id(),
             value_addr: self.add_value(value),
         };
         self.field_values.push(field_value);
     }
 
+    /// Add a (field, array) to the document, the array holding the given values.
+    ///
+    /// This is the same as adding an array value with [`CompactDoc::add_field_value`], without
+    /// building the array first. Reading the field then returns a single
+    /// [`ReferenceValue::Array`].
+    pub fn add_array<'a, I>(&mut self, field: Field, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Value<'a>,
+    {
+        let field_value = FieldValueAddr {
+            field: field.field_id(),
+            value_addr: self.add_array_value(values),
+        };
+        self.field_values.push(field_value);
+    }
+
     /// Add a (field, leaf value) to the document.
     /// Leaf values don't have nested values.
     pub fn add_leaf_field_value<'a, T: Into<ReferenceValueLeaf<'a>>>(
@@ -147,10 +261,7 @@ impl CompactDoc {
     ) {
         let value = typed_val.into();
         let field_value = FieldValueAddr {
-            field: field
-                .field_id()
-                .try_into()
-                .expect("support only up to u16::MAX field ids"),
+            field: field.field_id(),
             value_addr: self.add_value_leaf(value),
         };
         self.field_values.push(field_value);
@@ -159,17 +270,126 @@ impl CompactDoc {
     /// field_values accessor
     pub fn field_values(&self) -> impl Iterator<Item = (Field, CompactDocValue<'_>)> {
         self.field_values.iter().map(|field_val| {
-            let field = Field::from_field_id(field_val.field as u32);
+            let field = Field::from_field_id(field_val.field);
             let val = self.get_compact_doc_value(field_val.value_addr);
             (field, val)
         })
     }
 
+    /// Removes the values of the fields which are neither indexed, stored nor fast, and which
+    /// would be discarded when adding the document to an index.
+    ///
+    /// Returns the number of values removed.
+    pub fn strip_discarded_values(&mut self, schema: &Schema) -> usize {
+        let num_values = self.field_values.len();
+        self.field_values.retain(|field_value| {
+            let field = Field::from_field_id(field_value.field);
+            !schema.get_field_entry(field).discards_values()
+        });
+        let num_discarded_values = num_values - self.field_values.len();
+        if num_discarded_values > 0 {
+            self.compact_node_data();
+        }
+        num_discarded_values
+    }
+
+    /// Removes all of the values of the given field.
+    ///
+    /// The data of the removed values is left in the document, until
+    /// [`CompactDoc::compact_node_data`] is called.
+    ///
+    /// Returns the number of values removed.
+    pub fn remove_field(&mut self, field: Field) -> usize {
+        self.retain_fields(|value_field| value_field != field)
+    }
+
+    /// Removes all of the values of the given field, and returns them decoded, in the order they
+    /// were added.
+    ///
+    /// The values are removed from the document right away, even if the returned iterator is
+    /// not consumed. As for [`CompactDoc::remove_field`], their data is left in the document
+    /// until [`CompactDoc::compact_node_data`] is called.
+    pub fn drain_field(&mut self, field: Field) -> impl Iterator<Item = OwnedValue> + '_ {
+        let mut drained_addrs = Vec::new();
+        self.field_values.retain(|field_value| {
+            if field_value.field == field.field_id() {
+                drained_addrs.push(field_value.value_addr);
+                false
+            } else {
+                true
+            }
+        });
+        let doc: &CompactDoc = self;
+        drained_addrs
+            .into_iter()
+            .map(move |value_addr| OwnedValue::from(doc.get_compact_doc_value(value_addr)))
+    }
+
+    /// Removes all of the values of the fields for which `predicate` returns false.
+    ///
+    /// As for [`CompactDoc::remove_field`], the data of the removed values is left in the
+    /// document until [`CompactDoc::compact_node_data`] is called.
+    ///
+    /// Returns the number of values removed.
+    pub fn retain_fields<F: FnMut(Field) -> bool>(&mut self, mut predicate: F) -> usize {
+        let num_values = self.field_values.len();
+        self.field_values
+            .retain(|field_value| predicate(Field::from_field_id(field_value.field)));
+        num_values - self.field_values.len()
+    }
+
+    /// Replaces all of the values of the given field by `value`.
+    ///
+    /// As for [`CompactDoc::remove_field`], the data of the replaced values is left in the
+    /// document until [`CompactDoc::compact_node_data`] is called.
+    pub fn set_field_value<'a, V: Value<'a>>(&mut self, field: Field, value: V) {
+        self.remove_field(field);
+        self.add_field_value(field, value);
+    }
+
+    /// Rewrites the data of the document, dropping the data of the values which were removed.
+    pub fn compact_node_data(&mut self) {
+        // The document is rebuilt, as the data of the removed values is interleaved with the
+        // data of the values kept.
+        let mut compacted_doc =
+            CompactDoc::with_capacity(self.node_data.len(), self.field_values.len());
+        if self.interned_strs.is_some() {
+            compacted_doc.interned_strs = Some(HashMap::new());
+        }
+        for (field, value) in self.field_values() {
+            compacted_doc.add_field_value(field, value);
+        }
+        compacted_doc.shrink_to_fit();
+        *self = compacted_doc;
+    }
+
+    /// Appends all of the values of `other` to the document.
+    ///
+    /// The data of `other` is copied as is, only the addresses of its top level values are
+    /// shifted.
+    pub fn merge(&mut self, other: &CompactDoc) {
+        let offset = to_addr(self.node_data.len());
+        // Checks that the shifted addresses fit.
+        to_addr(self.node_data.len() + other.node_data.len());
+        self.node_data.extend_from_slice(&other.node_data);
+        self.field_values
+            .extend(other.field_values.iter().map(|field_value| {
+                let mut value_addr = field_value.value_addr;
+                if value_addr.has_payload() {
+                    value_addr.val_addr += offset;
+                }
+                FieldValueAddr {
+                    field: field_value.field,
+                    value_addr,
+                }
+            }));
+    }
+
     /// Returns all of the `ReferenceValue`s associated the given field
     pub fn get_all(&self, field: Field) -> impl Iterator<Item = CompactDocValue<'_>> + '_ {
         self.field_values
             .iter()
-            .filter(move |field_value| Field::from_field_id(field_value.field as u32) == field)
+            .filter(move |field_value| Field::from_field_id(field_value.field) == field)
             .map(|val| self.get_compact_doc_value(val.value_addr))
     }
 
@@ -178,52 +398,371 @@ impl CompactDoc {
         self.get_all(field).next()
     }
 
+    /// Returns the first value of the given field which has a value at `path`, see
+    /// [`CompactDocValue::get_path`].
+    pub fn get_path(&self, field: Field, path: &[&str]) -> Option<CompactDocValue<'_>> {
+        self.get_all(field).find_map(|value| value.get_path(path))
+    }
+
+    /// Returns the first value of the given field if it is a string.
+    ///
+    /// Like the other typed getters, this returns `None` if the field has no value or if its
+    /// first value has another type. The values nested in an object or an array are not
+    /// considered.
+    pub fn get_first_str(&self, field: Field) -> Option<&str> {
+        self.get_first(field)?.as_str()
+    }
+
+    /// Returns the first value of the given field if it is a `u64`, or a non-negative `i64`.
+    pub fn get_first_u64(&self, field: Field) -> Option<u64> {
+        match self.get_first(field)?.as_leaf()? {
+            ReferenceValueLeaf::U64(val) => Some(val),
+            ReferenceValueLeaf::I64(val) => u64::try_from(val).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the first value of the given field if it is an `i64`, or a `u64` that fits in an
+    /// `i64`.
+    pub fn get_first_i64(&self, field: Field) -> Option<i64> {
+        match self.get_first(field)?.as_leaf()? {
+            ReferenceValueLeaf::I64(val) => Some(val),
+            ReferenceValueLeaf::U64(val) => i64::try_from(val).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the first value of the given field if it is a `f64`, or an integer converted to
+    /// `f64`.
+    pub fn get_first_f64(&self, field: Field) -> Option<f64> {
+        match self.get_first(field)?.as_leaf()? {
+            ReferenceValueLeaf::F64(val) => Some(val),
+            ReferenceValueLeaf::U64(val) => Some(val as f64),
+            ReferenceValueLeaf::I64(val) => Some(val as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the first value of the given field if it is a date.
+    pub fn get_first_date(&self, field: Field) -> Option<DateTime> {
+        self.get_first(field)?.as_datetime()
+    }
+
+    /// Returns the first value of the given field if it is a bytes value.
+    pub fn get_first_bytes(&self, field: Field) -> Option<&[u8]> {
+        self.get_first(field)?.as_bytes()
+    }
+
+    /// Returns the first value of the given field if it is an IP address.
+    pub fn get_first_ip_addr(&self, field: Field) -> Option<Ipv6Addr> {
+        self.get_first(field)?.as_ip_addr()
+    }
+
+    /// Returns the string values of the given field, skipping the values of other types.
+    pub fn get_all_strs(&self, field: Field) -> impl Iterator<Item = &str> + '_ {
+        self.get_all(field).filter_map(|value| value.as_str())
+    }
+
+    /// Returns the values of the given field converted to `T`, skipping the values that can't be
+    /// converted.
+    ///
+    /// The values nested in an object or an array are not considered, and are skipped as well.
+    ///
+    /// ```
+    /// use tantivy::schema::{Schema, STORED};
+    /// use tantivy::TantivyDocument;
+    ///
+    /// let mut schema_builder = Schema::builder();
+    /// let count = schema_builder.add_u64_field("count", STORED);
+    /// let mut doc = TantivyDocument::new();
+    /// doc.add_u64(count, 1);
+    /// doc.add_text(count, "not a number");
+    /// doc.add_u64(count, 2);
+    /// assert_eq!(doc.get_all_typed::<u64>(count).collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn get_all_typed<'a, T>(&'a self, field: Field) -> impl Iterator<Item = T> + 'a
+    where
+        T: TryFrom<OwnedValue, Error = OwnedValue>
+            + TryFrom<ReferenceValueLeaf<'a>, Error = ReferenceValueLeaf<'a>>,
+    {
+        self.get_all_typed_strict(field)
+            .filter_map(|value_res| value_res.ok())
+    }
+
+    /// Returns the values of the given field converted to `T`.
+    ///
+    /// Unlike [`CompactDoc::get_all_typed`], the values that can't be converted are returned as
+    /// errors, holding the value itself.
+    ///
+    /// Leaf values are converted without going through an [`OwnedValue`], which is only built for
+    /// arrays and objects, or for the values that can't be converted.
+    pub fn get_all_typed_strict<'a, T>(
+        &'a self,
+        field: Field,
+    ) -> impl Iterator<Item = Result<T, OwnedValue>> + 'a
+    where
+        T: TryFrom<OwnedValue, Error = OwnedValue>
+            + TryFrom<ReferenceValueLeaf<'a>, Error = ReferenceValueLeaf<'a>>,
+    {
+        self.get_all(field).map(|value| match value.as_value() {
+            ReferenceValue::Leaf(leaf) => T::try_from(leaf).map_err(OwnedValue::from),
+            _ => T::try_from(OwnedValue::from(value)),
+        })
+    }
+
     /// Create document from a named doc.
+    ///
+    /// The fields that are not in the schema are ignored, see
+    /// [`CompactDoc::convert_named_doc_lossy`] to know which ones. The values are checked against
+    /// the type of their field, and converted with the coercion rules of
+    /// [`FieldType::value_from_json`] if needed, see [`FieldType::coerce_value`].
     pub fn convert_named_doc(
         schema: &Schema,
         named_doc: NamedFieldDocument,
+    ) -> Result<Self, DocParsingError> {
+        Self::convert_named_doc_with_mode(schema, named_doc, ParseMode::Lenient)
+    }
+
+    /// Create document from a named doc, handling the fields that are not in the schema
+    /// according to `parse_mode`.
+    pub fn convert_named_doc_with_mode(
+        schema: &Schema,
+        named_doc: NamedFieldDocument,
+        parse_mode: ParseMode,
+    ) -> Result<Self, DocParsingError> {
+        Self::convert_named_doc_impl(schema, named_doc, parse_mode, None)
+    }
+
+    /// Create document from a named doc, returning the names of the fields that are not in the
+    /// schema along with the document.
+    ///
+    /// The values of these fields are dropped, like with [`ParseMode::Lenient`]. The names are
+    /// returned in the order of the named doc, which is sorted.
+    pub fn convert_named_doc_lossy(
+        schema: &Schema,
+        named_doc: NamedFieldDocument,
+    ) -> Result<(Self, Vec<String>), DocParsingError> {
+        let dropped_fields: Vec<String> = named_doc
+            .0
+            .keys()
+            .filter(|field_name| schema.get_field(field_name).is_err())
+            .cloned()
+            .collect();
+        let document = Self::convert_named_doc_impl(schema, named_doc, ParseMode::Lenient, None)?;
+        Ok((document, dropped_fields))
+    }
+
+    /// Create document from a named doc, skipping the values that do not match the type of
+    /// their field.
+    ///
+    /// The errors of the skipped values are returned along with the document. The fields that
+    /// are not in the schema are handled according to `parse_mode`.
+    pub fn convert_named_doc_skipping_invalid_values(
+        schema: &Schema,
+        named_doc: NamedFieldDocument,
+        parse_mode: ParseMode,
+    ) -> Result<(Self, Vec<DocParsingError>), DocParsingError> {
+        let mut invalid_values = Vec::new();
+        let document =
+            Self::convert_named_doc_impl(schema, named_doc, parse_mode, Some(&mut invalid_values))?;
+        Ok((document, invalid_values))
+    }
+
+    /// Values are checked against the type of their field with [`FieldType::coerce_value`].
+    ///
+    /// If `invalid_values` is given, the errors of the invalid values are pushed into it instead
+    /// of being returned.
+    fn convert_named_doc_impl(
+        schema: &Schema,
+        named_doc: NamedFieldDocument,
+        parse_mode: ParseMode,
+        mut invalid_values: Option<&mut Vec<DocParsingError>>,
     ) -> Result<Self, DocParsingError> {
         let mut document = Self::new();
         for (field_name, values) in named_doc.0 {
-            if let Ok(field) = schema.get_field(&field_name) {
-                for value in values {
-                    document.add_field_value(field, &value);
-                }
+            let Some(field) = parse_mode.resolve_field(schema, field_name.as_str())? else {
+                continue;
+            };
+            let field_type = schema.get_field_entry(field).field_type();
+            let path = ValuePath::new(&field_name);
+            for value in values {
+                document.add_coerced_value(
+                    field,
+                    &path,
+                    field_type,
+                    value,
+                    invalid_values.as_deref_mut(),
+                )?;
             }
         }
         Ok(document)
     }
 
+    fn add_coerced_value(
+        &mut self,
+        field: Field,
+        path: &ValuePath,
+        field_type: &FieldType,
+        value: OwnedValue,
+        mut invalid_values: Option<&mut Vec<DocParsingError>>,
+    ) -> Result<(), DocParsingError> {
+        match value {
+            // Like in `from_json_object`, arrays hold several values of the field, except in json
+            // fields.
+            OwnedValue::Array(elements) if !matches!(field_type, FieldType::JsonObject(_)) => {
+                for (idx, element) in elements.into_iter().enumerate() {
+                    self.add_coerced_value(
+                        field,
+                        &path.index(idx),
+                        field_type,
+                        element,
+                        invalid_values.as_deref_mut(),
+                    )?;
+                }
+            }
+            value => match field_type.coerce_value(value) {
+                Ok(value) => self.add_field_value(field, &value),
+                Err(error) => {
+                    let error = path.value_error(error);
+                    let Some(invalid_values) = invalid_values else {
+                        return Err(error);
+                    };
+                    invalid_values.push(error);
+                }
+            },
+        }
+        Ok(())
+    }
+
     /// Build a document object from a json-object.
+    ///
+    /// The keys that are not fields of the schema are ignored.
     pub fn parse_json(schema: &Schema, doc_json: &str) -> Result<Self, DocParsingError> {
+        Self::parse_json_with_mode(schema, doc_json, ParseMode::Lenient)
+    }
+
+    /// Build a document object from a json-object, returning
+    /// [`DocParsingError::NoSuchFieldInSchema`] if a key is not a field of the schema.
+    pub fn parse_json_strict(schema: &Schema, doc_json: &str) -> Result<Self, DocParsingError> {
+        Self::parse_json_with_mode(schema, doc_json, ParseMode::Strict)
+    }
+
+    /// Build a document object from a json-object, handling the keys that are not fields of the
+    /// schema according to `parse_mode`.
+    pub fn parse_json_with_mode(
+        schema: &Schema,
+        doc_json: &str,
+        parse_mode: ParseMode,
+    ) -> Result<Self, DocParsingError> {
+        Self::parse_json_with_options(schema, doc_json, parse_mode, &JsonParseOptions::default())
+    }
+
+    /// Build a document object from a json-object, handling the keys that are not fields of the
+    /// schema according to `parse_mode`, and converting the values with `options`.
+    ///
+    /// See [`CompactDoc::from_json_object_with_options`].
+    pub fn parse_json_with_options(
+        schema: &Schema,
+        doc_json: &str,
+        parse_mode: ParseMode,
+        options: &JsonParseOptions,
+    ) -> Result<Self, DocParsingError> {
+        let json_obj: Map<String, serde_json::Value> =
+            serde_json::from_str(doc_json).map_err(|_| DocParsingError::invalid_json(doc_json))?;
+        Self::from_json_object_with_options(schema, json_obj, parse_mode, options)
+    }
+
+    /// Build a document object from a json-object, in which the nested objects are flattened
+    /// into dot-separated field names.
+    ///
+    /// `{"user": {"name": "Alice"}}` and `{"user.name": "Alice"}` both set the `user.name`
+    /// field. A dot escaped with a backslash in a key does not split it, but still matches a dot
+    /// of the field name. Arrays may appear at any level, so that
+    /// `{"user": [{"name": "Alice"}, {"name": "Bob"}]}` gives two values to `user.name`. An object
+    /// reaching a JSON field is the value of this field.
+    ///
+    /// The keys that match no field of the schema are ignored.
+    pub fn from_flattened_json(schema: &Schema, doc_json: &str) -> Result<Self, DocParsingError> {
         let json_obj: Map<String, serde_json::Value> =
             serde_json::from_str(doc_json).map_err(|_| DocParsingError::invalid_json(doc_json))?;
-        Self::from_json_object(schema, json_obj)
+        let mut flattened_obj = Map::new();
+        flatten_json_value(
+            schema,
+            &mut Vec::new(),
+            serde_json::Value::Object(json_obj),
+            &mut flattened_obj,
+        );
+        Self::from_json_object(schema, flattened_obj)
+    }
+
+    /// Returns an iterator over the documents of `reader`, which holds one JSON object per line.
+    ///
+    /// The keys that are not fields of the schema are ignored. See
+    /// [`JsonDocParser::parse_lines`].
+    pub fn parse_json_lines<R: BufRead>(schema: &Schema, reader: R) -> JsonLines<R> {
+        JsonDocParser::new(schema.clone()).parse_lines(reader)
     }
 
     /// Build a document object from a json-object.
+    ///
+    /// The keys that are not fields of the schema are ignored.
     pub fn from_json_object(
         schema: &Schema,
         json_obj: Map<String, serde_json::Value>,
+    ) -> Result<Self, DocParsingError> {
+        Self::from_json_object_with_mode(schema, json_obj, ParseMode::Lenient)
+    }
+
+    /// Build a document object from a json-object, handling the keys that are not fields of the
+    /// schema according to `parse_mode`.
+    pub fn from_json_object_with_mode(
+        schema: &Schema,
+        json_obj: Map<String, serde_json::Value>,
+        parse_mode: ParseMode,
+    ) -> Result<Self, DocParsingError> {
+        Self::from_json_object_with_options(
+            schema,
+            json_obj,
+            parse_mode,
+            &JsonParseOptions::default(),
+        )
+    }
+
+    /// Build a document object from a json-object, handling the keys that are not fields of the
+    /// schema according to `parse_mode`, and converting the values with `options`.
+    ///
+    /// The values which `options` turn into missing values, like an empty string for a numeric
+    /// field with [`JsonParseOptions::coerce_numbers`], are skipped. With the default options,
+    /// this is the same as [`CompactDoc::from_json_object_with_mode`].
+    pub fn from_json_object_with_options(
+        schema: &Schema,
+        json_obj: Map<String, serde_json::Value>,
+        parse_mode: ParseMode,
+        options: &JsonParseOptions,
     ) -> Result<Self, DocParsingError> {
         let mut doc = Self::default();
         for (field_name, json_value) in json_obj {
-            if let Ok(field) = schema.get_field(&field_name) {
+            if let Some(field) = parse_mode.resolve_field(schema, field_name.as_str())? {
                 let field_entry = schema.get_field_entry(field);
                 let field_type = field_entry.field_type();
-                match json_value {
+                let (json_items, is_array) = match json_value {
                     serde_json::Value::Array(json_items) => {
-                        for json_item in json_items {
-                            let value = field_type
-                                .value_from_json(json_item)
-                                .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
-                            doc.add_field_value(field, &value);
-                        }
+                        (Either::Left(json_items.into_iter()), true)
                     }
-                    _ => {
-                        let value = field_type
-                            .value_from_json(json_value)
-                            .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
+                    json_value => (Either::Right(std::iter::once(json_value)), false),
+                };
+                for (idx, json_item) in json_items.enumerate() {
+                    let value_opt = field_type
+                        .value_from_json_with_options(json_item, options)
+                        .map_err(|error| {
+                            let path = ValuePath::new(&field_name);
+                            if is_array {
+                                path.index(idx).value_error(error)
+                            } else {
+                                path.value_error(error)
+                            }
+                        })?;
+                    if let Some(value) = value_opt {
                         doc.add_field_value(field, &value);
                     }
                 }
@@ -232,15 +771,189 @@ impl CompactDoc {
         Ok(doc)
     }
 
+    /// Serializes the document, so that it can be read back with [`CompactDoc::from_bytes`].
+    ///
+    /// The format is a version byte, followed by the document serialized with
+    /// [`doc_binary_wrappers::serialize`].
+    pub fn serialize_into<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        COMPACT_DOC_FORMAT_VERSION.serialize(writer)?;
+        doc_binary_wrappers::serialize(self, writer)
+    }
+
+    /// Serializes the document into a new buffer. See [`CompactDoc::serialize_into`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.node_data.len() + 8 * self.field_values.len());
+        self.serialize_into(&mut buffer)
+            .expect("writing into a vec should not fail");
+        buffer
+    }
+
+    /// Deserializes a document serialized by [`CompactDoc::serialize_into`].
+    ///
+    /// The values are checked, so that invalid bytes return an error instead of panicking
+    /// when the document is read. The field ids are not checked against a schema.
+    pub fn from_bytes(mut bytes: &[u8]) -> io::Result<CompactDoc> {
+        let version = u8::deserialize(&mut bytes)?;
+        if !(2..=COMPACT_DOC_FORMAT_VERSION).contains(&version) {
+            return Err(invalid_data(format!(
+                "Unsupported compact document format version: {version}"
+            )));
+        }
+        let doc = if version < 5 {
+            let mut doc = CompactDoc::deserialize_with_layout(&mut bytes, false)?;
+            // The lists of nested values are rewritten with their number of entries.
+            doc.compact_node_data();
+            doc
+        } else {
+            doc_binary_wrappers::deserialize(&mut bytes)?
+        };
+        if !bytes.is_empty() {
+            return Err(invalid_data(format!(
+                "{} unexpected bytes after the document",
+                bytes.len()
+            )));
+        }
+        Ok(doc)
+    }
+
+    /// Checks that the value and its nested values can be read from `node_data`.
+    fn check_value(&self, value_addr: ValueAddr) -> io::Result<()> {
+        // The values are checked iteratively, as the nesting depth is not bounded.
+        let mut value_addrs = vec![value_addr];
+        while let Some(value_addr) = value_addrs.pop() {
+            let addr = value_addr.val_addr;
+            match value_addr.type_id {
+                ValueType::Null | ValueType::Bool => {}
+                ValueType::Str | ValueType::Facet => {
+                    std::str::from_utf8(self.checked_bytes(addr)?)
+                        .map_err(|_| invalid_data("Invalid utf-8 string".to_string()))?;
+                }
+                ValueType::Bytes => {
+                    self.checked_bytes(addr)?;
+                }
+                ValueType::PreTokStr => {
+                    // The json payload is read as a string, which trusts its length.
+                    self.checked_bytes(addr)?;
+                    self.read_from::<PreTokenizedString>(addr)?;
+                }
+                ValueType::U64 | ValueType::I64 | ValueType::F64 | ValueType::Date => {
+                    self.checked_slice(addr)?;
+                    self.read_from::<u64>(addr)?;
+                }
+                ValueType::IpAddr => {
+                    self.checked_slice(addr)?;
+                    self.read_from::<u128>(addr)?;
+                }
+                ValueType::Object
+                | ValueType::IndexedObject
+                | ValueType::Array
+                | ValueType::IndexedArray => {
+                    let is_object = value_addr.type_id.is_object();
+                    let (_, mut node_addresses_slice) =
+                        self.checked_nested_list(addr, is_object)?;
+                    let table = match value_addr.type_id {
+                        ValueType::IndexedArray => Some(array_skip_table(node_addresses_slice)),
+                        ValueType::IndexedObject => Some(self.object_key_index(addr)?),
+                        _ => None,
+                    };
+                    if let Some(table) = table {
+                        let table_start = self.nested_table_start(addr);
+                        if self.node_data.get(table_start..table_start + table.len())
+                            != Some(&table[..])
+                        {
+                            return Err(invalid_data(format!(
+                                "Invalid table for the value at address {addr}"
+                            )));
+                        }
+                    }
+                    let mut is_key = is_object;
+                    while !node_addresses_slice.is_empty() {
+                        let child_addr = ValueAddr::deserialize(&mut node_addresses_slice)?
+                            .resolve_relative_to(addr)
+                            .ok_or_else(|| {
+                                invalid_data(
+                                    "Nested value not written before its parent".to_string(),
+                                )
+                            })?;
+                        let child_type_id = child_addr.type_id;
+                        let child_val_addr = child_addr.val_addr;
+                        if is_key && child_type_id != ValueType::Str {
+                            return Err(invalid_data(format!(
+                                "Object key of type {child_type_id:?}"
+                            )));
+                        }
+                        // Nested values are written before their parent, which rules out
+                        // cycles.
+                        if (child_type_id.is_object() || child_type_id.is_array())
+                            && child_val_addr >= addr
+                        {
+                            return Err(invalid_data(
+                                "Nested value not written before its parent".to_string(),
+                            ));
+                        }
+                        value_addrs.push(child_addr);
+                        if is_object {
+                            is_key = !is_key;
+                        }
+                    }
+                    if is_key != is_object {
+                        return Err(invalid_data("Object key without value".to_string()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Bounds checked alternative to `get_slice`.
+    fn checked_slice(&self, addr: Addr) -> io::Result<&[u8]> {
+        self.node_data
+            .get(addr as usize..)
+            .ok_or_else(|| invalid_data(format!("Value address {addr} out of bounds")))
+    }
+
+    /// Bounds checked alternative to `extract_bytes`.
+    fn checked_bytes(&self, addr: Addr) -> io::Result<&[u8]> {
+        let mut data = self.checked_slice(addr)?;
+        let len = VInt::deserialize_u64(&mut data)?;
+        if len > data.len() as u64 {
+            return Err(invalid_data(format!(
+                "Value of {len} bytes at address {addr} out of bounds"
+            )));
+        }
+        Ok(&data[..len as usize])
+    }
+
+    /// Bounds checked alternative to `nested_list`, also checking the number of entries.
+    fn checked_nested_list(&self, list_addr: Addr, is_object: bool) -> io::Result<(usize, &[u8])> {
+        let mut len_opt = None;
+        let mut bytes_addr = list_addr;
+        if self.nested_list_lens {
+            let mut data = self.checked_slice(list_addr)?;
+            let data_len = data.len();
+            len_opt = Some(VInt::deserialize_u64(&mut data)?);
+            bytes_addr += (data_len - data.len()) as Addr;
+        }
+        let node_addresses_slice = self.checked_bytes(bytes_addr)?;
+        let addrs_per_entry = if is_object { 2 } else { 1 };
+        let num_entries = num_value_addrs(node_addresses_slice) / addrs_per_entry;
+        if let Some(len) = len_opt {
+            if len != num_entries as u64 {
+                return Err(invalid_data(format!(
+                    "Invalid number of entries {len} for the value at address {list_addr}"
+                )));
+            }
+        }
+        Ok((num_entries, node_addresses_slice))
+    }
+
     fn add_value_leaf(&mut self, leaf: ReferenceValueLeaf) -> ValueAddr {
         let type_id = ValueType::from(&leaf);
         // Write into `node_data` and return u32 position as its address
         // Null and bool are inlined into the address
         let val_addr = match leaf {
             ReferenceValueLeaf::Null => 0,
-            ReferenceValueLeaf::Str(bytes) => {
-                write_bytes_into(&mut self.node_data, bytes.as_bytes())
-            }
+            ReferenceValueLeaf::Str(text) => self.write_str(text),
             ReferenceValueLeaf::Facet(bytes) => {
                 write_bytes_into(&mut self.node_data, bytes.as_bytes())
             }
@@ -259,39 +972,132 @@ impl CompactDoc {
     }
     /// Adds a value and returns in address into the
     fn add_value<'a, V: Value<'a>>(&mut self, value: V) -> ValueAddr {
-        let value = value.as_value();
-        let type_id = ValueType::from(&value);
-        match value {
+        match value.as_value() {
             ReferenceValue::Leaf(leaf) => self.add_value_leaf(leaf),
-            ReferenceValue::Array(elements) => {
-                // addresses of the elements in node_data
-                // Reusing a vec would be nicer, but it's not easy because of the recursion
-                // A global vec would work if every writer get it's discriminator
-                let mut addresses = Vec::new();
-                for elem in elements {
-                    let value_addr = self.add_value(elem);
-                    write_into(&mut addresses, value_addr);
-                }
-                ValueAddr {
-                    type_id,
-                    val_addr: write_bytes_into(&mut self.node_data, &addresses),
-                }
-            }
-            ReferenceValue::Object(entries) => {
-                // addresses of the elements in node_data
-                let mut addresses = Vec::new();
-                for (key, value) in entries {
-                    let key_addr = self.add_value_leaf(ReferenceValueLeaf::Str(key));
-                    let value_addr = self.add_value(value);
-                    write_into(&mut addresses, key_addr);
-                    write_into(&mut addresses, value_addr);
-                }
-                ValueAddr {
-                    type_id,
-                    val_addr: write_bytes_into(&mut self.node_data, &addresses),
-                }
-            }
+            ReferenceValue::Array(elements) => self.add_array_value(elements),
+            ReferenceValue::Object(entries) => self.add_object_value(entries),
+        }
+    }
+
+    /// Adds an object and returns its address.
+    fn add_object_value<'a, I, V>(&mut self, entries: I) -> ValueAddr
+    where
+        I: IntoIterator<Item = (&'a str, V)>,
+        V: Value<'a>,
+    {
+        // addresses of the elements in node_data
+        let mut value_addrs = Vec::new();
+        for (key, value) in entries {
+            value_addrs.push(self.add_value_leaf(ReferenceValueLeaf::Str(key)));
+            value_addrs.push(self.add_value(value));
+        }
+        let list_addr = self.write_nested_value_addrs(&value_addrs, value_addrs.len() / 2);
+        if value_addrs.len() < 2 * OBJECT_KEY_INDEX_MIN_LEN {
+            return ValueAddr {
+                type_id: ValueType::Object,
+                val_addr: list_addr,
+            };
+        }
+        let key_index = self
+            .object_key_index(list_addr)
+            .expect("the object was just written");
+        self.node_data.extend_from_slice(&key_index);
+        ValueAddr {
+            type_id: ValueType::IndexedObject,
+            val_addr: list_addr,
+        }
+    }
+
+    /// Adds an array and returns its address.
+    fn add_array_value<'a, I>(&mut self, elements: I) -> ValueAddr
+    where
+        I: IntoIterator,
+        I::Item: Value<'a>,
+    {
+        // addresses of the elements in node_data
+        // Reusing a vec would be nicer, but it's not easy because of the recursion
+        // A global vec would work if every writer get it's discriminator
+        let mut value_addrs = Vec::new();
+        for elem in elements {
+            value_addrs.push(self.add_value(elem));
+        }
+        let list_addr = self.write_nested_value_addrs(&value_addrs, value_addrs.len());
+        if value_addrs.len() < ARRAY_SKIP_TABLE_MIN_LEN {
+            return ValueAddr {
+                type_id: ValueType::Array,
+                val_addr: list_addr,
+            };
+        }
+        let (_, node_addresses_slice) = self.nested_list(list_addr, false);
+        let skip_table = array_skip_table(node_addresses_slice);
+        self.node_data.extend_from_slice(&skip_table);
+        ValueAddr {
+            type_id: ValueType::IndexedArray,
+            val_addr: list_addr,
+        }
+    }
+
+    /// Writes a string into `node_data` and returns its address.
+    ///
+    /// If strings are interned, the address of an identical string already written is returned
+    /// instead.
+    fn write_str(&mut self, text: &str) -> Addr {
+        let Some(interned_strs) = &mut self.interned_strs else {
+            return write_bytes_into(&mut self.node_data, text.as_bytes());
+        };
+        if let Some(addr) = interned_strs.get(text) {
+            return *addr;
+        }
+        let addr = write_bytes_into(&mut self.node_data, text.as_bytes());
+        interned_strs.insert(text.to_string(), addr);
+        addr
+    }
+
+    /// Writes the addresses of the values nested in an array or an object, and returns the
+    /// address of the list.
+    ///
+    /// The list starts with `num_entries`, the number of elements of the array or of entries
+    /// of the object, as a vint. It is followed by the addresses, as bytes.
+    ///
+    /// The addresses are relative to the address of the list, so that `node_data` does not
+    /// depend on where it starts, and can be appended to the data of another document as is.
+    fn write_nested_value_addrs(&mut self, value_addrs: &[ValueAddr], num_entries: usize) -> Addr {
+        let list_addr = to_addr(self.node_data.len());
+        let mut addresses = Vec::with_capacity(value_addrs.len() * 2);
+        for value_addr in value_addrs {
+            write_into(&mut addresses, value_addr.relative_to(list_addr));
+        }
+        VInt(num_entries as u64)
+            .serialize(&mut self.node_data)
+            .expect("writing into a vec should not fail");
+        write_bytes_into(&mut self.node_data, &addresses);
+        list_addr
+    }
+
+    /// Returns the number of entries of the array or object whose list of addresses is at
+    /// `list_addr`, and the addresses, as bytes.
+    fn nested_list(&self, list_addr: Addr, is_object: bool) -> (usize, &[u8]) {
+        let bytes_addr = self.nested_list_bytes_addr(list_addr);
+        let node_addresses_slice = self.extract_bytes(bytes_addr);
+        if bytes_addr == list_addr {
+            let addrs_per_entry = if is_object { 2 } else { 1 };
+            return (
+                num_value_addrs(node_addresses_slice) / addrs_per_entry,
+                node_addresses_slice,
+            );
         }
+        let (num_entries, _) = read_u32_vint_no_advance(self.get_slice(list_addr));
+        (num_entries as usize, node_addresses_slice)
+    }
+
+    /// Returns the address of the addresses, as bytes, of the list at `list_addr`, i.e. the
+    /// address following its number of entries.
+    fn nested_list_bytes_addr(&self, list_addr: Addr) -> Addr {
+        if !self.nested_list_lens {
+            return list_addr;
+        }
+        let (_, bytes_read) = read_u32_vint_no_advance(self.get_slice(list_addr));
+        list_addr + bytes_read as Addr
     }
 
     /// Get CompactDocValue for address
@@ -321,47 +1127,301 @@ impl CompactDoc {
         T::deserialize(&mut cursor)
     }
 
+    /// Builds the key index of an object, given the address of its list of addresses.
+    ///
+    /// The key index is written right after the list of addresses. It is made of the number of
+    /// entries of the object, followed by the position in the list of the address of the key
+    /// of every entry, sorted by key, all as u32 in little endian. Entries with the same key
+    /// keep their order.
+    ///
+    /// The list is read with bounds checks, so that this can validate untrusted data.
+    fn object_key_index(&self, list_addr: Addr) -> io::Result<Vec<u8>> {
+        let (_, node_addresses_slice) = self.checked_nested_list(list_addr, true)?;
+        let mut entries: Vec<(&[u8], u32)> = Vec::new();
+        let mut offset = 0;
+        while offset < node_addresses_slice.len() {
+            let key_addr = ValueAddr::deserialize(&mut &node_addresses_slice[offset..])?
+                .resolve_relative_to(list_addr)
+                .filter(|key_addr| key_addr.type_id == ValueType::Str)
+                .ok_or_else(|| invalid_data(format!("Invalid object key at offset {offset}")))?;
+            entries.push((self.checked_bytes(key_addr.val_addr)?, offset as u32));
+            offset += skip_value_addrs(&node_addresses_slice[offset..], 2);
+        }
+        entries.sort_by_key(|(key, _)| *key);
+        let mut key_index = Vec::with_capacity(4 + 4 * entries.len());
+        key_index.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for (_, offset) in entries {
+            key_index.extend_from_slice(&offset.to_le_bytes());
+        }
+        Ok(key_index)
+    }
+
+    /// Returns the position of the table written after the list of addresses at `list_addr`,
+    /// i.e. the skip table of an indexed array or the key index of an indexed object.
+    fn nested_table_start(&self, list_addr: Addr) -> usize {
+        let bytes_addr = self.nested_list_bytes_addr(list_addr);
+        let (len, bytes_read) = read_u32_vint_no_advance(self.get_slice(bytes_addr));
+        bytes_addr as usize + bytes_read + len as usize
+    }
+
     /// get slice from address. The returned slice is open ended
     fn get_slice(&self, addr: Addr) -> &[u8] {
         &self.node_data[addr as usize..]
     }
 }
 
-/// BinarySerializable alternative to read references
-fn binary_deserialize_bytes(data: &[u8]) -> &[u8] {
-    let (len, bytes_read) = read_u32_vint_no_advance(data);
-    &data[bytes_read..bytes_read + len as usize]
+/// Version of the format written by [`CompactDoc::serialize_into`].
+///
+/// Version 2 stores the addresses of nested values relative to their parent.
+/// Version 3 adds skip tables to large arrays.
+/// Version 4 adds key indexes to large objects.
+/// Version 5 starts the lists of nested values with their number of entries.
+/// Documents of the previous versions are still valid, the documents of a version below 5 are
+/// rewritten when they are read.
+const COMPACT_DOC_FORMAT_VERSION: u8 = 5;
+
+/// Arrays with at least this number of elements are written with a skip table, allowing to
+/// access their elements by index.
+const ARRAY_SKIP_TABLE_MIN_LEN: usize = 64;
+
+/// Number of elements between two entries of the skip table of an array.
+const ARRAY_SKIP_INTERVAL: usize = 16;
+
+/// Objects with at least this number of entries are written with a key index, allowing to
+/// look their keys up with a binary search.
+const OBJECT_KEY_INDEX_MIN_LEN: usize = 32;
+
+/// Builds the skip table of an array, given its list of addresses.
+///
+/// The skip table is written right after the list of addresses. It is made of the number of
+/// elements of the array, followed by the position in the list of the address of every
+/// [`ARRAY_SKIP_INTERVAL`]-th element, all as u32 in little endian.
+fn array_skip_table(node_addresses_slice: &[u8]) -> Vec<u8> {
+    let num_elements = num_value_addrs(node_addresses_slice);
+    let mut skip_table = Vec::with_capacity(4 + 4 * num_elements.div_ceil(ARRAY_SKIP_INTERVAL));
+    skip_table.extend_from_slice(&(num_elements as u32).to_le_bytes());
+    let mut offset = 0;
+    for element_ord in 0..num_elements {
+        if element_ord % ARRAY_SKIP_INTERVAL == 0 {
+            skip_table.extend_from_slice(&(offset as u32).to_le_bytes());
+        }
+        offset += skip_value_addrs(&node_addresses_slice[offset..], 1);
+    }
+    skip_table
 }
 
-/// Write bytes and return the position of the written data.
+/// Moves `json_value`, found at `path` in the document, to the field of `schema` it belongs to
+/// in `flattened_obj`, which is keyed by field name.
+fn flatten_json_value(
+    schema: &Schema,
+    path: &mut Vec<String>,
+    json_value: serde_json::Value,
+    flattened_obj: &mut Map<String, serde_json::Value>,
+) {
+    if let Some(field) = schema.get_field_by_path(path) {
+        let field_type = schema.get_field_entry(field).field_type();
+        // Objects are flattened further, unless they are the value of a JSON field.
+        if !json_value.is_object() || matches!(field_type, FieldType::JsonObject(_)) {
+            let field_name = schema.get_field_name(field).to_string();
+            match flattened_obj.entry(field_name) {
+                serde_json::map::Entry::Vacant(entry) => {
+                    entry.insert(json_value);
+                }
+                serde_json::map::Entry::Occupied(mut entry) => {
+                    let values = entry.get_mut();
+                    if !values.is_array() {
+                        *values = serde_json::Value::Array(vec![values.take()]);
+                    }
+                    let serde_json::Value::Array(values) = values else {
+                        unreachable!()
+                    };
+                    match json_value {
+                        serde_json::Value::Array(elements) => values.extend(elements),
+                        json_value => values.push(json_value),
+                    }
+                }
+            }
+            return;
+        }
+    }
+    match json_value {
+        serde_json::Value::Object(json_obj) => {
+            for (key, value) in json_obj {
+                let mut segments = split_json_path(&key);
+                let first_segment = segments.remove(0);
+                // `{"a.b": value}` is handled as `{"a": {"b": value}}`, so that the value of a
+                // JSON field `a` can be set this way too.
+                let value = segments.into_iter().rev().fold(value, |value, segment| {
+                    serde_json::Value::Object(Map::from_iter([(segment, value)]))
+                });
+                path.push(first_segment);
+                flatten_json_value(schema, path, value, flattened_obj);
+                path.pop();
+            }
+        }
+        serde_json::Value::Array(elements) => {
+            for element in elements {
+                flatten_json_value(schema, path, element, flattened_obj);
+            }
+        }
+        // The value matches no field.
+        _ => {}
+    }
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// BinarySerializable alternative to read references
+fn binary_deserialize_bytes(data: &[u8]) -> &[u8] {
+    let (len, bytes_read) = read_u32_vint_no_advance(data);
+    &data[bytes_read..bytes_read + len as usize]
+}
+
+/// Write bytes and return the position of the written data.
 ///
 /// BinarySerializable alternative to write references
-fn write_bytes_into(vec: &mut Vec<u8>, data: &[u8]) -> u32 {
-    let pos = vec.len() as u32;
+fn write_bytes_into(vec: &mut Vec<u8>, data: &[u8]) -> Addr {
+    let pos = to_addr(vec.len());
+    let len: u32 = data
+        .len()
+        .try_into()
+        .expect("support only values of up to u32::MAX bytes");
     let mut buf = [0u8; 8];
-    let len_vint_bytes = serialize_vint_u32(data.len() as u32, &mut buf);
+    let len_vint_bytes = serialize_vint_u32(len, &mut buf);
     vec.extend_from_slice(len_vint_bytes);
     vec.extend_from_slice(data);
     pos
 }
 
 /// Serialize and return the position
-fn write_into<T: BinarySerializable>(vec: &mut Vec<u8>, value: T) -> u32 {
-    let pos = vec.len() as u32;
+fn write_into<T: BinarySerializable>(vec: &mut Vec<u8>, value: T) -> Addr {
+    let pos = to_addr(vec.len());
     value.serialize(vec).unwrap();
     pos
 }
 
-impl PartialEq for CompactDoc {
-    fn eq(&self, other: &Self) -> bool {
-        // super slow, but only here for tests
+/// Converts a position in `node_data` into an address.
+///
+/// Addresses are 32 bits, so the payload of a document can be up to 4 GB. Silently
+/// truncating the position would make the values point to the wrong data.
+fn to_addr(pos: usize) -> Addr {
+    pos.try_into()
+        .expect("support only documents with up to u32::MAX bytes of payload")
+}
+
+impl BinarySerializable for FieldValueAddr {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.field as u64).serialize(writer)?;
+        let value_addr = self.value_addr;
+        value_addr.serialize(writer)
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let field = VInt::deserialize_u64(reader)?
+            .try_into()
+            .map_err(|_| invalid_data("Field id larger than u32::MAX".to_string()))?;
+        let value_addr = ValueAddr::deserialize(reader)?;
+        Ok(FieldValueAddr { field, value_addr })
+    }
+}
+
+/// The serialized document starts with the number of (field, value address) pairs, followed by
+/// the pairs, the length of `node_data` and `node_data`.
+///
+/// Deserialization checks the values, so that invalid bytes return an error instead of
+/// panicking when the document is read.
+impl BinarySerializable for CompactDoc {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.field_values.len() as u64).serialize(writer)?;
+        for field_value in &self.field_values {
+            field_value.serialize(writer)?;
+        }
+        VInt(self.node_data.len() as u64).serialize(writer)?;
+        writer.write_all(&self.node_data)
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        CompactDoc::deserialize_with_layout(reader, true)
+    }
+}
+
+impl CompactDoc {
+    /// Deserializes a document, whose lists of nested values start with their number of
+    /// entries if `nested_list_lens` is true.
+    fn deserialize_with_layout<R: Read>(
+        reader: &mut R,
+        nested_list_lens: bool,
+    ) -> io::Result<CompactDoc> {
+        let num_field_values = VInt::deserialize_u64(reader)? as usize;
+        // The capacity is bounded, as the length may be invalid.
+        let mut field_values = Vec::with_capacity(num_field_values.min(1_024));
+        for _ in 0..num_field_values {
+            field_values.push(FieldValueAddr::deserialize(reader)?);
+        }
+        let node_data_len = VInt::deserialize_u64(reader)?;
+        let mut node_data = Vec::new();
+        reader
+            .by_ref()
+            .take(node_data_len)
+            .read_to_end(&mut node_data)?;
+        if node_data.len() as u64 != node_data_len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Expected {node_data_len} bytes of document data, got {}",
+                    node_data.len()
+                ),
+            ));
+        }
+        let doc = CompactDoc {
+            node_data,
+            field_values,
+            interned_strs: None,
+            nested_list_lens,
+        };
+        for field_value in &doc.field_values {
+            doc.check_value(field_value.value_addr)?;
+        }
+        Ok(doc)
+    }
+}
+
+/// Binary serialization of documents through any `Read`/`Write`.
+pub mod doc_binary_wrappers {
+    use std::io::{self, Read, Write};
+
+    use common::BinarySerializable;
+
+    /// Serializes the document into the writer.
+    pub fn serialize<D: BinarySerializable, W: Write + ?Sized>(
+        doc: &D,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        doc.serialize(writer)
+    }
+
+    /// Deserializes a document serialized by [`serialize`] from the reader.
+    pub fn deserialize<D: BinarySerializable, R: Read>(reader: &mut R) -> io::Result<D> {
+        D::deserialize(reader)
+    }
+}
+
+impl CompactDoc {
+    /// Returns true if both documents hold the same values for each field, regardless of the
+    /// order of the fields and of the values of a field.
+    ///
+    /// Values occurring several times in a field are counted once. Unlike `==`, which compares
+    /// the values in order, this converts every value to a JSON string, and is slow.
+    pub fn eq_unordered(&self, other: &Self) -> bool {
         let convert_to_comparable_map = |doc: &CompactDoc| {
             let mut field_value_set: HashMap<Field, HashSet<String>> = Default::default();
             for field_value in doc.field_values.iter() {
                 let value: OwnedValue = doc.get_compact_doc_value(field_value.value_addr).into();
                 let value = serde_json::to_string(&value).unwrap();
                 field_value_set
-                    .entry(Field::from_field_id(field_value.field as u32))
+                    .entry(Field::from_field_id(field_value.field))
                     .or_default()
                     .insert(value);
             }
@@ -373,8 +1433,125 @@ impl PartialEq for CompactDoc {
     }
 }
 
+/// Two documents are equal if they hold the same (field, value) pairs, in the same order.
+///
+/// The values are compared by walking both documents, so that documents built differently,
+/// e.g. with [`CompactDoc::add_u64`] and by parsing JSON, are equal if they hold the same
+/// values. Integers are compared by value, so that a `u64` read back as an `i64` from JSON is
+/// equal to it. Floats are compared by their bits, so that `NaN` is equal to itself.
+///
+/// See [`CompactDoc::eq_unordered`] to ignore the order of the values.
+impl PartialEq for CompactDoc {
+    fn eq(&self, other: &Self) -> bool {
+        if self.field_values.len() != other.field_values.len() {
+            return false;
+        }
+        for (field_value, other_field_value) in self.field_values.iter().zip(&other.field_values) {
+            if field_value.field != other_field_value.field
+                || !compact_doc_value_eq(
+                    self.get_compact_doc_value(field_value.value_addr),
+                    other.get_compact_doc_value(other_field_value.value_addr),
+                )
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 impl Eq for CompactDoc {}
 
+impl Hash for CompactDoc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.field_values.len());
+        for field_value in &self.field_values {
+            state.write_u32(field_value.field);
+            hash_compact_doc_value(self.get_compact_doc_value(field_value.value_addr), state);
+        }
+    }
+}
+
+fn leaf_eq(left: &ReferenceValueLeaf, right: &ReferenceValueLeaf) -> bool {
+    match (left, right) {
+        (ReferenceValueLeaf::F64(left), ReferenceValueLeaf::F64(right)) => {
+            left.to_bits() == right.to_bits()
+        }
+        (ReferenceValueLeaf::U64(left), ReferenceValueLeaf::I64(right))
+        | (ReferenceValueLeaf::I64(right), ReferenceValueLeaf::U64(left)) => {
+            i64::try_from(*left) == Ok(*right)
+        }
+        _ => left == right,
+    }
+}
+
+fn compact_doc_value_eq(left: CompactDocValue, right: CompactDocValue) -> bool {
+    match (left.as_value(), right.as_value()) {
+        (ReferenceValue::Leaf(left), ReferenceValue::Leaf(right)) => leaf_eq(&left, &right),
+        (ReferenceValue::Array(mut left), ReferenceValue::Array(mut right)) => loop {
+            match (left.next(), right.next()) {
+                (None, None) => return true,
+                (Some(left), Some(right)) if compact_doc_value_eq(left, right) => {}
+                _ => return false,
+            }
+        },
+        (ReferenceValue::Object(mut left), ReferenceValue::Object(mut right)) => loop {
+            match (left.next(), right.next()) {
+                (None, None) => return true,
+                (Some((left_key, left)), Some((right_key, right)))
+                    if left_key == right_key && compact_doc_value_eq(left, right) => {}
+                _ => return false,
+            }
+        },
+        _ => false,
+    }
+}
+
+fn hash_compact_doc_value<H: Hasher>(value: CompactDocValue, state: &mut H) {
+    match value.as_value() {
+        ReferenceValue::Leaf(leaf) => {
+            // Integers are equal across `U64` and `I64`, so they are hashed alike.
+            match leaf {
+                ReferenceValueLeaf::U64(_) | ReferenceValueLeaf::I64(_) => state.write_u8(0xFD),
+                _ => mem::discriminant(&leaf).hash(state),
+            }
+            match leaf {
+                ReferenceValueLeaf::Null => {}
+                ReferenceValueLeaf::Str(text) => text.hash(state),
+                ReferenceValueLeaf::U64(val) => i128::from(val).hash(state),
+                ReferenceValueLeaf::I64(val) => i128::from(val).hash(state),
+                ReferenceValueLeaf::F64(val) => val.to_bits().hash(state),
+                ReferenceValueLeaf::Date(val) => val.hash(state),
+                ReferenceValueLeaf::Facet(facet) => facet.hash(state),
+                ReferenceValueLeaf::Bytes(bytes) => bytes.hash(state),
+                ReferenceValueLeaf::IpAddr(val) => val.hash(state),
+                ReferenceValueLeaf::Bool(val) => val.hash(state),
+                ReferenceValueLeaf::PreTokStr(pre_tokenized) => pre_tokenized.hash(state),
+            }
+        }
+        ReferenceValue::Array(elements) => {
+            // The number of elements is hashed last, so that it is counted on the way.
+            state.write_u8(0xFE);
+            let mut num_elements = 0usize;
+            for element in elements {
+                hash_compact_doc_value(element, state);
+                num_elements += 1;
+            }
+            state.write_usize(num_elements);
+        }
+        ReferenceValue::Object(entries) => {
+            state.write_u8(0xFF);
+            let mut num_entries = 0usize;
+            for (key, value) in entries {
+                key.hash(state);
+                hash_compact_doc_value(value, state);
+                num_entries += 1;
+            }
+            state.write_usize(num_entries);
+        }
+    }
+}
+
 impl DocumentDeserialize for CompactDoc {
     fn deserialize<'de, D>(mut deserializer: D) -> Result<Self, DeserializeError>
     where D: DocumentDeserializer<'de> {
@@ -406,6 +1583,62 @@ impl From<CompactDocValue<'_>> for OwnedValue {
         value.as_value().into()
     }
 }
+
+/// Converts a document into an object, mapping the ids of its fields, as strings, to the arrays
+/// of their values.
+///
+/// The fields are sorted by the string of their id, and the values of a field keep their order.
+impl From<CompactDoc> for OwnedValue {
+    fn from(doc: CompactDoc) -> Self {
+        let mut object: BTreeMap<String, Vec<OwnedValue>> = BTreeMap::new();
+        for (field, value) in doc.field_values() {
+            object
+                .entry(field.field_id().to_string())
+                .or_default()
+                .push(value.into());
+        }
+        object
+            .into_iter()
+            .map(|(field_id, values)| (field_id, OwnedValue::Array(values)))
+            .collect::<BTreeMap<_, _>>()
+            .into()
+    }
+}
+
+/// Converts an object shaped like the conversion of a document into [`OwnedValue`] back into a
+/// document.
+///
+/// The keys must be field ids. The arrays hold the values of the fields, and any other value is
+/// a single value. The value is given back as an error if it is not an object, or if one of its
+/// keys is not a field id.
+impl TryFrom<OwnedValue> for CompactDoc {
+    type Error = OwnedValue;
+
+    fn try_from(value: OwnedValue) -> Result<Self, OwnedValue> {
+        let OwnedValue::Object(entries) = &value else {
+            return Err(value);
+        };
+        if entries.iter().any(|(key, _)| key.parse::<u32>().is_err()) {
+            return Err(value);
+        }
+        let OwnedValue::Object(entries) = value else {
+            unreachable!()
+        };
+        let mut doc = CompactDoc::new();
+        for (key, value) in entries {
+            let field = Field::from_field_id(key.parse().expect("the keys were checked"));
+            match value {
+                OwnedValue::Array(elements) => {
+                    for element in &elements {
+                        doc.add_field_value(field, element);
+                    }
+                }
+                value => doc.add_field_value(field, &value),
+            }
+        }
+        Ok(doc)
+    }
+}
 impl<'a> Value<'a> for CompactDocValue<'a> {
     type ArrayIter = CompactDocArrayIter<'a>;
 
@@ -464,19 +1697,176 @@ impl<'a> CompactDocValue<'a> {
                 .map(Into::into)
                 .map(ReferenceValueLeaf::PreTokStr)
                 .map(Into::into),
-            ValueType::Object => Ok(ReferenceValue::Object(CompactDocObjectIter::new(
-                self.container,
-                addr,
-            )?)),
-            ValueType::Array => Ok(ReferenceValue::Array(CompactDocArrayIter::new(
-                self.container,
-                addr,
-            )?)),
+            ValueType::Object | ValueType::IndexedObject => Ok(ReferenceValue::Object(
+                CompactDocObjectIter::new(self.container, addr)?,
+            )),
+            ValueType::Array | ValueType::IndexedArray => Ok(ReferenceValue::Array(
+                CompactDocArrayIter::new(self.container, addr)?,
+            )),
+        }
+    }
+
+    /// Returns an accessor to the elements of the value by index, if it is an array.
+    ///
+    /// Large arrays are written with a skip table, so that accessing one of their elements
+    /// does not require to read all of the elements before it.
+    pub fn as_array_accessor(&self) -> Option<CompactDocArrayAccessor<'a>> {
+        let list_addr = self.value_addr.val_addr;
+        let skip_table = match self.value_addr.type_id {
+            ValueType::Array => None,
+            ValueType::IndexedArray => {
+                Some(&self.container.node_data[self.container.nested_table_start(list_addr)..])
+            }
+            _ => return None,
+        };
+        let (len, node_addresses_slice) = self.container.nested_list(list_addr, false);
+        Some(CompactDocArrayAccessor {
+            container: self.container,
+            list_addr,
+            node_addresses_slice,
+            skip_table,
+            len,
+        })
+    }
+
+    /// Returns the value of `key`, if the value is an object with this key.
+    ///
+    /// If the key appears several times, its first value is returned. Large objects are
+    /// written with a key index, so that their keys are found with a binary search instead of
+    /// a scan of their entries.
+    pub fn get_key(&self, key: &str) -> Option<CompactDocValue<'a>> {
+        let list_addr = self.value_addr.val_addr;
+        match self.value_addr.type_id {
+            ValueType::Object => CompactDocObjectIter::new(self.container, list_addr)
+                .ok()?
+                .find(|(entry_key, _)| *entry_key == key)
+                .map(|(_, value)| value),
+            ValueType::IndexedObject => {
+                let container = self.container;
+                let (_, node_addresses_slice) = container.nested_list(list_addr, true);
+                let key_index = &container.node_data[container.nested_table_start(list_addr)..];
+                let num_entries = read_u32_le(key_index, 0) as usize;
+                let read_value_addr = |offset: usize| {
+                    ValueAddr::deserialize(&mut &node_addresses_slice[offset..])
+                        .ok()?
+                        .resolve_relative_to(list_addr)
+                };
+                let entry_key = |entry_ord: usize| {
+                    let offset = read_u32_le(key_index, 4 + 4 * entry_ord) as usize;
+                    let key_addr = read_value_addr(offset)?;
+                    Some((container.extract_str(key_addr.val_addr), offset))
+                };
+                // Finds the first entry whose key is not lower than `key`.
+                let (mut low, mut high) = (0, num_entries);
+                while low < high {
+                    let mid = low + (high - low) / 2;
+                    if entry_key(mid)?.0 < key {
+                        low = mid + 1;
+                    } else {
+                        high = mid;
+                    }
+                }
+                if low == num_entries {
+                    return None;
+                }
+                let (found_key, offset) = entry_key(low)?;
+                if found_key != key {
+                    return None;
+                }
+                let value_offset = offset + skip_value_addrs(&node_addresses_slice[offset..], 1);
+                Some(CompactDocValue {
+                    container,
+                    value_addr: read_value_addr(value_offset)?,
+                })
+            }
+            _ => None,
         }
     }
+
+    /// Returns the value at `path`, descending through the nested objects and arrays.
+    ///
+    /// Each segment of the path is a key of an object, or the index of an element of an array.
+    /// Segments are matched as is: a key containing a dot is a single segment. An empty path
+    /// returns the value itself.
+    pub fn get_path(&self, path: &[&str]) -> Option<CompactDocValue<'a>> {
+        let mut value = *self;
+        for segment in path {
+            value = if value.value_addr.type_id.is_array() {
+                let index: usize = segment.parse().ok()?;
+                value.as_array_accessor()?.get(index)?
+            } else {
+                value.get_key(segment)?
+            };
+        }
+        Some(value)
+    }
+}
+
+/// Random access to the elements of an array of a [`CompactDoc`].
+///
+/// See [`CompactDocValue::as_array_accessor`].
+#[derive(Debug, Clone, Copy)]
+pub struct CompactDocArrayAccessor<'a> {
+    container: &'a CompactDoc,
+    list_addr: Addr,
+    node_addresses_slice: &'a [u8],
+    skip_table: Option<&'a [u8]>,
+    len: usize,
+}
+
+impl<'a> CompactDocArrayAccessor<'a> {
+    /// Returns the number of elements of the array.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<CompactDocValue<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        let (start, num_skipped) = match self.skip_table {
+            Some(skip_table) => {
+                let entry_ord = index / ARRAY_SKIP_INTERVAL;
+                let start = read_u32_le(skip_table, 4 + 4 * entry_ord) as usize;
+                (start, entry_ord * ARRAY_SKIP_INTERVAL)
+            }
+            None => (0, 0),
+        };
+        let mut addresses = &self.node_addresses_slice[start..];
+        addresses = &addresses[skip_value_addrs(addresses, index - num_skipped)..];
+        let value_addr = ValueAddr::deserialize(&mut addresses)
+            .ok()?
+            .resolve_relative_to(self.list_addr)?;
+        Some(CompactDocValue {
+            container: self.container,
+            value_addr,
+        })
+    }
+
+    /// Returns an iterator over the elements of the array.
+    pub fn iter(&self) -> CompactDocArrayIter<'a> {
+        CompactDocArrayIter {
+            container: self.container,
+            list_addr: self.list_addr,
+            node_addresses_slice: self.node_addresses_slice,
+            len: self.len,
+        }
+    }
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
 }
 
-/// The address in the vec
+/// The address in the vec.
+///
+/// It is 32 bits, which allows documents with up to 4 GB of payload.
 type Addr = u32;
 
 #[derive(Clone, Copy, Default)]
@@ -487,6 +1877,38 @@ struct ValueAddr {
     /// This is the address to the value in the vec, except for bool and null, which are inlined
     val_addr: Addr,
 }
+impl ValueAddr {
+    /// Null and bool values are inlined in the address, and have no payload in `node_data`.
+    fn has_payload(&self) -> bool {
+        !matches!(self.type_id, ValueType::Null | ValueType::Bool)
+    }
+
+    /// Converts the address of a value nested in an array or an object into an address
+    /// relative to the list of addresses of its parent, written after it.
+    fn relative_to(self, list_addr: Addr) -> ValueAddr {
+        if !self.has_payload() {
+            return self;
+        }
+        ValueAddr {
+            type_id: self.type_id,
+            val_addr: list_addr - self.val_addr,
+        }
+    }
+
+    /// Inverse of [`ValueAddr::relative_to`].
+    ///
+    /// Returns `None` if the address does not point before the list.
+    fn resolve_relative_to(self, list_addr: Addr) -> Option<ValueAddr> {
+        if !self.has_payload() {
+            return Some(self);
+        }
+        Some(ValueAddr {
+            type_id: self.type_id,
+            val_addr: list_addr.checked_sub(self.val_addr)?,
+        })
+    }
+}
+
 impl BinarySerializable for ValueAddr {
     fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         self.type_id.serialize(writer)?;
@@ -540,8 +1962,22 @@ pub enum ValueType {
     PreTokStr = 10,
     /// Object
     Object = 11,
-    /// Pre-tokenized str type,
+    /// Array
     Array = 12,
+    /// Array followed by a skip table, see [`array_skip_table`].
+    IndexedArray = 13,
+    /// Object followed by a key index, see [`CompactDoc::object_key_index`].
+    IndexedObject = 14,
+}
+
+impl ValueType {
+    fn is_object(self) -> bool {
+        matches!(self, ValueType::Object | ValueType::IndexedObject)
+    }
+
+    fn is_array(self) -> bool {
+        matches!(self, ValueType::Array | ValueType::IndexedArray)
+    }
 }
 
 impl BinarySerializable for ValueType {
@@ -552,7 +1988,7 @@ impl BinarySerializable for ValueType {
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
         let num = u8::deserialize(reader)?;
-        let type_id = if (0..=12).contains(&num) {
+        let type_id = if (0..=14).contains(&num) {
             unsafe { std::mem::transmute::<u8, ValueType>(num) }
         } else {
             return Err(io::Error::new(
@@ -595,18 +2031,35 @@ impl<'a> From<&ReferenceValueLeaf<'a>> for ValueType {
 /// The Iterator for the object values in the compact document
 pub struct CompactDocObjectIter<'a> {
     container: &'a CompactDoc,
+    /// The nested addresses are relative to the address of the list.
+    list_addr: Addr,
     node_addresses_slice: &'a [u8],
+    /// Number of entries left.
+    len: usize,
 }
 
 impl<'a> CompactDocObjectIter<'a> {
     fn new(container: &'a CompactDoc, addr: Addr) -> io::Result<Self> {
-        // Objects are `&[ValueAddr]` serialized into bytes
-        let node_addresses_slice = container.extract_bytes(addr);
+        // Objects are `&[ValueAddr]` serialized into bytes, each entry being a key followed by
+        // a value.
+        let (len, node_addresses_slice) = container.nested_list(addr, true);
         Ok(Self {
             container,
+            list_addr: addr,
             node_addresses_slice,
+            len,
         })
     }
+
+    /// Returns the number of entries left in the object.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no entries left in the object.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 impl<'a> Iterator for CompactDocObjectIter<'a> {
@@ -616,33 +2069,60 @@ impl<'a> Iterator for CompactDocObjectIter<'a> {
         if self.node_addresses_slice.is_empty() {
             return None;
         }
-        let key_addr = ValueAddr::deserialize(&mut self.node_addresses_slice).ok()?;
+        let key_addr = ValueAddr::deserialize(&mut self.node_addresses_slice)
+            .ok()?
+            .resolve_relative_to(self.list_addr)?;
         let key = self.container.extract_str(key_addr.val_addr);
-        let value = ValueAddr::deserialize(&mut self.node_addresses_slice).ok()?;
+        let value = ValueAddr::deserialize(&mut self.node_addresses_slice)
+            .ok()?
+            .resolve_relative_to(self.list_addr)?;
         let value = CompactDocValue {
             container: self.container,
             value_addr: value,
         };
+        self.len -= 1;
         Some((key, value))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl ExactSizeIterator for CompactDocObjectIter<'_> {}
+
 #[derive(Debug, Clone)]
 /// The Iterator for the array values in the compact document
 pub struct CompactDocArrayIter<'a> {
     container: &'a CompactDoc,
+    /// The nested addresses are relative to the address of the list.
+    list_addr: Addr,
     node_addresses_slice: &'a [u8],
+    /// Number of elements left.
+    len: usize,
 }
 
 impl<'a> CompactDocArrayIter<'a> {
     fn new(container: &'a CompactDoc, addr: Addr) -> io::Result<Self> {
         // Arrays are &[ValueAddr] serialized into bytes
-        let node_addresses_slice = container.extract_bytes(addr);
+        let (len, node_addresses_slice) = container.nested_list(addr, false);
         Ok(Self {
             container,
+            list_addr: addr,
             node_addresses_slice,
+            len,
         })
     }
+
+    /// Returns the number of elements left in the array.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no elements left in the array.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 impl<'a> Iterator for CompactDocArrayIter<'a> {
@@ -652,13 +2132,50 @@ impl<'a> Iterator for CompactDocArrayIter<'a> {
         if self.node_addresses_slice.is_empty() {
             return None;
         }
-        let value = ValueAddr::deserialize(&mut self.node_addresses_slice).ok()?;
+        let value = ValueAddr::deserialize(&mut self.node_addresses_slice)
+            .ok()?
+            .resolve_relative_to(self.list_addr)?;
         let value = CompactDocValue {
             container: self.container,
             value_addr: value,
         };
+        self.len -= 1;
         Some(value)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl ExactSizeIterator for CompactDocArrayIter<'_> {}
+
+/// Returns the number of [`ValueAddr`] serialized in `node_addresses_slice`.
+///
+/// A [`ValueAddr`] is its type id, which is below 128, followed by a vint, whose last byte is
+/// the only one with the stop bit set. Counting the bytes with the stop bit set is enough.
+fn num_value_addrs(node_addresses_slice: &[u8]) -> usize {
+    node_addresses_slice
+        .iter()
+        .filter(|&&byte| byte >= 128)
+        .count()
+}
+
+/// Returns the number of bytes of the first `num_value_addrs` [`ValueAddr`] serialized in
+/// `node_addresses_slice`.
+///
+/// Relies on the same property as [`num_value_addrs`].
+fn skip_value_addrs(node_addresses_slice: &[u8], num_value_addrs: usize) -> usize {
+    if num_value_addrs == 0 {
+        return 0;
+    }
+    node_addresses_slice
+        .iter()
+        .enumerate()
+        .filter(|(_, &byte)| byte >= 128)
+        .nth(num_value_addrs - 1)
+        .map(|(pos, _)| pos + 1)
+        .unwrap_or(node_addresses_slice.len())
 }
 
 impl Document for CompactDoc {
@@ -685,7 +2202,7 @@ impl<'a> Iterator for FieldValueIterRef<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.slice.next().map(|field_value| {
             (
-                Field::from_field_id(field_value.field as u32),
+                Field::from_field_id(field_value.field),
                 CompactDocValue::<'a> {
                     container: self.container,
                     value_addr: field_value.value_addr,
@@ -695,6 +2212,33 @@ impl<'a> Iterator for FieldValueIterRef<'a> {
     }
 }
 
+/// Defines how the keys that are not fields of the schema are handled when parsing a document.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// The keys that are not fields of the schema are ignored.
+    #[default]
+    Lenient,
+    /// The keys that are not fields of the schema are rejected with
+    /// [`DocParsingError::NoSuchFieldInSchema`].
+    Strict,
+}
+
+impl ParseMode {
+    pub(crate) fn resolve_field(
+        self,
+        schema: &Schema,
+        field_name: &str,
+    ) -> Result<Option<Field>, DocParsingError> {
+        match (schema.get_field(field_name), self) {
+            (Ok(field), _) => Ok(Some(field)),
+            (Err(_), ParseMode::Lenient) => Ok(None),
+            (Err(_), ParseMode::Strict) => {
+                Err(DocParsingError::NoSuchFieldInSchema(field_name.to_string()))
+            }
+        }
+    }
+}
+
 /// Error that may happen when deserializing
 /// a document from JSON.
 #[derive(Debug, Error, PartialEq)]
@@ -705,19 +2249,106 @@ pub enum DocParsingError {
     /// One of the value node could not be parsed.
     #[error("The field '{0:?}' could not be parsed: {1:?}")]
     ValueError(String, ValueParsingError),
+    /// The document has a field that is not in the schema, in strict parsing mode.
+    #[error("The field '{0:?}' does not exist in the schema")]
+    NoSuchFieldInSchema(String),
+    /// A line of JSON lines could not be parsed, see [`JsonDocParser::parse_lines`].
+    #[error("Line {0}: {1}")]
+    Line(usize, Box<DocParsingError>),
+    /// The JSON lines could not be read.
+    #[error("Failed to read the JSON lines: {0}")]
+    ReadError(String),
+    /// A value nested in an array of a field could not be parsed.
+    #[error("The value at '{path}' could not be parsed: {value}")]
+    NestedValueError {
+        /// Path of the value in the document, like `tags[3]`.
+        ///
+        /// The path starts with the name of the field, in which the dots and backslashes are
+        /// escaped with a backslash.
+        path: String,
+        /// The value, rendered as JSON and truncated if it is too long.
+        value: String,
+        /// Why the value could not be parsed.
+        #[source]
+        error: ValueParsingError,
+    },
 }
 
 impl DocParsingError {
     /// Builds a NotJson DocParsingError
-    fn invalid_json(invalid_json: &str) -> Self {
+    pub(crate) fn invalid_json(invalid_json: &str) -> Self {
         let sample = invalid_json.chars().take(20).collect();
         DocParsingError::InvalidJson(sample)
     }
 }
 
+/// Maximum number of characters of the values rendered in
+/// [`DocParsingError::NestedValueError`].
+const MAX_RENDERED_VALUE_CHARS: usize = 100;
+
+/// Path of a value in a document being parsed, used to report where the invalid values are.
+#[derive(Clone, Debug)]
+pub(crate) struct ValuePath<'a> {
+    field_name: &'a str,
+    /// The array indexes below the field, like `[3][0]`.
+    nested_path: String,
+}
+
+impl<'a> ValuePath<'a> {
+    /// Path of the values of a field.
+    pub(crate) fn new(field_name: &'a str) -> ValuePath<'a> {
+        ValuePath {
+            field_name,
+            nested_path: String::new(),
+        }
+    }
+
+    /// Path of the element `idx` of the array at this path.
+    pub(crate) fn index(&self, idx: usize) -> ValuePath<'a> {
+        ValuePath {
+            field_name: self.field_name,
+            nested_path: format!("{}[{idx}]", self.nested_path),
+        }
+    }
+
+    /// Builds the error of the invalid value at this path.
+    ///
+    /// The values of the field itself are reported with [`DocParsingError::ValueError`], the
+    /// values nested in arrays with [`DocParsingError::NestedValueError`].
+    pub(crate) fn value_error(&self, error: ValueParsingError) -> DocParsingError {
+        if self.nested_path.is_empty() {
+            return DocParsingError::ValueError(self.field_name.to_string(), error);
+        }
+        let mut path = String::with_capacity(self.field_name.len() + self.nested_path.len());
+        for ch in self.field_name.chars() {
+            if ch == '.' || ch == '\\' {
+                path.push('\\');
+            }
+            path.push(ch);
+        }
+        path.push_str(&self.nested_path);
+        DocParsingError::NestedValueError {
+            path,
+            value: error.render_value(MAX_RENDERED_VALUE_CHARS),
+            error,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::hash_map::RandomState;
+    use std::collections::{BTreeMap, HashSet};
+    use std::hash::BuildHasher;
+    use std::net::Ipv6Addr;
+
+    use columnar::MonotonicallyMappableToU128;
+    use common::DateTime;
+    use proptest::prelude::*;
+
+    use super::{doc_binary_wrappers, CompactDocValue, ReferenceValue, ValueType};
     use crate::schema::*;
+    use crate::tokenizer::PreTokenizedString;
 
     #[test]
     fn test_doc() {
@@ -732,6 +2363,1136 @@ mod tests {
         let _json = doc.to_named_doc(&schema);
     }
 
+    #[test]
+    fn test_typed_getters() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let delta = schema_builder.add_i64_field("delta", INDEXED);
+        let score = schema_builder.add_f64_field("score", INDEXED);
+        let date = schema_builder.add_date_field("date", INDEXED);
+        let bytes = schema_builder.add_bytes_field("bytes", STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let missing = schema_builder.add_text_field("missing", TEXT);
+        let mut doc = TantivyDocument::default();
+        doc.add_text(title, "first title");
+        doc.add_u64(count, 3);
+        doc.add_text(title, "second title");
+        doc.add_i64(delta, -2);
+        doc.add_f64(score, 0.5);
+        doc.add_date(date, DateTime::from_timestamp_secs(1_000));
+        doc.add_bytes(bytes, b"abc");
+        doc.add_ip_addr(ip, Ipv6Addr::LOCALHOST);
+        doc.add_object(
+            attributes,
+            BTreeMap::from([("name".to_string(), OwnedValue::Str("nested".to_string()))]),
+        );
+        doc.add_field_value(missing, &OwnedValue::Null);
+        doc.add_text(missing, "after null");
+
+        assert_eq!(doc.get_first_str(title), Some("first title"));
+        assert_eq!(
+            doc.get_all_strs(title).collect::<Vec<_>>(),
+            vec!["first title", "second title"]
+        );
+        assert_eq!(doc.get_first_u64(count), Some(3));
+        assert_eq!(doc.get_first_i64(count), Some(3));
+        assert_eq!(doc.get_first_f64(count), Some(3.0));
+        assert_eq!(doc.get_first_i64(delta), Some(-2));
+        assert_eq!(doc.get_first_u64(delta), None);
+        assert_eq!(doc.get_first_f64(score), Some(0.5));
+        assert_eq!(doc.get_first_u64(score), None);
+        assert_eq!(
+            doc.get_first_date(date),
+            Some(DateTime::from_timestamp_secs(1_000))
+        );
+        assert_eq!(doc.get_first_bytes(bytes), Some(&b"abc"[..]));
+        assert_eq!(doc.get_first_ip_addr(ip), Some(Ipv6Addr::LOCALHOST));
+
+        // Type mismatches.
+        assert_eq!(doc.get_first_str(count), None);
+        assert_eq!(doc.get_first_date(title), None);
+        assert_eq!(doc.get_first_bytes(title), None);
+        assert_eq!(doc.get_first_ip_addr(count), None);
+        // The values nested in a JSON object are not returned.
+        assert_eq!(doc.get_first_str(attributes), None);
+        assert_eq!(doc.get_all_strs(attributes).count(), 0);
+        // The first value is null.
+        assert_eq!(doc.get_first_str(missing), None);
+        assert_eq!(
+            doc.get_all_strs(missing).collect::<Vec<_>>(),
+            vec!["after null"]
+        );
+        let empty_doc = TantivyDocument::default();
+        assert_eq!(empty_doc.get_first_str(title), None);
+        assert_eq!(empty_doc.get_first_u64(count), None);
+    }
+
+    #[test]
+    fn test_get_all_typed() {
+        let mut schema_builder = Schema::builder();
+        let count = schema_builder.add_u64_field("count", STORED);
+        let title = schema_builder.add_text_field("title", STORED);
+        let mixed = schema_builder.add_json_field("mixed", STORED);
+        let mut doc = TantivyDocument::default();
+        doc.add_u64(count, 3);
+        doc.add_u64(count, 5);
+        doc.add_text(title, "first title");
+        doc.add_text(title, "second title");
+        doc.add_field_value(mixed, &OwnedValue::U64(1));
+        doc.add_field_value(mixed, &OwnedValue::I64(-2));
+        doc.add_field_value(mixed, &OwnedValue::Str("text".to_string()));
+        doc.add_field_value(mixed, &OwnedValue::F64(0.5));
+        doc.add_field_value(mixed, &OwnedValue::Array(vec![OwnedValue::U64(4)]));
+
+        assert_eq!(
+            doc.get_all_typed::<u64>(count).collect::<Vec<_>>(),
+            vec![3, 5]
+        );
+        assert_eq!(
+            doc.get_all_typed::<f64>(count).collect::<Vec<_>>(),
+            vec![3.0, 5.0]
+        );
+        assert_eq!(doc.get_all_typed::<String>(count).count(), 0);
+        assert_eq!(
+            doc.get_all_typed::<String>(title).collect::<Vec<_>>(),
+            vec!["first title".to_string(), "second title".to_string()]
+        );
+        assert_eq!(doc.get_all_typed::<u64>(title).count(), 0);
+
+        // Mixed types: the values of other types are skipped, including nested values.
+        assert_eq!(doc.get_all_typed::<u64>(mixed).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(
+            doc.get_all_typed::<i64>(mixed).collect::<Vec<_>>(),
+            vec![1, -2]
+        );
+        assert_eq!(
+            doc.get_all_typed::<f64>(mixed).collect::<Vec<_>>(),
+            vec![1.0, -2.0, 0.5]
+        );
+        assert_eq!(
+            doc.get_all_typed::<String>(mixed).collect::<Vec<_>>(),
+            vec!["text".to_string()]
+        );
+        assert_eq!(
+            doc.get_all_typed_strict::<u64>(mixed).collect::<Vec<_>>(),
+            vec![
+                Ok(1),
+                Err(OwnedValue::I64(-2)),
+                Err(OwnedValue::Str("text".to_string())),
+                Err(OwnedValue::F64(0.5)),
+                Err(OwnedValue::Array(vec![OwnedValue::U64(4)])),
+            ]
+        );
+        assert_eq!(
+            doc.get_all_typed_strict::<u64>(count).collect::<Vec<_>>(),
+            vec![Ok(3), Ok(5)]
+        );
+    }
+
+    #[test]
+    fn test_remove_field() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+        let mut doc = TantivyDocument::default();
+        doc.add_text(title, "first title");
+        doc.add_text(body, "a rather long body, long enough to be noticed");
+        doc.add_text(title, "second title");
+        doc.add_u64(count, 3);
+
+        assert_eq!(doc.remove_field(body), 1);
+        assert_eq!(doc.remove_field(body), 0);
+        assert_eq!(doc.len(), 3);
+        assert!(doc.get_first(body).is_none());
+        let titles: Vec<&str> = doc
+            .get_all(title)
+            .flat_map(|value| value.as_str())
+            .collect();
+        assert_eq!(titles, ["first title", "second title"]);
+
+        let num_bytes = doc.node_data.len();
+        doc.compact_node_data();
+        assert!(doc.node_data.len() < num_bytes);
+        assert_eq!(doc.get_first(count).unwrap().as_u64(), Some(3));
+        let titles: Vec<&str> = doc
+            .get_all(title)
+            .flat_map(|value| value.as_str())
+            .collect();
+        assert_eq!(titles, ["first title", "second title"]);
+
+        assert_eq!(doc.remove_field(title), 2);
+        doc.add_text(title, "new title");
+        doc.add_text(body, "new body");
+        doc.compact_node_data();
+        assert_eq!(
+            doc.to_json(&schema),
+            r#"{"body":["new body"],"count":[3],"title":["new title"]}"#
+        );
+    }
+
+    #[test]
+    fn test_retain_fields_and_set_field_value() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+        let mut doc = TantivyDocument::default();
+        doc.add_text(title, "first title");
+        doc.add_text(body, "a rather long body, long enough to be noticed");
+        doc.add_text(title, "second title");
+        doc.add_u64(count, 3);
+        let num_bytes = doc.to_bytes().len();
+
+        assert_eq!(doc.retain_fields(|field| field != body), 1);
+        assert_eq!(doc.retain_fields(|_| true), 0);
+        assert_eq!(doc.len(), 3);
+        assert!(doc.get_first(body).is_none());
+
+        doc.set_field_value(title, &OwnedValue::from("new title"));
+        doc.set_field_value(count, &OwnedValue::U64(4));
+        assert_eq!(doc.len(), 2);
+        let titles: Vec<&str> = doc
+            .get_all(title)
+            .flat_map(|value| value.as_str())
+            .collect();
+        assert_eq!(titles, ["new title"]);
+        assert_eq!(doc.get_first(count).unwrap().as_u64(), Some(4));
+        // The data of the removed values is only dropped by the compaction.
+        assert!(doc.to_bytes().len() > num_bytes);
+
+        doc.compact_node_data();
+        assert!(doc.to_bytes().len() < num_bytes);
+        assert_eq!(
+            doc.to_json(&schema),
+            r#"{"count":[4],"title":["new title"]}"#
+        );
+        assert_eq!(doc.retain_fields(|_| false), 2);
+        assert_eq!(doc.len(), 0);
+    }
+
+    #[test]
+    fn test_field_ids_above_u16_max() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        for field_ord in 0..u16::MAX as usize {
+            schema_builder.add_u64_field(&format!("padding{field_ord}"), NumericOptions::default());
+        }
+        let first = schema_builder.add_text_field("first", TEXT | STORED);
+        let count = schema_builder.add_u64_field("count", INDEXED | STORED);
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let schema = schema_builder.build();
+        assert!(title.field_id() > u16::MAX as u32);
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(title, "first title");
+        doc.add_u64(count, 3);
+        doc.add_text(title, "second title");
+        doc.add_text(first, "first");
+        let check_doc = |doc: &TantivyDocument| {
+            let titles: Vec<&str> = doc
+                .get_all(title)
+                .flat_map(|value| value.as_str())
+                .collect();
+            assert_eq!(titles, ["first title", "second title"]);
+            let fields: Vec<Field> = doc
+                .iter_fields_and_values()
+                .map(|(field, _)| field)
+                .collect();
+            assert_eq!(fields, [title, count, title, first]);
+            assert_eq!(doc.get_first(count).unwrap().as_u64(), Some(3));
+        };
+        check_doc(&doc);
+
+        let index = crate::Index::create_in_ram(schema);
+        let mut index_writer: crate::IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stored_doc: TantivyDocument = searcher.doc(crate::DocAddress::new(0, 0))?;
+        check_doc(&stored_doc);
+        let title_term = crate::Term::from_field_text(title, "second");
+        assert_eq!(searcher.doc_freq(&title_term)?, 1);
+        Ok(())
+    }
+
+    fn doc_with_all_value_types() -> (Schema, TantivyDocument) {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let unsigned = schema_builder.add_u64_field("unsigned", STORED);
+        let signed = schema_builder.add_i64_field("signed", STORED);
+        let float = schema_builder.add_f64_field("float", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        let facet = schema_builder.add_facet_field("facet", FacetOptions::default());
+        let bytes = schema_builder.add_bytes_field("bytes", STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", STORED);
+        let boolean = schema_builder.add_bool_field("bool", STORED);
+        let json = schema_builder.add_json_field("json", STORED);
+        let schema = schema_builder.build();
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(text, "first text");
+        doc.add_pre_tokenized_text(
+            text,
+            PreTokenizedString {
+                text: "pre tokenized".to_string(),
+                tokens: vec![crate::tokenizer::Token::new(0, 3, 0, "pre".to_string())],
+            },
+        );
+        doc.add_text(text, "last text");
+        doc.add_u64(unsigned, u64::MAX);
+        doc.add_i64(signed, -2);
+        doc.add_f64(float, -0.5);
+        doc.add_date(date, DateTime::from_timestamp_nanos(1_234_567_890));
+        doc.add_facet(facet, "/a/b");
+        doc.add_bytes(bytes, b"\x00\xffbytes");
+        doc.add_ip_addr(ip, "2001:db8::1".parse::<Ipv6Addr>().unwrap());
+        doc.add_ip_addr(ip, Ipv6Addr::from_u128(u128::MAX));
+        doc.add_bool(boolean, true);
+        doc.add_bool(boolean, false);
+        let json_obj: BTreeMap<String, OwnedValue> = serde_json::from_str(
+            r#"{
+                "null": null,
+                "pages": [{"id": 1, "tags": ["a", "b"]}, {"id": 2, "empty": {}}, []],
+                "nested": {"float": 1.5, "signed": -3, "bool": true}
+            }"#,
+        )
+        .unwrap();
+        doc.add_object(json, json_obj);
+        (schema, doc)
+    }
+
+    fn owned_field_values(doc: &TantivyDocument) -> Vec<(Field, OwnedValue)> {
+        doc.field_values()
+            .map(|(field, value)| (field, OwnedValue::from(value)))
+            .collect()
+    }
+
+    fn leaf_value_strategy() -> impl Strategy<Value = OwnedValue> {
+        prop_oneof![
+            Just(OwnedValue::Null),
+            ".*".prop_map(OwnedValue::Str),
+            any::<u64>().prop_map(OwnedValue::U64),
+            any::<i64>().prop_map(OwnedValue::I64),
+            (-1e9f64..1e9).prop_map(OwnedValue::F64),
+            any::<bool>().prop_map(OwnedValue::Bool),
+            any::<i64>().prop_map(|ts| OwnedValue::Date(DateTime::from_timestamp_nanos(ts))),
+            "(/[a-z]{1,3}){1,3}"
+                .prop_map(|path| OwnedValue::Facet(Facet::from_text(&path).unwrap())),
+            prop::collection::vec(any::<u8>(), 0..16).prop_map(OwnedValue::Bytes),
+            any::<u128>().prop_map(|num| OwnedValue::IpAddr(Ipv6Addr::from_u128(num))),
+            "[a-z ]{0,10}".prop_map(|text| OwnedValue::PreTokStr(PreTokenizedString {
+                text,
+                tokens: Vec::new(),
+            })),
+        ]
+    }
+
+    fn value_strategy() -> impl Strategy<Value = OwnedValue> {
+        leaf_value_strategy().prop_recursive(4, 32, 4, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(OwnedValue::Array),
+                prop::collection::vec(("[a-z.]{0,4}", inner), 0..4).prop_map(OwnedValue::Object),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_doc_binary_wrappers_round_trip(
+            field_values in prop::collection::vec((0u32..4, value_strategy()), 0..8)
+        ) {
+            let mut doc = TantivyDocument::default();
+            for (field_id, value) in &field_values {
+                doc.add_field_value(Field::from_field_id(*field_id), value);
+            }
+            let mut payload = Vec::new();
+            doc_binary_wrappers::serialize(&doc, &mut payload).unwrap();
+            let deserialized_doc: TantivyDocument =
+                doc_binary_wrappers::deserialize(&mut &payload[..]).unwrap();
+            let expected_field_values: Vec<(Field, OwnedValue)> = field_values
+                .into_iter()
+                .map(|(field_id, value)| (Field::from_field_id(field_id), value))
+                .collect();
+            prop_assert_eq!(owned_field_values(&deserialized_doc), expected_field_values);
+        }
+    }
+
+    #[test]
+    fn test_doc_binary_wrappers_out_of_bounds_address() {
+        let mut doc = TantivyDocument::default();
+        doc.add_text(Field::from_field_id(0), "hello");
+        let mut payload = Vec::new();
+        doc_binary_wrappers::serialize(&doc, &mut payload).unwrap();
+        // num field values, field id, value type, value address, node data len, node data
+        assert_eq!(&payload[..5], &[129, 128, 1, 128, 134]);
+        payload[3] = 129;
+        assert!(doc_binary_wrappers::deserialize::<TantivyDocument, _>(&mut &payload[..]).is_err());
+        payload[3] = 128;
+        payload[4] = 135;
+        assert!(doc_binary_wrappers::deserialize::<TantivyDocument, _>(&mut &payload[..]).is_err());
+    }
+
+    #[test]
+    fn test_compact_doc_bytes_round_trip() {
+        let (schema, doc) = doc_with_all_value_types();
+        let bytes = doc.to_bytes();
+        assert_eq!(bytes[0], 5);
+        let mut serialized = Vec::new();
+        doc.serialize_into(&mut serialized).unwrap();
+        assert_eq!(serialized, bytes);
+
+        let deserialized_doc = TantivyDocument::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            owned_field_values(&deserialized_doc),
+            owned_field_values(&doc)
+        );
+        assert_eq!(deserialized_doc.to_json(&schema), doc.to_json(&schema));
+        assert_eq!(deserialized_doc.to_bytes(), bytes);
+
+        let empty_doc = TantivyDocument::from_bytes(&TantivyDocument::new().to_bytes()).unwrap();
+        assert_eq!(empty_doc.len(), 0);
+    }
+
+    #[test]
+    fn test_compact_doc_from_invalid_bytes() {
+        let (_schema, doc) = doc_with_all_value_types();
+        let bytes = doc.to_bytes();
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[0] = 1;
+        assert!(TantivyDocument::from_bytes(&wrong_version).is_err());
+        let mut trailing_bytes = bytes.clone();
+        trailing_bytes.push(0);
+        assert!(TantivyDocument::from_bytes(&trailing_bytes).is_err());
+        for len in 0..bytes.len() {
+            assert!(TantivyDocument::from_bytes(&bytes[..len]).is_err());
+        }
+        // Corrupted bytes are either rejected or give a document which can be read.
+        for pos in 0..bytes.len() {
+            for corrupted_byte in [0u8, 1, 11, 12, 127, 128, 255] {
+                let mut corrupted_bytes = bytes.clone();
+                corrupted_bytes[pos] = corrupted_byte;
+                if let Ok(corrupted_doc) = TantivyDocument::from_bytes(&corrupted_bytes) {
+                    owned_field_values(&corrupted_doc);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let (schema, doc) = doc_with_all_value_types();
+        let text = schema.get_field("text").unwrap();
+        let json = schema.get_field("json").unwrap();
+        let mut other_doc = TantivyDocument::default();
+        other_doc.add_text(text, "other text");
+        other_doc.add_object(
+            json,
+            serde_json::from_str(r#"{"arr": [null, true, {"nested": ["a", 1]}], "b": false}"#)
+                .unwrap(),
+        );
+        other_doc.add_text(text, "last other text");
+
+        let mut merged_doc = doc.clone();
+        merged_doc.merge(&other_doc);
+        assert_eq!(
+            merged_doc.node_data.len(),
+            doc.node_data.len() + other_doc.node_data.len()
+        );
+        let mut expected_field_values = owned_field_values(&doc);
+        expected_field_values.extend(owned_field_values(&other_doc));
+        assert_eq!(owned_field_values(&merged_doc), expected_field_values);
+        let deserialized_doc = TantivyDocument::from_bytes(&merged_doc.to_bytes()).unwrap();
+        assert_eq!(owned_field_values(&deserialized_doc), expected_field_values);
+
+        let mut empty_doc = TantivyDocument::default();
+        empty_doc.merge(&other_doc);
+        assert_eq!(
+            owned_field_values(&empty_doc),
+            owned_field_values(&other_doc)
+        );
+        let mut other_doc_merged = other_doc.clone();
+        other_doc_merged.merge(&TantivyDocument::default());
+        assert_eq!(
+            owned_field_values(&other_doc_merged),
+            owned_field_values(&other_doc)
+        );
+    }
+
+    #[test]
+    fn test_string_interning() {
+        let mut schema_builder = Schema::builder();
+        let json = schema_builder.add_json_field("json", STORED);
+        let text = schema_builder.add_text_field("text", STORED);
+        let entries: Vec<serde_json::Value> = (0..1_000)
+            .map(|i| {
+                let value = if i % 2 == 0 {
+                    "enum_value_b"
+                } else {
+                    "enum_value_c"
+                };
+                serde_json::json!({"attribute_type": "enum_value_a", "attribute_value": value})
+            })
+            .collect();
+        let object: BTreeMap<String, OwnedValue> =
+            serde_json::from_value(serde_json::json!({ "entries": entries })).unwrap();
+        let build_doc = |mut doc: TantivyDocument| {
+            doc.add_object(json, object.clone());
+            doc.add_text(text, "enum_value_a");
+            doc.add_text(text, "other");
+            doc
+        };
+        let doc = build_doc(TantivyDocument::default());
+        let interned_doc = build_doc(TantivyDocument::with_interning());
+        assert!(interned_doc.node_data.len() * 3 < doc.node_data.len());
+        assert_eq!(owned_field_values(&interned_doc), owned_field_values(&doc));
+        assert_eq!(
+            interned_doc.get_all_strs(text).collect::<Vec<_>>(),
+            vec!["enum_value_a", "other"]
+        );
+
+        let deserialized_doc = TantivyDocument::from_bytes(&interned_doc.to_bytes()).unwrap();
+        assert_eq!(
+            owned_field_values(&deserialized_doc),
+            owned_field_values(&doc)
+        );
+
+        // Compacting the document keeps interning the strings.
+        let mut compacted_doc = interned_doc.clone();
+        compacted_doc.remove_field(text);
+        compacted_doc.compact_node_data();
+        compacted_doc.add_text(text, "enum_value_a");
+        assert!(compacted_doc.node_data.len() < interned_doc.node_data.len());
+        assert_eq!(
+            owned_field_values(&compacted_doc),
+            owned_field_values(&doc)[..2]
+        );
+    }
+
+    #[test]
+    fn test_nested_iter_len() {
+        fn check_len(value: CompactDocValue) -> usize {
+            match value.as_value() {
+                ReferenceValue::Leaf(_) => 1,
+                ReferenceValue::Array(mut elements) => {
+                    let len = elements.len();
+                    assert_eq!(elements.size_hint(), (len, Some(len)));
+                    assert_eq!(elements.is_empty(), len == 0);
+                    let mut num_elements = 0;
+                    while let Some(element) = elements.next() {
+                        num_elements += 1;
+                        assert_eq!(elements.len(), len - num_elements);
+                        check_len(element);
+                    }
+                    assert_eq!(num_elements, len);
+                    assert!(elements.is_empty());
+                    len
+                }
+                ReferenceValue::Object(mut entries) => {
+                    let len = entries.len();
+                    assert_eq!(entries.size_hint(), (len, Some(len)));
+                    assert_eq!(entries.is_empty(), len == 0);
+                    let mut num_entries = 0;
+                    while let Some((_, value)) = entries.next() {
+                        num_entries += 1;
+                        assert_eq!(entries.len(), len - num_entries);
+                        check_len(value);
+                    }
+                    assert_eq!(num_entries, len);
+                    assert!(entries.is_empty());
+                    len
+                }
+            }
+        }
+
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STORED);
+        let long_array: Vec<serde_json::Value> = (0..300u64)
+            .map(|i| serde_json::json!(["some text", i, i % 2 == 0, null]))
+            .collect();
+        let json_val: serde_json::Value = serde_json::json!({
+            "empty_object": {},
+            "empty_array": [],
+            "nested": {"a": [[], [1, 2], {}], "b": {"c": [{"d": true}]}},
+            "long_array": long_array,
+        });
+        let mut doc = TantivyDocument::default();
+        doc.add_field_value(json_field, &OwnedValue::from(json_val));
+        doc.add_field_value(json_field, &OwnedValue::Array(Vec::new()));
+
+        let values: Vec<CompactDocValue> = doc.get_all(json_field).collect();
+        assert_eq!(check_len(values[0]), 4);
+        assert_eq!(check_len(values[1]), 0);
+        let ReferenceValue::Object(entries) = values[0].as_value() else {
+            panic!("expected an object");
+        };
+        let (_, long_array) = entries
+            .into_iter()
+            .find(|(key, _)| *key == "long_array")
+            .unwrap();
+        assert_eq!(check_len(long_array), 300);
+    }
+
+    #[test]
+    fn test_array_accessor() {
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STORED);
+        let text_field = schema_builder.add_text_field("text", STORED);
+        let element = |i: usize| match i % 3 {
+            0 => OwnedValue::U64(i as u64),
+            1 => OwnedValue::Str(format!("element {i}")),
+            _ => OwnedValue::Object(vec![("id".to_string(), OwnedValue::I64(-(i as i64)))]),
+        };
+        let mut doc = TantivyDocument::default();
+        let lens = [0, 1, 63, 64, 65, 1_000];
+        for len in lens {
+            doc.add_field_value(
+                json_field,
+                &OwnedValue::Array((0..len).map(element).collect()),
+            );
+        }
+        doc.add_text(text_field, "not an array");
+        assert!(doc
+            .get_first(text_field)
+            .unwrap()
+            .as_array_accessor()
+            .is_none());
+
+        let check_arrays = |doc: &TantivyDocument| {
+            for (value, &len) in doc.get_all(json_field).zip(lens.iter()) {
+                let accessor = value.as_array_accessor().unwrap();
+                assert_eq!(accessor.len(), len);
+                assert_eq!(accessor.is_empty(), len == 0);
+                assert_eq!(accessor.iter().len(), len);
+                for i in 0..len {
+                    assert_eq!(OwnedValue::from(accessor.get(i).unwrap()), element(i));
+                }
+                assert!(accessor.get(len).is_none());
+                assert_eq!(
+                    accessor.iter().map(OwnedValue::from).collect::<Vec<_>>(),
+                    (0..len).map(element).collect::<Vec<_>>()
+                );
+            }
+        };
+        check_arrays(&doc);
+        check_arrays(&TantivyDocument::from_bytes(&doc.to_bytes()).unwrap());
+        let mut merged_doc = TantivyDocument::default();
+        merged_doc.add_text(text_field, "first");
+        merged_doc.merge(&doc);
+        check_arrays(&merged_doc);
+        doc.remove_field(text_field);
+        doc.compact_node_data();
+        check_arrays(&doc);
+
+        // Corrupted bytes are either rejected or give a document which can be read.
+        let mut doc = TantivyDocument::default();
+        doc.add_field_value(
+            json_field,
+            &OwnedValue::Array((0..70).map(element).collect()),
+        );
+        let bytes = doc.to_bytes();
+        for pos in 0..bytes.len() {
+            for corrupted_byte in [0u8, 1, 12, 13, 127, 128, 255] {
+                let mut corrupted_bytes = bytes.clone();
+                corrupted_bytes[pos] = corrupted_byte;
+                if let Ok(corrupted_doc) = TantivyDocument::from_bytes(&corrupted_bytes) {
+                    owned_field_values(&corrupted_doc);
+                    for (_, value) in corrupted_doc.field_values() {
+                        if let Some(accessor) = value.as_array_accessor() {
+                            for i in 0..accessor.len() {
+                                accessor.get(i).map(OwnedValue::from);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_object_key_lookup() {
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STORED);
+        let text_field = schema_builder.add_text_field("text", STORED);
+        let key = |i: usize| format!("key{}", (i * 7) % 40);
+        let large_object = OwnedValue::Object(
+            (0..50)
+                .map(|i| (key(i), OwnedValue::U64(i as u64)))
+                .collect(),
+        );
+        let object = OwnedValue::Object(vec![
+            (
+                "user".to_string(),
+                OwnedValue::Object(vec![("id".to_string(), OwnedValue::U64(1))]),
+            ),
+            ("user".to_string(), OwnedValue::U64(2)),
+            ("a.b".to_string(), OwnedValue::Str("dotted".to_string())),
+            (
+                "list".to_string(),
+                OwnedValue::Array(vec![OwnedValue::Null, large_object]),
+            ),
+        ]);
+        let mut doc = TantivyDocument::default();
+        doc.add_text(text_field, "not an object");
+        doc.add_field_value(json_field, &OwnedValue::Str("not an object".to_string()));
+        doc.add_field_value(json_field, &object);
+
+        let check_lookups = |doc: &TantivyDocument| {
+            let get_path = |path: &[&str]| doc.get_path(json_field, path).map(OwnedValue::from);
+            assert_eq!(get_path(&["user", "id"]), Some(OwnedValue::U64(1)));
+            assert_eq!(
+                get_path(&["a.b"]),
+                Some(OwnedValue::Str("dotted".to_string()))
+            );
+            assert_eq!(get_path(&["a", "b"]), None);
+            assert_eq!(get_path(&["list", "0"]), Some(OwnedValue::Null));
+            assert_eq!(get_path(&["list", "2"]), None);
+            assert_eq!(get_path(&["list", "first"]), None);
+            assert_eq!(get_path(&["user", "id", "other"]), None);
+            assert_eq!(
+                get_path(&[]),
+                Some(OwnedValue::Str("not an object".to_string()))
+            );
+            assert!(doc.get_path(text_field, &["user"]).is_none());
+
+            let large_object = doc.get_path(json_field, &["list", "1"]).unwrap();
+            assert_eq!(large_object.value_addr.type_id, ValueType::IndexedObject);
+            for i in 0..40 {
+                // Keys appear several times: the first value is returned.
+                let first = (0..50).find(|&j| key(j) == key(i)).unwrap();
+                assert_eq!(
+                    large_object.get_key(&key(i)).map(OwnedValue::from),
+                    Some(OwnedValue::U64(first as u64))
+                );
+            }
+            assert!(large_object.get_key("key").is_none());
+            assert!(large_object.get_key("key99").is_none());
+            assert!(large_object.get_key("").is_none());
+            // The entries keep their order.
+            assert_eq!(
+                large_object
+                    .as_object()
+                    .unwrap()
+                    .map(|(key, _)| key.to_string())
+                    .collect::<Vec<_>>(),
+                (0..50).map(key).collect::<Vec<_>>()
+            );
+        };
+        check_lookups(&doc);
+        check_lookups(&TantivyDocument::from_bytes(&doc.to_bytes()).unwrap());
+        let mut merged_doc = TantivyDocument::default();
+        merged_doc.add_text(text_field, "first");
+        merged_doc.merge(&doc);
+        check_lookups(&merged_doc);
+
+        // Corrupted bytes are either rejected or give a document which can be read.
+        let mut doc = TantivyDocument::default();
+        doc.add_field_value(
+            json_field,
+            &OwnedValue::Object(
+                (0..33)
+                    .map(|i| (key(i), OwnedValue::Bool(i % 2 == 0)))
+                    .collect(),
+            ),
+        );
+        let bytes = doc.to_bytes();
+        for pos in 0..bytes.len() {
+            for corrupted_byte in [0u8, 1, 11, 14, 127, 128, 255] {
+                let mut corrupted_bytes = bytes.clone();
+                corrupted_bytes[pos] = corrupted_byte;
+                if let Ok(corrupted_doc) = TantivyDocument::from_bytes(&corrupted_bytes) {
+                    owned_field_values(&corrupted_doc);
+                    for (_, value) in corrupted_doc.field_values() {
+                        for i in 0..40 {
+                            value.get_key(&key(i)).map(OwnedValue::from);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_doc_owned_value_round_trip() {
+        let (_schema, doc) = doc_with_all_value_types();
+        let value = OwnedValue::from(doc.clone());
+        let OwnedValue::Object(entries) = &value else {
+            panic!("expected an object, got {value:?}");
+        };
+        assert!(entries
+            .iter()
+            .all(|(_, values)| matches!(values, OwnedValue::Array(_))));
+        let round_tripped_doc = TantivyDocument::try_from(value.clone()).unwrap();
+        let mut expected_field_values = owned_field_values(&doc);
+        expected_field_values.sort_by_key(|(field, _)| field.field_id().to_string());
+        assert_eq!(owned_field_values(&round_tripped_doc), expected_field_values);
+        assert_eq!(OwnedValue::from(round_tripped_doc), value);
+
+        // The document can be embedded as a nested value.
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STORED);
+        let mut event = TantivyDocument::default();
+        event.add_field_value(json_field, &value);
+        let embedded = OwnedValue::from(event.get_first(json_field).unwrap());
+        assert_eq!(
+            owned_field_values(&TantivyDocument::try_from(embedded).unwrap()),
+            expected_field_values
+        );
+
+        assert_eq!(
+            TantivyDocument::try_from(OwnedValue::Object(Vec::new()))
+                .unwrap()
+                .len(),
+            0
+        );
+        let single_value = OwnedValue::Object(vec![("3".to_string(), OwnedValue::U64(7))]);
+        assert_eq!(
+            owned_field_values(&TantivyDocument::try_from(single_value).unwrap()),
+            vec![(Field::from_field_id(3), OwnedValue::U64(7))]
+        );
+        for invalid_value in [
+            OwnedValue::U64(1),
+            OwnedValue::Array(Vec::new()),
+            OwnedValue::Object(vec![("title".to_string(), OwnedValue::Null)]),
+            OwnedValue::Object(vec![("-1".to_string(), OwnedValue::Null)]),
+        ] {
+            assert_eq!(
+                TantivyDocument::try_from(invalid_value.clone()),
+                Err(invalid_value)
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_doc_format_versions_below_5() {
+        // The array `[7, "a"]` in the field 0 and the object `{"k": true}` in the field 1, with
+        // lists of nested values which do not start with their number of entries: 7 is at 0,
+        // "a" at 8, the list of the array at 10, "k" at 15 and the list of the object at 17.
+        let field_values: [u8; 8] = [0x82, 0x80, 12, 0x8A, 0x81, 11, 0x91, 0x96];
+        let node_data: [u8; 22] = [
+            7, 0, 0, 0, 0, 0, 0, 0, 0x81, b'a', 0x84, 2, 0x8A, 1, 0x82, 0x81, b'k', 0x84, 1, 0x82,
+            9, 0x81,
+        ];
+        let mut expected_doc = TantivyDocument::new();
+        expected_doc.add_field_value(
+            Field::from_field_id(0),
+            &OwnedValue::Array(vec![OwnedValue::U64(7), OwnedValue::Str("a".to_string())]),
+        );
+        expected_doc.add_field_value(
+            Field::from_field_id(1),
+            &OwnedValue::Object(vec![("k".to_string(), OwnedValue::Bool(true))]),
+        );
+        for version in 2..5 {
+            let bytes = [&[version][..], &field_values, &node_data].concat();
+            let doc = TantivyDocument::from_bytes(&bytes).unwrap();
+            assert_eq!(doc, expected_doc);
+            let ReferenceValue::Array(elements) =
+                doc.get_first(Field::from_field_id(0)).unwrap().as_value()
+            else {
+                panic!("expected an array");
+            };
+            assert_eq!(elements.len(), 2);
+            // The document is rewritten in the current format.
+            assert_eq!(doc.to_bytes(), expected_doc.to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_compact_doc_invalid_nested_list_len() {
+        let mut doc = TantivyDocument::new();
+        doc.add_field_value(
+            Field::from_field_id(0),
+            &OwnedValue::Array(vec![OwnedValue::Bool(true), OwnedValue::Bool(false)]),
+        );
+        // The list is the whole `node_data`: its number of entries, then its addresses.
+        assert_eq!(doc.node_data, [0x82, 0x84, 9, 0x81, 9, 0x80]);
+        let mut bytes = doc.to_bytes();
+        assert!(TantivyDocument::from_bytes(&bytes).is_ok());
+        let num_entries_pos = bytes.len() - doc.node_data.len();
+        bytes[num_entries_pos] = 0x83;
+        assert!(TantivyDocument::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = TantivyDocument::new();
+        let empty_doc_usage = doc.estimated_memory_usage();
+        assert_eq!(empty_doc_usage, std::mem::size_of_val(&doc));
+        assert_eq!(
+            doc.allocated_memory_usage(),
+            std::mem::size_of_val(&doc) + 1024 + 4 * std::mem::size_of::<super::FieldValueAddr>()
+        );
+        for i in 0..10 {
+            doc.add_text(title, format!("title {i}"));
+            doc.add_u64(count, i);
+        }
+        assert_eq!(
+            doc.estimated_memory_usage(),
+            std::mem::size_of_val(&doc)
+                + std::mem::size_of_val(doc.node_data.as_slice())
+                + std::mem::size_of_val(doc.field_values.as_slice())
+        );
+        assert!(doc.allocated_memory_usage() > doc.estimated_memory_usage());
+        doc.shrink_to_fit();
+        assert_eq!(doc.allocated_memory_usage(), doc.estimated_memory_usage());
+
+        let mut interned_doc = TantivyDocument::with_interning();
+        interned_doc.add_text(title, "repeated");
+        interned_doc.add_text(title, "repeated");
+        let interned_doc_usage = interned_doc.estimated_memory_usage();
+        assert_eq!(
+            interned_doc_usage,
+            std::mem::size_of_val(&interned_doc)
+                + std::mem::size_of_val(interned_doc.node_data.as_slice())
+                + std::mem::size_of_val(interned_doc.field_values.as_slice())
+                + "repeated".len()
+                + std::mem::size_of::<(String, super::Addr)>()
+        );
+        assert!(interned_doc.allocated_memory_usage() >= interned_doc_usage);
+    }
+
+    #[test]
+    fn test_drain_field() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let mut doc = TantivyDocument::new();
+        doc.add_text(title, "first");
+        doc.add_u64(count, 1);
+        doc.add_field_value(
+            title,
+            &OwnedValue::Array(vec![OwnedValue::Str("second".to_string())]),
+        );
+        doc.add_u64(count, 2);
+
+        let payload_len = doc.payload_len();
+        let drained: Vec<OwnedValue> = doc.drain_field(title).collect();
+        assert_eq!(
+            drained,
+            vec![
+                OwnedValue::Str("first".to_string()),
+                OwnedValue::Array(vec![OwnedValue::Str("second".to_string())]),
+            ]
+        );
+        assert_eq!(doc.len(), 2);
+        assert!(doc.get_first(title).is_none());
+        assert_eq!(
+            doc.get_all(count)
+                .map(|value| value.as_u64().unwrap())
+                .collect::<Vec<u64>>(),
+            vec![1, 2]
+        );
+        assert_eq!(doc.drain_field(title).count(), 0);
+
+        // The values are removed even if the iterator is dropped.
+        drop(doc.drain_field(count));
+        assert_eq!(doc.len(), 0);
+
+        // The data of the drained values is left until the document is compacted.
+        assert_eq!(doc.payload_len(), payload_len);
+        doc.add_u64(count, 3);
+        doc.compact_node_data();
+        assert!(doc.payload_len() < payload_len);
+        assert_eq!(doc.get_first_u64(count), Some(3));
+    }
+
+    #[test]
+    fn test_add_array() {
+        let mut schema_builder = Schema::builder();
+        let scores = schema_builder.add_f64_field("scores", STORED);
+        let tags = schema_builder.add_text_field("tags", STORED);
+        let mut doc = TantivyDocument::new();
+        doc.add_array(scores, [0.5f64, 1.5].iter());
+        doc.add_array(tags, ["a", "b", "c"]);
+        let long_array: Vec<u64> = (0..1_000).collect();
+        doc.add_array(scores, long_array.iter());
+
+        let mut expected_doc = TantivyDocument::new();
+        expected_doc.add_field_value(
+            scores,
+            &OwnedValue::Array(vec![OwnedValue::F64(0.5), OwnedValue::F64(1.5)]),
+        );
+        expected_doc.add_field_value(
+            tags,
+            &OwnedValue::Array(
+                ["a", "b", "c"]
+                    .into_iter()
+                    .map(|tag| OwnedValue::Str(tag.to_string()))
+                    .collect(),
+            ),
+        );
+        expected_doc.add_field_value(
+            scores,
+            &OwnedValue::Array(long_array.iter().copied().map(OwnedValue::U64).collect()),
+        );
+        assert_eq!(doc, expected_doc);
+
+        let values: Vec<CompactDocValue> = doc.get_all(scores).collect();
+        assert_eq!(values.len(), 2);
+        assert!(matches!(values[0].as_value(), ReferenceValue::Array(_)));
+        let accessor = values[1].as_array_accessor().unwrap();
+        assert_eq!(accessor.len(), 1_000);
+        assert_eq!(accessor.get(999).unwrap().as_u64(), Some(999));
+
+        let bytes = doc.to_bytes();
+        assert_eq!(TantivyDocument::from_bytes(&bytes).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_add_json_object_same_as_owned_value() {
+        let mut schema_builder = Schema::builder();
+        let json = schema_builder.add_json_field("json", STORED);
+        let json_value: serde_json::Value = serde_json::json!({
+            "signed": -3,
+            "unsigned": u64::MAX,
+            "float": 1.5,
+            "date": "1985-04-12T23:20:50.52Z",
+            "nested": {"tags": ["a", "b", null, true]},
+            "wide": (0..100)
+                .map(|i| (format!("key{i}"), serde_json::Value::from(i)))
+                .collect::<serde_json::Map<String, serde_json::Value>>(),
+        });
+        let serde_json::Value::Object(json_object) = &json_value else {
+            panic!("expected an object");
+        };
+        let mut doc = TantivyDocument::new();
+        doc.add_json_object(json, json_object);
+        doc.add_json_value(json, &json_value["nested"]["tags"]);
+
+        let mut expected_doc = TantivyDocument::new();
+        expected_doc.add_field_value(json, &OwnedValue::from(json_object.clone()));
+        expected_doc.add_field_value(
+            json,
+            &OwnedValue::from(json_value["nested"]["tags"].clone()),
+        );
+        assert_eq!(doc, expected_doc);
+        let values: Vec<OwnedValue> = doc.get_all(json).map(OwnedValue::from).collect();
+        assert!(matches!(
+            &values[0],
+            OwnedValue::Object(entries) if entries.iter().any(|(key, value)| {
+                key == "date" && matches!(value, OwnedValue::Date(_))
+            })
+        ));
+    }
+
+    #[test]
+    fn test_mem_usage_and_payload_len() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let doc_field = schema_builder.add_text_field("doc", STORED);
+        let field_value_size = std::mem::size_of::<super::FieldValueAddr>();
+
+        let mut doc = TantivyDocument::with_capacity(16, 2);
+        assert_eq!(doc.payload_len(), 0);
+        assert_eq!(doc.mem_usage(), 16 + 2 * field_value_size);
+        assert_eq!(
+            doc.allocated_memory_usage(),
+            std::mem::size_of_val(&doc) + doc.mem_usage()
+        );
+
+        doc.add_text(title, "a title which does not fit in sixteen bytes");
+        let payload_len_with_str = doc.payload_len();
+        assert!(payload_len_with_str > "a title which does not fit in sixteen bytes".len());
+        assert!(doc.mem_usage() >= payload_len_with_str + field_value_size);
+
+        doc.add_field_value(
+            title,
+            &OwnedValue::Array(vec![
+                OwnedValue::Str("first".to_string()),
+                OwnedValue::Str("second".to_string()),
+            ]),
+        );
+        let payload_len_with_array = doc.payload_len();
+        assert!(payload_len_with_array > payload_len_with_str + "firstsecond".len());
+
+        doc.add_pre_tokenized_text(
+            doc_field,
+            PreTokenizedString {
+                text: "hello".to_string(),
+                tokens: vec![crate::tokenizer::Token::new(0, 5, 0, "hello".to_string())],
+            },
+        );
+        assert!(doc.payload_len() > payload_len_with_array + "hellohello".len());
+        assert!(doc.mem_usage() >= doc.payload_len() + 3 * field_value_size);
+
+        for i in 0..100 {
+            doc.add_text(title, format!("title {i}"));
+        }
+        let mem_usage = doc.mem_usage();
+        assert!(mem_usage > doc.payload_len() + doc.len() * field_value_size);
+        doc.shrink_to_fit();
+        assert!(doc.mem_usage() < mem_usage);
+        assert_eq!(
+            doc.mem_usage(),
+            doc.payload_len() + doc.len() * field_value_size
+        );
+
+        // Removed values are counted until the data of the document is compacted.
+        let payload_len = doc.payload_len();
+        doc.remove_field(title);
+        assert_eq!(doc.payload_len(), payload_len);
+        doc.compact_node_data();
+        assert!(doc.payload_len() < payload_len);
+    }
+
+    #[test]
+    fn test_pre_tokenized_text_payload_round_trip() -> crate::Result<()> {
+        use crate::tokenizer::Token;
+        use crate::{DocAddress, Index, IndexWriter};
+
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let pre_tokenized_text = PreTokenizedString {
+            text: "hello world".to_string(),
+            tokens: vec![
+                Token::new(0, 5, 0, "hello".to_string()).with_payload(vec![1, 2, 255]),
+                Token::new(6, 11, 1, "world".to_string()),
+            ],
+        };
+        let expected_value = OwnedValue::PreTokStr(pre_tokenized_text.clone());
+        let mut doc = TantivyDocument::default();
+        doc.add_pre_tokenized_text(text, pre_tokenized_text.clone());
+        assert_eq!(
+            OwnedValue::from(doc.get_first(text).unwrap()),
+            expected_value
+        );
+        let doc_from_bytes = TantivyDocument::from_bytes(&doc.to_bytes())?;
+        assert_eq!(
+            OwnedValue::from(doc_from_bytes.get_first(text).unwrap()),
+            expected_value
+        );
+
+        // Through the doc store.
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stored_doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        assert_eq!(
+            stored_doc.to_named_doc(&schema).0["text"],
+            vec![expected_value]
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&stored_doc.to_json(&schema)).unwrap();
+        let json_tokens = &json["text"][0]["tokens"];
+        assert_eq!(json_tokens[0]["payload"], serde_json::json!([1, 2, 255]));
+        // The tokens without payload are serialized as before.
+        assert!(json_tokens[1].get("payload").is_none());
+        let pre_tokenized_text_from_json: PreTokenizedString =
+            serde_json::from_value(json["text"][0].clone()).unwrap();
+        assert_eq!(pre_tokenized_text_from_json, pre_tokenized_text);
+        Ok(())
+    }
+
     #[test]
     fn test_json_value() {
         let json_str = r#"{
@@ -761,24 +3522,302 @@ mod tests {
         assert_eq!(actual_json["json"][0], expected_json);
     }
 
-    // TODO: Should this be re-added with the serialize method
-    //       technically this is no longer useful since the doc types
-    //       do not implement BinarySerializable due to orphan rules.
-    // #[test]
-    // fn test_doc_serialization_issue() {
-    //     let mut doc = Document::default();
-    //     doc.add_json_object(
-    //         Field::from_field_id(0),
-    //         serde_json::json!({"key": 2u64})
-    //             .as_object()
-    //             .unwrap()
-    //             .clone(),
-    //     );
-    //     doc.add_text(Field::from_field_id(1), "hello");
-    //     assert_eq!(doc.field_values().len(), 2);
-    //     let mut payload: Vec<u8> = Vec::new();
-    //     doc_binary_wrappers::serialize(&doc, &mut payload).unwrap();
-    //     assert_eq!(payload.len(), 26);
-    //     doc_binary_wrappers::deserialize::<Document, _>(&mut &payload[..]).unwrap();
-    // }
+    #[test]
+    fn test_nested_value_error_path() {
+        use crate::schema::field_type::ValueParsingError;
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("counts", STORED);
+        schema_builder.add_u64_field("events.count", STORED);
+        schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+
+        let error =
+            TantivyDocument::parse_json(&schema, r#"{"counts": [1, 2, "three"]}"#).unwrap_err();
+        assert_eq!(
+            error,
+            DocParsingError::NestedValueError {
+                path: "counts[2]".to_string(),
+                value: r#""three""#.to_string(),
+                error: ValueParsingError::TypeError {
+                    expected: "a u64",
+                    json: serde_json::json!("three"),
+                },
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            r#"The value at 'counts[2]' could not be parsed: "three""#
+        );
+
+        // Arrays of objects in json fields.
+        let error = TantivyDocument::parse_json(
+            &schema,
+            r#"{"attributes": [{"events": [{"ts": 1}]}, "not an object"]}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            DocParsingError::NestedValueError { ref path, .. } if path == "attributes[1]"
+        ));
+
+        // The dots of the field names are escaped.
+        let error =
+            TantivyDocument::parse_json(&schema, r#"{"events.count": [1, -1]}"#).unwrap_err();
+        assert!(matches!(
+            error,
+            DocParsingError::NestedValueError { ref path, ref value, .. }
+                if path == r"events\.count[1]" && value == "-1"
+        ));
+
+        // The values are truncated.
+        let long_text = "a".repeat(1_000);
+        let error = TantivyDocument::parse_json(
+            &schema,
+            &format!(r#"{{"counts": [{{"text": "{long_text}"}}]}}"#),
+        )
+        .unwrap_err();
+        let (path, value) = match error {
+            DocParsingError::NestedValueError { path, value, .. } => (path, value),
+            error => panic!("expected a nested value error, got {error:?}"),
+        };
+        assert_eq!(path, "counts[0]");
+        assert!(value.starts_with(r#"{"text":"aaa"#));
+        assert!(value.ends_with("..."));
+        assert_eq!(value.chars().count(), 103);
+
+        // Arrays nested in arrays.
+        let named_doc = NamedFieldDocument(BTreeMap::from([(
+            "counts".to_string(),
+            vec![OwnedValue::Array(vec![
+                OwnedValue::U64(1),
+                OwnedValue::Array(vec![OwnedValue::Str("two".to_string())]),
+            ])],
+        )]));
+        let error = TantivyDocument::convert_named_doc(&schema, named_doc).unwrap_err();
+        assert!(matches!(
+            error,
+            DocParsingError::NestedValueError { ref path, .. } if path == "counts[1][0]"
+        ));
+
+        // The values of the fields themselves are still reported with their field name.
+        assert!(matches!(
+            TantivyDocument::parse_json(&schema, r#"{"counts": "three"}"#),
+            Err(DocParsingError::ValueError(field_name, _)) if field_name == "counts"
+        ));
+    }
+
+    #[test]
+    fn test_large_bytes_value() -> crate::Result<()> {
+        // Larger than what a 24 bits address could point to.
+        let large_bytes: Vec<u8> = (0..32 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let mut schema_builder = Schema::builder();
+        let bytes_field = schema_builder.add_bytes_field("bytes", STORED);
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+
+        let mut doc = TantivyDocument::default();
+        doc.add_bytes(bytes_field, &large_bytes);
+        doc.add_text(text_field, "after the bytes");
+        assert!(doc.node_data.len() > large_bytes.len());
+        assert_eq!(
+            doc.get_first(bytes_field).unwrap().as_bytes(),
+            Some(&large_bytes[..])
+        );
+        assert_eq!(
+            doc.get_first(text_field).unwrap().as_str(),
+            Some("after the bytes")
+        );
+
+        let index = crate::Index::create_in_ram(schema);
+        let mut index_writer: crate::IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let stored_doc: TantivyDocument = searcher.doc(crate::DocAddress::new(0, 0))?;
+        assert_eq!(
+            stored_doc.get_first(bytes_field).unwrap().as_bytes(),
+            Some(&large_bytes[..])
+        );
+        assert_eq!(
+            stored_doc.get_first(text_field).unwrap().as_str(),
+            Some("after the bytes")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_nested_json_value() {
+        // The values nested in the object are addressed past 16 MB.
+        let large_text = "a".repeat(4 * 1024 * 1024);
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_json_field("json", STORED);
+        let schema = schema_builder.build();
+        let json_str = serde_json::json!({
+            "pages": (0..5)
+                .map(|page| serde_json::json!({ "id": page, "body": large_text }))
+                .collect::<Vec<_>>(),
+            "last": "end",
+        })
+        .to_string();
+        let doc =
+            TantivyDocument::parse_json(&schema, &format!(r#"{{"json": {json_str}}}"#)).unwrap();
+        assert!(doc.node_data.len() > 16 * 1024 * 1024);
+        let json: serde_json::Value = serde_json::from_str(&doc.to_json(&schema)).unwrap();
+        assert_eq!(json["json"][0]["last"], "end");
+        assert_eq!(json["json"][0]["pages"][4]["id"], 4);
+        assert_eq!(json["json"][0]["pages"][4]["body"], large_text.as_str());
+    }
+
+    #[test]
+    fn test_doc_serialization_issue() {
+        let mut doc = TantivyDocument::default();
+        doc.add_object(
+            Field::from_field_id(0),
+            serde_json::from_str(r#"{"key": 2}"#).unwrap(),
+        );
+        doc.add_text(Field::from_field_id(1), "hello");
+        assert_eq!(doc.field_values().count(), 2);
+        let mut payload: Vec<u8> = Vec::new();
+        doc_binary_wrappers::serialize(&doc, &mut payload).unwrap();
+        assert_eq!(payload.len(), 32);
+        let deserialized_doc: TantivyDocument =
+            doc_binary_wrappers::deserialize(&mut &payload[..]).unwrap();
+        assert_eq!(
+            owned_field_values(&deserialized_doc),
+            owned_field_values(&doc)
+        );
+    }
+
+    fn hash_doc(hasher_builder: &RandomState, doc: &TantivyDocument) -> u64 {
+        hasher_builder.hash_one(doc)
+    }
+
+    #[test]
+    fn test_compact_doc_eq_and_hash_across_value_paths() {
+        let mut schema_builder = Schema::builder();
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let title = schema_builder.add_text_field("title", STORED);
+        let schema = schema_builder.build();
+        let hasher_builder = RandomState::new();
+
+        // `parse_json` adds the fields in the order of their names.
+        let parsed_doc = TantivyDocument::parse_json(
+            &schema,
+            r#"{"title": ["first", "second"], "count": 3, "score": 0.5,
+                "attributes": {"n": -1, "tags": ["a", "b"]}}"#,
+        )
+        .unwrap();
+        let attributes_value = serde_json::json!({"n": -1, "tags": ["a", "b"]});
+        let mut doc = TantivyDocument::new();
+        doc.add_json_value(attributes, &attributes_value);
+        doc.add_u64(count, 3);
+        doc.add_f64(score, 0.5);
+        doc.add_text(title, "first");
+        doc.add_text(title, "second");
+        assert_eq!(doc, parsed_doc);
+        assert_eq!(
+            hash_doc(&hasher_builder, &doc),
+            hash_doc(&hasher_builder, &parsed_doc)
+        );
+        let deserialized_doc = TantivyDocument::from_bytes(&doc.to_bytes()).unwrap();
+        assert_eq!(deserialized_doc, doc);
+        assert_eq!(
+            hash_doc(&hasher_builder, &deserialized_doc),
+            hash_doc(&hasher_builder, &doc)
+        );
+
+        let mut reordered_doc = TantivyDocument::new();
+        reordered_doc.add_text(title, "first");
+        reordered_doc.add_text(title, "second");
+        reordered_doc.add_u64(count, 3);
+        reordered_doc.add_f64(score, 0.5);
+        reordered_doc.add_json_value(attributes, &attributes_value);
+        assert_ne!(reordered_doc, parsed_doc);
+        assert!(reordered_doc.eq_unordered(&parsed_doc));
+
+        let mut other_doc = doc.clone();
+        other_doc.add_i64(count, 3);
+        assert_ne!(other_doc, doc);
+    }
+
+    #[test]
+    fn test_compact_doc_eq_compares_value_types_and_order() {
+        let field = Field::from_field_id(0);
+        let hasher_builder = RandomState::new();
+        let doc_with = |values: &[OwnedValue]| {
+            let mut doc = TantivyDocument::new();
+            for value in values {
+                doc.add_field_value(field, value);
+            }
+            doc
+        };
+        let doc = doc_with(&[OwnedValue::U64(1), OwnedValue::Str("a".to_string())]);
+        assert_eq!(
+            doc,
+            doc_with(&[OwnedValue::U64(1), OwnedValue::Str("a".to_string())])
+        );
+        assert_ne!(
+            doc,
+            doc_with(&[OwnedValue::I64(2), OwnedValue::Str("a".to_string())])
+        );
+        assert_ne!(doc, doc_with(&[OwnedValue::U64(1)]));
+        assert_ne!(
+            doc,
+            doc_with(&[OwnedValue::F64(1.0), OwnedValue::Str("a".to_string())])
+        );
+
+        // Integers are compared by value, whether they are stored as `u64` or `i64`.
+        let i64_doc = doc_with(&[OwnedValue::I64(1), OwnedValue::Str("a".to_string())]);
+        assert_eq!(doc, i64_doc);
+        assert_eq!(
+            hash_doc(&hasher_builder, &doc),
+            hash_doc(&hasher_builder, &i64_doc)
+        );
+        assert_ne!(
+            doc_with(&[OwnedValue::U64(u64::MAX)]),
+            doc_with(&[OwnedValue::I64(-1)])
+        );
+
+        let reversed_doc = doc_with(&[OwnedValue::Str("a".to_string()), OwnedValue::U64(1)]);
+        assert_ne!(doc, reversed_doc);
+        assert!(doc.eq_unordered(&reversed_doc));
+
+        let nan_doc = doc_with(&[OwnedValue::F64(f64::NAN)]);
+        assert_eq!(nan_doc, doc_with(&[OwnedValue::F64(f64::NAN)]));
+        assert_ne!(nan_doc, doc_with(&[OwnedValue::F64(0.0)]));
+
+        let nested = |last: OwnedValue| {
+            doc_with(&[OwnedValue::Object(vec![(
+                "k".to_string(),
+                OwnedValue::Array(vec![OwnedValue::Bool(true), last]),
+            )])])
+        };
+        assert_eq!(nested(OwnedValue::Null), nested(OwnedValue::Null));
+        assert_ne!(nested(OwnedValue::Null), nested(OwnedValue::Bool(false)));
+        assert_ne!(
+            doc_with(&[OwnedValue::Array(vec![OwnedValue::U64(1)])]),
+            doc_with(&[OwnedValue::Array(vec![
+                OwnedValue::U64(1),
+                OwnedValue::U64(1)
+            ])])
+        );
+
+        let docs: HashSet<TantivyDocument> = [
+            doc.clone(),
+            reversed_doc.clone(),
+            doc_with(&[OwnedValue::U64(1), OwnedValue::Str("a".to_string())]),
+            nested(OwnedValue::Null),
+            nested(OwnedValue::Null),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(docs.len(), 3);
+        assert_eq!(
+            hash_doc(&hasher_builder, &nested(OwnedValue::Null)),
+            hash_doc(&hasher_builder, &nested(OwnedValue::Null))
+        );
+    }
 }
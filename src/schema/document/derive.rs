@@ -0,0 +1,469 @@
+//! Runtime support for the `#[derive(TantivyDoc)]` macro of the `derive` feature.
+//!
+//! The code generated by the macro only goes through this module, which keeps it small and
+//! makes unsupported field types fail at compile time with a missing `DerivedFieldValue` impl.
+//!
+//! ```compile_fail
+//! use tantivy::TantivyDoc;
+//!
+//! #[derive(TantivyDoc)]
+//! struct Doc {
+//!     #[tantivy(stored)]
+//!     scores: std::collections::HashMap<String, u64>,
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! use tantivy::TantivyDoc;
+//!
+//! #[derive(TantivyDoc)]
+//! struct Doc {
+//!     #[tantivy(stored)]
+//!     tags: Vec<Vec<String>>,
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! use tantivy::TantivyDoc;
+//!
+//! #[derive(TantivyDoc)]
+//! struct Doc {
+//!     #[tantivy(stored, tokenized)]
+//!     title: String,
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! use tantivy::TantivyDoc;
+//!
+//! #[derive(TantivyDoc)]
+//! struct Doc(String);
+//! ```
+
+use std::net::Ipv6Addr;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::schema::document::{DeserializeError, OwnedValue};
+use crate::schema::{
+    DateOptions, Field, IpAddrOptions, JsonObjectOptions, NumericOptions, SchemaBuilder,
+    TextOptions, FAST, STORED, STRING, TEXT,
+};
+use crate::{DateTime, TantivyDocument};
+
+/// The flags of a `#[tantivy(...)]` field attribute.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FieldAttributes {
+    /// `stored`: the values are kept in the doc store.
+    pub stored: bool,
+    /// `indexed`: the values are indexed. For strings, this is the same as `text`.
+    pub indexed: bool,
+    /// `fast`: the values are written to a fast field.
+    pub fast: bool,
+    /// `text`: strings are indexed with the default tokenizer.
+    pub text: bool,
+    /// `string`: strings are indexed untokenized.
+    pub string: bool,
+}
+
+impl FieldAttributes {
+    fn text_options(&self) -> TextOptions {
+        let mut options = if self.string {
+            STRING
+        } else if self.text || self.indexed {
+            TEXT
+        } else {
+            TextOptions::default()
+        };
+        if self.stored {
+            options = options | STORED;
+        }
+        if self.fast {
+            options = options | FAST;
+        }
+        options
+    }
+
+    fn numeric_options(&self) -> NumericOptions {
+        let mut options = NumericOptions::default();
+        if self.stored {
+            options = options.set_stored();
+        }
+        if self.indexed {
+            options = options.set_indexed();
+        }
+        if self.fast {
+            options = options.set_fast();
+        }
+        options
+    }
+
+    fn date_options(&self) -> DateOptions {
+        let mut options = DateOptions::default();
+        if self.stored {
+            options = options.set_stored();
+        }
+        if self.indexed {
+            options = options.set_indexed();
+        }
+        if self.fast {
+            options = options.set_fast();
+        }
+        options
+    }
+
+    fn ip_addr_options(&self) -> IpAddrOptions {
+        let mut options = IpAddrOptions::default();
+        if self.stored {
+            options = options.set_stored();
+        }
+        if self.indexed {
+            options = options.set_indexed();
+        }
+        if self.fast {
+            options = options.set_fast();
+        }
+        options
+    }
+
+    fn json_options(&self) -> JsonObjectOptions {
+        // The JSON field is indexed like a string field: it is left unindexed unless `indexed`,
+        // `text` or `string` is set.
+        JsonObjectOptions::from(self.text_options())
+    }
+}
+
+/// A type holding the value of a single-valued field of a derived document.
+pub trait DerivedLeafValue: Sized {
+    /// Adds a field able to hold values of this type to the schema.
+    fn add_field(
+        schema_builder: &mut SchemaBuilder,
+        field_name: &str,
+        attributes: FieldAttributes,
+    ) -> Field;
+
+    /// Adds the value to the document.
+    fn add_to_document(&self, document: &mut TantivyDocument, field: Field);
+
+    /// Converts a value read from the document back, returning `None` on a type mismatch.
+    fn from_owned_value(value: OwnedValue) -> Option<Self>;
+}
+
+/// A type holding all the values of a field of a derived document.
+///
+/// This is implemented for the supported leaf types, and for `Option` and `Vec` of them.
+pub trait DerivedFieldValue: Sized {
+    /// Adds a field able to hold values of this type to the schema.
+    fn add_field(
+        schema_builder: &mut SchemaBuilder,
+        field_name: &str,
+        attributes: FieldAttributes,
+    ) -> Field;
+
+    /// Adds the values to the document.
+    fn add_to_document(&self, document: &mut TantivyDocument, field: Field);
+
+    /// Builds the value out of all the values of the field in the document.
+    fn from_owned_values(
+        field_name: &str,
+        values: Vec<OwnedValue>,
+    ) -> Result<Self, DeserializeError>;
+}
+
+fn leaf_from_owned_value<T: DerivedLeafValue>(
+    field_name: &str,
+    value: OwnedValue,
+) -> Result<T, DeserializeError> {
+    let debug_value = format!("{value:?}");
+    T::from_owned_value(value).ok_or_else(|| {
+        DeserializeError::custom(format!(
+            "Field `{field_name}`: cannot convert value {debug_value} to `{}`",
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+macro_rules! impl_derived_leaf_value {
+    ($type:ty, $options:ident, $add_field:ident, |$self:ident, $doc:ident, $field:ident| $add:expr,
+     |$value:ident| $from_value:expr) => {
+        impl DerivedLeafValue for $type {
+            fn add_field(
+                schema_builder: &mut SchemaBuilder,
+                field_name: &str,
+                attributes: FieldAttributes,
+            ) -> Field {
+                schema_builder.$add_field(field_name, attributes.$options())
+            }
+
+            fn add_to_document(&$self, $doc: &mut TantivyDocument, $field: Field) {
+                $add
+            }
+
+            fn from_owned_value($value: OwnedValue) -> Option<Self> {
+                $from_value
+            }
+        }
+
+        impl DerivedFieldValue for $type {
+            fn add_field(
+                schema_builder: &mut SchemaBuilder,
+                field_name: &str,
+                attributes: FieldAttributes,
+            ) -> Field {
+                <Self as DerivedLeafValue>::add_field(schema_builder, field_name, attributes)
+            }
+
+            fn add_to_document(&self, document: &mut TantivyDocument, field: Field) {
+                DerivedLeafValue::add_to_document(self, document, field)
+            }
+
+            fn from_owned_values(
+                field_name: &str,
+                values: Vec<OwnedValue>,
+            ) -> Result<Self, DeserializeError> {
+                let num_values = values.len();
+                let mut values = values.into_iter();
+                match (values.next(), num_values) {
+                    (Some(value), 1) => leaf_from_owned_value(field_name, value),
+                    _ => Err(DeserializeError::custom(format!(
+                        "Field `{field_name}`: expected exactly one value, found {num_values}"
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! impl_derived_unsigned_value {
+    ($($type:ty),*) => {$(
+        impl_derived_leaf_value!($type, numeric_options, add_u64_field,
+            |self, doc, field| doc.add_u64(field, u64::from(*self)),
+            |value| match value {
+                OwnedValue::U64(val) => <$type>::try_from(val).ok(),
+                _ => None,
+            });
+    )*};
+}
+
+macro_rules! impl_derived_signed_value {
+    ($($type:ty),*) => {$(
+        impl_derived_leaf_value!($type, numeric_options, add_i64_field,
+            |self, doc, field| doc.add_i64(field, i64::from(*self)),
+            |value| match value {
+                OwnedValue::I64(val) => <$type>::try_from(val).ok(),
+                _ => None,
+            });
+    )*};
+}
+
+impl_derived_unsigned_value!(u32, u16, u8);
+impl_derived_signed_value!(i32, i16, i8);
+
+impl_derived_leaf_value!(
+    u64,
+    numeric_options,
+    add_u64_field,
+    |self, doc, field| doc.add_u64(field, *self),
+    |value| match value {
+        OwnedValue::U64(val) => Some(val),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    i64,
+    numeric_options,
+    add_i64_field,
+    |self, doc, field| doc.add_i64(field, *self),
+    |value| match value {
+        OwnedValue::I64(val) => Some(val),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    String,
+    text_options,
+    add_text_field,
+    |self, doc, field| doc.add_text(field, self),
+    |value| match value {
+        OwnedValue::Str(text) => Some(text),
+        OwnedValue::PreTokStr(pre_tokenized) => Some(pre_tokenized.text),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    f64,
+    numeric_options,
+    add_f64_field,
+    |self, doc, field| doc.add_f64(field, *self),
+    |value| match value {
+        OwnedValue::F64(val) => Some(val),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    f32,
+    numeric_options,
+    add_f64_field,
+    |self, doc, field| doc.add_f64(field, f64::from(*self)),
+    |value| match value {
+        OwnedValue::F64(val) => Some(val as f32),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    bool,
+    numeric_options,
+    add_bool_field,
+    |self, doc, field| doc.add_bool(field, *self),
+    |value| match value {
+        OwnedValue::Bool(val) => Some(val),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    DateTime,
+    date_options,
+    add_date_field,
+    |self, doc, field| doc.add_date(field, *self),
+    |value| match value {
+        OwnedValue::Date(val) => Some(val),
+        _ => None,
+    }
+);
+
+impl_derived_leaf_value!(
+    Ipv6Addr,
+    ip_addr_options,
+    add_ip_addr_field,
+    |self, doc, field| doc.add_ip_addr(field, *self),
+    |value| match value {
+        OwnedValue::IpAddr(val) => Some(val),
+        _ => None,
+    }
+);
+
+impl<T: DerivedLeafValue> DerivedFieldValue for Option<T> {
+    fn add_field(
+        schema_builder: &mut SchemaBuilder,
+        field_name: &str,
+        attributes: FieldAttributes,
+    ) -> Field {
+        T::add_field(schema_builder, field_name, attributes)
+    }
+
+    fn add_to_document(&self, document: &mut TantivyDocument, field: Field) {
+        if let Some(value) = self {
+            value.add_to_document(document, field);
+        }
+    }
+
+    fn from_owned_values(
+        field_name: &str,
+        values: Vec<OwnedValue>,
+    ) -> Result<Self, DeserializeError> {
+        if values.len() > 1 {
+            return Err(DeserializeError::custom(format!(
+                "Field `{field_name}`: expected at most one value, found {}",
+                values.len()
+            )));
+        }
+        values
+            .into_iter()
+            .next()
+            .map(|value| leaf_from_owned_value(field_name, value))
+            .transpose()
+    }
+}
+
+impl<T: DerivedLeafValue> DerivedFieldValue for Vec<T> {
+    fn add_field(
+        schema_builder: &mut SchemaBuilder,
+        field_name: &str,
+        attributes: FieldAttributes,
+    ) -> Field {
+        T::add_field(schema_builder, field_name, attributes)
+    }
+
+    fn add_to_document(&self, document: &mut TantivyDocument, field: Field) {
+        for value in self {
+            value.add_to_document(document, field);
+        }
+    }
+
+    fn from_owned_values(
+        field_name: &str,
+        values: Vec<OwnedValue>,
+    ) -> Result<Self, DeserializeError> {
+        values
+            .into_iter()
+            .map(|value| leaf_from_owned_value(field_name, value))
+            .collect()
+    }
+}
+
+/// Adds a JSON field holding the serde serialization of a `#[tantivy(json)]` struct field.
+pub fn add_json_field(
+    schema_builder: &mut SchemaBuilder,
+    field_name: &str,
+    attributes: FieldAttributes,
+) -> Field {
+    schema_builder.add_json_field(field_name, attributes.json_options())
+}
+
+/// Serializes a `#[tantivy(json)]` struct field into the document.
+///
+/// Values serializing to `null`, like `None`, are skipped.
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized to JSON, e.g. a map with non-string keys.
+pub fn add_json_value<T: Serialize>(value: &T, document: &mut TantivyDocument, field: Field) {
+    let json_value =
+        serde_json::to_value(value).expect("`#[tantivy(json)]` fields must serialize to JSON");
+    if !json_value.is_null() {
+        document.add_json_value(field, &json_value);
+    }
+}
+
+/// Deserializes a `#[tantivy(json)]` struct field from the values of its JSON field.
+///
+/// A missing value is deserialized from `null`, so that `Option` fields can be left empty.
+pub fn from_json_values<T: DeserializeOwned>(
+    field_name: &str,
+    values: Vec<OwnedValue>,
+) -> Result<T, DeserializeError> {
+    if values.len() > 1 {
+        return Err(DeserializeError::custom(format!(
+            "Field `{field_name}`: expected at most one JSON object, found {}",
+            values.len()
+        )));
+    }
+    let json_value = match values.into_iter().next() {
+        Some(value) => serde_json::to_value(value).map_err(DeserializeError::custom)?,
+        None => serde_json::Value::Null,
+    };
+    serde_json::from_value(json_value)
+        .map_err(|err| DeserializeError::custom(format!("Field `{field_name}`: {err}")))
+}
+
+/// Returns the field named `field_name` in the schema of a derived document.
+pub fn get_field(
+    schema: &crate::schema::Schema,
+    field_name: &str,
+) -> Result<Field, DeserializeError> {
+    schema
+        .get_field(field_name)
+        .map_err(DeserializeError::custom)
+}
+
+/// Returns all the values of `field` in `document`.
+pub fn owned_values(document: &TantivyDocument, field: Field) -> Vec<OwnedValue> {
+    document.get_all(field).map(OwnedValue::from).collect()
+}
@@ -0,0 +1,451 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::default_document::ValuePath;
+use super::existing_type_impls::JsonObjectIter;
+use super::{DocParsingError, Document, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::schema::field_type::ValueParsingError;
+use crate::schema::{Field, FieldType, IntoIpv6Addr, Schema};
+use crate::time::format_description::well_known::Rfc3339;
+use crate::time::OffsetDateTime;
+use crate::DateTime;
+
+/// A document backed by a JSON object, whose keys are the names of the fields.
+///
+/// Contrary to [`TantivyDocument::from_json_object`](crate::TantivyDocument::from_json_object),
+/// the values are not copied: they are read from the JSON object when the document is indexed
+/// or stored, and converted to the type of their field on the fly.
+///
+/// The values are converted following the same rules as `TantivyDocument::from_json_object`,
+/// and the values of arrays are added as several values of their field. The values whose
+/// conversion would require an allocation (facets, bytes, pre-tokenized strings, and numbers
+/// or bools coerced to strings other than `true`, `false` and `null`) are not supported.
+///
+/// The keys which are not fields of the schema are skipped, unless a catch-all JSON field is
+/// set with [`JsonDocument::with_catch_all_field`].
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::schema::{JsonDocument, Schema, STORED, TEXT};
+/// use tantivy::{Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// schema_builder.add_text_field("title", TEXT | STORED);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema.clone());
+/// let mut index_writer: IndexWriter<JsonDocument> = index.writer(15_000_000)?;
+/// let serde_json::Value::Object(object) = serde_json::json!({"title": "Of Mice and Men"}) else {
+///     unreachable!();
+/// };
+/// index_writer.add_document(JsonDocument::new(schema, object)?)?;
+/// index_writer.commit()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct JsonDocument {
+    schema: Schema,
+    object: Map<String, JsonValue>,
+    catch_all_field: Option<Field>,
+}
+
+impl JsonDocument {
+    /// Creates a document from a JSON object.
+    ///
+    /// Returns an error if one of the values cannot be converted to the type of its field.
+    pub fn new(
+        schema: Schema,
+        object: Map<String, JsonValue>,
+    ) -> Result<JsonDocument, DocParsingError> {
+        for (field_name, json_value) in &object {
+            let Ok(field) = schema.get_field(field_name) else {
+                continue;
+            };
+            let field_type = schema.get_field_entry(field).field_type();
+            let path = ValuePath::new(field_name);
+            match json_value {
+                JsonValue::Array(json_items) => {
+                    for (idx, json_item) in json_items.iter().enumerate() {
+                        check_json_value(field_type, json_item)
+                            .map_err(|error| path.index(idx).value_error(error))?;
+                    }
+                }
+                _ => check_json_value(field_type, json_value)
+                    .map_err(|error| path.value_error(error))?,
+            }
+        }
+        Ok(JsonDocument {
+            schema,
+            object,
+            catch_all_field: None,
+        })
+    }
+
+    /// Adds the entries of the keys which are not fields of the schema to `field`, which must
+    /// be a JSON field, instead of skipping them.
+    ///
+    /// Each of these entries is added as a JSON object holding this single entry.
+    pub fn with_catch_all_field(mut self, field: Field) -> JsonDocument {
+        self.catch_all_field = Some(field);
+        self
+    }
+
+    /// Returns the JSON object of the document.
+    pub fn object(&self) -> &Map<String, JsonValue> {
+        &self.object
+    }
+}
+
+impl Document for JsonDocument {
+    type Value<'a> = JsonFieldValue<'a>;
+    type FieldsValuesIter<'a> = JsonDocumentFieldsIter<'a>;
+
+    fn iter_fields_and_values(&self) -> Self::FieldsValuesIter<'_> {
+        JsonDocumentFieldsIter {
+            doc: self,
+            entries: self.object.iter(),
+            current_values: None,
+        }
+    }
+}
+
+/// The iterator over the fields and values of a [`JsonDocument`].
+pub struct JsonDocumentFieldsIter<'a> {
+    doc: &'a JsonDocument,
+    entries: serde_json::map::Iter<'a>,
+    // The values of an array, and the type of their field.
+    current_values: Option<(Field, &'a FieldType, std::slice::Iter<'a, JsonValue>)>,
+}
+
+impl<'a> Iterator for JsonDocumentFieldsIter<'a> {
+    type Item = (Field, JsonFieldValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((field, field_type, json_items)) = &mut self.current_values {
+                if let Some(json_item) = json_items.next() {
+                    return Some((*field, field_value(field_type, json_item)));
+                }
+                self.current_values = None;
+            }
+            let (field_name, json_value) = self.entries.next()?;
+            let Ok(field) = self.doc.schema.get_field(field_name) else {
+                if let Some(catch_all_field) = self.doc.catch_all_field {
+                    let value = JsonFieldValue(JsonFieldValueRepr::Entry(field_name, json_value));
+                    return Some((catch_all_field, value));
+                }
+                continue;
+            };
+            let field_type = self.doc.schema.get_field_entry(field).field_type();
+            match json_value {
+                JsonValue::Array(json_items) => {
+                    self.current_values = Some((field, field_type, json_items.iter()));
+                }
+                _ => return Some((field, field_value(field_type, json_value))),
+            }
+        }
+    }
+}
+
+/// A value of a [`JsonDocument`], borrowing from its JSON object.
+#[derive(Clone, Debug)]
+pub struct JsonFieldValue<'a>(JsonFieldValueRepr<'a>);
+
+#[derive(Clone, Debug)]
+enum JsonFieldValueRepr<'a> {
+    /// A value converted to the type of its field.
+    Leaf(ReferenceValueLeaf<'a>),
+    /// A value of a JSON field.
+    Json(&'a JsonValue),
+    /// An entry added to the catch-all field, as an object.
+    Entry(&'a str, &'a JsonValue),
+}
+
+impl<'a> Value<'a> for JsonFieldValue<'a> {
+    type ArrayIter = JsonFieldValueArrayIter<'a>;
+    type ObjectIter = JsonFieldValueObjectIter<'a>;
+
+    fn as_value(&self) -> ReferenceValue<'a, Self> {
+        match &self.0 {
+            JsonFieldValueRepr::Leaf(leaf) => ReferenceValue::Leaf(leaf.clone()),
+            JsonFieldValueRepr::Json(json_value) => match json_value.as_value() {
+                ReferenceValue::Leaf(leaf) => ReferenceValue::Leaf(leaf),
+                ReferenceValue::Array(elements) => {
+                    ReferenceValue::Array(JsonFieldValueArrayIter(elements))
+                }
+                ReferenceValue::Object(JsonObjectIter(entries)) => {
+                    ReferenceValue::Object(JsonFieldValueObjectIter {
+                        entries: Some(entries),
+                        entry: None,
+                    })
+                }
+            },
+            JsonFieldValueRepr::Entry(key, json_value) => {
+                ReferenceValue::Object(JsonFieldValueObjectIter {
+                    entries: None,
+                    entry: Some((*key, *json_value)),
+                })
+            }
+        }
+    }
+}
+
+/// The iterator over the elements of an array of a [`JsonFieldValue`].
+pub struct JsonFieldValueArrayIter<'a>(std::slice::Iter<'a, JsonValue>);
+
+impl<'a> Iterator for JsonFieldValueArrayIter<'a> {
+    type Item = JsonFieldValue<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let json_value = self.0.next()?;
+        Some(JsonFieldValue(JsonFieldValueRepr::Json(json_value)))
+    }
+}
+
+/// The iterator over the entries of an object of a [`JsonFieldValue`].
+pub struct JsonFieldValueObjectIter<'a> {
+    entries: Option<serde_json::map::Iter<'a>>,
+    entry: Option<(&'a str, &'a JsonValue)>,
+}
+
+impl<'a> Iterator for JsonFieldValueObjectIter<'a> {
+    type Item = (&'a str, JsonFieldValue<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, json_value) = match &mut self.entries {
+            Some(entries) => entries
+                .next()
+                .map(|(key, json_value)| (key.as_str(), json_value))?,
+            None => self.entry.take()?,
+        };
+        Some((key, JsonFieldValue(JsonFieldValueRepr::Json(json_value))))
+    }
+}
+
+fn field_value<'a>(field_type: &FieldType, json_value: &'a JsonValue) -> JsonFieldValue<'a> {
+    if let FieldType::JsonObject(_) = field_type {
+        return JsonFieldValue(JsonFieldValueRepr::Json(json_value));
+    }
+    let leaf = leaf_from_json(field_type, json_value)
+        .expect("the values are checked when the document is created");
+    JsonFieldValue(JsonFieldValueRepr::Leaf(leaf))
+}
+
+/// Checks that `json_value` can be added to a field of type `field_type`.
+fn check_json_value(
+    field_type: &FieldType,
+    json_value: &JsonValue,
+) -> Result<(), ValueParsingError> {
+    let leaf_opt = match field_type {
+        FieldType::JsonObject(_) if json_value.is_object() => return Ok(()),
+        FieldType::JsonObject(_) => None,
+        _ => leaf_from_json(field_type, json_value),
+    };
+    if let Some(leaf) = leaf_opt {
+        if let (FieldType::Str(text_options), ReferenceValueLeaf::Str(text)) = (field_type, leaf) {
+            if let Some(enum_values) = text_options.get_enum_values() {
+                enum_values.check_value(text)?;
+            }
+        }
+        return Ok(());
+    }
+    // The value is either invalid, in which case the error is the one of
+    // `TantivyDocument::from_json_object`, or cannot be borrowed.
+    field_type.value_from_json(json_value.clone())?;
+    Err(ValueParsingError::TypeError {
+        expected: "a value which does not need to be copied",
+        json: json_value.clone(),
+    })
+}
+
+/// Converts `json_value` to the type of a field, with the rules of
+/// [`FieldType::value_from_json`], if it is valid and can be borrowed.
+fn leaf_from_json<'a>(
+    field_type: &FieldType,
+    json_value: &'a JsonValue,
+) -> Option<ReferenceValueLeaf<'a>> {
+    let leaf = match (field_type, json_value) {
+        (FieldType::Str(_), JsonValue::String(text)) => ReferenceValueLeaf::Str(text),
+        (FieldType::Str(text_options), JsonValue::Bool(val)) if text_options.should_coerce() => {
+            ReferenceValueLeaf::Str(if *val { "true" } else { "false" })
+        }
+        (FieldType::Str(text_options), JsonValue::Null) if text_options.should_coerce() => {
+            ReferenceValueLeaf::Str("null")
+        }
+        (FieldType::U64(_), JsonValue::Number(num)) => ReferenceValueLeaf::U64(num.as_u64()?),
+        (FieldType::U64(options), JsonValue::String(text)) if options.should_coerce() => {
+            ReferenceValueLeaf::U64(text.parse().ok()?)
+        }
+        (FieldType::I64(_) | FieldType::Date(_), JsonValue::Number(num)) => {
+            ReferenceValueLeaf::I64(num.as_i64()?)
+        }
+        (FieldType::I64(options), JsonValue::String(text)) if options.should_coerce() => {
+            ReferenceValueLeaf::I64(text.parse().ok()?)
+        }
+        (FieldType::F64(_) | FieldType::F32Vector(_), JsonValue::Number(num)) => {
+            ReferenceValueLeaf::F64(num.as_f64()?)
+        }
+        (FieldType::F64(options), JsonValue::String(text)) if options.should_coerce() => {
+            ReferenceValueLeaf::F64(text.parse().ok()?)
+        }
+        (FieldType::F32(_), JsonValue::Number(num)) => {
+            let val = num.as_f64()? as f32;
+            if !val.is_finite() {
+                return None;
+            }
+            ReferenceValueLeaf::F64(f64::from(val))
+        }
+        (FieldType::F32(options), JsonValue::String(text)) if options.should_coerce() => {
+            ReferenceValueLeaf::F64(f64::from(text.parse::<f32>().ok()?))
+        }
+        (FieldType::Bool(_), JsonValue::Bool(val)) => ReferenceValueLeaf::Bool(*val),
+        (FieldType::Bool(options), JsonValue::String(text)) if options.should_coerce() => {
+            ReferenceValueLeaf::Bool(text.parse().ok()?)
+        }
+        (FieldType::Date(_), JsonValue::String(text)) => {
+            let date_time = OffsetDateTime::parse(text, &Rfc3339).ok()?;
+            ReferenceValueLeaf::Date(DateTime::from_utc(date_time))
+        }
+        (FieldType::IpAddr(_), JsonValue::String(text)) => {
+            ReferenceValueLeaf::IpAddr(IpAddr::from_str(text).ok()?.into_ipv6_addr())
+        }
+        _ => return None,
+    };
+    Some(leaf)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::JsonDocument;
+    use crate::collector::DocSetCollector;
+    use crate::query::TermQuery;
+    use crate::schema::{
+        DocParsingError, Document, FacetOptions, IndexRecordOption, OwnedValue, Schema, INDEXED,
+        STORED, STRING, TEXT,
+    };
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    fn json_object(json_value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        match json_value {
+            serde_json::Value::Object(object) => object,
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn test_json_document_same_as_tantivy_document() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let tags = schema_builder.add_text_field("tags", STRING | STORED);
+        let count = schema_builder.add_u64_field("count", INDEXED | STORED);
+        let delta = schema_builder.add_i64_field("delta", INDEXED | STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let published = schema_builder.add_date_field("published", INDEXED | STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", STORED);
+        let attributes = schema_builder.add_json_field("attributes", TEXT | STORED);
+        let schema = schema_builder.build();
+        let object = json_object(json!({
+            "title": "Of Mice and Men",
+            "tags": ["novel", "classic"],
+            "count": 3,
+            "delta": -2,
+            "score": 4.5,
+            "published": "1937-02-06T00:00:00Z",
+            "ip": "127.0.0.1",
+            "attributes": {"author": "Steinbeck", "pages": [107, 112]},
+            "unknown": "skipped",
+        }));
+
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter<JsonDocument> = index.writer_for_tests()?;
+        index_writer.add_document(JsonDocument::new(schema.clone(), object.clone())?)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for term in [
+            Term::from_field_text(title, "mice"),
+            Term::from_field_text(tags, "classic"),
+            Term::from_field_u64(count, 3),
+            Term::from_field_i64(delta, -2),
+        ] {
+            let query = TermQuery::new(term, IndexRecordOption::Basic);
+            assert_eq!(searcher.search(&query, &DocSetCollector)?.len(), 1);
+        }
+        let stored_doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        let expected_doc = TantivyDocument::from_json_object(&schema, object)?;
+        assert_eq!(stored_doc.to_json(&schema), expected_doc.to_json(&schema));
+        assert!(stored_doc.get_first(published).is_some());
+        assert!(stored_doc.get_first(ip).is_some());
+        assert!(stored_doc.get_first(score).is_some());
+        assert_eq!(
+            OwnedValue::from(stored_doc.get_first(attributes).unwrap()),
+            OwnedValue::from(expected_doc.get_first(attributes).unwrap())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_document_catch_all_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let extra = schema_builder.add_json_field("extra", STRING | STORED);
+        let schema = schema_builder.build();
+        let object = json_object(json!({"title": "Cannery Row", "color": "red", "pages": 185}));
+        let doc = JsonDocument::new(schema.clone(), object)?.with_catch_all_field(extra);
+
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter<JsonDocument> = index.writer_for_tests()?;
+        index_writer.add_document(doc)?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TermQuery::new(
+            Term::from_json_path_text(extra, "color", false, "red"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &DocSetCollector)?.len(), 1);
+
+        let stored_doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        let mut expected_doc = TantivyDocument::new();
+        expected_doc.add_text(title, "Cannery Row");
+        expected_doc.add_field_value(extra, &OwnedValue::from(json!({"color": "red"})));
+        expected_doc.add_field_value(extra, &OwnedValue::from(json!({"pages": 185})));
+        let mut stored_values: Vec<_> = stored_doc
+            .field_values()
+            .map(|(field, value)| (field, OwnedValue::from(value)))
+            .collect();
+        let mut expected_values: Vec<_> = expected_doc
+            .field_values()
+            .map(|(field, value)| (field, OwnedValue::from(value)))
+            .collect();
+        stored_values.sort_by_key(|(field, _)| *field);
+        expected_values.sort_by_key(|(field, _)| *field);
+        assert_eq!(stored_values, expected_values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_document_invalid_values() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("count", INDEXED);
+        schema_builder.add_facet_field("category", FacetOptions::default());
+        let schema = schema_builder.build();
+        assert!(matches!(
+            JsonDocument::new(schema.clone(), json_object(json!({"count": [1, -1]}))),
+            Err(DocParsingError::NestedValueError { path, .. }) if path == "count[1]"
+        ));
+        assert!(matches!(
+            JsonDocument::new(schema.clone(), json_object(json!({"count": "1"}))),
+            Err(DocParsingError::ValueError(..))
+        ));
+        // Facets cannot be borrowed from the JSON object.
+        assert!(matches!(
+            JsonDocument::new(schema, json_object(json!({"category": "/a/b"}))),
+            Err(DocParsingError::ValueError(..))
+        ));
+    }
+}
@@ -0,0 +1,401 @@
+//! Parsing of JSON documents, and of JSON lines, into [`TantivyDocument`]s.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+use serde::Deserializer;
+
+use super::default_document::ValuePath;
+use super::{DocParsingError, ParseMode, TantivyDocument};
+use crate::schema::{Field, Schema};
+
+/// Parses JSON objects into [`TantivyDocument`]s, like [`TantivyDocument::parse_json`].
+///
+/// Unlike [`TantivyDocument::parse_json`], the parser does not build an intermediate
+/// [`serde_json::Map`]: the values are added to the document as the object is read, and the
+/// values of the keys that are not fields of the schema are skipped without being built. The
+/// fields of the keys are cached, so that parsing many documents only looks each key up in the
+/// schema once.
+///
+/// The fields are added in the order of the keys in the object. If a key appears several
+/// times, all its values are added.
+///
+/// ```
+/// use tantivy::schema::{JsonDocParser, Schema, Value, STORED, TEXT};
+/// use tantivy::TantivyDocument;
+///
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let schema = schema_builder.build();
+///
+/// let json_lines = "{\"title\": \"Of Mice and Men\"}\n{\"title\": \"East of Eden\"}\n";
+/// let docs: Vec<TantivyDocument> = JsonDocParser::new(schema)
+///     .parse_lines(json_lines.as_bytes())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(docs.len(), 2);
+/// assert_eq!(docs[1].get_first(title).and_then(|value| value.as_str()), Some("East of Eden"));
+/// ```
+#[derive(Clone)]
+pub struct JsonDocParser {
+    schema: Schema,
+    parse_mode: ParseMode,
+    fields: HashMap<String, Option<Field>>,
+}
+
+impl JsonDocParser {
+    /// Creates a parser for the documents of `schema`, ignoring the keys that are not fields of
+    /// the schema.
+    pub fn new(schema: Schema) -> JsonDocParser {
+        JsonDocParser::with_mode(schema, ParseMode::Lenient)
+    }
+
+    /// Creates a parser for the documents of `schema`, handling the keys that are not fields of
+    /// the schema according to `parse_mode`.
+    pub fn with_mode(schema: Schema, parse_mode: ParseMode) -> JsonDocParser {
+        JsonDocParser {
+            schema,
+            parse_mode,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Returns the schema of the parsed documents.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Parses a JSON object into a document.
+    pub fn parse(&mut self, doc_json: &str) -> Result<TantivyDocument, DocParsingError> {
+        self.parse_slice(doc_json.as_bytes())
+    }
+
+    /// Parses a JSON object, given as UTF-8 bytes, into a document.
+    pub fn parse_slice(&mut self, doc_json: &[u8]) -> Result<TantivyDocument, DocParsingError> {
+        let mut error = None;
+        let mut deserializer = serde_json::Deserializer::from_slice(doc_json);
+        let doc = DocSeed {
+            parser: self,
+            error: &mut error,
+        }
+        .deserialize(&mut deserializer)
+        .and_then(|doc| deserializer.end().map(|_| doc));
+        match (doc, error) {
+            (_, Some(error)) => Err(error),
+            (Ok(doc), None) => Ok(doc),
+            (Err(_), None) => Err(DocParsingError::invalid_json(&String::from_utf8_lossy(
+                doc_json,
+            ))),
+        }
+    }
+
+    /// Returns an iterator over the documents of `reader`, which holds one JSON object per
+    /// line.
+    ///
+    /// Blank lines are skipped. The errors are returned as [`DocParsingError::Line`], along
+    /// with the number of their line, starting from 1. The iterator continues after the lines
+    /// which cannot be parsed, and stops after an error reading `reader`.
+    pub fn parse_lines<R: BufRead>(self, reader: R) -> JsonLines<R> {
+        JsonLines {
+            parser: self,
+            reader: Some(reader),
+            line_buffer: Vec::new(),
+            line_num: 0,
+        }
+    }
+
+    /// Resolves the field of a key, in the cache or else in the schema.
+    fn resolve_field(&mut self, key: &str) -> Result<Option<Field>, DocParsingError> {
+        let field = match self.fields.get(key) {
+            Some(field) => *field,
+            None => {
+                let field = self.schema.get_field(key).ok();
+                self.fields.insert(key.to_string(), field);
+                field
+            }
+        };
+        match (field, self.parse_mode) {
+            (None, ParseMode::Strict) => Err(DocParsingError::NoSuchFieldInSchema(key.to_string())),
+            (field, _) => Ok(field),
+        }
+    }
+}
+
+impl fmt::Debug for JsonDocParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonDocParser")
+            .field("parse_mode", &self.parse_mode)
+            .finish()
+    }
+}
+
+/// Iterator over the documents of JSON lines, see [`JsonDocParser::parse_lines`].
+pub struct JsonLines<R> {
+    parser: JsonDocParser,
+    // `None` once the reader has failed.
+    reader: Option<R>,
+    line_buffer: Vec<u8>,
+    line_num: usize,
+}
+
+impl<R> JsonLines<R> {
+    /// Returns the parser, to parse other documents with the fields it has cached.
+    pub fn into_parser(self) -> JsonDocParser {
+        self.parser
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLines<R> {
+    type Item = Result<TantivyDocument, DocParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let reader = self.reader.as_mut()?;
+            self.line_buffer.clear();
+            self.line_num += 1;
+            let line_error = |line_num, error| DocParsingError::Line(line_num, Box::new(error));
+            match reader.read_until(b'\n', &mut self.line_buffer) {
+                Ok(0) => {
+                    self.reader = None;
+                    return None;
+                }
+                Ok(_) => {}
+                Err(io_error) => {
+                    self.reader = None;
+                    let error = DocParsingError::ReadError(io_error.to_string());
+                    return Some(Err(line_error(self.line_num, error)));
+                }
+            }
+            if self.line_buffer.trim_ascii().is_empty() {
+                continue;
+            }
+            return Some(
+                self.parser
+                    .parse_slice(&self.line_buffer)
+                    .map_err(|error| line_error(self.line_num, error)),
+            );
+        }
+    }
+}
+
+/// Deserializes a document, keeping the [`DocParsingError`] of the values which cannot be
+/// parsed, since serde errors only hold a message.
+struct DocSeed<'p> {
+    parser: &'p mut JsonDocParser,
+    error: &'p mut Option<DocParsingError>,
+}
+
+impl DocSeed<'_> {
+    fn fail<E: serde::de::Error>(&mut self, error: DocParsingError) -> E {
+        let serde_error = E::custom(&error);
+        *self.error = Some(error);
+        serde_error
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for DocSeed<'_> {
+    type Value = TantivyDocument;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DocSeed<'_> {
+    type Value = TantivyDocument;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        let mut doc = TantivyDocument::default();
+        while let Some(key) = map.next_key_seed(KeySeed)? {
+            let field = match self.parser.resolve_field(&key) {
+                Ok(Some(field)) => field,
+                Ok(None) => {
+                    map.next_value::<IgnoredAny>()?;
+                    continue;
+                }
+                Err(error) => return Err(self.fail(error)),
+            };
+            let field_type = self.parser.schema.get_field_entry(field).field_type();
+            let (json_values, is_array) = match map.next_value()? {
+                serde_json::Value::Array(json_items) => (json_items, true),
+                json_value => (vec![json_value], false),
+            };
+            for (idx, json_value) in json_values.into_iter().enumerate() {
+                match field_type.value_from_json(json_value) {
+                    Ok(value) => doc.add_field_value(field, &value),
+                    Err(error) => {
+                        let path = ValuePath::new(&key);
+                        let error = if is_array {
+                            path.index(idx).value_error(error)
+                        } else {
+                            path.value_error(error)
+                        };
+                        return Err(self.fail(error));
+                    }
+                }
+            }
+        }
+        Ok(doc)
+    }
+}
+
+/// Deserializes a key without allocating, unless it holds escaped characters.
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Cow<'de, str>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed {
+    type Value = Cow<'de, str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E: serde::de::Error>(self, key: &'de str) -> Result<Self::Value, E> {
+        Ok(key.into())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, key: &str) -> Result<Self::Value, E> {
+        Ok(key.to_string().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, BufRead, BufReader, Read};
+
+    use super::JsonDocParser;
+    use crate::schema::*;
+    use crate::TantivyDocument;
+
+    fn schema() -> (Schema, Field, Field) {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        (schema_builder.build(), title, count)
+    }
+
+    #[test]
+    fn test_parse_like_parse_json() {
+        let (schema, title, count) = schema();
+        let mut parser = JsonDocParser::new(schema.clone());
+        for doc_json in [
+            r#"{"title": "hello", "count": 3}"#,
+            r#"{"count": [1, 2], "unknown": {"nested": [1, "a"]}, "title": ["a", "b"]}"#,
+            r#"{"ti\u0074le": "escaped \"key\""}"#,
+            r#"{}"#,
+        ] {
+            let doc = parser.parse(doc_json).unwrap();
+            let expected_doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+            for field in [title, count] {
+                let values = |doc: &TantivyDocument| {
+                    doc.get_all(field).map(OwnedValue::from).collect::<Vec<_>>()
+                };
+                assert_eq!(values(&doc), values(&expected_doc));
+            }
+        }
+        // Unlike `parse_json`, all the values of duplicate keys are kept.
+        let doc = parser
+            .parse(r#"{"title": "first", "title": "second"}"#)
+            .unwrap();
+        assert_eq!(doc.get_all(title).count(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let (schema, _, _) = schema();
+        let mut parser = JsonDocParser::new(schema.clone());
+        assert!(matches!(
+            parser.parse(r#"{"count": "three"}"#),
+            Err(DocParsingError::ValueError(field_name, _)) if field_name == "count"
+        ));
+        assert!(matches!(
+            parser.parse(r#"{"title": "a"} trailing"#),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            parser.parse(r#"["title"]"#),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+        assert_eq!(parser.parse(r#"{"titel": "a"}"#).unwrap().len(), 0);
+        let mut strict_parser = JsonDocParser::with_mode(schema, ParseMode::Strict);
+        assert_eq!(
+            strict_parser.parse(r#"{"titel": "a"}"#),
+            Err(DocParsingError::NoSuchFieldInSchema("titel".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let (schema, title, _) = schema();
+        let json_lines = [
+            r#"{"title": "first"}"#,
+            "\r\n\n  \n",
+            r#"{"count": "three"}"#,
+            "\n",
+            r#"{"title": "last", "count": 1}"#,
+        ]
+        .concat();
+        let results: Vec<_> = JsonDocParser::new(schema)
+            .parse_lines(json_lines.as_bytes())
+            .collect();
+        assert_eq!(results.len(), 3);
+        let first_doc = results[0].as_ref().unwrap();
+        assert_eq!(
+            first_doc.get_first(title).and_then(|value| value.as_str()),
+            Some("first")
+        );
+        assert!(matches!(
+            &results[1],
+            Err(DocParsingError::Line(4, error)) if matches!(**error, DocParsingError::ValueError(..))
+        ));
+        let last_doc = results[2].as_ref().unwrap();
+        assert_eq!(
+            last_doc.get_first(title).and_then(|value| value.as_str()),
+            Some("last")
+        );
+    }
+
+    #[test]
+    fn test_parse_lines_invalid_utf8_and_read_error() {
+        let (schema, _, _) = schema();
+        let json_lines: &[u8] = b"{\"title\": \"\xff\"}\n{\"title\": \"ok\"}\n";
+        let results: Vec<_> = JsonDocParser::new(schema.clone())
+            .parse_lines(json_lines)
+            .collect();
+        assert!(matches!(&results[0], Err(DocParsingError::Line(1, _))));
+        assert!(results[1].is_ok());
+
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk failure"))
+            }
+        }
+        let reader: Box<dyn BufRead> =
+            Box::new(io::Cursor::new(b"{}\n".to_vec()).chain(BufReader::new(FailingReader)));
+        let results: Vec<_> = JsonDocParser::new(schema).parse_lines(reader).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            &results[1],
+            Err(DocParsingError::Line(2, error)) if matches!(**error, DocParsingError::ReadError(_))
+        ));
+    }
+}
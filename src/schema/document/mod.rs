@@ -159,25 +159,46 @@
 //!
 //! TODO: Complete this section...
 
+#[cfg(feature = "arrow")]
+mod arrow;
 mod de;
 mod default_document;
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive;
 mod existing_type_impls;
+mod json_document;
+mod json_lines;
+#[cfg(feature = "msgpack")]
+mod msgpack;
 mod owned_value;
+mod schema_doc;
 mod se;
 mod value;
 
 use std::collections::BTreeMap;
 use std::mem;
 
+#[cfg(feature = "arrow")]
+pub(crate) use self::arrow::documents_from_record_batch;
+#[cfg(feature = "arrow")]
+pub use self::arrow::ArrowFieldMapping;
 pub(crate) use self::de::BinaryDocumentDeserializer;
 pub use self::de::{
     ArrayAccess, DeserializeError, DocumentDeserialize, DocumentDeserializer, ObjectAccess,
     ValueDeserialize, ValueDeserializer, ValueType, ValueVisitor,
 };
 pub use self::default_document::{
-    CompactDocArrayIter, CompactDocObjectIter, CompactDocValue, DocParsingError, TantivyDocument,
+    doc_binary_wrappers, CompactDocArrayAccessor, CompactDocArrayIter, CompactDocObjectIter,
+    CompactDocValue, DocParsingError, ParseMode, TantivyDocument,
 };
+pub use self::json_document::{
+    JsonDocument, JsonDocumentFieldsIter, JsonFieldValue, JsonFieldValueArrayIter,
+    JsonFieldValueObjectIter,
+};
+pub use self::json_lines::{JsonDocParser, JsonLines};
 pub use self::owned_value::OwnedValue;
+pub use self::schema_doc::{SchemaDoc, SchemaDocSeed};
 pub(crate) use self::se::BinaryDocumentSerializer;
 pub use self::value::{ReferenceValue, ReferenceValueLeaf, Value};
 use super::*;
@@ -273,4 +294,6 @@ pub(crate) mod type_codes {
 
     // Extended type codes
     pub const TOK_STR_EXT_CODE: u8 = 0;
+    // A string referencing the store dictionary of its field.
+    pub const DICT_STR_EXT_CODE: u8 = 1;
 }
@@ -0,0 +1,341 @@
+//! Parsing of [MessagePack](https://msgpack.org) documents into [`TantivyDocument`]s.
+//!
+//! MessagePack documents are parsed like JSON documents, with the same coercion rules, so that
+//! a document gives the same [`TantivyDocument`] whichever of the two formats it is sent in.
+//! Unlike JSON, MessagePack has binary values: they are added as is to bytes fields and json
+//! fields, instead of being encoded in base64.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value as JsonValue;
+
+use super::default_document::ValuePath;
+use super::{DocParsingError, OwnedValue, ParseMode, TantivyDocument};
+use crate::schema::field_type::ValueParsingError;
+use crate::schema::{FieldType, Schema};
+
+impl TantivyDocument {
+    /// Build a document object from a MessagePack map.
+    ///
+    /// The keys that are not fields of the schema are ignored.
+    pub fn from_msgpack(schema: &Schema, doc_msgpack: &[u8]) -> Result<Self, DocParsingError> {
+        Self::from_msgpack_with_mode(schema, doc_msgpack, ParseMode::Lenient)
+    }
+
+    /// Build a document object from a MessagePack map, handling the keys that are not fields of
+    /// the schema according to `parse_mode`.
+    ///
+    /// The values are converted like the values of [`TantivyDocument::parse_json_with_mode`].
+    /// Binary values are accepted by bytes fields, and kept as bytes in json fields.
+    pub fn from_msgpack_with_mode(
+        schema: &Schema,
+        doc_msgpack: &[u8],
+        parse_mode: ParseMode,
+    ) -> Result<Self, DocParsingError> {
+        let entries: BTreeMap<String, WireValue> = rmp_serde::from_slice(doc_msgpack)
+            .map_err(|err| DocParsingError::InvalidJson(err.to_string()))?;
+        let mut doc = TantivyDocument::default();
+        for (field_name, wire_value) in entries {
+            let Some(field) = parse_mode.resolve_field(schema, &field_name)? else {
+                continue;
+            };
+            let field_type = schema.get_field_entry(field).field_type();
+            // Like in JSON, arrays hold several values of the field.
+            let (wire_values, is_array) = match wire_value {
+                WireValue::Array(elements) => (elements, true),
+                wire_value => (vec![wire_value], false),
+            };
+            for (idx, wire_value) in wire_values.into_iter().enumerate() {
+                let value = wire_value.into_field_value(field_type).map_err(|err| {
+                    let path = ValuePath::new(&field_name);
+                    if is_array {
+                        path.index(idx).value_error(err)
+                    } else {
+                        path.value_error(err)
+                    }
+                })?;
+                doc.add_field_value(field, &value);
+            }
+        }
+        Ok(doc)
+    }
+}
+
+/// A value read from MessagePack: a JSON value, which may hold binary values.
+enum WireValue {
+    /// Null, boolean, number or string.
+    Leaf(JsonValue),
+    Bytes(Vec<u8>),
+    Array(Vec<WireValue>),
+    // Sorted by key, keeping the last value of duplicate keys, like a `serde_json::Map`.
+    Object(BTreeMap<String, WireValue>),
+}
+
+impl WireValue {
+    fn has_bytes(&self) -> bool {
+        match self {
+            WireValue::Leaf(_) => false,
+            WireValue::Bytes(_) => true,
+            WireValue::Array(elements) => elements.iter().any(WireValue::has_bytes),
+            WireValue::Object(entries) => entries.values().any(WireValue::has_bytes),
+        }
+    }
+
+    fn into_field_value(self, field_type: &FieldType) -> Result<OwnedValue, ValueParsingError> {
+        if self.has_bytes() {
+            field_type.coerce_value(self.into_owned_value())
+        } else {
+            field_type.value_from_json(self.into_json())
+        }
+    }
+
+    /// Converts a value without binary values into JSON.
+    fn into_json(self) -> JsonValue {
+        match self {
+            WireValue::Leaf(json) => json,
+            WireValue::Bytes(_) => unreachable!("binary values have no JSON counterpart"),
+            WireValue::Array(elements) => {
+                JsonValue::Array(elements.into_iter().map(WireValue::into_json).collect())
+            }
+            WireValue::Object(entries) => JsonValue::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_json()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Converts a value like `OwnedValue::from(serde_json::Value)`, keeping the binary values.
+    fn into_owned_value(self) -> OwnedValue {
+        match self {
+            WireValue::Leaf(json) => OwnedValue::from(json),
+            WireValue::Bytes(bytes) => OwnedValue::Bytes(bytes),
+            WireValue::Array(elements) => {
+                OwnedValue::Array(elements.into_iter().map(WireValue::into_owned_value).collect())
+            }
+            WireValue::Object(entries) => OwnedValue::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_owned_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WireValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_any(WireValueVisitor)
+    }
+}
+
+struct WireValueVisitor;
+
+impl<'de> Visitor<'de> for WireValueVisitor {
+    type Value = WireValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a MessagePack value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::Null))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::Null))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<WireValue, D::Error>
+    where D: Deserializer<'de> {
+        WireValue::deserialize(deserializer)
+    }
+
+    fn visit_bool<E: de::Error>(self, val: bool) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::Bool(val)))
+    }
+
+    fn visit_i64<E: de::Error>(self, val: i64) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::from(val)))
+    }
+
+    fn visit_u64<E: de::Error>(self, val: u64) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::from(val)))
+    }
+
+    fn visit_f64<E: de::Error>(self, val: f64) -> Result<WireValue, E> {
+        // Like `serde_json`, non finite numbers are converted to null.
+        Ok(WireValue::Leaf(JsonValue::from(val)))
+    }
+
+    fn visit_str<E: de::Error>(self, val: &str) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::String(val.to_string())))
+    }
+
+    fn visit_string<E: de::Error>(self, val: String) -> Result<WireValue, E> {
+        Ok(WireValue::Leaf(JsonValue::String(val)))
+    }
+
+    fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<WireValue, E> {
+        Ok(WireValue::Bytes(val.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, val: Vec<u8>) -> Result<WireValue, E> {
+        Ok(WireValue::Bytes(val))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<WireValue, A::Error>
+    where A: SeqAccess<'de> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(WireValue::Array(elements))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<WireValue, A::Error>
+    where A: MapAccess<'de> {
+        let mut entries = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry::<String, WireValue>()? {
+            entries.insert(key, value);
+        }
+        Ok(WireValue::Object(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Serializer};
+
+    use crate::schema::*;
+    use crate::{DateTime, TantivyDocument};
+
+    /// Serializes bytes as a MessagePack binary value.
+    struct Blob<'a>(&'a [u8]);
+
+    impl Serialize for Blob<'_> {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+
+    fn test_schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_u64_field("count", STORED);
+        schema_builder.add_i64_field("delta", STORED);
+        schema_builder.add_f64_field("score", STORED);
+        schema_builder.add_date_field("date", STORED);
+        schema_builder.add_bytes_field("payload", STORED);
+        schema_builder.add_json_field("attributes", STORED);
+        schema_builder.build()
+    }
+
+    #[test]
+    fn test_msgpack_same_as_json() {
+        let schema = test_schema();
+        let doc_json = r#"{
+            "title": ["first", "second"],
+            "count": 4,
+            "delta": -3,
+            "score": 0.5,
+            "date": "2024-01-02T03:04:05Z",
+            "payload": "AAH/",
+            "attributes": {"b": [1, -1, 1.5, null], "a": {"nested": true}, "when": "2024-01-02T03:04:05Z"},
+            "unknown": 1
+        }"#;
+        let json_value: serde_json::Value = serde_json::from_str(doc_json).unwrap();
+        let doc_msgpack = rmp_serde::to_vec(&json_value).unwrap();
+        let doc = TantivyDocument::from_msgpack(&schema, &doc_msgpack).unwrap();
+        let expected_doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+        assert_eq!(doc, expected_doc);
+        assert_eq!(doc.len(), 8);
+    }
+
+    #[test]
+    fn test_msgpack_binary_values() {
+        let schema = test_schema();
+        let payload = schema.get_field("payload").unwrap();
+        let attributes = schema.get_field("attributes").unwrap();
+        let date = schema.get_field("date").unwrap();
+        #[derive(Serialize)]
+        struct Attributes<'a> {
+            blob: Blob<'a>,
+            when: &'a str,
+        }
+        #[derive(Serialize)]
+        struct Doc<'a> {
+            payload: Blob<'a>,
+            attributes: Attributes<'a>,
+            date: i64,
+        }
+        let doc_msgpack = rmp_serde::to_vec_named(&Doc {
+            payload: Blob(b"\x00\x01\xff"),
+            attributes: Attributes {
+                blob: Blob(b"raw"),
+                when: "2024-01-02T03:04:05Z",
+            },
+            date: 1_000_000,
+        })
+        .unwrap();
+        let doc = TantivyDocument::from_msgpack(&schema, &doc_msgpack).unwrap();
+        assert_eq!(
+            doc.get_first(payload).map(OwnedValue::from),
+            Some(OwnedValue::Bytes(b"\x00\x01\xff".to_vec()))
+        );
+        let when = DateTime::from_timestamp_secs(1_704_164_645);
+        assert_eq!(
+            doc.get_first(attributes).map(OwnedValue::from),
+            Some(OwnedValue::Object(vec![
+                ("blob".to_string(), OwnedValue::Bytes(b"raw".to_vec())),
+                ("when".to_string(), OwnedValue::Date(when)),
+            ]))
+        );
+        // Integers are accepted by date fields, like in JSON.
+        assert_eq!(
+            doc.get_first(date).map(OwnedValue::from),
+            TantivyDocument::parse_json(&schema, r#"{"date": 1000000}"#)
+                .unwrap()
+                .get_first(date)
+                .map(OwnedValue::from)
+        );
+    }
+
+    #[test]
+    fn test_msgpack_errors() {
+        let schema = test_schema();
+        #[derive(Serialize)]
+        struct Doc<'a> {
+            count: Blob<'a>,
+        }
+        let doc_msgpack = rmp_serde::to_vec_named(&Doc { count: Blob(b"1") }).unwrap();
+        assert!(matches!(
+            TantivyDocument::from_msgpack(&schema, &doc_msgpack),
+            Err(DocParsingError::ValueError(field_name, _)) if field_name == "count"
+        ));
+        let doc_msgpack = rmp_serde::to_vec(&serde_json::json!({"count": -1})).unwrap();
+        assert_eq!(
+            TantivyDocument::from_msgpack(&schema, &doc_msgpack),
+            TantivyDocument::parse_json(&schema, r#"{"count": -1}"#)
+        );
+        assert!(matches!(
+            TantivyDocument::from_msgpack(&schema, b"\xc1"),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+        let doc_msgpack = rmp_serde::to_vec(&serde_json::json!([1, 2])).unwrap();
+        assert!(matches!(
+            TantivyDocument::from_msgpack(&schema, &doc_msgpack),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+        let doc_msgpack = rmp_serde::to_vec(&serde_json::json!({"titel": "a"})).unwrap();
+        assert_eq!(
+            TantivyDocument::from_msgpack_with_mode(&schema, &doc_msgpack, ParseMode::Strict),
+            Err(DocParsingError::NoSuchFieldInSchema("titel".to_string()))
+        );
+    }
+}
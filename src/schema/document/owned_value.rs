@@ -293,24 +293,30 @@ impl<'de> serde::Deserialize<'de> for OwnedValue {
     }
 }
 
+impl From<ReferenceValueLeaf<'_>> for OwnedValue {
+    fn from(leaf: ReferenceValueLeaf<'_>) -> OwnedValue {
+        match leaf {
+            ReferenceValueLeaf::Null => OwnedValue::Null,
+            ReferenceValueLeaf::Str(val) => OwnedValue::Str(val.to_string()),
+            ReferenceValueLeaf::U64(val) => OwnedValue::U64(val),
+            ReferenceValueLeaf::I64(val) => OwnedValue::I64(val),
+            ReferenceValueLeaf::F64(val) => OwnedValue::F64(val),
+            ReferenceValueLeaf::Date(val) => OwnedValue::Date(val),
+            ReferenceValueLeaf::Facet(val) => {
+                OwnedValue::Facet(Facet::from_encoded_string(val.to_string()))
+            }
+            ReferenceValueLeaf::Bytes(val) => OwnedValue::Bytes(val.to_vec()),
+            ReferenceValueLeaf::IpAddr(val) => OwnedValue::IpAddr(val),
+            ReferenceValueLeaf::Bool(val) => OwnedValue::Bool(val),
+            ReferenceValueLeaf::PreTokStr(val) => OwnedValue::PreTokStr(*val),
+        }
+    }
+}
+
 impl<'a, V: Value<'a>> From<ReferenceValue<'a, V>> for OwnedValue {
     fn from(val: ReferenceValue<'a, V>) -> OwnedValue {
         match val {
-            ReferenceValue::Leaf(leaf) => match leaf {
-                ReferenceValueLeaf::Null => OwnedValue::Null,
-                ReferenceValueLeaf::Str(val) => OwnedValue::Str(val.to_string()),
-                ReferenceValueLeaf::U64(val) => OwnedValue::U64(val),
-                ReferenceValueLeaf::I64(val) => OwnedValue::I64(val),
-                ReferenceValueLeaf::F64(val) => OwnedValue::F64(val),
-                ReferenceValueLeaf::Date(val) => OwnedValue::Date(val),
-                ReferenceValueLeaf::Facet(val) => {
-                    OwnedValue::Facet(Facet::from_encoded_string(val.to_string()))
-                }
-                ReferenceValueLeaf::Bytes(val) => OwnedValue::Bytes(val.to_vec()),
-                ReferenceValueLeaf::IpAddr(val) => OwnedValue::IpAddr(val),
-                ReferenceValueLeaf::Bool(val) => OwnedValue::Bool(val),
-                ReferenceValueLeaf::PreTokStr(val) => OwnedValue::PreTokStr(*val.clone()),
-            },
+            ReferenceValue::Leaf(leaf) => leaf.into(),
             ReferenceValue::Array(val) => {
                 OwnedValue::Array(val.map(|v| v.as_value().into()).collect())
             }
@@ -449,6 +455,71 @@ impl From<serde_json::Map<String, serde_json::Value>> for OwnedValue {
     }
 }
 
+// The `TryFrom<OwnedValue>` conversions give the value back as an error if it has another type.
+
+macro_rules! try_from_owned_value {
+    ($typ:ty, $variant:ident) => {
+        impl TryFrom<OwnedValue> for $typ {
+            type Error = OwnedValue;
+
+            fn try_from(value: OwnedValue) -> Result<Self, OwnedValue> {
+                match value {
+                    OwnedValue::$variant(val) => Ok(val),
+                    _ => Err(value),
+                }
+            }
+        }
+    };
+}
+
+try_from_owned_value!(String, Str);
+try_from_owned_value!(PreTokenizedString, PreTokStr);
+try_from_owned_value!(bool, Bool);
+try_from_owned_value!(DateTime, Date);
+try_from_owned_value!(Facet, Facet);
+try_from_owned_value!(Vec<u8>, Bytes);
+try_from_owned_value!(Ipv6Addr, IpAddr);
+
+/// Accepts a `u64`, or a non-negative `i64`.
+impl TryFrom<OwnedValue> for u64 {
+    type Error = OwnedValue;
+
+    fn try_from(value: OwnedValue) -> Result<Self, OwnedValue> {
+        match value {
+            OwnedValue::U64(val) => Ok(val),
+            OwnedValue::I64(val) => u64::try_from(val).map_err(|_| value),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Accepts an `i64`, or a `u64` that fits in an `i64`.
+impl TryFrom<OwnedValue> for i64 {
+    type Error = OwnedValue;
+
+    fn try_from(value: OwnedValue) -> Result<Self, OwnedValue> {
+        match value {
+            OwnedValue::I64(val) => Ok(val),
+            OwnedValue::U64(val) => i64::try_from(val).map_err(|_| value),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Accepts a `f64`, or an integer converted to `f64`.
+impl TryFrom<OwnedValue> for f64 {
+    type Error = OwnedValue;
+
+    fn try_from(value: OwnedValue) -> Result<Self, OwnedValue> {
+        match value {
+            OwnedValue::F64(val) => Ok(val),
+            OwnedValue::U64(val) => Ok(val as f64),
+            OwnedValue::I64(val) => Ok(val as f64),
+            _ => Err(value),
+        }
+    }
+}
+
 /// A wrapper type for iterating over a serde_json object producing reference values.
 pub struct ObjectMapIter<'a>(std::slice::Iter<'a, (String, OwnedValue)>);
 
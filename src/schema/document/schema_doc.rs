@@ -0,0 +1,279 @@
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::{DeserializeSeed, Error as _};
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Map;
+
+use super::{CompactDocValue, Document, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::schema::{Facet, Schema};
+use crate::TantivyDocument;
+
+/// A [`TantivyDocument`] along with the [`Schema`] giving the names of its fields.
+///
+/// `SchemaDoc` serializes the document as a map from the field names to the arrays of their
+/// values, like [`Document::to_json`], without building an intermediate
+/// [`NamedFieldDocument`](crate::schema::NamedFieldDocument). Bytes are encoded in base64.
+///
+/// A document is deserialized with [`SchemaDoc::seed`], which parses the values according to
+/// the types of the fields, like [`TantivyDocument::from_json_object`].
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use tantivy::schema::{SchemaDoc, Schema, STORED, TEXT};
+/// use tantivy::{doc, TantivyDocument};
+///
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let schema = schema_builder.build();
+/// let doc = doc!(title => "The Old Man and the Sea");
+///
+/// let json = serde_json::to_string(&SchemaDoc::new(&schema, &doc)).unwrap();
+/// assert_eq!(json, r#"{"title":["The Old Man and the Sea"]}"#);
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(&json);
+/// let deserialized_doc: TantivyDocument =
+///     SchemaDoc::seed(&schema).deserialize(&mut deserializer).unwrap();
+/// assert_eq!(deserialized_doc, doc);
+/// ```
+#[derive(Clone, Copy)]
+pub struct SchemaDoc<'a> {
+    schema: &'a Schema,
+    doc: &'a TantivyDocument,
+}
+
+impl<'a> SchemaDoc<'a> {
+    /// Wraps `doc`, whose fields are named after `schema`.
+    pub fn new(schema: &'a Schema, doc: &'a TantivyDocument) -> SchemaDoc<'a> {
+        SchemaDoc { schema, doc }
+    }
+
+    /// Returns a [`DeserializeSeed`] deserializing a [`TantivyDocument`] following `schema`.
+    ///
+    /// The keys that are not fields of the schema are ignored.
+    pub fn seed(schema: &Schema) -> SchemaDocSeed<'_> {
+        SchemaDocSeed { schema }
+    }
+}
+
+impl fmt::Debug for SchemaDoc<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SchemaDoc").field(self.doc).finish()
+    }
+}
+
+impl Serialize for SchemaDoc<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let sorted_field_values = self.doc.get_sorted_field_values();
+        let mut map = serializer.serialize_map(Some(sorted_field_values.len()))?;
+        for (field, values) in &sorted_field_values {
+            let values: Vec<SerializableValue> =
+                values.iter().copied().map(SerializableValue).collect();
+            map.serialize_entry(self.schema.get_field_name(*field), &values)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes a value of a [`TantivyDocument`] like the equivalent
+/// [`OwnedValue`](crate::schema::OwnedValue).
+struct SerializableValue<'a>(CompactDocValue<'a>);
+
+impl Serialize for SerializableValue<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        match self.0.as_value() {
+            ReferenceValue::Leaf(leaf) => match leaf {
+                ReferenceValueLeaf::Null => serializer.serialize_unit(),
+                ReferenceValueLeaf::Str(text) => serializer.serialize_str(text),
+                ReferenceValueLeaf::PreTokStr(pre_tok_str) => pre_tok_str.serialize(serializer),
+                ReferenceValueLeaf::U64(val) => serializer.serialize_u64(val),
+                ReferenceValueLeaf::I64(val) => serializer.serialize_i64(val),
+                ReferenceValueLeaf::F64(val) => serializer.serialize_f64(val),
+                ReferenceValueLeaf::Bool(val) => serializer.serialize_bool(val),
+                ReferenceValueLeaf::Date(date) => {
+                    time::serde::rfc3339::serialize(&date.into_utc(), serializer)
+                }
+                ReferenceValueLeaf::Facet(encoded_facet) => {
+                    Facet::from_encoded_string(encoded_facet.to_string()).serialize(serializer)
+                }
+                ReferenceValueLeaf::Bytes(bytes) => serializer.serialize_str(&BASE64.encode(bytes)),
+                ReferenceValueLeaf::IpAddr(ip_v6) => {
+                    // Ensure IpV4 addresses get serialized as IpV4, but excluding IpV6 loopback.
+                    if let Some(ip_v4) = ip_v6.to_ipv4_mapped() {
+                        ip_v4.serialize(serializer)
+                    } else {
+                        ip_v6.serialize(serializer)
+                    }
+                }
+            },
+            ReferenceValue::Array(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(&SerializableValue(element))?;
+                }
+                seq.end()
+            }
+            ReferenceValue::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, &SerializableValue(value))?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// [`DeserializeSeed`] deserializing a [`TantivyDocument`] following a [`Schema`].
+///
+/// See [`SchemaDoc::seed`].
+#[derive(Clone, Copy, Debug)]
+pub struct SchemaDocSeed<'a> {
+    schema: &'a Schema,
+}
+
+impl<'de> DeserializeSeed<'de> for SchemaDocSeed<'_> {
+    type Value = TantivyDocument;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        let json_obj = Map::<String, serde_json::Value>::deserialize(deserializer)?;
+        TantivyDocument::from_json_object(self.schema, json_obj).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use common::DateTime;
+    use serde::de::DeserializeSeed;
+
+    use super::SchemaDoc;
+    use crate::schema::{Facet, OwnedValue, Schema, FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::tokenizer::{PreTokenizedString, Token};
+    use crate::TantivyDocument;
+
+    fn round_trip(schema: &Schema, doc: &TantivyDocument) -> TantivyDocument {
+        let json = serde_json::to_vec(&SchemaDoc::new(schema, doc)).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_slice(&json);
+        SchemaDoc::seed(schema)
+            .deserialize(&mut deserializer)
+            .unwrap()
+    }
+
+    /// `SchemaDoc` groups the values by field name, so that only the order of the values of each
+    /// field is kept.
+    fn assert_same_field_values(schema: &Schema, left: &TantivyDocument, right: &TantivyDocument) {
+        assert!(left.eq_unordered(right));
+        let field_values = |doc: &TantivyDocument, field| {
+            let mut field_doc = TantivyDocument::default();
+            for value in doc.get_all(field) {
+                field_doc.add_field_value(field, &OwnedValue::from(value));
+            }
+            field_doc
+        };
+        for (field, _) in schema.fields() {
+            assert_eq!(field_values(left, field), field_values(right, field));
+        }
+    }
+
+    #[test]
+    fn test_schema_doc_round_trip() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let delta = schema_builder.add_i64_field("delta", INDEXED | STORED);
+        let score = schema_builder.add_f64_field("score", FAST | STORED);
+        let flag = schema_builder.add_bool_field("flag", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", STORED);
+        let category = schema_builder.add_facet_field("category", STORED);
+        let bytes = schema_builder.add_bytes_field("bytes", STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(title, "first title");
+        doc.add_text(title, "second title");
+        doc.add_pre_tokenized_text(
+            id,
+            PreTokenizedString {
+                text: "pre tokenized".to_string(),
+                tokens: vec![Token::new(0, 3, 0, "pre".to_string())],
+            },
+        );
+        doc.add_u64(count, u64::MAX);
+        doc.add_i64(delta, -3);
+        doc.add_f64(score, 0.25);
+        doc.add_bool(flag, true);
+        doc.add_date(
+            date,
+            DateTime::from_timestamp_nanos(1_700_000_000_123_456_789),
+        );
+        doc.add_ip_addr(ip, Ipv4Addr::new(127, 0, 0, 1).to_ipv6_mapped());
+        doc.add_ip_addr(ip, Ipv6Addr::LOCALHOST);
+        doc.add_facet(category, Facet::from("/books/fiction"));
+        doc.add_bytes(bytes, &[0u8, 255, 3][..]);
+        doc.add_bytes(bytes, &[][..]);
+        doc.add_object(
+            attributes,
+            BTreeMap::from([
+                (
+                    "nested".to_string(),
+                    OwnedValue::Object(vec![
+                        ("empty".to_string(), OwnedValue::Array(Vec::new())),
+                        (
+                            "list".to_string(),
+                            OwnedValue::Array(vec![
+                                OwnedValue::U64(1),
+                                OwnedValue::I64(-1),
+                                OwnedValue::F64(1.5),
+                                OwnedValue::Null,
+                            ]),
+                        ),
+                    ]),
+                ),
+                ("name".to_string(), OwnedValue::Str("nested".to_string())),
+            ]),
+        );
+
+        // The values are serialized like with `to_json`.
+        let json: serde_json::Value = serde_json::to_value(SchemaDoc::new(&schema, &doc)).unwrap();
+        let expected: serde_json::Value =
+            serde_json::from_str(&crate::schema::Document::to_json(&doc, &schema)).unwrap();
+        assert_eq!(json, expected);
+        assert_eq!(json["bytes"], serde_json::json!(["AP8D", ""]));
+        assert_eq!(json["ip"], serde_json::json!(["127.0.0.1", "::1"]));
+
+        // JSON numbers are read back as `i64` when they fit, and are still equal.
+        assert_same_field_values(&schema, &round_trip(&schema, &doc), &doc);
+        assert_eq!(
+            round_trip(&schema, &TantivyDocument::default()),
+            TantivyDocument::default()
+        );
+    }
+
+    #[test]
+    fn test_schema_doc_deserialize_error() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{"count": ["abc"]}"#);
+        assert!(SchemaDoc::seed(&schema)
+            .deserialize(&mut deserializer)
+            .is_err());
+        // Unknown keys are ignored.
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{"other": [1]}"#);
+        let doc = SchemaDoc::seed(&schema)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(doc, TantivyDocument::default());
+    }
+}
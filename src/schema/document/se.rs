@@ -8,11 +8,14 @@ use common::{f64_to_u64, BinarySerializable, VInt};
 use super::{OwnedValue, ReferenceValueLeaf};
 use crate::schema::document::{type_codes, Document, ReferenceValue, Value};
 use crate::schema::Schema;
+use crate::store::StoreDictionariesWriter;
+use crate::tokenizer::PreTokenizedString;
 
 /// A serializer writing documents which implement [`Document`] to a provided writer.
 pub struct BinaryDocumentSerializer<'se, W> {
     writer: &'se mut W,
     schema: &'se Schema,
+    dictionaries: Option<&'se mut StoreDictionariesWriter>,
 }
 
 impl<'se, W> BinaryDocumentSerializer<'se, W>
@@ -20,7 +23,21 @@ where W: Write
 {
     /// Creates a new serializer with a provided writer.
     pub(crate) fn new(writer: &'se mut W, schema: &'se Schema) -> Self {
-        Self { writer, schema }
+        Self {
+            writer,
+            schema,
+            dictionaries: None,
+        }
+    }
+
+    /// Replaces the values of the dictionary encoded fields by their ordinal in the
+    /// dictionary of their field.
+    pub(crate) fn with_dictionaries(
+        mut self,
+        dictionaries: Option<&'se mut StoreDictionariesWriter>,
+    ) -> Self {
+        self.dictionaries = dictionaries;
+        self
     }
 
     /// Attempts to serialize a given document and write the output
@@ -39,9 +56,31 @@ where W: Write
         for (field, value_access) in stored_field_values() {
             field.serialize(self.writer)?;
 
+            let value = value_access.as_value();
+            let dictionary_ord = match (self.dictionaries.as_deref_mut(), &value) {
+                (Some(dictionaries), ReferenceValue::Leaf(ReferenceValueLeaf::Str(text))) => {
+                    dictionaries.ord_or_insert(field, text)
+                }
+                (
+                    Some(dictionaries),
+                    ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text)),
+                ) if !has_token_payloads(pre_tokenized_text) => {
+                    dictionaries.ord_or_insert(field, &pre_tokenized_text.text)
+                }
+                _ => None,
+            };
+
             let mut serializer = BinaryValueSerializer::new(self.writer);
-            match value_access.as_value() {
-                ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text)) => {
+            if let Some(ord) = dictionary_ord {
+                serializer.serialize_dictionary_ord(ord)?;
+                actual_length += 1;
+                continue;
+            }
+            match value {
+                // The tokens are only stored when they carry payloads.
+                ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text))
+                    if !has_token_payloads(&pre_tokenized_text) =>
+                {
                     serializer.serialize_value(ReferenceValue::Leaf::<&'_ OwnedValue>(
                         ReferenceValueLeaf::Str(&pre_tokenized_text.text),
                     ))?;
@@ -65,6 +104,14 @@ where W: Write
     }
 }
 
+/// Returns true if any token of the pre-tokenized text carries a payload.
+fn has_token_payloads(pre_tokenized_text: &PreTokenizedString) -> bool {
+    pre_tokenized_text
+        .tokens
+        .iter()
+        .any(|token| token.payload.is_some())
+}
+
 /// A serializer for a single value.
 pub struct BinaryValueSerializer<'se, W> {
     writer: &'se mut W,
@@ -167,6 +214,13 @@ where W: Write
         }
     }
 
+    /// Serializes a string as its ordinal in the store dictionary of its field.
+    fn serialize_dictionary_ord(&mut self, ord: u32) -> io::Result<()> {
+        self.write_type_code(type_codes::EXT_CODE)?;
+        self.write_type_code(type_codes::DICT_STR_EXT_CODE)?;
+        VInt(ord as u64).serialize(self.writer)
+    }
+
     fn write_type_code(&mut self, code: u8) -> io::Result<()> {
         code.serialize(self.writer)
     }
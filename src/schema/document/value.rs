@@ -3,6 +3,7 @@ use std::net::Ipv6Addr;
 
 use common::DateTime;
 
+use crate::schema::Facet;
 use crate::tokenizer::PreTokenizedString;
 
 /// A single field value.
@@ -224,6 +225,86 @@ impl<'a, T: Value<'a> + ?Sized> From<ReferenceValueLeaf<'a>> for ReferenceValue<
     }
 }
 
+// The `TryFrom<ReferenceValueLeaf>` conversions give the value back as an error if it has another
+// type.
+
+macro_rules! try_from_reference_value_leaf {
+    ($typ:ty, $variant:ident) => {
+        impl<'a> TryFrom<ReferenceValueLeaf<'a>> for $typ {
+            type Error = ReferenceValueLeaf<'a>;
+
+            fn try_from(value: ReferenceValueLeaf<'a>) -> Result<Self, ReferenceValueLeaf<'a>> {
+                match value {
+                    ReferenceValueLeaf::$variant(val) => Ok(val),
+                    _ => Err(value),
+                }
+            }
+        }
+    };
+    ($typ:ty, $variant:ident, $convert:expr) => {
+        impl<'a> TryFrom<ReferenceValueLeaf<'a>> for $typ {
+            type Error = ReferenceValueLeaf<'a>;
+
+            fn try_from(value: ReferenceValueLeaf<'a>) -> Result<Self, ReferenceValueLeaf<'a>> {
+                match value {
+                    ReferenceValueLeaf::$variant(val) => Ok(($convert)(val)),
+                    _ => Err(value),
+                }
+            }
+        }
+    };
+}
+
+try_from_reference_value_leaf!(String, Str, str::to_string);
+try_from_reference_value_leaf!(PreTokenizedString, PreTokStr, |val: Box<_>| *val);
+try_from_reference_value_leaf!(bool, Bool);
+try_from_reference_value_leaf!(DateTime, Date);
+try_from_reference_value_leaf!(Facet, Facet, |val: &str| Facet::from_encoded_string(
+    val.to_string()
+));
+try_from_reference_value_leaf!(Vec<u8>, Bytes, <[u8]>::to_vec);
+try_from_reference_value_leaf!(Ipv6Addr, IpAddr);
+
+/// Accepts a `u64`, or a non-negative `i64`.
+impl<'a> TryFrom<ReferenceValueLeaf<'a>> for u64 {
+    type Error = ReferenceValueLeaf<'a>;
+
+    fn try_from(value: ReferenceValueLeaf<'a>) -> Result<Self, ReferenceValueLeaf<'a>> {
+        match value {
+            ReferenceValueLeaf::U64(val) => Ok(val),
+            ReferenceValueLeaf::I64(val) => u64::try_from(val).map_err(|_| value),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Accepts an `i64`, or a `u64` that fits in an `i64`.
+impl<'a> TryFrom<ReferenceValueLeaf<'a>> for i64 {
+    type Error = ReferenceValueLeaf<'a>;
+
+    fn try_from(value: ReferenceValueLeaf<'a>) -> Result<Self, ReferenceValueLeaf<'a>> {
+        match value {
+            ReferenceValueLeaf::I64(val) => Ok(val),
+            ReferenceValueLeaf::U64(val) => i64::try_from(val).map_err(|_| value),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Accepts a `f64`, or an integer converted to `f64`.
+impl<'a> TryFrom<ReferenceValueLeaf<'a>> for f64 {
+    type Error = ReferenceValueLeaf<'a>;
+
+    fn try_from(value: ReferenceValueLeaf<'a>) -> Result<Self, ReferenceValueLeaf<'a>> {
+        match value {
+            ReferenceValueLeaf::F64(val) => Ok(val),
+            ReferenceValueLeaf::U64(val) => Ok(val as f64),
+            ReferenceValueLeaf::I64(val) => Ok(val as f64),
+            _ => Err(value),
+        }
+    }
+}
+
 impl<'a> ReferenceValueLeaf<'a> {
     #[inline]
     /// Returns if the value is `null` or not.
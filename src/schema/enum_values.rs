@@ -0,0 +1,460 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::schema::field_type::ValueParsingError;
+use crate::schema::{Field, FieldType, Schema};
+use crate::TantivyError;
+
+/// Defines what happens when a document holds a value which is not part of the
+/// dictionary of an enum field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownEnumValue {
+    /// The document is rejected.
+    #[default]
+    Reject,
+    /// The value is appended to the dictionary.
+    Register,
+}
+
+/// Dictionary of the values of an enum-like text field, such as a country code or a status.
+///
+/// The dictionary is owned by the schema, and persisted with it in the index meta. It is
+/// enforced when documents are parsed from JSON, see
+/// [`FieldType::value_from_json`](crate::schema::FieldType::value_from_json), and when they
+/// are added to the index.
+///
+/// Values are never removed from the dictionary, and keep their ordinal as new values get
+/// registered. Values are indexed as is: the term dictionary and the fast field column of
+/// the field already map them to small term ordinals.
+///
+/// If unknown values are registered, each segment records the values it registered, see
+/// [`SegmentMeta::registered_enum_values`](crate::index::SegmentMeta::registered_enum_values).
+/// They are appended to the dictionary of the schema saved on commit, and are discarded by
+/// a rollback. The registered values are part of the data of the index rather than of its
+/// configuration, and are ignored when comparing dictionaries.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "SerializedEnumValues", from = "SerializedEnumValues")]
+pub struct EnumValues {
+    values: Vec<String>,
+    registered_values: Vec<String>,
+    ordinals: HashMap<String, u64>,
+    on_unknown_value: UnknownEnumValue,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedEnumValues {
+    values: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    registered_values: Vec<String>,
+    #[serde(default)]
+    on_unknown_value: UnknownEnumValue,
+}
+
+impl From<EnumValues> for SerializedEnumValues {
+    fn from(enum_values: EnumValues) -> SerializedEnumValues {
+        SerializedEnumValues {
+            values: enum_values.values,
+            registered_values: enum_values.registered_values,
+            on_unknown_value: enum_values.on_unknown_value,
+        }
+    }
+}
+
+impl From<SerializedEnumValues> for EnumValues {
+    fn from(serialized: SerializedEnumValues) -> EnumValues {
+        EnumValues::new(serialized.values, serialized.on_unknown_value)
+            .with_registered_values(serialized.registered_values)
+    }
+}
+
+impl EnumValues {
+    /// Creates a dictionary from its initial values.
+    ///
+    /// Duplicate values are ignored.
+    pub fn new<S: Into<String>>(
+        values: impl IntoIterator<Item = S>,
+        on_unknown_value: UnknownEnumValue,
+    ) -> EnumValues {
+        let mut enum_values = EnumValues {
+            values: Vec::new(),
+            registered_values: Vec::new(),
+            ordinals: HashMap::new(),
+            on_unknown_value,
+        };
+        for value in values {
+            let value = value.into();
+            if enum_values.insert_ordinal(&value) {
+                enum_values.values.push(value);
+            }
+        }
+        enum_values
+    }
+
+    /// Returns what happens to values which are not part of the dictionary.
+    pub fn on_unknown_value(&self) -> UnknownEnumValue {
+        self.on_unknown_value
+    }
+
+    /// Returns the values of the dictionary, ordered by ordinal.
+    ///
+    /// The initial values come first, followed by the registered values.
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.values
+            .iter()
+            .chain(&self.registered_values)
+            .map(String::as_str)
+    }
+
+    /// Returns the values which were registered in the dictionary by committed segments,
+    /// ordered by ordinal.
+    pub fn registered_values(&self) -> &[String] {
+        &self.registered_values
+    }
+
+    /// Returns the ordinal of `value`, if it is part of the dictionary.
+    pub fn ordinal(&self, value: &str) -> Option<u64> {
+        self.ordinals.get(value).copied()
+    }
+
+    /// Returns the value associated with `ordinal`.
+    pub fn value(&self, ordinal: u64) -> Option<&str> {
+        let ordinal = usize::try_from(ordinal).ok()?;
+        match ordinal.checked_sub(self.values.len()) {
+            None => Some(&self.values[ordinal]),
+            Some(registered_ordinal) => self
+                .registered_values
+                .get(registered_ordinal)
+                .map(String::as_str),
+        }
+    }
+
+    /// Checks that `value` is part of the dictionary, unless unknown values are registered.
+    pub(crate) fn check_value(&self, value: &str) -> Result<(), ValueParsingError> {
+        if self.on_unknown_value == UnknownEnumValue::Reject && self.ordinal(value).is_none() {
+            return Err(ValueParsingError::UnknownEnumValue {
+                value: value.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the dictionary with the unknown values among `values` registered, in order.
+    pub(crate) fn with_registered_values<S: Into<String>>(
+        mut self,
+        values: impl IntoIterator<Item = S>,
+    ) -> EnumValues {
+        for value in values {
+            let value = value.into();
+            if self.insert_ordinal(&value) {
+                self.registered_values.push(value);
+            }
+        }
+        self
+    }
+
+    /// Assigns the next ordinal to `value`, returning `false` if it is already known.
+    fn insert_ordinal(&mut self, value: &str) -> bool {
+        if self.ordinals.contains_key(value) {
+            return false;
+        }
+        let ordinal = self.ordinals.len() as u64;
+        self.ordinals.insert(value.to_string(), ordinal);
+        true
+    }
+}
+
+impl PartialEq for EnumValues {
+    fn eq(&self, other: &EnumValues) -> bool {
+        // Registered values are data, not configuration.
+        self.values == other.values && self.on_unknown_value == other.on_unknown_value
+    }
+}
+
+impl fmt::Debug for EnumValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnumValues")
+            .field("values", &self.values)
+            .field("registered_values", &self.registered_values)
+            .field("on_unknown_value", &self.on_unknown_value)
+            .finish()
+    }
+}
+
+/// The enum fields of a schema.
+#[derive(Clone)]
+pub(crate) struct EnumFields {
+    // Dictionary of each field of the schema, if it is an enum field.
+    enum_values: Vec<Option<EnumValues>>,
+}
+
+impl EnumFields {
+    pub(crate) fn for_schema(schema: &Schema) -> EnumFields {
+        let enum_values = schema
+            .fields()
+            .map(|(_, field_entry)| match field_entry.field_type() {
+                FieldType::Str(text_options) => text_options.get_enum_values().cloned(),
+                _ => None,
+            })
+            .collect();
+        EnumFields { enum_values }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.enum_values.iter().all(Option::is_none)
+    }
+
+    /// Checks that the values of the enum fields of the document are part of their
+    /// dictionary, unless the field registers unknown values.
+    pub(crate) fn check_document<D: Document>(
+        &self,
+        schema: &Schema,
+        doc: &D,
+    ) -> crate::Result<()> {
+        self.for_each_unknown_value(schema, doc, |_, _| {})
+    }
+
+    /// Same as [`EnumFields::check_document`], also collecting the unknown values of the
+    /// fields which register them in `registered_values`, by field name.
+    pub(crate) fn register_document<D: Document>(
+        &self,
+        schema: &Schema,
+        doc: &D,
+        registered_values: &mut BTreeMap<String, BTreeSet<String>>,
+    ) -> crate::Result<()> {
+        self.for_each_unknown_value(schema, doc, |field, value| {
+            let field_name = schema.get_field_name(field);
+            if let Some(field_values) = registered_values.get_mut(field_name) {
+                if !field_values.contains(value) {
+                    field_values.insert(value.to_string());
+                }
+            } else {
+                registered_values
+                    .insert(field_name.to_string(), BTreeSet::from([value.to_string()]));
+            }
+        })
+    }
+
+    /// Calls `register` on the values of the document which are not part of the dictionary
+    /// of their enum field, or returns an error if the field rejects unknown values.
+    fn for_each_unknown_value<D: Document>(
+        &self,
+        schema: &Schema,
+        doc: &D,
+        mut register: impl FnMut(Field, &str),
+    ) -> crate::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        for (field, value) in doc.iter_fields_and_values() {
+            let Some(Some(enum_values)) = self.enum_values.get(field.field_id() as usize) else {
+                continue;
+            };
+            for_each_str(value, &mut |text| {
+                if enum_values.ordinal(text).is_some() {
+                    return Ok(());
+                }
+                match enum_values.on_unknown_value() {
+                    UnknownEnumValue::Reject => Err(TantivyError::InvalidArgument(format!(
+                        "Value {text:?} is not part of the enum values of the field {:?}",
+                        schema.get_field_name(field)
+                    ))),
+                    UnknownEnumValue::Register => {
+                        register(field, text);
+                        Ok(())
+                    }
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn for_each_str<'a, V: Value<'a>>(
+    value: V,
+    callback: &mut impl FnMut(&str) -> crate::Result<()>,
+) -> crate::Result<()> {
+    match value.as_value() {
+        ReferenceValue::Leaf(ReferenceValueLeaf::Str(text)) => callback(text),
+        ReferenceValue::Array(elements) => {
+            for element in elements {
+                for_each_str(element, callback)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_values_register() {
+        let enum_values = EnumValues::new(["fr", "de", "fr"], UnknownEnumValue::Register);
+        assert_eq!(enum_values.values().collect::<Vec<_>>(), vec!["fr", "de"]);
+        assert!(enum_values.check_value("it").is_ok());
+        assert_eq!(enum_values.ordinal("it"), None);
+        let registered = enum_values
+            .clone()
+            .with_registered_values(["it", "fr", "it"]);
+        assert_eq!(registered.registered_values(), ["it"]);
+        assert_eq!(registered.ordinal("it"), Some(2));
+        assert_eq!(registered.value(1), Some("de"));
+        assert_eq!(registered.value(2), Some("it"));
+        assert_eq!(registered.value(3), None);
+        // Registered values are not part of the configuration of the dictionary.
+        assert_eq!(registered, enum_values);
+        assert_ne!(
+            enum_values,
+            EnumValues::new(["fr"], UnknownEnumValue::Register)
+        );
+    }
+
+    #[test]
+    fn test_enum_values_reject() {
+        let enum_values = EnumValues::new(["open", "closed"], UnknownEnumValue::Reject);
+        assert!(matches!(
+            enum_values.check_value("pending"),
+            Err(ValueParsingError::UnknownEnumValue { value }) if value == "pending"
+        ));
+        assert!(enum_values.check_value("open").is_ok());
+        assert_eq!(enum_values.ordinal("pending"), None);
+        assert_ne!(
+            enum_values,
+            EnumValues::new(["open"], UnknownEnumValue::Reject)
+        );
+    }
+
+    #[test]
+    fn test_enum_values_serde() {
+        let enum_values = EnumValues::new(["open", "closed"], UnknownEnumValue::Register);
+        let json = serde_json::to_string(&enum_values).unwrap();
+        assert_eq!(
+            json,
+            r#"{"values":["open","closed"],"on_unknown_value":"register"}"#
+        );
+        let json = serde_json::to_string(&enum_values.with_registered_values(["pending"])).unwrap();
+        assert_eq!(
+            json,
+            r#"{"values":["open","closed"],"registered_values":["pending"],"on_unknown_value":"register"}"#
+        );
+        let deserialized: EnumValues = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.ordinal("pending"), Some(2));
+        let deserialized: EnumValues = serde_json::from_str(r#"{"values":["open"]}"#).unwrap();
+        assert_eq!(deserialized.on_unknown_value(), UnknownEnumValue::Reject);
+        assert_eq!(deserialized.values().collect::<Vec<_>>(), vec!["open"]);
+    }
+
+    #[test]
+    fn test_enum_field_indexing() -> crate::Result<()> {
+        use crate::aggregation::agg_req::Aggregations;
+        use crate::aggregation::AggregationCollector;
+        use crate::directory::RamDirectory;
+        use crate::query::AllQuery;
+        use crate::schema::{DocParsingError, FieldType, Schema, FAST, STRING};
+        use crate::{Index, IndexSettings, IndexWriter, TantivyDocument};
+
+        let mut schema_builder = Schema::builder();
+        let country = schema_builder.add_text_field(
+            "country",
+            (STRING | FAST).set_enum_values(EnumValues::new(["fr"], UnknownEnumValue::Register)),
+        );
+        let status = schema_builder.add_text_field(
+            "status",
+            STRING.set_enum_values(EnumValues::new(
+                ["open", "closed"],
+                UnknownEnumValue::Reject,
+            )),
+        );
+        let schema = schema_builder.build();
+        let country_values = |schema: &Schema| -> Vec<String> {
+            let FieldType::Str(text_options) = schema.get_field_entry(country).field_type() else {
+                panic!("country should be a text field");
+            };
+            let enum_values = text_options.get_enum_values().unwrap();
+            enum_values.values().map(str::to_string).collect()
+        };
+        let directory = RamDirectory::create();
+        let index = Index::create(directory.clone(), schema.clone(), IndexSettings::default())?;
+        {
+            let index_schema = index.schema();
+            assert!(matches!(
+                TantivyDocument::parse_json(&index_schema, r#"{"status": "pending"}"#),
+                Err(DocParsingError::ValueError(field_name, ValueParsingError::UnknownEnumValue { value }))
+                    if field_name == "status" && value == "pending"
+            ));
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            // Documents built without parsing are checked as well.
+            let mut doc = TantivyDocument::default();
+            doc.add_text(status, "pending");
+            assert!(matches!(
+                index_writer.add_document(doc),
+                Err(crate::TantivyError::InvalidArgument(_))
+            ));
+            // Values registered by uncommitted documents are discarded by a rollback.
+            index_writer.add_document(TantivyDocument::parse_json(
+                &index_schema,
+                r#"{"country": "es"}"#,
+            )?)?;
+            index_writer.rollback()?;
+            index_writer.add_document(TantivyDocument::parse_json(
+                &index_schema,
+                r#"{"country": "de", "status": "open"}"#,
+            )?)?;
+            index_writer.commit()?;
+        }
+
+        // Registered values are persisted with the schema, and keep growing across commits.
+        let index = Index::open(directory.clone())?;
+        assert_eq!(country_values(&index.schema()), vec!["fr", "de"]);
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            let mut doc = TantivyDocument::default();
+            doc.add_text(country, "it");
+            doc.add_text(country, "de");
+            doc.add_text(status, "closed");
+            index_writer.add_document(doc)?;
+            index_writer.commit()?;
+        }
+        let index = Index::open(directory.clone())?;
+        assert_eq!(country_values(&index.schema()), vec!["fr", "de", "it"]);
+        let segment_metas = index.searchable_segment_metas()?;
+        assert!(segment_metas.iter().any(|segment_meta| {
+            segment_meta.registered_enum_values()
+                == &BTreeMap::from([("country".to_string(), BTreeSet::from(["it".to_string()]))])
+        }));
+        // The schema the index was created with is left untouched.
+        assert_eq!(country_values(&schema), vec!["fr"]);
+        assert_eq!(
+            country_values(
+                &Index::builder()
+                    .schema(schema)
+                    .open_or_create(directory)?
+                    .schema()
+            ),
+            vec!["fr", "de", "it"]
+        );
+
+        // Aggregations report the values as strings.
+        let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+            "countries": { "terms": { "field": "country" } }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let agg_res = index.reader()?.searcher().search(&AllQuery, &collector)?;
+        let agg_json = serde_json::to_value(agg_res).unwrap();
+        assert_eq!(
+            agg_json["countries"]["buckets"],
+            serde_json::json!([
+                { "key": "de", "doc_count": 2 },
+                { "key": "it", "doc_count": 1 },
+            ])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use std::ops::BitOr;
+
+use serde::{Deserialize, Serialize};
+
+use super::flags::{FastFlag, SchemaFlagList, StoredFlag};
+
+/// Define how a f32 vector field should be handled by tantivy.
+///
+/// The values of a f32 vector field are always stored, as f32, in a column of their own
+/// allowing aligned access to the vector of each document.
+/// See [`F32VectorColumn`](crate::fastfield::F32VectorColumn).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct F32VectorOptions {
+    stored: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dimensions: Option<u32>,
+}
+
+impl F32VectorOptions {
+    /// Returns true if the value is stored.
+    #[inline]
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns the number of values of the vector of each document, if it is fixed.
+    #[inline]
+    pub fn dimensions(&self) -> Option<u32> {
+        self.dimensions
+    }
+
+    /// Set the field as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    #[must_use]
+    pub fn set_stored(mut self) -> F32VectorOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Fixes the number of values of the vector of each document.
+    ///
+    /// Adding a document whose vector has a different number of values then fails.
+    /// Documents without any value for the field are still accepted.
+    #[must_use]
+    pub fn set_dimensions(mut self, dimensions: u32) -> F32VectorOptions {
+        self.dimensions = Some(dimensions);
+        self
+    }
+}
+
+impl<T: Into<F32VectorOptions>> BitOr<T> for F32VectorOptions {
+    type Output = F32VectorOptions;
+
+    fn bitor(self, other: T) -> F32VectorOptions {
+        let other = other.into();
+        F32VectorOptions {
+            stored: self.stored | other.stored,
+            dimensions: self.dimensions.or(other.dimensions),
+        }
+    }
+}
+
+impl From<()> for F32VectorOptions {
+    fn from(_: ()) -> Self {
+        Self::default()
+    }
+}
+
+impl From<FastFlag> for F32VectorOptions {
+    fn from(_: FastFlag) -> Self {
+        Self::default()
+    }
+}
+
+impl From<StoredFlag> for F32VectorOptions {
+    fn from(_: StoredFlag) -> Self {
+        F32VectorOptions {
+            stored: true,
+            dimensions: None,
+        }
+    }
+}
+
+impl<Head, Tail> From<SchemaFlagList<Head, Tail>> for F32VectorOptions
+where
+    Head: Clone,
+    Tail: Clone,
+    Self: BitOr<Output = Self> + From<Head> + From<Tail>,
+{
+    fn from(head_tail: SchemaFlagList<Head, Tail>) -> Self {
+        Self::from(head_tail.head) | Self::from(head_tail.tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::F32VectorOptions;
+    use crate::schema::{FAST, STORED};
+
+    #[test]
+    fn test_f32_vector_options_serialization() {
+        let options = F32VectorOptions::from(STORED | FAST).set_dimensions(3);
+        assert!(options.is_stored());
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(json, r#"{"stored":true,"dimensions":3}"#);
+        let deser: F32VectorOptions = serde_json::from_str(r#"{"stored":false}"#).unwrap();
+        assert_eq!(deser, F32VectorOptions::default());
+    }
+}
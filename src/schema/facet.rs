@@ -167,6 +167,22 @@ impl Facet {
         other_str.starts_with(self_str) && other_str.as_bytes()[self_str.len()] == FACET_SEP_BYTE
     }
 
+    /// Returns an iterator over the ancestors of the facet, from the root facet to the facet
+    /// itself.
+    ///
+    /// `/category/cat1` yields `/`, `/category` and `/category/cat1`. The root facet only yields
+    /// itself.
+    pub fn ancestors(&self) -> impl Iterator<Item = Facet> + '_ {
+        let encoded_str = self.encoded_str();
+        let prefix_lens = encoded_str
+            .match_indices(FACET_SEP_CHAR)
+            .map(|(pos, _)| pos)
+            .chain((!self.is_root()).then_some(encoded_str.len()));
+        std::iter::once(0)
+            .chain(prefix_lens)
+            .map(move |prefix_len| Facet(encoded_str[..prefix_len].to_string()))
+    }
+
     /// Extract path from the `Facet`.
     pub fn to_path(&self) -> Vec<&str> {
         self.encoded_str().split(FACET_SEP_CHAR).collect()
@@ -325,6 +341,33 @@ mod tests {
         assert!(!Facet::from("/").is_prefix_of(&Facet::from("/")));
     }
 
+    #[test]
+    fn test_ancestors() {
+        let facet = Facet::from("/electronics/tv_and_video/led\\/lcd/oled");
+        let ancestors: Vec<Facet> = facet.ancestors().collect();
+        assert_eq!(
+            ancestors,
+            vec![
+                Facet::root(),
+                Facet::from("/electronics"),
+                Facet::from("/electronics/tv_and_video"),
+                Facet::from("/electronics/tv_and_video/led\\/lcd"),
+                facet.clone(),
+            ]
+        );
+        for (ancestor, next_ancestor) in ancestors.iter().zip(&ancestors[1..]) {
+            assert!(ancestor.is_prefix_of(next_ancestor));
+        }
+        assert_eq!(
+            Facet::root().ancestors().collect::<Vec<_>>(),
+            vec![Facet::root()]
+        );
+        assert_eq!(
+            Facet::from("/top").ancestors().collect::<Vec<_>>(),
+            vec![Facet::root(), Facet::from("/top")]
+        );
+    }
+
     #[test]
     fn deserialize_from_borrowed_string() {
         let facet = serde_json::from_str::<Facet>(r#""/foo/bar""#).unwrap();
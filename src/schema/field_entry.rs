@@ -3,8 +3,8 @@ use serde::{Deserialize, Serialize};
 use super::ip_options::IpAddrOptions;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::{
-    is_valid_field_name, DateOptions, FacetOptions, FieldType, JsonObjectOptions, NumericOptions,
-    TextOptions,
+    is_valid_field_name, DateOptions, F32VectorOptions, FacetOptions, FieldType, JsonObjectOptions,
+    NumericOptions, TextOptions,
 };
 
 /// A `FieldEntry` represents a field and its configuration.
@@ -24,6 +24,12 @@ impl FieldEntry {
     /// Creates a new field entry given a name and a field type
     pub fn new(field_name: String, field_type: FieldType) -> FieldEntry {
         assert!(is_valid_field_name(&field_name));
+        Self::new_unchecked(field_name, field_type)
+    }
+
+    /// Creates a new field entry, leaving the validation of its name to the
+    /// [`SchemaBuilder`](crate::schema::SchemaBuilder).
+    pub(crate) fn new_unchecked(field_name: String, field_type: FieldType) -> FieldEntry {
         FieldEntry {
             name: field_name,
             field_type,
@@ -50,6 +56,11 @@ impl FieldEntry {
         Self::new(field_name, FieldType::F64(f64_options))
     }
 
+    /// Creates a new f32 field entry.
+    pub fn new_f32(field_name: String, f32_options: NumericOptions) -> FieldEntry {
+        Self::new(field_name, FieldType::F32(f32_options))
+    }
+
     /// Creates a new bool field entry.
     pub fn new_bool(field_name: String, bool_options: NumericOptions) -> FieldEntry {
         Self::new(field_name, FieldType::Bool(bool_options))
@@ -75,6 +86,11 @@ impl FieldEntry {
         Self::new(field_name, FieldType::Bytes(bytes_options))
     }
 
+    /// Creates a field entry for a f32 vector field
+    pub fn new_f32_vector(field_name: String, f32_vector_options: F32VectorOptions) -> FieldEntry {
+        Self::new(field_name, FieldType::F32Vector(f32_vector_options))
+    }
+
     /// Creates a field entry for a json field
     pub fn new_json(field_name: String, json_object_options: JsonObjectOptions) -> FieldEntry {
         Self::new(field_name, FieldType::JsonObject(json_object_options))
@@ -122,6 +138,7 @@ impl FieldEntry {
             FieldType::U64(ref options)
             | FieldType::I64(ref options)
             | FieldType::F64(ref options)
+            | FieldType::F32(ref options)
             | FieldType::Bool(ref options) => options.is_stored(),
             FieldType::Date(ref options) => options.is_stored(),
             FieldType::Str(ref options) => options.is_stored(),
@@ -129,8 +146,15 @@ impl FieldEntry {
             FieldType::Bytes(ref options) => options.is_stored(),
             FieldType::JsonObject(ref options) => options.is_stored(),
             FieldType::IpAddr(ref options) => options.is_stored(),
+            FieldType::F32Vector(ref options) => options.is_stored(),
         }
     }
+
+    /// Returns true if the values of the field are discarded when a document is added, the
+    /// field being neither indexed, stored nor fast.
+    pub fn discards_values(&self) -> bool {
+        !self.is_indexed() && !self.is_stored() && !self.is_fast()
+    }
 }
 
 #[cfg(test)]
@@ -13,8 +13,8 @@ use super::IntoIpv6Addr;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
-    DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, OwnedValue,
-    TextFieldIndexing, TextOptions,
+    DateOptions, F32VectorOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions,
+    OwnedValue, TextFieldIndexing, TextOptions,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
@@ -42,6 +42,45 @@ pub enum ValueParsingError {
     },
     #[error("Invalid base64: {base64}")]
     InvalidBase64 { base64: String },
+    #[error("Value {value:?} is not part of the enum values of the field")]
+    UnknownEnumValue { value: String },
+}
+
+impl ValueParsingError {
+    /// Returns the value which could not be parsed, rendered as JSON, keeping at most
+    /// `max_chars` characters.
+    pub(crate) fn render_value(&self, max_chars: usize) -> String {
+        let rendered = match self {
+            ValueParsingError::OverflowError { json, .. }
+            | ValueParsingError::TypeError { json, .. }
+            | ValueParsingError::ParseError { json, .. } => json.to_string(),
+            ValueParsingError::InvalidBase64 { base64: text }
+            | ValueParsingError::UnknownEnumValue { value: text } => {
+                JsonValue::String(text.clone()).to_string()
+            }
+        };
+        match rendered.char_indices().nth(max_chars) {
+            Some((truncated_len, _)) => format!("{}...", &rendered[..truncated_len]),
+            None => rendered,
+        }
+    }
+}
+
+/// Options relaxing how JSON values are converted to the values of a field.
+///
+/// All of the options are disabled by default, which is the behavior of
+/// [`FieldType::value_from_json`]. See [`FieldType::value_from_json_with_options`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JsonParseOptions {
+    /// Accepts a string holding a number, like `"42"` or `"-3.5"`, for the numeric fields,
+    /// even if they do not have the `coerce` option. An empty string is a missing value.
+    pub coerce_numbers: bool,
+    /// Accepts the strings `"true"` and `"false"` for the bool fields, even if they do not
+    /// have the `coerce` option. An empty string is a missing value.
+    pub coerce_bools: bool,
+    /// Skips the `null` values of the fields which are not json fields, instead of returning
+    /// an error.
+    pub treat_null_as_missing: bool,
 }
 
 /// Type of the value that a field can take.
@@ -79,7 +118,7 @@ impl From<ColumnType> for Type {
             ColumnType::Str => Type::Str,
             ColumnType::U64 => Type::U64,
             ColumnType::I64 => Type::I64,
-            ColumnType::F64 => Type::F64,
+            ColumnType::F64 | ColumnType::F32 => Type::F64,
             ColumnType::Bool => Type::Bool,
             ColumnType::DateTime => Type::Date,
             ColumnType::Bytes => Type::Bytes,
@@ -177,6 +216,11 @@ pub enum FieldType {
     I64(NumericOptions),
     /// 64-bits float 64 field type configuration
     F64(NumericOptions),
+    /// 32-bits float field type configuration.
+    ///
+    /// Values are handled as `f64` in documents and in the inverted index, but they are
+    /// rounded to `f32` and take half the space of a `f64` in the fast field.
+    F32(NumericOptions),
     /// Bool field type configuration
     Bool(NumericOptions),
     /// Signed 64-bits Date 64 field type configuration,
@@ -189,6 +233,8 @@ pub enum FieldType {
     JsonObject(JsonObjectOptions),
     /// IpAddr field
     IpAddr(IpAddrOptions),
+    /// Vector of f32 (any number per document), stored in its own file
+    F32Vector(F32VectorOptions),
 }
 
 impl FieldType {
@@ -198,13 +244,14 @@ impl FieldType {
             FieldType::Str(_) => Type::Str,
             FieldType::U64(_) => Type::U64,
             FieldType::I64(_) => Type::I64,
-            FieldType::F64(_) => Type::F64,
+            FieldType::F64(_) | FieldType::F32(_) => Type::F64,
             FieldType::Bool(_) => Type::Bool,
             FieldType::Date(_) => Type::Date,
             FieldType::Facet(_) => Type::Facet,
             FieldType::Bytes(_) => Type::Bytes,
             FieldType::JsonObject(_) => Type::Json,
             FieldType::IpAddr(_) => Type::IpAddr,
+            FieldType::F32Vector(_) => Type::F64,
         }
     }
 
@@ -233,6 +280,11 @@ impl FieldType {
         matches!(self, FieldType::Date(_))
     }
 
+    /// returns true if this is a f32 vector field
+    pub fn is_f32_vector(&self) -> bool {
+        matches!(self, FieldType::F32Vector(_))
+    }
+
     /// returns true if the field is indexed.
     pub fn is_indexed(&self) -> bool {
         match *self {
@@ -240,12 +292,14 @@ impl FieldType {
             FieldType::U64(ref int_options)
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
+            | FieldType::F32(ref int_options)
             | FieldType::Bool(ref int_options) => int_options.is_indexed(),
             FieldType::Date(ref date_options) => date_options.is_indexed(),
             FieldType::Facet(ref _facet_options) => true,
             FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_indexed(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_indexed(),
+            FieldType::F32Vector(_) => false,
         }
     }
 
@@ -278,11 +332,14 @@ impl FieldType {
             FieldType::U64(ref int_options)
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
+            | FieldType::F32(ref int_options)
             | FieldType::Bool(ref int_options) => int_options.is_fast(),
             FieldType::Date(ref date_options) => date_options.is_fast(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_fast(),
             FieldType::Facet(_) => true,
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_fast(),
+            // f32 vectors are stored in their own file, not as fast field columns.
+            FieldType::F32Vector(_) => false,
         }
     }
 
@@ -296,12 +353,27 @@ impl FieldType {
             FieldType::U64(ref int_options)
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
+            | FieldType::F32(ref int_options)
             | FieldType::Bool(ref int_options) => int_options.fieldnorms(),
             FieldType::Date(ref date_options) => date_options.fieldnorms(),
             FieldType::Facet(_) => false,
             FieldType::Bytes(ref bytes_options) => bytes_options.fieldnorms(),
             FieldType::JsonObject(ref _json_object_options) => false,
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.fieldnorms(),
+            FieldType::F32Vector(_) => false,
+        }
+    }
+
+    /// returns true if the byte offsets of the tokens are stored along with their positions.
+    ///
+    /// See [`TextFieldIndexing::set_offsets`].
+    pub fn has_offsets(&self) -> bool {
+        match *self {
+            FieldType::Str(ref text_options) => text_options
+                .get_indexing_options()
+                .map(TextFieldIndexing::offsets)
+                .unwrap_or(false),
+            _ => false,
         }
     }
 
@@ -321,6 +393,7 @@ impl FieldType {
             FieldType::U64(ref int_options)
             | FieldType::I64(ref int_options)
             | FieldType::F64(ref int_options)
+            | FieldType::F32(ref int_options)
             | FieldType::Bool(ref int_options) => {
                 if int_options.is_indexed() {
                     Some(IndexRecordOption::Basic)
@@ -353,6 +426,7 @@ impl FieldType {
                     None
                 }
             }
+            FieldType::F32Vector(_) => None,
         }
     }
 
@@ -362,7 +436,132 @@ impl FieldType {
     /// For instance, If the json value is the integer `3` and the
     /// target field is a `Str`, this method will return an Error if `coerce`
     /// is not enabled.
+    ///
+    /// The values of enum fields which reject unknown values are checked against the
+    /// dictionary of the field, see [`EnumValues`](crate::schema::EnumValues).
     pub fn value_from_json(&self, json: JsonValue) -> Result<OwnedValue, ValueParsingError> {
+        let value = self.parse_json_value(json)?;
+        self.check_enum_value(&value)?;
+        Ok(value)
+    }
+
+    /// Parses a field value from json, as [`FieldType::value_from_json`], with the conversions
+    /// enabled in `options`.
+    ///
+    /// Returns `None` if `options` make the value a missing value, like a `null` with
+    /// [`JsonParseOptions::treat_null_as_missing`].
+    pub fn value_from_json_with_options(
+        &self,
+        json: JsonValue,
+        options: &JsonParseOptions,
+    ) -> Result<Option<OwnedValue>, ValueParsingError> {
+        match json {
+            JsonValue::Null
+                if options.treat_null_as_missing && !matches!(self, FieldType::JsonObject(_)) =>
+            {
+                Ok(None)
+            }
+            JsonValue::String(text) if self.coerces_strings(options) => {
+                if text.is_empty() {
+                    return Ok(None);
+                }
+                self.coerce_json_str(text).map(Some)
+            }
+            json => self.value_from_json(json).map(Some),
+        }
+    }
+
+    fn coerces_strings(&self, options: &JsonParseOptions) -> bool {
+        match self {
+            FieldType::U64(_) | FieldType::I64(_) | FieldType::F64(_) | FieldType::F32(_) => {
+                options.coerce_numbers
+            }
+            FieldType::Bool(_) => options.coerce_bools,
+            _ => false,
+        }
+    }
+
+    /// Parses a string as a value of a numeric or bool field, whatever its `coerce` option.
+    fn coerce_json_str(&self, text: String) -> Result<OwnedValue, ValueParsingError> {
+        let (value_opt, expected) = match self {
+            FieldType::U64(_) => (text.parse().ok().map(OwnedValue::U64), "a u64 as string"),
+            FieldType::I64(_) => (text.parse().ok().map(OwnedValue::I64), "a i64 as string"),
+            FieldType::F64(_) => (text.parse().ok().map(OwnedValue::F64), "a f64 as string"),
+            FieldType::F32(_) => (
+                text.parse::<f32>()
+                    .ok()
+                    .filter(|val| val.is_finite())
+                    .map(|val| OwnedValue::F64(f64::from(val))),
+                "a f32 as string",
+            ),
+            FieldType::Bool(_) => (text.parse().ok().map(OwnedValue::Bool), "a bool as string"),
+            _ => (None, self.value_type().name()),
+        };
+        value_opt.ok_or(ValueParsingError::TypeError {
+            expected,
+            json: JsonValue::String(text),
+        })
+    }
+
+    /// Checks that `value` can be added to a field of this type.
+    ///
+    /// Values of another type are converted with the rules of [`FieldType::value_from_json`],
+    /// as if they were given as JSON. Values without a JSON counterpart (dates, facets, bytes,
+    /// ip addresses and pre-tokenized strings) are only accepted by the fields of their type.
+    /// Null values are accepted by all of the fields.
+    pub fn coerce_value(&self, value: OwnedValue) -> Result<OwnedValue, ValueParsingError> {
+        let has_field_type = matches!(
+            (self, &value),
+            (_, OwnedValue::Null)
+                | (
+                    FieldType::Str(_),
+                    OwnedValue::Str(_) | OwnedValue::PreTokStr(_)
+                )
+                | (FieldType::U64(_), OwnedValue::U64(_))
+                | (FieldType::I64(_), OwnedValue::I64(_))
+                | (
+                    FieldType::F64(_) | FieldType::F32Vector(_),
+                    OwnedValue::F64(_)
+                )
+                | (FieldType::Bool(_), OwnedValue::Bool(_))
+                | (FieldType::Date(_), OwnedValue::Date(_))
+                | (FieldType::Facet(_), OwnedValue::Facet(_))
+                | (FieldType::Bytes(_), OwnedValue::Bytes(_))
+                | (FieldType::IpAddr(_), OwnedValue::IpAddr(_))
+                | (
+                    FieldType::JsonObject(_),
+                    OwnedValue::Object(_) | OwnedValue::Array(_)
+                )
+        );
+        if has_field_type {
+            self.check_enum_value(&value)?;
+            return Ok(value);
+        }
+        let json = serde_json::to_value(&value).expect("values can always be serialized to JSON");
+        match value {
+            OwnedValue::Date(_)
+            | OwnedValue::Facet(_)
+            | OwnedValue::Bytes(_)
+            | OwnedValue::IpAddr(_)
+            | OwnedValue::PreTokStr(_) => Err(ValueParsingError::TypeError {
+                expected: self.value_type().name(),
+                json,
+            }),
+            _ => self.value_from_json(json),
+        }
+    }
+
+    /// Checks a value against the dictionary of the field, if it is an enum field.
+    pub(crate) fn check_enum_value(&self, value: &OwnedValue) -> Result<(), ValueParsingError> {
+        if let (FieldType::Str(text_options), OwnedValue::Str(text)) = (self, value) {
+            if let Some(enum_values) = text_options.get_enum_values() {
+                enum_values.check_value(text)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_json_value(&self, json: JsonValue) -> Result<OwnedValue, ValueParsingError> {
         match json {
             JsonValue::String(field_text) => {
                 match self {
@@ -420,6 +619,23 @@ impl FieldType {
                             })
                         }
                     }
+                    FieldType::F32(opt) => {
+                        if opt.should_coerce() {
+                            let val: f32 =
+                                field_text
+                                    .parse()
+                                    .map_err(|_| ValueParsingError::TypeError {
+                                        expected: "a f32 or a f32 as string",
+                                        json: JsonValue::String(field_text),
+                                    })?;
+                            Ok(OwnedValue::F64(f64::from(val)))
+                        } else {
+                            Err(ValueParsingError::TypeError {
+                                expected: "a f32",
+                                json: JsonValue::String(field_text),
+                            })
+                        }
+                    }
                     FieldType::Bool(opt) => {
                         if opt.should_coerce() {
                             Ok(OwnedValue::Bool(field_text.parse().map_err(|_| {
@@ -435,6 +651,10 @@ impl FieldType {
                             })
                         }
                     }
+                    FieldType::F32Vector(_) => Err(ValueParsingError::TypeError {
+                        expected: "a f64",
+                        json: JsonValue::String(field_text),
+                    }),
                     FieldType::Facet(_) => Ok(OwnedValue::Facet(Facet::from(&field_text))),
                     FieldType::Bytes(_) => BASE64
                         .decode(&field_text)
@@ -477,7 +697,7 @@ impl FieldType {
                         })
                     }
                 }
-                FieldType::F64(_) => {
+                FieldType::F64(_) | FieldType::F32Vector(_) => {
                     if let Some(field_val_f64) = field_val_num.as_f64() {
                         Ok(OwnedValue::F64(field_val_f64))
                     } else {
@@ -487,6 +707,16 @@ impl FieldType {
                         })
                     }
                 }
+                FieldType::F32(_) => {
+                    let field_val_f32 = field_val_num.as_f64().map(|val| val as f32);
+                    match field_val_f32 {
+                        Some(val) if val.is_finite() => Ok(OwnedValue::F64(f64::from(val))),
+                        _ => Err(ValueParsingError::OverflowError {
+                            expected: "a f32",
+                            json: JsonValue::Number(field_val_num),
+                        }),
+                    }
+                }
                 FieldType::Bool(_) => Err(ValueParsingError::TypeError {
                     expected: "a boolean",
                     json: JsonValue::Number(field_val_num),
@@ -579,10 +809,11 @@ impl FieldType {
 mod tests {
     use serde_json::json;
 
-    use super::FieldType;
+    use super::{FieldType, JsonParseOptions};
     use crate::schema::field_type::ValueParsingError;
     use crate::schema::{
-        Document, NumericOptions, OwnedValue, Schema, TextOptions, Type, COERCE, INDEXED,
+        DocParsingError, Document, NumericOptions, OwnedValue, ParseMode, Schema, TextOptions,
+        Type, Value, COERCE, INDEXED, STORED,
     };
     use crate::time::{Date, Month, PrimitiveDateTime, Time};
     use crate::tokenizer::{PreTokenizedString, Token};
@@ -679,6 +910,149 @@ mod tests {
             .contains("a f64"));
     }
 
+    #[test]
+    fn test_value_from_json_with_options() {
+        let options = JsonParseOptions {
+            coerce_numbers: true,
+            coerce_bools: true,
+            treat_null_as_missing: true,
+        };
+        let u64_type = FieldType::U64(NumericOptions::default());
+        let i64_type = FieldType::I64(NumericOptions::default());
+        let f64_type = FieldType::F64(NumericOptions::default());
+        let bool_type = FieldType::Bool(NumericOptions::default());
+        let str_type = FieldType::Str(TextOptions::default());
+        assert_eq!(
+            u64_type.value_from_json_with_options(json!("42"), &options),
+            Ok(Some(OwnedValue::U64(42)))
+        );
+        assert_eq!(
+            i64_type.value_from_json_with_options(json!("-3"), &options),
+            Ok(Some(OwnedValue::I64(-3)))
+        );
+        assert_eq!(
+            f64_type.value_from_json_with_options(json!("-3.5"), &options),
+            Ok(Some(OwnedValue::F64(-3.5)))
+        );
+        assert_eq!(
+            bool_type.value_from_json_with_options(json!("false"), &options),
+            Ok(Some(OwnedValue::Bool(false)))
+        );
+        assert_eq!(
+            u64_type.value_from_json_with_options(json!(""), &options),
+            Ok(None)
+        );
+        assert_eq!(
+            u64_type.value_from_json_with_options(json!(null), &options),
+            Ok(None)
+        );
+        assert_eq!(
+            str_type.value_from_json_with_options(json!(null), &options),
+            Ok(None)
+        );
+        // Strings are still strings for the text fields.
+        assert_eq!(
+            str_type.value_from_json_with_options(json!(""), &options),
+            Ok(Some(OwnedValue::Str(String::new())))
+        );
+        assert_eq!(
+            i64_type.value_from_json_with_options(json!("-3.5"), &options),
+            Err(ValueParsingError::TypeError {
+                expected: "a i64 as string",
+                json: json!("-3.5"),
+            })
+        );
+        // Numbers are not coerced with `coerce_bools` alone, and the other way around.
+        let bools_only = JsonParseOptions {
+            coerce_bools: true,
+            ..JsonParseOptions::default()
+        };
+        assert!(u64_type
+            .value_from_json_with_options(json!("42"), &bools_only)
+            .is_err());
+        assert!(u64_type
+            .value_from_json_with_options(json!(null), &bools_only)
+            .is_err());
+        assert_eq!(
+            bool_type.value_from_json_with_options(json!("true"), &bools_only),
+            Ok(Some(OwnedValue::Bool(true)))
+        );
+    }
+
+    #[test]
+    fn test_parse_json_with_options() {
+        let mut schema_builder = Schema::builder();
+        let count = schema_builder.add_u64_field("count", NumericOptions::default());
+        let score = schema_builder.add_f64_field("score", NumericOptions::default());
+        let active = schema_builder.add_bool_field("active", NumericOptions::default());
+        schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+        let doc_json = r#"{
+            "count": ["42", "", 7],
+            "score": "-3.5",
+            "active": "true",
+            "missing": null
+        }"#;
+        assert!(TantivyDocument::parse_json(&schema, doc_json).is_err());
+
+        let options = JsonParseOptions {
+            coerce_numbers: true,
+            coerce_bools: true,
+            treat_null_as_missing: true,
+        };
+        let doc = TantivyDocument::parse_json_with_options(
+            &schema,
+            doc_json,
+            ParseMode::Lenient,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.get_all(count)
+                .map(|value| value.as_u64().unwrap())
+                .collect::<Vec<u64>>(),
+            vec![42, 7]
+        );
+        assert_eq!(doc.get_first_f64(score), Some(-3.5));
+        assert_eq!(doc.get_first(active).unwrap().as_bool(), Some(true));
+
+        let doc = TantivyDocument::parse_json_with_options(
+            &schema,
+            r#"{"count": null, "score": null}"#,
+            ParseMode::Lenient,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(doc.len(), 0);
+
+        // The errors name the field and the value.
+        let error = TantivyDocument::parse_json_with_options(
+            &schema,
+            r#"{"count": "many"}"#,
+            ParseMode::Lenient,
+            &options,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            DocParsingError::ValueError(
+                "count".to_string(),
+                ValueParsingError::TypeError {
+                    expected: "a u64 as string",
+                    json: json!("many"),
+                }
+            )
+        );
+        // `null` is not a missing value for a json field.
+        assert!(TantivyDocument::parse_json_with_options(
+            &schema,
+            r#"{"attributes": null}"#,
+            ParseMode::Lenient,
+            &options,
+        )
+        .is_err());
+    }
+
     #[test]
     fn test_deserialize_json_date() {
         let mut schema_builder = Schema::builder();
@@ -760,27 +1134,9 @@ mod tests {
         let expected_value = OwnedValue::PreTokStr(PreTokenizedString {
             text: String::from("The Old Man"),
             tokens: vec![
-                Token {
-                    offset_from: 0,
-                    offset_to: 3,
-                    position: 0,
-                    text: String::from("The"),
-                    position_length: 1,
-                },
-                Token {
-                    offset_from: 4,
-                    offset_to: 7,
-                    position: 1,
-                    text: String::from("Old"),
-                    position_length: 1,
-                },
-                Token {
-                    offset_from: 8,
-                    offset_to: 11,
-                    position: 2,
-                    text: String::from("Man"),
-                    position_length: 1,
-                },
+                Token::new(0, 3, 0, String::from("The")),
+                Token::new(4, 7, 1, String::from("Old")),
+                Token::new(8, 11, 2, String::from("Man")),
             ],
         });
 
@@ -114,6 +114,7 @@ pub mod document;
 mod facet;
 mod facet_options;
 mod schema;
+mod schema_validation;
 pub(crate) mod term;
 
 mod field_entry;
@@ -121,6 +122,8 @@ mod field_type;
 
 mod bytes_options;
 mod date_time_options;
+mod enum_values;
+mod f32_vector_options;
 mod field;
 mod flags;
 mod index_record_option;
@@ -134,13 +137,21 @@ use columnar::ColumnType;
 
 pub use self::bytes_options::BytesOptions;
 pub use self::date_time_options::{DateOptions, DateTimePrecision, DATE_TIME_PRECISION_INDEXED};
-pub use self::document::{DocParsingError, Document, OwnedValue, TantivyDocument, Value};
+#[cfg(feature = "arrow")]
+pub use self::document::ArrowFieldMapping;
+pub use self::document::{
+    DocParsingError, Document, JsonDocParser, JsonDocument, JsonLines, OwnedValue, ParseMode,
+    SchemaDoc, SchemaDocSeed, TantivyDocument, Value,
+};
+pub(crate) use self::enum_values::EnumFields;
+pub use self::enum_values::{EnumValues, UnknownEnumValue};
+pub use self::f32_vector_options::F32VectorOptions;
 pub(crate) use self::facet::FACET_SEP_BYTE;
 pub use self::facet::{Facet, FacetParseError};
 pub use self::facet_options::FacetOptions;
 pub use self::field::Field;
 pub use self::field_entry::FieldEntry;
-pub use self::field_type::{FieldType, Type};
+pub use self::field_type::{FieldType, JsonParseOptions, Type};
 pub use self::flags::{COERCE, FAST, INDEXED, STORED};
 pub use self::index_record_option::IndexRecordOption;
 pub use self::ip_options::{IntoIpv6Addr, IpAddrOptions};
@@ -148,6 +159,7 @@ pub use self::json_object_options::JsonObjectOptions;
 pub use self::named_field_document::NamedFieldDocument;
 pub use self::numeric_options::NumericOptions;
 pub use self::schema::{Schema, SchemaBuilder};
+pub use self::schema_validation::{SchemaProblem, SchemaValidationError};
 pub use self::term::{Term, ValueBytes};
 pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
 
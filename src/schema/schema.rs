@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 use std::sync::Arc;
 
@@ -6,8 +6,10 @@ use serde::de::{SeqAccess, Visitor};
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use super::schema_validation::validate_field_entries;
 use super::*;
 use crate::json_utils::split_json_path;
+use crate::tokenizer::TokenizerManager;
 use crate::TantivyError;
 
 /// Tantivy has a very strict schema.
@@ -43,116 +45,101 @@ impl SchemaBuilder {
 
     /// Adds a new u64 field.
     /// Returns the associated field handle
-    ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
     pub fn add_u64_field<T: Into<NumericOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_u64(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::U64(field_options.into()))
     }
 
     /// Adds a new i64 field.
     /// Returns the associated field handle
-    ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
     pub fn add_i64_field<T: Into<NumericOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_i64(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::I64(field_options.into()))
     }
 
     /// Adds a new f64 field.
     /// Returns the associated field handle
-    ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
     pub fn add_f64_field<T: Into<NumericOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_f64(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::F64(field_options.into()))
     }
 
-    /// Adds a new bool field.
+    /// Adds a new f32 field.
     /// Returns the associated field handle
     ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
+    /// Values are rounded to `f32`, and the fast field stores them on 32 bits.
+    /// They can be read back with
+    /// [`FastFieldReaders::f32`](crate::fastfield::FastFieldReaders::f32).
+    pub fn add_f32_field<T: Into<NumericOptions>>(
+        &mut self,
+        field_name_str: &str,
+        field_options: T,
+    ) -> Field {
+        self.add_field_with_type(field_name_str, FieldType::F32(field_options.into()))
+    }
+
+    /// Adds a new bool field.
+    /// Returns the associated field handle
     pub fn add_bool_field<T: Into<NumericOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_bool(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::Bool(field_options.into()))
     }
 
     /// Adds a new date field.
     /// Returns the associated field handle
     /// Internally, Tantivy simply stores dates as i64 UTC timestamps,
     /// while the user supplies DateTime values for convenience.
-    ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
     pub fn add_date_field<T: Into<DateOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_date(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::Date(field_options.into()))
     }
 
     /// Adds a ip field.
     /// Returns the associated field handle.
-    ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
     pub fn add_ip_addr_field<T: Into<IpAddrOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_ip_addr(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::IpAddr(field_options.into()))
+    }
+
+    /// Adds a f32 vector field.
+    /// Returns the associated field handle.
+    ///
+    /// The values of the field are accessed, per document, via
+    /// [`FastFieldReaders::f32_vector`](crate::fastfield::FastFieldReaders::f32_vector).
+    pub fn add_f32_vector_field<T: Into<F32VectorOptions>>(
+        &mut self,
+        field_name_str: &str,
+        field_options: T,
+    ) -> Field {
+        self.add_field_with_type(field_name_str, FieldType::F32Vector(field_options.into()))
     }
 
     /// Adds a new text field.
     /// Returns the associated field handle
-    ///
-    /// # Panics
-    ///
-    /// Panics when field already exists.
     pub fn add_text_field<T: Into<TextOptions>>(
         &mut self,
         field_name_str: &str,
         field_options: T,
     ) -> Field {
-        let field_name = String::from(field_name_str);
-        let field_entry = FieldEntry::new_text(field_name, field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name_str, FieldType::Str(field_options.into()))
     }
 
     /// Adds a facet field to the schema.
@@ -161,8 +148,7 @@ impl SchemaBuilder {
         field_name: &str,
         facet_options: impl Into<FacetOptions>,
     ) -> Field {
-        let field_entry = FieldEntry::new_facet(field_name.to_string(), facet_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name, FieldType::Facet(facet_options.into()))
     }
 
     /// Adds a fast bytes field to the schema.
@@ -180,8 +166,7 @@ impl SchemaBuilder {
         field_name: &str,
         field_options: T,
     ) -> Field {
-        let field_entry = FieldEntry::new_bytes(field_name.to_string(), field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name, FieldType::Bytes(field_options.into()))
     }
 
     /// Adds a json object field to the schema.
@@ -190,24 +175,81 @@ impl SchemaBuilder {
         field_name: &str,
         field_options: T,
     ) -> Field {
-        let field_entry = FieldEntry::new_json(field_name.to_string(), field_options.into());
-        self.add_field(field_entry)
+        self.add_field_with_type(field_name, FieldType::JsonObject(field_options.into()))
     }
 
     /// Adds a field entry to the schema in build.
+    ///
+    /// A field with the same name as a field added before is accepted here, and reported
+    /// when the schema is built.
     pub fn add_field(&mut self, field_entry: FieldEntry) -> Field {
         let field = Field::from_field_id(self.fields.len() as u32);
         let field_name = field_entry.name().to_string();
-        if let Some(_previous_value) = self.fields_map.insert(field_name, field) {
-            panic!("Field already exists in schema {}", field_entry.name());
-        };
+        self.fields_map.entry(field_name).or_insert(field);
         self.fields.push(field_entry);
         field
     }
 
+    fn add_field_with_type(&mut self, field_name: &str, field_type: FieldType) -> Field {
+        let field_entry = FieldEntry::new_unchecked(field_name.to_string(), field_type);
+        self.add_field(field_entry)
+    }
+
     /// Finalize the creation of a `Schema`
     /// This will consume your `SchemaBuilder`
+    ///
+    /// # Panics
+    ///
+    /// Panics when a field name is invalid or is used by several fields, listing all of the
+    /// offending fields. Use [`SchemaBuilder::try_build`] to get these problems, and the
+    /// conflicting options of the fields, as an error instead.
     pub fn build(self) -> Schema {
+        let problems: Vec<SchemaProblem> = validate_field_entries(&self.fields, None)
+            .into_iter()
+            .filter(SchemaProblem::is_fatal)
+            .collect();
+        if !problems.is_empty() {
+            panic!("{}", SchemaValidationError::new(problems));
+        }
+        self.build_unchecked()
+    }
+
+    /// Validates all of the fields, and finalizes the creation of a `Schema`.
+    ///
+    /// Instead of stopping at the first problem, all of the fields are checked, and the
+    /// returned error lists every invalid or duplicate field name and every field with
+    /// conflicting options.
+    pub fn try_build(self) -> Result<Schema, SchemaValidationError> {
+        self.validate(None)
+    }
+
+    /// Same as [`SchemaBuilder::try_build`], also checking that the tokenizers the fields
+    /// refer to are registered.
+    ///
+    /// The tokenizers used for indexing are looked up in `tokenizers`, and the tokenizers of
+    /// the fast fields in `fast_field_tokenizers`, as
+    /// [`Index::tokenizers`](crate::Index::tokenizers) and
+    /// [`Index::fast_field_tokenizer`](crate::Index::fast_field_tokenizer) do.
+    pub fn try_build_with_tokenizers(
+        self,
+        tokenizers: &TokenizerManager,
+        fast_field_tokenizers: &TokenizerManager,
+    ) -> Result<Schema, SchemaValidationError> {
+        self.validate(Some((tokenizers, fast_field_tokenizers)))
+    }
+
+    fn validate(
+        self,
+        tokenizers_opt: Option<(&TokenizerManager, &TokenizerManager)>,
+    ) -> Result<Schema, SchemaValidationError> {
+        let problems = validate_field_entries(&self.fields, tokenizers_opt);
+        if !problems.is_empty() {
+            return Err(SchemaValidationError::new(problems));
+        }
+        Ok(self.build_unchecked())
+    }
+
+    fn build_unchecked(self) -> Schema {
         Schema(Arc::new(InnerSchema {
             fields: self.fields,
             fields_map: self.fields_map,
@@ -301,6 +343,52 @@ impl Schema {
             .map(|(field_id, field_entry)| (Field::from_field_id(field_id as u32), field_entry))
     }
 
+    /// Returns the schema with the given values, by field name, registered in the
+    /// dictionaries of its enum fields.
+    ///
+    /// The schema is shared rather than copied if all of the values are already known.
+    pub(crate) fn with_registered_enum_values<'a>(
+        &self,
+        registered_enum_values: impl IntoIterator<Item = &'a BTreeMap<String, BTreeSet<String>>>,
+    ) -> Schema {
+        let mut fields: Option<Vec<FieldEntry>> = None;
+        for registered_values in registered_enum_values {
+            for (field_name, values) in registered_values {
+                let Some(&field) = self.0.fields_map.get(field_name) else {
+                    continue;
+                };
+                let field_entry =
+                    &fields.as_ref().unwrap_or(&self.0.fields)[field.field_id() as usize];
+                let FieldType::Str(text_options) = field_entry.field_type() else {
+                    continue;
+                };
+                let Some(enum_values) = text_options.get_enum_values() else {
+                    continue;
+                };
+                if values
+                    .iter()
+                    .all(|value| enum_values.ordinal(value).is_some())
+                {
+                    continue;
+                }
+                let text_options = text_options
+                    .clone()
+                    .set_enum_values(enum_values.clone().with_registered_values(values));
+                let field_entry =
+                    FieldEntry::new(field_entry.name().to_string(), FieldType::Str(text_options));
+                fields.get_or_insert_with(|| self.0.fields.clone())[field.field_id() as usize] =
+                    field_entry;
+            }
+        }
+        match fields {
+            Some(fields) => Schema(Arc::new(InnerSchema {
+                fields,
+                fields_map: self.0.fields_map.clone(),
+            })),
+            None => self.clone(),
+        }
+    }
+
     /// Creates a new builder.
     pub fn builder() -> SchemaBuilder {
         SchemaBuilder::default()
@@ -315,6 +403,22 @@ impl Schema {
             .ok_or_else(|| TantivyError::FieldNotFound(field_name.to_string()))
     }
 
+    /// Returns the field whose name is the segments of `path` joined with dots.
+    ///
+    /// Unlike [`Schema::find_field`], all of the segments belong to the field name, and they are
+    /// not escaped: `["user", "name"]` and `["user.name"]` both return the `user.name` field.
+    pub fn get_field_by_path<S: AsRef<str>>(&self, path: &[S]) -> Option<Field> {
+        if path.is_empty() {
+            return None;
+        }
+        let field_name = path
+            .iter()
+            .map(|segment| segment.as_ref())
+            .collect::<Vec<&str>>()
+            .join(".");
+        self.0.fields_map.get(&field_name).cloned()
+    }
+
     /// Searches for a full_path in the schema, returning the field name and a JSON path.
     ///
     /// This function works by checking if the field exists for the exact given full_path.
@@ -327,6 +431,13 @@ impl Schema {
         if let Some(field) = self.0.fields_map.get(full_path) {
             return Some((*field, ""));
         }
+        // The field name may contain escaped characters, as in `my\\field`.
+        if full_path.contains('\\') {
+            let unescaped_path = split_json_path(full_path).join(".");
+            if let Some(field) = self.0.fields_map.get(&unescaped_path) {
+                return Some((*field, ""));
+            }
+        }
 
         let mut splitting_period_pos: Vec<usize> = locate_splitting_dots(full_path);
         while let Some(pos) = splitting_period_pos.pop() {
@@ -653,7 +764,8 @@ mod tests {
         );
         assert_eq!(
             doc.get_all(val).map(OwnedValue::from).collect::<Vec<_>>(),
-            vec![OwnedValue::from(14u64), OwnedValue::from(-1i64)]
+            // The u64 value is converted to the type of the field.
+            vec![OwnedValue::from(14i64), OwnedValue::from(-1i64)]
         );
     }
 
@@ -668,6 +780,199 @@ mod tests {
         TantivyDocument::convert_named_doc(&schema, NamedFieldDocument(named_doc_map)).unwrap();
     }
 
+    #[test]
+    pub fn test_document_missing_field_strict() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let mut named_doc_map = BTreeMap::default();
+        named_doc_map.insert("title".to_string(), vec![OwnedValue::from("title1")]);
+        named_doc_map.insert("titel".to_string(), vec![OwnedValue::from("title2")]);
+        assert_eq!(
+            TantivyDocument::convert_named_doc_with_mode(
+                &schema,
+                NamedFieldDocument(named_doc_map),
+                ParseMode::Strict
+            ),
+            Err(DocParsingError::NoSuchFieldInSchema("titel".to_string()))
+        );
+    }
+
+    #[test]
+    pub fn test_document_missing_field_lossy() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let count = schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+        let named_doc = || {
+            NamedFieldDocument(BTreeMap::from([
+                (
+                    "title".to_string(),
+                    vec![OwnedValue::from("title1"), OwnedValue::from("title2")],
+                ),
+                ("titel".to_string(), vec![OwnedValue::from("title3")]),
+                (
+                    "count".to_string(),
+                    vec![OwnedValue::U64(1), OwnedValue::U64(2)],
+                ),
+                (
+                    "author".to_string(),
+                    vec![OwnedValue::from("paul"), OwnedValue::from("pierre")],
+                ),
+            ]))
+        };
+        let (doc, dropped_fields) =
+            TantivyDocument::convert_named_doc_lossy(&schema, named_doc()).unwrap();
+        assert_eq!(
+            dropped_fields,
+            vec!["author".to_string(), "titel".to_string()]
+        );
+        assert_eq!(
+            doc.get_all(title)
+                .flat_map(|value| value.as_str())
+                .collect::<Vec<_>>(),
+            vec!["title1", "title2"]
+        );
+        assert_eq!(
+            doc.get_all(count)
+                .flat_map(|value| value.as_u64())
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        // Same document as the lenient conversion, while the strict one reports the first
+        // unknown field.
+        assert_eq!(
+            TantivyDocument::convert_named_doc(&schema, named_doc()).unwrap(),
+            doc
+        );
+        assert_eq!(
+            TantivyDocument::convert_named_doc_with_mode(&schema, named_doc(), ParseMode::Strict),
+            Err(DocParsingError::NoSuchFieldInSchema("author".to_string()))
+        );
+    }
+
+    #[test]
+    pub fn test_document_from_nameddoc_type_mismatch() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("count", INDEXED);
+        let delta = schema_builder.add_i64_field("delta", INDEXED);
+        schema_builder.add_f64_field("score", INDEXED);
+        schema_builder.add_bool_field("flag", INDEXED);
+        schema_builder.add_date_field("date", INDEXED);
+        schema_builder.add_facet_field("category", FacetOptions::default());
+        schema_builder.add_bytes_field("bytes", STORED);
+        schema_builder.add_ip_addr_field("ip", INDEXED);
+        schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+        let invalid_values = [
+            ("title", OwnedValue::U64(3)),
+            (
+                "title",
+                OwnedValue::Date(crate::DateTime::from_timestamp_secs(0)),
+            ),
+            ("count", OwnedValue::Str("three".to_string())),
+            ("count", OwnedValue::I64(-3)),
+            ("delta", OwnedValue::Bool(true)),
+            ("score", OwnedValue::Str("0.5".to_string())),
+            ("flag", OwnedValue::U64(1)),
+            ("date", OwnedValue::Str("yesterday".to_string())),
+            ("category", OwnedValue::U64(1)),
+            ("bytes", OwnedValue::Str("not base64!".to_string())),
+            ("ip", OwnedValue::Bytes(vec![127, 0, 0, 1])),
+            ("attributes", OwnedValue::Str("red".to_string())),
+        ];
+        for (field_name, value) in &invalid_values {
+            let named_doc = NamedFieldDocument(BTreeMap::from([(
+                field_name.to_string(),
+                vec![value.clone()],
+            )]));
+            let error = TantivyDocument::convert_named_doc(&schema, named_doc).unwrap_err();
+            assert!(
+                matches!(error, DocParsingError::ValueError(ref name, _) if name == field_name),
+                "{field_name}: {value:?}"
+            );
+        }
+
+        // Values are converted like JSON values.
+        let named_doc = NamedFieldDocument(BTreeMap::from([
+            ("delta".to_string(), vec![OwnedValue::U64(3)]),
+            (
+                "title".to_string(),
+                vec![OwnedValue::Str("title".to_string())],
+            ),
+        ]));
+        let doc = TantivyDocument::convert_named_doc(&schema, named_doc).unwrap();
+        assert_eq!(doc.get_first_i64(delta), Some(3));
+
+        // Invalid values can be skipped instead.
+        let mut named_doc_map: BTreeMap<String, Vec<OwnedValue>> = BTreeMap::new();
+        for (field_name, value) in invalid_values {
+            named_doc_map
+                .entry(field_name.to_string())
+                .or_default()
+                .push(value);
+        }
+        named_doc_map
+            .get_mut("title")
+            .unwrap()
+            .push(OwnedValue::Array(vec![OwnedValue::Str(
+                "valid".to_string(),
+            )]));
+        let (doc, errors) = TantivyDocument::convert_named_doc_skipping_invalid_values(
+            &schema,
+            NamedFieldDocument(named_doc_map),
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(errors.len(), 12);
+        assert_eq!(doc.field_values().count(), 1);
+        assert_eq!(doc.get_first_str(title), Some("valid"));
+        assert_eq!(
+            errors[0],
+            DocParsingError::ValueError(
+                "attributes".to_string(),
+                ValueParsingError::TypeError {
+                    expected: "a json object",
+                    json: serde_json::json!("red"),
+                }
+            )
+        );
+    }
+
+    #[test]
+    pub fn test_parse_document_strict() {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let attributes_field = schema_builder.add_json_field("attributes", TEXT);
+        let schema = schema_builder.build();
+        let doc = TantivyDocument::parse_json_strict(
+            &schema,
+            r#"{
+                "title": ["my title", "my other title"],
+                "attributes": {"color": "red", "sizes": [1, 2]}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(doc.get_all(title_field).count(), 2);
+        assert_eq!(doc.get_all(attributes_field).count(), 1);
+        let doc_json = r#"{
+            "title": "my title",
+            "attributes.color": "red"
+        }"#;
+        assert!(TantivyDocument::parse_json(&schema, doc_json).is_ok());
+        assert_eq!(
+            TantivyDocument::parse_json_strict(&schema, doc_json),
+            Err(DocParsingError::NoSuchFieldInSchema(
+                "attributes.color".to_string()
+            ))
+        );
+        assert_eq!(
+            TantivyDocument::parse_json_with_mode(&schema, doc_json, ParseMode::Lenient),
+            TantivyDocument::parse_json(&schema, doc_json)
+        );
+    }
+
     #[test]
     pub fn test_parse_document() {
         let mut schema_builder = Schema::builder();
@@ -818,6 +1123,92 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_get_field_by_path() {
+        let mut schema_builder = Schema::builder();
+        let user_name = schema_builder.add_text_field("user.name", STRING);
+        let user = schema_builder.add_json_field("user", STORED);
+        let schema = schema_builder.build();
+        assert_eq!(schema.get_field_by_path(&["user", "name"]), Some(user_name));
+        assert_eq!(schema.get_field_by_path(&["user.name"]), Some(user_name));
+        assert_eq!(schema.get_field_by_path(&["user"]), Some(user));
+        assert_eq!(schema.get_field_by_path(&["user", "age"]), None);
+        assert_eq!(schema.get_field_by_path::<&str>(&[]), None);
+    }
+
+    #[test]
+    pub fn test_from_flattened_json() {
+        let mut schema_builder = Schema::builder();
+        let user_name = schema_builder.add_text_field("user.name", STRING);
+        let user_age = schema_builder.add_u64_field("user.age", INDEXED);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+        let str_values = |doc: &TantivyDocument, field: Field| -> Vec<String> {
+            doc.get_all(field)
+                .flat_map(|value| value.as_str().map(str::to_string))
+                .collect()
+        };
+
+        let doc = TantivyDocument::from_flattened_json(
+            &schema,
+            r#"{
+                "user": {"name": "Alice", "age": 31, "country": "fr"},
+                "title": "my title",
+                "unknown": {"field": 3}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(str_values(&doc, user_name), vec!["Alice"]);
+        assert_eq!(doc.get_first(user_age).unwrap().as_u64(), Some(31));
+        assert_eq!(str_values(&doc, title), vec!["my title"]);
+        assert_eq!(doc.field_values().count(), 3);
+
+        // Dotted and escaped keys match the field names that contain dots.
+        for doc_json in [r#"{"user.name": "Alice"}"#, r#"{"user\\.name": "Alice"}"#] {
+            let doc = TantivyDocument::from_flattened_json(&schema, doc_json).unwrap();
+            assert_eq!(str_values(&doc, user_name), vec!["Alice"], "{doc_json}");
+        }
+
+        // Arrays at intermediate levels give several values to the field.
+        let doc = TantivyDocument::from_flattened_json(
+            &schema,
+            r#"{
+                "user": [{"name": "Alice"}, {"name": ["Bob", "Carol"]}],
+                "user.name": "Dave"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            str_values(&doc, user_name),
+            vec!["Alice", "Bob", "Carol", "Dave"]
+        );
+
+        // The objects reaching a JSON field are not flattened.
+        let doc = TantivyDocument::from_flattened_json(
+            &schema,
+            r#"{"attributes": {"color": "red", "size": {"width": 3}}}"#,
+        )
+        .unwrap();
+        let attributes_value = OwnedValue::from(doc.get_first(attributes).unwrap());
+        assert_eq!(
+            serde_json::to_value(&attributes_value).unwrap(),
+            serde_json::json!({"color": "red", "size": {"width": 3}})
+        );
+        let doc = TantivyDocument::from_flattened_json(&schema, r#"{"attributes.color": "red"}"#)
+            .unwrap();
+        let attributes_value = OwnedValue::from(doc.get_first(attributes).unwrap());
+        assert_eq!(
+            serde_json::to_value(&attributes_value).unwrap(),
+            serde_json::json!({"color": "red"})
+        );
+
+        // The values are checked against the type of their field.
+        let error = TantivyDocument::from_flattened_json(&schema, r#"{"user": {"age": "old"}}"#)
+            .unwrap_err();
+        assert!(matches!(error, DocParsingError::ValueError(ref name, _) if name == "user.age"));
+    }
+
     #[test]
     pub fn test_schema_add_field() {
         let mut schema_builder = SchemaBuilder::default();
@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use thiserror::Error;
+
+use super::{is_valid_field_name, FieldEntry, FieldType, TextFieldIndexing};
+use crate::tokenizer::TokenizerManager;
+
+/// A problem found in a field of a schema being built.
+///
+/// See [`SchemaBuilder::try_build`](crate::schema::SchemaBuilder::try_build).
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SchemaProblem {
+    /// The field name is empty, or starts with a `-`.
+    #[error("Invalid field name {0:?}")]
+    InvalidFieldName(String),
+    /// Several fields have the same name.
+    #[error("Field {0:?} is defined more than once")]
+    DuplicateFieldName(String),
+    /// Some options of the field contradict each other, or have no effect.
+    #[error("Field {field_name:?} has conflicting options: {reason}")]
+    ConflictingOptions {
+        /// Name of the field.
+        field_name: String,
+        /// What is wrong with the options.
+        reason: String,
+    },
+    /// The field refers to a tokenizer which is not registered.
+    #[error("Field {field_name:?} uses the unknown tokenizer {tokenizer_name:?}")]
+    UnknownTokenizer {
+        /// Name of the field.
+        field_name: String,
+        /// Name of the tokenizer.
+        tokenizer_name: String,
+    },
+}
+
+impl SchemaProblem {
+    /// Returns the name of the field the problem is about.
+    pub fn field_name(&self) -> &str {
+        match self {
+            SchemaProblem::InvalidFieldName(field_name)
+            | SchemaProblem::DuplicateFieldName(field_name)
+            | SchemaProblem::ConflictingOptions { field_name, .. }
+            | SchemaProblem::UnknownTokenizer { field_name, .. } => field_name,
+        }
+    }
+
+    /// Returns true if the schema cannot be used at all with this problem.
+    pub(crate) fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            SchemaProblem::InvalidFieldName(_) | SchemaProblem::DuplicateFieldName(_)
+        )
+    }
+}
+
+/// Error returned when building a schema, listing all of the problems of its fields.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub struct SchemaValidationError {
+    problems: Vec<SchemaProblem>,
+}
+
+impl SchemaValidationError {
+    pub(crate) fn new(problems: Vec<SchemaProblem>) -> SchemaValidationError {
+        SchemaValidationError { problems }
+    }
+
+    /// Returns the problems found, in the order of the fields.
+    pub fn problems(&self) -> &[SchemaProblem] {
+        &self.problems
+    }
+}
+
+impl fmt::Display for SchemaValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid schema, {} problem(s) found",
+            self.problems.len()
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n- {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns all of the problems of the given fields.
+///
+/// If `tokenizers_opt` is set, the tokenizers used for indexing are checked against the first
+/// manager, and the tokenizers of the fast fields against the second one.
+pub(crate) fn validate_field_entries(
+    field_entries: &[FieldEntry],
+    tokenizers_opt: Option<(&TokenizerManager, &TokenizerManager)>,
+) -> Vec<SchemaProblem> {
+    let mut problems = Vec::new();
+    let mut field_names: HashSet<&str> = HashSet::new();
+    let mut reported_duplicates: HashSet<&str> = HashSet::new();
+    for field_entry in field_entries {
+        let field_name = field_entry.name();
+        if !is_valid_field_name(field_name) {
+            problems.push(SchemaProblem::InvalidFieldName(field_name.to_string()));
+        }
+        if !field_names.insert(field_name) && reported_duplicates.insert(field_name) {
+            problems.push(SchemaProblem::DuplicateFieldName(field_name.to_string()));
+        }
+        if let Some(reason) = conflicting_options(field_entry.field_type()) {
+            problems.push(SchemaProblem::ConflictingOptions {
+                field_name: field_name.to_string(),
+                reason: reason.to_string(),
+            });
+        }
+        if let Some((tokenizers, fast_field_tokenizers)) = tokenizers_opt {
+            let (indexing_opt, fast_field_tokenizer_opt) = match field_entry.field_type() {
+                FieldType::Str(text_options) => (
+                    text_options.get_indexing_options(),
+                    text_options.get_fast_field_tokenizer_name(),
+                ),
+                FieldType::JsonObject(json_options) => (
+                    json_options.get_text_indexing_options(),
+                    json_options.get_fast_field_tokenizer_name(),
+                ),
+                _ => (None, None),
+            };
+            let indexing_tokenizer_opt = indexing_opt.map(TextFieldIndexing::tokenizer);
+            for (tokenizer_name_opt, tokenizer_manager) in [
+                (indexing_tokenizer_opt, tokenizers),
+                (fast_field_tokenizer_opt, fast_field_tokenizers),
+            ] {
+                let Some(tokenizer_name) = tokenizer_name_opt else {
+                    continue;
+                };
+                if tokenizer_manager.get(tokenizer_name).is_none() {
+                    problems.push(SchemaProblem::UnknownTokenizer {
+                        field_name: field_name.to_string(),
+                        tokenizer_name: tokenizer_name.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn conflicting_options(field_type: &FieldType) -> Option<&'static str> {
+    match field_type {
+        FieldType::Str(text_options)
+            if text_options.is_dictionary_encoded() && !text_options.is_stored() =>
+        {
+            Some("dictionary encoding requires the field to be stored")
+        }
+        FieldType::F32Vector(f32_vector_options) if f32_vector_options.dimensions() == Some(0) => {
+            Some("the vectors must have at least one dimension")
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{
+        F32VectorOptions, Schema, SchemaProblem, TextFieldIndexing, TextOptions, FAST, STORED,
+        STRING, TEXT,
+    };
+    use crate::tokenizer::TokenizerManager;
+
+    #[test]
+    fn test_try_build_reports_all_problems() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("-body", TEXT);
+        schema_builder.add_u64_field("title", FAST);
+        schema_builder.add_text_field("title", STRING);
+        schema_builder.add_text_field("source", STRING.set_dictionary_encoded());
+        schema_builder
+            .add_f32_vector_field("embedding", F32VectorOptions::from(FAST).set_dimensions(0));
+        schema_builder.add_text_field("", STORED);
+        let error = schema_builder.try_build().unwrap_err();
+        assert_eq!(
+            error.problems(),
+            &[
+                SchemaProblem::InvalidFieldName("-body".to_string()),
+                SchemaProblem::DuplicateFieldName("title".to_string()),
+                SchemaProblem::ConflictingOptions {
+                    field_name: "source".to_string(),
+                    reason: "dictionary encoding requires the field to be stored".to_string(),
+                },
+                SchemaProblem::ConflictingOptions {
+                    field_name: "embedding".to_string(),
+                    reason: "the vectors must have at least one dimension".to_string(),
+                },
+                SchemaProblem::InvalidFieldName("".to_string()),
+            ]
+        );
+        let message = error.to_string();
+        assert!(message.starts_with("Invalid schema, 5 problem(s) found"));
+        for field_name in ["-body", "title", "source", "embedding"] {
+            assert!(message.contains(&format!("{field_name:?}")));
+        }
+    }
+
+    #[test]
+    fn test_try_build_unknown_tokenizers() {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("my_tokenizer"))
+            .set_fast(Some("my_normalizer"));
+        schema_builder.add_text_field("body", text_options);
+        schema_builder.add_text_field("title", TEXT | FAST);
+        let error = schema_builder
+            .try_build_with_tokenizers(&TokenizerManager::default(), &TokenizerManager::default())
+            .unwrap_err();
+        assert_eq!(
+            error.problems(),
+            &[
+                SchemaProblem::UnknownTokenizer {
+                    field_name: "body".to_string(),
+                    tokenizer_name: "my_tokenizer".to_string(),
+                },
+                SchemaProblem::UnknownTokenizer {
+                    field_name: "body".to_string(),
+                    tokenizer_name: "my_normalizer".to_string(),
+                },
+            ]
+        );
+        assert_eq!(error.problems()[0].field_name(), "body");
+    }
+
+    #[test]
+    fn test_try_build_valid_schema() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let schema = schema_builder
+            .try_build_with_tokenizers(&TokenizerManager::default(), &TokenizerManager::default())
+            .unwrap();
+        assert_eq!(schema.get_field("title").unwrap(), title);
+    }
+
+    #[test]
+    #[should_panic(expected = "Field \"title\" is defined more than once")]
+    fn test_build_panics_on_duplicate_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_u64_field("title", FAST);
+        schema_builder.build();
+    }
+
+    #[test]
+    fn test_build_accepts_conflicting_options() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("source", STRING.set_dictionary_encoded());
+        let schema = schema_builder.build();
+        assert_eq!(schema.num_fields(), 1);
+    }
+}
@@ -2,13 +2,13 @@ use std::hash::Hash;
 use std::net::Ipv6Addr;
 use std::{fmt, str};
 
-use columnar::MonotonicallyMappableToU128;
+use columnar::{MonotonicallyMappableToU128, NumericalValue};
 use common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP_STR};
 use common::JsonPathWriter;
 use serde::{Deserialize, Serialize};
 
 use super::date_time_options::DATE_TIME_PRECISION_INDEXED;
-use super::{Field, Schema};
+use super::{Field, OwnedValue, Schema};
 use crate::fastfield::FastValue;
 use crate::json_utils::split_json_path;
 use crate::schema::{Facet, Type};
@@ -89,6 +89,138 @@ impl Term {
         term
     }
 
+    /// Builds a term for a text token at the given path of a JSON field.
+    ///
+    /// The text is used as is: it needs to be a token produced by the tokenizer of the JSON
+    /// field to match the indexed documents, e.g. it needs to be lowercased with the default
+    /// tokenizer. See [`Term::from_field_json_path`] for the syntax of `json_path` and the role
+    /// of `expand_dots_enabled`, which needs to match the options of the field.
+    pub fn from_json_path_text(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        text: &str,
+    ) -> Term {
+        let mut term = Term::from_field_json_path(field, json_path, expand_dots_enabled);
+        term.append_type_and_str(text);
+        term
+    }
+
+    /// Builds a term for a `u64` value at the given path of a JSON field.
+    ///
+    /// As when indexing JSON documents, the value is stored as an `i64` if it fits in one.
+    pub fn from_json_path_u64(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        val: u64,
+    ) -> Term {
+        Term::from_json_path_numerical(
+            field,
+            json_path,
+            expand_dots_enabled,
+            NumericalValue::U64(val),
+        )
+    }
+
+    /// Builds a term for an `i64` value at the given path of a JSON field.
+    pub fn from_json_path_i64(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        val: i64,
+    ) -> Term {
+        Term::from_json_path_numerical(
+            field,
+            json_path,
+            expand_dots_enabled,
+            NumericalValue::I64(val),
+        )
+    }
+
+    /// Builds a term for an `f64` value at the given path of a JSON field.
+    ///
+    /// As when indexing JSON documents, the value is stored as an `i64` or a `u64` if it is an
+    /// integer.
+    pub fn from_json_path_f64(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        val: f64,
+    ) -> Term {
+        Term::from_json_path_numerical(
+            field,
+            json_path,
+            expand_dots_enabled,
+            NumericalValue::F64(val),
+        )
+    }
+
+    fn from_json_path_numerical(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        val: NumericalValue,
+    ) -> Term {
+        let mut term = Term::from_field_json_path(field, json_path, expand_dots_enabled);
+        match val.normalize() {
+            NumericalValue::I64(val) => term.append_type_and_fast_value(val),
+            NumericalValue::U64(val) => term.append_type_and_fast_value(val),
+            NumericalValue::F64(val) => term.append_type_and_fast_value(val),
+        }
+        term
+    }
+
+    /// Builds a term for a `bool` value at the given path of a JSON field.
+    pub fn from_json_path_bool(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        val: bool,
+    ) -> Term {
+        let mut term = Term::from_field_json_path(field, json_path, expand_dots_enabled);
+        term.append_type_and_fast_value(val);
+        term
+    }
+
+    /// Builds a term for a `DateTime` value at the given path of a JSON field.
+    ///
+    /// As when indexing JSON documents, the value is truncated to the precision used in the
+    /// index ([super::DATE_TIME_PRECISION_INDEXED]).
+    pub fn from_json_path_date(
+        field: Field,
+        json_path: &str,
+        expand_dots_enabled: bool,
+        val: DateTime,
+    ) -> Term {
+        let mut term = Term::from_field_json_path(field, json_path, expand_dots_enabled);
+        term.append_type_and_fast_value(val.truncate(DATE_TIME_PRECISION_INDEXED));
+        term
+    }
+
+    /// Returns the json path of a JSON term, in the syntax accepted by
+    /// [`Term::from_field_json_path`]: segments are separated by dots, and the dots and
+    /// backslashes within a segment are escaped with a backslash.
+    ///
+    /// Returns `None` if the term is not a JSON term.
+    pub fn json_path(&self) -> Option<String> {
+        let value = self.value();
+        let (json_path_bytes, _) = value.as_json()?;
+        let json_path = str::from_utf8(&json_path_bytes[..json_path_bytes.len() - 1]).ok()?;
+        let segments: Vec<String> = json_path
+            .split(JSON_PATH_SEGMENT_SEP_STR)
+            .map(|segment| segment.replace('\\', "\\\\").replace('.', "\\."))
+            .collect();
+        Some(segments.join("."))
+    }
+
+    /// Returns the value of the term, or the value after the json path for a JSON term.
+    ///
+    /// Returns `None` if the term byte representation is invalid.
+    pub fn typed_value(&self) -> Option<OwnedValue> {
+        self.value().typed_value()
+    }
+
     /// Gets the full path of the field name + optional json path.
     pub fn get_full_path(&self, schema: &Schema) -> String {
         let field = self.field();
@@ -466,6 +598,28 @@ where B: AsRef<[u8]>
         }
     }
 
+    /// Returns the value, or the value after the json path for a JSON value.
+    ///
+    /// Returns `None` if the byte representation is invalid.
+    pub fn typed_value(&self) -> Option<OwnedValue> {
+        if self.0.as_ref().is_empty() {
+            return None;
+        }
+        let value = match self.typ() {
+            Type::Str => OwnedValue::Str(self.as_str()?.to_string()),
+            Type::U64 => OwnedValue::U64(self.as_u64()?),
+            Type::I64 => OwnedValue::I64(self.as_i64()?),
+            Type::F64 => OwnedValue::F64(self.as_f64()?),
+            Type::Bool => OwnedValue::Bool(self.as_bool()?),
+            Type::Date => OwnedValue::Date(self.as_date()?),
+            Type::Facet => OwnedValue::Facet(self.as_facet()?),
+            Type::Bytes => OwnedValue::Bytes(self.as_bytes()?.to_vec()),
+            Type::IpAddr => OwnedValue::IpAddr(self.as_ip_addr()?),
+            Type::Json => return self.as_json_value_bytes()?.typed_value(),
+        };
+        Some(value)
+    }
+
     /// Returns the serialized representation of the value bytes including the type tag.
     pub fn as_serialized(&self) -> &[u8] {
         self.0.as_ref()
@@ -540,7 +694,12 @@ impl fmt::Debug for Term {
 #[cfg(test)]
 mod tests {
 
+    use std::collections::BTreeMap;
+
+    use crate::collector::Count;
+    use crate::query::TermQuery;
     use crate::schema::*;
+    use crate::{DateTime, Index, IndexWriter};
 
     #[test]
     pub fn test_term_str() {
@@ -574,4 +733,125 @@ mod tests {
         assert_eq!(term.serialized_value_bytes().len(), 8);
         assert_eq!(term.value().as_bool(), Some(true))
     }
+
+    #[test]
+    pub fn test_json_path_terms_match_indexed_documents() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let attributes = schema_builder.add_json_field("attributes", STRING);
+        let expanded = schema_builder.add_json_field(
+            "expanded",
+            JsonObjectOptions::from(STRING).set_expand_dots_enabled(),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let date = DateTime::from_timestamp_micros(1_700_000_000_123_456);
+        for field in [attributes, expanded] {
+            let mut doc = TantivyDocument::default();
+            doc.add_object(
+                field,
+                BTreeMap::from([
+                    (
+                        "k8s".to_string(),
+                        OwnedValue::Object(vec![(
+                            "node.name".to_string(),
+                            OwnedValue::Str("Node-1".to_string()),
+                        )]),
+                    ),
+                    ("count".to_string(), OwnedValue::U64(5)),
+                    ("big".to_string(), OwnedValue::U64(u64::MAX)),
+                    ("delta".to_string(), OwnedValue::I64(-3)),
+                    ("ratio".to_string(), OwnedValue::F64(2.0)),
+                    ("score".to_string(), OwnedValue::F64(0.5)),
+                    ("enabled".to_string(), OwnedValue::Bool(true)),
+                    ("created".to_string(), OwnedValue::Date(date)),
+                ]),
+            );
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let count = |term: &Term| -> usize {
+            let query = TermQuery::new(term.clone(), IndexRecordOption::Basic);
+            searcher.search(&query, &Count).unwrap()
+        };
+        for (field, expand_dots) in [(attributes, false), (expanded, true)] {
+            let node_path = if expand_dots {
+                "k8s.node.name"
+            } else {
+                r"k8s.node\.name"
+            };
+            let terms = [
+                Term::from_json_path_text(field, node_path, expand_dots, "Node-1"),
+                Term::from_json_path_u64(field, "count", expand_dots, 5),
+                Term::from_json_path_i64(field, "count", expand_dots, 5),
+                Term::from_json_path_f64(field, "count", expand_dots, 5.0),
+                Term::from_json_path_u64(field, "big", expand_dots, u64::MAX),
+                Term::from_json_path_i64(field, "delta", expand_dots, -3),
+                Term::from_json_path_u64(field, "ratio", expand_dots, 2),
+                Term::from_json_path_f64(field, "score", expand_dots, 0.5),
+                Term::from_json_path_bool(field, "enabled", expand_dots, true),
+                Term::from_json_path_date(field, "created", expand_dots, date),
+            ];
+            for term in &terms {
+                assert_eq!(count(term), 1, "{term:?}");
+                let json_path = term.json_path().unwrap();
+                let mut rebuilt_term = Term::from_field_json_path(field, &json_path, expand_dots);
+                rebuilt_term
+                    .append_bytes(term.value().as_json_value_bytes().unwrap().as_serialized());
+                assert_eq!(&rebuilt_term, term);
+            }
+            assert_eq!(
+                count(&Term::from_json_path_bool(
+                    field,
+                    "enabled",
+                    expand_dots,
+                    false
+                )),
+                0
+            );
+        }
+        // Without expand dots, the dot of the key is not a separator.
+        assert_eq!(
+            count(&Term::from_json_path_text(
+                attributes,
+                "k8s.node.name",
+                false,
+                "Node-1"
+            )),
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_json_path_term_inspection() {
+        let field = Field::from_field_id(1);
+        let term = Term::from_json_path_text(field, r"k8s.node\.name", false, "node-1");
+        assert_eq!(term.json_path().as_deref(), Some(r"k8s.node\.name"));
+        assert_eq!(
+            term.typed_value(),
+            Some(OwnedValue::Str("node-1".to_string()))
+        );
+        let term = Term::from_json_path_text(field, r"k8s.node\.name", true, "node-1");
+        assert_eq!(term.json_path().as_deref(), Some("k8s.node.name"));
+        let term = Term::from_json_path_u64(field, "count", false, 5);
+        assert_eq!(term.typed_value(), Some(OwnedValue::I64(5)));
+        let term = Term::from_json_path_f64(field, "score", false, 0.5);
+        assert_eq!(term.typed_value(), Some(OwnedValue::F64(0.5)));
+        let date = DateTime::from_timestamp_micros(1_700_000_000_123_456);
+        let term = Term::from_json_path_date(field, "created", false, date);
+        assert_eq!(
+            term.typed_value(),
+            Some(OwnedValue::Date(DateTime::from_timestamp_secs(
+                1_700_000_000
+            )))
+        );
+        assert_eq!(
+            Term::from_field_json_path(field, "count", false).typed_value(),
+            None
+        );
+        let term = Term::from_field_u64(field, 7);
+        assert_eq!(term.json_path(), None);
+        assert_eq!(term.typed_value(), Some(OwnedValue::U64(7)));
+    }
 }
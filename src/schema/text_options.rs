@@ -4,6 +4,7 @@ use std::ops::BitOr;
 use serde::{Deserialize, Serialize};
 
 use super::flags::{CoerceFlag, FastFlag};
+use crate::schema::enum_values::EnumValues;
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
 use crate::schema::IndexRecordOption;
 
@@ -21,6 +22,14 @@ pub struct TextOptions {
     #[serde(skip_serializing_if = "is_false")]
     /// coerce values into string if they are not of type string
     coerce: bool,
+    #[serde(default)]
+    #[serde(rename = "enum")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enum_values: Option<EnumValues>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    /// store the values as ids referencing a per-segment dictionary of the values
+    dictionary_encoded: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -138,6 +147,43 @@ impl TextOptions {
         self
     }
 
+    /// Returns the dictionary of the values of the field, if it is an enum field.
+    #[inline]
+    pub fn get_enum_values(&self) -> Option<&EnumValues> {
+        self.enum_values.as_ref()
+    }
+
+    /// Makes the field an enum field, only accepting the values of the given dictionary.
+    ///
+    /// See [`EnumValues`].
+    #[must_use]
+    pub fn set_enum_values(mut self, enum_values: EnumValues) -> TextOptions {
+        self.enum_values = Some(enum_values);
+        self
+    }
+
+    /// Returns true if the stored values reference a per-segment dictionary of the values.
+    #[inline]
+    pub fn is_dictionary_encoded(&self) -> bool {
+        self.dictionary_encoded
+    }
+
+    /// Stores the values of the field as ids referencing a dictionary of the values of the
+    /// segment, written once in the segment instead of in every block of the doc store.
+    ///
+    /// This saves space for fields with few distinct values, like the name of the system a
+    /// document comes from. Values are resolved transparently when retrieving documents. Once
+    /// the dictionary of a segment holds
+    /// [`MAX_STORE_DICTIONARY_SIZE`](crate::store::MAX_STORE_DICTIONARY_SIZE) values, new
+    /// values are stored literally.
+    ///
+    /// This only has an effect if the field is stored.
+    #[must_use]
+    pub fn set_dictionary_encoded(mut self) -> TextOptions {
+        self.dictionary_encoded = true;
+        self
+    }
+
     /// Coerce values if they are not of type string. Defaults to false.
     #[must_use]
     pub fn set_coerce(mut self) -> TextOptions {
@@ -194,6 +240,8 @@ impl TokenizerName {
 /// - The name of the `Tokenizer` that should be used to process the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - Flag indicating, if the byte offsets of the tokens should be stored along with their
+///   positions. Defaults to `false`.
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -201,6 +249,9 @@ pub struct TextFieldIndexing {
     #[serde(default = "default_fieldnorms")]
     fieldnorms: bool,
     #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    offsets: bool,
+    #[serde(default)]
     tokenizer: TokenizerName,
 }
 
@@ -214,6 +265,7 @@ impl Default for TextFieldIndexing {
             tokenizer: TokenizerName::default(),
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            offsets: false,
         }
     }
 }
@@ -243,6 +295,24 @@ impl TextFieldIndexing {
         self.fieldnorms
     }
 
+    /// Sets whether the byte offsets of the tokens should be stored in the postings.
+    ///
+    /// Offsets are recorded along with the positions, and are ignored unless the index option
+    /// is [`IndexRecordOption::WithFreqsAndPositions`]. They let the
+    /// [`SnippetGenerator`](crate::snippet::SnippetGenerator) highlight a document without
+    /// running the tokenizer on its text again. Only text fields record offsets: the flag is
+    /// ignored for JSON fields.
+    #[must_use]
+    pub fn set_offsets(mut self, offsets: bool) -> TextFieldIndexing {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Returns true if and only if the byte offsets of the tokens are stored.
+    pub fn offsets(&self) -> bool {
+        self.offsets && self.record.has_positions()
+    }
+
     /// Sets which information should be indexed with the tokens.
     ///
     /// See [`IndexRecordOption`] for more detail.
@@ -265,11 +335,14 @@ pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
         fieldnorms: true,
+        offsets: false,
         record: IndexRecordOption::Basic,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
     coerce: false,
+    enum_values: None,
+    dictionary_encoded: false,
 };
 
 /// The field will be tokenized and indexed.
@@ -277,11 +350,14 @@ pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
         fieldnorms: true,
+        offsets: false,
         record: IndexRecordOption::WithFreqsAndPositions,
     }),
     stored: false,
     coerce: false,
     fast: FastFieldTextOptions::IsEnabled(false),
+    enum_values: None,
+    dictionary_encoded: false,
 };
 
 impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
@@ -294,6 +370,8 @@ impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            enum_values: self.enum_values.or(other.enum_values),
+            dictionary_encoded: self.dictionary_encoded | other.dictionary_encoded,
         }
     }
 }
@@ -311,6 +389,8 @@ impl From<StoredFlag> for TextOptions {
             stored: true,
             fast: FastFieldTextOptions::default(),
             coerce: false,
+            enum_values: None,
+            dictionary_encoded: false,
         }
     }
 }
@@ -322,6 +402,8 @@ impl From<CoerceFlag> for TextOptions {
             stored: false,
             fast: FastFieldTextOptions::default(),
             coerce: true,
+            enum_values: None,
+            dictionary_encoded: false,
         }
     }
 }
@@ -333,6 +415,8 @@ impl From<FastFlag> for TextOptions {
             stored: false,
             fast: FastFieldTextOptions::IsEnabled(true),
             coerce: false,
+            enum_values: None,
+            dictionary_encoded: false,
         }
     }
 }
@@ -433,4 +517,16 @@ mod tests {
             serde_json::from_str(&serde_json::to_string(&options).unwrap()).unwrap();
         assert_eq!(options.fast, FastFieldTextOptions::IsEnabled(false));
     }
+
+    #[test]
+    fn serde_dictionary_encoded() {
+        let options = (STRING | STORED).set_dictionary_encoded();
+        assert!(options.is_dictionary_encoded());
+        let json = serde_json::to_string(&options).unwrap();
+        assert!(json.contains(r#""dictionary_encoded":true"#));
+        let options: TextOptions = serde_json::from_str(&json).unwrap();
+        assert!(options.is_dictionary_encoded());
+        let json = serde_json::to_string(&(STRING | STORED)).unwrap();
+        assert!(!json.contains("dictionary_encoded"));
+    }
 }
@@ -52,11 +52,17 @@
 //! You can also specify the maximum number of characters for the snippets generated with the
 //! `set_max_num_chars` method. By default, this limit is set to 150.
 //!
+//! Fragments are scored by summing the weights of the query terms they contain. Terms are
+//! weighted by their inverse document frequency and by the boosts of the query. Distinct
+//! query terms appearing close to each other earn a proximity bonus, so that a fragment
+//! where the terms are adjacent wins over one where they are scattered. The window in which
+//! this bonus applies can be configured with `set_proximity_window`.
+//!
 //! SnippetGenerator needs to be created from the `Searcher` and the query, and the field on which
 //! the `SnippetGenerator` should generate the snippets.
 
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeMap;
 use std::ops::Range;
 
 use htmlescape::encode_minimal;
@@ -65,19 +71,31 @@ use crate::query::Query;
 use crate::schema::document::{Document, Value};
 use crate::schema::Field;
 use crate::tokenizer::{TextAnalyzer, Token};
-use crate::{Score, Searcher, Term};
+use crate::{DocAddress, Score, Searcher, TantivyDocument, Term};
 
 const DEFAULT_MAX_NUM_CHARS: usize = 150;
 
+const DEFAULT_PROXIMITY_WINDOW: usize = 3;
+
 const DEFAULT_SNIPPET_PREFIX: &str = "<b>";
 const DEFAULT_SNIPPET_POSTFIX: &str = "</b>";
 
+/// A query term found in a fragment.
+#[derive(Debug)]
+struct TermMatch {
+    position: usize,
+    offset_to: usize,
+    text: String,
+    score: Score,
+}
+
 #[derive(Debug)]
 pub(crate) struct FragmentCandidate {
     score: Score,
     start_offset: usize,
     stop_offset: usize,
     highlighted: Vec<Range<usize>>,
+    last_match: Option<TermMatch>,
 }
 
 impl FragmentCandidate {
@@ -92,6 +110,7 @@ impl FragmentCandidate {
             start_offset,
             stop_offset: start_offset,
             highlighted: vec![],
+            last_match: None,
         }
     }
 
@@ -100,12 +119,40 @@ impl FragmentCandidate {
     /// taking the token and terms, the token is added to the fragment.
     /// if the token is one of the terms, the score
     /// and highlighted fields are updated in the fragment.
-    fn try_add_token(&mut self, token: &Token, terms: &BTreeMap<String, Score>) {
+    ///
+    /// If the previous term found in the fragment is a different term, not overlapping the
+    /// token and at most `proximity_window` positions before it, a proximity bonus is added to
+    /// the score. The bonus is the lowest weight of the two terms for adjacent terms and decreases
+    /// linearly with the distance.
+    fn try_add_token(
+        &mut self,
+        token: &Token,
+        terms: &BTreeMap<String, Score>,
+        proximity_window: usize,
+    ) {
         self.stop_offset = token.offset_to;
 
-        if let Some(&score) = terms.get(&token.text.to_lowercase()) {
+        let text = token.text.to_lowercase();
+        if let Some(&score) = terms.get(&text) {
             self.score += score;
             self.highlighted.push(token.offset_from..token.offset_to);
+            if let Some(last_match) = &self.last_match {
+                let distance = token.position.saturating_sub(last_match.position).max(1);
+                if last_match.text != text
+                    && last_match.offset_to <= token.offset_from
+                    && distance <= proximity_window
+                {
+                    let proximity =
+                        (proximity_window + 1 - distance) as Score / proximity_window as Score;
+                    self.score += proximity * score.min(last_match.score);
+                }
+            }
+            self.last_match = Some(TermMatch {
+                position: token.position,
+                offset_to: token.offset_to,
+                text,
+                score,
+            });
         }
     }
 }
@@ -116,16 +163,18 @@ impl FragmentCandidate {
 pub struct Snippet {
     fragment: String,
     highlighted: Vec<Range<usize>>,
+    score: Score,
     snippet_prefix: String,
     snippet_postfix: String,
 }
 
 impl Snippet {
     /// Create a new `Snippet`.
-    fn new(fragment: &str, highlighted: Vec<Range<usize>>) -> Self {
+    fn new(fragment: &str, highlighted: Vec<Range<usize>>, score: Score) -> Self {
         Self {
             fragment: fragment.to_string(),
             highlighted,
+            score,
             snippet_prefix: DEFAULT_SNIPPET_PREFIX.to_string(),
             snippet_postfix: DEFAULT_SNIPPET_POSTFIX.to_string(),
         }
@@ -136,6 +185,7 @@ impl Snippet {
         Snippet {
             fragment: String::new(),
             highlighted: Vec::new(),
+            score: 0.0,
             snippet_prefix: String::new(),
             snippet_postfix: String::new(),
         }
@@ -174,6 +224,14 @@ impl Snippet {
         &self.highlighted
     }
 
+    /// Returns the score of the fragment used in the snippet.
+    ///
+    /// The score is 0 for an empty snippet. It can be used to decide whether a snippet is
+    /// relevant enough to be displayed.
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
     /// Sets highlighted prefix and postfix.
     pub fn set_snippet_prefix_postfix(&mut self, prefix: &str, postfix: &str) {
         self.snippet_prefix = prefix.to_string();
@@ -207,19 +265,37 @@ fn search_fragments(
     text: &str,
     terms: &BTreeMap<String, Score>,
     max_num_chars: usize,
+    proximity_window: usize,
 ) -> Vec<FragmentCandidate> {
     let mut token_stream = tokenizer.token_stream(text);
+    search_fragments_in_tokens(
+        |add_token| token_stream.process(add_token),
+        terms,
+        max_num_chars,
+        proximity_window,
+    )
+}
+
+/// Returns the fragments of the tokens passed by `for_each_token`, in increasing order of
+/// position, to the closure it receives.
+fn search_fragments_in_tokens(
+    for_each_token: impl FnOnce(&mut dyn FnMut(&Token)),
+    terms: &BTreeMap<String, Score>,
+    max_num_chars: usize,
+    proximity_window: usize,
+) -> Vec<FragmentCandidate> {
     let mut fragment = FragmentCandidate::new(0);
     let mut fragments: Vec<FragmentCandidate> = vec![];
-    while let Some(next) = token_stream.next() {
+    for_each_token(&mut |next: &Token| {
         if (next.offset_to - fragment.start_offset) > max_num_chars {
-            if fragment.score > 0.0 {
-                fragments.push(fragment)
+            let previous_fragment =
+                std::mem::replace(&mut fragment, FragmentCandidate::new(next.offset_from));
+            if previous_fragment.score > 0.0 {
+                fragments.push(previous_fragment)
             };
-            fragment = FragmentCandidate::new(next.offset_from);
         }
-        fragment.try_add_token(next, terms);
-    }
+        fragment.try_add_token(next, terms, proximity_window);
+    });
     if fragment.score > 0.0 {
         fragments.push(fragment)
     }
@@ -227,30 +303,39 @@ fn search_fragments(
     fragments
 }
 
+/// Orders fragments by score, and then by position with earlier fragments first.
+fn compare_fragments(left: &FragmentCandidate, right: &FragmentCandidate) -> Ordering {
+    let cmp_score = left
+        .score
+        .partial_cmp(&right.score)
+        .unwrap_or(Ordering::Equal);
+    if cmp_score == Ordering::Equal {
+        (right.start_offset, right.stop_offset).cmp(&(left.start_offset, left.stop_offset))
+    } else {
+        cmp_score
+    }
+}
+
+fn snippet_from_fragment(fragment: &FragmentCandidate, text: &str) -> Snippet {
+    let fragment_text = &text[fragment.start_offset..fragment.stop_offset];
+    let highlighted = fragment
+        .highlighted
+        .iter()
+        .map(|item| item.start - fragment.start_offset..item.end - fragment.start_offset)
+        .collect();
+    Snippet::new(fragment_text, highlighted, fragment.score)
+}
+
 /// Returns a Snippet
 ///
 /// Takes a vector of `FragmentCandidate`s and the text.
 /// Figures out the best fragment from it and creates a snippet.
 fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str) -> Snippet {
-    let best_fragment_opt = fragments.iter().max_by(|left, right| {
-        let cmp_score = left
-            .score
-            .partial_cmp(&right.score)
-            .unwrap_or(Ordering::Equal);
-        if cmp_score == Ordering::Equal {
-            (right.start_offset, right.stop_offset).cmp(&(left.start_offset, left.stop_offset))
-        } else {
-            cmp_score
-        }
-    });
-    if let Some(fragment) = best_fragment_opt {
-        let fragment_text = &text[fragment.start_offset..fragment.stop_offset];
-        let highlighted = fragment
-            .highlighted
-            .iter()
-            .map(|item| item.start - fragment.start_offset..item.end - fragment.start_offset)
-            .collect();
-        Snippet::new(fragment_text, highlighted)
+    if let Some(fragment) = fragments
+        .iter()
+        .max_by(|left, right| compare_fragments(left, right))
+    {
+        snippet_from_fragment(fragment, text)
     } else {
         // When there are no fragments to chose from,
         // for now create an empty snippet.
@@ -258,6 +343,17 @@ fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str)
     }
 }
 
+/// Returns the `n` best fragments as snippets, ordered by decreasing score.
+fn select_top_fragments(fragments: &[FragmentCandidate], text: &str, n: usize) -> Vec<Snippet> {
+    let mut sorted_fragments: Vec<&FragmentCandidate> = fragments.iter().collect();
+    sorted_fragments.sort_by(|left, right| compare_fragments(right, left));
+    sorted_fragments
+        .into_iter()
+        .take(n)
+        .map(|fragment| snippet_from_fragment(fragment, text))
+        .collect()
+}
+
 /// Sorts and removes duplicate ranges from the input.
 ///
 /// This function first sorts the ranges by their start position,
@@ -379,6 +475,7 @@ pub struct SnippetGenerator {
     tokenizer: TextAnalyzer,
     field: Field,
     max_num_chars: usize,
+    proximity_window: usize,
 }
 
 impl SnippetGenerator {
@@ -394,6 +491,7 @@ impl SnippetGenerator {
             tokenizer,
             field,
             max_num_chars,
+            proximity_window: DEFAULT_PROXIMITY_WINDOW,
         }
     }
     /// Creates a new snippet generator
@@ -402,14 +500,15 @@ impl SnippetGenerator {
         query: &dyn Query,
         field: Field,
     ) -> crate::Result<SnippetGenerator> {
-        let mut terms: BTreeSet<&Term> = BTreeSet::new();
-        query.query_terms(&mut |term, _| {
+        let mut terms: BTreeMap<&Term, Score> = BTreeMap::new();
+        query.query_terms_with_boost(&mut |term, boost| {
             if term.field() == field {
-                terms.insert(term);
+                let term_boost = terms.entry(term).or_insert(boost);
+                *term_boost = term_boost.max(boost);
             }
         });
         let mut terms_text: BTreeMap<String, Score> = Default::default();
-        for term in terms {
+        for (term, boost) in terms {
             let term_value = term.value();
             let term_str = if let Some(term_str) = term_value.as_str() {
                 term_str
@@ -418,7 +517,7 @@ impl SnippetGenerator {
             };
             let doc_freq = searcher.doc_freq(term)?;
             if doc_freq > 0 {
-                let score = 1.0 / (1.0 + doc_freq as Score);
+                let score = boost / (1.0 + doc_freq as Score);
                 terms_text.insert(term_str.to_string(), score);
             }
         }
@@ -428,6 +527,7 @@ impl SnippetGenerator {
             tokenizer,
             field,
             max_num_chars: DEFAULT_MAX_NUM_CHARS,
+            proximity_window: DEFAULT_PROXIMITY_WINDOW,
         })
     }
 
@@ -436,6 +536,14 @@ impl SnippetGenerator {
         self.max_num_chars = max_num_chars;
     }
 
+    /// Sets the maximum distance, in token positions, between two distinct query terms
+    /// for the fragment to get a proximity bonus. Default is 3.
+    ///
+    /// Setting it to 0 disables the proximity bonus.
+    pub fn set_proximity_window(&mut self, proximity_window: usize) {
+        self.proximity_window = proximity_window;
+    }
+
     #[cfg(test)]
     pub(crate) fn terms_text(&self) -> &BTreeMap<String, Score> {
         &self.terms_text
@@ -462,15 +570,104 @@ impl SnippetGenerator {
         self.snippet(text.trim())
     }
 
+    /// Generates a snippet for the document at `doc_address`, locating the query terms
+    /// thanks to the offsets stored in the index.
+    ///
+    /// If the field [records offsets](crate::schema::TextFieldIndexing::set_offsets), the text of
+    /// the document is not analyzed again: the fragments are built from the occurrences of the
+    /// query terms alone, and each of them ends with its last highlighted term. The highlighted
+    /// ranges are the same as the ones [`SnippetGenerator::snippet_from_doc`] would find.
+    ///
+    /// Otherwise, the stored document is analyzed as in [`SnippetGenerator::snippet_from_doc`].
+    pub fn snippet_from_doc_address(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+    ) -> crate::Result<Snippet> {
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let inverted_index = searcher
+            .segment_reader(doc_address.segment_ord)
+            .inverted_index(self.field)?;
+        let mut tokens: Vec<Token> = Vec::new();
+        for term_text in self.terms_text.keys() {
+            let term = Term::from_field_text(self.field, term_text);
+            let Some(positions_and_offsets) =
+                inverted_index.positions_and_offsets(&term, doc_address.doc_id)?
+            else {
+                return Ok(self.snippet_from_doc(&doc));
+            };
+            tokens.extend(
+                positions_and_offsets
+                    .into_iter()
+                    .map(|(position, offsets)| {
+                        Token::new(
+                            offsets.start,
+                            offsets.end,
+                            position as usize,
+                            term_text.clone(),
+                        )
+                    }),
+            );
+        }
+        tokens.sort_by_key(|token| (token.position, token.offset_from));
+
+        // The offsets refer to the values of the field joined by a single space.
+        let mut text = String::new();
+        let mut num_values = 0;
+        for (field, value) in doc.iter_fields_and_values() {
+            if field != self.field {
+                continue;
+            }
+            if let Some(val) = value.as_str() {
+                if num_values > 0 {
+                    text.push(' ');
+                }
+                text.push_str(val);
+                num_values += 1;
+            }
+        }
+        if !tokens.iter().all(|token| {
+            text.is_char_boundary(token.offset_from) && text.is_char_boundary(token.offset_to)
+        }) {
+            // The offsets do not match the stored text, for instance because some of the values
+            // were pre-tokenized.
+            return Ok(self.snippet_from_doc(&doc));
+        }
+        let fragment_candidates = search_fragments_in_tokens(
+            |add_token| tokens.iter().for_each(add_token),
+            &self.terms_text,
+            self.max_num_chars,
+            self.proximity_window,
+        );
+        Ok(select_best_fragment_combination(
+            &fragment_candidates[..],
+            &text,
+        ))
+    }
+
     /// Generates a snippet for the given text.
     pub fn snippet(&self, text: &str) -> Snippet {
-        let fragment_candidates = search_fragments(
+        let fragment_candidates = self.search_fragments(text);
+        select_best_fragment_combination(&fragment_candidates[..], text)
+    }
+
+    /// Generates up to `n` alternative snippets for the given text, ordered by
+    /// decreasing score.
+    ///
+    /// The first snippet is the one returned by [`SnippetGenerator::snippet`].
+    pub fn top_fragments(&self, text: &str, n: usize) -> Vec<Snippet> {
+        let fragment_candidates = self.search_fragments(text);
+        select_top_fragments(&fragment_candidates[..], text, n)
+    }
+
+    fn search_fragments(&self, text: &str) -> Vec<FragmentCandidate> {
+        search_fragments(
             &mut self.tokenizer.clone(),
             text,
             &self.terms_text,
             self.max_num_chars,
-        );
-        select_best_fragment_combination(&fragment_candidates[..], text)
+            self.proximity_window,
+        )
     }
 }
 
@@ -481,10 +678,13 @@ mod tests {
 
     use maplit::btreemap;
 
-    use super::{collapse_overlapped_ranges, search_fragments, select_best_fragment_combination};
+    use super::{
+        collapse_overlapped_ranges, search_fragments, select_best_fragment_combination,
+        select_top_fragments, DEFAULT_PROXIMITY_WINDOW,
+    };
     use crate::query::QueryParser;
     use crate::schema::{Schema, TEXT};
-    use crate::snippet::SnippetGenerator;
+    use crate::snippet::{Snippet, SnippetGenerator};
     use crate::tokenizer::{NgramTokenizer, SimpleTokenizer};
     use crate::Index;
 
@@ -513,6 +713,7 @@ Survey in 2016, 2017, and 2018."#;
             TEST_TEXT,
             &terms,
             100,
+            DEFAULT_PROXIMITY_WINDOW,
         );
         assert_eq!(fragments.len(), 7);
         {
@@ -545,6 +746,7 @@ Survey in 2016, 2017, and 2018."#;
                 TEST_TEXT,
                 &terms,
                 20,
+                DEFAULT_PROXIMITY_WINDOW,
             );
             {
                 let first = &fragments[0];
@@ -564,6 +766,7 @@ Survey in 2016, 2017, and 2018."#;
                 TEST_TEXT,
                 &terms,
                 20,
+                DEFAULT_PROXIMITY_WINDOW,
             );
             // assert_eq!(fragments.len(), 7);
             {
@@ -583,8 +786,13 @@ Survey in 2016, 2017, and 2018."#;
         let mut terms = BTreeMap::new();
         terms.insert(String::from("c"), 1.0);
 
-        let fragments =
-            search_fragments(&mut From::from(SimpleTokenizer::default()), text, &terms, 3);
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            3,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
 
         assert_eq!(fragments.len(), 1);
         {
@@ -606,8 +814,13 @@ Survey in 2016, 2017, and 2018."#;
         let mut terms = BTreeMap::new();
         terms.insert(String::from("f"), 1.0);
 
-        let fragments =
-            search_fragments(&mut From::from(SimpleTokenizer::default()), text, &terms, 3);
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            3,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
 
         assert_eq!(fragments.len(), 2);
         {
@@ -630,8 +843,13 @@ Survey in 2016, 2017, and 2018."#;
         terms.insert(String::from("f"), 1.0);
         terms.insert(String::from("a"), 0.9);
 
-        let fragments =
-            search_fragments(&mut From::from(SimpleTokenizer::default()), text, &terms, 7);
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            7,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
 
         assert_eq!(fragments.len(), 2);
         {
@@ -653,8 +871,13 @@ Survey in 2016, 2017, and 2018."#;
         let mut terms = BTreeMap::new();
         terms.insert(String::from("z"), 1.0);
 
-        let fragments =
-            search_fragments(&mut From::from(SimpleTokenizer::default()), text, &terms, 3);
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            3,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
 
         assert_eq!(fragments.len(), 0);
 
@@ -669,8 +892,13 @@ Survey in 2016, 2017, and 2018."#;
         let text = "a b c d";
 
         let terms = BTreeMap::new();
-        let fragments =
-            search_fragments(&mut From::from(SimpleTokenizer::default()), text, &terms, 3);
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            3,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
         assert_eq!(fragments.len(), 0);
 
         let snippet = select_best_fragment_combination(&fragments[..], text);
@@ -727,6 +955,98 @@ Survey in 2016, 2017, and 2018."#;
         Ok(())
     }
 
+    #[test]
+    fn test_snippet_generator_term_boost() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => "a"))?;
+            index_writer.add_document(doc!(text_field => "a"))?;
+            index_writer.add_document(doc!(text_field => "a b"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("a^4 b (a^2 b^0.5)")?;
+        let snippet_generator = SnippetGenerator::create(&searcher, &*query, text_field)?;
+        // The highest boost applied to a term is retained.
+        assert_eq!(
+            &btreemap!("a".to_string() => 1.0, "b".to_string() => 0.5),
+            snippet_generator.terms_text()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet_proximity_flips_best_fragment() {
+        let text = "alpha one two beta zz alpha beta zz";
+        let terms = btreemap! {
+            String::from("alpha") => 1.0,
+            String::from("beta") => 1.0
+        };
+        let mut tokenizer = From::from(SimpleTokenizer::default());
+        let fragments = search_fragments(&mut tokenizer, text, &terms, 20, 0);
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0].score, 2.0);
+        assert_eq!(fragments[1].score, 2.0);
+        let snippet = select_best_fragment_combination(&fragments[..], text);
+        assert_eq!(snippet.to_html(), "<b>alpha</b> one two <b>beta</b>");
+
+        let fragments = search_fragments(&mut tokenizer, text, &terms, 20, 2);
+        assert_eq!(fragments[0].score, 2.0);
+        assert_eq!(fragments[1].score, 3.0);
+        let snippet = select_best_fragment_combination(&fragments[..], text);
+        assert_eq!(snippet.to_html(), "zz <b>alpha</b> <b>beta</b> zz");
+        assert_eq!(snippet.score(), 3.0);
+
+        // With a wider window, the scattered terms also get a smaller bonus.
+        let fragments = search_fragments(&mut tokenizer, text, &terms, 20, 4);
+        assert_eq!(fragments[0].score, 2.5);
+        assert_eq!(fragments[1].score, 3.0);
+    }
+
+    #[test]
+    fn test_snippet_proximity_ignores_repeated_term() {
+        let text = "alpha alpha";
+        let terms = btreemap! { String::from("alpha") => 1.0 };
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            100,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
+        assert_eq!(fragments.len(), 1);
+        assert_eq!(fragments[0].score, 2.0);
+    }
+
+    #[test]
+    fn test_snippet_top_fragments() {
+        let text = "alpha one two beta zz alpha beta zz gamma";
+        let terms = btreemap! {
+            String::from("alpha") => 1.0,
+            String::from("beta") => 1.0
+        };
+        let fragments = search_fragments(
+            &mut From::from(SimpleTokenizer::default()),
+            text,
+            &terms,
+            20,
+            DEFAULT_PROXIMITY_WINDOW,
+        );
+        let snippets = select_top_fragments(&fragments[..], text, 5);
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].to_html(), "zz <b>alpha</b> <b>beta</b> zz");
+        assert!(snippets[0].score() > snippets[1].score());
+        assert_eq!(snippets[1].to_html(), "<b>alpha</b> one two <b>beta</b>");
+        assert_eq!(select_top_fragments(&fragments[..], text, 1).len(), 1);
+        assert!(select_top_fragments(&[], text, 3).is_empty());
+        assert_eq!(Snippet::empty().score(), 0.0);
+    }
+
     #[cfg(feature = "stemmer")]
     #[test]
     fn test_snippet_generator() -> crate::Result<()> {
@@ -773,6 +1093,115 @@ Survey in 2016, 2017, and 2018."#;
         Ok(())
     }
 
+    #[test]
+    fn test_snippet_from_doc_address_with_offsets() -> crate::Result<()> {
+        use crate::schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
+        use crate::{DocAddress, IndexWriter, TantivyDocument, Term};
+        let indexing =
+            TextFieldIndexing::default().set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let mut schema_builder = Schema::builder();
+        let with_offsets = schema_builder.add_text_field(
+            "with_offsets",
+            TextOptions::default()
+                .set_indexing_options(indexing.clone().set_offsets(true))
+                .set_stored(),
+        );
+        let without_offsets = schema_builder.add_text_field(
+            "without_offsets",
+            TextOptions::default()
+                .set_indexing_options(indexing)
+                .set_stored(),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let docs: [&[&str]; 3] = [
+            &["Un café à Zürich, puis un autre café"],
+            &["東京 café", "Über das Café in Zürich: café crème"],
+            &["Ωμέγα zürich"],
+        ];
+        for (doc_id, values) in docs.into_iter().enumerate() {
+            let mut doc = TantivyDocument::default();
+            for &value in values {
+                doc.add_text(with_offsets, value);
+                doc.add_text(without_offsets, value);
+            }
+            index_writer.add_document(doc)?;
+            if doc_id > 0 {
+                index_writer.commit()?;
+            }
+        }
+
+        let check_snippets = |index: &Index| -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            for field in [with_offsets, without_offsets] {
+                let query =
+                    QueryParser::for_index(index, vec![field]).parse_query("café zürich")?;
+                let snippet_generator = SnippetGenerator::create(&searcher, &*query, field)?;
+                for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+                    for doc_id in 0..segment_reader.max_doc() {
+                        let doc_address = DocAddress::new(segment_ord as u32, doc_id);
+                        let doc: TantivyDocument = searcher.doc(doc_address)?;
+                        let reanalyzed = snippet_generator.snippet_from_doc(&doc);
+                        let snippet =
+                            snippet_generator.snippet_from_doc_address(&searcher, doc_address)?;
+                        assert!(!snippet.highlighted().is_empty());
+                        assert_eq!(snippet.highlighted(), reanalyzed.highlighted());
+                        if field == with_offsets {
+                            // The fragment ends with the last highlighted term.
+                            assert!(reanalyzed.fragment().starts_with(snippet.fragment()));
+                        } else {
+                            assert_eq!(snippet.fragment(), reanalyzed.fragment());
+                        }
+                    }
+                }
+            }
+            Ok(())
+        };
+        check_snippets(&index)?;
+
+        let searcher = index.reader()?.searcher();
+        let cafe = |field| Term::from_field_text(field, "café");
+        // The first segment holds the first two documents.
+        let segment_reader = searcher
+            .segment_readers()
+            .iter()
+            .find(|segment_reader| segment_reader.max_doc() == 2)
+            .unwrap();
+        let inverted_index = segment_reader.inverted_index(with_offsets)?;
+        assert!(inverted_index.has_offsets());
+        assert_eq!(
+            inverted_index.positions_and_offsets(&cafe(with_offsets), 0)?,
+            Some(vec![(1, 3..8), (7, 35..40)])
+        );
+        // The second value starts after "東京 café" and a space.
+        assert_eq!(
+            inverted_index.positions_and_offsets(&cafe(with_offsets), 1)?,
+            Some(vec![(1, 7..12), (5, 23..28), (8, 41..46)])
+        );
+        let inverted_index = segment_reader.inverted_index(without_offsets)?;
+        assert!(!inverted_index.has_offsets());
+        assert_eq!(
+            inverted_index.positions_and_offsets(&cafe(without_offsets), 0)?,
+            None
+        );
+        let segment_reader = searcher
+            .segment_readers()
+            .iter()
+            .find(|segment_reader| segment_reader.max_doc() == 1)
+            .unwrap();
+        let inverted_index = segment_reader.inverted_index(with_offsets)?;
+        assert_eq!(
+            inverted_index.positions_and_offsets(&cafe(with_offsets), 0)?,
+            Some(Vec::new())
+        );
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        check_snippets(&index)?;
+        Ok(())
+    }
+
     #[test]
     fn test_snippet_with_overlapped_highlighted_ranges() {
         let text = "abc";
@@ -786,6 +1215,7 @@ Survey in 2016, 2017, and 2018."#;
             text,
             &terms,
             3,
+            DEFAULT_PROXIMITY_WINDOW,
         );
 
         assert_eq!(fragments.len(), 1);
@@ -809,6 +1239,7 @@ Survey in 2016, 2017, and 2018."#;
             TEST_TEXT,
             &terms,
             100,
+            DEFAULT_PROXIMITY_WINDOW,
         );
         let mut snippet = select_best_fragment_combination(&fragments[..], TEST_TEXT);
         assert_eq!(
@@ -6,6 +6,10 @@
 //! One important caveat for all of this functionality is that none of it currently takes
 //! storage-level details into consideration. For example, if your file system block size is 4096
 //! bytes, we can under-count actual resultant space usage by up to 4095 bytes per file.
+//!
+//! Besides the size of the files of each segment, which are mapped in memory by the
+//! `MmapDirectory`, the report includes the memory held by the data loaded at runtime by the
+//! searcher, see [`SegmentMemoryUsage`].
 
 use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
@@ -31,6 +35,8 @@ pub enum ComponentSpaceUsage {
 pub struct SearcherSpaceUsage {
     segments: Vec<SegmentSpaceUsage>,
     total: ByteCount,
+    #[serde(default)]
+    memory_total: ByteCount,
 }
 
 impl SearcherSpaceUsage {
@@ -38,6 +44,7 @@ impl SearcherSpaceUsage {
         SearcherSpaceUsage {
             segments: Vec::new(),
             total: Default::default(),
+            memory_total: Default::default(),
         }
     }
 
@@ -45,6 +52,7 @@ impl SearcherSpaceUsage {
     /// Performs no deduplication or other intelligence.
     pub(crate) fn add_segment(&mut self, segment: SegmentSpaceUsage) {
         self.total += segment.total();
+        self.memory_total += segment.memory().total();
         self.segments.push(segment);
     }
 
@@ -58,6 +66,11 @@ impl SearcherSpaceUsage {
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Returns the memory held by the data loaded at runtime by the segments of this searcher.
+    pub fn memory_total(&self) -> ByteCount {
+        self.memory_total
+    }
 }
 
 /// Represents combined space usage for all of the large components comprising a segment.
@@ -70,12 +83,19 @@ pub struct SegmentSpaceUsage {
     positions: PerFieldSpaceUsage,
     fast_fields: PerFieldSpaceUsage,
     fieldnorms: PerFieldSpaceUsage,
+    #[serde(default)]
+    f32_vectors: PerFieldSpaceUsage,
 
     store: StoreSpaceUsage,
 
     deletes: ByteCount,
+    #[serde(default)]
+    soft_deletes: ByteCount,
 
     total: ByteCount,
+
+    #[serde(default)]
+    memory: SegmentMemoryUsage,
 }
 
 impl SegmentSpaceUsage {
@@ -87,16 +107,20 @@ impl SegmentSpaceUsage {
         positions: PerFieldSpaceUsage,
         fast_fields: PerFieldSpaceUsage,
         fieldnorms: PerFieldSpaceUsage,
+        f32_vectors: PerFieldSpaceUsage,
         store: StoreSpaceUsage,
         deletes: ByteCount,
+        soft_deletes: ByteCount,
     ) -> SegmentSpaceUsage {
         let total = termdict.total()
             + postings.total()
             + positions.total()
             + fast_fields.total()
             + fieldnorms.total()
+            + f32_vectors.total()
             + store.total()
-            + deletes;
+            + deletes
+            + soft_deletes;
         SegmentSpaceUsage {
             num_docs,
             termdict,
@@ -104,12 +128,19 @@ impl SegmentSpaceUsage {
             positions,
             fast_fields,
             fieldnorms,
+            f32_vectors,
             store,
             deletes,
+            soft_deletes,
             total,
+            memory: SegmentMemoryUsage::default(),
         }
     }
 
+    pub(crate) fn set_memory(&mut self, memory: SegmentMemoryUsage) {
+        self.memory = memory;
+    }
+
     /// Space usage for the given component
     ///
     /// Clones the underlying data.
@@ -122,9 +153,12 @@ impl SegmentSpaceUsage {
             Positions => PerField(self.positions().clone()),
             FastFields => PerField(self.fast_fields().clone()),
             FieldNorms => PerField(self.fieldnorms().clone()),
+            F32Vectors => PerField(self.f32_vectors().clone()),
             Terms => PerField(self.termdict().clone()),
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
+            StoreDictionaries => Basic(self.store().dictionaries_usage()),
             Delete => Basic(self.deletes()),
+            SoftDelete => Basic(self.soft_deletes()),
         }
     }
 
@@ -158,6 +192,11 @@ impl SegmentSpaceUsage {
         &self.fieldnorms
     }
 
+    /// Space usage for f32 vectors
+    pub fn f32_vectors(&self) -> &PerFieldSpaceUsage {
+        &self.f32_vectors
+    }
+
     /// Space usage for stored documents
     pub fn store(&self) -> &StoreSpaceUsage {
         &self.store
@@ -168,10 +207,71 @@ impl SegmentSpaceUsage {
         self.deletes
     }
 
+    /// Space usage for soft deleted documents
+    pub fn soft_deletes(&self) -> ByteCount {
+        self.soft_deletes
+    }
+
     /// Total space usage in bytes for this segment.
     pub fn total(&self) -> ByteCount {
         self.total
     }
+
+    /// Memory held by the data loaded at runtime for this segment.
+    ///
+    /// It is not part of [`total`](Self::total).
+    pub fn memory(&self) -> &SegmentMemoryUsage {
+        &self.memory
+    }
+}
+
+/// Represents the memory held by the data a segment loads at runtime, as opposed to the files
+/// of the segment.
+///
+/// The fieldnorm readers and the alive bitset point to the files of the segment, which are
+/// mapped in memory or held in anonymous memory depending on the directory. The decompressed
+/// blocks of the doc store are always held in anonymous memory.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SegmentMemoryUsage {
+    store_block_cache: ByteCount,
+    fieldnorm_readers: ByteCount,
+    alive_bitset: ByteCount,
+    total: ByteCount,
+}
+
+impl SegmentMemoryUsage {
+    pub(crate) fn new(
+        store_block_cache: ByteCount,
+        fieldnorm_readers: ByteCount,
+        alive_bitset: ByteCount,
+    ) -> SegmentMemoryUsage {
+        SegmentMemoryUsage {
+            store_block_cache,
+            fieldnorm_readers,
+            alive_bitset,
+            total: store_block_cache + fieldnorm_readers + alive_bitset,
+        }
+    }
+
+    /// Decompressed doc store blocks held in the cache of the searcher.
+    pub fn store_block_cache(&self) -> ByteCount {
+        self.store_block_cache
+    }
+
+    /// Fieldnorm readers opened so far.
+    pub fn fieldnorm_readers(&self) -> ByteCount {
+        self.fieldnorm_readers
+    }
+
+    /// Alive bitset, for segments with deletes.
+    pub fn alive_bitset(&self) -> ByteCount {
+        self.alive_bitset
+    }
+
+    /// Total memory usage in bytes for this segment.
+    pub fn total(&self) -> ByteCount {
+        self.total
+    }
 }
 
 /// Represents space usage for the Store for this segment.
@@ -183,11 +283,21 @@ impl SegmentSpaceUsage {
 pub struct StoreSpaceUsage {
     data: ByteCount,
     offsets: ByteCount,
+    #[serde(default)]
+    dictionaries: ByteCount,
 }
 
 impl StoreSpaceUsage {
     pub(crate) fn new(data: ByteCount, offsets: ByteCount) -> StoreSpaceUsage {
-        StoreSpaceUsage { data, offsets }
+        StoreSpaceUsage {
+            data,
+            offsets,
+            dictionaries: ByteCount::default(),
+        }
+    }
+
+    pub(crate) fn set_dictionaries(&mut self, dictionaries: ByteCount) {
+        self.dictionaries = dictionaries;
     }
 
     /// Space usage for the data part of the store
@@ -200,9 +310,14 @@ impl StoreSpaceUsage {
         self.offsets
     }
 
+    /// Space usage for the dictionaries of the dictionary encoded fields
+    pub fn dictionaries_usage(&self) -> ByteCount {
+        self.dictionaries
+    }
+
     /// Total space usage in bytes for this Store
     pub fn total(&self) -> ByteCount {
-        self.data + self.offsets
+        self.data + self.offsets + self.dictionaries
     }
 }
 
@@ -210,7 +325,7 @@ impl StoreSpaceUsage {
 ///
 /// A field can appear with a single index (typically 0) or with multiple indexes.
 /// Multiple indexes are used to handle variable length things, where
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PerFieldSpaceUsage {
     fields: BTreeMap<String, FieldUsage>,
     total: ByteCount,
@@ -355,10 +470,12 @@ fn merge_column_space_usage(
 
 #[cfg(test)]
 mod test {
+    use crate::collector::TopDocs;
     use crate::index::Index;
+    use crate::query::QueryParser;
     use crate::schema::{Schema, FAST, INDEXED, STORED, TEXT};
-    use crate::space_usage::PerFieldSpaceUsage;
-    use crate::{IndexWriter, Term};
+    use crate::space_usage::{PerFieldSpaceUsage, SegmentMemoryUsage};
+    use crate::{DocAddress, IndexWriter, TantivyDocument, Term};
 
     #[test]
     fn test_empty() {
@@ -555,4 +672,50 @@ mod test {
         assert!(segment_space_usage.deletes() > 0);
         Ok(())
     }
+
+    #[test]
+    fn test_memory_usage() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..1_000u64 {
+            index_writer.add_document(doc!(id => doc_id, text => "hello happy tax payer"))?;
+        }
+        index_writer.commit()?;
+        let reader = index.reader()?;
+
+        let memory_usage = |searcher: &crate::Searcher| -> crate::Result<SegmentMemoryUsage> {
+            let space_usage = searcher.space_usage()?;
+            assert_eq!(space_usage.segments().len(), 1);
+            let memory_usage = space_usage.segments()[0].memory().clone();
+            assert_eq!(space_usage.memory_total(), memory_usage.total());
+            Ok(memory_usage)
+        };
+        let searcher = reader.searcher();
+        let initial_memory_usage = memory_usage(&searcher)?;
+        assert_eq!(initial_memory_usage.total(), 0u64);
+
+        // Searching loads the fieldnorms, fetching a document fills the doc store cache.
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("happy")?;
+        searcher.search(&query, &TopDocs::with_limit(1).order_by_score())?;
+        let _doc: TantivyDocument = searcher.doc(DocAddress::new(0, 0))?;
+        let loaded_memory_usage = memory_usage(&searcher)?;
+        assert_eq!(loaded_memory_usage.fieldnorm_readers(), 1_000u64);
+        assert!(loaded_memory_usage.store_block_cache() > 0);
+        assert_eq!(loaded_memory_usage.alive_bitset(), 0u64);
+
+        index_writer.delete_term(Term::from_field_u64(id, 3));
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        let memory_usage_with_deletes = memory_usage(&searcher)?;
+        assert!(memory_usage_with_deletes.alive_bitset() > 0);
+        assert!(memory_usage_with_deletes.total() > initial_memory_usage.total());
+
+        let json = serde_json::to_value(searcher.space_usage()?).unwrap();
+        assert_eq!(json["memory_total"], json["segments"][0]["memory"]["total"]);
+        Ok(())
+    }
 }
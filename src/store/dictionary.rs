@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::io;
+
+use common::{BinarySerializable, OwnedBytes, TerminatingWrite, VInt};
+
+use crate::directory::WritePtr;
+use crate::schema::{Field, FieldEntry, FieldType, Schema};
+
+/// Maximum number of values in the dictionary of a dictionary encoded field of a segment.
+///
+/// Once the dictionary is full, new values of the field are stored literally.
+/// See [`TextOptions::set_dictionary_encoded`](crate::schema::TextOptions::set_dictionary_encoded).
+pub const MAX_STORE_DICTIONARY_SIZE: usize = 4_096;
+
+#[derive(Default)]
+struct FieldDictionaryWriter {
+    ords: HashMap<String, u32>,
+    values: Vec<String>,
+    num_bytes: usize,
+}
+
+impl FieldDictionaryWriter {
+    fn ord_or_insert(&mut self, value: &str) -> Option<u32> {
+        if let Some(&ord) = self.ords.get(value) {
+            return Some(ord);
+        }
+        if self.values.len() >= MAX_STORE_DICTIONARY_SIZE {
+            return None;
+        }
+        let ord = self.values.len() as u32;
+        self.ords.insert(value.to_string(), ord);
+        self.values.push(value.to_string());
+        self.num_bytes += 2 * value.len();
+        Some(ord)
+    }
+}
+
+/// Builds the dictionaries of the dictionary encoded fields of a segment, as their
+/// values are stored.
+pub(crate) struct StoreDictionariesWriter {
+    // Indexed by field id. `None` for the fields that are not dictionary encoded.
+    fields: Vec<Option<FieldDictionaryWriter>>,
+    wrt: WritePtr,
+}
+
+impl StoreDictionariesWriter {
+    /// Returns true if the schema has stored fields that are dictionary encoded.
+    pub(crate) fn has_dictionary_encoded_fields(schema: &Schema) -> bool {
+        schema
+            .fields()
+            .any(|(_, field_entry)| is_dictionary_encoded(field_entry))
+    }
+
+    pub(crate) fn for_schema(schema: &Schema, wrt: WritePtr) -> StoreDictionariesWriter {
+        let fields = schema
+            .fields()
+            .map(|(_, field_entry)| {
+                is_dictionary_encoded(field_entry).then(FieldDictionaryWriter::default)
+            })
+            .collect();
+        StoreDictionariesWriter { fields, wrt }
+    }
+
+    /// Returns the ordinal of the value in the dictionary of the field, adding it if needed.
+    ///
+    /// Returns `None` if the field is not dictionary encoded, or if its dictionary is full.
+    pub(crate) fn ord_or_insert(&mut self, field: Field, value: &str) -> Option<u32> {
+        self.fields
+            .get_mut(field.field_id() as usize)?
+            .as_mut()?
+            .ord_or_insert(value)
+    }
+
+    pub(crate) fn mem_usage(&self) -> usize {
+        self.fields
+            .iter()
+            .flatten()
+            .map(|field_dictionary| field_dictionary.num_bytes)
+            .sum()
+    }
+
+    /// Serializes the dictionaries.
+    ///
+    /// The format is the number of dictionaries, followed by the field and the values of
+    /// each dictionary.
+    pub(crate) fn close(mut self) -> io::Result<()> {
+        let dictionaries: Vec<(Field, &[String])> = self
+            .fields
+            .iter()
+            .enumerate()
+            .filter_map(|(field_id, field_dictionary)| {
+                let field_dictionary = field_dictionary.as_ref()?;
+                Some((
+                    Field::from_field_id(field_id as u32),
+                    &field_dictionary.values[..],
+                ))
+            })
+            .collect();
+        VInt(dictionaries.len() as u64).serialize(&mut self.wrt)?;
+        for (field, values) in dictionaries {
+            field.serialize(&mut self.wrt)?;
+            VInt(values.len() as u64).serialize(&mut self.wrt)?;
+            for value in values {
+                value.serialize(&mut self.wrt)?;
+            }
+        }
+        self.wrt.terminate()
+    }
+}
+
+fn is_dictionary_encoded(field_entry: &FieldEntry) -> bool {
+    field_entry.is_stored()
+        && matches!(field_entry.field_type(),
+            FieldType::Str(text_options) if text_options.is_dictionary_encoded())
+}
+
+/// The dictionaries of the dictionary encoded fields of a segment, used to resolve their
+/// stored values.
+#[derive(Debug, Default)]
+pub(crate) struct StoreDictionaries {
+    fields: HashMap<Field, Vec<String>>,
+    num_bytes: usize,
+}
+
+impl StoreDictionaries {
+    /// Opens the dictionaries serialized by [`StoreDictionariesWriter::close`].
+    pub(crate) fn open(bytes: OwnedBytes) -> io::Result<StoreDictionaries> {
+        let num_bytes = bytes.len();
+        let mut data = bytes.as_slice();
+        let num_dictionaries = VInt::deserialize(&mut data)?.val();
+        let mut fields = HashMap::new();
+        for _ in 0..num_dictionaries {
+            let field = Field::deserialize(&mut data)?;
+            let num_values = VInt::deserialize(&mut data)?.val();
+            let values = (0..num_values)
+                .map(|_| String::deserialize(&mut data))
+                .collect::<io::Result<Vec<String>>>()?;
+            fields.insert(field, values);
+        }
+        Ok(StoreDictionaries { fields, num_bytes })
+    }
+
+    /// Returns the values of the dictionary of the field, by ordinal.
+    pub(crate) fn field_values(&self, field: Field) -> Option<&[String]> {
+        self.fields.get(&field).map(|values| &values[..])
+    }
+
+    /// Number of bytes of the serialized dictionaries.
+    pub(crate) fn num_bytes(&self) -> usize {
+        self.num_bytes
+    }
+}
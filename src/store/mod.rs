@@ -31,6 +31,7 @@
 
 mod compressors;
 mod decompressors;
+mod dictionary;
 mod footer;
 mod index;
 mod reader;
@@ -38,6 +39,8 @@ mod writer;
 
 pub use self::compressors::{Compressor, ZstdCompressor};
 pub use self::decompressors::Decompressor;
+pub use self::dictionary::MAX_STORE_DICTIONARY_SIZE;
+pub(crate) use self::dictionary::{StoreDictionaries, StoreDictionariesWriter};
 pub use self::reader::{CacheStats, StoreReader};
 pub(crate) use self::reader::{DocStoreVersion, DOCSTORE_CACHE_CAPACITY};
 pub use self::writer::StoreWriter;
@@ -60,8 +63,10 @@ pub(crate) mod tests {
     use super::*;
     use crate::directory::{Directory, RamDirectory, WritePtr};
     use crate::fastfield::AliveBitSet;
+    use crate::indexer::NoMergePolicy;
     use crate::schema::{
-        self, Schema, TantivyDocument, TextFieldIndexing, TextOptions, Value, STORED, TEXT,
+        self, Document, Schema, TantivyDocument, TextFieldIndexing, TextOptions, Value, STORED,
+        STRING, TEXT,
     };
     use crate::{Index, IndexWriter, Term};
 
@@ -357,6 +362,102 @@ pub(crate) mod tests {
         assert_eq!(store.block_checkpoints().count(), 1);
         Ok(())
     }
+
+    fn index_sources(
+        source_options: TextOptions,
+        sources: &[String],
+        num_commits: usize,
+    ) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let source_field = schema_builder.add_text_field("source", source_options);
+        let id_field = schema_builder.add_u64_field("id", schema::STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let docs_per_commit = sources.len().div_ceil(num_commits);
+        for (id, source) in sources.iter().enumerate() {
+            index_writer
+                .add_document(doc!(source_field => source.as_str(), id_field => id as u64))?;
+            if (id + 1) % docs_per_commit == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    /// Returns the stored documents as json, sorted as the order of the segments varies.
+    fn stored_docs(index: &Index) -> crate::Result<Vec<String>> {
+        let searcher = index.reader()?.searcher();
+        let schema = index.schema();
+        let mut docs = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let store_reader = segment_reader.get_store_reader(10)?;
+            for doc in store_reader.iter::<TantivyDocument>(None) {
+                docs.push(doc?.to_json(&schema));
+            }
+        }
+        docs.sort();
+        Ok(docs)
+    }
+
+    fn store_num_bytes(index: &Index) -> crate::Result<u64> {
+        let space_usage = index.reader()?.searcher().space_usage()?;
+        Ok(space_usage
+            .segments()
+            .iter()
+            .map(|segment| segment.store().total().get_bytes())
+            .sum())
+    }
+
+    #[test]
+    fn test_dictionary_encoded_store() -> crate::Result<()> {
+        let sources: Vec<String> = (0..5_000)
+            .map(|i| {
+                format!(
+                    "source-system-{}.eu-west.internal.example.com",
+                    (i * 7) % 50
+                )
+            })
+            .collect();
+        let literal_index = index_sources(STRING | STORED, &sources, 2)?;
+        let dictionary_index =
+            index_sources((STRING | STORED).set_dictionary_encoded(), &sources, 2)?;
+        assert_eq!(
+            stored_docs(&dictionary_index)?,
+            stored_docs(&literal_index)?
+        );
+        let literal_num_bytes = store_num_bytes(&literal_index)?;
+        assert!(store_num_bytes(&dictionary_index)? < literal_num_bytes);
+
+        // Merges rebuild the dictionaries.
+        let docs_before_merge = stored_docs(&dictionary_index)?;
+        let segment_ids = dictionary_index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        let mut index_writer: IndexWriter = dictionary_index.writer_for_tests()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        assert_eq!(dictionary_index.searchable_segment_ids()?.len(), 1);
+        assert_eq!(stored_docs(&dictionary_index)?, docs_before_merge);
+        assert!(store_num_bytes(&dictionary_index)? < literal_num_bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_encoded_store_falls_back_to_literals() -> crate::Result<()> {
+        // Past the maximum size of the dictionary, values are stored literally.
+        let sources: Vec<String> = (0..MAX_STORE_DICTIONARY_SIZE + 100)
+            .map(|i| format!("source-{}", i % (MAX_STORE_DICTIONARY_SIZE + 50)))
+            .collect();
+        let literal_index = index_sources(STRING | STORED, &sources, 1)?;
+        let dictionary_index =
+            index_sources((STRING | STORED).set_dictionary_encoded(), &sources, 1)?;
+        assert_eq!(
+            stored_docs(&dictionary_index)?,
+            stored_docs(&literal_index)?
+        );
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
@@ -6,12 +6,12 @@ use std::ops::{AddAssign, Range};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-use common::{BinarySerializable, OwnedBytes};
+use common::{BinarySerializable, ByteCount, OwnedBytes};
 use lru::LruCache;
 
 use super::footer::DocStoreFooter;
 use super::index::SkipIndex;
-use super::Decompressor;
+use super::{Decompressor, StoreDictionaries};
 use crate::directory::FileSlice;
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
@@ -67,6 +67,7 @@ pub struct StoreReader {
     skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
     cache: BlockCache,
+    dictionaries: Option<Arc<StoreDictionaries>>,
 }
 
 /// The cache for decompressed blocks.
@@ -104,6 +105,17 @@ impl BlockCache {
         }
     }
 
+    fn num_bytes(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| {
+            cache
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, block)| block.len())
+                .sum()
+        })
+    }
+
     fn len(&self) -> usize {
         self.cache
             .as_ref()
@@ -119,7 +131,8 @@ impl BlockCache {
 }
 
 #[derive(Debug, Default)]
-/// CacheStats for the `StoreReader`.
+/// CacheStats for the `StoreReader`, and for the fast field and fieldnorm handles cached by
+/// the `SegmentReader`.
 pub struct CacheStats {
     /// The number of entries in the cache
     pub num_entries: usize,
@@ -174,9 +187,25 @@ impl StoreReader {
             },
             skip_index: Arc::new(skip_index),
             space_usage,
+            dictionaries: None,
         })
     }
 
+    /// Sets the dictionaries of the dictionary encoded fields of the segment.
+    pub(crate) fn with_dictionaries(
+        mut self,
+        dictionaries: Option<Arc<StoreDictionaries>>,
+    ) -> StoreReader {
+        let dictionaries_num_bytes = dictionaries
+            .as_ref()
+            .map(|dictionaries| dictionaries.num_bytes())
+            .unwrap_or(0);
+        self.space_usage
+            .set_dictionaries(ByteCount::from(dictionaries_num_bytes as u64));
+        self.dictionaries = dictionaries;
+        self
+    }
+
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
         self.skip_index.checkpoints()
     }
@@ -190,6 +219,11 @@ impl StoreReader {
         self.cache.stats()
     }
 
+    /// Returns the number of bytes of the decompressed blocks held in the cache.
+    pub(crate) fn cache_num_bytes(&self) -> ByteCount {
+        ByteCount::from(self.cache.num_bytes() as u64)
+    }
+
     /// Get checkpoint for `DocId`. The checkpoint can be used to load a block containing the
     /// document.
     ///
@@ -241,12 +275,15 @@ impl StoreReader {
 
         let deserializer =
             BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                .map_err(crate::TantivyError::from)?;
+                .map_err(crate::TantivyError::from)?
+                .with_dictionaries(self.dictionaries.as_deref());
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 
     /// Returns raw bytes of a given document.
     ///
+    /// The values of the dictionary encoded fields are not resolved in the raw bytes.
+    ///
     /// Calling `.get(doc)` is relatively costly as it requires
     /// decompressing a compressed block. The store utilizes a LRU cache,
     /// so accessing docs from the same compressed block should be faster.
@@ -283,7 +320,8 @@ impl StoreReader {
 
             let deserializer =
                 BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                    .map_err(crate::TantivyError::from)?;
+                    .map_err(crate::TantivyError::from)?
+                    .with_dictionaries(self.dictionaries.as_deref());
             D::deserialize(deserializer).map_err(crate::TantivyError::from)
         })
     }
@@ -433,7 +471,8 @@ impl StoreReader {
 
         let deserializer =
             BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                .map_err(crate::TantivyError::from)?;
+                .map_err(crate::TantivyError::from)?
+                .with_dictionaries(self.dictionaries.as_deref());
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 }
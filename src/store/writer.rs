@@ -3,7 +3,7 @@ use std::io;
 use common::BinarySerializable;
 
 use super::compressors::Compressor;
-use super::StoreReader;
+use super::{StoreDictionariesWriter, StoreReader};
 use crate::directory::WritePtr;
 use crate::schema::document::{BinaryDocumentSerializer, Document};
 use crate::schema::Schema;
@@ -24,6 +24,8 @@ pub struct StoreWriter {
     current_block: Vec<u8>,
     doc_pos: Vec<u32>,
     block_compressor: BlockCompressor,
+    num_bytes_stored: u64,
+    dictionaries: Option<StoreDictionariesWriter>,
 }
 
 impl StoreWriter {
@@ -45,16 +47,43 @@ impl StoreWriter {
             doc_pos: Vec::new(),
             current_block: Vec::new(),
             block_compressor,
+            num_bytes_stored: 0,
+            dictionaries: None,
         })
     }
 
+    /// Sets the writer of the dictionaries of the dictionary encoded fields.
+    ///
+    /// Documents stored from then on reference the dictionaries, so the raw bytes of documents
+    /// stored with other dictionaries must not be added via [`StoreWriter::store_bytes`] or
+    /// [`StoreWriter::stack`].
+    pub(crate) fn set_dictionaries(&mut self, dictionaries: StoreDictionariesWriter) {
+        self.dictionaries = Some(dictionaries);
+    }
+
+    /// Returns true if the values of the dictionary encoded fields reference dictionaries.
+    pub(crate) fn has_dictionaries(&self) -> bool {
+        self.dictionaries.is_some()
+    }
+
     pub(crate) fn compressor(&self) -> Compressor {
         self.compressor
     }
 
+    /// Number of bytes of the serialized documents stored so far, before compression.
+    pub(crate) fn num_bytes_stored(&self) -> u64 {
+        self.num_bytes_stored
+    }
+
     /// The memory used (inclusive childs)
     pub fn mem_usage(&self) -> usize {
-        self.current_block.capacity() + self.doc_pos.capacity() * std::mem::size_of::<u32>()
+        self.current_block.capacity()
+            + self.doc_pos.capacity() * std::mem::size_of::<u32>()
+            + self
+                .dictionaries
+                .as_ref()
+                .map(|dictionaries| dictionaries.mem_usage())
+                .unwrap_or(0)
     }
 
     /// Checks if the current block is full, and if so, compresses and flushes it.
@@ -99,8 +128,11 @@ impl StoreWriter {
     pub fn store<D: Document>(&mut self, document: &D, schema: &Schema) -> io::Result<()> {
         self.doc_pos.push(self.current_block.len() as u32);
 
-        let mut serializer = BinaryDocumentSerializer::new(&mut self.current_block, schema);
+        let block_len_before = self.current_block.len();
+        let mut serializer = BinaryDocumentSerializer::new(&mut self.current_block, schema)
+            .with_dictionaries(self.dictionaries.as_mut());
         serializer.serialize_doc(document)?;
+        self.num_bytes_stored += (self.current_block.len() - block_len_before) as u64;
 
         self.num_docs_in_current_block += 1;
         self.check_flush_block()?;
@@ -114,6 +146,7 @@ impl StoreWriter {
     pub fn store_bytes(&mut self, serialized_document: &[u8]) -> io::Result<()> {
         self.doc_pos.push(self.current_block.len() as u32);
         self.current_block.extend_from_slice(serialized_document);
+        self.num_bytes_stored += serialized_document.len() as u64;
         self.num_docs_in_current_block += 1;
         self.check_flush_block()?;
         Ok(())
@@ -133,10 +166,13 @@ impl StoreWriter {
     /// Finalized the store writer.
     ///
     /// Compress the last unfinished block if any,
-    /// and serializes the skip list index on disc.
+    /// and serializes the skip list index and the dictionaries on disc.
     pub fn close(mut self) -> io::Result<()> {
         self.send_current_block_to_compressor()?;
         self.block_compressor.close()?;
+        if let Some(dictionaries) = self.dictionaries {
+            dictionaries.close()?;
+        }
         Ok(())
     }
 }
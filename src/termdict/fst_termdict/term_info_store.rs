@@ -8,6 +8,7 @@ use tantivy_bitpacker::{compute_num_bits, BitPacker};
 use crate::directory::{FileSlice, OwnedBytes};
 use crate::postings::TermInfo;
 use crate::termdict::TermOrdinal;
+use crate::DocId;
 
 const BLOCK_LEN: usize = 256;
 
@@ -18,6 +19,9 @@ struct TermInfoBlockMeta {
     doc_freq_nbits: u8,
     postings_offset_nbits: u8,
     positions_offset_nbits: u8,
+    // Number of bits of the inline postings of the block, 0 if it has none.
+    inline_doc_nbits: u8,
+    inline_term_freq_nbits: u8,
 }
 
 impl BinarySerializable for TermInfoBlockMeta {
@@ -28,11 +32,31 @@ impl BinarySerializable for TermInfoBlockMeta {
             self.doc_freq_nbits,
             self.postings_offset_nbits,
             self.positions_offset_nbits,
+            self.inline_doc_nbits,
+            self.inline_term_freq_nbits,
         ])?;
         Ok(())
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut term_info_block_meta = TermInfoBlockMeta::deserialize_without_inline(reader)?;
+        let mut buffer = [0u8; 2];
+        reader.read_exact(&mut buffer)?;
+        term_info_block_meta.inline_doc_nbits = buffer[0];
+        term_info_block_meta.inline_term_freq_nbits = buffer[1];
+        Ok(term_info_block_meta)
+    }
+}
+
+impl FixedSize for TermInfoBlockMeta {
+    const SIZE_IN_BYTES: usize = u64::SIZE_IN_BYTES + TermInfo::SIZE_IN_BYTES + 5;
+}
+
+impl TermInfoBlockMeta {
+    /// Size of the block metas of the stores written without inline postings.
+    const SIZE_IN_BYTES_WITHOUT_INLINE: usize = Self::SIZE_IN_BYTES - 2;
+
+    fn deserialize_without_inline<R: Read>(reader: &mut R) -> io::Result<Self> {
         let offset = u64::deserialize(reader)?;
         let ref_term_info = TermInfo::deserialize(reader)?;
         let mut buffer = [0u8; 3];
@@ -43,15 +67,11 @@ impl BinarySerializable for TermInfoBlockMeta {
             doc_freq_nbits: buffer[0],
             postings_offset_nbits: buffer[1],
             positions_offset_nbits: buffer[2],
+            inline_doc_nbits: 0,
+            inline_term_freq_nbits: 0,
         })
     }
-}
-
-impl FixedSize for TermInfoBlockMeta {
-    const SIZE_IN_BYTES: usize = u64::SIZE_IN_BYTES + TermInfo::SIZE_IN_BYTES + 3;
-}
 
-impl TermInfoBlockMeta {
     fn num_bits(&self) -> u8 {
         self.doc_freq_nbits + self.postings_offset_nbits + self.positions_offset_nbits
     }
@@ -88,8 +108,36 @@ impl TermInfoBlockMeta {
             doc_freq,
             postings_range: postings_start_offset..postings_end_offset,
             positions_range: positions_start_offset..positions_end_offset,
+            inline_posting: None,
         }
     }
+
+    // The inline postings of the `num_terms` terms of the block are bitpacked after their
+    // term infos, `inner_offset` including the first term info here.
+    fn deserialize_inline_posting(
+        &self,
+        data: &[u8],
+        num_terms: usize,
+        inner_offset: usize,
+    ) -> Option<(DocId, u32)> {
+        if self.inline_doc_nbits == 0 {
+            return None;
+        }
+        let term_infos_num_bits = self.num_bits() as usize * (num_terms - 1)
+            + self.postings_offset_nbits as usize
+            + self.positions_offset_nbits as usize;
+        let inline_data = &data[term_infos_num_bits.div_ceil(8)..];
+        let doc_addr =
+            (self.inline_doc_nbits + self.inline_term_freq_nbits) as usize * inner_offset;
+        // Docs are shifted by one, 0 standing for terms without an inline posting.
+        let doc_plus_one = extract_bits(inline_data, doc_addr, self.inline_doc_nbits);
+        if doc_plus_one == 0 {
+            return None;
+        }
+        let term_freq_addr = doc_addr + self.inline_doc_nbits as usize;
+        let term_freq = extract_bits(inline_data, term_freq_addr, self.inline_term_freq_nbits);
+        Some(((doc_plus_one - 1) as DocId, term_freq as u32))
+    }
 }
 
 #[derive(Clone)]
@@ -97,10 +145,14 @@ pub struct TermInfoStore {
     num_terms: usize,
     block_meta_bytes: OwnedBytes,
     term_info_bytes: OwnedBytes,
+    has_inline_postings: bool,
 }
 
 fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
     assert!(num_bits <= 56);
+    if num_bits == 0 {
+        return 0;
+    }
     let addr_byte = addr_bits / 8;
     let bit_shift = (addr_bits % 8) as u64;
     let val_unshifted_unmasked: u64 = if data.len() >= addr_byte + 8 {
@@ -121,7 +173,14 @@ fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
 }
 
 impl TermInfoStore {
-    pub fn open(term_info_store_file: FileSlice) -> io::Result<TermInfoStore> {
+    /// Opens a term info store.
+    ///
+    /// `has_inline_postings` is false for the stores written before the inline postings were
+    /// introduced.
+    pub fn open(
+        term_info_store_file: FileSlice,
+        has_inline_postings: bool,
+    ) -> io::Result<TermInfoStore> {
         let (len_slice, main_slice) = term_info_store_file.split(16);
         let mut bytes = len_slice.read_bytes()?;
         let len = u64::deserialize(&mut bytes)? as usize;
@@ -132,24 +191,37 @@ impl TermInfoStore {
             num_terms,
             block_meta_bytes: block_meta_file.read_bytes()?,
             term_info_bytes,
+            has_inline_postings,
         })
     }
 
     pub fn get(&self, term_ord: TermOrdinal) -> TermInfo {
         let block_id = (term_ord as usize) / BLOCK_LEN;
         let buffer = self.block_meta_bytes.as_slice();
-        let mut block_data: &[u8] = &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES..];
-        let term_info_block_data = TermInfoBlockMeta::deserialize(&mut block_data)
-            .expect("Failed to deserialize terminfoblockmeta");
-        let inner_offset = (term_ord as usize) % BLOCK_LEN;
-        if inner_offset == 0 {
-            return term_info_block_data.ref_term_info;
+        let term_info_block_data = if self.has_inline_postings {
+            let mut block_data: &[u8] = &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES..];
+            TermInfoBlockMeta::deserialize(&mut block_data)
+        } else {
+            let mut block_data: &[u8] =
+                &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES_WITHOUT_INLINE..];
+            TermInfoBlockMeta::deserialize_without_inline(&mut block_data)
         }
-        let term_info_data = self.term_info_bytes.as_slice();
-        term_info_block_data.deserialize_term_info(
-            &term_info_data[term_info_block_data.offset as usize..],
-            inner_offset - 1,
-        )
+        .expect("Failed to deserialize terminfoblockmeta");
+        let inner_offset = (term_ord as usize) % BLOCK_LEN;
+        let term_info_data =
+            &self.term_info_bytes.as_slice()[term_info_block_data.offset as usize..];
+        let mut term_info = if inner_offset == 0 {
+            term_info_block_data.ref_term_info.clone()
+        } else {
+            term_info_block_data.deserialize_term_info(term_info_data, inner_offset - 1)
+        };
+        let num_terms_in_block = (self.num_terms - block_id * BLOCK_LEN).min(BLOCK_LEN);
+        term_info.inline_posting = term_info_block_data.deserialize_inline_posting(
+            term_info_data,
+            num_terms_in_block,
+            inner_offset,
+        );
+        term_info
     }
 
     pub fn num_terms(&self) -> usize {
@@ -226,12 +298,27 @@ impl TermInfoStoreWriter {
         let max_postings_offset_nbits = compute_num_bits(postings_end_offset as u64);
         let max_positions_offset_nbits = compute_num_bits(positions_end_offset as u64);
 
+        let (max_inline_doc, max_inline_term_freq) = self
+            .term_infos
+            .iter()
+            .filter_map(|term_info| term_info.inline_posting)
+            .fold(
+                (0u32, 0u32),
+                |(max_doc, max_term_freq), (doc, term_freq)| {
+                    (max_doc.max(doc + 1), max_term_freq.max(term_freq))
+                },
+            );
+        let inline_doc_nbits = compute_num_bits(u64::from(max_inline_doc));
+        let inline_term_freq_nbits = compute_num_bits(u64::from(max_inline_term_freq));
+
         let term_info_block_meta = TermInfoBlockMeta {
             offset: self.buffer_term_infos.len() as u64,
             ref_term_info,
             doc_freq_nbits: max_doc_freq_nbits,
             postings_offset_nbits: max_postings_offset_nbits,
             positions_offset_nbits: max_positions_offset_nbits,
+            inline_doc_nbits,
+            inline_term_freq_nbits,
         };
 
         term_info_block_meta.serialize(&mut self.buffer_block_metas)?;
@@ -258,6 +345,27 @@ impl TermInfoStoreWriter {
 
         // Block need end up at the end of a byte.
         bit_packer.flush(&mut self.buffer_term_infos)?;
+
+        // The inline postings follow, docs being shifted by one.
+        if inline_doc_nbits > 0 {
+            for term_info in &self.term_infos {
+                let (doc_plus_one, term_freq) = term_info
+                    .inline_posting
+                    .map(|(doc, term_freq)| (doc + 1, term_freq))
+                    .unwrap_or((0, 0));
+                bit_packer.write(
+                    u64::from(doc_plus_one),
+                    inline_doc_nbits,
+                    &mut self.buffer_term_infos,
+                )?;
+                bit_packer.write(
+                    u64::from(term_freq),
+                    inline_term_freq_nbits,
+                    &mut self.buffer_term_infos,
+                )?;
+            }
+            bit_packer.flush(&mut self.buffer_term_infos)?;
+        }
         self.term_infos.clear();
 
         Ok(())
@@ -325,10 +433,13 @@ mod tests {
                 doc_freq: 512,
                 postings_range: 51..57,
                 positions_range: 110..134,
+                inline_posting: None,
             },
             doc_freq_nbits: 10,
             postings_offset_nbits: 5,
             positions_offset_nbits: 8,
+            inline_doc_nbits: 12,
+            inline_term_freq_nbits: 3,
         };
         let mut buffer: Vec<u8> = Vec::new();
         term_info_block_meta.serialize(&mut buffer).unwrap();
@@ -347,13 +458,14 @@ mod tests {
                 doc_freq: i as u32,
                 postings_range: offset(i)..offset(i + 1),
                 positions_range: offset(i) * 3..offset(i + 1) * 3,
+                inline_posting: None,
             };
             store_writer.write_term_info(&term_info)?;
             term_infos.push(term_info);
         }
         let mut buffer = Vec::new();
         store_writer.serialize(&mut buffer)?;
-        let term_info_store = TermInfoStore::open(FileSlice::from(buffer))?;
+        let term_info_store = TermInfoStore::open(FileSlice::from(buffer), true)?;
         for i in 0..1000 {
             assert_eq!(
                 term_info_store.get(i as u64),
@@ -363,4 +475,44 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_pack_inline_postings() -> crate::Result<()> {
+        let mut store_writer = TermInfoStoreWriter::new();
+        let mut term_infos = vec![];
+        let mut postings_offset = 0;
+        // The first block has no inline posting, the second one has some, including its
+        // first term, and the last block is incomplete.
+        for i in 0usize..600usize {
+            let inline_posting = (i >= 256 && i % 3 == 1).then_some((i as u32 * 7, i as u32 % 5));
+            let postings_num_bytes = if inline_posting.is_some() {
+                0
+            } else {
+                i % 17 + 1
+            };
+            let term_info = TermInfo {
+                doc_freq: if inline_posting.is_some() {
+                    1
+                } else {
+                    i as u32 % 13 + 2
+                },
+                postings_range: postings_offset..postings_offset + postings_num_bytes,
+                positions_range: i * 3..i * 3 + 3,
+                inline_posting,
+            };
+            postings_offset += postings_num_bytes;
+            store_writer.write_term_info(&term_info)?;
+            term_infos.push(term_info);
+        }
+        let mut buffer = Vec::new();
+        store_writer.serialize(&mut buffer)?;
+        let term_info_store = TermInfoStore::open(FileSlice::from(buffer), true)?;
+        assert!(term_info_store.get(255).inline_posting.is_none());
+        assert_eq!(term_info_store.get(256).inline_posting, Some((1792, 1)));
+        assert_eq!(term_info_store.get(514).inline_posting, Some((3598, 4)));
+        for (i, term_info) in term_infos.iter().enumerate() {
+            assert_eq!(&term_info_store.get(i as u64), term_info, "term info {i}");
+        }
+        Ok(())
+    }
 }
@@ -16,7 +16,11 @@ fn convert_fst_error(e: tantivy_fst::Error) -> io::Error {
     io::Error::other(e)
 }
 
-const FST_VERSION: u32 = 1;
+const FST_VERSION: u32 = 2;
+
+/// Version of the dictionaries written before the posting lists of the terms contained in a
+/// single document were inlined in the term info store.
+const FST_VERSION_WITHOUT_INLINE_POSTINGS: u32 = 1;
 
 /// Builder for the new term dictionary.
 ///
@@ -127,7 +131,7 @@ impl TermDictionary {
         let mut footer_len_bytes = footer_len_slice.read_bytes()?;
         let footer_size = u64::deserialize(&mut footer_len_bytes)?;
         let version = u32::deserialize(&mut footer_len_bytes)?;
-        if version != FST_VERSION {
+        if version != FST_VERSION && version != FST_VERSION_WITHOUT_INLINE_POSTINGS {
             return Err(io::Error::other(format!(
                 "Unsupported fst version, expected {version}, found {FST_VERSION}",
             )));
@@ -135,7 +139,7 @@ impl TermDictionary {
 
         let (fst_file_slice, values_file_slice) = main_slice.split_from_end(footer_size as usize);
         let fst_index = open_fst_index(fst_file_slice)?;
-        let term_info_store = TermInfoStore::open(values_file_slice)?;
+        let term_info_store = TermInfoStore::open(values_file_slice, version == FST_VERSION)?;
         Ok(TermDictionary {
             fst_index: Arc::new(fst_index),
             term_info_store,
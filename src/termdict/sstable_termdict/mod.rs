@@ -11,6 +11,7 @@ use tantivy_fst::automaton::AlwaysMatch;
 
 pub use self::merger::TermMerger;
 use crate::postings::TermInfo;
+use crate::DocId;
 
 /// The term dictionary contains all of the terms in
 /// `tantivy index` in a sorted manner.
@@ -64,10 +65,19 @@ impl ValueReader for TermInfoValueReader {
             let positions_num_bytes = VInt::deserialize_u64(&mut data)?;
             let postings_end = postings_start + postings_num_bytes as usize;
             let positions_end = positions_start + positions_num_bytes as usize;
+            // Posting lists are never empty, unless they are inlined.
+            let inline_posting = if doc_freq == 1 && postings_num_bytes == 0 {
+                let doc = VInt::deserialize_u64(&mut data)? as DocId;
+                let term_freq = VInt::deserialize_u64(&mut data)? as u32;
+                Some((doc, term_freq))
+            } else {
+                None
+            };
             let term_info = TermInfo {
                 doc_freq,
                 postings_range: postings_start..postings_end,
                 positions_range: positions_start..positions_end,
+                inline_posting,
             };
             self.term_infos.push(term_info);
             postings_start = postings_end;
@@ -101,6 +111,10 @@ impl ValueWriter for TermInfoValueWriter {
             VInt(term_info.doc_freq as u64).serialize_into_vec(buffer);
             VInt(term_info.postings_range.len() as u64).serialize_into_vec(buffer);
             VInt(term_info.positions_range.len() as u64).serialize_into_vec(buffer);
+            if let Some((doc, term_freq)) = term_info.inline_posting {
+                VInt(doc as u64).serialize_into_vec(buffer);
+                VInt(term_freq as u64).serialize_into_vec(buffer);
+            }
         }
     }
 
@@ -123,16 +137,25 @@ mod tests {
             doc_freq: 120u32,
             postings_range: 17..45,
             positions_range: 10..122,
+            inline_posting: None,
         });
         term_info_writer.write(&TermInfo {
             doc_freq: 10u32,
             postings_range: 45..450,
             positions_range: 122..1100,
+            inline_posting: None,
+        });
+        term_info_writer.write(&TermInfo {
+            doc_freq: 1u32,
+            postings_range: 450..450,
+            positions_range: 1100..1103,
+            inline_posting: Some((1_000_000, 3)),
         });
         term_info_writer.write(&TermInfo {
             doc_freq: 17u32,
             postings_range: 450..462,
-            positions_range: 1100..1302,
+            positions_range: 1103..1302,
+            inline_posting: None,
         });
         let mut buffer = Vec::new();
         term_info_writer.serialize_block(&mut buffer);
@@ -143,9 +166,20 @@ mod tests {
             &TermInfo {
                 doc_freq: 120u32,
                 postings_range: 17..45,
-                positions_range: 10..122
+                positions_range: 10..122,
+                inline_posting: None,
+            }
+        );
+        assert_eq!(
+            term_info_reader.value(2),
+            &TermInfo {
+                doc_freq: 1u32,
+                postings_range: 450..450,
+                positions_range: 1100..1103,
+                inline_posting: Some((1_000_000, 3)),
             }
         );
+        assert_eq!(term_info_reader.value(3).postings_range, 450..462);
         assert_eq!(buffer.len(), num_bytes);
     }
 }
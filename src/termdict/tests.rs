@@ -13,6 +13,7 @@ fn make_term_info(term_ord: u64) -> TermInfo {
         doc_freq: term_ord as u32,
         postings_range: offset(term_ord)..offset(term_ord + 1),
         positions_range: offset(term_ord) * 2..offset(term_ord + 1) * 2,
+        inline_posting: None,
     }
 }
 
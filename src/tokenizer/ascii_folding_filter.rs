@@ -69,7 +69,7 @@ impl<T: TokenStream> TokenStream for AsciiFoldingFilterTokenStream<'_, T> {
 // (e.g. simple ASCII chars like `A`) or if the `char`
 // does not have a sensible ascii equivalent (e.g.: Kanjis like 馬,
 // this function returns `None`.
-fn fold_non_ascii_char(c: char) -> Option<&'static str> {
+pub(super) fn fold_non_ascii_char(c: char) -> Option<&'static str> {
     match c {
         '\u{00C0}' | // À  [LATIN CAPITAL LETTER A WITH GRAVE]
         '\u{00C1}' | // Á  [LATIN CAPITAL LETTER A WITH ACUTE]
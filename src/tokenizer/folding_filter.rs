@@ -0,0 +1,260 @@
+use std::mem;
+use std::sync::Arc;
+
+use rustc_hash::FxHashMap;
+
+use super::ascii_folding_filter::fold_non_ascii_char;
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// A configurable version of [`AsciiFoldingFilter`](super::AsciiFoldingFilter).
+///
+/// By default, characters are folded exactly like `AsciiFoldingFilter` does.
+/// The folding of individual characters can then be adapted to a language:
+/// - a mapping override replaces a character by a given string, e.g. `ü` by `ue` in German.
+/// - an exception leaves a character untouched, e.g. `ø` in Norwegian.
+///
+/// Overrides can also be loaded from a mapping table,
+/// see [`FoldingFilter::from_mapping_table`].
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::{FoldingFilter, LowerCaser, SimpleTokenizer, TextAnalyzer};
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(LowerCaser)
+///     .filter(FoldingFilter::default().with_mapping('ü', "ue").with_exception('ø'))
+///     .build();
+/// let mut stream = tokenizer.token_stream("Müller Smørbrød café");
+/// assert_eq!(stream.next().unwrap().text, "mueller");
+/// assert_eq!(stream.next().unwrap().text, "smørbrød");
+/// assert_eq!(stream.next().unwrap().text, "cafe");
+/// assert_eq!(stream.next(), None);
+/// ```
+#[derive(Clone, Default)]
+pub struct FoldingFilter {
+    // `None` marks an exception: the character is kept as is.
+    overrides: Arc<FxHashMap<char, Option<String>>>,
+}
+
+impl FoldingFilter {
+    /// Folds `c` into `replacement`, instead of its default ASCII folding.
+    pub fn with_mapping(mut self, c: char, replacement: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.overrides).insert(c, Some(replacement.into()));
+        self
+    }
+
+    /// Leaves `c` untouched.
+    pub fn with_exception(mut self, c: char) -> Self {
+        Arc::make_mut(&mut self.overrides).insert(c, None);
+        self
+    }
+
+    /// Creates a filter from a mapping table.
+    ///
+    /// Each line of the table either maps a character to its replacement, as in `ü => ue`,
+    /// or consists of a single character which is then left untouched, as in `ø`.
+    /// Blank lines and lines starting with `#` are ignored.
+    ///
+    /// ```text
+    /// # German
+    /// ü => ue
+    /// Ü => Ue
+    /// ```
+    pub fn from_mapping_table(table: &str) -> crate::Result<Self> {
+        let mut filter = FoldingFilter::default();
+        for (line_ord, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (source, replacement) = match line.split_once("=>") {
+                Some((source, replacement)) => (source.trim(), Some(replacement.trim())),
+                None => (line, None),
+            };
+            let mut source_chars = source.chars();
+            let (Some(c), None) = (source_chars.next(), source_chars.next()) else {
+                return Err(crate::TantivyError::InvalidArgument(format!(
+                    "Invalid folding mapping at line {}: expected a single character, got \
+                     {source:?}",
+                    line_ord + 1
+                )));
+            };
+            filter = match replacement {
+                Some(replacement) => filter.with_mapping(c, replacement),
+                None => filter.with_exception(c),
+            };
+        }
+        Ok(filter)
+    }
+
+    fn fold(&self, text: &str, output: &mut String) {
+        output.clear();
+        for c in text.chars() {
+            match self.overrides.get(&c) {
+                Some(Some(replacement)) => output.push_str(replacement),
+                Some(None) => output.push(c),
+                None => match fold_non_ascii_char(c) {
+                    Some(folded) => output.push_str(folded),
+                    None => output.push(c),
+                },
+            }
+        }
+    }
+}
+
+impl TokenFilter for FoldingFilter {
+    type Tokenizer<T: Tokenizer> = FoldingFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> FoldingFilterWrapper<T> {
+        FoldingFilterWrapper {
+            filter: self,
+            tokenizer,
+            buffer: String::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FoldingFilterWrapper<T> {
+    filter: FoldingFilter,
+    tokenizer: T,
+    buffer: String,
+}
+
+impl<T: Tokenizer> Tokenizer for FoldingFilterWrapper<T> {
+    type TokenStream<'a> = FoldingFilterTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.buffer.clear();
+        FoldingFilterTokenStream {
+            filter: &self.filter,
+            buffer: &mut self.buffer,
+            tail: self.tokenizer.token_stream(text),
+        }
+    }
+}
+
+pub struct FoldingFilterTokenStream<'a, T> {
+    filter: &'a FoldingFilter,
+    buffer: &'a mut String,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for FoldingFilterTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        if !self.tail.token().text.is_ascii() {
+            self.filter.fold(&self.tail.token().text, self.buffer);
+            mem::swap(&mut self.tail.token_mut().text, self.buffer);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FoldingFilter;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+    use crate::{collector, Index, IndexWriter};
+
+    fn folding_helper(filter: FoldingFilter, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(filter)
+            .build()
+            .token_stream(text)
+            .process(&mut |token| {
+                tokens.push(token.text.clone());
+            });
+        tokens
+    }
+
+    #[test]
+    fn test_folding_filter_defaults_to_ascii_folding() {
+        assert_eq!(
+            folding_helper(FoldingFilter::default(), "Müller Smørbrød"),
+            &["Muller", "Smorbrod"]
+        );
+    }
+
+    #[test]
+    fn test_folding_filter_german() {
+        let filter = FoldingFilter::default()
+            .with_mapping('ü', "ue")
+            .with_mapping('Ü', "Ue")
+            .with_mapping('ß', "ss");
+        assert_eq!(
+            folding_helper(filter, "Übergrößenträger Müller café"),
+            &["Uebergrossentrager", "Mueller", "cafe"]
+        );
+    }
+
+    #[test]
+    fn test_folding_filter_norwegian() {
+        let filter = FoldingFilter::default()
+            .with_exception('ø')
+            .with_exception('å')
+            .with_exception('æ');
+        assert_eq!(
+            folding_helper(filter, "Blåbærsyltetøy på crème brûlée"),
+            &["Blåbærsyltetøy", "på", "creme", "brulee"]
+        );
+    }
+
+    #[test]
+    fn test_folding_filter_from_mapping_table() -> crate::Result<()> {
+        let filter =
+            FoldingFilter::from_mapping_table("# German\n  ü => ue \nö=>oe\n\n# Norwegian\nø\n")?;
+        assert_eq!(
+            folding_helper(filter, "Köln München Tromsø Ålesund"),
+            &["Koeln", "Muenchen", "Tromsø", "Alesund"]
+        );
+        assert!(FoldingFilter::from_mapping_table("ue => u").is_err());
+        assert!(FoldingFilter::from_mapping_table("=> u").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_folding_filter_at_query_time() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("german")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "german",
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(LowerCaser)
+                .filter(FoldingFilter::from_mapping_table("ü => ue")?)
+                .build(),
+        );
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "Herr Müller"))?;
+        index_writer.add_document(doc!(text => "Herr Muller"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        for (query, expected_count) in [("Müller", 1), ("mueller", 1), ("muller", 1)] {
+            let query = query_parser.parse_query(query)?;
+            assert_eq!(searcher.search(&query, &collector::Count)?, expected_count);
+        }
+        Ok(())
+    }
+}
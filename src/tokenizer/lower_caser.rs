@@ -12,6 +12,55 @@ impl TokenFilter for LowerCaser {
     fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
         LowerCaserFilter {
             tokenizer,
+            locale: None,
+            buffer: String::new(),
+        }
+    }
+}
+
+/// Locale of which the lowercasing rules differ from the default Unicode ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowerCaserLocale {
+    /// Turkish, where `I` lowercases to the dotless `ı`, and `İ` to `i`.
+    ///
+    /// Azerbaijani follows the same rules.
+    Turkish,
+}
+
+/// Token filter that lowercase terms according to the rules of a given locale.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::{LocaleLowerCaser, LowerCaserLocale, SimpleTokenizer, TextAnalyzer};
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(LocaleLowerCaser::new(LowerCaserLocale::Turkish))
+///     .build();
+/// let mut stream = tokenizer.token_stream("İSTANBUL DIŞ");
+/// assert_eq!(stream.next().unwrap().text, "istanbul");
+/// assert_eq!(stream.next().unwrap().text, "dış");
+/// assert_eq!(stream.next(), None);
+/// ```
+#[derive(Clone)]
+pub struct LocaleLowerCaser {
+    locale: LowerCaserLocale,
+}
+
+impl LocaleLowerCaser {
+    /// Creates a `LocaleLowerCaser` for the given locale.
+    pub fn new(locale: LowerCaserLocale) -> LocaleLowerCaser {
+        LocaleLowerCaser { locale }
+    }
+}
+
+impl TokenFilter for LocaleLowerCaser {
+    type Tokenizer<T: Tokenizer> = LowerCaserFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        LowerCaserFilter {
+            tokenizer,
+            locale: Some(self.locale),
             buffer: String::new(),
         }
     }
@@ -20,6 +69,7 @@ impl TokenFilter for LowerCaser {
 #[derive(Clone)]
 pub struct LowerCaserFilter<T> {
     tokenizer: T,
+    locale: Option<LowerCaserLocale>,
     buffer: String,
 }
 
@@ -30,6 +80,7 @@ impl<T: Tokenizer> Tokenizer for LowerCaserFilter<T> {
         self.buffer.clear();
         LowerCaserTokenStream {
             tail: self.tokenizer.token_stream(text),
+            locale: self.locale,
             buffer: &mut self.buffer,
         }
     }
@@ -37,6 +88,7 @@ impl<T: Tokenizer> Tokenizer for LowerCaserFilter<T> {
 
 pub struct LowerCaserTokenStream<'a, T> {
     buffer: &'a mut String,
+    locale: Option<LowerCaserLocale>,
     tail: T,
 }
 
@@ -51,17 +103,40 @@ fn to_lowercase_unicode(text: &str, output: &mut String) {
     }
 }
 
+// writes a version of text lowercased according to the Turkish rules into output.
+fn to_lowercase_turkish(text: &str, output: &mut String) {
+    output.clear();
+    output.reserve(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // `I` followed by a combining dot above is a decomposed `İ`.
+            'I' if chars.next_if_eq(&'\u{0307}').is_some() => output.push('i'),
+            'I' => output.push('ı'),
+            'İ' => output.push('i'),
+            _ => output.extend(c.to_lowercase()),
+        }
+    }
+}
+
 impl<T: TokenStream> TokenStream for LowerCaserTokenStream<'_, T> {
     fn advance(&mut self) -> bool {
         if !self.tail.advance() {
             return false;
         }
-        if self.token_mut().text.is_ascii() {
-            // fast track for ascii.
-            self.token_mut().text.make_ascii_lowercase();
-        } else {
-            to_lowercase_unicode(&self.tail.token().text, self.buffer);
-            mem::swap(&mut self.tail.token_mut().text, self.buffer);
+        match self.locale {
+            None if self.tail.token().text.is_ascii() => {
+                // fast track for ascii.
+                self.token_mut().text.make_ascii_lowercase();
+            }
+            None => {
+                to_lowercase_unicode(&self.tail.token().text, self.buffer);
+                mem::swap(&mut self.tail.token_mut().text, self.buffer);
+            }
+            Some(LowerCaserLocale::Turkish) => {
+                to_lowercase_turkish(&self.tail.token().text, self.buffer);
+                mem::swap(&mut self.tail.token_mut().text, self.buffer);
+            }
         }
         true
     }
@@ -78,7 +153,10 @@ impl<T: TokenStream> TokenStream for LowerCaserTokenStream<'_, T> {
 #[cfg(test)]
 mod tests {
     use crate::tokenizer::tests::assert_token;
-    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer, Token};
+    use crate::tokenizer::{
+        LocaleLowerCaser, LowerCaser, LowerCaserLocale, SimpleTokenizer, TextAnalyzer, Token,
+        WhitespaceTokenizer,
+    };
 
     #[test]
     fn test_to_lower_case() {
@@ -92,6 +170,21 @@ mod tests {
         assert_token(&tokens[1], 1, "текст", 15, 25);
     }
 
+    #[test]
+    fn test_to_lower_case_turkish() {
+        let mut analyzer = TextAnalyzer::builder(WhitespaceTokenizer::default())
+            .filter(LocaleLowerCaser::new(LowerCaserLocale::Turkish))
+            .build();
+        let mut token_stream = analyzer.token_stream("İSTANBUL ISPARTA I\u{0307}zmir Çığ");
+        let mut tokens = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.text.clone()));
+        assert_eq!(tokens, &["istanbul", "ısparta", "izmir", "çığ"]);
+
+        // Without a locale, `I` lowercases to `i`.
+        let tokens = token_stream_helper("ISPARTA");
+        assert_eq!(tokens[0].text, "isparta");
+    }
+
     fn token_stream_helper(text: &str) -> Vec<Token> {
         let mut token_stream = TextAnalyzer::builder(SimpleTokenizer::default())
             .filter(LowerCaser)
@@ -125,7 +125,9 @@ mod alphanum_only;
 mod ascii_folding_filter;
 mod empty_tokenizer;
 mod facet_tokenizer;
+mod folding_filter;
 mod lower_caser;
+mod nfkc_normalizer;
 mod ngram_tokenizer;
 mod raw_tokenizer;
 mod regex_tokenizer;
@@ -145,7 +147,9 @@ pub use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokeniz
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
 pub use self::facet_tokenizer::FacetTokenizer;
-pub use self::lower_caser::LowerCaser;
+pub use self::folding_filter::FoldingFilter;
+pub use self::lower_caser::{LocaleLowerCaser, LowerCaser, LowerCaserLocale};
+pub use self::nfkc_normalizer::NfkcNormalizer;
 pub use self::ngram_tokenizer::NgramTokenizer;
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::regex_tokenizer::RegexTokenizer;
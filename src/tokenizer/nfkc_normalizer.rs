@@ -0,0 +1,175 @@
+use std::mem;
+
+use unicode_normalization::{is_nfkc_quick, IsNormalized, UnicodeNormalization};
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// Token filter that normalizes terms to the Unicode NFKC form.
+///
+/// Compatibility characters are replaced by their canonical equivalent, so that for instance
+/// full-width Latin letters (`ＡＢＣ`) become `ABC` and ligatures (`ﬁ`) become `fi`.
+///
+/// The normalization does not lowercase: it is usually followed by a [`LowerCaser`].
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::{LowerCaser, NfkcNormalizer, SimpleTokenizer, TextAnalyzer};
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(NfkcNormalizer)
+///     .filter(LowerCaser)
+///     .build();
+/// let mut stream = tokenizer.token_stream("Ｔａｎｔｉｖｙ ﬁle");
+/// assert_eq!(stream.next().unwrap().text, "tantivy");
+/// assert_eq!(stream.next().unwrap().text, "file");
+/// assert_eq!(stream.next(), None);
+/// ```
+///
+/// [`LowerCaser`]: super::LowerCaser
+#[derive(Clone)]
+pub struct NfkcNormalizer;
+
+impl TokenFilter for NfkcNormalizer {
+    type Tokenizer<T: Tokenizer> = NfkcNormalizerFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> Self::Tokenizer<T> {
+        NfkcNormalizerFilter {
+            tokenizer,
+            buffer: String::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct NfkcNormalizerFilter<T> {
+    tokenizer: T,
+    buffer: String,
+}
+
+impl<T: Tokenizer> Tokenizer for NfkcNormalizerFilter<T> {
+    type TokenStream<'a> = NfkcNormalizerTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.buffer.clear();
+        NfkcNormalizerTokenStream {
+            tail: self.tokenizer.token_stream(text),
+            buffer: &mut self.buffer,
+        }
+    }
+}
+
+pub struct NfkcNormalizerTokenStream<'a, T> {
+    buffer: &'a mut String,
+    tail: T,
+}
+
+impl<T: TokenStream> TokenStream for NfkcNormalizerTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let text = &self.tail.token().text;
+        // ASCII text, and most of the text in general, is already normalized.
+        if !text.is_ascii() && is_nfkc_quick(text.chars()) != IsNormalized::Yes {
+            self.buffer.clear();
+            self.buffer.extend(text.nfkc());
+            mem::swap(&mut self.tail.token_mut().text, self.buffer);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NfkcNormalizer;
+    use crate::query::QueryParser;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        LocaleLowerCaser, LowerCaserLocale, SimpleTokenizer, TextAnalyzer, Token,
+    };
+    use crate::{collector, Index, IndexWriter};
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(NfkcNormalizer)
+            .build()
+            .token_stream(text)
+            .process(&mut |token| {
+                tokens.push(token.clone());
+            });
+        tokens
+    }
+
+    #[test]
+    fn test_nfkc_normalizer() {
+        let tokens = token_stream_helper("Ｔａｎｔｉｖｙ ﬁle café Tree");
+        assert_eq!(tokens.len(), 4);
+        // The offsets still refer to the original text.
+        assert_token(&tokens[0], 0, "Tantivy", 0, 21);
+        assert_token(&tokens[1], 1, "file", 22, 27);
+        assert_token(&tokens[2], 2, "café", 28, 33);
+        assert_token(&tokens[3], 3, "Tree", 34, 38);
+    }
+
+    #[test]
+    fn test_nfkc_normalizer_compatibility_numbers() {
+        let tokens = token_stream_helper("Ⅻ x²");
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].text, "XII");
+        assert_eq!(tokens[1].text, "x2");
+    }
+
+    #[test]
+    fn test_nfkc_normalizer_and_turkish_lowercaser_at_query_time() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("turkish")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register(
+            "turkish",
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(NfkcNormalizer)
+                .filter(LocaleLowerCaser::new(LowerCaserLocale::Turkish))
+                .build(),
+        );
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "İSTANBUL"))?;
+        index_writer.add_document(doc!(text => "ＤＩＹＡＲＢＡＫＩＲ"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        for (query, expected_count) in [
+            ("istanbul", 1),
+            ("İstanbul", 1),
+            ("ıstanbul", 0),
+            ("Diyarbakir", 0),
+            ("DIYARBAKIR", 1),
+            ("dıyarbakır", 1),
+            ("ＤＩＹＡＲＢＡＫＩＲ", 1),
+        ] {
+            let parsed_query = query_parser.parse_query(query)?;
+            assert_eq!(
+                searcher.search(&parsed_query, &collector::Count)?,
+                expected_count,
+                "{query}"
+            );
+        }
+        Ok(())
+    }
+}
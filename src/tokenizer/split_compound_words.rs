@@ -149,10 +149,15 @@ impl<T: TokenStream> SplitCompoundWordsTokenStream<'_, T> {
                 let (head, tail) = text.split_at(*pos);
 
                 text = head;
-                self.parts.push(Token {
-                    text: tail.to_owned(),
-                    ..*token
-                });
+                let mut part = Token::new(
+                    token.offset_from,
+                    token.offset_to,
+                    token.position,
+                    tail.to_owned(),
+                )
+                .with_position_length(token.position_length);
+                part.payload = token.payload.clone();
+                self.parts.push(part);
             }
         }
     }
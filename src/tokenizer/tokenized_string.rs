@@ -7,7 +7,7 @@ use common::*;
 use crate::tokenizer::{Token, TokenStream};
 
 /// Struct representing pre-tokenized text
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq, Hash)]
 pub struct PreTokenizedString {
     /// Original text
     pub text: String,
@@ -99,20 +99,8 @@ mod tests {
         let tok_text = PreTokenizedString {
             text: String::from("A a"),
             tokens: vec![
-                Token {
-                    offset_from: 0,
-                    offset_to: 1,
-                    position: 0,
-                    text: String::from("A"),
-                    position_length: 1,
-                },
-                Token {
-                    offset_from: 2,
-                    offset_to: 3,
-                    position: 1,
-                    text: String::from("a"),
-                    position_length: 1,
-                },
+                Token::new(0, 1, 0, String::from("A")),
+                Token::new(2, 3, 1, String::from("a")),
             ],
         };
 
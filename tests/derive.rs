@@ -0,0 +1,168 @@
+use std::net::Ipv6Addr;
+
+use serde::{Deserialize, Serialize};
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::document::DeserializeError;
+use tantivy::schema::{FieldType, Schema, STORED};
+use tantivy::{DateTime, Index, IndexWriter, TantivyDoc, TantivyDocument};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Metadata {
+    author: String,
+    pages: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, TantivyDoc)]
+struct Book {
+    #[tantivy(stored, text)]
+    title: String,
+    #[tantivy(field = "book_id", stored, indexed, fast)]
+    id: u64,
+    #[tantivy(stored, string)]
+    tags: Vec<String>,
+    #[tantivy(stored)]
+    rating: Option<f32>,
+    #[tantivy(stored, indexed)]
+    year: i32,
+    #[tantivy(stored)]
+    available: bool,
+    #[tantivy(stored, fast)]
+    published: DateTime,
+    #[tantivy(stored)]
+    mirror: Option<Ipv6Addr>,
+    #[tantivy(stored, text, json)]
+    metadata: Option<Metadata>,
+    #[tantivy(skip)]
+    score: f64,
+}
+
+fn book() -> Book {
+    Book {
+        title: "The Name of the Rose".to_string(),
+        id: 42,
+        tags: vec!["novel".to_string(), "mystery".to_string()],
+        rating: Some(4.5),
+        year: 1980,
+        available: true,
+        published: DateTime::from_timestamp_secs(326_246_400),
+        mirror: None,
+        metadata: Some(Metadata {
+            author: "Umberto Eco".to_string(),
+            pages: 512,
+        }),
+        score: 0.0,
+    }
+}
+
+#[test]
+fn test_derive_schema() {
+    let schema = Book::schema();
+    let field_names: Vec<&str> = schema
+        .fields()
+        .map(|(_, field_entry)| field_entry.name())
+        .collect();
+    assert_eq!(
+        field_names,
+        [
+            "title",
+            "book_id",
+            "tags",
+            "rating",
+            "year",
+            "available",
+            "published",
+            "mirror",
+            "metadata"
+        ]
+    );
+    let book_id = schema.get_field_entry(schema.get_field("book_id").unwrap());
+    assert!(matches!(book_id.field_type(), FieldType::U64(_)));
+    assert!(book_id.is_stored() && book_id.is_indexed() && book_id.is_fast());
+    let rating = schema.get_field_entry(schema.get_field("rating").unwrap());
+    assert!(matches!(rating.field_type(), FieldType::F64(_)));
+    assert!(!rating.is_indexed());
+    let metadata = schema.get_field_entry(schema.get_field("metadata").unwrap());
+    assert!(matches!(metadata.field_type(), FieldType::JsonObject(_)));
+    assert!(metadata.is_stored() && metadata.is_indexed());
+}
+
+#[test]
+fn test_derive_round_trip_through_index() -> tantivy::Result<()> {
+    let schema = Book::schema();
+    let index = Index::create_in_ram(schema.clone());
+    let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+    index_writer.add_document(book().to_document(&schema))?;
+    index_writer.commit()?;
+
+    let searcher = index.reader()?.searcher();
+    let query_parser = QueryParser::for_index(&index, vec![schema.get_field("title")?]);
+    for query in ["rose", "tags:mystery", "book_id:42", "metadata.author:eco"] {
+        let top_docs = searcher.search(
+            &query_parser.parse_query(query)?,
+            &TopDocs::with_limit(1).order_by_score(),
+        )?;
+        assert_eq!(top_docs.len(), 1, "{query}");
+        let retrieved: TantivyDocument = searcher.doc(top_docs[0].1)?;
+        assert_eq!(Book::from_document(&retrieved, &schema).unwrap(), book());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_derive_optional_values() {
+    let schema = Book::schema();
+    let book = Book {
+        rating: None,
+        tags: Vec::new(),
+        mirror: Some(Ipv6Addr::LOCALHOST),
+        metadata: None,
+        ..book()
+    };
+    let document = book.to_document(&schema);
+    assert!(document
+        .get_first(schema.get_field("rating").unwrap())
+        .is_none());
+    assert!(document
+        .get_first(schema.get_field("metadata").unwrap())
+        .is_none());
+    assert_eq!(Book::from_document(&document, &schema).unwrap(), book);
+}
+
+#[test]
+fn test_derive_skips_fields_missing_from_schema() {
+    let mut schema_builder = Schema::builder();
+    let title = schema_builder.add_text_field("title", STORED);
+    let schema = schema_builder.build();
+    let document = book().to_document(&schema);
+    assert_eq!(document.len(), 1);
+    assert_eq!(document.get_first_str(title), Some("The Name of the Rose"));
+    assert!(matches!(
+        Book::from_document(&document, &schema),
+        Err(DeserializeError::Custom(_))
+    ));
+}
+
+#[test]
+fn test_derive_from_document_errors() {
+    let schema = Book::schema();
+    let id = schema.get_field("book_id").unwrap();
+    let year = schema.get_field("year").unwrap();
+
+    let mut document = book().to_document(&schema);
+    document.add_u64(id, 43);
+    let err = Book::from_document(&document, &schema).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("expected exactly one value, found 2"));
+
+    let mut document = TantivyDocument::default();
+    for (field, value) in book().to_document(&schema).field_values() {
+        if field != year {
+            document.add_field_value(field, value);
+        }
+    }
+    document.add_i64(year, i64::MAX);
+    let err = Book::from_document(&document, &schema).unwrap_err();
+    assert!(err.to_string().contains("Field `year`"));
+}
@@ -1,6 +1,7 @@
 use std::path::Path;
 
 use tantivy::directory::{Directory, ManagedDirectory, RamDirectory, TerminatingWrite};
+use tantivy::indexer::CommitGroup;
 use tantivy::schema::{Schema, TEXT};
 use tantivy::{doc, Index, IndexWriter, Term};
 
@@ -122,3 +123,42 @@ fn test_fail_on_commit_segment() -> tantivy::Result<()> {
     assert!(index_writer.commit().is_err());
     Ok(())
 }
+
+#[test]
+fn test_commit_group_fails_between_prepares() -> tantivy::Result<()> {
+    let _fail_scenario_guard = fail::FailScenario::setup();
+    let mut schema_builder = Schema::builder();
+    let text_field = schema_builder.add_text_field("text", TEXT);
+    let schema = schema_builder.build();
+    let index_a = Index::create_in_ram(schema.clone());
+    let index_b = Index::create_in_ram(schema);
+    let mut writer_a: IndexWriter = index_a.writer_with_num_threads(1, 15_000_000)?;
+    let mut writer_b: IndexWriter = index_b.writer_with_num_threads(1, 15_000_000)?;
+    writer_a.add_document(doc!(text_field => "a"))?;
+    writer_b.add_document(doc!(text_field => "b"))?;
+    let opstamps = CommitGroup::new(vec![&mut writer_a, &mut writer_b]).commit()?;
+
+    writer_a.add_document(doc!(text_field => "a"))?;
+    writer_b.add_document(doc!(text_field => "b"))?;
+    // The first writer is prepared, the second one fails.
+    fail::cfg(
+        "CommitGroup::prepare_commit",
+        "1*off->return(simulatederror)",
+    )
+    .unwrap();
+    assert!(CommitGroup::new(vec![&mut writer_a, &mut writer_b])
+        .commit()
+        .is_err());
+    fail::remove("CommitGroup::prepare_commit");
+
+    for (index, opstamp) in [&index_a, &index_b].into_iter().zip(opstamps) {
+        assert_eq!(index.load_metas()?.opstamp, opstamp);
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+    }
+    // The pending documents were rolled back on both writers.
+    CommitGroup::new(vec![&mut writer_a, &mut writer_b]).commit()?;
+    for index in [&index_a, &index_b] {
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+    }
+    Ok(())
+}
@@ -11,7 +11,11 @@ use std::ops::{Deref, DerefMut};
 use serde::{Deserialize, Serialize};
 
 /// Token
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+///
+/// New fields may be added to `Token`, so that tokens are created with [`Token::new`] or
+/// [`Token::default`] outside of this crate.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[non_exhaustive]
 pub struct Token {
     /// Offset (byte index) of the first character of the token.
     /// Offsets shall not be modified by token filters.
@@ -26,6 +30,12 @@ pub struct Token {
     pub text: String,
     /// Is the length expressed in term of number of original tokens.
     pub position_length: usize,
+    /// Opaque data attached to the token, such as a payload set on the tokens of a
+    /// pre-tokenized text.
+    ///
+    /// The payload is stored along with a pre-tokenized text, but it is not indexed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Vec<u8>>,
 }
 
 impl Default for Token {
@@ -36,11 +46,36 @@ impl Default for Token {
             position: usize::MAX,
             text: String::new(),
             position_length: 1,
+            payload: None,
         }
     }
 }
 
 impl Token {
+    /// Creates a token of `text` at `position`, spanning the bytes `offset_from..offset_to` of
+    /// the original text and a single original token.
+    pub fn new(offset_from: usize, offset_to: usize, position: usize, text: String) -> Token {
+        Token {
+            offset_from,
+            offset_to,
+            position,
+            text,
+            ..Token::default()
+        }
+    }
+
+    /// Sets the number of original tokens the token spans.
+    pub fn with_position_length(mut self, position_length: usize) -> Token {
+        self.position_length = position_length;
+        self
+    }
+
+    /// Attaches a payload to the token.
+    pub fn with_payload(mut self, payload: Vec<u8>) -> Token {
+        self.payload = Some(payload);
+        self
+    }
+
     /// reset to default
     pub fn reset(&mut self) {
         self.offset_from = 0;
@@ -48,6 +83,7 @@ impl Token {
         self.position = usize::MAX;
         self.text.clear();
         self.position_length = 1;
+        self.payload = None;
     }
 }
 
@@ -169,6 +205,7 @@ mod test {
             offset_to: 3,
             text: "abc".to_string(),
             position_length: 1,
+            payload: None,
         };
         let t2 = t1.clone();
 